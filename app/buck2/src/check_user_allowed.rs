@@ -7,6 +7,38 @@
  * of this source tree.
  */
 
+use buck2_core::env_helper::EnvHelper;
+
+/// Environment variable which skips [`check_user_allowed`], for trusted environments (e.g. CI
+/// running as a restricted synthetic user) where the check misfires. It must be set to
+/// [`SKIP_CHECK_USER_ALLOWED_TOKEN`], so the check is not skipped by accident.
+pub(crate) const SKIP_CHECK_USER_ALLOWED_VAR: &str = "BUCK2_SKIP_CHECK_USER_ALLOWED";
+
+pub(crate) const SKIP_CHECK_USER_ALLOWED_TOKEN: &str = "i-am-a-trusted-ci-user";
+
+#[derive(Debug, buck2_error::Error)]
+#[error(
+    "`{}` is set to `{0}`, but the check can only be skipped by setting it to `{}`",
+    SKIP_CHECK_USER_ALLOWED_VAR,
+    SKIP_CHECK_USER_ALLOWED_TOKEN
+)]
+struct UnknownSkipCheckUserAllowedToken(String);
+
+/// Whether [`check_user_allowed`] should be skipped. Callers are expected to log and record the
+/// bypass, so it can be audited.
+pub(crate) fn skip_check_user_allowed() -> anyhow::Result<bool> {
+    static SKIP_CHECK_USER_ALLOWED: EnvHelper<String> = EnvHelper::new(SKIP_CHECK_USER_ALLOWED_VAR);
+    skip_check_user_allowed_for(SKIP_CHECK_USER_ALLOWED.get()?.map(String::as_str))
+}
+
+fn skip_check_user_allowed_for(value: Option<&str>) -> anyhow::Result<bool> {
+    match value {
+        None | Some("") => Ok(false),
+        Some(SKIP_CHECK_USER_ALLOWED_TOKEN) => Ok(true),
+        Some(value) => Err(UnknownSkipCheckUserAllowedToken(value.to_owned()).into()),
+    }
+}
+
 #[cfg(windows)]
 pub(crate) fn check_user_allowed() -> anyhow::Result<()> {
     use std::env;
@@ -110,3 +142,17 @@ pub(crate) fn check_user_allowed() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_check_user_allowed() {
+        assert!(!skip_check_user_allowed_for(None).unwrap());
+        assert!(!skip_check_user_allowed_for(Some("")).unwrap());
+        assert!(skip_check_user_allowed_for(Some(SKIP_CHECK_USER_ALLOWED_TOKEN)).unwrap());
+        // Anything else is a mistake, rather than silently running the check.
+        assert!(skip_check_user_allowed_for(Some("true")).is_err());
+    }
+}
@@ -70,6 +70,13 @@ pub(crate) struct DocsStarlarkCommand {
     )]
     prelude: bool,
 
+    #[clap(
+        long = "symbol",
+        value_name = "NAME",
+        help = "print the signature and documentation of a single symbol, e.g. `glob`, rather than the whole doc set. Implies --builtins; pass --prelude to also look up rules"
+    )]
+    symbol: Option<String>,
+
     #[clap(
         name = "SYMBOL_PATTERNS",
         help = "Patterns to interpret. //foo:bar.bzl is 'every symbol in //foo:bar.bzl', //foo:bar.bzl:baz only returns the documentation for the symbol 'baz' in //foo:bar.bzl"
@@ -94,7 +101,7 @@ impl StreamingCommand for DocsStarlarkCommand {
                 UnstableDocsRequest {
                     context: Some(client_context),
                     symbol_patterns: self.patterns.clone(),
-                    retrieve_builtins: self.builtins,
+                    retrieve_builtins: self.builtins || self.symbol.is_some(),
                     retrieve_prelude: self.prelude,
                     format: match self.format {
                         DocsOutputFormatArg::Json => {
@@ -119,6 +126,7 @@ impl StreamingCommand for DocsStarlarkCommand {
                         .transpose()?,
                     markdown_starlark_subdir: self.markdown_file_opts.starlark_subdir.clone(),
                     markdown_native_subdir: self.markdown_file_opts.native_subdir.clone(),
+                    symbol: self.symbol.clone(),
                 },
                 ctx.stdin().console_interaction_stream(&self.console_opts),
                 &mut NoPartialResultHandler,
@@ -128,6 +136,9 @@ impl StreamingCommand for DocsStarlarkCommand {
         if let Some(json_output) = response.json_output {
             buck2_client_ctx::println!("{}", json_output.trim_end())?;
         }
+        if let Some(symbol_output) = response.symbol_output {
+            buck2_client_ctx::println!("{}", symbol_output.trim_end())?;
+        }
 
         ExitResult::success()
     }
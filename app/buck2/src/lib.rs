@@ -13,7 +13,8 @@
 
 use anyhow::Context as _;
 use buck2_audit::AuditCommand;
-use buck2_client::args::expand_argfiles_with_context;
+use buck2_client::args::dump_expanded_args;
+use buck2_client::args::expand_args;
 use buck2_client::commands::build::BuildCommand;
 use buck2_client::commands::bxl::BxlCommand;
 use buck2_client::commands::clean::CleanCommand;
@@ -43,6 +44,7 @@ use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::client_metadata::ClientMetadata;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::immediate_config::ImmediateConfigContext;
+use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::streaming::BuckSubcommand;
 use buck2_client_ctx::tokio_runtime_setup::client_tokio_runtime;
 use buck2_client_ctx::version::BuckVersion;
@@ -51,6 +53,7 @@ use buck2_common::invocation_roots::find_invocation_roots;
 use buck2_core::env_helper::EnvHelper;
 use buck2_core::fs::paths::file_name::FileNameBuf;
 use buck2_event_observer::verbosity::Verbosity;
+use buck2_starlark::LintCommand;
 use buck2_starlark::StarlarkCommand;
 use clap::AppSettings;
 use clap::Parser;
@@ -60,6 +63,8 @@ use dupe::Dupe;
 use no_buckd::start_in_process_daemon;
 
 use crate::check_user_allowed::check_user_allowed;
+use crate::check_user_allowed::skip_check_user_allowed;
+use crate::check_user_allowed::SKIP_CHECK_USER_ALLOWED_VAR;
 use crate::commands::daemon::DaemonCommand;
 use crate::commands::docs::DocsCommand;
 use crate::commands::forkserver::ForkserverCommand;
@@ -101,14 +106,15 @@ struct BeforeSubcommandOptions {
     /// How verbose buck should be while logging.
     ///
     /// Values:
-    /// 0 = Quiet, errors only;
-    /// 1 = Show status. Default;
-    /// 2 = more info about errors;
-    /// 3 = more info about everything;
-    /// 4 = more info about everything + stderr;
+    /// 0 or quiet = Quiet, errors only;
+    /// 1 or normal = Show status. Default;
+    /// 2 or verbose = more info about errors;
+    /// 3 or detailed = more info about everything;
+    /// 4 or trace = more info about everything + stderr;
     ///
     /// It can be combined with specific log items (stderr, full_failed_command, commands, actions,
     /// status, stats, success) to fine-tune the verbosity of the log. Example usage "-v=1,stderr"
+    /// or "-v=detailed,stderr"
     #[clap(
         short = 'v',
         long = "verbose",
@@ -118,6 +124,17 @@ struct BeforeSubcommandOptions {
     )]
     verbosity: Verbosity,
 
+    /// Also write the console output at the highest verbosity to this file, regardless of the
+    /// verbosity of the terminal output. For example, `-v=1 --verbosity-file=build.log` shows
+    /// status on the terminal, and every action and its stderr in `build.log`.
+    #[clap(long, global = true, value_name = "PATH")]
+    verbosity_file: Option<PathArg>,
+
+    /// After the command finishes, print the last N lines of stderr of each failed action,
+    /// regardless of verbosity. Useful when an action's full stderr is too long to read.
+    #[clap(long, global = true, value_name = "N")]
+    stderr_tail: Option<usize>,
+
     /// The oncall executing this command
     #[clap(long, global = true)]
     oncall: Option<String>,
@@ -128,6 +145,12 @@ struct BeforeSubcommandOptions {
     #[clap(long, global = true)]
     client_metadata: Vec<ClientMetadata>,
 
+    /// Cancel the command if it has not finished after this long (e.g. `30m`), and exit with
+    /// the timeout exit code. The command is cancelled like it is on Ctrl-C, so running actions
+    /// are interrupted rather than killed.
+    #[clap(long, global = true, value_name = "DURATION")]
+    timeout: Option<humantime::Duration>,
+
     /// Do not launch a daemon process, run buck server in client process.
     ///
     /// Note even when running in no-buckd mode, it still writes state files.
@@ -141,6 +164,12 @@ struct BeforeSubcommandOptions {
     // NO_BUCKD=1 for buck1.
     no_buckd: bool,
 
+    /// Print the command line with argfiles expanded, one argument per line, and exit without
+    /// running the command.
+    #[clap(long)]
+    #[allow(dead_code)] // Handled before parsing, by `dump_expanded_args`.
+    dump_expanded_args: bool,
+
     /// Print buck wrapper help.
     #[clap(skip)] // @oss-enable
     // @oss-disable: #[clap(long)]
@@ -213,9 +242,12 @@ impl Opt {
 
 pub fn exec(process: ProcessContext<'_>) -> ExitResult {
     let mut immediate_config = ImmediateConfigContext::new(process.working_dir);
-    let mut expanded_args =
-        expand_argfiles_with_context(process.args.to_vec(), &mut immediate_config)
-            .context("Error expanding argsfiles")?;
+    let mut expanded_args = expand_args(process.args.to_vec(), &mut immediate_config, |args| {
+        Opt::clap()
+            .try_get_matches_from(args)
+            .map_or(false, |matches| matches.subcommand_name() == Some("run"))
+    })
+    .context("Error expanding argsfiles")?;
 
     // Override arg0 in `buck2 help`.
     static BUCK2_ARG0: EnvHelper<String> = EnvHelper::new("BUCK2_ARG0");
@@ -223,9 +255,16 @@ pub fn exec(process: ProcessContext<'_>) -> ExitResult {
         expanded_args[0] = arg0.clone();
     }
 
+    if let Some(args) = dump_expanded_args(&expanded_args) {
+        for arg in args {
+            buck2_client_ctx::println!("{}", arg)?;
+        }
+        return ExitResult::success();
+    }
+
     let clap = Opt::clap();
     let matches = clap.get_matches_from(&expanded_args);
-    let opt: Opt = Opt::from_clap(&matches);
+    let mut opt: Opt = Opt::from_clap(&matches);
 
     if opt.common_opts.help_wrapper {
         return ExitResult::err(anyhow::anyhow!(
@@ -236,7 +275,19 @@ pub fn exec(process: ProcessContext<'_>) -> ExitResult {
     match &opt.cmd {
         CommandKind::Clean(..) | CommandKind::Daemon(..) | CommandKind::Forkserver(..) => {}
         _ => {
-            check_user_allowed()?;
+            if skip_check_user_allowed()? {
+                tracing::warn!(
+                    "Skipping the check that buck2 is run by an allowed user, because `{}` is set",
+                    SKIP_CHECK_USER_ALLOWED_VAR
+                );
+                // Recorded in the invocation record, so that bypasses can be audited.
+                opt.common_opts.client_metadata.push(ClientMetadata {
+                    key: "check_user_allowed_skipped_by".to_owned(),
+                    value: SKIP_CHECK_USER_ALLOWED_VAR.to_owned(),
+                });
+            } else {
+                check_user_allowed()?;
+            }
         }
     }
 
@@ -267,6 +318,7 @@ pub(crate) enum CommandKind {
     Install(InstallCommand),
     Kill(KillCommand),
     Killall(KillallCommand),
+    Lint(LintCommand),
     Root(RootCommand),
     /// Alias for `uquery`.
     Query(UqueryCommand),
@@ -346,6 +398,8 @@ impl CommandKind {
             immediate_config,
             paths,
             verbosity: common_opts.verbosity,
+            verbosity_file: common_opts.verbosity_file,
+            stderr_tail: common_opts.stderr_tail,
             start_in_process_daemon,
             working_dir: process.working_dir.clone(),
             trace_id: process.trace_id.dupe(),
@@ -357,6 +411,7 @@ impl CommandKind {
             runtime: &runtime,
             oncall: common_opts.oncall,
             client_metadata: common_opts.client_metadata,
+            timeout: common_opts.timeout.map(Into::into),
         };
 
         match self {
@@ -387,6 +442,7 @@ impl CommandKind {
             CommandKind::Ctargets(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Audit(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Starlark(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Lint(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Run(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Uquery(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Debug(cmd) => cmd.exec(matches, command_ctx),
@@ -11,6 +11,7 @@
 //!
 //! The Buck2 CLI unwinds the stack on a panic, while the Buck2 daemon terminates without unwinding.
 //! This module sets up a shared panic hook to run before unwinding/termination for crash reporting.
+//! If `$BUCK2_PANIC_REPORT_DIR` is set, the hook also writes a JSON report of each panic there.
 
 use std::panic;
 use std::panic::PanicInfo;
@@ -49,23 +50,32 @@ pub fn initialize(fb: FacebookInit) -> anyhow::Result<()> {
 /// to collect additional information.
 fn the_panic_hook(fb: FacebookInit, info: &PanicInfo) {
     imp::write_panic_to_scribe(fb, info);
+    imp::write_panic_report(info);
 }
 
 mod imp {
     use std::collections::HashMap;
     use std::panic::PanicInfo;
+    use std::path::PathBuf;
     use std::thread;
     use std::time::Duration;
+    use std::time::SystemTime;
+    use std::time::UNIX_EPOCH;
 
     use backtrace::Backtrace;
+    use buck2_core::env_helper::EnvHelper;
     use buck2_core::error::StructuredErrorOptions;
     use buck2_data::Location;
     use buck2_events::metadata;
     use buck2_events::sink::scribe::new_thrift_scribe_sink_if_enabled;
     use buck2_events::BuckEvent;
     use fbinit::FacebookInit;
+    use serde::Serialize;
     use tokio::runtime::Builder;
 
+    /// When set, every panic is additionally written as a JSON report into this directory.
+    static PANIC_REPORT_DIR: EnvHelper<PathBuf> = EnvHelper::new("BUCK2_PANIC_REPORT_DIR");
+
     fn get_stack() -> Vec<buck2_data::structured_error::StackFrame> {
         fn ptr_to_string<T>(ptr: *mut T) -> String {
             format!("0x{:x}", ptr as usize)
@@ -145,6 +155,55 @@ mod imp {
         );
     }
 
+    #[derive(Serialize)]
+    struct PanicReport {
+        message: String,
+        location: Option<String>,
+        backtrace: String,
+        invocation_id: Option<String>,
+        argv: Vec<String>,
+        pid: u32,
+        timestamp_millis: u128,
+    }
+
+    /// Writes a JSON report of the given `PanicInfo` to `$BUCK2_PANIC_REPORT_DIR`, if set.
+    ///
+    /// This is best-effort: we are already panicking, so any failure here is silently ignored,
+    /// and nothing in here may panic (a panic in the panic hook aborts the process).
+    pub(crate) fn write_panic_report(info: &PanicInfo) {
+        let dir = match PANIC_REPORT_DIR.get() {
+            Ok(Some(dir)) => dir,
+            _ => return,
+        };
+        let timestamp_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let report = PanicReport {
+            message: get_message_for_panic(info),
+            location: info
+                .location()
+                .map(|loc| format!("{}:{}:{}", loc.file(), loc.line(), loc.column())),
+            backtrace: format!("{:?}", Backtrace::new()),
+            invocation_id: buck2_events::dispatch::get_dispatcher_opt()
+                .map(|dispatcher| dispatcher.trace_id().to_string()),
+            argv: std::env::args_os()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            pid: std::process::id(),
+            timestamp_millis,
+        };
+        let report = match serde_json::to_vec_pretty(&report) {
+            Ok(report) => report,
+            Err(_) => return,
+        };
+        let path = dir.join(format!(
+            "panic-{}-{}.json",
+            timestamp_millis,
+            std::process::id()
+        ));
+        let _ignored = std::fs::create_dir_all(dir).and_then(|()| std::fs::write(path, report));
+    }
+
     pub(crate) fn write_soft_error(
         fb: FacebookInit,
         category: &str,
@@ -11,26 +11,38 @@ use std::borrow::Cow;
 use std::io::Write;
 
 use anyhow::Context;
+use buck2_action_metadata_proto::REMOTE_DEP_FILE_KEY;
 use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::calculation::ActionCalculation;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
 use buck2_build_api::audit_dep_files::AUDIT_DEP_FILES;
+use buck2_build_api::audit_dep_files::AUDIT_DEP_FILE_CACHE;
 use buck2_core::base_deferred_key::BaseDeferredKey;
 use buck2_core::category::Category;
 use buck2_core::directory::Directory;
 use buck2_core::directory::DirectoryIterator;
+use buck2_core::execution_types::executor_config::Executor;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_execute::digest_config::HasDigestConfig;
+use buck2_execute::execute::action_digest::ActionDigestKind;
+use buck2_execute::execute::dice_data::GetReClient;
 use buck2_execute::materialize::materializer::HasMaterializer;
 use dice::DiceTransaction;
+use dupe::Dupe;
 
 use crate::actions::impls::run::dep_files::get_dep_files;
+use crate::actions::impls::run::dep_files::get_remote_dep_file_lookup;
 use crate::actions::impls::run::dep_files::read_dep_files;
 use crate::actions::impls::run::dep_files::DepFilesKey;
+use crate::actions::impls::run::dep_files::RemoteDepFileLookup;
 use crate::actions::impls::run::dep_files::StoredFingerprints;
 
 pub(crate) fn init_audit_dep_files() {
     AUDIT_DEP_FILES.init(|ctx, label, category, identifier, stdout| {
         Box::pin(audit_dep_files(ctx, label, category, identifier, stdout))
     });
+    AUDIT_DEP_FILE_CACHE
+        .init(|ctx, label, stdout| Box::pin(audit_dep_file_cache(ctx, label, stdout)));
 }
 
 async fn audit_dep_files(
@@ -89,3 +101,157 @@ async fn audit_dep_files(
 
     Ok(())
 }
+
+async fn audit_dep_file_cache(
+    ctx: &DiceTransaction,
+    label: ConfiguredTargetLabel,
+    stdout: &mut (dyn Write + Send),
+) -> anyhow::Result<()> {
+    let analysis = ctx
+        .get_analysis_result(&label)
+        .await?
+        .require_compatible()?;
+    let re_client = ctx.per_transaction_data().get_re_client();
+
+    for key in analysis.iter_action_keys() {
+        let action = ctx.get_action(&key).await?;
+        // Only run actions can declare dep files.
+        if action.kind() != buck2_data::ActionKind::Run {
+            continue;
+        }
+        writeln!(stdout, "{}", action.name())?;
+        if !action.declares_dep_files() {
+            writeln!(
+                stdout,
+                "  dep files: none declared, not eligible for dep file caching"
+            )?;
+            continue;
+        }
+        writeln!(stdout, "  dep files: declared")?;
+
+        let re_use_case = match &action.execution_config().executor {
+            Executor::RemoteEnabled {
+                re_use_case,
+                remote_dep_file_cache_enabled: true,
+                ..
+            } => *re_use_case,
+            _ => {
+                writeln!(
+                    stdout,
+                    "  remote dep file cache: not enabled for the executor of this action"
+                )?;
+                continue;
+            }
+        };
+        writeln!(stdout, "  remote dep file cache: enabled")?;
+
+        // The remote dep file key depends on the inputs of the action, so we only know it once
+        // the action was executed.
+        let dep_files_key = DepFilesKey::new(
+            action.owner().dupe(),
+            action.category().clone(),
+            action.identifier().map(|i| i.to_owned()),
+        );
+        let lookup = match get_remote_dep_file_lookup(&dep_files_key) {
+            Some(lookup) => lookup,
+            None => {
+                writeln!(
+                    stdout,
+                    "  last build: not executed since the daemon started, remote dep file key unknown"
+                )?;
+                continue;
+            }
+        };
+        // A failed lookup only concerns this action, so report it and carry on with the others.
+        let entry_exists = re_client
+            .action_cache(lookup.key.dupe().coerce::<ActionDigestKind>(), re_use_case)
+            .await
+            .map(|entry| {
+                entry.map_or(false, |entry| {
+                    entry
+                        .action_result
+                        .execution_metadata
+                        .auxiliary_metadata
+                        .iter()
+                        .any(|m| m.type_url == REMOTE_DEP_FILE_KEY)
+                })
+            });
+        write_remote_dep_file_lookup(stdout, &lookup, &entry_exists)?;
+    }
+
+    Ok(())
+}
+
+/// Describe the last remote dep file cache lookup of an action. `entry_exists` is whether the
+/// remote dep file cache has an entry for its key now, or why we could not find out.
+fn write_remote_dep_file_lookup(
+    stdout: &mut (dyn Write + Send),
+    lookup: &RemoteDepFileLookup,
+    entry_exists: &anyhow::Result<bool>,
+) -> anyhow::Result<()> {
+    writeln!(stdout, "  remote dep file key: {}", lookup.key)?;
+    match entry_exists {
+        Ok(true) => writeln!(stdout, "  remote entry: found")?,
+        Ok(false) => writeln!(stdout, "  remote entry: not found")?,
+        Err(e) => writeln!(stdout, "  remote entry: lookup failed: {:#}", e)?,
+    }
+    writeln!(stdout, "  last build: {}", lookup.outcome)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::cas_digest::CasDigestConfig;
+    use buck2_execute::execute::dep_file_digest::DepFileDigest;
+
+    use super::*;
+    use crate::actions::impls::run::dep_files::RemoteDepFileCacheOutcome;
+
+    #[test]
+    fn test_write_remote_dep_file_lookup() -> anyhow::Result<()> {
+        let key = DepFileDigest::from_content(b"key", CasDigestConfig::testing_default());
+        let write = |outcome, entry_exists: anyhow::Result<bool>| -> anyhow::Result<String> {
+            let mut stdout = Vec::new();
+            write_remote_dep_file_lookup(
+                &mut stdout,
+                &RemoteDepFileLookup {
+                    key: key.dupe(),
+                    outcome,
+                },
+                &entry_exists,
+            )?;
+            Ok(String::from_utf8(stdout)?)
+        };
+
+        assert_eq!(
+            write(RemoteDepFileCacheOutcome::Hit, Ok(true))?,
+            format!(
+                "  remote dep file key: {}\n  remote entry: found\n  last build: hit\n",
+                key
+            )
+        );
+        // An entry may have been uploaded since the lookup missed.
+        assert!(
+            write(RemoteDepFileCacheOutcome::Miss, Ok(true))?
+                .ends_with("  remote entry: found\n  last build: miss (no entry)\n")
+        );
+        // Not querying the cache is not reported as a miss.
+        assert!(write(RemoteDepFileCacheOutcome::NotQueried, Ok(false))?.ends_with(
+            "  remote entry: not found\n  last build: not queried (remote dep file cache not used)\n"
+        ));
+        assert!(write(RemoteDepFileCacheOutcome::Rejected, Ok(true))?.ends_with(
+            "  last build: miss (an entry was found, but the inputs its dep files track differ)\n"
+        ));
+        // A failed lookup is reported along with what we know from the last build.
+        assert!(
+            write(
+                RemoteDepFileCacheOutcome::Miss,
+                Err(anyhow::anyhow!("RE is down"))
+            )?
+            .ends_with(
+                "  remote entry: lookup failed: RE is down\n  last build: miss (no entry)\n"
+            )
+        );
+        Ok(())
+    }
+}
@@ -71,6 +71,11 @@ use tracing::instrument;
 #[allocative::root]
 static DEP_FILES: Lazy<DashMap<DepFilesKey, Arc<DepFileState>>> = Lazy::new(DashMap::new);
 
+/// The last remote dep file cache lookup of each action, for `buck2 audit dep-files`.
+#[allocative::root]
+static REMOTE_DEP_FILE_LOOKUPS: Lazy<DashMap<DepFilesKey, RemoteDepFileLookup>> =
+    Lazy::new(DashMap::new);
+
 /// When this is set, we retain directories after fingerprintig, so that we can output them later
 /// for debugging via `buck2 audit dep-files`.
 static KEEP_DIRECTORIES: EnvHelper<bool> = EnvHelper::new("BUCK2_KEEP_DEP_FILE_DIRECTORIES");
@@ -80,6 +85,7 @@ static KEEP_DIRECTORIES: EnvHelper<bool> = EnvHelper::new("BUCK2_KEEP_DEP_FILE_D
 fn flush_dep_files() {
     tracing::info!("Flushing {} dep files", DEP_FILES.len());
     DEP_FILES.clear();
+    REMOTE_DEP_FILE_LOOKUPS.clear();
 }
 
 pub(crate) fn init_flush_dep_files() {
@@ -90,8 +96,62 @@ pub(crate) fn get_dep_files(key: &DepFilesKey) -> Option<Arc<DepFileState>> {
     DEP_FILES.get(key).map(|s| s.dupe())
 }
 
+pub(crate) fn get_remote_dep_file_lookup(key: &DepFilesKey) -> Option<RemoteDepFileLookup> {
+    REMOTE_DEP_FILE_LOOKUPS.get(key).map(|l| l.clone())
+}
+
+/// The remote dep file key an action was last executed with, and what the remote dep file cache
+/// did for it.
+#[derive(Clone, Allocative)]
+pub(crate) struct RemoteDepFileLookup {
+    pub(crate) key: DepFileDigest,
+    pub(crate) outcome: RemoteDepFileCacheOutcome,
+}
+
+#[derive(Clone, Copy, Dupe, Debug, Display, PartialEq, Eq, Allocative)]
+pub(crate) enum RemoteDepFileCacheOutcome {
+    #[display(fmt = "hit")]
+    Hit,
+    /// There was an entry, but the inputs tracked by its dep files differ from ours.
+    #[display(fmt = "miss (an entry was found, but the inputs its dep files track differ)")]
+    Rejected,
+    /// There was no entry.
+    #[display(fmt = "miss (no entry)")]
+    Miss,
+    /// The remote dep file cache is not used for this action, e.g. because it runs locally only
+    /// or remote caching is disabled.
+    #[display(fmt = "not queried (remote dep file cache not used)")]
+    NotQueried,
+    /// Served before the remote dep file cache was queried.
+    #[display(fmt = "not queried (served by the local dep file cache)")]
+    LocalDepFileCacheHit,
+    #[display(fmt = "not queried (served by the action cache)")]
+    ActionCacheHit,
+}
+
+impl RemoteDepFileCacheOutcome {
+    /// The outcome when the cache served a result, either from the remote dep file cache or from
+    /// the action cache, and whether that result could be used.
+    pub(crate) fn of_cache_hit(served_by_remote_dep_file_cache: bool, usable: bool) -> Self {
+        match (usable, served_by_remote_dep_file_cache) {
+            (true, true) => Self::Hit,
+            (true, false) => Self::ActionCacheHit,
+            (false, _) => Self::Rejected,
+        }
+    }
+
+    /// The outcome when the cache had no result.
+    pub(crate) fn of_cache_miss(remote_dep_file_cache_queried: bool) -> Self {
+        if remote_dep_file_cache_queried {
+            Self::Miss
+        } else {
+            Self::NotQueried
+        }
+    }
+}
+
 /// A key used to associate a RunAction with a possible previous dep file.
-#[derive(Eq, PartialEq, Hash, Display, Allocative)]
+#[derive(Clone, Eq, PartialEq, Hash, Display, Allocative)]
 #[display(
     fmt = "{} {} {}",
     owner,
@@ -368,6 +428,16 @@ pub(crate) struct DepFileBundle {
 }
 
 impl DepFileBundle {
+    pub(crate) fn record_remote_dep_file_cache_outcome(&self, outcome: RemoteDepFileCacheOutcome) {
+        REMOTE_DEP_FILE_LOOKUPS.insert(
+            self.dep_files_key.clone(),
+            RemoteDepFileLookup {
+                key: self.remote_dep_file_key.dupe(),
+                outcome,
+            },
+        );
+    }
+
     pub async fn make_remote_dep_file_entry(
         &mut self,
         ctx: &dyn ActionExecutionCtx,
@@ -1466,4 +1536,31 @@ mod test {
         assert!(!decl2.declares_same_dep_files(&decl3));
         assert!(!decl3.declares_same_dep_files(&decl4));
     }
+
+    #[test]
+    fn test_remote_dep_file_cache_outcome() {
+        assert_eq!(
+            RemoteDepFileCacheOutcome::of_cache_hit(true, true),
+            RemoteDepFileCacheOutcome::Hit
+        );
+        // An entry whose dep files track inputs that differ from ours can't be used.
+        assert_eq!(
+            RemoteDepFileCacheOutcome::of_cache_hit(true, false),
+            RemoteDepFileCacheOutcome::Rejected
+        );
+        // The action cache hit first, so the remote dep file cache had nothing to do.
+        assert_eq!(
+            RemoteDepFileCacheOutcome::of_cache_hit(false, true),
+            RemoteDepFileCacheOutcome::ActionCacheHit
+        );
+        assert_eq!(
+            RemoteDepFileCacheOutcome::of_cache_miss(true),
+            RemoteDepFileCacheOutcome::Miss
+        );
+        // Not querying the cache is not a miss.
+        assert_eq!(
+            RemoteDepFileCacheOutcome::of_cache_miss(false),
+            RemoteDepFileCacheOutcome::NotQueried
+        );
+    }
 }
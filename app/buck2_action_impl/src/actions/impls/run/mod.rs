@@ -81,6 +81,7 @@ use self::dep_files::DepFileBundle;
 use crate::actions::impls::run::dep_files::make_dep_file_bundle;
 use crate::actions::impls::run::dep_files::populate_dep_files;
 use crate::actions::impls::run::dep_files::DepFilesCommandLineVisitor;
+use crate::actions::impls::run::dep_files::RemoteDepFileCacheOutcome;
 use crate::actions::impls::run::dep_files::RunActionDepFiles;
 use crate::actions::impls::run::metadata::metadata_content;
 
@@ -604,6 +605,18 @@ impl Action for RunAction {
             "allow_dep_file_cache_upload".to_owned() => self.inner.allow_dep_file_cache_upload.to_string(),
         }
     }
+
+    fn env(&self, fs: &ExecutorFs) -> anyhow::Result<Option<Vec<(String, String)>>> {
+        // Variables buck2 itself adds at execution time (e.g. `BUCK_SCRATCH_PATH`) are not
+        // included, only those declared by the rule.
+        let (expanded, _worker) =
+            self.expand_command_line_and_worker(fs, &mut SimpleCommandLineArtifactVisitor::new())?;
+        Ok(Some(expanded.env.into_iter().collect()))
+    }
+
+    fn declares_dep_files(&self) -> bool {
+        !self.inner.dep_files.labels.is_empty()
+    }
 }
 
 #[async_trait]
@@ -657,6 +670,9 @@ impl IncrementalActionExecutable for RunAction {
                 .check_local_dep_file_cache_for_identical_action(ctx, self.outputs.as_slice())
                 .await?;
             if let Some(m) = outputs {
+                dep_file_bundle.record_remote_dep_file_cache_outcome(
+                    RemoteDepFileCacheOutcome::LocalDepFileCacheHit,
+                );
                 return Ok(m);
             }
             should_fully_check_dep_file_cache
@@ -673,15 +689,33 @@ impl IncrementalActionExecutable for RunAction {
         // If the result was served by the remote dep file cache, we can't use the result just yet. We need to verify that
         // the inputs tracked by a depfile that was actually used in the cache hit are indentical to the inputs we have for this action.
         let result = if let ControlFlow::Break(res) = action_cache_result {
-            self.check_cache_result_is_useable(
-                ctx,
-                &req,
-                &prepared_action.action_and_blobs.action,
-                res,
-                &dep_file_bundle,
-            )
-            .await?
+            let served_by_remote_dep_file_cache = res.was_served_by_remote_dep_file_cache();
+            let result = self
+                .check_cache_result_is_useable(
+                    ctx,
+                    &req,
+                    &prepared_action.action_and_blobs.action,
+                    res,
+                    &dep_file_bundle,
+                )
+                .await?;
+            if let Some(bundle) = &dep_file_bundle {
+                bundle.record_remote_dep_file_cache_outcome(
+                    RemoteDepFileCacheOutcome::of_cache_hit(
+                        served_by_remote_dep_file_cache,
+                        result.is_break(),
+                    ),
+                );
+            }
+            result
         } else {
+            if let (Some(bundle), ControlFlow::Continue(manager)) =
+                (&dep_file_bundle, &action_cache_result)
+            {
+                bundle.record_remote_dep_file_cache_outcome(
+                    RemoteDepFileCacheOutcome::of_cache_miss(manager.remote_dep_file_cache_queried),
+                );
+            }
             action_cache_result
         };
 
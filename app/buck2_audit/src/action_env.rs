@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_client_ctx::common::CommonCommandOptions;
+
+use crate::AuditSubcommand;
+
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(
+    name = "audit-action-env",
+    about = "prints out the environment variables an action of a target is run with"
+)]
+pub struct AuditActionEnvCommand {
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+
+    #[clap(help = "Target whose action to print the environment for")]
+    pub pattern: String,
+
+    #[clap(help = "Action category")]
+    pub category: String,
+
+    #[clap(help = "Action identifier")]
+    pub identifier: Option<String>,
+
+    /// Print the environment as a JSON object.
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditActionEnvCommand {
+    fn common_opts(&self) -> &CommonCommandOptions {
+        &self.common_opts
+    }
+}
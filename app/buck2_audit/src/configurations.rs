@@ -27,6 +27,28 @@ pub struct AuditConfigurationsCommand {
         help = "configurations to audit (example: `cell//package:target-105fe3389fc7e436`). If none provided, will print information about all known configurations."
     )]
     pub configs: Vec<String>,
+
+    /// Instead of printing configurations, compare two configured targets and print the
+    /// attributes and `select()` resolutions which differ. Takes two targets, or a single target
+    /// together with `--diff-target-platforms`.
+    #[clap(
+        long,
+        multiple_values = true,
+        min_values = 1,
+        max_values = 2,
+        value_names = &["A", "B"],
+        conflicts_with = "configurations"
+    )]
+    pub diff: Vec<String>,
+
+    /// The two target platforms to configure the `--diff` target with.
+    #[clap(
+        long,
+        number_of_values = 2,
+        value_names = &["PLATFORM_A", "PLATFORM_B"],
+        requires = "diff"
+    )]
+    pub diff_target_platforms: Vec<String>,
 }
 
 #[async_trait]
@@ -15,7 +15,7 @@ use crate::AuditSubcommand;
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
 #[clap(
     name = "audit-dep-files",
-    about = "prints out the select files for a command"
+    about = "prints out the select files for a command, or how the commands of a target use dep file caching"
 )]
 pub struct AuditDepFilesCommand {
     #[clap(flatten)]
@@ -24,8 +24,12 @@ pub struct AuditDepFilesCommand {
     #[clap(help = "Target to query dep files for")]
     pub pattern: String,
 
-    #[clap(help = "Action category")]
-    pub category: String,
+    #[clap(
+        help = "Action category. Without one, prints for every command of the target whether it \
+                declares dep files, whether the remote dep file cache has an entry for it, and \
+                whether its last execution hit that cache"
+    )]
+    pub category: Option<String>,
 
     #[clap(help = "Action identifier")]
     pub identifier: Option<String>,
@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_cli_proto::common_build_options::ExecutionStrategy;
+use buck2_client_ctx::common::parse_re_use_case;
+use buck2_client_ctx::common::CommonCommandOptions;
+
+use crate::AuditSubcommand;
+
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(
+    name = "audit-execution-platforms",
+    about = "prints out the executor configuration that actions of the given targets run with"
+)]
+pub struct AuditExecutionPlatformsCommand {
+    #[clap(flatten)]
+    common_opts: CommonCommandOptions,
+
+    #[clap(name = "TARGET_PATTERNS", help = "Patterns to analyze")]
+    pub patterns: Vec<String>,
+
+    /// Show the executor config as a build with this `--re-use-case` would use it.
+    #[clap(long, value_name = "USE_CASE", parse(try_from_str = parse_re_use_case))]
+    pub re_use_case: Option<String>,
+
+    /// Show the executor, and the executor preference, that a build with this execution strategy
+    /// would use. Strategies overridden per category by `build.execution_strategy_overrides` are
+    /// shown too.
+    #[clap(long, arg_enum, value_name = "STRATEGY", default_value = "default")]
+    pub execution_strategy: AuditExecutionStrategy,
+
+    /// Show the executor preference as a daemon with paranoid mode enabled would use it.
+    #[clap(long)]
+    pub paranoid: bool,
+}
+
+/// The execution strategies which can be selected by build flags.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    clap::ArgEnum,
+    serde::Serialize,
+    serde::Deserialize
+)]
+pub enum AuditExecutionStrategy {
+    Default,
+    /// `--local-only`
+    LocalOnly,
+    /// `--remote-only`
+    RemoteOnly,
+    /// `--remote-only-with-local-fallback`
+    RemoteOnlyWithLocalFallback,
+    /// `--local-first-then-remote`
+    LocalFirstThenRemote,
+    /// `--prefer-local`
+    PreferLocal,
+    /// `--prefer-remote`
+    PreferRemote,
+}
+
+impl AuditExecutionStrategy {
+    pub fn to_proto(self) -> ExecutionStrategy {
+        match self {
+            AuditExecutionStrategy::Default => ExecutionStrategy::Default,
+            AuditExecutionStrategy::LocalOnly => ExecutionStrategy::LocalOnly,
+            AuditExecutionStrategy::RemoteOnly => ExecutionStrategy::RemoteOnly,
+            AuditExecutionStrategy::RemoteOnlyWithLocalFallback => {
+                ExecutionStrategy::RemoteOnlyWithLocalFallback
+            }
+            AuditExecutionStrategy::LocalFirstThenRemote => ExecutionStrategy::LocalFirstThenRemote,
+            AuditExecutionStrategy::PreferLocal => ExecutionStrategy::HybridPreferLocal,
+            AuditExecutionStrategy::PreferRemote => ExecutionStrategy::HybridPreferRemote,
+        }
+    }
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditExecutionPlatformsCommand {
+    fn common_opts(&self) -> &CommonCommandOptions {
+        &self.common_opts
+    }
+}
@@ -25,6 +25,12 @@ pub struct AuditIncludesCommand {
     #[clap(long)]
     pub json: bool,
 
+    /// Print the includes as a tree of load relationships rather than a flat list. Files
+    /// provided by the prelude are marked with `(prelude)`, and a file whose loads were already
+    /// shown is marked with `(*)`.
+    #[clap(long, conflicts_with = "json")]
+    pub tree: bool,
+
     #[clap(
         name = "BUILD_FILES",
         help = "Build files to audit. These are expected to be relative paths from the working dir cell."
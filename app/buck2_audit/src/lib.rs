@@ -24,6 +24,7 @@ use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::streaming::StreamingCommand;
 use classpath::AuditClasspathCommand;
 
+use crate::action_env::AuditActionEnvCommand;
 use crate::analysis_queries::AuditAnalysisQueriesCommand;
 use crate::cell::AuditCellCommand;
 use crate::config::AuditConfigCommand;
@@ -31,6 +32,7 @@ use crate::configurations::AuditConfigurationsCommand;
 use crate::deferred_materializer::DeferredMaterializerCommand;
 use crate::dep_files::AuditDepFilesCommand;
 use crate::execution_platform_resolution::AuditExecutionPlatformResolutionCommand;
+use crate::execution_platforms::AuditExecutionPlatformsCommand;
 use crate::includes::AuditIncludesCommand;
 use crate::output::command::AuditOutputCommand;
 use crate::output::parse::AuditParseCommand;
@@ -41,6 +43,7 @@ use crate::starlark::StarlarkCommand;
 use crate::subtargets::AuditSubtargetsCommand;
 use crate::visibility::AuditVisibilityCommand;
 
+pub mod action_env;
 pub mod analysis_queries;
 pub mod cell;
 pub mod classpath;
@@ -49,6 +52,7 @@ pub mod configurations;
 pub mod deferred_materializer;
 pub mod dep_files;
 pub mod execution_platform_resolution;
+pub mod execution_platforms;
 pub mod includes;
 pub mod output;
 pub mod package_values;
@@ -71,10 +75,12 @@ pub enum AuditCommand {
     Subtargets(AuditSubtargetsCommand),
     AnalysisQueries(AuditAnalysisQueriesCommand),
     ExecutionPlatformResolution(AuditExecutionPlatformResolutionCommand),
+    ExecutionPlatforms(AuditExecutionPlatformsCommand),
     Visibility(AuditVisibilityCommand),
     #[clap(subcommand)]
     Starlark(StarlarkCommand),
     DepFiles(AuditDepFilesCommand),
+    ActionEnv(AuditActionEnvCommand),
     DeferredMaterializer(DeferredMaterializerCommand),
     Output(AuditOutputCommand),
     Parse(AuditParseCommand),
@@ -107,8 +113,10 @@ impl AuditCommand {
             AuditCommand::Subtargets(cmd) => cmd,
             AuditCommand::AnalysisQueries(cmd) => cmd,
             AuditCommand::ExecutionPlatformResolution(cmd) => cmd,
+            AuditCommand::ExecutionPlatforms(cmd) => cmd,
             AuditCommand::Starlark(cmd) => cmd,
             AuditCommand::DepFiles(cmd) => cmd,
+            AuditCommand::ActionEnv(cmd) => cmd,
             AuditCommand::DeferredMaterializer(cmd) => cmd,
             AuditCommand::Visibility(cmd) => cmd,
             AuditCommand::Output(cmd) => cmd,
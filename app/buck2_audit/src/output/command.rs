@@ -16,7 +16,7 @@ use crate::AuditSubcommand;
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
 #[clap(
     name = "audit-output",
-    about = "Query the action that produced the output artifact. Does not support BXL, test, scratch, or anon artifacts. If the configuration hash of the output path does not match the current platform configuration, the unconfigured target label will be returned."
+    about = "Query the action that produced the output artifact. Does not support BXL, test, scratch, or anon artifacts, though `--owner` identifies scratch and test paths. If the configuration hash of the output path does not match the current platform configuration, the unconfigured target label will be returned."
 )]
 pub struct AuditOutputCommand {
     #[clap(flatten)]
@@ -31,6 +31,12 @@ pub struct AuditOutputCommand {
     #[clap(long)]
     pub json: bool,
 
+    /// Only report what owns the path: the configured target label, its rule type, and the
+    /// category and identifier of the action which produces the path. Scratch and test paths are
+    /// reported as such, and paths which no action produces as having no owner.
+    #[clap(long)]
+    pub owner: bool,
+
     #[clap(flatten)]
     pub query_attributes: CommonAttributeArgs,
 }
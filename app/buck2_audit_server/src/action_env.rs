@@ -0,0 +1,207 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io::Write;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_audit::action_env::AuditActionEnvCommand;
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::calculation::ActionCalculation;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_cli_proto::ClientContext;
+use buck2_core::category::Category;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_node::target_calculation::ConfiguredTargetCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use buck2_server_ctx::pattern::target_platform_from_client_context;
+use itertools::Itertools;
+
+use crate::AuditSubcommand;
+
+#[derive(Debug, buck2_error::Error)]
+enum AuditActionEnvError {
+    #[error("No action of `{0}` matches `{1}`")]
+    NotFound(String, String),
+    #[error("Multiple actions of `{0}` match `{1}`, specify an identifier: {2}")]
+    Ambiguous(String, String, String),
+    #[error("Action `{0}` does not run a command")]
+    NoCommand(String),
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditActionEnvCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        client_ctx: ClientContext,
+    ) -> anyhow::Result<()> {
+        server_ctx
+            .with_dice_ctx(async move |server_ctx, mut ctx| {
+                let target_platform =
+                    target_platform_from_client_context(&client_ctx, server_ctx, &mut ctx).await?;
+
+                let label = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                    &mut ctx,
+                    &[buck2_data::TargetPattern {
+                        value: self.pattern.clone(),
+                    }],
+                    server_ctx.working_dir(),
+                )
+                .await?
+                .into_iter()
+                .next()
+                .context("Parsing patterns returned nothing")?
+                .as_target_label(&self.pattern)?;
+
+                let label = ctx
+                    .get_configured_target(&label, target_platform.as_ref())
+                    .await?;
+
+                let category = Category::try_from(self.category.as_str())?;
+
+                let analysis = ctx
+                    .get_analysis_result(&label)
+                    .await?
+                    .require_compatible()?;
+                let mut actions = Vec::new();
+                for key in analysis.iter_action_keys() {
+                    actions.push(ctx.get_action(&key).await?);
+                }
+                let action = select_action(
+                    &label.to_string(),
+                    actions,
+                    &category,
+                    self.identifier.as_deref(),
+                    |action| (action.category(), action.identifier()),
+                )?;
+
+                let env = action
+                    .env(&ctx.get_artifact_fs().await?)?
+                    .ok_or_else(|| AuditActionEnvError::NoCommand(action.name()))?;
+
+                let mut stdout = stdout.as_writer();
+                if self.json {
+                    writeln!(stdout, "{}", serde_json::to_string_pretty(&env_json(env))?)?;
+                } else {
+                    for (k, v) in env {
+                        writeln!(stdout, "{}={}", k, v)?;
+                    }
+                }
+
+                Ok(())
+            })
+            .await
+    }
+}
+
+/// Picks the one action of `category` with `identifier`, or with any identifier if `None`.
+/// `describe` gives the category and identifier of an action.
+fn select_action<A>(
+    label: &str,
+    actions: Vec<A>,
+    category: &Category,
+    identifier: Option<&str>,
+    describe: impl Fn(&A) -> (&Category, Option<&str>),
+) -> anyhow::Result<A> {
+    let mut matching: Vec<A> = actions
+        .into_iter()
+        .filter(|action| {
+            let (action_category, action_identifier) = describe(action);
+            action_category == category && (identifier.is_none() || action_identifier == identifier)
+        })
+        .collect();
+
+    match matching.len() {
+        0 => Err(AuditActionEnvError::NotFound(label.to_owned(), category.to_string()).into()),
+        1 => Ok(matching.pop().unwrap()),
+        _ => Err(AuditActionEnvError::Ambiguous(
+            label.to_owned(),
+            category.to_string(),
+            matching
+                .iter()
+                .map(|a| describe(a).1.unwrap_or("<none>"))
+                .join(", "),
+        )
+        .into()),
+    }
+}
+
+fn env_json(env: Vec<(String, String)>) -> serde_json::Value {
+    serde_json::Value::Object(
+        env.into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_client_ctx::json_schema::JsonOutput;
+
+    use super::*;
+
+    #[test]
+    fn test_select_action() -> anyhow::Result<()> {
+        let compile = Category::try_from("cxx_compile")?;
+        let link = Category::try_from("cxx_link")?;
+        let actions = || {
+            vec![
+                (compile.clone(), Some("a.cpp")),
+                (compile.clone(), Some("b.cpp")),
+                (link.clone(), None),
+            ]
+        };
+        let select = |category: &Category, identifier: Option<&str>| {
+            select_action(
+                "root//:foo",
+                actions(),
+                category,
+                identifier,
+                |(category, identifier)| (category, *identifier),
+            )
+        };
+
+        assert_eq!(select(&compile, Some("b.cpp"))?.1, Some("b.cpp"));
+        // Any identifier matches when there is only one action of the category.
+        assert_eq!(select(&link, None)?.0, link);
+
+        assert_eq!(
+            select(&compile, None).unwrap_err().to_string(),
+            "Multiple actions of `root//:foo` match `cxx_compile`, specify an identifier: a.cpp, b.cpp"
+        );
+        assert_eq!(
+            select(&compile, Some("c.cpp")).unwrap_err().to_string(),
+            "No action of `root//:foo` matches `cxx_compile`"
+        );
+        assert_eq!(
+            select(&Category::try_from("genrule")?, None)
+                .unwrap_err()
+                .to_string(),
+            "No action of `root//:foo` matches `genrule`"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_json_matches_schema() -> anyhow::Result<()> {
+        let env = env_json(vec![
+            ("PATH".to_owned(), "/bin".to_owned()),
+            ("EMPTY".to_owned(), "".to_owned()),
+        ]);
+        JsonOutput::AuditActionEnv.validate(&env)?;
+        assert_eq!(env["PATH"], "/bin");
+        JsonOutput::AuditActionEnv.validate(&env_json(Vec::new()))?;
+        Ok(())
+    }
+}
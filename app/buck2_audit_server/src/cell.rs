@@ -14,6 +14,8 @@ use buck2_audit::cell::AuditCellCommand;
 use buck2_build_api::audit_cell::AUDIT_CELL;
 use buck2_cli_proto::ClientContext;
 use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::cells::name::CellName;
+use buck2_core::cells::CellAliasResolver;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::project::ProjectRoot;
@@ -41,6 +43,17 @@ impl AuditSubcommand for AuditCellCommand {
 
                 let mappings = audit_cell(&self.aliases_to_resolve, self.aliases, &cells, cwd, fs)?;
 
+                if self.aliases || !self.aliases_to_resolve.is_empty() {
+                    let alias_resolver = cells.get(cells.find(cwd)?)?.cell_alias_resolver();
+                    let mut stderr = server_ctx.stderr()?;
+                    for shadowed in shadowed_aliases(&cells, alias_resolver) {
+                        if mappings.contains_key(shadowed.alias.as_str()) {
+                            writeln!(stderr, "{}", shadowed)?;
+                        }
+                    }
+                    stderr.flush()?;
+                }
+
                 let mut stdout = stdout.as_writer();
                 if self.paths_only {
                     if self.json {
@@ -65,6 +78,55 @@ impl AuditSubcommand for AuditCellCommand {
     }
 }
 
+/// An alias spelled like the canonical name of a cell, but resolving to a different cell.
+#[derive(Debug, PartialEq)]
+struct ShadowedAlias {
+    alias: String,
+    shadowed: CellName,
+    resolved: CellName,
+    resolved_path: String,
+}
+
+impl std::fmt::Display for ShadowedAlias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Warning: alias `{}` shadows cell `{}`, it resolves to cell `{}` at `{}`",
+            self.alias, self.shadowed, self.resolved, self.resolved_path
+        )
+    }
+}
+
+fn shadowed_aliases(
+    cells: &CellResolver,
+    alias_resolver: &CellAliasResolver,
+) -> Vec<ShadowedAlias> {
+    let mut shadowed: Vec<_> = alias_resolver
+        .mappings()
+        .filter_map(|(alias, resolved)| {
+            let (shadowed, _) = cells
+                .cells()
+                .find(|(name, _)| name.as_str() == alias.as_str())?;
+            if shadowed == resolved {
+                return None;
+            }
+            Some(ShadowedAlias {
+                alias: alias.as_str().to_owned(),
+                shadowed,
+                resolved,
+                resolved_path: cells
+                    .get(resolved)
+                    .ok()?
+                    .path()
+                    .as_project_relative_path()
+                    .to_string(),
+            })
+        })
+        .collect();
+    shadowed.sort_by(|a, b| a.alias.cmp(&b.alias));
+    shadowed
+}
+
 pub(crate) fn audit_cell(
     aliases_to_resolve: &Vec<String>,
     aliases: bool,
@@ -130,3 +192,56 @@ pub(crate) fn init_audit_cell() {
         audit_cell(aliases_to_resolve, aliases, cells, cwd, fs)
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use buck2_core::cells::alias::NonEmptyCellAlias;
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_shadowed_aliases() -> anyhow::Result<()> {
+        let root = CellName::testing_new("root");
+        let foo = CellName::testing_new("foo");
+        let bar = CellName::testing_new("bar");
+        let path = |p: &str| CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new(p.into()));
+        let cells = CellResolver::testing_with_names_and_paths_with_alias(&[
+            (
+                root,
+                path(""),
+                HashMap::from([
+                    (NonEmptyCellAlias::testing_new("foo"), bar),
+                    (NonEmptyCellAlias::testing_new("bar"), bar),
+                    (NonEmptyCellAlias::testing_new("other"), foo),
+                ]),
+            ),
+            (foo, path("foo"), HashMap::new()),
+            (bar, path("bar"), HashMap::new()),
+        ]);
+
+        let shadowed = shadowed_aliases(&cells, cells.get(root)?.cell_alias_resolver());
+        assert_eq!(
+            vec![ShadowedAlias {
+                alias: "foo".to_owned(),
+                shadowed: foo,
+                resolved: bar,
+                resolved_path: "bar".to_owned(),
+            }],
+            shadowed
+        );
+        assert_eq!(
+            "Warning: alias `foo` shadows cell `foo`, it resolves to cell `bar` at `bar`",
+            shadowed[0].to_string()
+        );
+
+        assert_eq!(
+            Vec::<ShadowedAlias>::new(),
+            shadowed_aliases(&cells, cells.get(foo)?.cell_alias_resolver())
+        );
+        Ok(())
+    }
+}
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::io::Write;
 
 use async_trait::async_trait;
@@ -14,20 +16,40 @@ use buck2_audit::configurations::AuditConfigurationsCommand;
 use buck2_cli_proto::ClientContext;
 use buck2_core::configuration::bound_id::BoundConfigurationId;
 use buck2_core::configuration::data::ConfigurationData;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_node::attrs::display::AttrDisplayWithContextExt;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use buck2_node::target_calculation::ConfiguredTargetCalculation;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use buck2_server_ctx::pattern::target_platform_from_client_context;
+use buck2_server_ctx::pattern::PatternParser;
 use itertools::Itertools;
 
 use crate::AuditSubcommand;
 
+#[derive(Debug, buck2_error::Error)]
+enum AuditConfigurationsError {
+    #[error("`--diff` needs two targets, or one target and `--diff-target-platforms`")]
+    DiffNeedsTwoConfigurations,
+}
+
 #[async_trait]
 impl AuditSubcommand for AuditConfigurationsCommand {
     async fn server_execute(
         &self,
-        _server_ctx: &dyn ServerCommandContextTrait,
+        server_ctx: &dyn ServerCommandContextTrait,
         mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
-        _client_ctx: ClientContext,
+        client_ctx: ClientContext,
     ) -> anyhow::Result<()> {
+        if !self.diff.is_empty() {
+            return self.diff(server_ctx, stdout, client_ctx).await;
+        }
+
         let mut stdout = stdout.as_writer();
 
         if self.configs.is_empty() {
@@ -68,3 +90,162 @@ fn print_cfg(stdout: &mut impl Write, cfg: &ConfigurationData) -> anyhow::Result
 
     Ok(())
 }
+
+impl AuditConfigurationsCommand {
+    async fn diff(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        client_ctx: ClientContext,
+    ) -> anyhow::Result<()> {
+        server_ctx
+            .with_dice_ctx(async move |server_ctx, mut ctx| {
+                let pattern_parser = PatternParser::new(&mut ctx, server_ctx.working_dir()).await?;
+                let labels = self
+                    .diff
+                    .iter()
+                    .map(|pat| {
+                        pattern_parser
+                            .parse_pattern::<TargetPatternExtra>(pat)?
+                            .as_target_label(pat)
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                let mut configured: Vec<ConfiguredTargetLabel> = Vec::new();
+                match (labels.as_slice(), self.diff_target_platforms.as_slice()) {
+                    ([a, b], []) => {
+                        let target_platform =
+                            target_platform_from_client_context(&client_ctx, server_ctx, &mut ctx)
+                                .await?;
+                        for label in [a, b] {
+                            configured.push(
+                                ctx.get_configured_target(label, target_platform.as_ref())
+                                    .await?,
+                            );
+                        }
+                    }
+                    ([label], [_, _]) => {
+                        for platform in &self.diff_target_platforms {
+                            let client_ctx = ClientContext {
+                                target_platform: platform.clone(),
+                                ..client_ctx.clone()
+                            };
+                            let target_platform = target_platform_from_client_context(
+                                &client_ctx,
+                                server_ctx,
+                                &mut ctx,
+                            )
+                            .await?;
+                            configured.push(
+                                ctx.get_configured_target(label, target_platform.as_ref())
+                                    .await?,
+                            );
+                        }
+                    }
+                    _ => return Err(AuditConfigurationsError::DiffNeedsTwoConfigurations.into()),
+                }
+
+                let mut nodes = Vec::new();
+                for label in &configured {
+                    nodes.push(
+                        ctx.get_configured_target_node(label)
+                            .await?
+                            .require_compatible()?,
+                    );
+                }
+
+                let mut stdout = stdout.as_writer();
+                print_node_diff(&mut stdout, &nodes[0], &nodes[1])?;
+                Ok(())
+            })
+            .await
+    }
+}
+
+/// Print the attributes and `select()` resolutions which differ between two configured nodes.
+fn print_node_diff(
+    stdout: &mut impl Write,
+    a: &ConfiguredTargetNode,
+    b: &ConfiguredTargetNode,
+) -> anyhow::Result<()> {
+    fn attrs(node: &ConfiguredTargetNode) -> BTreeMap<String, String> {
+        let mut attrs: BTreeMap<String, String> = node
+            .attrs(AttrInspectOptions::All)
+            .map(|a| (a.name.to_owned(), a.value.as_display_no_ctx().to_string()))
+            .collect();
+        attrs.insert("buck.type".to_owned(), node.rule_type().to_string());
+        attrs
+    }
+
+    fn selects(node: &ConfiguredTargetNode) -> BTreeMap<String, String> {
+        node.selected_keys(AttrInspectOptions::All)
+            .map(|(name, keys)| (name.to_owned(), keys.join(", ")))
+            .collect()
+    }
+
+    writeln!(stdout, "--- {}", a.label())?;
+    writeln!(stdout, "+++ {}", b.label())?;
+
+    let attr_diffs = diff_maps(&attrs(a), &attrs(b));
+    let select_diffs = diff_maps(&selects(a), &selects(b));
+    if attr_diffs.is_empty() && select_diffs.is_empty() {
+        writeln!(stdout, "no differences")?;
+        return Ok(());
+    }
+
+    for (title, diffs) in [
+        ("attribute", attr_diffs),
+        ("select() resolution in", select_diffs),
+    ] {
+        for (name, a, b) in diffs {
+            writeln!(stdout, "{} `{}`:", title, name)?;
+            writeln!(stdout, "  - {}", a.unwrap_or("<none>"))?;
+            writeln!(stdout, "  + {}", b.unwrap_or("<none>"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The keys whose values differ between `a` and `b`, with the value on each side.
+fn diff_maps<'a>(
+    a: &'a BTreeMap<String, String>,
+    b: &'a BTreeMap<String, String>,
+) -> Vec<(&'a str, Option<&'a str>, Option<&'a str>)> {
+    a.keys()
+        .chain(b.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|k| {
+            let a = a.get(k).map(String::as_str);
+            let b = b.get(k).map(String::as_str);
+            (a != b).then_some((k.as_str(), a, b))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_maps() {
+        let a = BTreeMap::from_iter([
+            ("srcs".to_owned(), "[\"a.c\"]".to_owned()),
+            ("name".to_owned(), "foo".to_owned()),
+            ("linker".to_owned(), "ld".to_owned()),
+        ]);
+        let b = BTreeMap::from_iter([
+            ("srcs".to_owned(), "[\"b.c\"]".to_owned()),
+            ("name".to_owned(), "foo".to_owned()),
+        ]);
+        assert_eq!(
+            vec![
+                ("linker", Some("ld"), None),
+                ("srcs", Some("[\"a.c\"]"), Some("[\"b.c\"]")),
+            ],
+            diff_maps(&a, &b)
+        );
+        assert!(diff_maps(&a, &a).is_empty());
+    }
+}
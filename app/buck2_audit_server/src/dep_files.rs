@@ -11,6 +11,7 @@ use anyhow::Context as _;
 use async_trait::async_trait;
 use buck2_audit::dep_files::AuditDepFilesCommand;
 use buck2_build_api::audit_dep_files::AUDIT_DEP_FILES;
+use buck2_build_api::audit_dep_files::AUDIT_DEP_FILE_CACHE;
 use buck2_cli_proto::ClientContext;
 use buck2_core::category::Category;
 use buck2_core::pattern::pattern_type::TargetPatternExtra;
@@ -53,7 +54,13 @@ impl AuditSubcommand for AuditDepFilesCommand {
                     .get_configured_target(&label, target_platform.as_ref())
                     .await?;
 
-                let category = Category::try_from(self.category.as_str())?;
+                let category = match &self.category {
+                    Some(category) => Category::try_from(category.as_str())?,
+                    None => {
+                        return (AUDIT_DEP_FILE_CACHE.get()?)(&ctx, label, &mut stdout.as_writer())
+                            .await;
+                    }
+                };
 
                 (AUDIT_DEP_FILES.get()?)(
                     &ctx,
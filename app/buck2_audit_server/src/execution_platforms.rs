@@ -0,0 +1,328 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_audit::execution_platforms::AuditExecutionPlatformsCommand;
+use buck2_cli_proto::common_build_options::ExecutionStrategy;
+use buck2_cli_proto::ClientContext;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
+use buck2_core::execution_types::executor_config::CacheUploadBehavior;
+use buck2_core::execution_types::executor_config::CommandExecutorConfig;
+use buck2_core::execution_types::executor_config::Executor;
+use buck2_core::execution_types::executor_config::RemoteEnabledExecutor;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_execute::execute::request::ExecutorPreference;
+use buck2_execute::execute::strategy::parse_execution_strategy_overrides;
+use buck2_execute::execute::strategy::strategy_for_category;
+use buck2_execute::execute::strategy::SelectedExecutor;
+use buck2_node::load_patterns::load_patterns;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use buck2_node::target_calculation::ConfiguredTargetCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use buck2_server_ctx::pattern::parse_patterns_from_cli_args;
+use buck2_server_ctx::pattern::target_platform_from_client_context;
+use gazebo::prelude::SliceExt;
+use itertools::Itertools;
+
+use crate::AuditSubcommand;
+
+fn write_executor_config(
+    mut stdout: impl Write,
+    config: &CommandExecutorConfig,
+    re_use_case_override: Option<&str>,
+) -> anyhow::Result<()> {
+    match &config.executor {
+        Executor::Local(options) => {
+            writeln!(stdout, "  Executor: local")?;
+            writeln!(
+                stdout,
+                "    Use persistent workers: {}",
+                options.use_persistent_workers
+            )?;
+        }
+        Executor::RemoteEnabled {
+            executor,
+            re_properties,
+            re_use_case,
+            re_action_key,
+            cache_upload_behavior,
+            remote_cache_enabled,
+            remote_dep_file_cache_enabled,
+        } => {
+            writeln!(stdout, "  Executor: remote enabled ({})", executor)?;
+            match re_use_case_override {
+                Some(re_use_case_override) => writeln!(
+                    stdout,
+                    "    RE use case: {} (overridden, configured: {})",
+                    re_use_case_override, re_use_case
+                )?,
+                None => writeln!(stdout, "    RE use case: {}", re_use_case)?,
+            }
+            writeln!(stdout, "    RE properties:")?;
+            for (k, v) in re_properties.iter() {
+                writeln!(stdout, "      {} = {}", k, v)?;
+            }
+            if let Some(re_action_key) = re_action_key {
+                writeln!(stdout, "    RE action key: {}", re_action_key)?;
+            }
+            match cache_upload_behavior {
+                CacheUploadBehavior::Enabled {
+                    max_bytes: Some(max_bytes),
+                } => writeln!(stdout, "    Cache upload: enabled, max {} bytes", max_bytes)?,
+                CacheUploadBehavior::DryRun {
+                    max_bytes: Some(max_bytes),
+                } => writeln!(stdout, "    Cache upload: dry run, max {} bytes", max_bytes)?,
+                _ => writeln!(stdout, "    Cache upload: {}", cache_upload_behavior)?,
+            }
+            writeln!(stdout, "    Remote cache enabled: {}", remote_cache_enabled)?;
+            writeln!(
+                stdout,
+                "    Remote dep file cache enabled: {}",
+                remote_dep_file_cache_enabled
+            )?;
+        }
+    }
+    writeln!(
+        stdout,
+        "  Path separator: {:?}",
+        config.options.path_separator
+    )?;
+    writeln!(
+        stdout,
+        "  Output paths behavior: {:?}",
+        config.options.output_paths_behavior
+    )?;
+    Ok(())
+}
+
+/// Print the executor that a build with `strategy` would run actions with, both for actions in
+/// general and for the categories whose strategy is overridden.
+fn write_selected_executor(
+    mut stdout: impl Write,
+    executor: &Executor,
+    strategy: ExecutionStrategy,
+    overrides: &HashMap<String, ExecutionStrategy>,
+) -> anyhow::Result<()> {
+    fn selected(executor: &Executor, strategy: ExecutionStrategy) -> String {
+        match SelectedExecutor::select(executor, strategy) {
+            Some(selected) => selected.to_string(),
+            None => "none, the strategy is incompatible with the executor config".to_owned(),
+        }
+    }
+
+    writeln!(stdout, "  Execution strategy: {:?}", strategy)?;
+    writeln!(
+        stdout,
+        "  Selected executor: {}",
+        selected(executor, strategy)
+    )?;
+    for category in overrides.keys().sorted() {
+        let strategy = strategy_for_category(strategy, overrides, Some(category));
+        writeln!(
+            stdout,
+            "    Actions with category `{}`: strategy {:?}, executor {}",
+            category,
+            strategy,
+            selected(executor, strategy)
+        )?;
+    }
+    Ok(())
+}
+
+/// Print the preference that a hybrid executor runs actions with, including how it combines
+/// with the preferences actions can set for themselves.
+fn write_executor_preference(
+    mut stdout: impl Write,
+    strategy: ExecutionStrategy,
+    paranoid: bool,
+) -> anyhow::Result<()> {
+    let executor_preference = ExecutorPreference::for_hybrid_executor(strategy, paranoid)?;
+    writeln!(stdout, "    Executor preference: {}", executor_preference)?;
+    for (description, action_preference) in [
+        ("prefer_local", ExecutorPreference::LocalPreferred),
+        ("local_only", ExecutorPreference::LocalRequired),
+        ("prefer_remote", ExecutorPreference::RemotePreferred),
+    ] {
+        // This is how the hybrid executor combines them.
+        match executor_preference.and(action_preference) {
+            Ok(p) => writeln!(stdout, "      Actions with {}: {}", description, p)?,
+            Err(e) => writeln!(stdout, "      Actions with {}: {:#}", description, e)?,
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditExecutionPlatformsCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        client_ctx: ClientContext,
+    ) -> anyhow::Result<()> {
+        server_ctx
+            .with_dice_ctx(async move |server_ctx, mut ctx| {
+                let parsed_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                    &mut ctx,
+                    &self
+                        .patterns
+                        .map(|pat| buck2_data::TargetPattern { value: pat.clone() }),
+                    server_ctx.working_dir(),
+                )
+                .await?;
+                let loaded_patterns =
+                    load_patterns(&ctx, parsed_patterns, MissingTargetBehavior::Fail).await?;
+                let target_platform =
+                    target_platform_from_client_context(&client_ctx, server_ctx, &mut ctx).await?;
+                let root_cell = ctx.get_cell_resolver().await?.root_cell();
+                let overrides = ctx
+                    .get_legacy_config_property(root_cell, "build", "execution_strategy_overrides")
+                    .await?
+                    .map(|overrides| {
+                        parse_execution_strategy_overrides(
+                            &overrides.split(',').map(|o| o.to_owned()).collect::<Vec<_>>(),
+                        )
+                    })
+                    .transpose()?
+                    .unwrap_or_default();
+                let strategy = self.execution_strategy.to_proto();
+
+                let mut stdout = stdout.as_writer();
+
+                for (_, targets) in loaded_patterns.into_iter() {
+                    for (_, node) in targets? {
+                        let configured_target = ctx
+                            .get_configured_target(node.label(), target_platform.as_ref())
+                            .await?;
+                        let configured_node =
+                            ctx.get_configured_target_node(&configured_target).await?;
+                        let configured_node = configured_node.require_compatible()?;
+                        writeln!(stdout, "{}:", configured_target)?;
+                        // This is the same executor config that actions of this target are
+                        // executed with.
+                        let platform = match configured_node.execution_platform_resolution().platform() {
+                            Ok(platform) => platform,
+                            Err(e) => {
+                                writeln!(stdout, "  {:#}", e)?;
+                                continue;
+                            }
+                        };
+                        if platform.is_legacy() {
+                            writeln!(
+                                stdout,
+                                "  No execution platforms are configured, using the default executor config"
+                            )?;
+                        } else {
+                            writeln!(stdout, "  Execution platform: {}", platform.id())?;
+                        }
+                        write_executor_config(
+                            &mut stdout,
+                            platform.executor_config(),
+                            self.re_use_case.as_deref(),
+                        )?;
+                        write_selected_executor(
+                            &mut stdout,
+                            &platform.executor_config().executor,
+                            strategy,
+                            &overrides,
+                        )?;
+                        if let Executor::RemoteEnabled {
+                            executor: RemoteEnabledExecutor::Hybrid { .. },
+                            ..
+                        } = &platform.executor_config().executor
+                        {
+                            write_executor_preference(&mut stdout, strategy, self.paranoid)?;
+                        }
+                    }
+                }
+
+                Ok(())
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use buck2_cli_proto::common_build_options::ExecutionStrategy;
+    use buck2_core::execution_types::executor_config::CacheUploadBehavior;
+    use buck2_core::execution_types::executor_config::Executor;
+    use buck2_core::execution_types::executor_config::LocalExecutorOptions;
+    use buck2_core::execution_types::executor_config::RemoteEnabledExecutor;
+    use buck2_core::execution_types::executor_config::RemoteExecutorOptions;
+    use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
+    use starlark_map::sorted_map::SortedMap;
+
+    use super::write_selected_executor;
+
+    fn selected_executor(
+        executor: &Executor,
+        strategy: ExecutionStrategy,
+        overrides: &[(&str, ExecutionStrategy)],
+    ) -> anyhow::Result<String> {
+        let overrides: HashMap<String, ExecutionStrategy> = overrides
+            .iter()
+            .map(|(c, s)| ((*c).to_owned(), *s))
+            .collect();
+        let mut out = Vec::new();
+        write_selected_executor(&mut out, executor, strategy, &overrides)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_selected_executor_local() -> anyhow::Result<()> {
+        let local = Executor::Local(LocalExecutorOptions::default());
+        assert_eq!(
+            selected_executor(&local, ExecutionStrategy::Default, &[])?,
+            "  Execution strategy: Default\n  Selected executor: local\n"
+        );
+        assert_eq!(
+            selected_executor(&local, ExecutionStrategy::RemoteOnly, &[])?,
+            "  Execution strategy: RemoteOnly\n  Selected executor: none, the strategy is incompatible with the executor config\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_selected_executor_with_overrides() -> anyhow::Result<()> {
+        let remote = Executor::RemoteEnabled {
+            executor: RemoteEnabledExecutor::Remote(RemoteExecutorOptions::default()),
+            re_properties: SortedMap::new(),
+            re_use_case: RemoteExecutorUseCase::buck2_default(),
+            re_action_key: None,
+            cache_upload_behavior: CacheUploadBehavior::Disabled,
+            remote_cache_enabled: true,
+            remote_dep_file_cache_enabled: false,
+        };
+        assert_eq!(
+            selected_executor(
+                &remote,
+                ExecutionStrategy::Default,
+                &[
+                    ("cxx_link", ExecutionStrategy::LocalOnly),
+                    (
+                        "cxx_compile",
+                        ExecutionStrategy::RemoteOnlyWithLocalFallback
+                    ),
+                ],
+            )?,
+            "  Execution strategy: Default\n  Selected executor: remote\n    Actions with category `cxx_compile`: strategy RemoteOnlyWithLocalFallback, executor remote, with local fallback\n    Actions with category `cxx_link`: strategy LocalOnly, executor none, the strategy is incompatible with the executor config\n"
+        );
+        Ok(())
+    }
+}
@@ -7,6 +7,10 @@
  * of this source tree.
  */
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::hash::Hash;
 use std::io::Write;
 
 use async_trait::async_trait;
@@ -25,6 +29,7 @@ use buck2_core::package::PackageLabel;
 use buck2_interpreter::file_loader::LoadedModule;
 use buck2_interpreter::load_module::InterpreterCalculation;
 use buck2_interpreter::paths::module::StarlarkModulePath;
+use buck2_interpreter::prelude_path::prelude_path;
 use buck2_node::nodes::eval_result::EvaluationResult;
 use buck2_node::nodes::frontend::TargetGraphCalculation;
 use buck2_query::query::environment::LabeledNode;
@@ -59,10 +64,20 @@ enum AuditIncludesError {
     InvalidPath(CellPath),
 }
 
+/// The files transitively loaded by a build file, along with the load graph between them.
+struct TransitiveIncludes {
+    /// Every loaded file, each listed once, dependencies first.
+    includes: Vec<ImportPath>,
+    /// The files loaded directly by the build file, including implicit loads like the prelude.
+    roots: Vec<ImportPath>,
+    /// The files loaded directly by each of the `includes`.
+    loads: HashMap<ImportPath, Vec<ImportPath>>,
+}
+
 async fn get_transitive_includes(
     ctx: &DiceComputations,
     load_result: &EvaluationResult,
-) -> anyhow::Result<Vec<ImportPath>> {
+) -> anyhow::Result<TransitiveIncludes> {
     // We define a simple graph of LoadedModules to traverse.
     #[derive(Clone, Dupe)]
     struct Node(LoadedModule);
@@ -107,6 +122,7 @@ async fn get_transitive_includes(
 
     struct Delegate {
         imports: Vec<ImportPath>,
+        loads: HashMap<ImportPath, Vec<ImportPath>>,
     }
 
     #[async_trait]
@@ -121,14 +137,19 @@ async fn get_transitive_includes(
             target: &Node,
             func: &mut dyn ChildVisitor<Node>,
         ) -> anyhow::Result<()> {
-            for import in target.0.imports() {
+            let imports: Vec<ImportPath> = target.0.imports().cloned().collect();
+            for import in &imports {
                 func.visit(NodeRef(import.clone()))?;
             }
+            self.loads.insert(target.import_path().clone(), imports);
             Ok(())
         }
     }
 
-    let mut delegate = Delegate { imports: vec![] };
+    let mut delegate = Delegate {
+        imports: vec![],
+        loads: HashMap::new(),
+    };
     let lookup = Lookup { ctx };
 
     async_depth_first_postorder_traversal(
@@ -137,13 +158,64 @@ async fn get_transitive_includes(
         &mut delegate,
     )
     .await?;
-    Ok(delegate.imports)
+    Ok(TransitiveIncludes {
+        includes: delegate.imports,
+        roots: load_result.imports().to_vec(),
+        loads: delegate.loads,
+    })
+}
+
+/// Print the load graph below `roots` as an indented tree. A file whose loads were already shown
+/// is marked with `(*)` rather than expanded again, and a load which leads back to one of its
+/// ancestors is reported as a cycle rather than followed.
+fn write_include_tree<T: Eq + Hash + Clone + Display>(
+    out: &mut dyn Write,
+    roots: &[T],
+    loads: &HashMap<T, Vec<T>>,
+    is_prelude: &dyn Fn(&T) -> bool,
+) -> anyhow::Result<()> {
+    fn write_node<T: Eq + Hash + Clone + Display>(
+        out: &mut dyn Write,
+        node: &T,
+        loads: &HashMap<T, Vec<T>>,
+        is_prelude: &dyn Fn(&T) -> bool,
+        ancestors: &mut Vec<T>,
+        expanded: &mut HashSet<T>,
+    ) -> anyhow::Result<()> {
+        write!(out, "{}{}", "  ".repeat(ancestors.len()), node)?;
+        if is_prelude(node) {
+            write!(out, " (prelude)")?;
+        }
+        if let Some(start) = ancestors.iter().position(|a| a == node) {
+            let cycle = ancestors[start..].iter().chain([node]).join(" -> ");
+            writeln!(out, " (load cycle: {})", cycle)?;
+            return Ok(());
+        }
+        let children = loads.get(node).map_or(&[][..], |c| c.as_slice());
+        if !children.is_empty() && !expanded.insert(node.clone()) {
+            writeln!(out, " (*)")?;
+            return Ok(());
+        }
+        writeln!(out)?;
+        ancestors.push(node.clone());
+        for child in children {
+            write_node(out, child, loads, is_prelude, ancestors, expanded)?;
+        }
+        ancestors.pop();
+        Ok(())
+    }
+
+    let mut expanded = HashSet::new();
+    for root in roots {
+        write_node(out, root, loads, is_prelude, &mut Vec::new(), &mut expanded)?;
+    }
+    Ok(())
 }
 
 async fn load_and_collect_includes(
     ctx: &mut DiceComputations,
     path: &CellPath,
-) -> buck2_error::Result<Vec<ImportPath>> {
+) -> buck2_error::Result<TransitiveIncludes> {
     let parent = path
         .parent()
         .ok_or_else(|| anyhow::anyhow!(AuditIncludesError::InvalidPath(path.clone())))?;
@@ -222,7 +294,42 @@ impl AuditSubcommand for AuditIncludesCommand {
                     })
                     .collect();
 
-                let results: Vec<(_, buck2_error::Result<Vec<_>>)> = futures.collect().await;
+                let results: Vec<(_, buck2_error::Result<TransitiveIncludes>)> =
+                    futures.collect().await;
+
+                if self.tree {
+                    let mut stdout = stdout.as_writer();
+                    let prelude = prelude_path(&cells).ok();
+                    let is_prelude = |include: &ImportPath| {
+                        prelude
+                            .as_ref()
+                            .map_or(false, |prelude| prelude.is_prelude_path(include.path()))
+                    };
+                    for (path, includes) in &results {
+                        match includes {
+                            Ok(includes) => {
+                                // intentionally add a blank line after the header
+                                writeln!(stdout, "# {}\n", path)?;
+                                write_include_tree(
+                                    &mut stdout,
+                                    &includes.roots,
+                                    &includes.loads,
+                                    &is_prelude,
+                                )?;
+                            }
+                            Err(e) => {
+                                // intentionally add a blank line after the header
+                                writeln!(stdout, "! {}\n", path)?;
+                                writeln!(stdout, "{:#}", e)?;
+                            }
+                        }
+                    }
+                    for (_, result) in results {
+                        result?;
+                    }
+                    return Ok(());
+                }
+
                 // This is expected to not return any errors, and so we're not careful about not propagating it.
                 let to_absolute_path = move |include: ImportPath| -> anyhow::Result<_> {
                     let include = include.path();
@@ -235,7 +342,12 @@ impl AuditSubcommand for AuditIncludesCommand {
                         Ok(paths.into_try_map(&to_absolute_path)?)
                     };
                 let results: Vec<(String, buck2_error::Result<Vec<AbsNormPathBuf>>)> = results
-                    .into_map(|(path, includes)| (path, includes.and_then(absolutize_paths)));
+                    .into_map(|(path, includes)| {
+                        (
+                            path,
+                            includes.and_then(|includes| absolutize_paths(includes.includes)),
+                        )
+                    });
 
                 let mut stdout = stdout.as_writer();
 
@@ -297,3 +409,51 @@ impl AuditSubcommand for AuditIncludesCommand {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(roots: &[&str], loads: &[(&str, &[&str])], prelude: &[&str]) -> String {
+        let roots = roots.map(|r| r.to_string());
+        let loads = loads
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.map(|t| t.to_string())))
+            .collect();
+        let mut out = Vec::new();
+        write_include_tree(&mut out, &roots, &loads, &|path: &String| {
+            prelude.contains(&path.as_str())
+        })
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_include_tree() {
+        assert_eq!(
+            tree(
+                &["prelude.bzl", "a.bzl", "b.bzl"],
+                &[
+                    ("prelude.bzl", &["rules.bzl"]),
+                    ("a.bzl", &["c.bzl"]),
+                    ("b.bzl", &["c.bzl"]),
+                    ("c.bzl", &["d.bzl"]),
+                ],
+                &["prelude.bzl", "rules.bzl"],
+            ),
+            "prelude.bzl (prelude)\n  rules.bzl (prelude)\na.bzl\n  c.bzl\n    d.bzl\nb.bzl\n  c.bzl (*)\n"
+        );
+    }
+
+    #[test]
+    fn test_include_tree_cycle() {
+        assert_eq!(
+            tree(
+                &["a.bzl"],
+                &[("a.bzl", &["b.bzl"]), ("b.bzl", &["a.bzl"])],
+                &[],
+            ),
+            "a.bzl\n  b.bzl\n    a.bzl (load cycle: a.bzl -> b.bzl -> a.bzl)\n"
+        );
+    }
+}
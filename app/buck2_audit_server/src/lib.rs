@@ -19,6 +19,7 @@ use buck2_cli_proto::ClientContext;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 
+mod action_env;
 mod analysis_queries;
 mod cell;
 mod classpath;
@@ -27,6 +28,7 @@ mod configurations;
 pub mod deferred_materializer;
 mod dep_files;
 mod execution_platform_resolution;
+mod execution_platforms;
 mod includes;
 pub mod output;
 mod package_values;
@@ -90,8 +92,10 @@ impl AuditCommandExt for AuditCommand {
             AuditCommand::Subtargets(cmd) => cmd,
             AuditCommand::AnalysisQueries(cmd) => cmd,
             AuditCommand::ExecutionPlatformResolution(cmd) => cmd,
+            AuditCommand::ExecutionPlatforms(cmd) => cmd,
             AuditCommand::Starlark(cmd) => cmd,
             AuditCommand::DepFiles(cmd) => cmd,
+            AuditCommand::ActionEnv(cmd) => cmd,
             AuditCommand::DeferredMaterializer(cmd) => cmd,
             AuditCommand::Visibility(cmd) => cmd,
             AuditCommand::Output(cmd) => cmd,
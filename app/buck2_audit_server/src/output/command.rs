@@ -7,8 +7,10 @@
  * of this source tree.
  */
 
+use std::fmt;
 use std::io::Write;
 
+use anyhow::Context;
 use async_trait::async_trait;
 use buck2_audit::output::command::AuditOutputCommand;
 use buck2_build_api::actions::query::FIND_MATCHING_ACTION;
@@ -18,9 +20,12 @@ use buck2_build_api::audit_output::AuditOutputResult;
 use buck2_build_api::audit_output::AUDIT_OUTPUT;
 use buck2_cli_proto::ClientContext;
 use buck2_common::dice::cells::HasCellResolver;
+use buck2_core::base_deferred_key::BaseDeferredKey;
+use buck2_core::cells::cell_path::CellPath;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::target::label::TargetLabel;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
 use buck2_node::target_calculation::ConfiguredTargetCalculation;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::ctx::ServerCommandDiceContext;
@@ -94,6 +99,171 @@ async fn audit_output<'v>(
     .map(AuditOutputResult::Match))
 }
 
+/// What owns a buck-out path, for `--owner`.
+#[derive(Debug)]
+enum OutputOwner {
+    Action {
+        target: String,
+        rule_type: String,
+        category: String,
+        identifier: Option<String>,
+    },
+    /// A scratch directory, where actions of the target write temporary files.
+    Scratch { target: TargetLabel },
+    /// A directory where tests of the package run.
+    Test { path: CellPath },
+    /// No action produces the path, with the reason why.
+    NoOwner { reason: String },
+}
+
+impl OutputOwner {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            OutputOwner::Action {
+                target,
+                rule_type,
+                category,
+                identifier,
+            } => serde_json::json!({
+                "kind": "action",
+                "target": target,
+                "rule_type": rule_type,
+                "category": category,
+                "identifier": identifier,
+            }),
+            OutputOwner::Scratch { target } => serde_json::json!({
+                "kind": "scratch",
+                "target": target.to_string(),
+            }),
+            OutputOwner::Test { path } => serde_json::json!({
+                "kind": "test",
+                "path": path.to_string(),
+            }),
+            OutputOwner::NoOwner { reason } => serde_json::json!({
+                "kind": "none",
+                "reason": reason,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for OutputOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputOwner::Action {
+                target,
+                rule_type,
+                category,
+                identifier,
+            } => {
+                writeln!(f, "target: {}", target)?;
+                writeln!(f, "rule type: {}", rule_type)?;
+                match identifier {
+                    Some(identifier) => write!(f, "action: {} {}", category, identifier),
+                    None => write!(f, "action: {}", category),
+                }
+            }
+            OutputOwner::Scratch { target } => {
+                write!(f, "scratch path of `{}`, not an output", target)
+            }
+            OutputOwner::Test { path } => write!(f, "test path of `{}`, not an output", path),
+            OutputOwner::NoOwner { reason } => write!(f, "no owner: {}", reason),
+        }
+    }
+}
+
+/// The owner of a path which is known from the path alone, without looking at its target. `None`
+/// for rule outputs, whose owner is the action producing them.
+fn owner_from_path(output_path: &str, cell_resolver: &CellResolver) -> Option<OutputOwner> {
+    match BuckOutPathParser::new(cell_resolver).parse(output_path) {
+        Ok(BuckOutPathType::RuleOutput { .. }) => None,
+        Ok(BuckOutPathType::TmpOutput { target_label, .. }) => Some(OutputOwner::Scratch {
+            target: target_label,
+        }),
+        Ok(BuckOutPathType::TestOutput { path, .. }) => Some(OutputOwner::Test { path }),
+        Ok(BuckOutPathType::BxlOutput {
+            bxl_function_label, ..
+        }) => Some(OutputOwner::NoOwner {
+            reason: format!(
+                "the path is an output of the BXL function `{}`, not of a target",
+                bxl_function_label
+            ),
+        }),
+        Ok(BuckOutPathType::AnonOutput { target_label, .. }) => Some(OutputOwner::NoOwner {
+            reason: format!(
+                "the path is an output of an anonymous target created by `{}`",
+                target_label
+            ),
+        }),
+        Err(e) => Some(OutputOwner::NoOwner {
+            reason: format!("the path is not an output path: {:#}", e),
+        }),
+    }
+}
+
+async fn audit_output_owner<'v>(
+    output_path: &'v str,
+    working_dir: &'v ProjectRelativePath,
+    cell_resolver: &'v CellResolver,
+    dice_ctx: &'v DiceComputations,
+    global_target_platform: Option<TargetLabel>,
+) -> anyhow::Result<OutputOwner> {
+    if let Some(owner) = owner_from_path(output_path, cell_resolver) {
+        return Ok(owner);
+    }
+
+    let action = match audit_output(
+        output_path,
+        working_dir,
+        cell_resolver,
+        dice_ctx,
+        global_target_platform,
+    )
+    .await
+    {
+        Ok(Some(AuditOutputResult::Match(action))) => action,
+        Ok(Some(AuditOutputResult::MaybeRelevant(label))) => {
+            return Ok(OutputOwner::NoOwner {
+                reason: format!(
+                    "the configuration of the path is not the configuration of `{}` for the target platform of this command",
+                    label
+                ),
+            });
+        }
+        Ok(None) => {
+            return Ok(OutputOwner::NoOwner {
+                reason: "no action produces the path".to_owned(),
+            });
+        }
+        // E.g. the target was deleted or renamed since the path was written.
+        Err(e) => {
+            return Ok(OutputOwner::NoOwner {
+                reason: format!("the target of the path can't be analyzed: {:#}", e),
+            });
+        }
+    };
+    let action = action
+        .action()
+        .context("Matching action query node is not an action")?;
+
+    let (target, rule_type) = match action.owner() {
+        BaseDeferredKey::TargetLabel(label) => {
+            let node = dice_ctx
+                .get_configured_target_node(label)
+                .await?
+                .require_compatible()?;
+            (label.to_string(), node.rule_type().name().to_owned())
+        }
+        owner => (owner.to_string(), "none".to_owned()),
+    };
+    Ok(OutputOwner::Action {
+        target,
+        rule_type,
+        category: action.category().to_string(),
+        identifier: action.identifier().map(ToOwned::to_owned),
+    })
+}
+
 pub(crate) fn init_audit_output() {
     AUDIT_OUTPUT.init(
         |output_path, working_dir, cell_resolver, dice_ctx, global_target_platform| {
@@ -135,10 +305,20 @@ impl AuditSubcommand for AuditOutputCommand {
                 )
                 .await?;
 
-                let result = audit_output(&self.output_path, working_dir, &cell_resolver, &dice_ctx, global_target_platform).await?;
-
                 let mut stdout = stdout.as_writer();
 
+                if self.owner {
+                    let owner = audit_output_owner(&self.output_path, working_dir, &cell_resolver, &dice_ctx, global_target_platform).await?;
+                    if self.json {
+                        writeln!(stdout, "{}", serde_json::to_string_pretty(&owner.to_json())?)?;
+                    } else {
+                        writeln!(stdout, "{}", owner)?;
+                    }
+                    return Ok(());
+                }
+
+                let result = audit_output(&self.output_path, working_dir, &cell_resolver, &dice_ctx, global_target_platform).await?;
+
                 match result {
                     Some(result) => {
                         match result {
@@ -168,3 +348,77 @@ impl AuditSubcommand for AuditOutputCommand {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::cells::cell_root_path::CellRootPath;
+    use buck2_core::cells::name::CellName;
+
+    use super::*;
+
+    #[test]
+    fn test_display_output_owner() {
+        let owner = OutputOwner::Action {
+            target: "root//foo:bar (cfg//:linux#0123456789abcdef)".to_owned(),
+            rule_type: "cxx_library".to_owned(),
+            category: "cxx_compile".to_owned(),
+            identifier: Some("bar.cpp".to_owned()),
+        };
+        assert_eq!(
+            owner.to_string(),
+            "target: root//foo:bar (cfg//:linux#0123456789abcdef)\n\
+            rule type: cxx_library\n\
+            action: cxx_compile bar.cpp"
+        );
+        assert_eq!(owner.to_json()["kind"], "action");
+
+        let owner = OutputOwner::NoOwner {
+            reason: "no action produces the path".to_owned(),
+        };
+        assert_eq!(owner.to_string(), "no owner: no action produces the path");
+        assert_eq!(owner.to_json()["kind"], "none");
+    }
+
+    #[test]
+    fn test_owner_from_path() -> anyhow::Result<()> {
+        let cell_resolver = CellResolver::testing_with_name_and_path(
+            CellName::testing_new("bar"),
+            CellRootPath::new(ProjectRelativePath::new("foo/bar")?).to_buf(),
+        );
+        let owner = |path: &str| owner_from_path(path, &cell_resolver);
+        let no_owner = |path: &str| match owner(path) {
+            Some(OutputOwner::NoOwner { reason }) => reason,
+            owner => panic!("Expected no owner for `{}`, got {:?}", path, owner),
+        };
+
+        assert!(owner("buck-out/v2/gen/bar/cfg_hash/pkg/__target__/out").is_none());
+        match owner("buck-out/v2/tmp/bar/cfg_hash/pkg/__target__/out") {
+            Some(OutputOwner::Scratch { target }) => {
+                assert_eq!(target.to_string(), "bar//pkg:target")
+            }
+            owner => panic!("Expected a scratch path, got {:?}", owner),
+        }
+        match owner("buck-out/v2/test/bar/cfg_hash/pkg/out") {
+            Some(OutputOwner::Test { path }) => assert_eq!(path.to_string(), "bar//pkg/out"),
+            owner => panic!("Expected a test path, got {:?}", owner),
+        }
+
+        let reason = no_owner("buck-out/v2/gen-bxl/bar/cfg_hash/pkg/f.bxl/__func__/out");
+        assert!(reason.contains("BXL function"), "{}", reason);
+        let reason = no_owner("buck-out/v2/gen-anon/bar/cfg_hash/pkg/anon_hash/__target__/out");
+        assert!(reason.contains("anonymous target"), "{}", reason);
+        for path in [
+            "src/foo.cpp",
+            "buck-out/v2/gen/nonexistent_cell/cfg_hash/pkg/__target__/out",
+            ".isolation-buck-out/gen/bar/pkg/__target__/out",
+        ] {
+            let reason = no_owner(path);
+            assert!(
+                reason.starts_with("the path is not an output path"),
+                "{}",
+                reason
+            );
+        }
+        Ok(())
+    }
+}
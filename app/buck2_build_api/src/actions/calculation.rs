@@ -110,7 +110,7 @@ async fn build_action_no_redirect(
     };
 
     let executor = ctx
-        .get_action_executor(action.execution_config())
+        .get_action_executor(action.execution_config(), action.category())
         .await
         .context(format!("for action `{}`", action))?;
 
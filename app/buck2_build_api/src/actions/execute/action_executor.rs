@@ -19,6 +19,7 @@ use buck2_common::dice::data::HasIoProvider;
 use buck2_common::events::HasEvents;
 use buck2_common::io::IoProvider;
 use buck2_common::liveliness_observer::NoopLivelinessObserver;
+use buck2_core::category::Category;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::fs::buck_out_path::BuckOutPath;
@@ -229,6 +230,7 @@ pub trait HasActionExecutor {
     async fn get_action_executor(
         &self,
         config: &CommandExecutorConfig,
+        category: &Category,
     ) -> anyhow::Result<Arc<dyn ActionExecutor>>;
 }
 
@@ -237,6 +239,7 @@ impl HasActionExecutor for DiceComputations {
     async fn get_action_executor(
         &self,
         executor_config: &CommandExecutorConfig,
+        category: &Category,
     ) -> anyhow::Result<Arc<dyn ActionExecutor>> {
         let artifact_fs = self.get_artifact_fs().await?;
         let digest_config = self.global_data().get_digest_config();
@@ -246,7 +249,7 @@ impl HasActionExecutor for DiceComputations {
             platform,
             cache_checker,
             cache_uploader,
-        } = self.get_command_executor(&artifact_fs, executor_config)?;
+        } = self.get_command_executor(&artifact_fs, executor_config, Some(category))?;
         let blocking_executor = self.get_blocking_executor();
         let materializer = self.per_transaction_data().get_materializer();
         let events = self.per_transaction_data().get_dispatcher().dupe();
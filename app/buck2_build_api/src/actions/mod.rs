@@ -153,6 +153,17 @@ pub trait Action: Allocative + Debug + Send + Sync + 'static {
         indexmap! {}
     }
 
+    /// The environment variables the command of this action is run with, expanded the same way
+    /// as for execution. `None` if this action doesn't run a command.
+    fn env(&self, _fs: &ExecutorFs) -> anyhow::Result<Option<Vec<(String, String)>>> {
+        Ok(None)
+    }
+
+    /// Whether this action declares dep files, which makes it eligible for the dep file caches.
+    fn declares_dep_files(&self) -> bool {
+        false
+    }
+
     // TODO this probably wants more data for execution, like printing a short_name and the target
 }
 
@@ -353,6 +364,14 @@ impl RegisteredAction {
     pub fn identifier(&self) -> Option<&str> {
         self.action.identifier()
     }
+
+    /// The environment variables the command of this action is run with, if it runs one.
+    pub fn env(&self, fs: &ArtifactFs) -> anyhow::Result<Option<Vec<(String, String)>>> {
+        self.action.env(&ExecutorFs::new(
+            fs,
+            self.executor_config.options.path_separator,
+        ))
+    }
 }
 
 impl Deref for RegisteredAction {
@@ -10,6 +10,8 @@
 use std::fmt::Debug;
 use std::sync::Arc;
 
+use buck2_artifact::actions::key::ActionKey;
+use buck2_artifact::artifact::provide_outputs::ProvideActionKey;
 use buck2_artifact::deferred::id::DeferredId;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_interpreter::starlark_profiler::StarlarkProfileDataAndStats;
@@ -71,6 +73,13 @@ impl AnalysisResult {
         self.deferred.iter()
     }
 
+    /// The keys of all the actions declared by this analysis.
+    pub fn iter_action_keys(&self) -> impl Iterator<Item = ActionKey> + '_ {
+        self.iter_deferreds().filter_map(|entry| {
+            provider::request_value::<ProvideActionKey>(entry.as_complex()).map(|key| key.0)
+        })
+    }
+
     pub fn testing_deferred(&self) -> &DeferredTable {
         &self.deferred
     }
@@ -26,3 +26,13 @@ pub static AUDIT_DEP_FILES: LateBinding<
         &'a mut (dyn Write + Send),
     ) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>>,
 > = LateBinding::new("AUDIT_DEP_FILES");
+
+/// Implementation of `audit dep-files` without a category: how the actions of a target use the
+/// remote dep file cache.
+pub static AUDIT_DEP_FILE_CACHE: LateBinding<
+    for<'a> fn(
+        ctx: &'a DiceTransaction,
+        ConfiguredTargetLabel,
+        &'a mut (dyn Write + Send),
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>>,
+> = LateBinding::new("AUDIT_DEP_FILE_CACHE");
@@ -9,7 +9,11 @@
 
 //! DICE calculations for bxl
 
+use std::collections::HashSet;
+use std::fmt;
 use std::fmt::Debug;
+use std::fmt::Display;
+use std::hash::Hash;
 use std::sync::Arc;
 
 use allocative::Allocative;
@@ -17,10 +21,14 @@ use async_trait::async_trait;
 use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_core::base_deferred_key::BaseDeferredKeyDyn;
 use buck2_util::late_binding::LateBinding;
+use buck2_wrapper_common::invocation_id::TraceId;
 use dashmap::DashMap;
 use dice::DiceComputations;
+use dice::DiceTransactionUpdater;
+use dice::InjectedKey;
 use dupe::Dupe;
 
+use crate::artifact_groups::ArtifactGroup;
 use crate::bxl::result::BxlResult;
 
 #[async_trait]
@@ -36,6 +44,158 @@ pub trait BxlCalculationDyn: Debug + Send + Sync + 'static {
 pub struct BxlComputeResult {
     pub bxl_result: Arc<BxlResult>,
     pub materializations: Arc<DashMap<BuildArtifact, ()>>,
+    /// The command that evaluated the bxl function to produce this result.
+    computed_by: TraceId,
+}
+
+impl BxlComputeResult {
+    pub fn new(
+        bxl_result: Arc<BxlResult>,
+        materializations: Arc<DashMap<BuildArtifact, ()>>,
+        computed_by: TraceId,
+    ) -> Self {
+        Self {
+            bxl_result,
+            materializations,
+            computed_by,
+        }
+    }
+
+    /// Whether the command `trace_id` was served this result from the DICE cache, i.e. it was
+    /// evaluated by an earlier command.
+    pub fn is_cache_hit(&self, trace_id: &TraceId) -> bool {
+        &self.computed_by != trace_id
+    }
+
+    /// Compares this result against the result of a previous run of the same bxl function.
+    /// The outputs live in buck-out, so the caller reads them and passes them in.
+    pub fn diff(
+        &self,
+        previous: &BxlComputeResult,
+        output: &[u8],
+        previous_output: &[u8],
+    ) -> BxlResultDiff {
+        let (added_artifacts, removed_artifacts) = diff_sets(
+            self.bxl_result.ensured_artifacts().iter().cloned(),
+            previous.bxl_result.ensured_artifacts().iter().cloned(),
+        );
+        let (added_materializations, removed_materializations) = diff_sets(
+            self.materializations.iter().map(|x| x.key().dupe()),
+            previous.materializations.iter().map(|x| x.key().dupe()),
+        );
+        BxlResultDiff {
+            added_artifacts,
+            removed_artifacts,
+            added_materializations,
+            removed_materializations,
+            output_changed: output != previous_output,
+        }
+    }
+}
+
+/// Returns the items only in `new` and only in `old`, each sorted by their display form so the
+/// diff is stable.
+fn diff_sets<T: Clone + Eq + Hash + Display>(
+    new: impl Iterator<Item = T>,
+    old: impl Iterator<Item = T>,
+) -> (Vec<T>, Vec<T>) {
+    let new: HashSet<T> = new.collect();
+    let old: HashSet<T> = old.collect();
+    let sorted = |xs: &mut Vec<T>| xs.sort_by_cached_key(|x| x.to_string());
+    let mut added: Vec<T> = new.difference(&old).cloned().collect();
+    let mut removed: Vec<T> = old.difference(&new).cloned().collect();
+    sorted(&mut added);
+    sorted(&mut removed);
+    (added, removed)
+}
+
+/// What changed between two runs of a bxl function, see `BxlComputeResult::diff`.
+#[derive(Debug, Default)]
+pub struct BxlResultDiff {
+    pub added_artifacts: Vec<ArtifactGroup>,
+    pub removed_artifacts: Vec<ArtifactGroup>,
+    pub added_materializations: Vec<BuildArtifact>,
+    pub removed_materializations: Vec<BuildArtifact>,
+    /// Whether the output printed by the bxl function changed.
+    pub output_changed: bool,
+}
+
+impl BxlResultDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_artifacts.is_empty()
+            && self.removed_artifacts.is_empty()
+            && self.added_materializations.is_empty()
+            && self.removed_materializations.is_empty()
+            && !self.output_changed
+    }
+}
+
+impl Display for BxlResultDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for x in &self.added_artifacts {
+            writeln!(f, "+ artifact {}", x)?;
+        }
+        for x in &self.removed_artifacts {
+            writeln!(f, "- artifact {}", x)?;
+        }
+        for x in &self.added_materializations {
+            writeln!(f, "+ materialized {}", x)?;
+        }
+        for x in &self.removed_materializations {
+            writeln!(f, "- materialized {}", x)?;
+        }
+        if self.output_changed {
+            writeln!(f, "~ output")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every bxl evaluation depends on this, so setting a new generation at the start of a command
+/// (for `--no-bxl-cache`) makes bxl functions evaluate again rather than being served from the
+/// DICE cache.
+#[derive(
+    Debug,
+    derive_more::Display,
+    Copy,
+    Clone,
+    Dupe,
+    Eq,
+    PartialEq,
+    Hash,
+    Allocative
+)]
+#[display(fmt = "{:?}", self)]
+pub struct BxlCacheGenerationKey;
+
+impl InjectedKey for BxlCacheGenerationKey {
+    type Value = u64;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+pub trait SetBxlCacheGeneration {
+    fn set_bxl_cache_generation(&mut self, generation: u64) -> anyhow::Result<()>;
+}
+
+impl SetBxlCacheGeneration for DiceTransactionUpdater {
+    fn set_bxl_cache_generation(&mut self, generation: u64) -> anyhow::Result<()> {
+        Ok(self.changed_to([(BxlCacheGenerationKey, generation)])?)
+    }
+}
+
+#[async_trait]
+pub trait GetBxlCacheGeneration {
+    async fn get_bxl_cache_generation(&self) -> anyhow::Result<u64>;
+}
+
+#[async_trait]
+impl GetBxlCacheGeneration for DiceComputations {
+    async fn get_bxl_cache_generation(&self) -> anyhow::Result<u64> {
+        Ok(self.compute(&BxlCacheGenerationKey).await?)
+    }
 }
 
 /// Dependency injection for BXL.
@@ -44,3 +204,88 @@ pub struct BxlComputeResult {
 /// This field is initialized at program start, so this crate can call BXL calculation.
 pub static BXL_CALCULATION_IMPL: LateBinding<&'static dyn BxlCalculationDyn> =
     LateBinding::new("BXL_CALCULATION_IMPL");
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use buck2_artifact::artifact::artifact_type::testing::BuildArtifactTestingExt;
+    use buck2_artifact::artifact::artifact_type::Artifact;
+    use buck2_artifact::artifact::build_artifact::BuildArtifact;
+    use buck2_artifact::deferred::id::DeferredId;
+    use buck2_core::base_deferred_key::BaseDeferredKey;
+    use buck2_core::configuration::data::ConfigurationData;
+    use buck2_core::fs::buck_out_path::BuckOutPath;
+    use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+    use buck2_core::target::label::TargetLabel;
+    use buck2_wrapper_common::invocation_id::TraceId;
+    use dashmap::DashMap;
+    use dupe::Dupe;
+
+    use crate::artifact_groups::ArtifactGroup;
+    use crate::bxl::calculation::BxlComputeResult;
+    use crate::bxl::result::BxlResult;
+    use crate::deferred::types::DeferredTable;
+
+    fn artifact(path: &str) -> BuildArtifact {
+        BuildArtifact::testing_new(
+            TargetLabel::testing_parse("cell//pkg:foo").configure(ConfigurationData::testing_new()),
+            ForwardRelativePathBuf::unchecked_new(path.to_owned()),
+            DeferredId::testing_new(0),
+        )
+    }
+
+    fn result(ensured: &[&str], materialized: &[&str]) -> BxlComputeResult {
+        let owner = BaseDeferredKey::TargetLabel(
+            TargetLabel::testing_parse("cell//pkg:foo").configure(ConfigurationData::testing_new()),
+        );
+        let loc = |x: &str| {
+            BuckOutPath::new(
+                owner.dupe(),
+                ForwardRelativePathBuf::unchecked_new(x.to_owned()),
+            )
+        };
+        let materializations = DashMap::new();
+        for x in materialized {
+            materializations.insert(artifact(x), ());
+        }
+        BxlComputeResult::new(
+            Arc::new(BxlResult::new(
+                loc("output"),
+                Vec::new(),
+                loc("error"),
+                ensured
+                    .iter()
+                    .map(|x| ArtifactGroup::Artifact(Artifact::from(artifact(x))))
+                    .collect(),
+                DeferredTable::new(Default::default()),
+            )),
+            Arc::new(materializations),
+            TraceId::new(),
+        )
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = result(&["a", "b"], &["a", "b"]);
+        let same = result(&["b", "a"], &["b", "a"]);
+        assert!(same.diff(&previous, b"x", b"x").is_empty());
+
+        let current = result(&["b", "c"], &["b", "c", "d"]);
+        let diff = current.diff(&previous, b"y", b"x");
+        assert_eq!(
+            diff.added_artifacts,
+            vec![ArtifactGroup::Artifact(Artifact::from(artifact("c")))]
+        );
+        assert_eq!(
+            diff.removed_artifacts,
+            vec![ArtifactGroup::Artifact(Artifact::from(artifact("a")))]
+        );
+        assert_eq!(
+            diff.added_materializations,
+            vec![artifact("c"), artifact("d")]
+        );
+        assert_eq!(diff.removed_materializations, vec![artifact("a")]);
+        assert!(diff.output_changed);
+    }
+}
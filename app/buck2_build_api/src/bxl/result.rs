@@ -17,17 +17,37 @@ use crate::bxl::build_result::BxlBuildResult;
 use crate::deferred::types::DeferredLookup;
 use crate::deferred::types::DeferredTable;
 
+/// How a piece of bxl output was printed.
+#[derive(Allocative, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BxlOutputKind {
+    /// Written by `ctx.output.print`.
+    Text,
+    /// Written by `ctx.output.print_json`.
+    Json,
+}
+
+/// A single print to the bxl output, so the output file can be split back into the values
+/// that were printed.
+#[derive(Allocative, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BxlOutputRecord {
+    pub kind: BxlOutputKind,
+    /// The number of bytes written, including the trailing newline.
+    pub len: usize,
+}
+
 /// The result of evaluating a bxl function
 #[derive(Allocative)]
 pub enum BxlResult {
     /// represents that the bxl function has no built results
     None {
         output_loc: BuckOutPath,
+        output_records: Vec<BxlOutputRecord>,
         error_loc: BuckOutPath,
     },
     /// a bxl that deals with builds
     BuildsArtifacts {
         output_loc: BuckOutPath,
+        output_records: Vec<BxlOutputRecord>,
         error_loc: BuckOutPath,
         built: Vec<BxlBuildResult>,
         artifacts: Vec<ArtifactGroup>,
@@ -38,6 +58,7 @@ pub enum BxlResult {
 impl BxlResult {
     pub fn new(
         output_loc: BuckOutPath,
+        output_records: Vec<BxlOutputRecord>,
         error_loc: BuckOutPath,
         ensured_artifacts: IndexSet<ArtifactGroup>,
         deferred: DeferredTable,
@@ -45,11 +66,13 @@ impl BxlResult {
         if ensured_artifacts.is_empty() {
             Self::None {
                 output_loc,
+                output_records,
                 error_loc,
             }
         } else {
             Self::BuildsArtifacts {
                 output_loc,
+                output_records,
                 error_loc,
                 built: vec![],
                 artifacts: ensured_artifacts.into_iter().collect(),
@@ -73,6 +96,22 @@ impl BxlResult {
         }
     }
 
+    /// The prints that make up the output file, in order.
+    pub fn get_output_records(&self) -> &[BxlOutputRecord] {
+        match self {
+            BxlResult::None { output_records, .. } => output_records,
+            BxlResult::BuildsArtifacts { output_records, .. } => output_records,
+        }
+    }
+
+    /// The artifacts the bxl function asked to be built.
+    pub fn ensured_artifacts(&self) -> &[ArtifactGroup] {
+        match self {
+            BxlResult::None { .. } => &[],
+            BxlResult::BuildsArtifacts { artifacts, .. } => artifacts,
+        }
+    }
+
     pub fn get_error_loc(&self) -> &BuckOutPath {
         match self {
             BxlResult::None { error_loc, .. } => error_loc,
@@ -71,6 +71,13 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
     /// * `remote_execution_max_input_files_mebibytes`: The maximum input file size (in bytes) that remote execution can support
     /// * `remote_execution_queue_time_threshold_s`: The maximum time in seconds we are willing to wait
     /// in the RE queue for remote execution to start running our action
+    /// * `remote_execution_local_fallback_after_failures`: With `--remote-only-with-local-fallback`,
+    /// the number of consecutive remote execution errors after which actions may run locally
+    /// * `remote_execution_max_attempts`: The number of times to attempt scheduling an action
+    /// on RE when RE is unavailable. Actions which ran and failed are not retried
+    /// * `remote_execution_retry_backoff_ms`: The delay before the first retry, doubled for every
+    /// subsequent one
+    /// * `remote_execution_retry_jitter`: Whether to randomize retry delays
     /// * `remote_execution_use_case`: The use case to use when communicating with RE
     /// * `use_limited_hybrid`: Whether to use the limited hybrid executor
     /// * `allow_limited_hybrid_fallbacks`: Whether to allow fallbacks
@@ -79,6 +86,8 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
     /// * `use_persistent workers`: Whether to use persistent workers for local execution if they are available
     /// * `allow_cache_uploads`: Whether to upload local actions to the RE cache
     /// * `max_cache_upload_mebibytes`: Maximum size to upload in cache uploads
+    /// * `dry_run_cache_uploads`: Without `allow_cache_uploads`, report the cache uploads that
+    /// would have happened (and their size) in the event log instead of uploading anything
     /// * `experimental_low_pass_filter`: Whether to use the experimental low pass filter
     /// * `remote_output_paths`: How to express output paths to RE
     #[starlark(as_type = StarlarkCommandExecutorConfig)]
@@ -93,6 +102,14 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
         remote_execution_max_input_files_mebibytes: NoneOr<i32>,
         #[starlark(default = NoneOr::None, require = named)]
         remote_execution_queue_time_threshold_s: NoneOr<i32>,
+        #[starlark(default = NoneOr::None, require = named)]
+        remote_execution_local_fallback_after_failures: NoneOr<i32>,
+        #[starlark(default = NoneOr::None, require = named)] remote_execution_max_attempts: NoneOr<
+            i32,
+        >,
+        #[starlark(default = NoneOr::None, require = named)]
+        remote_execution_retry_backoff_ms: NoneOr<i32>,
+        #[starlark(default = false, require = named)] remote_execution_retry_jitter: bool,
         #[starlark(default = NoneType, require = named)] remote_execution_use_case: Value<'v>,
         #[starlark(default = false, require = named)] use_limited_hybrid: bool,
         #[starlark(default = false, require = named)] allow_limited_hybrid_fallbacks: bool,
@@ -103,6 +120,7 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
         #[starlark(default = NoneOr::None, require = named)] max_cache_upload_mebibytes: NoneOr<
             i32,
         >,
+        #[starlark(default = false, require = named)] dry_run_cache_uploads: bool,
         #[starlark(default = false, require = named)] experimental_low_pass_filter: bool,
         #[starlark(default = NoneOr::None, require = named)] remote_output_paths: NoneOr<&str>,
     ) -> anyhow::Result<StarlarkCommandExecutorConfig> {
@@ -172,9 +190,34 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
                     .context("remote_execution_queue_time_threshold_s is negative")?
                     .map(|t| t * 1000);
 
+                let local_fallback_after_failures = remote_execution_local_fallback_after_failures
+                    .into_option()
+                    .map(u32::try_from)
+                    .transpose()
+                    .context("remote_execution_local_fallback_after_failures is negative")?;
+
+                let re_max_attempts = remote_execution_max_attempts
+                    .into_option()
+                    .map(u32::try_from)
+                    .transpose()
+                    .context("remote_execution_max_attempts is negative")?;
+
+                let re_retry_base_backoff_ms = remote_execution_retry_backoff_ms
+                    .into_option()
+                    .map(u64::try_from)
+                    .transpose()
+                    .context("remote_execution_retry_backoff_ms is negative")?;
+
                 Some(RemoteExecutorOptions {
                     re_max_input_files_bytes,
                     re_max_queue_time_ms,
+                    local_fallback_after_failures,
+                    local_fallback_options: LocalExecutorOptions {
+                        use_persistent_workers,
+                    },
+                    re_max_attempts,
+                    re_retry_base_backoff_ms,
+                    re_retry_jitter: remote_execution_retry_jitter,
                 })
             } else {
                 None
@@ -203,6 +246,10 @@ pub fn register_command_executor_config(builder: &mut GlobalsBuilder) {
                 CacheUploadBehavior::Enabled {
                     max_bytes: max_cache_upload_bytes,
                 }
+            } else if dry_run_cache_uploads {
+                CacheUploadBehavior::DryRun {
+                    max_bytes: max_cache_upload_bytes,
+                }
             } else {
                 CacheUploadBehavior::Disabled
             };
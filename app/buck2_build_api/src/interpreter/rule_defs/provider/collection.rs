@@ -379,6 +379,56 @@ impl FrozenProviderCollection {
     pub fn provider_ids(&self) -> Vec<&ProviderId> {
         self.providers.keys().map(|k| &**k).collect()
     }
+
+    /// A shallow summary of every provider in the collection, for `buck2 build --show-providers`.
+    pub fn summaries(&self) -> Vec<ProviderSummary> {
+        self.providers
+            .iter()
+            .map(|(id, provider)| ProviderSummary {
+                name: id.name.clone(),
+                fields: provider
+                    .to_value()
+                    .as_provider()
+                    .map(|p| {
+                        p.items()
+                            .into_iter()
+                            .map(|(name, value)| (name.to_owned(), summarize_value(value)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+/// The name of a provider, and a short description of each of its fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderSummary {
+    pub name: String,
+    pub fields: Vec<(String, String)>,
+}
+
+/// Describe a provider field without dumping it: containers are summarized by their size, and
+/// anything else by its repr, truncated if it is long.
+fn summarize_value(value: Value) -> String {
+    const MAX_REPR_LEN: usize = 80;
+
+    let ty = value.get_type();
+    if ty != "string" {
+        if let Ok(len) = value.length() {
+            return format!(
+                "<{} with {} item{}>",
+                ty,
+                len,
+                if len == 1 { "" } else { "s" }
+            );
+        }
+    }
+    let repr = value.to_repr();
+    match repr.char_indices().nth(MAX_REPR_LEN) {
+        Some((end, _)) => format!("{}...", &repr[..end]),
+        None => repr,
+    }
 }
 
 /// Thin wrapper around `FrozenValue` that can only be constructed if that value is a `FrozenProviderCollection`
@@ -547,3 +597,25 @@ pub(crate) fn register_provider_collection(globals: &mut GlobalsBuilder) {
     const ProviderCollection: StarlarkValueAsType<ProviderCollectionGen<Value>> =
         StarlarkValueAsType::new();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_value() {
+        let heap = Heap::new();
+        assert_eq!(summarize_value(heap.alloc(1)), "1");
+        assert_eq!(summarize_value(heap.alloc("foo")), "\"foo\"");
+        assert_eq!(summarize_value(Value::new_none()), "None");
+        assert_eq!(
+            summarize_value(heap.alloc(vec!["a"; 10_000])),
+            "<list with 10000 items>"
+        );
+        assert_eq!(summarize_value(heap.alloc(vec![1])), "<list with 1 item>");
+
+        let long = "x".repeat(1000);
+        let summary = summarize_value(heap.alloc(long.as_str()));
+        assert_eq!(summary, format!("\"{}...", "x".repeat(79)));
+    }
+}
@@ -184,6 +184,7 @@ async fn make_default_dice_state(
             &self,
             artifact_fs: &ArtifactFs,
             _config: &CommandExecutorConfig,
+            _category: Option<&Category>,
         ) -> anyhow::Result<CommandExecutorResponse> {
             let executor = Arc::new(DryRunExecutor::new(
                 self.dry_run_tracker.dupe(),
@@ -12,7 +12,9 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use buck2_build_api::bxl::calculation::BxlCalculationDyn;
 use buck2_build_api::bxl::calculation::BxlComputeResult;
+use buck2_build_api::bxl::calculation::GetBxlCacheGeneration;
 use buck2_build_api::bxl::calculation::BXL_CALCULATION_IMPL;
+use buck2_common::events::HasEvents;
 use buck2_core::base_deferred_key::BaseDeferredKeyDyn;
 use buck2_interpreter::dice::starlark_profiler::GetStarlarkProfilerInstrumentation;
 use dice::DiceComputations;
@@ -62,7 +64,15 @@ impl Key for internal::BxlComputeKey {
     ) -> Self::Value {
         let key = self.0.dupe();
 
+        // Only for the dependency, so that `--no-bxl-cache` evaluates this again.
+        ctx.get_bxl_cache_generation().await?;
+
         let profiler = ctx.get_profile_mode_for_intermediate_analysis().await?;
+        let computed_by = ctx
+            .per_transaction_data()
+            .get_dispatcher()
+            .trace_id()
+            .dupe();
 
         cancellation
             .with_structured_cancellation(|observer| {
@@ -70,9 +80,8 @@ impl Key for internal::BxlComputeKey {
                     eval(ctx, key, profiler, observer)
                         .await
                         .map_err(buck2_error::Error::from)
-                        .map(|(result, _, materializations)| BxlComputeResult {
-                            bxl_result: Arc::new(result),
-                            materializations,
+                        .map(|(result, _, materializations)| {
+                            BxlComputeResult::new(Arc::new(result), materializations, computed_by)
                         })
                 }
                 .boxed()
@@ -100,3 +109,150 @@ mod internal {
 pub(crate) mod testing {
     pub(crate) use crate::bxl::calculation::internal::BxlComputeKey;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use buck2_build_api::bxl::calculation::SetBxlCacheGeneration;
+    use buck2_build_api::bxl::types::BxlFunctionLabel;
+    use buck2_build_api::context::SetBuildContextData;
+    use buck2_common::dice::data::testing::SetTestingIoProvider;
+    use buck2_common::dice::file_ops::FileChangeTracker;
+    use buck2_common::legacy_configs::LegacyBuckConfig;
+    use buck2_common::legacy_configs::LegacyBuckConfigs;
+    use buck2_core::cells::cell_path::CellPath;
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::name::CellName;
+    use buck2_core::cells::paths::CellRelativePathBuf;
+    use buck2_core::cells::CellResolver;
+    use buck2_core::fs::project::ProjectRootTemp;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+    use buck2_events::dispatch::EventDispatcher;
+    use buck2_execute::digest_config::DigestConfig;
+    use buck2_execute::digest_config::SetDigestConfig;
+    use buck2_interpreter::bxl::BXL_SPECIFIC_GLOBALS;
+    use buck2_interpreter::dice::starlark_debug::SetStarlarkDebugger;
+    use buck2_interpreter::extra::InterpreterHostArchitecture;
+    use buck2_interpreter::extra::InterpreterHostPlatform;
+    use buck2_interpreter::paths::bxl::BxlFilePath;
+    use buck2_interpreter_for_build::interpreter::configuror::BuildInterpreterConfiguror;
+    use buck2_interpreter_for_build::interpreter::interpreter_setup::setup_interpreter_basic;
+    use buck2_wrapper_common::invocation_id::TraceId;
+    use dice::DetectCycles;
+    use dice::Dice;
+    use dice::DiceTransaction;
+    use dice::UserComputationData;
+    use dupe::Dupe;
+    use starlark_map::ordered_map::OrderedMap;
+
+    use crate::bxl::calculation::eval_bxl;
+    use crate::bxl::key::BxlKey;
+
+    fn root_cell() -> CellName {
+        CellName::testing_new("root")
+    }
+
+    async fn new_dice(fs: &ProjectRootTemp) -> anyhow::Result<Arc<Dice>> {
+        let mut builder = Dice::builder();
+        builder.set_testing_io_provider(fs);
+        builder.set_digest_config(DigestConfig::testing_default());
+        let dice = builder.build(DetectCycles::Enabled);
+
+        let cell_resolver = CellResolver::testing_with_name_and_path(
+            root_cell(),
+            CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new(String::new())),
+        );
+        let legacy_configs =
+            LegacyBuckConfigs::new(HashMap::from([(root_cell(), LegacyBuckConfig::empty())]));
+        let configuror = BuildInterpreterConfiguror::new(
+            None,
+            InterpreterHostPlatform::Linux,
+            InterpreterHostArchitecture::X86_64,
+            None,
+            false,
+            false,
+            |_| {},
+            |_| {},
+            |_| {},
+            |globals| (BXL_SPECIFIC_GLOBALS.get().unwrap())(globals),
+            None,
+        )?;
+
+        let mut updater = dice.updater();
+        setup_interpreter_basic(&mut updater, cell_resolver, configuror, legacy_configs)?;
+        updater.set_buck_out_path(Some(ProjectRelativePathBuf::unchecked_new(
+            "buck-out/v2".to_owned(),
+        )))?;
+        updater.commit().await;
+        Ok(dice)
+    }
+
+    async fn command(
+        dice: &Arc<Dice>,
+        trace_id: &TraceId,
+        generation: u64,
+        changes: FileChangeTracker,
+    ) -> anyhow::Result<DiceTransaction> {
+        let mut data = UserComputationData::new();
+        data.data
+            .set(EventDispatcher::null_sink_with_trace(trace_id.dupe()));
+        data.set_starlark_debugger_handle(None);
+        let mut updater = dice.updater_with_data(data);
+        changes.write_to_dice(&mut updater)?;
+        updater.set_bxl_cache_generation(generation)?;
+        Ok(updater.commit().await)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cache_hits_are_results_computed_by_another_command() -> anyhow::Result<()> {
+        let fs = ProjectRootTemp::new()?;
+        fs.write_file("defs.bzl", "message = \"hello\"\n");
+        fs.write_file(
+            "test.bxl",
+            "load(\"//defs.bzl\", \"message\")\n\
+             def _impl(ctx):\n    ctx.output.print(message)\n\
+             main = bxl_main(impl = _impl, cli_args = {})\n",
+        );
+        let key = BxlKey::new(
+            BxlFunctionLabel {
+                bxl_path: BxlFilePath::testing_new("root", "test.bxl"),
+                name: "main".to_owned(),
+            },
+            Arc::new(OrderedMap::new()),
+            None,
+        );
+        let dice = new_dice(&fs).await?;
+
+        let first = TraceId::new();
+        let ctx = command(&dice, &first, 0, FileChangeTracker::new()).await?;
+        assert!(!eval_bxl(&ctx, key.dupe()).await?.is_cache_hit(&first));
+        // Asking again within the same command is still the result it evaluated.
+        assert!(!eval_bxl(&ctx, key.dupe()).await?.is_cache_hit(&first));
+        drop(ctx);
+
+        let second = TraceId::new();
+        let ctx = command(&dice, &second, 0, FileChangeTracker::new()).await?;
+        assert!(eval_bxl(&ctx, key.dupe()).await?.is_cache_hit(&second));
+        drop(ctx);
+
+        // Changing a `.bzl` file loaded by the bxl file evaluates it again.
+        fs.write_file("defs.bzl", "message = \"goodbye\"\n");
+        let mut changes = FileChangeTracker::new();
+        changes.file_changed(CellPath::new(
+            root_cell(),
+            CellRelativePathBuf::unchecked_new("defs.bzl".to_owned()),
+        ));
+        let third = TraceId::new();
+        let ctx = command(&dice, &third, 0, changes).await?;
+        assert!(!eval_bxl(&ctx, key.dupe()).await?.is_cache_hit(&third));
+        drop(ctx);
+
+        // What `--no-bxl-cache` does.
+        let fourth = TraceId::new();
+        let ctx = command(&dice, &fourth, 1, FileChangeTracker::new()).await?;
+        assert!(!eval_bxl(&ctx, key.dupe()).await?.is_cache_hit(&fourth));
+        Ok(())
+    }
+}
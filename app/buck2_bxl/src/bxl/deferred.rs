@@ -36,6 +36,7 @@ mod tests {
     use buck2_execute::digest_config::SetDigestConfig;
     use buck2_execute::execute::dice_data::set_fallback_executor_config;
     use buck2_interpreter::paths::bxl::BxlFilePath;
+    use buck2_wrapper_common::invocation_id::TraceId;
     use dice::testing::DiceBuilder;
     use dice::DiceComputations;
     use dice::UserComputationData;
@@ -105,16 +106,18 @@ mod tests {
             })
             .mock_and_return(
                 BxlComputeKey(bxl.dupe()),
-                anyhow::Ok(BxlComputeResult {
-                    bxl_result: Arc::new(BxlResult::BuildsArtifacts {
+                anyhow::Ok(BxlComputeResult::new(
+                    Arc::new(BxlResult::BuildsArtifacts {
                         output_loc: mk_stream_cache("test", &bxl),
+                        output_records: Vec::new(),
                         error_loc: mk_stream_cache("errortest", &bxl),
                         built: vec![],
                         artifacts: vec![],
                         deferred: deferred_result,
                     }),
-                    materializations: Arc::new(Default::default()),
-                })
+                    Arc::new(Default::default()),
+                    TraceId::new(),
+                ))
                 .map_err(buck2_error::Error::from),
             );
 
@@ -227,7 +227,7 @@ pub(crate) async fn eval(
                                 )));
                             }
 
-                            let (actions, ensured_artifacts, materializations) =
+                            let (actions, ensured_artifacts, output_records, materializations) =
                                 BxlContext::take_state(bxl_ctx)?;
                             std::mem::drop(eval);
 
@@ -256,6 +256,7 @@ pub(crate) async fn eval(
                                         frozen_module,
                                         BxlResult::new(
                                             output_stream,
+                                            output_records,
                                             error_stream,
                                             ensured_artifacts,
                                             deferred_table,
@@ -270,6 +271,7 @@ pub(crate) async fn eval(
                                         frozen_module,
                                         BxlResult::new(
                                             output_stream,
+                                            output_records,
                                             error_stream,
                                             ensured_artifacts,
                                             DeferredTable::new(Vec::new()),
@@ -195,6 +195,29 @@ impl FrozenBxlFunction {
         clap
     }
 
+    /// Converts the json object passed via `--args-json` into command line arguments, so that they
+    /// are coerced to the declared cli args exactly like arguments passed after `--`.
+    pub(crate) fn json_to_cli_args(&self, json: &str) -> anyhow::Result<Vec<String>> {
+        let json: serde_json::Value =
+            serde_json::from_str(json).context("Error parsing `--args-json`")?;
+        let object = json
+            .as_object()
+            .ok_or_else(|| CliArgError::NotAJsonObject(json.to_string()))?;
+
+        let mut args = Vec::new();
+        for (name, value) in object {
+            // Accept both the declared name and its snake case form, which is how the bxl
+            // function sees it.
+            let (_, flag, def) = self
+                .cli_args
+                .get_full(name.as_str())
+                .or_else(|| self.cli_args.get_full(name.replace('_', "-").as_str()))
+                .ok_or_else(|| CliArgError::UnknownJsonArg(name.clone()))?;
+            args.extend(def.json_to_cli_args(flag, value)?);
+        }
+        Ok(args)
+    }
+
     /// Parses the cli args as defined by this bxl function. Automatically changes the CLI args
     /// to snakecase when accessed from the bxl context.
     pub(crate) async fn parse_clap<'a>(
@@ -130,6 +130,16 @@ impl CliArgs {
         arg
     }
 
+    /// Converts a value from `--args-json` into command line arguments for the flag `name`, so
+    /// that it goes through the same validation as arguments passed on the command line.
+    pub(crate) fn json_to_cli_args(
+        &self,
+        name: &str,
+        value: &serde_json::Value,
+    ) -> anyhow::Result<Vec<String>> {
+        self.coercer.json_to_cli_args(name, value)
+    }
+
     pub(crate) async fn parse_clap<'a>(
         &self,
         clap: ArgAccessor<'a>,
@@ -379,6 +389,10 @@ pub(crate) enum CliArgError {
     DefinedBothKebabAndSnakeCase(String),
     #[error("Expecting json object. Got: `{0}`")]
     NotAJsonObject(String),
+    #[error("Expected value of type `{1}` for cli arg `{0}` in `--args-json`, but got `{2}`")]
+    JsonValueTypeError(String, CliArgType, String),
+    #[error("`--args-json` contains `{0}`, which is not a cli arg of this bxl function")]
+    UnknownJsonArg(String),
 }
 
 impl CliArgType {
@@ -472,6 +486,56 @@ impl CliArgType {
         })
     }
 
+    fn json_to_cli_args(
+        &self,
+        name: &str,
+        value: &serde_json::Value,
+    ) -> anyhow::Result<Vec<String>> {
+        let type_error =
+            || CliArgError::JsonValueTypeError(name.to_owned(), self.dupe(), value.to_string());
+        Ok(match self {
+            CliArgType::Option(inner) => {
+                if value.is_null() {
+                    Vec::new()
+                } else {
+                    inner.json_to_cli_args(name, value)?
+                }
+            }
+            CliArgType::List(inner) => {
+                let mut args = Vec::new();
+                for item in value.as_array().ok_or_else(type_error)? {
+                    args.extend(inner.json_to_cli_args(name, item)?);
+                }
+                args
+            }
+            _ => vec![format!(
+                "--{}={}",
+                name,
+                self.json_to_cli_value(value).ok_or_else(type_error)?
+            )],
+        })
+    }
+
+    /// Converts a json value into the string clap would get for a scalar of this type.
+    fn json_to_cli_value(&self, value: &serde_json::Value) -> Option<String> {
+        match self {
+            CliArgType::Bool => value.as_bool().map(|b| b.to_string()),
+            CliArgType::Int => match value {
+                serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => Some(n.to_string()),
+                _ => None,
+            },
+            CliArgType::Float => value.as_f64().map(|f| f.to_string()),
+            CliArgType::Json => Some(value.to_string()),
+            CliArgType::String
+            | CliArgType::Enumeration(_)
+            | CliArgType::TargetLabel
+            | CliArgType::TargetExpr
+            | CliArgType::SubTarget
+            | CliArgType::SubTargetExpr => value.as_str().map(ToOwned::to_owned),
+            CliArgType::List(_) | CliArgType::Option(_) => None,
+        }
+    }
+
     #[allow(deprecated)] // TODO(nga): fix.
     pub(crate) fn to_clap<'a>(&'a self, clap: clap::Arg<'a>) -> clap::Arg<'a> {
         match self {
@@ -775,6 +839,7 @@ mod tests {
     use buck2_interpreter::types::configured_providers_label::StarlarkProvidersLabel;
     use buck2_interpreter::types::target_label::StarlarkTargetLabel;
     use num_bigint::BigInt;
+    use serde_json::json;
     use starlark::values::Heap;
     use starlark::values::Value;
     use starlark_map::ordered_map::OrderedMap;
@@ -908,6 +973,98 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn json_to_cli_args() -> anyhow::Result<()> {
+        let args = |t: CliArgType, value: serde_json::Value| t.json_to_cli_args("foo", &value);
+
+        assert_eq!(
+            args(CliArgType::bool(), json!(true))?,
+            vec!["--foo=true".to_owned()]
+        );
+        assert_eq!(
+            args(CliArgType::bool(), json!(false))?,
+            vec!["--foo=false".to_owned()]
+        );
+        assert_eq!(
+            args(CliArgType::int(), json!(-3))?,
+            vec!["--foo=-3".to_owned()]
+        );
+        assert_eq!(
+            args(CliArgType::float(), json!(4.5))?,
+            vec!["--foo=4.5".to_owned()]
+        );
+        assert_eq!(
+            args(CliArgType::string(), json!("a b"))?,
+            vec!["--foo=a b".to_owned()]
+        );
+
+        // Lists repeat the flag, including lists nested in lists or options.
+        assert_eq!(
+            args(CliArgType::list(CliArgType::int()), json!([1, 2]))?,
+            vec!["--foo=1".to_owned(), "--foo=2".to_owned()]
+        );
+        assert_eq!(
+            args(
+                CliArgType::list(CliArgType::list(CliArgType::string())),
+                json!([["a"], [], ["b", "c"]])
+            )?,
+            vec![
+                "--foo=a".to_owned(),
+                "--foo=b".to_owned(),
+                "--foo=c".to_owned()
+            ]
+        );
+        assert_eq!(
+            args(
+                CliArgType::option(CliArgType::list(CliArgType::bool())),
+                json!([true])
+            )?,
+            vec!["--foo=true".to_owned()]
+        );
+        assert_eq!(
+            args(CliArgType::list(CliArgType::int()), json!([]))?,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            args(CliArgType::option(CliArgType::int()), json!(null))?,
+            Vec::<String>::new()
+        );
+
+        // Json args take nested values as they are.
+        assert_eq!(
+            args(CliArgType::json(), json!({"a": [1, {"b": null}]}))?,
+            vec![r#"--foo={"a":[1,{"b":null}]}"#.to_owned()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_to_cli_args_invalid() {
+        let args = |t: CliArgType, value: serde_json::Value| t.json_to_cli_args("foo", &value);
+
+        assert!(args(CliArgType::bool(), json!("true")).is_err());
+        assert!(args(CliArgType::bool(), json!(1)).is_err());
+        assert!(args(CliArgType::int(), json!(1.5)).is_err());
+        assert!(args(CliArgType::int(), json!("1")).is_err());
+        assert!(args(CliArgType::float(), json!("1.5")).is_err());
+        assert!(args(CliArgType::string(), json!(1)).is_err());
+        assert!(args(CliArgType::string(), json!(["a"])).is_err());
+        assert!(args(CliArgType::string(), json!(null)).is_err());
+        assert!(args(CliArgType::list(CliArgType::int()), json!(1)).is_err());
+        assert!(args(CliArgType::list(CliArgType::int()), json!([1, "2"])).is_err());
+        assert!(args(CliArgType::list(CliArgType::int()), json!({"a": 1})).is_err());
+
+        let err = args(CliArgType::int(), json!("x")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                r#"Expected value of type `{}` for cli arg `foo` in `--args-json`, but got `"x"`"#,
+                CliArgType::int()
+            )
+        );
+    }
 }
 
 pub(crate) enum ArgAccessor<'a> {
@@ -25,6 +25,7 @@ use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
 use buck2_build_api::analysis::registry::AnalysisRegistry;
 use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::bxl::result::BxlOutputRecord;
 use buck2_build_api::deferred::types::DeferredCtx;
 use buck2_build_api::dynamic::bxl::EVAL_BXL_FOR_DYNAMIC_OUTPUT;
 use buck2_build_api::dynamic::deferred::dynamic_lambda_ctx_data;
@@ -388,6 +389,7 @@ impl<'v> BxlContext<'v> {
     ) -> anyhow::Result<(
         Option<AnalysisRegistry<'v>>,
         IndexSet<ArtifactGroup>,
+        Vec<BxlOutputRecord>,
         Arc<DashMap<BuildArtifact, ()>>,
     )> {
         let this = value.as_ref();
@@ -419,6 +421,7 @@ impl<'v> BxlContext<'v> {
                 })
                 .flatten_ok()
                 .collect::<anyhow::Result<IndexSet<ArtifactGroup>>>()?,
+            output_stream.as_ref().take_records(),
             materializations.dupe(),
         ))
     }
@@ -10,13 +10,14 @@
 use std::cell::RefCell;
 use std::fmt::Display;
 use std::io::Write;
-use std::ops::DerefMut;
 use std::rc::Rc;
 
 use allocative::Allocative;
 use anyhow::Context;
 use buck2_build_api::artifact_groups::ArtifactGroup;
 use buck2_build_api::bxl::build_result::BxlBuildResult;
+use buck2_build_api::bxl::result::BxlOutputKind;
+use buck2_build_api::bxl::result::BxlOutputRecord;
 use buck2_build_api::interpreter::rule_defs::artifact::StarlarkArtifact;
 use buck2_build_api::interpreter::rule_defs::cmd_args::value_as::ValueAsCommandLineLike;
 use buck2_build_api::interpreter::rule_defs::cmd_args::CommandLineArgLike;
@@ -86,6 +87,10 @@ pub(crate) struct OutputStream<'v> {
     pub(crate) sink: RefCell<Box<dyn Write>>,
     #[trace(unsafe_ignore)]
     artifacts_to_ensure: RefCell<Option<SmallSet<EnsuredArtifactOrGroup>>>,
+    /// Each print to `sink`, in order.
+    #[trace(unsafe_ignore)]
+    #[derivative(Debug = "ignore")]
+    records: RefCell<Vec<BxlOutputRecord>>,
     #[derivative(Debug = "ignore")]
     pub(crate) project_fs: ProjectRoot,
     #[derivative(Debug = "ignore")]
@@ -115,6 +120,7 @@ impl<'v> OutputStream<'v> {
         Self {
             sink,
             artifacts_to_ensure: RefCell::new(Some(Default::default())),
+            records: RefCell::new(Vec::new()),
             project_fs,
             artifact_fs,
             async_ctx,
@@ -124,6 +130,20 @@ impl<'v> OutputStream<'v> {
     pub(crate) fn take_artifacts(&self) -> SmallSet<EnsuredArtifactOrGroup> {
         self.artifacts_to_ensure.borrow_mut().take().unwrap()
     }
+
+    pub(crate) fn take_records(&self) -> Vec<BxlOutputRecord> {
+        self.records.take()
+    }
+
+    /// Write the output of a single print to the sink, remembering how it was printed.
+    fn write_record(&self, kind: BxlOutputKind, output: &[u8]) -> anyhow::Result<()> {
+        self.sink.borrow_mut().write_all(output)?;
+        self.records.borrow_mut().push(BxlOutputRecord {
+            kind,
+            len: output.len(),
+        });
+        Ok(())
+    }
 }
 
 #[starlark_value(type = "bxl_output_stream", StarlarkTypeRepr, UnpackValue)]
@@ -161,12 +181,13 @@ fn output_stream_methods(builder: &mut MethodsBuilder) {
         #[starlark(args)] args: UnpackTuple<Value<'v>>,
         #[starlark(default = " ")] sep: &'v str,
     ) -> anyhow::Result<NoneType> {
+        let mut output = Vec::new();
         let mut first = true;
         let mut write = |d: &dyn Display| -> anyhow::Result<()> {
             if !first {
-                write!(this.sink.borrow_mut(), "{}{}", sep, d)?;
+                write!(output, "{}{}", sep, d)?;
             } else {
-                write!(this.sink.borrow_mut(), "{}", d)?;
+                write!(output, "{}", d)?;
                 first = false;
             }
             Ok(())
@@ -203,7 +224,8 @@ fn output_stream_methods(builder: &mut MethodsBuilder) {
             }
         }
 
-        writeln!(this.sink.borrow_mut())?;
+        writeln!(output)?;
+        this.write_record(BxlOutputKind::Text, &output)?;
 
         Ok(NoneType)
     }
@@ -311,8 +333,9 @@ fn output_stream_methods(builder: &mut MethodsBuilder) {
         } else {
             serde_json::to_writer
         };
+        let mut output = Vec::new();
         writer(
-            this.sink.borrow_mut().deref_mut(),
+            &mut output,
             &SerializeValue {
                 value,
                 artifact_fs: &this.artifact_fs,
@@ -321,7 +344,8 @@ fn output_stream_methods(builder: &mut MethodsBuilder) {
             },
         )
         .context("Error writing to JSON for `write_json`")?;
-        writeln!(this.sink.borrow_mut())?;
+        writeln!(output)?;
+        this.write_record(BxlOutputKind::Json, &output)?;
 
         Ok(NoneType)
     }
@@ -7,12 +7,16 @@
  * of this source tree.
  */
 
+use std::collections::HashMap;
 use std::io;
 use std::io::Write;
+use std::path::Path;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use anyhow::Context;
 use async_trait::async_trait;
+use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
 use buck2_build_api::artifact_groups::ArtifactGroup;
 use buck2_build_api::build::materialize_artifact_group;
@@ -21,6 +25,8 @@ use buck2_build_api::build::ConvertMaterializationContext;
 use buck2_build_api::build::MaterializationContext;
 use buck2_build_api::bxl::build_result::BxlBuildResult;
 use buck2_build_api::bxl::calculation::BxlComputeResult;
+use buck2_build_api::bxl::result::BxlOutputKind;
+use buck2_build_api::bxl::result::BxlOutputRecord;
 use buck2_build_api::bxl::types::BxlFunctionLabel;
 use buck2_cli_proto::build_request::Materializations;
 use buck2_cli_proto::BxlRequest;
@@ -28,11 +34,13 @@ use buck2_cli_proto::BxlResponse;
 use buck2_cli_proto::HasClientContext;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::data::HasIoProvider;
+use buck2_common::events::HasEvents;
 use buck2_common::target_aliases::HasTargetAliasResolver;
 use buck2_core::cells::cell_path::CellPath;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::buck_out_path::BuckOutPath;
 use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_path::AbsPath;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::package::PackageLabel;
 use buck2_core::soft_error;
@@ -53,11 +61,14 @@ use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::pattern::target_platform_from_client_context;
 use buck2_server_ctx::template::run_server_command;
 use buck2_server_ctx::template::ServerCommandTemplate;
+use dashmap::DashMap;
 use dice::DiceComputations;
 use dice::DiceTransaction;
 use dupe::Dupe;
 use futures::FutureExt;
 use itertools::Itertools;
+use once_cell::sync::Lazy;
+use serde::Serialize;
 use starlark::errors::Diagnostic;
 
 use crate::bxl::calculation::eval_bxl;
@@ -139,17 +150,26 @@ async fn bxl(
     let global_target_platform =
         target_platform_from_client_context(client_ctx, server_ctx, &mut ctx).await?;
 
-    let bxl_args =
-        match get_bxl_cli_args(cwd, &ctx, &bxl_label, &request.bxl_args, &cell_resolver).await? {
-            BxlResolvedCliArgs::Resolved(bxl_args) => Arc::new(bxl_args),
-            // Return early if user passed in `--help`
-            BxlResolvedCliArgs::Help => {
-                return Ok(BxlResponse {
-                    project_root,
-                    errors: Vec::new(),
-                });
-            }
-        };
+    let bxl_args = match get_bxl_cli_args(
+        cwd,
+        &ctx,
+        &bxl_label,
+        &request.bxl_args,
+        request.bxl_args_json.as_deref(),
+        &cell_resolver,
+    )
+    .await?
+    {
+        BxlResolvedCliArgs::Resolved(bxl_args) => Arc::new(bxl_args),
+        // Return early if user passed in `--help`
+        BxlResolvedCliArgs::Help => {
+            return Ok(BxlResponse {
+                project_root,
+                errors: Vec::new(),
+                cache_hit: false,
+            });
+        }
+    };
 
     let final_artifact_materializations =
         Materializations::from_i32(request.final_artifact_materializations)
@@ -160,10 +180,7 @@ async fn bxl(
 
     let ctx = &ctx;
 
-    let BxlComputeResult {
-        bxl_result,
-        materializations,
-    } = match eval_bxl(ctx, bxl_key.clone()).await {
+    let result = match eval_bxl(ctx, bxl_key.clone()).await {
         Ok(result) => result,
         Err(e) => {
             // `buck2_error::Error` has more reliable downcasting
@@ -187,6 +204,15 @@ async fn bxl(
             return Err(e.into());
         }
     };
+    let cache_hit = result.is_cache_hit(ctx.per_transaction_data().get_dispatcher().trace_id());
+    if request.diff_previous {
+        report_diff_from_previous(ctx, &bxl_key, &result).await?;
+    }
+    let BxlComputeResult {
+        bxl_result,
+        materializations,
+        ..
+    } = result;
 
     let materialization_context = ConvertMaterializationContext::with_existing_map(
         final_artifact_materializations,
@@ -198,7 +224,32 @@ async fn bxl(
     );
 
     let build_result = ensure_artifacts(ctx, &materialization_context, &bxl_result).await;
-    copy_output(stdout, ctx, bxl_result.get_output_loc()).await?;
+
+    if let Some(report_path) = &request.materializations_report {
+        let materialized = match &materialization_context {
+            MaterializationContext::Materialize { map, .. } => map.dupe(),
+            MaterializationContext::Skip => materializations.dupe(),
+        };
+        write_materializations_report(ctx, report_path, &materialized)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to write materializations report to `{}`",
+                    report_path
+                )
+            })?;
+    }
+    if request.print_bxl_output_json {
+        print_output_json(
+            stdout,
+            ctx,
+            bxl_result.get_output_loc(),
+            bxl_result.get_output_records(),
+        )
+        .await?;
+    } else {
+        copy_output(stdout, ctx, bxl_result.get_output_loc()).await?;
+    }
     copy_output(server_ctx.stderr()?, ctx, bxl_result.get_error_loc()).await?;
 
     let errors = match build_result {
@@ -212,14 +263,58 @@ async fn bxl(
     Ok(BxlResponse {
         project_root,
         errors,
+        cache_hit,
     })
 }
 
+/// The result and output of the last run of each bxl function with `--diff-previous`, to compare
+/// the next run against.
+static PREVIOUS_RESULTS: Lazy<Mutex<HashMap<BxlKey, (BxlComputeResult, Vec<u8>)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+async fn report_diff_from_previous(
+    ctx: &DiceComputations,
+    bxl_key: &BxlKey,
+    result: &BxlComputeResult,
+) -> anyhow::Result<()> {
+    let mut output = Vec::new();
+    io::Read::read_to_end(
+        &mut open_output(ctx, result.bxl_result.get_output_loc()).await?,
+        &mut output,
+    )?;
+
+    let previous = PREVIOUS_RESULTS
+        .lock()
+        .unwrap()
+        .insert(bxl_key.dupe(), (result.dupe(), output.clone()));
+    let message = match previous {
+        None => format!(
+            "No previous run of `{}` to compare against",
+            bxl_key.label()
+        ),
+        Some((previous, previous_output)) => {
+            let diff = result.diff(&previous, &output, &previous_output);
+            if diff.is_empty() {
+                format!("No changes since the previous run of `{}`", bxl_key.label())
+            } else {
+                format!(
+                    "Changes since the previous run of `{}`:\n{}",
+                    bxl_key.label(),
+                    diff.to_string().trim_end()
+                )
+            }
+        }
+    };
+    get_dispatcher().console_message(message);
+    Ok(())
+}
+
 pub(crate) async fn get_bxl_cli_args(
     cwd: &ProjectRelativePath,
     ctx: &DiceTransaction,
     bxl_label: &BxlFunctionLabel,
     bxl_args: &Vec<String>,
+    bxl_args_json: Option<&str>,
     cell_resolver: &CellResolver,
 ) -> anyhow::Result<BxlResolvedCliArgs> {
     let cur_package = PackageLabel::from_cell_path(cell_resolver.get_cell_path(&cwd)?.as_ref());
@@ -239,7 +334,41 @@ pub(crate) async fn get_bxl_cli_args(
         dice: ctx,
     };
 
-    resolve_cli_args(bxl_label, &cli_ctx, bxl_args, &frozen_callable).await
+    let mut bxl_args = bxl_args.clone();
+    if let Some(bxl_args_json) = bxl_args_json {
+        bxl_args.extend(frozen_callable.json_to_cli_args(bxl_args_json)?);
+    }
+
+    resolve_cli_args(bxl_label, &cli_ctx, &bxl_args, &frozen_callable).await
+}
+
+#[derive(Serialize)]
+struct MaterializedArtifact {
+    path: String,
+    owner: String,
+}
+
+/// Writes the artifacts that were materialized for this bxl as a JSON list, sorted by path.
+async fn write_materializations_report(
+    ctx: &DiceComputations,
+    report_path: &str,
+    materialized: &DashMap<BuildArtifact, ()>,
+) -> anyhow::Result<()> {
+    let artifact_fs = ctx.get_artifact_fs().await?;
+    let mut report: Vec<MaterializedArtifact> = materialized
+        .iter()
+        .map(|entry| {
+            let artifact = entry.key();
+            MaterializedArtifact {
+                path: artifact_fs.resolve_build(artifact.get_path()).to_string(),
+                owner: artifact.key().owner().to_string(),
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let report_path = AbsPath::new(Path::new(report_path))?;
+    fs_util::write(report_path, serde_json::to_vec_pretty(&report)?)
 }
 
 async fn copy_output<W: Write>(
@@ -247,6 +376,74 @@ async fn copy_output<W: Write>(
     dice: &DiceComputations,
     output_loc: &BuckOutPath,
 ) -> anyhow::Result<()> {
+    let mut file = open_output(dice, output_loc).await?;
+    io::copy(&mut file, &mut output)?;
+    Ok(())
+}
+
+/// One record of bxl output in `--print-bxl-output json` mode. Values printed with
+/// `ctx.output.print_json` are kept as JSON, values printed with `ctx.output.print` as text.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+enum OutputRecord<'a> {
+    Json(serde_json::Value),
+    Text(&'a str),
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    outputs: Vec<OutputRecord<'a>>,
+}
+
+/// Splits bxl output back into the values that were printed, as recorded when printing them.
+fn split_output<'a>(
+    output: &'a [u8],
+    records: &[BxlOutputRecord],
+) -> anyhow::Result<Vec<OutputRecord<'a>>> {
+    let mut rest = output;
+    let mut outputs = Vec::with_capacity(records.len());
+    for record in records {
+        if rest.len() < record.len {
+            return Err(anyhow::anyhow!(
+                "bxl output is shorter than the values printed to it"
+            ));
+        }
+        let (printed, tail) = rest.split_at(record.len);
+        rest = tail;
+        outputs.push(match record.kind {
+            BxlOutputKind::Json => OutputRecord::Json(serde_json::from_slice(printed)?),
+            BxlOutputKind::Text => {
+                let printed = std::str::from_utf8(printed)?;
+                OutputRecord::Text(printed.strip_suffix('\n').unwrap_or(printed))
+            }
+        });
+    }
+    Ok(outputs)
+}
+
+async fn print_output_json<W: Write>(
+    mut output: W,
+    dice: &DiceComputations,
+    output_loc: &BuckOutPath,
+    records: &[BxlOutputRecord],
+) -> anyhow::Result<()> {
+    let mut contents = Vec::new();
+    io::Read::read_to_end(&mut open_output(dice, output_loc).await?, &mut contents)?;
+    serde_json::to_writer_pretty(
+        &mut output,
+        &JsonOutput {
+            outputs: split_output(&contents, records)
+                .context("Error splitting bxl output into printed values")?,
+        },
+    )?;
+    writeln!(output)?;
+    Ok(())
+}
+
+async fn open_output(
+    dice: &DiceComputations,
+    output_loc: &BuckOutPath,
+) -> anyhow::Result<std::fs::File> {
     let loc = dice.global_data().get_io_provider().project_root().resolve(
         &dice
             .get_artifact_fs()
@@ -257,15 +454,14 @@ async fn copy_output<W: Write>(
 
     // we write the output to a file in buck-out as cache so we don't use memory caching it in
     // DICE. So now we open the file and read it all into the destination stream.
-    let mut file = tag_result!(
+    let file = tag_result!(
         "bxl_output_missing",
         fs_util::open_file(loc),
         quiet: true,
         daemon_in_memory_state_is_corrupted: true,
         task: false
     )?;
-    io::copy(&mut file, &mut output)?;
-    Ok(())
+    Ok(file)
 }
 
 async fn ensure_artifacts(
@@ -399,3 +595,54 @@ pub(crate) fn parse_bxl_label_from_cli(
         name: bxl_fn.to_owned(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_build_api::bxl::result::BxlOutputKind;
+    use buck2_build_api::bxl::result::BxlOutputRecord;
+    use serde_json::json;
+
+    use super::split_output;
+    use super::OutputRecord;
+
+    fn record(kind: BxlOutputKind, printed: &str) -> BxlOutputRecord {
+        BxlOutputRecord {
+            kind,
+            len: printed.len(),
+        }
+    }
+
+    #[test]
+    fn test_split_output() -> anyhow::Result<()> {
+        let printed = [
+            (BxlOutputKind::Text, "plain text\n"),
+            (BxlOutputKind::Json, "{\n  \"a\": [1, 2]\n}\n"),
+            // Text that happens to be valid JSON stays text.
+            (BxlOutputKind::Text, "42\n"),
+            (BxlOutputKind::Text, "two\nlines\n"),
+            (BxlOutputKind::Json, "\"str\"\n"),
+        ];
+        let output = printed.iter().map(|(_, p)| *p).collect::<String>();
+        let records = printed
+            .iter()
+            .map(|(kind, p)| record(*kind, p))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            split_output(output.as_bytes(), &records)?,
+            vec![
+                OutputRecord::Text("plain text"),
+                OutputRecord::Json(json!({"a": [1, 2]})),
+                OutputRecord::Text("42"),
+                OutputRecord::Text("two\nlines"),
+                OutputRecord::Json(json!("str")),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_output_truncated() {
+        let records = [record(BxlOutputKind::Text, "longer than the output\n")];
+        assert!(split_output(b"short\n", &records).is_err());
+    }
+}
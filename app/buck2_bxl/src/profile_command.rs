@@ -10,6 +10,7 @@
 use std::path::Path;
 use std::sync::Arc;
 
+use anyhow::Context;
 use async_trait::async_trait;
 use buck2_cli_proto::profile_request::ProfileOpts;
 use buck2_cli_proto::HasClientContext;
@@ -90,6 +91,7 @@ impl ServerCommandTemplate for BxlProfileServerCommand {
                             &ctx,
                             &bxl_label,
                             &opts.bxl_args,
+                            opts.bxl_args_json.as_deref(),
                             &cell_resolver,
                         )
                         .await?
@@ -114,20 +116,16 @@ impl ServerCommandTemplate for BxlProfileServerCommand {
                             .cancellation_context()
                             .with_structured_cancellation(|observer| {
                                 async move {
-                                    anyhow::Ok(
-                                        eval(
-                                            &mut ctx,
-                                            bxl_key,
-                                            StarlarkProfileModeOrInstrumentation::Profile(
-                                                profile_mode,
-                                            ),
-                                            observer,
-                                        )
-                                        .await?
-                                        .1
-                                        .map(Arc::new)
-                                        .expect("No bxl profile data found"),
+                                    eval(
+                                        &mut ctx,
+                                        bxl_key,
+                                        StarlarkProfileModeOrInstrumentation::Profile(profile_mode),
+                                        observer,
                                     )
+                                    .await?
+                                    .1
+                                    .map(Arc::new)
+                                    .context("No bxl profile data found (internal error)")
                                 }
                                 .boxed()
                             })
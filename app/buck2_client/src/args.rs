@@ -74,6 +74,31 @@ enum ArgFile {
     Stdin,
 }
 
+/// Expands argfiles in `args`. `is_run` tells from the arguments before the first `--` (already
+/// expanded) whether this is `buck2 run`.
+///
+/// For `buck2 run`, everything from the first `--` onwards is passed through verbatim: it is never
+/// expanded as argfiles (so `@`-prefixed values are kept as is), and empty or space-containing
+/// arguments are preserved exactly. This is what lets `buck2 run :bin -- @foo "a b" ""` hand
+/// precisely those arguments to the binary. Other commands expand all of `args` as usual.
+pub fn expand_args(
+    mut args: Vec<String>,
+    context: &mut ImmediateConfigContext,
+    is_run: impl FnOnce(&[String]) -> bool,
+) -> anyhow::Result<Vec<String>> {
+    let passthrough = match args.iter().position(|a| a == "--") {
+        Some(i) => args.split_off(i),
+        None => return expand_argfiles_with_context(args, context),
+    };
+    let mut expanded_args = expand_argfiles_with_context(args, context)?;
+    if is_run(&expanded_args) {
+        expanded_args.extend(passthrough);
+    } else {
+        expanded_args.extend(expand_argfiles_with_context(passthrough, context)?);
+    }
+    Ok(expanded_args)
+}
+
 // Expands any argfiles passed as command line parameters. There are
 // two ways to do: `@argfile` or `--flagfile PATH`.
 //
@@ -127,6 +152,32 @@ pub fn expand_argfiles_with_context(
     Ok(expanded_args)
 }
 
+/// The flag which makes buck2 print its expanded arguments instead of running the command.
+pub const DUMP_EXPANDED_ARGS: &str = "--dump-expanded-args";
+
+/// If `--dump-expanded-args` was passed (before any `--`), return the expanded arguments without
+/// it, so they can be printed.
+///
+/// This is checked before parsing the arguments with clap, so that it works even if the rest of
+/// the command line would not parse, e.g. because no subcommand was given.
+pub fn dump_expanded_args(expanded_args: &[String]) -> Option<Vec<String>> {
+    let end = expanded_args
+        .iter()
+        .position(|a| a == "--")
+        .unwrap_or(expanded_args.len());
+    if !expanded_args[..end].iter().any(|a| a == DUMP_EXPANDED_ARGS) {
+        return None;
+    }
+    Some(
+        expanded_args
+            .iter()
+            .enumerate()
+            .filter(|(i, a)| *i >= end || *a != DUMP_EXPANDED_ARGS)
+            .map(|(_, a)| a.clone())
+            .collect(),
+    )
+}
+
 // Resolves a path argument to an absolute path, reads the flag file and expands
 // it into a list of arguments.
 fn resolve_and_expand_argfile(
@@ -302,4 +353,115 @@ mod tests {
             expand_argfiles_with_context(vec!["@bar/arg1.txt".to_owned()], &mut context).unwrap();
         assert_eq!(res, vec!["--magic".to_owned()]);
     }
+
+    #[test]
+    fn test_expand_args_passthrough() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = AbsPath::new(tempdir.path()).unwrap();
+        fs_util::write(root.join("mode.txt"), "--magic").unwrap();
+        fs_util::write(root.join(".buckconfig"), "[repositories]\nroot = .").unwrap();
+        let cwd =
+            WorkingDir::unchecked_new(AbsNormPathBuf::new(root.canonicalize().unwrap()).unwrap());
+        let mut context = ImmediateConfigContext::new(&cwd);
+
+        let args = |args: &[&str]| args.iter().map(|a| (*a).to_owned()).collect::<Vec<_>>();
+        let is_run = |args: &[String]| args.get(1).map(String::as_str) == Some("run");
+        let res = expand_args(
+            args(&[
+                "buck2",
+                "run",
+                "@mode.txt",
+                ":bin",
+                "--",
+                "--flag",
+                "a b",
+                "@does-not-exist",
+                "@mode.txt",
+                "",
+                "--",
+                "--flagfile",
+            ]),
+            &mut context,
+            is_run,
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            args(&[
+                "buck2",
+                "run",
+                "--magic",
+                ":bin",
+                "--",
+                "--flag",
+                "a b",
+                "@does-not-exist",
+                "@mode.txt",
+                "",
+                "--",
+                "--flagfile",
+            ])
+        );
+
+        // Without `--` everything is expanded.
+        assert_eq!(
+            expand_args(
+                args(&["buck2", "@mode.txt", "a b", ""]),
+                &mut context,
+                is_run
+            )
+            .unwrap(),
+            args(&["buck2", "--magic", "a b", ""])
+        );
+
+        // Other commands expand their arguments as before.
+        let test_args = args(&["buck2", "test", "@mode.txt", ":t", "--", "@mode.txt", "a b"]);
+        assert_eq!(
+            expand_args(test_args.clone(), &mut context, is_run).unwrap(),
+            expand_argfiles_with_context(test_args, &mut context).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_dump_expanded_args_nested() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = AbsPath::new(tempdir.path()).unwrap();
+        fs_util::write(root.join("outer.txt"), "--dump-expanded-args\n@inner.txt").unwrap();
+        fs_util::write(root.join("inner.txt"), "build\n//:target").unwrap();
+        fs_util::write(root.join(".buckconfig"), "[repositories]\nroot = .").unwrap();
+        let cwd =
+            WorkingDir::unchecked_new(AbsNormPathBuf::new(root.canonicalize().unwrap()).unwrap());
+        let mut context = ImmediateConfigContext::new(&cwd);
+        let res = expand_argfiles_with_context(
+            vec![
+                "buck2".to_owned(),
+                "@outer.txt".to_owned(),
+                "--".to_owned(),
+                "--dump-expanded-args".to_owned(),
+            ],
+            &mut context,
+        )
+        .unwrap();
+        assert_eq!(
+            dump_expanded_args(&res),
+            Some(vec![
+                "buck2".to_owned(),
+                "build".to_owned(),
+                "//:target".to_owned(),
+                "--".to_owned(),
+                "--dump-expanded-args".to_owned(),
+            ])
+        );
+
+        // After `--`, the flag belongs to something else.
+        assert_eq!(
+            dump_expanded_args(&[
+                "buck2".to_owned(),
+                "run".to_owned(),
+                "--".to_owned(),
+                "--dump-expanded-args".to_owned(),
+            ]),
+            None
+        );
+    }
 }
@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Persist which targets built successfully, so that `buck2 build --since-last-success` can
+//! retry a partially failed build without rebuilding what already succeeded.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use buck2_cli_proto::BuildTarget;
+use buck2_cli_proto::ClientContext;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// What a build was of: a record is only reused by a build with the same key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BuildKey {
+    /// Patterns may be relative, so they are only comparable from the same working dir.
+    working_dir: String,
+    patterns: Vec<String>,
+    /// The target platforms, config overrides and host overrides, which decide what the patterns
+    /// build: a target built in one configuration is not built in another.
+    configuration: Vec<String>,
+}
+
+impl BuildKey {
+    pub(crate) fn new(working_dir: &str, patterns: &[String], context: &ClientContext) -> Self {
+        let mut configuration = vec![
+            format!("target_platforms={}", context.target_platform),
+            format!("host_platform={}", context.host_platform),
+            format!("host_arch={}", context.host_arch),
+            format!(
+                "host_xcode_version={}",
+                context.host_xcode_version.as_deref().unwrap_or_default()
+            ),
+        ];
+        // Overrides are applied in order, so their order matters.
+        configuration.extend(context.config_overrides.iter().map(|o| {
+            format!(
+                "{}={}",
+                o.config_type().as_str_name().to_lowercase(),
+                o.config_override
+            )
+        }));
+        Self {
+            working_dir: working_dir.to_owned(),
+            patterns: patterns.to_vec(),
+            configuration,
+        }
+    }
+}
+
+/// The targets which built successfully for a build. Only one such record is kept per isolation
+/// dir, for the last build.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LastSuccess {
+    key: BuildKey,
+    /// Targets as reported in `BuildTarget.target`.
+    succeeded: BTreeSet<String>,
+}
+
+impl LastSuccess {
+    /// Load the record for this build, if the last build had the same key.
+    ///
+    /// This is best-effort: a missing or unreadable record is treated as absent.
+    pub(crate) fn load(path: &AbsNormPath, key: &BuildKey) -> Option<Self> {
+        let contents = match fs_util::read_to_string_if_exists(path) {
+            Ok(contents) => contents?,
+            Err(e) => {
+                tracing::debug!("Failed to read last success state: {:#}", e);
+                return None;
+            }
+        };
+        let record: Self = serde_json::from_str(&contents).ok()?;
+        if &record.key == key {
+            Some(record)
+        } else {
+            None
+        }
+    }
+
+    /// Record the outcome of a build. If the build was a retry from `previous`, the targets
+    /// which succeeded then are kept, since they were not built this time.
+    pub(crate) fn update(
+        previous: Option<LastSuccess>,
+        key: BuildKey,
+        build_targets: &[BuildTarget],
+    ) -> Self {
+        let mut succeeded = previous.map(|p| p.succeeded).unwrap_or_default();
+        for target in build_targets {
+            if target.success {
+                succeeded.insert(target.target.clone());
+            } else {
+                succeeded.remove(&target.target);
+            }
+        }
+        Self { key, succeeded }
+    }
+
+    pub(crate) fn save(&self, path: &AbsNormPath) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs_util::create_dir_all(dir)?;
+        }
+        fs_util::write(path, serde_json::to_vec(self)?).context("Error writing last success state")
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.succeeded.is_empty()
+    }
+
+    pub(crate) fn targets(&self) -> impl Iterator<Item = &String> {
+        self.succeeded.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_cli_proto::ConfigOverride;
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+
+    use super::*;
+
+    fn target(name: &str, success: bool) -> BuildTarget {
+        BuildTarget {
+            target: name.to_owned(),
+            success,
+            ..Default::default()
+        }
+    }
+
+    fn build_key(working_dir: &str, patterns: &[&str], context: &ClientContext) -> BuildKey {
+        let patterns: Vec<String> = patterns.iter().map(|p| (*p).to_owned()).collect();
+        BuildKey::new(working_dir, &patterns, context)
+    }
+
+    #[test]
+    fn test_update_merges_previous() {
+        let key = build_key("/repo", &["//..."], &ClientContext::default());
+        let first = LastSuccess::update(
+            None,
+            key.clone(),
+            &[target("//:a", true), target("//:b", false)],
+        );
+        assert_eq!(first.targets().collect::<Vec<_>>(), vec!["//:a"]);
+
+        let second = LastSuccess::update(Some(first), key, &[target("//:b", true)]);
+        assert_eq!(second.targets().collect::<Vec<_>>(), vec!["//:a", "//:b"]);
+    }
+
+    #[test]
+    fn test_save_and_load() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = AbsNormPathBuf::try_from(temp_dir.path().join("last_success.json"))?;
+        let context = ClientContext::default();
+        let key = build_key("/repo", &["//:a"], &context);
+
+        assert_eq!(LastSuccess::load(&path, &key), None);
+
+        let record = LastSuccess::update(None, key.clone(), &[target("//:a", true)]);
+        record.save(&path)?;
+        assert_eq!(LastSuccess::load(&path, &key), Some(record));
+
+        // A different set of patterns, or a different working dir, is a different build.
+        assert_eq!(
+            LastSuccess::load(&path, &build_key("/repo", &["//:b"], &context)),
+            None
+        );
+        assert_eq!(
+            LastSuccess::load(&path, &build_key("/repo/sub", &["//:a"], &context)),
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_configuration_change_invalidates() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = AbsNormPathBuf::try_from(temp_dir.path().join("last_success.json"))?;
+        let context = ClientContext {
+            target_platform: "//platforms:linux".to_owned(),
+            config_overrides: vec![ConfigOverride {
+                config_override: "foo.bar=1".to_owned(),
+                config_type: buck2_cli_proto::config_override::ConfigType::Value as i32,
+            }],
+            ..Default::default()
+        };
+        let record = LastSuccess::update(
+            None,
+            build_key("/repo", &["//:a"], &context),
+            &[target("//:a", true)],
+        );
+        record.save(&path)?;
+        assert_eq!(
+            LastSuccess::load(&path, &build_key("/repo", &["//:a"], &context)),
+            Some(record)
+        );
+
+        let other_platform = ClientContext {
+            target_platform: "//platforms:mac".to_owned(),
+            ..context.clone()
+        };
+        let other_config = ClientContext {
+            config_overrides: vec![ConfigOverride {
+                config_override: "foo.bar=2".to_owned(),
+                config_type: buck2_cli_proto::config_override::ConfigType::Value as i32,
+            }],
+            ..context.clone()
+        };
+        let other_host = ClientContext {
+            host_platform: buck2_cli_proto::client_context::HostPlatformOverride::MacOs as i32,
+            ..context.clone()
+        };
+        for changed in [other_platform, other_config, other_host] {
+            assert_eq!(
+                LastSuccess::load(&path, &build_key("/repo", &["//:a"], &changed)),
+                None
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -37,9 +37,15 @@ use gazebo::prelude::*;
 use multimap::MultiMap;
 use serde::Serialize;
 
+use crate::commands::build::last_success::BuildKey;
+use crate::commands::build::last_success::LastSuccess;
 use crate::commands::build::out::copy_to_out;
+use crate::commands::build::watch::BuildOrChanged;
+use crate::commands::build::watch::FileChanges;
 
+mod last_success;
 mod out;
+mod watch;
 
 #[derive(Debug, clap::Parser)]
 #[clap(name = "build", about = "Build the specified targets")]
@@ -100,10 +106,16 @@ pub struct BuildCommand {
     )]
     show_full_json_output: bool,
 
+    #[clap(
+        long = "show-providers",
+        help = "Print the providers of each of the built rules, with a short summary of their fields"
+    )]
+    show_providers: bool,
+
     #[clap(
         long = "materializations",
         short = 'M',
-        help = "Materialize (or skip) the final artifacts, bypassing buckconfig.",
+        help = "Materialize (`all`) or skip (`none`) the final artifacts, bypassing buckconfig, or use the configured behavior (`default`). `--out` and `--show-output` still materialize the final artifacts under `none`, since they need them on disk.",
         ignore_case = true,
         arg_enum
     )]
@@ -157,7 +169,9 @@ pub struct BuildCommand {
 
     #[clap(
         long = "out",
-        help = "Copy the output of the built target to this path (`-` to stdout)"
+        help = "Copy the output of the built target to this path (`-` to stdout). \
+If multiple targets are built, this is a directory that each target's default outputs are copied into, \
+named by target"
     )]
     output_path: Option<OutputDestinationArg>,
 
@@ -169,6 +183,31 @@ pub struct BuildCommand {
         help = "Experimental: Path to a file where the Buck2 daemon should write a list of produced artifacts in json format"
     )]
     output_hashes_file: Option<PathArg>,
+
+    #[clap(
+        long,
+        help = "Only build the targets which did not build successfully in the last build of the \
+                same target patterns, e.g. to retry a partially failed build. Falls back to a \
+                full build if there is no previous build to resume from."
+    )]
+    since_last_success: bool,
+
+    #[clap(
+        long,
+        help = "Keep running, and build again whenever a file in the project changes, as seen by \
+                the daemon's file watcher (which must be `notify`). Changes made while a build is \
+                running cancel it and start it again. Press Ctrl-C to exit."
+    )]
+    watch: bool,
+
+    #[clap(
+        long,
+        default_value = "200ms",
+        value_name = "DURATION",
+        help = "With `--watch`, how long to wait for further changes after a change before \
+                building, so that a burst of changes only triggers one build"
+    )]
+    watch_debounce: humantime::Duration,
 }
 
 impl BuildCommand {
@@ -179,6 +218,26 @@ impl BuildCommand {
         build_providers::Action::Build
     }
 
+    fn return_outputs(&self) -> bool {
+        self.show_output
+            || self.show_full_output
+            || self.show_json_output
+            || self.show_full_json_output
+            || self.show_simple_output
+            || self.show_full_simple_output
+            || self.output_path.is_some()
+    }
+
+    fn final_artifact_materializations(&self) -> buck2_cli_proto::build_request::Materializations {
+        match self.materializations {
+            // The outputs are printed or copied, so they must exist on disk.
+            Some(FinalArtifactMaterializations::None) if self.return_outputs() => {
+                buck2_cli_proto::build_request::Materializations::Materialize
+            }
+            _ => self.materializations.to_proto(),
+        }
+    }
+
     fn run_info(&self) -> build_providers::Action {
         if self.skip_run_info {
             return build_providers::Action::Skip;
@@ -192,54 +251,110 @@ impl BuildCommand {
         }
         build_providers::Action::Skip
     }
-}
 
-#[derive(Debug, Clone, Dupe, clap::ArgEnum)]
-#[clap(rename_all = "snake_case")]
-pub enum FinalArtifactMaterializations {
-    All,
-    None,
-}
+    /// Build, then build again every time something changes, until interrupted.
+    async fn watch(
+        &self,
+        buckd: &mut BuckdClientConnector,
+        matches: &clap::ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let project_root = ctx.paths()?.project_root().dupe();
+        // Don't build again because of our own writes. The daemon already ignores buck-out.
+        let ignored = [
+            match &self.output_path {
+                Some(OutputDestinationArg::Path(path)) => Some(path),
+                _ => None,
+            },
+            self.output_hashes_file.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| {
+            project_root
+                .relativize_any(path.resolve(&ctx.working_dir))
+                .ok()
+        })
+        .collect();
+        let (mut changes, subscription) =
+            FileChanges::subscribe(ctx.paths()?, ctx.client_context(matches, self)?, ignored)
+                .await?;
+
+        tokio::select! {
+            result = self.build_on_changes(buckd, matches, ctx, &mut changes) => result,
+            error = subscription => ExitResult::err(error),
+        }
+    }
 
-pub trait MaterializationsToProto {
-    fn to_proto(&self) -> buck2_cli_proto::build_request::Materializations;
-}
-impl MaterializationsToProto for Option<FinalArtifactMaterializations> {
-    fn to_proto(&self) -> buck2_cli_proto::build_request::Materializations {
-        match self {
-            Some(FinalArtifactMaterializations::All) => {
-                buck2_cli_proto::build_request::Materializations::Materialize
+    async fn build_on_changes(
+        &self,
+        buckd: &mut BuckdClientConnector,
+        matches: &clap::ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+        changes: &mut FileChanges,
+    ) -> ExitResult {
+        let console = self.common_opts.console_opts.final_console();
+        let debounce = self.watch_debounce.into();
+        let mut changed: Option<Vec<_>> = None;
+        loop {
+            if let Some(changed) = changed.take() {
+                console.print_success(&format!(
+                    "{} file{} changed, building again",
+                    changed.len(),
+                    if changed.len() == 1 { "" } else { "s" }
+                ))?;
             }
-            Some(FinalArtifactMaterializations::None) => {
-                buck2_cli_proto::build_request::Materializations::Skip
+            match changes
+                .race(self.build(buckd, matches, ctx), debounce)
+                .await?
+            {
+                BuildOrChanged::Built(result) => {
+                    result.print()?;
+                    console.print_success("Watching for changes, press Ctrl-C to exit")?;
+                    changed = Some(changes.next(debounce).await?);
+                }
+                BuildOrChanged::Changed(paths) => {
+                    console.print_warning("Files changed during the build, cancelling it")?;
+                    changed = Some(paths);
+                }
             }
-            None => buck2_cli_proto::build_request::Materializations::Default,
         }
     }
-}
-
-pub fn print_build_result(
-    console: &FinalConsole,
-    errors: &[buck2_data::ErrorReport],
-) -> anyhow::Result<()> {
-    for error in errors {
-        console.print_error(&error.message)?;
-    }
-    Ok(())
-}
 
-#[async_trait]
-impl StreamingCommand for BuildCommand {
-    const COMMAND_NAME: &'static str = "build";
-
-    async fn exec_impl(
-        self,
+    async fn build(
+        &self,
         buckd: &mut BuckdClientConnector,
         matches: &clap::ArgMatches,
         ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
         let show_default_other_outputs = false;
-        let context = ctx.client_context(matches, &self)?;
+        let context = ctx.client_context(matches, self)?;
+        let console = self.common_opts.console_opts.final_console();
+
+        let last_success_path = ctx.paths()?.last_success_path();
+        let last_success_key = BuildKey::new(
+            &ctx.working_dir.path().to_string(),
+            &self.patterns,
+            &context,
+        );
+        let previous = if self.since_last_success {
+            match LastSuccess::load(&last_success_path, &last_success_key) {
+                Some(previous) if !previous.is_empty() => Some(previous),
+                _ => {
+                    console.print_warning(
+                        "No previous successful build of these target patterns in this configuration, \
+                        building everything",
+                    )?;
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let exclude_targets = previous
+            .as_ref()
+            .map(|p| p.targets().cloned().collect())
+            .unwrap_or_default();
 
         let result = buckd
             .with_flushing()
@@ -255,20 +370,16 @@ impl StreamingCommand for BuildCommand {
                         test_info: self.test_info() as i32,
                     }),
                     response_options: Some(ResponseOptions {
-                        return_outputs: self.show_output
-                            || self.show_full_output
-                            || self.show_json_output
-                            || self.show_full_json_output
-                            || self.show_simple_output
-                            || self.show_full_simple_output
-                            || self.output_path.is_some(),
+                        return_outputs: self.return_outputs(),
                         return_default_other_outputs: show_default_other_outputs,
+                        return_providers: self.show_providers,
                     }),
                     build_opts: Some(self.build_opts.to_proto()),
-                    final_artifact_materializations: self.materializations.to_proto() as i32,
-                    target_universe: self.target_universe,
+                    final_artifact_materializations: self.final_artifact_materializations() as i32,
+                    target_universe: self.target_universe.clone(),
                     output_hashes_file: self
                         .output_hashes_file
+                        .as_ref()
                         .map(|p| {
                             p.resolve(&ctx.working_dir).into_string().with_context(|| {
                                 format!(
@@ -278,6 +389,7 @@ impl StreamingCommand for BuildCommand {
                             })
                         })
                         .transpose()?,
+                    exclude_targets,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -290,7 +402,14 @@ impl StreamingCommand for BuildCommand {
             Err(_) => false,
         };
 
-        let console = self.common_opts.console_opts.final_console();
+        if let Ok(CommandOutcome::Success(response)) = &result {
+            // Best-effort: failing to record this should not fail the build.
+            if let Err(e) = LastSuccess::update(previous, last_success_key, &response.build_targets)
+                .save(&last_success_path)
+            {
+                tracing::debug!("Failed to record last success state: {:#}", e);
+            }
+        }
 
         if success {
             if self.patterns.is_empty() {
@@ -336,7 +455,7 @@ impl StreamingCommand for BuildCommand {
             {
                 print_outputs(
                     &mut stdout,
-                    response.build_targets,
+                    &response.build_targets,
                     if self.show_full_output
                         || self.show_full_json_output
                         || self.show_full_simple_output
@@ -358,6 +477,10 @@ impl StreamingCommand for BuildCommand {
                 )?;
             }
 
+            if self.show_providers {
+                print_providers(&mut stdout, &response.build_targets)?;
+            }
+
             ExitResult::success()
         } else {
             ExitResult::from_errors(&response.errors)
@@ -365,6 +488,61 @@ impl StreamingCommand for BuildCommand {
 
         res.with_stdout(stdout)
     }
+}
+
+#[derive(Debug, Clone, Dupe, clap::ArgEnum)]
+#[clap(rename_all = "snake_case")]
+pub enum FinalArtifactMaterializations {
+    All,
+    None,
+    Default,
+}
+
+pub trait MaterializationsToProto {
+    fn to_proto(&self) -> buck2_cli_proto::build_request::Materializations;
+}
+impl MaterializationsToProto for Option<FinalArtifactMaterializations> {
+    fn to_proto(&self) -> buck2_cli_proto::build_request::Materializations {
+        match self {
+            Some(FinalArtifactMaterializations::All) => {
+                buck2_cli_proto::build_request::Materializations::Materialize
+            }
+            Some(FinalArtifactMaterializations::None) => {
+                buck2_cli_proto::build_request::Materializations::Skip
+            }
+            Some(FinalArtifactMaterializations::Default) | None => {
+                buck2_cli_proto::build_request::Materializations::Default
+            }
+        }
+    }
+}
+
+pub fn print_build_result(
+    console: &FinalConsole,
+    errors: &[buck2_data::ErrorReport],
+) -> anyhow::Result<()> {
+    for error in errors {
+        console.print_error(&error.message)?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl StreamingCommand for BuildCommand {
+    const COMMAND_NAME: &'static str = "build";
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        matches: &clap::ArgMatches,
+        ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        if self.watch {
+            self.watch(buckd, matches, ctx).await
+        } else {
+            self.build(buckd, matches, ctx).await
+        }
+    }
 
     fn console_opts(&self) -> &CommonConsoleOptions {
         &self.common_opts.console_opts
@@ -402,7 +580,7 @@ pub(crate) enum PrintOutputsFormat {
 
 pub(crate) fn print_outputs(
     mut out: impl Write,
-    targets: Vec<BuildTarget>,
+    targets: &[BuildTarget],
     root_path: Option<String>,
     format: PrintOutputsFormat,
     show_all_outputs: bool,
@@ -467,7 +645,7 @@ pub(crate) fn print_outputs(
 
     for build_target in targets {
         // just print the default info for build command
-        let outputs = build_target.outputs.into_iter().filter(|output| {
+        let outputs = build_target.outputs.iter().filter(|output| {
             output
                 .providers
                 .as_ref()
@@ -484,7 +662,7 @@ pub(crate) fn print_outputs(
             continue;
         }
         for output in outputs {
-            process_output(&build_target.target, Some(output.path))?;
+            process_output(&build_target.target, Some(output.path.clone()))?;
         }
     }
 
@@ -496,6 +674,20 @@ pub(crate) fn print_outputs(
     Ok(())
 }
 
+/// Print each target followed by its providers, and the summary of each provider's fields.
+pub(crate) fn print_providers(mut out: impl Write, targets: &[BuildTarget]) -> anyhow::Result<()> {
+    for build_target in targets {
+        writeln!(&mut out, "{}", build_target.target)?;
+        for provider in &build_target.providers {
+            writeln!(&mut out, "  {}", provider.name)?;
+            for field in &provider.fields {
+                writeln!(&mut out, "    {}: {}", field.name, field.summary)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use assert_matches::assert_matches;
@@ -576,4 +768,131 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn materializations() -> anyhow::Result<()> {
+        use buck2_cli_proto::build_request::Materializations;
+
+        let materializations = |args: &[&str]| -> anyhow::Result<Materializations> {
+            Ok(parse(args)?.final_artifact_materializations())
+        };
+
+        assert_eq!(materializations(&[])?, Materializations::Default);
+        assert_eq!(
+            materializations(&["--materializations=default"])?,
+            Materializations::Default
+        );
+        assert_eq!(
+            materializations(&["--materializations=all"])?,
+            Materializations::Materialize
+        );
+        assert_eq!(
+            materializations(&["--materializations=none"])?,
+            Materializations::Skip
+        );
+
+        // Outputs that are printed or copied are still materialized.
+        assert_eq!(
+            materializations(&["--materializations=none", "--show-output"])?,
+            Materializations::Materialize
+        );
+        assert_eq!(
+            materializations(&["--materializations=none", "--out", "foo"])?,
+            Materializations::Materialize
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_outputs_match_schema() -> anyhow::Result<()> {
+        use buck2_cli_proto::build_target::build_output::BuildOutputProviders;
+        use buck2_cli_proto::build_target::BuildOutput;
+        use buck2_client_ctx::json_schema::JsonOutput;
+
+        let output = |path: &str, default_info: bool| BuildOutput {
+            path: path.to_owned(),
+            providers: Some(BuildOutputProviders {
+                default_info,
+                ..Default::default()
+            }),
+        };
+        let targets = [
+            BuildTarget {
+                target: "root//:bin".to_owned(),
+                outputs: vec![output("buck-out/bin", true), output("buck-out/run", false)],
+                ..Default::default()
+            },
+            BuildTarget {
+                target: "root//:two".to_owned(),
+                outputs: vec![output("buck-out/a", true), output("buck-out/b", true)],
+                ..Default::default()
+            },
+            BuildTarget {
+                target: "root//:none".to_owned(),
+                ..Default::default()
+            },
+        ];
+
+        for root_path in [None, Some("/repo".to_owned())] {
+            let mut out = Vec::new();
+            print_outputs(
+                &mut out,
+                &targets,
+                root_path,
+                PrintOutputsFormat::Json,
+                false,
+            )?;
+            let json: serde_json::Value = serde_json::from_slice(&out)?;
+            JsonOutput::BuildOutputs.validate(&json)?;
+            assert!(json.get("root//:bin").is_some(), "{}", json);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_print_providers() -> anyhow::Result<()> {
+        use buck2_cli_proto::build_target::provider_summary::Field;
+        use buck2_cli_proto::build_target::ProviderSummary;
+
+        let field = |name: &str, summary: &str| Field {
+            name: name.to_owned(),
+            summary: summary.to_owned(),
+        };
+        let targets = vec![BuildTarget {
+            target: "root//foo:bar".to_owned(),
+            providers: vec![
+                ProviderSummary {
+                    name: "DefaultInfo".to_owned(),
+                    fields: vec![
+                        field("default_outputs", "<list with 1 item>"),
+                        field("sub_targets", "<dict with 0 items>"),
+                    ],
+                },
+                ProviderSummary {
+                    name: "RunInfo".to_owned(),
+                    fields: vec![field("args", "cmd_args(\"bar\")")],
+                },
+            ],
+            ..Default::default()
+        }];
+
+        let mut out = Vec::new();
+        print_providers(&mut out, &targets)?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            [
+                "root//foo:bar",
+                "  DefaultInfo",
+                "    default_outputs: <list with 1 item>",
+                "    sub_targets: <dict with 0 items>",
+                "  RunInfo",
+                "    args: cmd_args(\"bar\")",
+                "",
+            ]
+            .join("\n")
+        );
+
+        Ok(())
+    }
 }
@@ -8,6 +8,7 @@
  */
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io;
 use std::path::Path;
 
@@ -34,6 +35,9 @@ use futures::TryStreamExt;
 /// Otherwise, we'll extract the single default output from the single top-level target and copy it to the output
 /// path. If the given path is a directory then all output files will be copied inside of it.
 ///
+/// If multiple top-level targets were built, `out` is instead a directory, and each target's default outputs are
+/// copied into it, named by target. See [`copy_to_out_dir`].
+///
 /// As a special case, `--out -` is interpreted as `--out /dev/stdout` and allows multiple output files to be
 /// written to it.
 pub(super) async fn copy_to_out(
@@ -42,6 +46,12 @@ pub(super) async fn copy_to_out(
     working_dir: &WorkingDir,
     out: &OutputDestinationArg,
 ) -> anyhow::Result<()> {
+    if let OutputDestinationArg::Path(path) = out {
+        if targets.len() > 1 {
+            return copy_to_out_dir(targets, root_path, &path.resolve(working_dir)).await;
+        }
+    }
+
     struct OutputToBeCopied {
         from_path: AbsNormPathBuf,
         is_dir: bool,
@@ -49,16 +59,7 @@ pub(super) async fn copy_to_out(
 
     let mut outputs_to_be_copied = Vec::new();
     for target in targets {
-        let default_outputs: Vec<&BuildOutput> = target
-            .outputs
-            .iter()
-            .filter(|output| {
-                output
-                    .providers
-                    .as_ref()
-                    .map_or(true, |p| p.default_info && !p.other)
-            })
-            .collect();
+        let default_outputs = default_outputs(target);
 
         let single_default_output = match default_outputs.len() {
             0 => {
@@ -106,7 +107,7 @@ pub(super) async fn copy_to_out(
             // Check we are outputting exactly 1 target. Okay if directory.
             if outputs_to_be_copied.len() != 1 {
                 return Err(anyhow::anyhow!(
-                    "build command built multiple top-level targets, choice of output is ambiguous"
+                    "build command built no top-level targets, there is no output to copy"
                 ));
             }
         }
@@ -134,6 +135,138 @@ pub(super) async fn copy_to_out(
     Ok(())
 }
 
+/// Copies the default outputs of every target to the directory `dir`, which is created if missing. A target with
+/// a single default output has it copied to `dir/<name>`, and a target with several has them copied inside
+/// `dir/<name>/`, where `<name>` is given by [`out_dir_names`]. A target with no default outputs is an error,
+/// as it is when building a single target.
+///
+/// Only the paths for the targets are written to, other files in `dir` are left alone.
+async fn copy_to_out_dir(
+    targets: &[BuildTarget],
+    root_path: &ProjectRoot,
+    dir: &Path,
+) -> anyhow::Result<()> {
+    if dir.exists() && !dir.is_dir() {
+        return Err(anyhow::anyhow!(
+            "build command built multiple top-level targets, so `{}` must be a directory",
+            dir.display()
+        ));
+    }
+
+    // Check before copying anything, so that a mistake doesn't leave a partially populated directory.
+    let names = out_dir_names(targets.iter().map(|t| t.target.as_str()))?;
+    let outputs: Vec<Vec<&BuildOutput>> = targets.iter().map(default_outputs).collect();
+    if let Some(i) = outputs.iter().position(|outputs| outputs.is_empty()) {
+        return Err(anyhow::anyhow!(
+            "target {} produced zero default outputs, there is nothing to copy to --out",
+            targets[i].target
+        ));
+    }
+
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("creating output directory `{}`", dir.display()))?;
+
+    for ((target, name), default_outputs) in targets.iter().zip(names).zip(outputs) {
+        let dst = dir.join(&name);
+        let multiple = default_outputs.len() > 1;
+        if multiple {
+            tokio::fs::create_dir_all(&dst).await?;
+        }
+        for output in default_outputs {
+            let from_path = root_path
+                .root()
+                .join(ForwardRelativePath::new(&output.path)?);
+            let is_dir = tokio::fs::metadata(&from_path)
+                .await
+                .context("Error inspecting file metadata")?
+                .is_dir();
+            // With several outputs `dst` is a directory, so each output keeps its file name.
+            let dst = if multiple {
+                Cow::Owned(
+                    dst.join(
+                        from_path
+                            .file_name()
+                            .context("Failed getting output name")?,
+                    ),
+                )
+            } else {
+                Cow::Borrowed(&dst)
+            };
+            if is_dir {
+                copy_directory(&from_path, &dst).await
+            } else {
+                copy_file(&from_path, &dst).await
+            }
+            .with_context(|| format!("copying output of {}", target.target))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The outputs of `target` from its `DefaultInfo`, which are the ones copied to `--out`.
+fn default_outputs(target: &BuildTarget) -> Vec<&BuildOutput> {
+    target
+        .outputs
+        .iter()
+        .filter(|output| {
+            output
+                .providers
+                .as_ref()
+                .map_or(true, |p| p.default_info && !p.other)
+        })
+        .collect()
+}
+
+/// The names to copy outputs of `targets` to in an `--out` directory: the target name, or the target name
+/// prefixed with its package path if several targets share a name.
+fn out_dir_names<'a>(targets: impl IntoIterator<Item = &'a str>) -> anyhow::Result<Vec<String>> {
+    fn sanitize(s: &str) -> String {
+        s.replace('/', "_")
+    }
+
+    let targets: Vec<(&str, &str, &str)> = targets
+        .into_iter()
+        .map(|target| {
+            // Targets are reported as `cell//package:name`.
+            let (_cell, label) = target.split_once("//").unwrap_or(("", target));
+            let (package, name) = label.rsplit_once(':').unwrap_or(("", label));
+            (target, package, name)
+        })
+        .collect();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_target, _package, name) in &targets {
+        *counts.entry(name).or_default() += 1;
+    }
+
+    let names: Vec<String> = targets
+        .iter()
+        .map(|(_target, package, name)| {
+            if counts[name] > 1 && !package.is_empty() {
+                format!("{}_{}", sanitize(package), sanitize(name))
+            } else {
+                sanitize(name)
+            }
+        })
+        .collect();
+
+    let mut seen = HashMap::new();
+    for (name, (target, _package, _name)) in names.iter().zip(&targets) {
+        if let Some(other) = seen.insert(name, target) {
+            return Err(anyhow::anyhow!(
+                "targets `{}` and `{}` would both be copied to `{}` in --out directory",
+                other,
+                target,
+                name
+            ));
+        }
+    }
+
+    Ok(names)
+}
+
 /// Recursively copies a directory to the output path, rooted at `dst`.
 #[async_recursion::async_recursion]
 async fn copy_directory(src: &Path, dst: &Path) -> anyhow::Result<()> {
@@ -202,6 +335,22 @@ fn convert_broken_pipe_error(e: io::Error) -> anyhow::Error {
 
 #[cfg(test)]
 mod test {
+    use super::*;
+
+    #[test]
+    fn test_out_dir_names() -> anyhow::Result<()> {
+        assert_eq!(
+            out_dir_names(["root//foo:bin", "root//foo/bar:lib", "root//baz:bin"])?,
+            vec!["foo_bin", "lib", "baz_bin"]
+        );
+        assert_eq!(
+            out_dir_names(["root//foo:gen/file.txt"])?,
+            vec!["gen_file.txt"]
+        );
+        assert!(out_dir_names(["root//foo:bin", "other//foo:bin"]).is_err());
+        Ok(())
+    }
+
     #[cfg(unix)]
     mod unix {
         use std::path::Path;
@@ -240,5 +389,86 @@ mod test {
 
             Ok(())
         }
+
+        fn target(target: &str, outputs: &[&str]) -> BuildTarget {
+            BuildTarget {
+                target: target.to_owned(),
+                outputs: outputs
+                    .iter()
+                    .map(|path| BuildOutput {
+                        path: (*path).to_owned(),
+                        providers: None,
+                    })
+                    .collect(),
+                ..Default::default()
+            }
+        }
+
+        fn write(path: &Path, contents: &str) -> anyhow::Result<()> {
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, contents)?;
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_copy_to_out_dir() -> anyhow::Result<()> {
+            let root = tempfile::tempdir()?;
+            let root_path =
+                ProjectRoot::new_unchecked(AbsNormPathBuf::new(root.path().to_path_buf())?);
+            write(&root.path().join("buck-out/a/bin"), "bin")?;
+            write(&root.path().join("buck-out/b/x.txt"), "x")?;
+            write(&root.path().join("buck-out/b/y.txt"), "y")?;
+            write(&root.path().join("buck-out/c/dir/nested/f"), "f")?;
+
+            let out = tempfile::tempdir()?;
+            let dir = out.path().join("out");
+            copy_to_out_dir(
+                &[
+                    target("root//a:bin", &["buck-out/a/bin"]),
+                    target("root//b:gen", &["buck-out/b/x.txt", "buck-out/b/y.txt"]),
+                    target("root//c:dir", &["buck-out/c/dir"]),
+                ],
+                &root_path,
+                &dir,
+            )
+            .await?;
+
+            assert_eq!(std::fs::read_to_string(dir.join("bin"))?, "bin");
+            assert_eq!(std::fs::read_to_string(dir.join("gen/x.txt"))?, "x");
+            assert_eq!(std::fs::read_to_string(dir.join("gen/y.txt"))?, "y");
+            assert_eq!(std::fs::read_to_string(dir.join("dir/nested/f"))?, "f");
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_copy_to_out_dir_no_default_outputs() -> anyhow::Result<()> {
+            let root = tempfile::tempdir()?;
+            let root_path =
+                ProjectRoot::new_unchecked(AbsNormPathBuf::new(root.path().to_path_buf())?);
+            write(&root.path().join("buck-out/a/bin"), "bin")?;
+
+            let out = tempfile::tempdir()?;
+            let dir = out.path().join("out");
+            let err = copy_to_out_dir(
+                &[
+                    target("root//a:bin", &["buck-out/a/bin"]),
+                    target("root//b:empty", &[]),
+                ],
+                &root_path,
+                &dir,
+            )
+            .await
+            .unwrap_err();
+
+            assert!(
+                err.to_string()
+                    .contains("target root//b:empty produced zero default outputs"),
+                "{:#}",
+                err
+            );
+            // Nothing was copied.
+            assert!(!dir.exists());
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,316 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Watching the project for changes, for `buck2 build --watch`. The changes come from the daemon's
+//! own file watcher, through a subscription, so they are exactly the changes the next build picks
+//! up, and ignored paths (`project.ignore` and buck-out) never trigger a build.
+
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use buck2_cli_proto::ClientContext;
+use buck2_client_ctx::command_outcome::CommandOutcome;
+use buck2_client_ctx::daemon::client::connect::BuckdConnectOptions;
+use buck2_client_ctx::events_ctx::PartialResultCtx;
+use buck2_client_ctx::events_ctx::PartialResultHandler;
+use buck2_common::invocation_paths::InvocationPaths;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_subscription_proto::subscription_response::Response;
+use buck2_subscription_proto::SubscriptionRequest;
+use futures::future::BoxFuture;
+use futures::future::FutureExt;
+use futures::stream::StreamExt;
+use indexmap::IndexSet;
+use tokio::sync::mpsc;
+
+/// What ended a build run with [`FileChanges::race`].
+pub(crate) enum BuildOrChanged<T> {
+    Built(T),
+    /// Files changed before the build was done, and the build was dropped.
+    Changed(Vec<ProjectRelativePathBuf>),
+}
+
+pub(crate) struct FileChanges {
+    events: mpsc::UnboundedReceiver<ProjectRelativePathBuf>,
+    pending: IndexSet<ProjectRelativePathBuf>,
+}
+
+impl FileChanges {
+    /// Subscribe to the daemon's file changes, on a connection of its own so that it can run
+    /// alongside builds. Changes under `ignored` (which should cover everything the build writes
+    /// to outside of buck-out) are skipped.
+    ///
+    /// The changes only arrive while the returned future is polled. It only completes if the
+    /// subscription ends, which is an error.
+    pub(crate) async fn subscribe(
+        paths: &InvocationPaths,
+        context: ClientContext,
+        ignored: Vec<ProjectRelativePathBuf>,
+    ) -> anyhow::Result<(FileChanges, BoxFuture<'static, anyhow::Error>)> {
+        let mut buckd = BuckdConnectOptions::existing_only_no_console()
+            .connect(paths)
+            .await
+            .context("Error connecting to the daemon to watch for file changes")?;
+        let (tx, events) = mpsc::unbounded_channel();
+        let mut handler = FileChangesHandler {
+            tx,
+            ignored,
+            goodbye: None,
+        };
+
+        // Never disconnect: the subscription ends with the command.
+        let requests = futures::stream::iter([buck2_cli_proto::SubscriptionRequestWrapper {
+            request: Some(SubscriptionRequest {
+                request: Some(buck2_subscription_proto::SubscribeToFileChanges {}.into()),
+            }),
+        }])
+        .chain(futures::stream::pending());
+
+        let subscription = async move {
+            let outcome = buckd
+                .with_flushing()
+                .subscription(context, requests, &mut handler)
+                .await;
+            let error = match outcome {
+                Ok(CommandOutcome::Success(_)) => match handler.goodbye {
+                    Some(reason) => anyhow::anyhow!(reason),
+                    None => anyhow::anyhow!("The daemon stopped reporting file changes"),
+                },
+                Ok(CommandOutcome::Failure(_)) => {
+                    anyhow::anyhow!("Subscribing to file changes failed")
+                }
+                Err(e) => e,
+            };
+            error.context("Error watching for file changes")
+        }
+        .boxed();
+
+        Ok((
+            FileChanges {
+                events,
+                pending: IndexSet::new(),
+            },
+            subscription,
+        ))
+    }
+
+    /// Wait for a change, then keep collecting changes until none arrived for `debounce`, so that
+    /// a burst of changes (e.g. a rebase, or saving many files at once) only triggers one build.
+    /// Changes which arrived while nobody was waiting (e.g. while printing the build result) count
+    /// too. Returns the changed paths.
+    pub(crate) async fn next(
+        &mut self,
+        debounce: Duration,
+    ) -> anyhow::Result<Vec<ProjectRelativePathBuf>> {
+        while self.pending.is_empty() {
+            let path = self
+                .events
+                .recv()
+                .await
+                .context("File changes stopped unexpectedly")?;
+            self.pending.insert(path);
+        }
+        while let Ok(event) = tokio::time::timeout(debounce, self.events.recv()).await {
+            match event {
+                Some(path) => {
+                    self.pending.insert(path);
+                }
+                None => break,
+            }
+        }
+        Ok(std::mem::take(&mut self.pending).into_iter().collect())
+    }
+
+    /// Run `build` until it is done or files change, whichever comes first. On a change, `build`
+    /// is dropped, which cancels it, so that the next build can start right away with the change.
+    /// Changes still being debounced when the build is done are kept for the next `next`.
+    pub(crate) async fn race<T>(
+        &mut self,
+        build: impl Future<Output = T>,
+        debounce: Duration,
+    ) -> anyhow::Result<BuildOrChanged<T>> {
+        tokio::select! {
+            result = build => Ok(BuildOrChanged::Built(result)),
+            changed = self.next(debounce) => Ok(BuildOrChanged::Changed(changed?)),
+        }
+    }
+}
+
+/// Forwards the changes reported by the daemon to `FileChanges`.
+struct FileChangesHandler {
+    tx: mpsc::UnboundedSender<ProjectRelativePathBuf>,
+    ignored: Vec<ProjectRelativePathBuf>,
+    /// Why the daemon ended the subscription, if it did so itself.
+    goodbye: Option<String>,
+}
+
+#[async_trait]
+impl PartialResultHandler for FileChangesHandler {
+    type PartialResult = buck2_cli_proto::SubscriptionResponseWrapper;
+
+    async fn handle_partial_result(
+        &mut self,
+        _ctx: PartialResultCtx<'_, '_>,
+        partial_res: Self::PartialResult,
+    ) -> anyhow::Result<()> {
+        let response = partial_res
+            .response
+            .context("Empty `SubscriptionResponseWrapper`")?;
+        self.handle_response(response)
+    }
+}
+
+impl FileChangesHandler {
+    fn handle_response(
+        &mut self,
+        response: buck2_subscription_proto::SubscriptionResponse,
+    ) -> anyhow::Result<()> {
+        match response.response {
+            Some(Response::FileChanged(buck2_subscription_proto::FileChanged { path })) => {
+                let path = ProjectRelativePathBuf::try_from(path)?;
+                if !self.ignored.iter().any(|ignored| path.starts_with(ignored)) {
+                    // The receiver is only gone once the command is exiting.
+                    let _ignored = self.tx.send(path);
+                }
+            }
+            Some(Response::Goodbye(goodbye)) => self.goodbye = Some(goodbye.reason),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes() -> (mpsc::UnboundedSender<ProjectRelativePathBuf>, FileChanges) {
+        let (tx, events) = mpsc::unbounded_channel();
+        let changes = FileChanges {
+            events,
+            pending: IndexSet::new(),
+        };
+        (tx, changes)
+    }
+
+    fn path(path: &str) -> ProjectRelativePathBuf {
+        ProjectRelativePathBuf::try_from(path.to_owned()).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_debounce_burst() -> anyhow::Result<()> {
+        let (tx, mut changes) = changes();
+        let debounce = Duration::from_millis(200);
+
+        let sender = tokio::spawn(async move {
+            for p in ["a", "b", "a", "c"] {
+                tx.send(path(p)).unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            // Long enough after the burst to be a separate batch.
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            tx.send(path("d")).unwrap();
+            tx
+        });
+
+        assert_eq!(
+            changes.next(debounce).await?,
+            vec![path("a"), path("b"), path("c")]
+        );
+        assert_eq!(changes.next(debounce).await?, vec![path("d")]);
+        drop(sender.await?);
+        assert!(changes.next(debounce).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_change_while_not_waiting_is_kept() -> anyhow::Result<()> {
+        let (tx, mut changes) = changes();
+        // Nobody is waiting for changes yet.
+        tx.send(path("a"))?;
+        tx.send(path("b"))?;
+        assert_eq!(
+            changes.next(Duration::from_millis(200)).await?,
+            vec![path("a"), path("b")]
+        );
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_change_during_build_cancels_it() -> anyhow::Result<()> {
+        let (tx, mut changes) = changes();
+        let debounce = Duration::from_millis(200);
+        let (cancelled_tx, cancelled_rx) = tokio::sync::oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            tx.send(path("a")).unwrap();
+            tx
+        });
+        let build = async move {
+            // Only sends (by being dropped) if the build is dropped before it is done.
+            let _cancelled = cancelled_tx;
+            futures::future::pending::<()>().await;
+        };
+
+        match changes.race(build, debounce).await? {
+            BuildOrChanged::Changed(changed) => assert_eq!(changed, vec![path("a")]),
+            BuildOrChanged::Built(()) => panic!("The build should not finish"),
+        }
+        assert!(cancelled_rx.await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_change_debounced_after_build_is_kept() -> anyhow::Result<()> {
+        let (tx, mut changes) = changes();
+        let debounce = Duration::from_millis(200);
+        tx.send(path("a"))?;
+
+        // The build finishes while the change is being debounced.
+        let build = tokio::time::sleep(Duration::from_millis(100));
+        assert!(matches!(
+            changes.race(build, debounce).await?,
+            BuildOrChanged::Built(())
+        ));
+        assert_eq!(changes.next(debounce).await?, vec![path("a")]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_handler_skips_ignored() -> anyhow::Result<()> {
+        let (tx, mut events) = mpsc::unbounded_channel();
+        let mut handler = FileChangesHandler {
+            tx,
+            ignored: vec![path("out")],
+            goodbye: None,
+        };
+        for p in ["out/a", "src/b"] {
+            handler.handle_response(buck2_subscription_proto::SubscriptionResponse {
+                response: Some(buck2_subscription_proto::FileChanged { path: p.to_owned() }.into()),
+            })?;
+        }
+        handler.handle_response(buck2_subscription_proto::SubscriptionResponse {
+            response: Some(
+                buck2_subscription_proto::Goodbye {
+                    reason: "bye".to_owned(),
+                    ok: false,
+                }
+                .into(),
+            ),
+        })?;
+        assert_eq!(handler.goodbye.as_deref(), Some("bye"));
+        drop(handler);
+        assert_eq!(events.recv().await, Some(path("src/b")));
+        assert_eq!(events.recv().await, None);
+        Ok(())
+    }
+}
@@ -7,6 +7,11 @@
  * of this source tree.
  */
 
+use std::io;
+use std::io::Read;
+use std::str::FromStr;
+
+use anyhow::Context;
 use async_trait::async_trait;
 use buck2_cli_proto::BxlRequest;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
@@ -21,6 +26,8 @@ use buck2_client_ctx::daemon::client::StdoutPartialResultHandler;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::working_dir::WorkingDir;
 
 use crate::commands::build::print_build_result;
 use crate::commands::build::FinalArtifactMaterializations;
@@ -34,6 +41,36 @@ pub struct BxlCommand {
 
     #[clap(flatten)]
     common_ops: CommonCommandOptions,
+
+    /// Evaluate the bxl function again, ignoring any result cached by the daemon. This discards
+    /// the cached results of all bxl functions, not just this one. Useful when the script reads
+    /// external state that Buck2 can't track.
+    #[clap(long)]
+    no_bxl_cache: bool,
+
+    /// Write a JSON list of the artifacts materialized by the bxl function (sorted by path, with
+    /// their owning target) to this file.
+    #[clap(long, value_name = "PATH")]
+    materializations_report: Option<PathArg>,
+
+    /// How to print the output of the bxl function. With `json`, the output is printed as a
+    /// single JSON object whose `outputs` list holds each value written by `ctx.output.print_json`
+    /// tagged as `json`, and each value written by `ctx.output.print` tagged as `text`.
+    #[clap(long, arg_enum, ignore_case = true, default_value = "text")]
+    print_bxl_output: BxlOutputFormat,
+
+    /// Print the artifacts and materializations added or removed, and whether the output changed,
+    /// since the previous run of this bxl function (with the same arguments) that also passed this
+    /// flag. The previous result is kept by the daemon, so it is lost when the daemon restarts.
+    #[clap(long)]
+    diff_previous: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ArgEnum)]
+#[clap(rename_all = "snake_case")]
+enum BxlOutputFormat {
+    Text,
+    Json,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -63,6 +100,12 @@ pub struct BxlCommandOptions {
     )]
     pub bxl_args: Vec<String>,
 
+    /// Read the bxl function's cli args from a JSON object in this file, or from stdin if `-`.
+    /// Keys are cli arg names and values are coerced to the declared arg types. Can be combined
+    /// with arguments passed after `--`.
+    #[clap(long, value_name = "PATH")]
+    pub args_json: Option<String>,
+
     /// Write user events to this log file. Both user and internal events are written to main event log.
     /// If this flag is specified, user events are additionally written to user event log.
     /// Log format is JSONL, uncompressed if no known extensions are detected, or you can explicitly specify
@@ -72,6 +115,32 @@ pub struct BxlCommandOptions {
     pub user_event_log: Option<PathArg>,
 }
 
+impl BxlCommandOptions {
+    /// Reads the contents of `--args-json`, if passed. The JSON is validated by the daemon
+    /// against the bxl function's declared cli args.
+    pub(crate) fn read_args_json(
+        &self,
+        working_dir: &WorkingDir,
+    ) -> anyhow::Result<Option<String>> {
+        let path = match &self.args_json {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let json = if path == "-" {
+            let mut json = String::new();
+            io::stdin()
+                .read_to_string(&mut json)
+                .context("Error reading `--args-json` from stdin")?;
+            json
+        } else {
+            let path = PathArg::from_str(path)?.resolve(working_dir);
+            fs_util::read_to_string(&path)
+                .with_context(|| format!("Error reading `--args-json` file `{}`", path))?
+        };
+        Ok(Some(json))
+    }
+}
+
 #[async_trait]
 impl StreamingCommand for BxlCommand {
     const COMMAND_NAME: &'static str = "bxl";
@@ -83,6 +152,7 @@ impl StreamingCommand for BxlCommand {
         ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
         let context = ctx.client_context(matches, &self)?;
+        let bxl_args_json = self.bxl_opts.read_args_json(&ctx.working_dir)?;
         let result = buckd
             .with_flushing()
             .bxl(
@@ -94,6 +164,21 @@ impl StreamingCommand for BxlCommand {
                     final_artifact_materializations: self.bxl_opts.materializations.to_proto()
                         as i32,
                     print_stacktrace: ctx.verbosity.print_success_stderr(),
+                    bxl_args_json,
+                    no_bxl_cache: self.no_bxl_cache,
+                    print_bxl_output_json: self.print_bxl_output == BxlOutputFormat::Json,
+                    diff_previous: self.diff_previous,
+                    materializations_report: self
+                        .materializations_report
+                        .map(|p| {
+                            p.resolve(&ctx.working_dir).into_string().with_context(|| {
+                                format!(
+                                    "Failed to convert materializations report path ({}) to string",
+                                    p.display()
+                                )
+                            })
+                        })
+                        .transpose()?,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_ops.console_opts),
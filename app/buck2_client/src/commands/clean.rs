@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -26,8 +27,10 @@ use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPath;
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
+use buck2_event_observer::humanized::HumanizedBytes;
 use dupe::Dupe;
 use gazebo::prelude::SliceExt;
+use gazebo::prelude::VecExt;
 use humantime;
 use threadpool::ThreadPool;
 use walkdir::WalkDir;
@@ -46,14 +49,17 @@ pub struct CleanCommand {
 
     #[clap(
         long = "dry-run",
-        help = "Performs a dry-run and prints the paths that would be removed."
+        help = "Performs a dry-run and prints the paths that would be removed, and how much \
+                space they use."
     )]
     dry_run: bool,
 
     #[clap(
         long = "stale",
+        alias = "older-than",
         help = "Delete artifacts from buck-out older than 1 week or older than
-the specified duration, without killing the daemon",
+the specified duration, without killing the daemon. Artifacts the daemon is
+currently using are kept.",
         value_name = "DURATION"
     )]
     stale: Option<Option<humantime::Duration>>,
@@ -115,18 +121,30 @@ async fn clean(
     lifecycle_lock: Option<&BuckdLifecycleLock>,
 ) -> anyhow::Result<()> {
     let mut paths_to_clean = Vec::new();
+    let mut dry_run_usage = None;
     // Try to clean EdenFS based buck-out first. For EdenFS based buck-out, "eden rm"
     // is efficient. Notice eden rm will remove the buck-out root directory,
     // but for the native fs, the buck-out root directory is kept.
     if let Some(paths) = try_clean_eden_buck_out(&buck_out_dir, lifecycle_lock.is_none()).await? {
         paths_to_clean = paths;
     } else if buck_out_dir.exists() {
-        paths_to_clean =
-            collect_paths_to_clean(&buck_out_dir)?.map(|path| path.display().to_string());
+        let paths = collect_paths_to_clean(&buck_out_dir)?;
+        paths_to_clean = paths.map(|path| path.display().to_string());
         if lifecycle_lock.is_some() {
             tokio::task::spawn_blocking(move || clean_buck_out_with_retry(&buck_out_dir))
                 .await?
                 .context("Failed to spawn clean")?;
+        } else {
+            dry_run_usage = Some(
+                tokio::task::spawn_blocking(move || {
+                    paths.into_map(|path| {
+                        let mut errors = Vec::new();
+                        let usage = disk_usage(path.as_path(), &mut errors);
+                        (path, usage, errors)
+                    })
+                })
+                .await?,
+            );
         }
     }
 
@@ -140,9 +158,78 @@ async fn clean(
     for path in paths_to_clean {
         console.print_stderr(&path)?;
     }
+
+    if let Some(dry_run_usage) = dry_run_usage {
+        print_disk_usage(console, &dry_run_usage)?;
+    }
     Ok(())
 }
 
+/// Space used by files under a directory.
+#[derive(Debug, Default, PartialEq)]
+struct DiskUsage {
+    files: u64,
+    bytes: u64,
+}
+
+/// Sum the sizes of all files under `path`, without following symlinks: a symlink is counted
+/// by its own size rather than that of its target. Errors (e.g. permission denied) are
+/// collected in `errors` and do not stop the scan.
+fn disk_usage(path: &Path, errors: &mut Vec<walkdir::Error>) -> DiskUsage {
+    let mut usage = DiskUsage::default();
+    for entry in WalkDir::new(path) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        // Since we don't follow links, this is the metadata of the symlink itself.
+        match entry.metadata() {
+            Ok(metadata) => {
+                usage.files += 1;
+                usage.bytes += metadata.len();
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    usage
+}
+
+fn print_disk_usage(
+    console: &FinalConsole,
+    usage: &[(AbsNormPathBuf, DiskUsage, Vec<walkdir::Error>)],
+) -> anyhow::Result<()> {
+    let mut total = DiskUsage::default();
+    for (path, dir_usage, errors) in usage {
+        for e in errors {
+            console.print_warning(&format!("Error scanning `{}`: {}", path, e))?;
+        }
+        let name = path.file_name().map_or_else(
+            || path.to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        console.print_stderr(&format!(
+            "{}: {} files, {}",
+            name,
+            dir_usage.files,
+            HumanizedBytes::new(dir_usage.bytes)
+        ))?;
+        total.files += dir_usage.files;
+        total.bytes += dir_usage.bytes;
+    }
+    console.print_stderr(&format!(
+        "Would remove {} files, {} ({} bytes)",
+        total.files,
+        HumanizedBytes::new(total.bytes),
+        total.bytes
+    ))
+}
+
 fn collect_paths_to_clean(buck_out_path: &AbsNormPathBuf) -> anyhow::Result<Vec<AbsNormPathBuf>> {
     let mut paths_to_clean = vec![];
     let dir = fs_util::read_dir(buck_out_path)?;
@@ -277,3 +364,55 @@ async fn try_clean_eden_buck_out(
 ) -> anyhow::Result<Option<Vec<String>>> {
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_usage() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let dir = temp_dir.path().join("gen");
+        std::fs::create_dir_all(dir.join("nested"))?;
+        std::fs::write(dir.join("a"), "12345")?;
+        std::fs::write(dir.join("nested/b"), "123")?;
+
+        let mut errors = Vec::new();
+        assert_eq!(
+            disk_usage(&dir, &mut errors),
+            DiskUsage { files: 2, bytes: 8 }
+        );
+        assert!(errors.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_older_than() -> anyhow::Result<()> {
+        use clap::Parser;
+
+        let command = CleanCommand::try_parse_from(["clean", "--older-than", "7d"])?;
+        assert_eq!(
+            command.stale.flatten().map(std::time::Duration::from),
+            Some(std::time::Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_usage_does_not_follow_symlinks() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let target = temp_dir.path().join("target");
+        std::fs::write(&target, vec![0u8; 1000])?;
+        let dir = temp_dir.path().join("gen");
+        std::fs::create_dir(&dir)?;
+        std::os::unix::fs::symlink(&target, dir.join("link"))?;
+
+        let mut errors = Vec::new();
+        let usage = disk_usage(&dir, &mut errors);
+        assert_eq!(usage.files, 1);
+        assert_eq!(usage.bytes, target.as_os_str().len() as u64);
+        assert!(errors.is_empty());
+        Ok(())
+    }
+}
@@ -78,6 +78,12 @@ fn format_result_stats(stats: buck2_data::CleanStaleStats) -> String {
         stats.untracked_artifact_count,
         bytesize::to_string(stats.untracked_bytes, true),
     );
+    if stats.active_stale_artifact_count > 0 {
+        output += &format!(
+            "Warning: kept {} stale artifacts which are in use by the daemon\n",
+            stats.active_stale_artifact_count,
+        );
+    }
     if stats.cleaned_path_count > 0 || stats.cleaned_bytes > 0 {
         output += &format!(
             "Cleaned {} paths ({} artifacts)\n",
@@ -160,3 +166,26 @@ impl StreamingCommand for CleanStaleCommand {
         &self.common_opts.config_opts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_result_stats_warns_about_active_artifacts() {
+        let stats = buck2_data::CleanStaleStats {
+            stale_artifact_count: 1,
+            retained_artifact_count: 3,
+            active_stale_artifact_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_result_stats(stats),
+            "Found 1 stale artifacts (0 B)\n\
+             Found 3 recent artifacts (0 B)\n\
+             Found 0 untracked artifacts (0 B)\n\
+             Warning: kept 2 stale artifacts which are in use by the daemon\n"
+        );
+        assert!(!format_result_stats(buck2_data::CleanStaleStats::default()).contains("Warning"));
+    }
+}
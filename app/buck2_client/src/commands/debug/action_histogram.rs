@@ -0,0 +1,74 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_cli_proto::UnstableActionHistogramRequest;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::streaming::StreamingCommand;
+
+/// Prints how many actions of the commands running in the daemon are in each state.
+#[derive(Debug, clap::Parser)]
+pub struct ActionHistogramCommand {}
+
+#[async_trait]
+impl StreamingCommand for ActionHistogramCommand {
+    const COMMAND_NAME: &'static str = "action_histogram";
+
+    fn existing_only() -> bool {
+        true
+    }
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        _matches: &clap::ArgMatches,
+        _ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let res = buckd
+            .with_flushing()
+            .unstable_action_histogram(UnstableActionHistogramRequest {})
+            .await?;
+
+        if res.commands.is_empty() {
+            buck2_client_ctx::println!("No active build, the daemon is idle")?;
+        }
+
+        for command in res.commands {
+            buck2_client_ctx::println!("{}: {}", command.trace_id, command.argv.join(" "))?;
+            for (state, count) in [
+                ("queued", command.queued),
+                ("running-local", command.running_local),
+                ("running-remote", command.running_remote),
+                ("cached", command.cached),
+                ("done", command.done),
+            ] {
+                buck2_client_ctx::println!("  {:<16}{:>8}", state, count)?;
+            }
+        }
+
+        ExitResult::success()
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        CommonConsoleOptions::none_ref()
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        CommonDaemonCommandOptions::default_ref()
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        CommonBuildConfigurationOptions::default_ref()
+    }
+}
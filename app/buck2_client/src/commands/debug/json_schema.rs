@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::json_schema::JsonOutput;
+
+/// Prints the JSON schema of the structured output of a command.
+///
+/// Each schema has a version in its `$id`, which is bumped whenever the output changes
+/// incompatibly.
+#[derive(Debug, clap::Parser)]
+pub struct JsonSchemaCommand {
+    #[clap(arg_enum)]
+    output: JsonOutput,
+}
+
+impl JsonSchemaCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, _ctx: ClientCommandContext<'_>) -> ExitResult {
+        buck2_client_ctx::println!("{}", serde_json::to_string_pretty(&self.output.schema())?)?;
+        ExitResult::success()
+    }
+}
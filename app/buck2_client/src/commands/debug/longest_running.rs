@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use buck2_cli_proto::UnstableLongestRunningActionsRequest;
+use buck2_cli_proto::UnstableLongestRunningActionsResponse;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::common::CommonBuildConfigurationOptions;
+use buck2_client_ctx::common::CommonConsoleOptions;
+use buck2_client_ctx::common::CommonDaemonCommandOptions;
+use buck2_client_ctx::daemon::client::BuckdClientConnector;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_client_ctx::streaming::StreamingCommand;
+use humantime::format_duration;
+
+#[derive(Debug, clap::Parser)]
+pub struct LongestRunningCommand {
+    /// Number of actions to show.
+    #[clap(short = 'n', long, default_value = "5", value_name = "N")]
+    limit: u32,
+}
+
+/// Commands are truncated to this many characters.
+const MAX_COMMAND_LEN: usize = 200;
+
+fn command_summary(command: &str) -> String {
+    match command.char_indices().nth(MAX_COMMAND_LEN) {
+        Some((end, _)) => format!("{}...", &command[..end]),
+        None => command.to_owned(),
+    }
+}
+
+fn format_response(res: UnstableLongestRunningActionsResponse) -> String {
+    let mut out = String::new();
+    if res.idle {
+        out.push_str("No active build, the daemon is idle\n");
+    } else if res.actions.is_empty() {
+        out.push_str("No actions are running\n");
+    }
+
+    for action in res.actions {
+        let elapsed = action.elapsed.map_or(Duration::ZERO, |elapsed| {
+            Duration::from_secs(elapsed.seconds as u64)
+        });
+        out.push_str(&format!(
+            "{:>10}  {:<14}  {}\n",
+            format_duration(elapsed).to_string(),
+            action.state,
+            action.identity
+        ));
+        if let Some(command) = action.command {
+            out.push_str(&format!("{:>28}{}\n", "", command_summary(&command)));
+        }
+    }
+    out
+}
+
+#[async_trait]
+impl StreamingCommand for LongestRunningCommand {
+    const COMMAND_NAME: &'static str = "longest_running";
+
+    fn existing_only() -> bool {
+        true
+    }
+
+    async fn exec_impl(
+        self,
+        buckd: &mut BuckdClientConnector,
+        _matches: &clap::ArgMatches,
+        _ctx: &mut ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let res = buckd
+            .with_flushing()
+            .unstable_longest_running_actions(UnstableLongestRunningActionsRequest {
+                limit: self.limit,
+            })
+            .await?;
+
+        buck2_client_ctx::print!("{}", format_response(res))?;
+
+        ExitResult::success()
+    }
+
+    fn console_opts(&self) -> &CommonConsoleOptions {
+        CommonConsoleOptions::none_ref()
+    }
+
+    fn event_log_opts(&self) -> &CommonDaemonCommandOptions {
+        CommonDaemonCommandOptions::default_ref()
+    }
+
+    fn common_opts(&self) -> &CommonBuildConfigurationOptions {
+        CommonBuildConfigurationOptions::default_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_summary() {
+        assert_eq!(command_summary("cc foo.c"), "cc foo.c");
+        let long = "é".repeat(MAX_COMMAND_LEN + 1);
+        assert_eq!(
+            command_summary(&long),
+            format!("{}...", "é".repeat(MAX_COMMAND_LEN))
+        );
+    }
+
+    #[test]
+    fn test_format_response() {
+        use buck2_cli_proto::unstable_longest_running_actions_response::Action;
+
+        assert_eq!(
+            format_response(UnstableLongestRunningActionsResponse {
+                actions: Vec::new(),
+                idle: true,
+            }),
+            "No active build, the daemon is idle\n"
+        );
+        assert_eq!(
+            format_response(UnstableLongestRunningActionsResponse {
+                actions: Vec::new(),
+                idle: false,
+            }),
+            "No actions are running\n"
+        );
+
+        let out = format_response(UnstableLongestRunningActionsResponse {
+            actions: vec![
+                Action {
+                    trace_id: "t".to_owned(),
+                    identity: "root//:foo (cxx_compile foo.c)".to_owned(),
+                    state: "running-local".to_owned(),
+                    command: Some("cc foo.c".to_owned()),
+                    elapsed: Some(prost_types::Duration {
+                        seconds: 125,
+                        nanos: 500,
+                    }),
+                },
+                Action {
+                    trace_id: "t".to_owned(),
+                    identity: "root//:bar (genrule)".to_owned(),
+                    state: "queued".to_owned(),
+                    command: None,
+                    elapsed: None,
+                },
+            ],
+            idle: false,
+        });
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            vec![
+                "     2m 5s  running-local   root//:foo (cxx_compile foo.c)",
+                "                            cc foo.c",
+                "        0s  queued          root//:bar (genrule)",
+            ]
+        );
+    }
+}
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use action_histogram::ActionHistogramCommand;
 use allocator_stats::AllocatorStatsCommand;
 use buck2_client_ctx::argv::Argv;
 use buck2_client_ctx::argv::SanitizedArgv;
@@ -20,6 +21,8 @@ use file_status::FileStatusCommand;
 use flush_dep_files::FlushDepFilesCommand;
 use heap_dump::HeapDumpCommand;
 use internal_version::InternalVersionCommand;
+use json_schema::JsonSchemaCommand;
+use longest_running::LongestRunningCommand;
 use materialize::MaterializeCommand;
 
 use crate::commands::debug::allocative::AllocativeCommand;
@@ -36,6 +39,7 @@ use crate::commands::debug::upload_re_logs::UploadReLogsCommand;
 use crate::commands::log::debug_replay::DebugReplayCommand;
 use crate::commands::log::debug_what_ran::DebugWhatRanCommand;
 
+mod action_histogram;
 mod allocative;
 mod allocator_stats;
 mod chrome_trace;
@@ -48,7 +52,9 @@ mod file_status;
 mod flush_dep_files;
 mod heap_dump;
 mod internal_version;
+mod json_schema;
 mod log_perf;
+mod longest_running;
 mod materialize;
 mod paranoid;
 mod persist_event_logs;
@@ -69,6 +75,11 @@ pub enum DebugCommand {
     HeapDump(HeapDumpCommand),
     /// Dumps allocator stat
     AllocatorStats(AllocatorStatsCommand),
+    /// Prints the number of actions in each state for the builds running in the daemon.
+    ActionHistogram(ActionHistogramCommand),
+    /// Prints the actions that have been running the longest in the daemon, to find wedged
+    /// actions.
+    LongestRunning(LongestRunningCommand),
     /// Dump the DICE graph to a file and saves it to disk.
     DiceDump(DiceDumpCommand),
     #[clap(setting(clap::AppSettings::Hidden))]
@@ -103,6 +114,7 @@ pub enum DebugCommand {
     #[clap(subcommand)]
     Paranoid(ParanoidCommand),
     Eval(EvalCommand),
+    JsonSchema(JsonSchemaCommand),
 }
 
 impl DebugCommand {
@@ -113,6 +125,8 @@ impl DebugCommand {
             DebugCommand::Crash(cmd) => cmd.exec(matches, ctx),
             DebugCommand::HeapDump(cmd) => cmd.exec(matches, ctx),
             DebugCommand::AllocatorStats(cmd) => cmd.exec(matches, ctx),
+            DebugCommand::ActionHistogram(cmd) => cmd.exec(matches, ctx),
+            DebugCommand::LongestRunning(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Replay(cmd) => cmd.exec(matches, ctx),
             DebugCommand::InternalVersion(cmd) => cmd.exec(matches, ctx),
             DebugCommand::ChromeTrace(cmd) => cmd.exec(matches, ctx),
@@ -131,6 +145,7 @@ impl DebugCommand {
             DebugCommand::PersistEventLogs(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Paranoid(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Eval(cmd) => cmd.exec(matches, ctx),
+            DebugCommand::JsonSchema(cmd) => cmd.exec(matches, ctx),
         }
     }
 
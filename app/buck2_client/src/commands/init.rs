@@ -22,6 +22,7 @@ use buck2_client_ctx::final_console::FinalConsole;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
 
 /// Buck2 Init
 ///
@@ -54,6 +55,13 @@ pub struct InitCommand {
     // Use git to initialize the project and pull in buck2-prelude as a submodule
     #[clap(long)]
     git: bool,
+
+    /// Scaffold the project from a template instead: a git repository, or a URL or path to a
+    /// `.tar`, `.tar.gz`, `.tgz` or `.zip` archive. The template must contain a `.buckconfig`,
+    /// and every `{{project_name}}` in its files is replaced with the project name.
+    /// The target directory must be empty.
+    #[clap(long, value_name = "URL")]
+    template: Option<String>,
 }
 
 impl InitCommand {
@@ -82,6 +90,9 @@ fn exec_impl(
     console: &FinalConsole,
 ) -> anyhow::Result<()> {
     let path = cmd.path.resolve(&ctx.working_dir);
+    if let Some(template) = &cmd.template {
+        return init_from_template(&path, template, cmd.name.as_deref(), cmd.git);
+    }
     fs_util::create_dir_all(&path)?;
     let absolute = fs_util::canonicalize(&path)?;
     let git = cmd.git;
@@ -275,6 +286,198 @@ fn set_up_project(repo_root: &AbsPath, git: bool, prelude: bool) -> anyhow::Resu
     Ok(())
 }
 
+/// Replaced with the project name in every file of a template.
+const PROJECT_NAME_PLACEHOLDER: &str = "{{project_name}}";
+
+/// Fetch `template` and install it at `path`. The template is fetched and prepared in a staging
+/// directory next to `path`, and only moved into place once complete, so failures (e.g. network
+/// errors) leave nothing behind.
+fn init_from_template(
+    path: &AbsPath,
+    template: &str,
+    name: Option<&str>,
+    git: bool,
+) -> anyhow::Result<()> {
+    if path.is_file() {
+        return Err(anyhow::anyhow!(
+            "Target path {} cannot be an existing file",
+            path.display()
+        ));
+    }
+    if path.exists() && std::fs::read_dir(path)?.next().is_some() {
+        return Err(anyhow::anyhow!(
+            "Refusing to initialize from a template into non-empty directory {}",
+            path.display()
+        ));
+    }
+    let parent = path
+        .parent()
+        .with_context(|| format!("Target path {} has no parent", path.display()))?;
+    fs_util::create_dir_all(parent)?;
+    let name = match name {
+        Some(name) => name.to_owned(),
+        None => {
+            let path = if path.exists() {
+                fs_util::canonicalize(path)?.as_path().to_owned()
+            } else {
+                path.as_path().to_owned()
+            };
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .context("Unable to derive a project name from the path, pass `--name`")?
+                .to_owned()
+        }
+    };
+
+    let staging = tempfile::Builder::new()
+        .prefix(".buck2-init-")
+        .tempdir_in(parent)
+        .context("Creating staging directory for the template")?;
+    let staging_path = AbsPath::new(staging.path())?;
+    let fetched = staging_path.join("template");
+    fetch_template(template, staging_path, &fetched)?;
+    let root = template_root(&fetched)?;
+    substitute_project_name(&root, &name)?;
+    install_template(&root, path)?;
+
+    set_up_project(path, git, false)
+}
+
+fn run_command(command: &mut Command, what: &str) -> anyhow::Result<()> {
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to run {}", what))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("{} failed with {}", what, status));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum TemplateArchive {
+    Tar,
+    Zip,
+}
+
+impl TemplateArchive {
+    fn from_url(url: &str) -> Option<TemplateArchive> {
+        let url = url.split(['?', '#']).next().unwrap_or(url);
+        if [".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tar.xz"]
+            .iter()
+            .any(|ext| url.ends_with(ext))
+        {
+            Some(TemplateArchive::Tar)
+        } else if url.ends_with(".zip") {
+            Some(TemplateArchive::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Fetch `template` into `dest`, which must not exist. Anything that isn't an archive is cloned
+/// with git.
+fn fetch_template(template: &str, staging: &AbsPath, dest: &AbsPath) -> anyhow::Result<()> {
+    let Some(archive) = TemplateArchive::from_url(template) else {
+        run_command(
+            Command::new("git")
+                .args(["clone", "--depth", "1", "--", template])
+                .arg(dest.as_path()),
+            "`git clone` of the template",
+        )?;
+        // The template's history is not the project's history.
+        fs_util::remove_all(dest.join(".git"))?;
+        return Ok(());
+    };
+
+    let archive_path = if std::path::Path::new(template).is_file() {
+        std::path::PathBuf::from(template)
+    } else {
+        let download = staging.join("template.archive");
+        run_command(
+            Command::new("curl")
+                .args([
+                    "--fail",
+                    "--silent",
+                    "--show-error",
+                    "--location",
+                    "--output",
+                ])
+                .arg(download.as_path())
+                .arg(template),
+            "Downloading the template",
+        )?;
+        download.into_path_buf()
+    };
+    fs_util::create_dir(dest)?;
+    match archive {
+        TemplateArchive::Tar => run_command(
+            Command::new("tar")
+                .arg("-xf")
+                .arg(&archive_path)
+                .arg("-C")
+                .arg(dest.as_path()),
+            "Extracting the template",
+        ),
+        TemplateArchive::Zip => run_command(
+            Command::new("unzip")
+                .arg("-q")
+                .arg(&archive_path)
+                .arg("-d")
+                .arg(dest.as_path()),
+            "Extracting the template",
+        ),
+    }
+}
+
+/// The directory of the fetched template containing its `.buckconfig`. Archives often wrap
+/// everything in a single top-level directory, which is looked through.
+fn template_root(fetched: &AbsPath) -> anyhow::Result<AbsPathBuf> {
+    if fetched.join(".buckconfig").exists() {
+        return Ok(fetched.to_owned());
+    }
+    let entries = std::fs::read_dir(fetched)?.collect::<Result<Vec<_>, _>>()?;
+    if let [entry] = entries.as_slice() {
+        let inner = fetched.join(entry.file_name());
+        if inner.is_dir() && inner.join(".buckconfig").exists() {
+            return Ok(inner);
+        }
+    }
+    Err(anyhow::anyhow!(
+        "The template does not contain a `.buckconfig`"
+    ))
+}
+
+/// Replace the project name placeholder in every text file of the template.
+fn substitute_project_name(root: &AbsPath, name: &str) -> anyhow::Result<()> {
+    for entry in walkdir::WalkDir::new(root) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let file = AbsPath::new(entry.path())?;
+        let Ok(contents) = String::from_utf8(fs_util::read(file)?) else {
+            continue;
+        };
+        if contents.contains(PROJECT_NAME_PLACEHOLDER) {
+            fs_util::write(file, contents.replace(PROJECT_NAME_PLACEHOLDER, name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Move the prepared template into `path`, which is either missing or an empty directory.
+fn install_template(root: &AbsPath, path: &AbsPath) -> anyhow::Result<()> {
+    if !path.exists() {
+        return fs_util::rename(root, path);
+    }
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        fs_util::rename(root.join(entry.file_name()), path.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use buck2_core::fs::fs_util;
@@ -282,8 +485,12 @@ mod tests {
 
     use crate::commands::init::initialize_buckconfig;
     use crate::commands::init::initialize_root_buck;
+    use crate::commands::init::install_template;
     use crate::commands::init::set_up_gitignore;
     use crate::commands::init::set_up_project;
+    use crate::commands::init::substitute_project_name;
+    use crate::commands::init::template_root;
+    use crate::commands::init::TemplateArchive;
 
     #[test]
     fn test_set_up_project_with_prelude_no_git() -> anyhow::Result<()> {
@@ -409,4 +616,68 @@ genrule(
         assert_eq!(actual_buck, expected_buck);
         Ok(())
     }
+
+    #[test]
+    fn test_template_archive_from_url() {
+        assert_eq!(
+            Some(TemplateArchive::Tar),
+            TemplateArchive::from_url("https://example.com/t.tar.gz?token=1")
+        );
+        assert_eq!(
+            Some(TemplateArchive::Zip),
+            TemplateArchive::from_url("/tmp/template.zip")
+        );
+        assert_eq!(
+            None,
+            TemplateArchive::from_url("https://github.com/example/template.git")
+        );
+    }
+
+    #[test]
+    fn test_template_root() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let tempdir_path = AbsPath::new(tempdir.path())?;
+
+        // A template without a `.buckconfig` is an error.
+        fs_util::create_dir_all(tempdir_path.join("wrapper"))?;
+        fs_util::write(tempdir_path.join("wrapper/BUCK"), "")?;
+        assert!(template_root(tempdir_path).is_err());
+
+        // A single top-level directory, like in most archives, is looked through.
+        fs_util::write(tempdir_path.join("wrapper/.buckconfig"), "")?;
+        assert_eq!(template_root(tempdir_path)?, tempdir_path.join("wrapper"));
+
+        fs_util::write(tempdir_path.join(".buckconfig"), "")?;
+        assert_eq!(template_root(tempdir_path)?, tempdir_path.to_owned());
+        Ok(())
+    }
+
+    #[test]
+    fn test_install_template() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let tempdir_path = AbsPath::new(tempdir.path())?;
+        let template = tempdir_path.join("template");
+        fs_util::create_dir_all(template.join("src"))?;
+        fs_util::write(
+            template.join(".buckconfig"),
+            "[project]\nname = {{project_name}}\n",
+        )?;
+        fs_util::write(template.join("src/BUCK"), "# {{project_name}} sources\n")?;
+
+        substitute_project_name(&template, "demo")?;
+        let project = tempdir_path.join("project");
+        fs_util::create_dir_all(&project)?;
+        install_template(&template, &project)?;
+
+        assert_eq!(
+            fs_util::read_to_string(project.join(".buckconfig"))?,
+            "[project]\nname = demo\n"
+        );
+        assert_eq!(
+            fs_util::read_to_string(project.join("src/BUCK"))?,
+            "# demo sources\n"
+        );
+        assert!(!template.join(".buckconfig").exists());
+        Ok(())
+    }
 }
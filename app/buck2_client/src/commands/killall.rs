@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use anyhow::Context;
 use buck2_client_ctx::argv::Argv;
 use buck2_client_ctx::argv::SanitizedArgv;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
@@ -15,14 +16,49 @@ use buck2_wrapper_common::is_buck2::WhoIsAsking;
 
 #[derive(Debug, clap::Parser)]
 #[clap(about = "Kill all buck2 processes on the machine")]
-pub struct KillallCommand {}
+pub struct KillallCommand {
+    /// Only kill the buck2 processes for isolation dirs matching this glob, e.g. `ci-*`.
+    #[clap(long, value_name = "GLOB")]
+    isolation_glob: Option<String>,
+}
+
+/// Find the isolation dir in the command line of a buck2 process. The daemon is always
+/// started with an explicit `--isolation-dir`.
+fn isolation_dir(cmd: &[String]) -> Option<&str> {
+    let mut args = cmd.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--isolation-dir" {
+            return args.next().map(|s| s.as_str());
+        }
+        if let Some(dir) = arg.strip_prefix("--isolation-dir=") {
+            return Some(dir);
+        }
+    }
+    None
+}
 
 impl KillallCommand {
     pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
-        ctx.instant_command("killall", async move |_ctx| {
-            buck2_wrapper_common::killall(WhoIsAsking::Buck2, |s| {
-                let _ignored = buck2_client_ctx::eprintln!("{}", s);
+        let isolation_glob = self
+            .isolation_glob
+            .as_deref()
+            .map(|glob| {
+                glob::Pattern::new(glob)
+                    .with_context(|| format!("Invalid isolation dir glob `{}`", glob))
             })
+            .transpose()?;
+
+        ctx.instant_command("killall", async move |_ctx| {
+            buck2_wrapper_common::killall_matching(
+                WhoIsAsking::Buck2,
+                |cmd| match &isolation_glob {
+                    Some(glob) => isolation_dir(cmd).map_or(false, |dir| glob.matches(dir)),
+                    None => true,
+                },
+                |s| {
+                    let _ignored = buck2_client_ctx::eprintln!("{}", s);
+                },
+            )
             .then_some(())
             .ok_or(anyhow::anyhow!("Killall command failed"))
         })
@@ -32,3 +68,25 @@ impl KillallCommand {
         argv.no_need_to_sanitize()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| (*s).to_owned()).collect()
+    }
+
+    #[test]
+    fn test_isolation_dir() {
+        assert_eq!(
+            isolation_dir(&args(&["buck2", "--isolation-dir", "ci-1", "daemon"])),
+            Some("ci-1")
+        );
+        assert_eq!(
+            isolation_dir(&args(&["buck2", "--isolation-dir=ci-2", "daemon"])),
+            Some("ci-2")
+        );
+        assert_eq!(isolation_dir(&args(&["buck2", "build"])), None);
+    }
+}
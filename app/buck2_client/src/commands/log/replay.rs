@@ -10,6 +10,7 @@
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::common::CommonConsoleOptions;
 use buck2_client_ctx::daemon::client::NoPartialResultHandler;
+use buck2_client_ctx::daemon_constraints;
 use buck2_client_ctx::events_ctx::EventsCtx;
 use buck2_client_ctx::exit_result::ExitCode;
 use buck2_client_ctx::exit_result::ExitResult;
@@ -19,6 +20,12 @@ use buck2_client_ctx::subscribers::get::get_console_with_root;
 
 use crate::commands::log::options::EventLogOptions;
 
+#[derive(Debug, thiserror::Error)]
+enum ReplayError {
+    #[error("Invalid replay speed `{0}`, expected a non-negative number")]
+    InvalidSpeed(f64),
+}
+
 /// Replay an event log.
 ///
 /// This command allows visualizing an existing event log in a Superconsole.
@@ -32,7 +39,8 @@ pub struct ReplayCommand {
 
     #[clap(
         long,
-        help = "Control the playback speed using a float (i.e. 0.5, 2, etc)",
+        help = "Control the playback speed using a float (i.e. 0.5, 2, etc). \
+            0 renders the final state of the console immediately",
         value_name = "NUMBER"
     )]
     pub speed: Option<f64>,
@@ -58,17 +66,33 @@ impl ReplayCommand {
             override_args: _,
         } = self;
 
+        if let Some(speed) = speed {
+            if !speed.is_finite() || speed < 0.0 {
+                return ExitResult::err(ReplayError::InvalidSpeed(speed).into());
+            }
+        }
+
         ctx.with_runtime(async move |mut ctx| {
             let work = async {
                 let (replayer, invocation) =
                     Replayer::new(event_log.get(&ctx).await?, speed, preload).await?;
 
+                // Events are rendered by this version of buck2, which may not understand all of
+                // them, or render them differently than the version which wrote the log did.
+                if let Some(warning) = version_skew_warning(
+                    invocation.buck2_version.as_deref(),
+                    &daemon_constraints::version(),
+                ) {
+                    buck2_client_ctx::eprintln!("{}", warning)?;
+                }
+
                 let console = get_console_with_root(
                     invocation.trace_id,
                     console_opts.console_type,
                     ctx.verbosity,
                     true,
-                    speed,
+                    // At speed 0 there is no playback time to scale.
+                    speed.filter(|speed| *speed > 0.0),
                     "(replay)", // Could be better
                     console_opts.superconsole_config(),
                 )?;
@@ -130,3 +154,34 @@ impl TryFrom<buck2_cli_proto::command_result::Result> for ReplayResult {
         Ok(Self { errors })
     }
 }
+
+fn version_skew_warning(log_version: Option<&str>, version: &str) -> Option<String> {
+    match log_version {
+        Some(log_version) if log_version == version => None,
+        Some(log_version) => Some(format!(
+            "Warning: Log was written by buck2 version `{}`, but this is version `{}`. \
+            Replay may be inaccurate.",
+            log_version, version
+        )),
+        None => Some(
+            "Warning: Log was written by an unknown version of buck2. Replay may be inaccurate."
+                .to_owned(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_skew_warning() {
+        assert_eq!(version_skew_warning(Some("abc"), "abc"), None);
+        assert_eq!(
+            version_skew_warning(Some("old"), "new").unwrap(),
+            "Warning: Log was written by buck2 version `old`, but this is version `new`. \
+            Replay may be inaccurate."
+        );
+        assert!(version_skew_warning(None, "new").is_some());
+    }
+}
@@ -22,6 +22,7 @@ use crate::commands::log::options::EventLogOptions;
 struct Stats {
     // TODO(yurysamkevich): add number of file changes since last build once availbale in log
     total_bytes_uploaded: u64,
+    cache_uploads_skipped_size: u64,
     total_files_materialized: u64,
     total_bytes_materialized: u64,
     total_local_actions: u64,
@@ -38,6 +39,11 @@ impl Stats {
                 Some(buck2_data::span_end_event::Data::ReUpload(ref data)) => {
                     self.total_bytes_uploaded += data.bytes_uploaded.unwrap_or_default();
                 }
+                Some(buck2_data::span_end_event::Data::CacheUpload(ref data)) => {
+                    if data.output_size_limit.is_some() && !data.dry_run {
+                        self.cache_uploads_skipped_size += 1;
+                    }
+                }
                 Some(buck2_data::span_end_event::Data::Materialization(ref data)) => {
                     self.total_files_materialized += data.file_count;
                     self.total_bytes_materialized += data.total_bytes;
@@ -73,6 +79,11 @@ impl Display for Stats {
             self.total_bytes_materialized
         )?;
         writeln!(f, "total bytes uploaded: {}", self.total_bytes_uploaded)?;
+        writeln!(
+            f,
+            "cache uploads skipped (outputs too large): {}",
+            self.cache_uploads_skipped_size
+        )?;
         writeln!(f, "local actions: {}", self.total_local_actions)?;
         writeln!(f, "remote actions: {}", self.total_remote_actions)?;
         writeln!(f, "other actions: {}", self.total_other_actions)?;
@@ -80,11 +91,133 @@ impl Display for Stats {
     }
 }
 
+/// Statistics about cache lookups and uploads.
+#[derive(Default)]
+struct CacheStats {
+    action_cache_hits: u64,
+    action_cache_misses: u64,
+    remote_dep_file_cache_hits: u64,
+    remote_dep_file_cache_misses: u64,
+    cache_uploads: u64,
+    cache_bytes_uploaded: u64,
+    /// Uploads which would have happened if the cache upload dry run was a real one.
+    projected_cache_uploads: u64,
+    projected_cache_bytes: u64,
+    local_actions: u64,
+    remote_actions: u64,
+}
+
+impl CacheStats {
+    fn update_with_event(&mut self, event: &buck2_data::BuckEvent) {
+        match &event.data {
+            Some(buck2_data::buck_event::Data::Instant(instant)) => match instant.data.as_ref() {
+                Some(buck2_data::instant_event::Data::ActionCacheOutcome(outcome)) => {
+                    let (hits, misses) = match outcome.cache_type() {
+                        buck2_data::CacheType::ActionCache => {
+                            (&mut self.action_cache_hits, &mut self.action_cache_misses)
+                        }
+                        buck2_data::CacheType::RemoteDepFileCache => (
+                            &mut self.remote_dep_file_cache_hits,
+                            &mut self.remote_dep_file_cache_misses,
+                        ),
+                    };
+                    if outcome.hit {
+                        *hits += 1;
+                    } else {
+                        *misses += 1;
+                    }
+                }
+                _ => {}
+            },
+            Some(buck2_data::buck_event::Data::SpanEnd(end)) => match end.data.as_ref() {
+                Some(buck2_data::span_end_event::Data::CacheUpload(ref data)) => {
+                    if data.dry_run {
+                        if data.output_size_limit.is_none() {
+                            self.projected_cache_uploads += 1;
+                            self.projected_cache_bytes += data.output_bytes.unwrap_or_default();
+                        }
+                    } else if data.success {
+                        self.cache_uploads += 1;
+                        self.cache_bytes_uploaded += data.output_bytes.unwrap_or_default();
+                    }
+                }
+                Some(buck2_data::span_end_event::Data::ActionExecution(ref data)) => {
+                    match ActionExecutionKind::from_i32(data.execution_kind) {
+                        Some(ActionExecutionKind::Local | ActionExecutionKind::LocalWorker) => {
+                            self.local_actions += 1
+                        }
+                        Some(ActionExecutionKind::Remote) => self.remote_actions += 1,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+fn hit_rate(hits: u64, misses: u64) -> String {
+    match hits + misses {
+        0 => "-".to_owned(),
+        total => format!("{:.1}%", hits as f64 * 100.0 / total as f64),
+    }
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>8} {:>8}",
+            "cache", "hits", "misses", "hit rate"
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>8} {:>8}",
+            "action cache",
+            self.action_cache_hits,
+            self.action_cache_misses,
+            hit_rate(self.action_cache_hits, self.action_cache_misses)
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>8} {:>8} {:>8}",
+            "remote dep file cache",
+            self.remote_dep_file_cache_hits,
+            self.remote_dep_file_cache_misses,
+            hit_rate(
+                self.remote_dep_file_cache_hits,
+                self.remote_dep_file_cache_misses
+            )
+        )?;
+        writeln!(
+            f,
+            "cache uploads: {} ({} bytes)",
+            self.cache_uploads, self.cache_bytes_uploaded
+        )?;
+        if self.projected_cache_uploads > 0 {
+            writeln!(
+                f,
+                "projected cache uploads (dry run): {} ({} bytes)",
+                self.projected_cache_uploads, self.projected_cache_bytes
+            )?;
+        }
+        writeln!(f, "locally executed actions: {}", self.local_actions)?;
+        write!(f, "remotely executed actions: {}", self.remote_actions)
+    }
+}
+
 /// Outputs high level statistics about the build
 #[derive(Debug, clap::Parser)]
 pub struct SummaryCommand {
     #[clap(flatten)]
     event_log: EventLogOptions,
+
+    /// Show statistics about cache lookups and uploads instead: action cache and remote dep
+    /// file cache hit rates, bytes uploaded (or projected, for a cache upload dry run), and how
+    /// many actions executed locally and remotely.
+    #[clap(long)]
+    cache: bool,
 }
 
 impl SummaryCommand {
@@ -100,17 +233,177 @@ impl SummaryCommand {
             )?;
 
             let mut stats = Stats::default();
+            let mut cache_stats = CacheStats::default();
 
             while let Some(event) = events.try_next().await? {
                 match event {
-                    StreamValue::Event(event) => stats.update_with_event(&event),
+                    StreamValue::Event(event) => {
+                        stats.update_with_event(&event);
+                        cache_stats.update_with_event(&event);
+                    }
                     StreamValue::Result(..) | StreamValue::PartialResult(..) => {}
                 }
             }
-            buck2_client_ctx::eprintln!("{}", stats)?;
+            if self.cache {
+                buck2_client_ctx::eprintln!("{}", cache_stats)?;
+            } else {
+                buck2_client_ctx::eprintln!("{}", stats)?;
+            }
             anyhow::Ok(())
         })?;
 
         ExitResult::success()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_upload_end(data: buck2_data::CacheUploadEnd) -> buck2_data::BuckEvent {
+        buck2_data::BuckEvent {
+            data: Some(buck2_data::buck_event::Data::SpanEnd(
+                buck2_data::SpanEndEvent {
+                    data: Some(buck2_data::span_end_event::Data::CacheUpload(Box::new(
+                        data,
+                    ))),
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cache_uploads_skipped_size() {
+        let mut stats = Stats::default();
+        stats.update_with_event(&cache_upload_end(buck2_data::CacheUploadEnd {
+            success: true,
+            output_bytes: Some(10),
+            ..Default::default()
+        }));
+        stats.update_with_event(&cache_upload_end(buck2_data::CacheUploadEnd {
+            success: false,
+            output_bytes: Some(200),
+            output_size_limit: Some(100),
+            ..Default::default()
+        }));
+        assert_eq!(stats.cache_uploads_skipped_size, 1);
+    }
+
+    fn instant(data: buck2_data::instant_event::Data) -> buck2_data::BuckEvent {
+        buck2_data::BuckEvent {
+            data: Some(buck2_data::buck_event::Data::Instant(
+                buck2_data::InstantEvent { data: Some(data) },
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn cache_outcome(cache_type: buck2_data::CacheType, hit: bool) -> buck2_data::BuckEvent {
+        instant(
+            buck2_data::ActionCacheOutcome {
+                cache_type: cache_type.into(),
+                hit,
+                ..Default::default()
+            }
+            .into(),
+        )
+    }
+
+    fn action_execution_end(kind: ActionExecutionKind) -> buck2_data::BuckEvent {
+        buck2_data::BuckEvent {
+            data: Some(buck2_data::buck_event::Data::SpanEnd(
+                buck2_data::SpanEndEvent {
+                    data: Some(buck2_data::span_end_event::Data::ActionExecution(Box::new(
+                        buck2_data::ActionExecutionEnd {
+                            execution_kind: kind.into(),
+                            ..Default::default()
+                        },
+                    ))),
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let events = [
+            cache_outcome(buck2_data::CacheType::ActionCache, true),
+            action_execution_end(ActionExecutionKind::ActionCache),
+            cache_outcome(buck2_data::CacheType::ActionCache, false),
+            cache_outcome(buck2_data::CacheType::RemoteDepFileCache, true),
+            cache_outcome(buck2_data::CacheType::ActionCache, false),
+            cache_outcome(buck2_data::CacheType::RemoteDepFileCache, false),
+            action_execution_end(ActionExecutionKind::Remote),
+            cache_outcome(buck2_data::CacheType::ActionCache, false),
+            cache_outcome(buck2_data::CacheType::RemoteDepFileCache, false),
+            action_execution_end(ActionExecutionKind::Local),
+            cache_upload_end(buck2_data::CacheUploadEnd {
+                success: true,
+                output_bytes: Some(10),
+                ..Default::default()
+            }),
+            cache_upload_end(buck2_data::CacheUploadEnd {
+                success: false,
+                output_bytes: Some(200),
+                ..Default::default()
+            }),
+        ];
+
+        let mut stats = CacheStats::default();
+        for event in &events {
+            stats.update_with_event(event);
+        }
+
+        assert_eq!(
+            stats.to_string(),
+            [
+                "cache                        hits   misses hit rate",
+                "action cache                    1        3    25.0%",
+                "remote dep file cache           1        2    33.3%",
+                "cache uploads: 1 (10 bytes)",
+                "locally executed actions: 1",
+                "remotely executed actions: 1",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_cache_stats_dry_run() {
+        let dry_run = |output_bytes, output_size_limit| {
+            cache_upload_end(buck2_data::CacheUploadEnd {
+                success: false,
+                dry_run: true,
+                output_bytes: Some(output_bytes),
+                output_size_limit,
+                ..Default::default()
+            })
+        };
+        let events = [
+            dry_run(10, None),
+            dry_run(20, None),
+            // Would have been rejected for being too large.
+            dry_run(200, Some(100)),
+        ];
+
+        let mut stats = Stats::default();
+        let mut cache_stats = CacheStats::default();
+        for event in &events {
+            stats.update_with_event(event);
+            cache_stats.update_with_event(event);
+        }
+
+        assert_eq!(stats.cache_uploads_skipped_size, 0);
+        assert_eq!(cache_stats.cache_uploads, 0);
+        assert_eq!(cache_stats.cache_bytes_uploaded, 0);
+        assert!(
+            cache_stats
+                .to_string()
+                .contains("projected cache uploads (dry run): 2 (30 bytes)\n")
+        );
+    }
+}
@@ -34,7 +34,10 @@ use buck2_client_ctx::streaming::StreamingCommand;
 use dupe::Dupe;
 use gazebo::prelude::VecExt;
 
-use super::bxl::BxlCommandOptions;
+use crate::commands::bxl::BxlCommandOptions;
+use crate::commands::profile::speedscope::write_speedscope_profile;
+
+mod speedscope;
 
 #[derive(Debug, clap::Parser)]
 #[clap(about = "Profiling mechanisms")]
@@ -105,6 +108,37 @@ enum BuckProfileMode {
     Typecheck,
 }
 
+impl BuckProfileMode {
+    /// The unit of the values in flame profiles, which are in folded stacks format.
+    fn flame_unit(&self) -> Option<&'static str> {
+        match self {
+            BuckProfileMode::TimeFlame => Some("milliseconds"),
+            BuckProfileMode::HeapFlameAllocated | BuckProfileMode::HeapFlameRetained => {
+                Some("bytes")
+            }
+            BuckProfileMode::HeapSummaryAllocated
+            | BuckProfileMode::HeapSummaryRetained
+            | BuckProfileMode::Statement
+            | BuckProfileMode::Bytecode
+            | BuckProfileMode::BytecodePairs
+            | BuckProfileMode::Typecheck => None,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Dupe, Clone, Copy, Debug, PartialEq, Eq)]
+enum ProfileFormat {
+    /// The format produced by the profile mode: folded stacks for flame modes, CSV otherwise.
+    Native,
+    /// Speedscope JSON, which can be opened in <https://www.speedscope.app>. Only for flame modes,
+    /// written as `profile.speedscope.json` in the output directory, next to the native outputs.
+    Speedscope,
+}
+
+#[derive(Debug, buck2_error::Error)]
+#[error("`--format speedscope` is only supported with flame profile modes, not `{0:?}`")]
+struct SpeedscopeNotSupported(BuckProfileMode);
+
 #[derive(Debug, clap::Parser)]
 pub struct BxlProfileOptions {
     #[clap(flatten)]
@@ -157,6 +191,10 @@ pub struct ProfileCommonOptions {
     /// `-allocated` means allocated memory, including memory which is later garbage collected.
     #[clap(long, short = 'm', value_enum)]
     mode: BuckProfileMode,
+
+    /// Output format.
+    #[clap(long, value_enum, default_value = "native")]
+    format: ProfileFormat,
 }
 
 pub struct ProfileSubcommand {
@@ -194,6 +232,15 @@ impl StreamingCommand for ProfileSubcommand {
 
         let profile_mode = &self.profile_common_opts.mode;
 
+        let speedscope_unit = match self.profile_common_opts.format {
+            ProfileFormat::Native => None,
+            ProfileFormat::Speedscope => Some(
+                profile_mode
+                    .flame_unit()
+                    .ok_or_else(|| SpeedscopeNotSupported(profile_mode.dupe()))?,
+            ),
+        };
+
         let destination_path = destination_path.into_string()?;
 
         let console_opts = ctx.stdin().console_interaction_stream(self.console_opts());
@@ -223,9 +270,11 @@ impl StreamingCommand for ProfileSubcommand {
                     .await??
             }
             ProfileOptionsType::BxlProfileOptions { opts } => {
+                let bxl_args_json = opts.read_args_json(&ctx.working_dir)?;
                 let bxl_opts = BxlProfile {
                     bxl_label: opts.bxl_label,
                     bxl_args: opts.bxl_args,
+                    bxl_args_json,
                 };
 
                 buckd
@@ -249,6 +298,16 @@ impl StreamingCommand for ProfileSubcommand {
             total_retained_bytes,
         } = response;
 
+        let speedscope_path = match speedscope_unit {
+            // The daemon writes the native format, so convert it next to it.
+            Some(unit) => Some(write_speedscope_profile(
+                &self.profile_common_opts.output.resolve(&ctx.working_dir),
+                &format!("{:?}", profile_mode),
+                unit,
+            )?),
+            None => None,
+        };
+
         let elapsed = elapsed
             .context("Missing duration")
             .and_then(|d| {
@@ -261,6 +320,12 @@ impl StreamingCommand for ProfileSubcommand {
             profile_mode,
             self.profile_common_opts.output.display(),
         )?;
+        if let Some(speedscope_path) = speedscope_path {
+            buck2_client_ctx::println!(
+                "Speedscope profile has been written to {}",
+                speedscope_path.display()
+            )?;
+        }
         buck2_client_ctx::println!("Elapsed: {:.3}s", elapsed.as_secs_f64())?;
         buck2_client_ctx::println!("Total retained bytes: {}", total_retained_bytes)?;
 
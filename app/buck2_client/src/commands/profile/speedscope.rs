@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Convert flame profiles to the [speedscope](https://www.speedscope.app/) file format,
+//! described in <https://github.com/jlfwong/speedscope/wiki/Importing-from-custom-sources>.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
+use serde_json::json;
+
+/// The folded stacks file the daemon writes into the output directory of flame profiles (see
+/// `buck2_profile::get_profile_response`).
+const FLAME_SRC: &str = "flame.src";
+
+/// The speedscope profile written next to it.
+const SPEEDSCOPE_JSON: &str = "profile.speedscope.json";
+
+/// Convert the folded stacks of a flame profile written to `output_dir` by the daemon into a
+/// speedscope profile in the same directory. Returns the path of the speedscope profile.
+pub(crate) fn write_speedscope_profile(
+    output_dir: &AbsPath,
+    name: &str,
+    unit: &str,
+) -> anyhow::Result<AbsPathBuf> {
+    let folded = fs_util::read_to_string(output_dir.join(FLAME_SRC))?;
+    let profile = folded_stacks_to_speedscope(&folded, name, unit)?;
+    let path = output_dir.join(SPEEDSCOPE_JSON);
+    fs_util::write(&path, serde_json::to_vec(&profile)?)
+        .context("Error writing speedscope profile")?;
+    Ok(path)
+}
+
+/// Convert a profile in folded stacks format (`a;b;c 10` per line), as produced by the flame
+/// profile modes, into a single sampled speedscope profile, where each line is one sample.
+pub(crate) fn folded_stacks_to_speedscope(
+    folded: &str,
+    name: &str,
+    unit: &str,
+) -> anyhow::Result<serde_json::Value> {
+    let mut frames: Vec<&str> = Vec::new();
+    let mut frame_indices: HashMap<&str, usize> = HashMap::new();
+    let mut samples = Vec::new();
+    let mut weights = Vec::new();
+
+    for line in folded.lines().filter(|l| !l.is_empty()) {
+        let (stack, weight) = line
+            .rsplit_once(' ')
+            .with_context(|| format!("Invalid folded stack line: `{}`", line))?;
+        let weight: u64 = weight
+            .parse()
+            .with_context(|| format!("Invalid weight in folded stack line: `{}`", line))?;
+        let sample: Vec<usize> = stack
+            .split(';')
+            .map(|frame| {
+                *frame_indices.entry(frame).or_insert_with(|| {
+                    frames.push(frame);
+                    frames.len() - 1
+                })
+            })
+            .collect();
+        samples.push(sample);
+        weights.push(weight);
+    }
+
+    let total: u64 = weights.iter().sum();
+    Ok(json!({
+        "$schema": "https://www.speedscope.app/file-format-schema.json",
+        "shared": {
+            "frames": frames.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+        },
+        "profiles": [{
+            "type": "sampled",
+            "name": name,
+            "unit": unit,
+            "startValue": 0,
+            "endValue": total,
+            "samples": samples,
+            "weights": weights,
+        }],
+        "name": name,
+        "exporter": "buck2",
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folded_stacks_to_speedscope() -> anyhow::Result<()> {
+        let profile =
+            folded_stacks_to_speedscope("a;b 10\na;c 5\na 1\n", "analysis", "milliseconds")?;
+
+        assert_eq!(
+            profile["$schema"],
+            "https://www.speedscope.app/file-format-schema.json"
+        );
+        assert_eq!(
+            profile["shared"]["frames"],
+            json!([{ "name": "a" }, { "name": "b" }, { "name": "c" }])
+        );
+        let profiles = profile["profiles"].as_array().unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0]["type"], "sampled");
+        assert_eq!(profiles[0]["unit"], "milliseconds");
+        assert_eq!(profiles[0]["endValue"], 16);
+        assert_eq!(profiles[0]["samples"], json!([[0, 1], [0, 2], [0]]));
+        assert_eq!(profiles[0]["weights"], json!([10, 5, 1]));
+
+        assert!(folded_stacks_to_speedscope("a;b ten\n", "analysis", "milliseconds").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_speedscope_profile() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let output_dir = AbsPath::new(tempdir.path())?;
+        // Flame modes write a directory with the folded stacks and an SVG rendering of them.
+        fs_util::write(output_dir.join("flame.src"), "a;b 10\na 1\n")?;
+        fs_util::write(output_dir.join("flame.svg"), "<svg/>")?;
+
+        let path = write_speedscope_profile(output_dir, "analysis", "milliseconds")?;
+
+        assert_eq!(path, output_dir.join("profile.speedscope.json"));
+        let profile: serde_json::Value = serde_json::from_str(&fs_util::read_to_string(&path)?)?;
+        assert_eq!(profile["profiles"][0]["weights"], json!([10, 1]));
+        // The native outputs are left alone.
+        assert_eq!(
+            fs_util::read_to_string(output_dir.join("flame.src"))?,
+            "a;b 10\na 1\n"
+        );
+        Ok(())
+    }
+}
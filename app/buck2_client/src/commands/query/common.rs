@@ -25,6 +25,7 @@ enum QueryOutputFormatArg {
     Dot,
     Json,
     DotCompact,
+    DotCollapsed,
 }
 
 /// Args common to all the query commands
@@ -46,6 +47,12 @@ pub(crate) struct CommonQueryOptions {
     #[clap(long, help = "Output in a more compact format than Graphviz Dot")]
     dot_compact: bool,
 
+    #[clap(
+        long,
+        help = "Output like --dot-compact, with chains of nodes collapsed into edges"
+    )]
+    dot_collapsed: bool,
+
     #[clap(
         long,
         ignore_case = true,
@@ -53,9 +60,10 @@ pub(crate) struct CommonQueryOptions {
         long_help = "Output format (default: list). \n
            dot -  dot graph format. \n
            dot_compact - compact alternative to dot format. \n
+           dot_collapsed - dot_compact with chains of single-in/single-out nodes collapsed into edges labeled with the number of nodes they replace. \n
            json - JSON format.
          ",
-        value_name = "dot|dot_compact|json",
+        value_name = "dot|dot_compact|dot_collapsed|json",
         arg_enum
     )]
     output_format: Option<QueryOutputFormatArg>,
@@ -87,6 +95,7 @@ impl CommonQueryOptions {
             Some(QueryOutputFormatArg::Json) => QueryOutputFormat::Json,
             Some(QueryOutputFormatArg::Dot) => QueryOutputFormat::Dot,
             Some(QueryOutputFormatArg::DotCompact) => QueryOutputFormat::DotCompact,
+            Some(QueryOutputFormatArg::DotCollapsed) => QueryOutputFormat::DotCollapsed,
             None => {
                 if self.json {
                     QueryOutputFormat::Json
@@ -94,6 +103,8 @@ impl CommonQueryOptions {
                     QueryOutputFormat::Dot
                 } else if self.dot_compact {
                     QueryOutputFormat::DotCompact
+                } else if self.dot_collapsed {
+                    QueryOutputFormat::DotCollapsed
                 } else {
                     QueryOutputFormat::Default
                 }
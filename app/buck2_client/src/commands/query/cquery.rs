@@ -10,6 +10,7 @@
 use async_trait::async_trait;
 use buck2_cli_proto::CqueryRequest;
 use buck2_cli_proto::CqueryResponse;
+use buck2_cli_proto::TargetUniverse;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::common::CommonBuildConfigurationOptions;
 use buck2_client_ctx::common::CommonCommandOptions;
@@ -33,7 +34,8 @@ use crate::commands::query::common::CommonQueryOptions;
 /// provided, any literals will resolve to all matching targets within the universe (which
 /// includes the targets passed as the universe and all transitive deps of them).
 /// When not provided, we implicitly set the universe to be rooted at every target literal
-/// in the `cquery`.
+/// in the `cquery`. To evaluate the query in several universes at once, pass each of them with
+/// `--separate-target-universe` instead: the results are then printed grouped by universe.
 ///
 /// Run `buck2 docs cquery` for more documentation about the functions available in cquery
 /// expressions.
@@ -68,12 +70,32 @@ pub struct CqueryCommand {
     )]
     target_universe: Vec<String>,
 
+    #[clap(
+        long,
+        value_name = "TARGETS",
+        conflicts_with = "target-universe",
+        help = "Comma separated list of targets at which to root one of several queryable
+                universes. May be repeated: the query is evaluated once per universe, and the
+                results are grouped by universe (keyed by the universe as written). JSON output
+                is a map from universe to results, other formats print each group after a
+                `#` comment line."
+    )]
+    separate_target_universe: Vec<String>,
+
     #[clap(
         long,
         help = "Show the providers of the query result instead of the attributes and labels"
     )]
     show_providers: bool,
 
+    #[clap(
+        long,
+        help = "Print the targets in dependency traversal order, repeating a target once for every \
+                dependency edge that reaches it, instead of as a set. Only supported with the \
+                default output format"
+    )]
+    keep_duplicates: bool,
+
     #[allow(rustdoc::bare_urls)]
     /// Enable deprecated `owner()` function behavior.
     ///
@@ -124,9 +146,11 @@ impl StreamingCommand for CqueryCommand {
                     context: Some(context),
                     output_attributes,
                     target_universe: self.target_universe,
+                    target_universes: split_target_universes(&self.separate_target_universe),
                     show_providers: self.show_providers,
                     unstable_output_format,
                     correct_owner,
+                    keep_duplicates: self.keep_duplicates,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -149,3 +173,68 @@ impl StreamingCommand for CqueryCommand {
         &self.common_opts.config_opts
     }
 }
+
+/// Each occurrence of `--separate-target-universe` is a universe of comma separated targets.
+fn split_target_universes(args: &[String]) -> Vec<TargetUniverse> {
+    args.iter()
+        .map(|arg| TargetUniverse {
+            targets: arg
+                .split(',')
+                .filter(|t| !t.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        })
+        .filter(|u| !u.targets.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_target_universe_flags() {
+        let parse = |args: &[&str]| {
+            CqueryCommand::try_parse_from(["cquery"].iter().chain(args).chain(&["deps(//a:a)"]))
+        };
+
+        // Repeating `-u` still adds to a single universe.
+        let cquery = parse(&["-u", "//a:a,//b:b", "-u", "//c:c"]).unwrap();
+        assert_eq!(cquery.target_universe, vec!["//a:a", "//b:b", "//c:c"]);
+        assert!(cquery.separate_target_universe.is_empty());
+
+        let cquery = parse(&[
+            "--separate-target-universe",
+            "//a:a,//b:b",
+            "--separate-target-universe",
+            "//c:c",
+        ])
+        .unwrap();
+        assert!(cquery.target_universe.is_empty());
+        assert_eq!(
+            cquery.separate_target_universe,
+            vec!["//a:a,//b:b", "//c:c"]
+        );
+
+        assert!(parse(&["-u", "//a:a", "--separate-target-universe", "//c:c"]).is_err());
+    }
+
+    #[test]
+    fn test_split_target_universes() {
+        let universes =
+            split_target_universes(&["//a:a,//b:b".to_owned(), "".to_owned(), "//c:c".to_owned()]);
+        assert_eq!(
+            universes,
+            vec![
+                TargetUniverse {
+                    targets: vec!["//a:a".to_owned(), "//b:b".to_owned()]
+                },
+                TargetUniverse {
+                    targets: vec!["//c:c".to_owned()]
+                },
+            ]
+        );
+    }
+}
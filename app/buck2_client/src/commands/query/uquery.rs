@@ -59,6 +59,14 @@ pub struct UqueryCommand {
 
     #[clap(flatten)]
     query_common: CommonQueryOptions,
+
+    #[clap(
+        long,
+        help = "Print the targets in dependency traversal order, repeating a target once for every \
+                dependency edge that reaches it, instead of as a set. Only supported with the \
+                default output format"
+    )]
+    keep_duplicates: bool,
 }
 
 #[async_trait]
@@ -85,6 +93,7 @@ impl StreamingCommand for UqueryCommand {
                     context: Some(context),
                     output_attributes,
                     unstable_output_format,
+                    keep_duplicates: self.keep_duplicates,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -28,11 +28,15 @@ use buck2_client_ctx::common::CommonConsoleOptions;
 use buck2_client_ctx::common::CommonDaemonCommandOptions;
 use buck2_client_ctx::daemon::client::BuckdClientConnector;
 use buck2_client_ctx::daemon::client::NoPartialResultHandler;
+use buck2_client_ctx::exit_result::ExecStdin;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
 use buck2_wrapper_common::BUCK2_WRAPPER_ENV_VAR;
 use buck2_wrapper_common::BUCK_WRAPPER_UUID_ENV_VAR;
+use dupe::Dupe;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -75,12 +79,31 @@ pub struct RunCommand {
     #[clap(long, group = "exec_options")]
     emit_shell: bool,
 
+    /// What the target gets as its stdin. `inherit` shares the terminal's stdin with the target,
+    /// `null` gives it an empty stdin (useful in non-interactive CI to avoid hangs on reads),
+    /// and `pipe` forwards buck2's stdin to the target through a pipe, e.g. for pipelines such as
+    /// `echo foo | buck2 run --stdin=pipe :tool`.
+    #[clap(
+        long,
+        ignore_case = true,
+        value_name = "MODE",
+        default_value = "inherit",
+        arg_enum
+    )]
+    stdin: RunStdin,
+
+    /// Read additional arguments for the target from a file, one argument per line. Blank lines
+    /// and lines starting with `#` are skipped. The arguments are appended after any arguments
+    /// passed after `--`.
+    #[clap(long, value_name = "PATH")]
+    args_file: Option<PathArg>,
+
     #[clap(name = "TARGET", help = "Target to build and run")]
     target: String,
 
     #[clap(
         name = "TARGET_ARGS",
-        help = "Additional arguments passed to the target when running it"
+        help = "Additional arguments passed to the target when running it. Arguments after `--` are passed verbatim: they are never expanded as @argfiles"
     )]
     extra_run_args: Vec<String>,
 }
@@ -96,6 +119,11 @@ impl StreamingCommand for RunCommand {
         ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
         let context = ctx.client_context(matches, &self)?;
+        // Read the args file before building so that a bad path fails fast.
+        let file_run_args = match &self.args_file {
+            Some(path) => read_args_file(&path.resolve(&ctx.working_dir))?,
+            None => Vec::new(),
+        };
         // TODO(rafaelc): fail fast on the daemon if the target doesn't have RunInfo
         let response = buckd
             .with_flushing()
@@ -116,9 +144,16 @@ impl StreamingCommand for RunCommand {
                     final_artifact_materializations: Materializations::Materialize as i32,
                     target_universe: Vec::new(),
                     output_hashes_file: None,
+                    exclude_targets: Vec::new(),
+                },
+                // When forwarding stdin to the target, don't consume any of it for console
+                // interaction during the build.
+                if self.stdin == RunStdin::Pipe {
+                    None
+                } else {
+                    ctx.stdin()
+                        .console_interaction_stream(&self.common_opts.console_opts)
                 },
-                ctx.stdin()
-                    .console_interaction_stream(&self.common_opts.console_opts),
                 &mut NoPartialResultHandler,
             )
             .await;
@@ -150,6 +185,7 @@ impl StreamingCommand for RunCommand {
         }
         let mut run_args = response.build_targets[0].run_args.clone();
         run_args.extend(self.extra_run_args);
+        run_args.extend(file_run_args);
 
         print_build_succeeded(&console, ctx)?;
 
@@ -195,6 +231,7 @@ impl StreamingCommand for RunCommand {
             run_args,
             chdir,
             vec![("BUCK_RUN_BUILD_ID".to_owned(), ctx.trace_id.to_string())],
+            self.stdin.to_exec_stdin(),
         )
     }
 
@@ -229,6 +266,43 @@ impl StreamingCommand for RunCommand {
     }
 }
 
+/// Parses the contents of an `--args-file`: one argument per line, skipping blank lines and
+/// `#` comments. Lines are taken verbatim otherwise (no quoting or escaping).
+fn parse_args_file(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            !trimmed.is_empty() && !trimmed.starts_with('#')
+        })
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+fn read_args_file(path: &AbsPathBuf) -> anyhow::Result<Vec<String>> {
+    let contents = fs_util::read_to_string(path)
+        .with_context(|| format!("Failed to read `--args-file` `{}`", path))?;
+    Ok(parse_args_file(&contents))
+}
+
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq, clap::ArgEnum)]
+#[clap(rename_all = "snake_case")]
+enum RunStdin {
+    Inherit,
+    Null,
+    Pipe,
+}
+
+impl RunStdin {
+    fn to_exec_stdin(self) -> ExecStdin {
+        match self {
+            RunStdin::Inherit => ExecStdin::Inherit,
+            RunStdin::Null => ExecStdin::Null,
+            RunStdin::Pipe => ExecStdin::Pipe,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct CommandArgsFile {
     path: String,
@@ -251,3 +325,31 @@ pub enum RunCommandError {
     )]
     MultipleTargets,
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::commands::run::parse_args_file;
+    use crate::commands::run::RunCommand;
+    use crate::commands::run::RunStdin;
+
+    #[test]
+    fn test_parse_args_file() {
+        let contents = "--foo\n\n# a comment\n  # indented comment\nbar baz\n   \n--qux=1\n";
+        assert_eq!(
+            vec!["--foo", "bar baz", "--qux=1"],
+            parse_args_file(contents)
+        );
+    }
+
+    #[test]
+    fn test_stdin() {
+        let run = RunCommand::try_parse_from(["run", ":tool"]).unwrap();
+        assert_eq!(run.stdin, RunStdin::Inherit);
+        let run = RunCommand::try_parse_from(["run", "--stdin=pipe", ":tool"]).unwrap();
+        assert_eq!(run.stdin, RunStdin::Pipe);
+        let run = RunCommand::try_parse_from(["run", "--stdin=null", ":tool"]).unwrap();
+        assert_eq!(run.stdin, RunStdin::Null);
+    }
+}
@@ -17,7 +17,13 @@ use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::streaming::StreamingCommand;
 
 #[derive(Debug, clap::Parser)]
-#[clap(about = "Start, query, and control the http server")]
+#[clap(
+    about = "Start, query, and control the http server",
+    long_about = "Start, query, and control the http server. Like any command, this restarts a \
+                  daemon which was started by a different version of buck2 (commands running on \
+                  it get the usual graceful shutdown period to finish, and are aborted after \
+                  that), and it reports the old and new versions when that happens."
+)]
 pub struct ServerCommand {}
 
 #[async_trait]
@@ -30,6 +36,18 @@ impl StreamingCommand for ServerCommand {
         _matches: &clap::ArgMatches,
         _ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
+        let version = &buckd.daemon_constraints().version;
+        // The daemon may also have been restarted for other constraints, e.g. its startup config.
+        if let Some(replaced_version) = buckd
+            .replaced_daemon_version()
+            .filter(|v| *v != version.as_str())
+        {
+            buck2_client_ctx::eprintln!(
+                "Restarted buck2 daemon due to version mismatch: old version `{}`, new version `{}`",
+                replaced_version,
+                version
+            )?;
+        }
         let status = buckd.with_flushing().status(false).await?;
         buck2_client_ctx::println!("buckd.endpoint={}", status.process_info.unwrap().endpoint)?;
         ExitResult::success()
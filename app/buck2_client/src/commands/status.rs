@@ -10,6 +10,7 @@
 use std::time::Duration;
 
 use anyhow::Context;
+use buck2_cli_proto::status_response;
 use buck2_cli_proto::StatusResponse;
 use buck2_client_ctx::argv::Argv;
 use buck2_client_ctx::argv::SanitizedArgv;
@@ -35,6 +36,11 @@ pub struct StatusCommand {
     snapshot: bool,
     #[clap(long, help = "Enable printing status for all running buckd")]
     all: bool,
+    /// Print a stable JSON object meant for scripts, see `buck2 debug json-schema status-json`.
+    /// Unlike the default output, this succeeds with `{"running": false}` when no buckd is
+    /// running.
+    #[clap(long, conflicts_with = "snapshot")]
+    json: bool,
 }
 
 impl StatusCommand {
@@ -64,13 +70,16 @@ impl StatusCommand {
                 let mut statuses = Vec::new();
                 for dir in daemon_dirs {
                     if let Ok(bootstrap_client) = establish_connection_existing(&dir).await {
-                        statuses.push(process_status(
-                            bootstrap_client
-                                .with_subscribers(vec![Box::new(StdoutStderrForwarder)])
-                                .with_flushing()
-                                .status(self.snapshot)
-                                .await?,
-                        )?);
+                        let status = bootstrap_client
+                            .with_subscribers(vec![Box::new(StdoutStderrForwarder)])
+                            .with_flushing()
+                            .status(self.snapshot)
+                            .await?;
+                        statuses.push(if self.json {
+                            stable_status(Some(status))
+                        } else {
+                            process_status(status)?
+                        });
                     }
                 }
 
@@ -80,13 +89,23 @@ impl StatusCommand {
                     .connect_buckd(BuckdConnectOptions::existing_only_no_console())
                     .await
                 {
+                    Err(_) if self.json => {
+                        buck2_client_ctx::println!(
+                            "{}",
+                            serde_json::to_string_pretty(&stable_status(None))?
+                        )?;
+                    }
                     Err(_) => {
                         buck2_client_ctx::eprintln!("no buckd running")?;
                         // Should this be an error?
                     }
                     Ok(mut client) => {
-                        let json_status =
-                            process_status(client.with_flushing().status(self.snapshot).await?)?;
+                        let status = client.with_flushing().status(self.snapshot).await?;
+                        let json_status = if self.json {
+                            stable_status(Some(status))
+                        } else {
+                            process_status(status)?
+                        };
                         buck2_client_ctx::println!(
                             "{}",
                             serde_json::to_string_pretty(&json_status)?
@@ -139,14 +158,66 @@ fn process_status(status: StatusResponse) -> anyhow::Result<serde_json::Value> {
         "isolation_dir": status.isolation_dir,
         "forkserver_pid": serde_json::to_value(status.forkserver_pid)?,
         "supports_vpnless": status.supports_vpnless.unwrap_or_default(),
+        "active_commands": status
+            .active_commands
+            .into_iter()
+            .map(active_command)
+            .collect::<Vec<_>>(),
     }))
 }
 
+fn active_command(command: status_response::ActiveCommand) -> serde_json::Value {
+    serde_json::json!({
+        "trace_id": command.trace_id,
+        "argv": command.argv,
+        "low_pass_filter": command.low_pass_filter.map(|filter| serde_json::json!({
+            "capacity": filter.capacity,
+            "occupancy": filter.occupancy,
+            "waiting": filter.waiting,
+        })),
+        "local_resources": command.local_resources.map(|resources| serde_json::json!({
+            "cpu_permits": resources.cpu_permits,
+            "cpu_permits_in_use": resources.cpu_permits_in_use,
+            "memory_bytes": resources.memory_bytes,
+        })),
+    })
+}
+
+/// The output of `--json`. Fields may be added, but existing ones must not change.
+fn stable_status(status: Option<StatusResponse>) -> serde_json::Value {
+    let status = match status {
+        None => return serde_json::json!({ "running": false }),
+        Some(status) => status,
+    };
+    let process_info = status.process_info.unwrap_or_default();
+    serde_json::json!({
+        "running": true,
+        "pid": process_info.pid,
+        "version": process_info.version,
+        "uptime_secs": status.uptime.map(|uptime| uptime.seconds),
+        "project_root": status.project_root,
+        "isolation_dir": status.isolation_dir,
+        "active_commands": status
+            .active_commands
+            .into_iter()
+            .map(active_command)
+            .collect::<Vec<_>>(),
+        "rss_bytes": status.rss_bytes,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
+    use buck2_cli_proto::status_response;
+    use buck2_cli_proto::DaemonProcessInfo;
+    use buck2_cli_proto::StatusResponse;
+    use buck2_client_ctx::json_schema::JsonOutput;
+
     use crate::commands::status::duration_to_string;
+    use crate::commands::status::process_status;
+    use crate::commands::status::stable_status;
     use crate::commands::status::timestamp_to_string;
 
     #[test]
@@ -158,6 +229,98 @@ mod tests {
         );
     }
 
+    fn status() -> StatusResponse {
+        StatusResponse {
+            process_info: Some(DaemonProcessInfo {
+                pid: 17,
+                version: "abc".to_owned(),
+                ..Default::default()
+            }),
+            uptime: Some(prost_types::Duration {
+                seconds: 60,
+                nanos: 5,
+            }),
+            isolation_dir: "v2".to_owned(),
+            active_commands: vec![
+                status_response::ActiveCommand {
+                    trace_id: "t".to_owned(),
+                    argv: vec!["buck2".to_owned(), "build".to_owned()],
+                    low_pass_filter: Some(status_response::LowPassFilter {
+                        capacity: 8,
+                        occupancy: 6,
+                        waiting: 3,
+                    }),
+                    local_resources: Some(status_response::LocalResources {
+                        cpu_permits: 8,
+                        cpu_permits_in_use: 2,
+                        memory_bytes: Some(16 << 30),
+                    }),
+                },
+                status_response::ActiveCommand {
+                    trace_id: "u".to_owned(),
+                    argv: vec!["buck2".to_owned(), "targets".to_owned()],
+                    low_pass_filter: None,
+                    local_resources: None,
+                },
+            ],
+            rss_bytes: Some(1024),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stable_status() {
+        assert_eq!(serde_json::json!({ "running": false }), stable_status(None));
+
+        let status = status();
+        assert_eq!(
+            serde_json::json!({
+                "running": true,
+                "pid": 17,
+                "version": "abc",
+                "uptime_secs": 60,
+                "project_root": "",
+                "isolation_dir": "v2",
+                "active_commands": [
+                    {
+                        "trace_id": "t",
+                        "argv": ["buck2", "build"],
+                        "low_pass_filter": {"capacity": 8, "occupancy": 6, "waiting": 3},
+                        "local_resources": {
+                            "cpu_permits": 8,
+                            "cpu_permits_in_use": 2,
+                            "memory_bytes": 17179869184u64,
+                        },
+                    },
+                    {
+                        "trace_id": "u",
+                        "argv": ["buck2", "targets"],
+                        "low_pass_filter": null,
+                        "local_resources": null,
+                    },
+                ],
+                "rss_bytes": 1024,
+            }),
+            stable_status(Some(status))
+        );
+    }
+
+    #[test]
+    fn test_status_matches_schema() -> anyhow::Result<()> {
+        JsonOutput::Status.validate(&process_status(status())?)?;
+        JsonOutput::Status.validate(&process_status(StatusResponse::default())?)?;
+        // With `--all`.
+        JsonOutput::Status.validate(&serde_json::Value::Array(vec![process_status(status())?]))?;
+
+        JsonOutput::StatusJson.validate(&stable_status(Some(status())))?;
+        JsonOutput::StatusJson.validate(&stable_status(None))?;
+        JsonOutput::StatusJson.validate(&serde_json::Value::Array(vec![
+            stable_status(Some(status())),
+            stable_status(None),
+        ]))?;
+        Ok(())
+    }
+
     #[test]
     fn test_duration_to_string() {
         assert_eq!(
@@ -55,6 +55,10 @@ pub struct SubscribeCommand {
     #[clap(long)]
     active_commands: bool,
 
+    /// Whether to request cache outcomes (hits, misses and uploads) of actions.
+    #[clap(long)]
+    cache_outcomes: bool,
+
     /// Whether to get output as JSON. The JSON format is deemed unstable so this should only be
     /// used for debugging.
     #[clap(long)]
@@ -101,15 +105,18 @@ impl StreamingCommand for SubscribeCommand {
             ok: true,
         };
 
-        let stream = if self.active_commands {
-            futures::stream::once(futures::future::ready(SubscriptionRequest {
+        let mut initial_requests = Vec::new();
+        if self.active_commands {
+            initial_requests.push(SubscriptionRequest {
                 request: Some(buck2_subscription_proto::SubscribeToActiveCommands {}.into()),
-            }))
-            .chain(stream)
-            .left_stream()
-        } else {
-            stream.right_stream()
-        };
+            });
+        }
+        if self.cache_outcomes {
+            initial_requests.push(SubscriptionRequest {
+                request: Some(buck2_subscription_proto::SubscribeToCacheOutcomes {}.into()),
+            });
+        }
+        let stream = futures::stream::iter(initial_requests).chain(stream);
 
         let stream = stream.map(|request| buck2_cli_proto::SubscriptionRequestWrapper {
             request: Some(request),
@@ -134,6 +134,12 @@ pub struct TargetsCommand {
     #[clap(flatten)]
     attributes: CommonAttributeArgs,
 
+    /// Regular expressions to match attributes, which must match the whole attribute name,
+    /// for example `--output-attribute-regex 'deps'` matches `deps` but not `exported_deps`.
+    /// Can be combined with `--output-attribute`.
+    #[clap(long, value_name = "REGEX", number_of_values = 1)]
+    output_attribute_regex: Vec<String>,
+
     /// Enables printing of default attributes. This would be attributes in a target that aren't
     /// explicitly set in the target but instead use the default set in the rule declaration.
     #[clap(long)]
@@ -206,7 +212,7 @@ impl TargetsCommand {
                 return Err(TargetsError::IncompatibleArguments.into());
             }
             Ok(OutputFormat::JsonLines)
-        } else if !self.attributes.get()?.is_empty() {
+        } else if !self.attributes.get()?.is_empty() || !self.output_attribute_regex.is_empty() {
             Ok(OutputFormat::Json)
         } else if self.package_values || !self.package_values_regex.is_empty() {
             Ok(OutputFormat::Json)
@@ -295,6 +301,7 @@ impl StreamingCommand for TargetsCommand {
             } else {
                 targets_request::Targets::Other(targets_request::Other {
                     output_attributes,
+                    output_attributes_anchored: self.output_attribute_regex.clone(),
                     target_hash_file_mode: match self.target_hash_file_mode {
                         TargetHashFileMode::PathsOnly => {
                             targets_request::TargetHashFileMode::PathsOnly as i32
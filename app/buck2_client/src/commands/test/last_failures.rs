@@ -0,0 +1,126 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Persist which test targets had failures, so that `buck2 test --rerun-failed` can run only
+//! those.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The test targets which failed in the last test run. Only one such record is kept per
+/// isolation dir, for the last run.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LastFailures {
+    /// Unconfigured target labels, as reported in `TestResponse.failed_targets`.
+    failed: BTreeSet<String>,
+    /// The names of the failed tests of those targets, if any.
+    #[serde(default)]
+    failed_tests: BTreeMap<String, Vec<String>>,
+}
+
+impl LastFailures {
+    pub(crate) fn new(
+        failed: impl IntoIterator<Item = String>,
+        failed_tests: impl IntoIterator<Item = buck2_cli_proto::FailedTests>,
+    ) -> Self {
+        Self {
+            failed: failed.into_iter().collect(),
+            failed_tests: failed_tests
+                .into_iter()
+                .map(|tests| (tests.target, tests.names))
+                .collect(),
+        }
+    }
+
+    /// Load the record of the last test run, or `None` if there was no test run yet.
+    pub(crate) fn load(path: &AbsNormPath) -> anyhow::Result<Option<Self>> {
+        let Some(contents) = fs_util::read_to_string_if_exists(path)? else {
+            return Ok(None);
+        };
+        let record = serde_json::from_str(&contents)
+            .with_context(|| format!("Error parsing last test failures in `{}`", path))?;
+        Ok(Some(record))
+    }
+
+    pub(crate) fn save(&self, path: &AbsNormPath) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs_util::create_dir_all(dir)?;
+        }
+        fs_util::write(path, serde_json::to_vec(self)?).context("Error writing last test failures")
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub(crate) fn targets(&self) -> impl Iterator<Item = &String> {
+        self.failed.iter()
+    }
+
+    /// The names of the tests of `target` which failed.
+    pub(crate) fn tests(&self, target: &str) -> &[String] {
+        self.failed_tests.get(target).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = AbsNormPathBuf::try_from(temp_dir.path().join("last_test_failures.json"))?;
+
+        // No previous run is distinct from a run without failures.
+        assert_eq!(LastFailures::load(&path)?, None);
+
+        let record = LastFailures::new(
+            ["root//:b".to_owned(), "root//:a".to_owned()],
+            [buck2_cli_proto::FailedTests {
+                target: "root//:a".to_owned(),
+                names: vec!["test_foo".to_owned()],
+            }],
+        );
+        record.save(&path)?;
+        let loaded = LastFailures::load(&path)?.unwrap();
+        assert_eq!(
+            loaded.targets().collect::<Vec<_>>(),
+            vec!["root//:a", "root//:b"]
+        );
+        assert_eq!(loaded.tests("root//:a"), ["test_foo"]);
+        // E.g. the target failed to build.
+        assert!(loaded.tests("root//:b").is_empty());
+
+        // Records written before test names were recorded still load.
+        fs_util::write(&path, r#"{"failed":["root//:a"]}"#)?;
+        assert!(
+            LastFailures::load(&path)?
+                .unwrap()
+                .tests("root//:a")
+                .is_empty()
+        );
+
+        LastFailures::new([], []).save(&path)?;
+        assert!(LastFailures::load(&path)?.unwrap().is_empty());
+
+        fs_util::write(&path, "not json")?;
+        assert!(LastFailures::load(&path).is_err());
+
+        Ok(())
+    }
+}
@@ -35,6 +35,15 @@ use superconsole::Line;
 use superconsole::Span;
 
 use crate::commands::build::print_build_result;
+use crate::commands::test::last_failures::LastFailures;
+
+mod last_failures;
+
+#[derive(Debug, thiserror::Error)]
+enum TestCommandError {
+    #[error("No previous test run to rerun the failed tests of, run `buck2 test` first")]
+    NoPreviousRun,
+}
 
 fn forward_output_to_path(
     output: &str,
@@ -126,6 +135,12 @@ If include patterns are present, regardless of whether exclude patterns are pres
     #[clap(name = "TARGET_PATTERNS", help = "Patterns to test")]
     patterns: Vec<String>,
 
+    /// Only test the targets which had failing tests, or failed to build, in the previous
+    /// `buck2 test` run. This works per target: all the tests of those targets run again, not
+    /// only the tests which failed. Targets which no longer exist are skipped with a warning.
+    #[clap(long, conflicts_with = "TARGET_PATTERNS")]
+    rerun_failed: bool,
+
     /// Writes the test executor stdout to the provided path
     ///
     /// --test-executor-stdout=- will write to stdout
@@ -169,13 +184,42 @@ impl StreamingCommand for TestCommand {
         ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
         let context = ctx.client_context(matches, &self)?;
+        let console = self.common_opts.console_opts.final_console();
+        let last_failures_path = ctx.paths()?.last_test_failures_path();
+
+        let mut build_opts = self.build_opts.to_proto();
+        let patterns = if self.rerun_failed {
+            let last_failures = match LastFailures::load(&last_failures_path)? {
+                Some(last_failures) => last_failures,
+                None => return ExitResult::err(TestCommandError::NoPreviousRun.into()),
+            };
+            if last_failures.is_empty() {
+                console.print_success("No tests failed in the previous run, nothing to rerun")?;
+                return ExitResult::success();
+            }
+            console.print_warning(&format!(
+                "Rerunning all tests of {} test targets which failed in the previous run",
+                last_failures.targets().count()
+            ))?;
+            for target in last_failures.targets() {
+                console.print_warning(&format!("  {}", target))?;
+                for test in last_failures.tests(target) {
+                    console.print_warning(&format!("    ✗ {}", test))?;
+                }
+            }
+            // A target which failed may since have been deleted or renamed.
+            build_opts.skip_missing_targets = true;
+            last_failures.targets().cloned().collect()
+        } else {
+            self.patterns
+        };
+
         let response = buckd
             .with_flushing()
             .test(
                 TestRequest {
                     context: Some(context),
-                    target_patterns: self
-                        .patterns
+                    target_patterns: patterns
                         .map(|pat| buck2_data::TargetPattern { value: pat.clone() }),
                     test_executor_args: self.test_executor_args,
                     excluded_labels: self.exclude,
@@ -184,7 +228,7 @@ impl StreamingCommand for TestCommand {
                     build_filtered_targets: self.build_filtered_targets,
                     // we don't currently have a different flag for this, so just use the build one.
                     concurrency: self.build_opts.num_threads.unwrap_or(0),
-                    build_opts: Some(self.build_opts.to_proto()),
+                    build_opts: Some(build_opts),
                     session_options: Some(TestSessionOptions {
                         allow_re: self.unstable_allow_compatible_tests_on_re
                             || self.unstable_allow_all_tests_on_re,
@@ -198,6 +242,16 @@ impl StreamingCommand for TestCommand {
             )
             .await??;
 
+        // Best-effort: failing to record this should not fail the test run.
+        if let Err(e) = LastFailures::new(
+            response.failed_targets.iter().cloned(),
+            response.failed_tests.iter().cloned(),
+        )
+        .save(&last_failures_path)
+        {
+            tracing::debug!("Failed to record last test failures: {:#}", e);
+        }
+
         let statuses = response
             .test_statuses
             .as_ref()
@@ -212,11 +266,19 @@ impl StreamingCommand for TestCommand {
         let fatals = statuses.fatals.as_ref().context("Missing `fatals`")?;
         let skipped = statuses.skipped.as_ref().context("Missing `skipped`")?;
 
-        let console = self.common_opts.console_opts.final_console();
         print_build_result(&console, &response.errors)?;
         if !response.errors.is_empty() {
             console.print_error(&format!("{} BUILDS FAILED", response.errors.len()))?;
         }
+        if !response.build_failed_targets.is_empty() {
+            console.print_error(&format!(
+                "{} TARGETS NOT TESTED DUE TO BUILD FAILURES",
+                response.build_failed_targets.len()
+            ))?;
+            for target in &response.build_failed_targets {
+                console.print_error(&format!("  ✗ {}", target))?;
+            }
+        }
 
         // TODO(nmj): Might make sense for us to expose the event ctx, and use its
         //            handle_stdout method, instead of raw buck2_client::println!s here.
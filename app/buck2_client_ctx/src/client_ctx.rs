@@ -8,6 +8,7 @@
  */
 
 use std::future::Future;
+use std::time::Duration;
 
 use anyhow::Context as _;
 use buck2_cli_proto::client_context::HostArchOverride as GrpcHostArchOverride;
@@ -31,6 +32,7 @@ use crate::daemon::client::connect::BuckdConnectOptions;
 use crate::daemon::client::BuckdClientConnector;
 use crate::exit_result::ExitResult;
 use crate::immediate_config::ImmediateConfigContext;
+use crate::path_arg::PathArg;
 use crate::restarter::Restarter;
 use crate::stdin::Stdin;
 use crate::streaming::StreamingCommand;
@@ -42,6 +44,10 @@ pub struct ClientCommandContext<'a> {
     pub paths: buck2_error::Result<InvocationPaths>,
     pub working_dir: WorkingDir,
     pub verbosity: Verbosity,
+    /// When set, the console output at the highest verbosity is also written to this file.
+    pub verbosity_file: Option<PathArg>,
+    /// When set, the last this many lines of stderr of each failed action are printed at the end.
+    pub stderr_tail: Option<usize>,
     /// When set, this function is called to launch in process daemon.
     /// The function returns `Ok` when daemon successfully started
     /// and ready to accept connections.
@@ -55,6 +61,8 @@ pub struct ClientCommandContext<'a> {
     pub runtime: &'a Runtime,
     pub oncall: Option<String>,
     pub client_metadata: Vec<ClientMetadata>,
+    /// When set, streaming commands are cancelled after running for this long.
+    pub timeout: Option<Duration>,
 }
 
 impl<'a> ClientCommandContext<'a> {
@@ -30,19 +30,32 @@ impl ClientMetadata {
     }
 }
 
+fn is_valid_key(key: &str) -> bool {
+    const REGEX_TEXT: &str = "^[a-z][a-z0-9]*(_[a-z][a-z0-9]*)*$";
+    static REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX_TEXT).unwrap());
+    REGEX.is_match(key)
+}
+
+/// Suggest a valid key for common mistakes, like `My-Key`.
+fn suggest_key(key: &str) -> String {
+    let suggestion = key.to_lowercase().replace('-', "_");
+    if is_valid_key(&suggestion) {
+        format!(" Did you mean `{}`?", suggestion)
+    } else {
+        String::new()
+    }
+}
+
 impl FromStr for ClientMetadata {
     type Err = anyhow::Error;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        const REGEX_TEXT: &str = "^[a-z][a-z0-9]*(_[a-z][a-z0-9]*)*$";
-        static REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX_TEXT).unwrap());
-
         let (key, value) = value
             .split_once('=')
             .with_context(|| ClientMetadataError::InvalidFormat(value.to_owned()))?;
 
-        if !REGEX.is_match(key) {
-            return Err(ClientMetadataError::InvalidKey(key.to_owned()).into());
+        if !is_valid_key(key) {
+            return Err(ClientMetadataError::InvalidKey(key.to_owned(), suggest_key(key)).into());
         }
 
         Ok(Self {
@@ -60,9 +73,11 @@ pub enum ClientMetadataError {
     InvalidFormat(String),
 
     #[error(
-        "Invalid client metadata key: `{0}`. Client metadata keys must be snake_case identifiers."
+        "Invalid client metadata key: `{0}`. Client metadata keys must be snake_case identifiers: \
+        lowercase letters and digits separated by single underscores, starting with a letter, \
+        e.g. `my_key=value`.{1}"
     )]
-    InvalidKey(String),
+    InvalidKey(String, String),
 }
 
 #[cfg(test)]
@@ -81,4 +96,30 @@ mod tests {
         assert!(ClientMetadata::from_str("foo").is_err());
         assert!(ClientMetadata::from_str("=foo").is_err());
     }
+
+    fn key_error(value: &str) -> String {
+        format!("{}", ClientMetadata::from_str(value).unwrap_err())
+    }
+
+    #[test]
+    fn test_invalid_key_leading_digit() {
+        let err = key_error("1key=1");
+        assert!(err.contains("`1key`"), "{}", err);
+        assert!(err.contains("e.g. `my_key=value`"), "{}", err);
+        assert!(!err.contains("Did you mean"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_key_uppercase() {
+        let err = key_error("MyKey=1");
+        assert!(err.contains("`MyKey`"), "{}", err);
+        assert!(err.contains("Did you mean `mykey`?"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_key_dashes() {
+        let err = key_error("My-Key=1");
+        assert!(err.contains("`My-Key`"), "{}", err);
+        assert!(err.contains("Did you mean `my_key`?"), "{}", err);
+    }
 }
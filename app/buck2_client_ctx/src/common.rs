@@ -354,6 +354,18 @@ pub struct CommonBuildOptions {
     #[clap(long, group = "build_strategy")]
     remote_only: bool,
 
+    /// Like `--remote-only`, but allow actions to run locally once remote execution has failed
+    /// for a number of actions in a row (3 unless the execution platform says otherwise). Use this
+    /// to keep a remote-only build going through a transient RE outage.
+    #[clap(long, group = "build_strategy")]
+    remote_only_with_local_fallback: bool,
+
+    /// Run actions whose inputs are small locally, and the others on RE, without racing them.
+    /// This suits dev loops with a cold cache, where small actions are fastest locally. The
+    /// threshold is `build.local_first_max_input_bytes` (64 MiB by default).
+    #[clap(long, group = "build_strategy")]
+    local_first_then_remote: bool,
+
     /// Enable hybrid execution. Will prefer executing actions that can execute locally on the
     /// local host.
     #[clap(long, group = "build_strategy")]
@@ -377,6 +389,11 @@ pub struct CommonBuildOptions {
     #[clap(long, requires("no-remote-cache"))]
     write_to_cache_anyway: bool,
 
+    /// Read from the remote cache, but never write to it: the results of local actions are not
+    /// uploaded, and remote actions are executed without writing their results to the cache.
+    #[clap(long, conflicts_with = "no-remote-cache")]
+    no_remote_cache_upload: bool,
+
     /// Process dep files when they are generated (i.e. after running a command that produces dep
     /// files), rather than when they are used (i.e. before re-running a command that previously
     /// produced dep files). Use this when debugging commands that produce dep files. Note that
@@ -402,6 +419,9 @@ pub struct CommonBuildOptions {
     /// `--keep-going` changes the behavior of buck to not only wait on `:bar` once one dependency
     /// of `:foo` has failed, but to additionally attempt to build other dependencies of `:foo` if
     /// possible.
+    ///
+    /// In `buck2 test`, `--fail-fast` additionally stops starting new tests once any target has
+    /// failed to build, whereas by default all tests which can be built are still run.
     #[clap(long, group = "fail-when")]
     fail_fast: bool,
 
@@ -421,9 +441,133 @@ pub struct CommonBuildOptions {
     #[clap(long)]
     skip_incompatible_targets: bool,
 
-    /// Materializes inputs for failed actions which ran on RE
+    /// Materializes inputs for failed actions which ran on RE, and lists where they were put in
+    /// the action's error. Actions that ran locally already have their inputs on disk.
     #[clap(long)]
     materialize_failed_inputs: bool,
+
+    /// Maximum time, in milliseconds, that actions may spend queued on RE before failing. This
+    /// overrides `re_max_queue_time_ms` from the executor configuration for this invocation, which
+    /// is useful to fall back to local execution quickly when RE is slow. 0 means no override.
+    #[clap(long, value_name = "MILLISECONDS")]
+    max_re_queue_time: Option<u64>,
+
+    /// RE use case to use for all actions, instead of the one from the executor configuration.
+    /// Useful to try out a different RE pool without changing execution platforms.
+    #[clap(long, value_name = "USE_CASE", parse(try_from_str = parse_re_use_case))]
+    re_use_case: Option<String>,
+
+    /// Key attached to all remote actions and action cache lookups of this invocation, instead of
+    /// the `re_action_key` from the executor configuration. Useful to attribute or group the
+    /// actions of e.g. a CI job on the RE backend.
+    #[clap(long, value_name = "KEY", parse(try_from_str = parse_re_action_key))]
+    re_action_key: Option<String>,
+
+    /// Resources available to local actions, as comma-separated `cpu=COUNT` and `memory=SIZE`
+    /// (e.g. `cpu=8,memory=16g`). Fewer CPUs than `-j` means fewer concurrent local actions.
+    /// Requests for more than the machine has are clamped to what it has. The memory budget is
+    /// only reported by `buck2 status`: it does not limit local actions, since they don't declare
+    /// how much memory they use.
+    #[clap(long, value_name = "RESOURCES", parse(try_from_str = parse_local_resources))]
+    local_resources: Option<LocalResourceLimits>,
+}
+
+/// Limits on the resources used by local actions, from `--local-resources`.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize
+)]
+pub struct LocalResourceLimits {
+    pub cpu: Option<u32>,
+    pub memory_bytes: Option<u64>,
+}
+
+/// Parses `--local-resources`, e.g. `cpu=8,memory=16g`.
+pub fn parse_local_resources(s: &str) -> anyhow::Result<LocalResourceLimits> {
+    let mut resources = LocalResourceLimits::default();
+    for entry in s.split(',') {
+        let (key, value) = entry.split_once('=').ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid local resource `{}`, expected `cpu=COUNT` or `memory=SIZE`",
+                entry
+            )
+        })?;
+        let value = value.trim();
+        match key.trim() {
+            "cpu" => {
+                resources.cpu = Some(
+                    value
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Invalid CPU count `{}`", value))?,
+                )
+            }
+            "memory" => resources.memory_bytes = Some(parse_memory_size(value)?),
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unknown local resource `{}`, expected `cpu` or `memory`",
+                    other
+                ));
+            }
+        }
+    }
+    Ok(resources)
+}
+
+/// Parses a number of bytes with an optional binary unit, e.g. `512m` or `16g`.
+fn parse_memory_size(s: &str) -> anyhow::Result<u64> {
+    let lower = s.to_ascii_lowercase();
+    let number = lower.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let shift = match &lower[number.len()..] {
+        "" | "b" => 0,
+        "k" | "kb" => 10,
+        "m" | "mb" => 20,
+        "g" | "gb" => 30,
+        "t" | "tb" => 40,
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Invalid memory size `{}`, expected a number of bytes with an optional unit \
+                (`k`, `m`, `g` or `t`)",
+                s
+            ));
+        }
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid memory size `{}`", s))?;
+    number
+        .checked_mul(1 << shift)
+        .ok_or_else(|| anyhow::anyhow!("Memory size `{}` is too large", s))
+}
+
+/// Validates a RE action key passed on the command line.
+pub fn parse_re_action_key(s: &str) -> anyhow::Result<String> {
+    if s.is_empty() {
+        return Err(anyhow::anyhow!("RE action key must not be empty"));
+    }
+    Ok(s.to_owned())
+}
+
+/// Validates a RE use case passed on the command line.
+pub fn parse_re_use_case(s: &str) -> anyhow::Result<String> {
+    if s.is_empty() {
+        return Err(anyhow::anyhow!("RE use case must not be empty"));
+    }
+    if !s
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid RE use case `{}`, expected only letters, digits, `-`, `_` and `.`",
+            s
+        ));
+    }
+    Ok(s.to_owned())
 }
 
 impl CommonBuildOptions {
@@ -447,6 +591,10 @@ impl CommonBuildOptions {
                 ExecutionStrategy::LocalOnly as i32
             } else if self.remote_only {
                 ExecutionStrategy::RemoteOnly as i32
+            } else if self.remote_only_with_local_fallback {
+                ExecutionStrategy::RemoteOnlyWithLocalFallback as i32
+            } else if self.local_first_then_remote {
+                ExecutionStrategy::LocalFirstThenRemote as i32
             } else if self.prefer_local {
                 ExecutionStrategy::HybridPreferLocal as i32
             } else if self.prefer_remote {
@@ -461,12 +609,22 @@ impl CommonBuildOptions {
             eager_dep_files: self.eager_dep_files,
             upload_all_actions: self.upload_all_actions,
             skip_cache_read: self.no_remote_cache,
-            skip_cache_write: self.no_remote_cache && !self.write_to_cache_anyway,
+            skip_cache_write: (self.no_remote_cache && !self.write_to_cache_anyway)
+                || self.no_remote_cache_upload,
             fail_fast: self.fail_fast,
             keep_going: self.keep_going,
             skip_missing_targets: self.skip_missing_targets,
             skip_incompatible_targets: self.skip_incompatible_targets,
             materialize_failed_inputs: self.materialize_failed_inputs,
+            max_re_queue_time_ms: self.max_re_queue_time.unwrap_or_default(),
+            re_use_case_override: self.re_use_case.clone().unwrap_or_default(),
+            re_action_key_override: self.re_action_key.clone().unwrap_or_default(),
+            local_resources: self.local_resources.map(|resources| {
+                buck2_cli_proto::LocalResources {
+                    cpu: resources.cpu.unwrap_or_default(),
+                    memory_bytes: resources.memory_bytes.unwrap_or_default(),
+                }
+            }),
         }
     }
 }
@@ -594,3 +752,54 @@ pub struct CommonCommandOptions {
     #[clap(flatten)]
     pub event_log_opts: CommonDaemonCommandOptions,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_local_resources() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_local_resources("cpu=8,memory=16g")?,
+            LocalResourceLimits {
+                cpu: Some(8),
+                memory_bytes: Some(16 << 30),
+            }
+        );
+        assert_eq!(
+            parse_local_resources("memory=512M")?,
+            LocalResourceLimits {
+                cpu: None,
+                memory_bytes: Some(512 << 20),
+            }
+        );
+        assert_eq!(
+            parse_local_resources("memory=1000")?.memory_bytes,
+            Some(1000)
+        );
+        assert!(parse_local_resources("cpu").is_err());
+        assert!(parse_local_resources("cpu=-1").is_err());
+        assert!(parse_local_resources("gpu=1").is_err());
+        assert!(parse_local_resources("memory=16x").is_err());
+        assert!(parse_local_resources("memory=99999999999t").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_re_use_case() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_re_use_case("my-experimental_pool.v2")?,
+            "my-experimental_pool.v2"
+        );
+        assert_eq!(
+            parse_re_use_case("").unwrap_err().to_string(),
+            "RE use case must not be empty"
+        );
+        assert_eq!(
+            parse_re_use_case("my pool").unwrap_err().to_string(),
+            "Invalid RE use case `my pool`, expected only letters, digits, `-`, `_` and `.`"
+        );
+        assert!(parse_re_use_case("pool/1").is_err());
+        Ok(())
+    }
+}
@@ -15,6 +15,7 @@ use std::net::Ipv4Addr;
 use std::time::Duration;
 
 use anyhow::Context;
+use async_trait::async_trait;
 use buck2_cli_proto::daemon_api_client::DaemonApiClient;
 use buck2_cli_proto::DaemonProcessInfo;
 use buck2_common::buckd_connection::ConnectionType;
@@ -443,6 +444,7 @@ impl BuckdChannel {
             daemon_dir,
             client,
             constraints,
+            replaced_daemon_version: None,
         })
     }
 }
@@ -455,6 +457,9 @@ pub struct BootstrapBuckdClient {
     client: DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>,
     /// The constraints for the daemon we're connected to.
     constraints: buck2_cli_proto::DaemonConstraints,
+    /// The version of the daemon that was killed to start this one because it did not satisfy
+    /// the constraints, if any.
+    replaced_daemon_version: Option<String>,
 }
 
 impl BootstrapBuckdClient {
@@ -500,6 +505,7 @@ impl BootstrapBuckdClient {
                 daemon_dir: self.daemon_dir,
                 client: self.client,
                 constraints: self.constraints,
+                replaced_daemon_version: self.replaced_daemon_version,
                 events_ctx: EventsCtx::new(subscribers),
                 tailers: None,
             },
@@ -621,17 +627,12 @@ async fn establish_connection_inner(
 
     // Even if we didn't connect before, it's possible that we just raced with another invocation
     // starting the server, so we try to connect again while holding the lock.
+    let mut replaced_daemon_version = None;
     if let Ok(channel) = try_connect_existing(&daemon_dir, &deadline).await {
-        let mut client = channel.upgrade().await?;
-        if constraints.satisfied(&client.constraints) {
-            return Ok(client);
+        match accept_or_kill(channel.upgrade().await?, &constraints, &deadline).await? {
+            RunningDaemonOutcome::Accepted(client) => return Ok(client),
+            RunningDaemonOutcome::Killed { version } => replaced_daemon_version = Some(version),
         }
-        deadline
-            .run(
-                "sending kill command to the Buck daemon",
-                client.kill_for_constraints_mismatch(),
-            )
-            .await?;
     }
 
     // Daemon dir may be corrupted. Safer to delete it.
@@ -654,7 +655,8 @@ async fn establish_connection_inner(
         )
         .await?;
 
-    let client = channel.upgrade().await?;
+    let mut client = channel.upgrade().await?;
+    client.replaced_daemon_version = replaced_daemon_version;
 
     if !constraints.satisfied(&client.constraints) {
         return Err(BuckdConnectError::BuckDaemonConstraintWrongAfterStart {
@@ -667,6 +669,55 @@ async fn establish_connection_inner(
     Ok(client)
 }
 
+/// A daemon found running while holding the lifecycle lock.
+#[async_trait]
+trait RunningDaemon {
+    fn constraints(&self) -> &buck2_cli_proto::DaemonConstraints;
+
+    async fn kill_for_constraints_mismatch(&mut self) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl RunningDaemon for BootstrapBuckdClient {
+    fn constraints(&self) -> &buck2_cli_proto::DaemonConstraints {
+        &self.constraints
+    }
+
+    async fn kill_for_constraints_mismatch(&mut self) -> anyhow::Result<()> {
+        BootstrapBuckdClient::kill_for_constraints_mismatch(self).await?;
+        Ok(())
+    }
+}
+
+enum RunningDaemonOutcome<D> {
+    Accepted(D),
+    /// The daemon did not satisfy the constraints and was killed.
+    Killed {
+        version: String,
+    },
+}
+
+/// Keep the running daemon if it satisfies the constraints, and kill it otherwise, remembering its
+/// version so that the restart can be reported.
+async fn accept_or_kill<D: RunningDaemon + Send>(
+    mut daemon: D,
+    constraints: &DaemonConstraintsRequest,
+    deadline: &StartupDeadline,
+) -> anyhow::Result<RunningDaemonOutcome<D>> {
+    if constraints.satisfied(daemon.constraints()) {
+        return Ok(RunningDaemonOutcome::Accepted(daemon));
+    }
+    deadline
+        .run(
+            "sending kill command to the Buck daemon",
+            daemon.kill_for_constraints_mismatch(),
+        )
+        .await?;
+    Ok(RunningDaemonOutcome::Killed {
+        version: daemon.constraints().version.clone(),
+    })
+}
+
 enum ConnectBeforeRestart {
     Accepted(BootstrapBuckdClient),
     Rejected,
@@ -824,6 +875,10 @@ fn daemon_connect_error(paths: &InvocationPaths) -> BuckdConnectError {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
     use super::*;
 
     fn constraints(trace_io_enabled: bool) -> buck2_cli_proto::DaemonConstraints {
@@ -941,6 +996,53 @@ mod tests {
         assert!(!req.satisfied(&daemon));
     }
 
+    struct FakeDaemon {
+        constraints: buck2_cli_proto::DaemonConstraints,
+        killed: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl RunningDaemon for FakeDaemon {
+        fn constraints(&self) -> &buck2_cli_proto::DaemonConstraints {
+            &self.constraints
+        }
+
+        async fn kill_for_constraints_mismatch(&mut self) -> anyhow::Result<()> {
+            self.killed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_version_mismatch_replaces_daemon() -> anyhow::Result<()> {
+        let deadline = StartupDeadline::duration_from_now(Duration::from_secs(60))?;
+        let req = request(DesiredTraceIoState::Existing);
+
+        let killed = Arc::new(AtomicBool::new(false));
+        let same_version = FakeDaemon {
+            constraints: constraints(false),
+            killed: killed.dupe(),
+        };
+        assert!(matches!(
+            accept_or_kill(same_version, &req, &deadline).await?,
+            RunningDaemonOutcome::Accepted(_)
+        ));
+        assert!(!killed.load(Ordering::SeqCst));
+
+        let mut old_version = FakeDaemon {
+            constraints: constraints(false),
+            killed: killed.dupe(),
+        };
+        old_version.constraints.version = "old".to_owned();
+        match accept_or_kill(old_version, &req, &deadline).await? {
+            RunningDaemonOutcome::Killed { version } => assert_eq!(version, "old"),
+            RunningDaemonOutcome::Accepted(_) => panic!("The daemon should be killed"),
+        }
+        assert!(killed.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+
     #[test]
     fn test_daemon_buster() {
         let mut req = DaemonConstraintsRequest {
@@ -70,6 +70,12 @@ impl<'a> BuckdClientConnector<'a> {
         &self.client.constraints
     }
 
+    /// The version of the daemon that was killed while connecting because it did not satisfy the
+    /// constraints of this client, if any.
+    pub fn replaced_daemon_version(&self) -> Option<&str> {
+        self.client.replaced_daemon_version.as_deref()
+    }
+
     pub fn error_observers(&self) -> impl Iterator<Item = &dyn ErrorObserver> {
         self.client
             .events_ctx
@@ -147,6 +153,7 @@ impl Drop for BuckdLifecycleLock {
 pub struct BuckdClient<'a> {
     client: DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>,
     constraints: buck2_cli_proto::DaemonConstraints,
+    replaced_daemon_version: Option<String>,
     daemon_dir: DaemonDir,
     // TODO(brasselsprouts): events_ctx should own tailers
     tailers: Option<FileTailers>,
@@ -553,6 +560,16 @@ impl<'a, 'b> FlushingBuckdClient<'a, 'b> {
         UnstableAllocatorStatsRequest,
         UnstableAllocatorStatsResponse
     );
+    debug_method!(
+        unstable_action_histogram,
+        UnstableActionHistogramRequest,
+        UnstableActionHistogramResponse
+    );
+    debug_method!(
+        unstable_longest_running_actions,
+        UnstableLongestRunningActionsRequest,
+        UnstableLongestRunningActionsResponse
+    );
     debug_method!(
         unstable_dice_dump,
         UnstableDiceDumpRequest,
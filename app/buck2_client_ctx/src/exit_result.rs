@@ -13,14 +13,28 @@ use std::io;
 use std::io::Write;
 use std::ops::FromResidual;
 use std::process::Command;
+use std::process::Stdio;
 
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
+use dupe::Dupe;
 
 pub struct ExecArgs {
     prog: String,
     argv: Vec<String>,
     chdir: Option<AbsPathBuf>,
     env: Vec<(String, String)>,
+    stdin: ExecStdin,
+}
+
+/// What the process launched by `buck2 run` gets as its stdin.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum ExecStdin {
+    /// Share the client's stdin with the new process.
+    Inherit,
+    /// Connect stdin to the null device, so reads see EOF immediately.
+    Null,
+    /// Spawn the process with a pipe as stdin and forward the client's stdin into it.
+    Pipe,
 }
 
 /// ExitResult represents the outcome of a process execution where we care to return a specific
@@ -31,6 +45,8 @@ pub struct ExecArgs {
 /// - Uncategorized Error : 1
 /// - Infra Error         : 2
 /// - User Error          : 3
+/// - Timeout             : 5
+/// - Crash Loop          : 12
 /// - Signal Interruption : 129-192 (128 + signal number)
 ///
 /// We can easily turn a anyhow::Result (or anyhow::Error, or even a message) into a ExitResult,
@@ -84,6 +100,7 @@ impl ExitResult {
         argv: Vec<String>,
         chdir: Option<AbsPathBuf>,
         env: Vec<(String, String)>,
+        stdin: ExecStdin,
     ) -> Self {
         Self {
             variant: ExitResultVariant::Buck2RunExec(ExecArgs {
@@ -91,6 +108,7 @@ impl ExitResult {
                 argv,
                 chdir,
                 env,
+                stdin,
             }),
             stdout: Vec::new(),
         }
@@ -135,6 +153,35 @@ impl ExitResult {
         self
     }
 
+    #[cfg(test)]
+    pub(crate) fn exit_code(self) -> Option<u8> {
+        match self.variant {
+            ExitResultVariant::Status(v) | ExitResultVariant::StatusWithErr(v, _) => {
+                Some(v.exit_code())
+            }
+            ExitResultVariant::Buck2RunExec(..) => None,
+        }
+    }
+
+    /// Print the stdout and the error of this result like `report`, but without exiting. This is
+    /// for commands which produce several results, like `buck2 build --watch`.
+    pub fn print(self) -> anyhow::Result<()> {
+        crate::stdio::print_bytes(&self.stdout)?;
+        match self.variant {
+            ExitResultVariant::Status(_) => {}
+            ExitResultVariant::Buck2RunExec(args) => {
+                return Err(anyhow::anyhow!(
+                    "Cannot exec `{}` from a command which keeps running",
+                    args.prog
+                ));
+            }
+            ExitResultVariant::StatusWithErr(_, e) => {
+                crate::eprintln!("Command failed: {:?}", e)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn report(self) -> ! {
         match crate::stdio::print_bytes(&self.stdout) {
             Ok(()) => self.variant.report(),
@@ -264,8 +311,14 @@ pub enum ExitCode {
     InfraError,
     UserError,
     DaemonIsBusy,
+    /// The command ran for longer than `--timeout` and was cancelled.
+    Timeout,
     ConnectError,
+    /// The same command repeatedly crashed the daemon, so we stopped restarting it.
+    CrashLoop,
     SignalInterrupt,
+    /// The client received SIGTERM, and cancelled the command.
+    SignalTerminate,
     BrokenPipe,
     /// Something other than buck2 itself (usually a test runner) explicitly requested that this
     /// exit code be returned
@@ -281,9 +334,12 @@ impl ExitCode {
             InfraError => 2,
             UserError => 3,
             DaemonIsBusy => 4,
+            Timeout => 5,
             ConnectError => 11,
+            CrashLoop => 12,
             BrokenPipe => 130,
             SignalInterrupt => 141,
+            SignalTerminate => 143,
             Explicit(code) => code,
         }
     }
@@ -306,6 +362,21 @@ fn do_exec(command: &mut Command) -> anyhow::Error {
     command.exec().into()
 }
 
+/// Spawns the command with a pipe as its stdin, copies the client's stdin into it, and waits for
+/// the command to exit. Returns the exit code of the command.
+fn spawn_with_piped_stdin(command: &mut Command) -> anyhow::Result<i32> {
+    command.stdin(Stdio::piped());
+    let mut child = command.spawn()?;
+    let mut child_stdin = child.stdin.take().expect("stdin was piped");
+    std::thread::spawn(move || {
+        // The child may exit without reading all of its input, in which case we get a broken
+        // pipe here, which is fine. Dropping `child_stdin` on EOF closes the pipe.
+        let _ignored = io::copy(&mut io::stdin().lock(), &mut child_stdin);
+    });
+    let status = child.wait()?;
+    Ok(status.code().unwrap_or(1))
+}
+
 /// Invokes the given program with the given argv and replaces the program image with the new program.
 /// Does not return.
 fn execv(args: ExecArgs) -> ! {
@@ -321,7 +392,18 @@ fn execv(args: ExecArgs) -> ! {
         // Same as above.
         command.env(k, v);
     }
-    let err = do_exec(&mut command).context(format!(
+    let err = match args.stdin {
+        ExecStdin::Inherit => do_exec(&mut command),
+        ExecStdin::Null => {
+            command.stdin(Stdio::null());
+            do_exec(&mut command)
+        }
+        ExecStdin::Pipe => match spawn_with_piped_stdin(&mut command) {
+            Ok(code) => unsafe { libc::_exit(code as libc::c_int) },
+            Err(e) => e,
+        },
+    };
+    let err = err.context(format!(
         "Failed to execute target process, running {:?} {:?}",
         args.prog, args.argv
     ));
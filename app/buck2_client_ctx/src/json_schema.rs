@@ -0,0 +1,411 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! JSON schemas of the structured outputs of commands, printed by `buck2 debug json-schema`.
+//!
+//! Each schema has a version in its `$id`, which is bumped whenever the output changes
+//! incompatibly. Update the schema here whenever you change one of these outputs; the tests of
+//! each output validate it against its schema.
+
+use anyhow::Context;
+use serde_json::json;
+
+/// A command output with a schema.
+#[derive(Debug, Clone, Copy, clap::ArgEnum)]
+pub enum JsonOutput {
+    /// `buck2 build --show-json-output`.
+    BuildOutputs,
+    /// `buck2 status`.
+    Status,
+    /// `buck2 status --json`.
+    StatusJson,
+    /// `buck2 targets --json`.
+    Targets,
+    /// `buck2 lint --json` and `buck2 starlark lint --json`, one object per line.
+    Lint,
+    /// `buck2 audit action-env --json`.
+    AuditActionEnv,
+}
+
+impl JsonOutput {
+    fn name(self) -> &'static str {
+        match self {
+            JsonOutput::BuildOutputs => "build-outputs",
+            JsonOutput::Status => "status",
+            JsonOutput::StatusJson => "status-json",
+            JsonOutput::Targets => "targets",
+            JsonOutput::Lint => "lint",
+            JsonOutput::AuditActionEnv => "audit-action-env",
+        }
+    }
+
+    fn version(self) -> u32 {
+        match self {
+            JsonOutput::BuildOutputs
+            | JsonOutput::Status
+            | JsonOutput::StatusJson
+            | JsonOutput::Targets
+            | JsonOutput::Lint
+            | JsonOutput::AuditActionEnv => 1,
+        }
+    }
+
+    fn body(self) -> serde_json::Value {
+        match self {
+            JsonOutput::BuildOutputs => json!({
+                "description": "Map from target label to the path of its default output.",
+                "type": "object",
+                "additionalProperties": { "type": "string" },
+            }),
+            JsonOutput::Status => json!({
+                "description": "Status of the daemon, or a list of them with `--all`.",
+                "oneOf": [
+                    { "$ref": "#/definitions/status" },
+                    { "type": "array", "items": { "$ref": "#/definitions/status" } },
+                ],
+                "definitions": {
+                    "status": {
+                        "type": "object",
+                        "properties": {
+                            "start_time": { "type": "string" },
+                            "uptime": { "type": "string" },
+                            "process_info": { "type": ["object", "null"] },
+                            "daemon_constraints": { "type": ["object", "null"] },
+                            "snapshot": { "type": ["object", "null"] },
+                            "project_root": { "type": "string" },
+                            "isolation_dir": { "type": "string" },
+                            "forkserver_pid": { "type": ["integer", "null"] },
+                            "supports_vpnless": { "type": "boolean" },
+                        },
+                        "required": ["start_time", "uptime", "project_root", "isolation_dir"],
+                    },
+                },
+            }),
+            JsonOutput::StatusJson => json!({
+                "description": "Status of the daemon, or a list of them with `--all`.",
+                "oneOf": [
+                    { "$ref": "#/definitions/status" },
+                    { "type": "array", "items": { "$ref": "#/definitions/status" } },
+                ],
+                "definitions": {
+                    "status": {
+                        "type": "object",
+                        "properties": {
+                            "running": { "type": "boolean" },
+                            "pid": { "type": "integer" },
+                            "version": { "type": "string" },
+                            "uptime_secs": { "type": ["integer", "null"] },
+                            "project_root": { "type": "string" },
+                            "isolation_dir": { "type": "string" },
+                            "active_commands": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "trace_id": { "type": "string" },
+                                        "argv": { "type": "array", "items": { "type": "string" } },
+                                        "low_pass_filter": {
+                                            "description": "Usage of the low pass filter limiting concurrent heavy actions, in action weights. Null until the command has set up its executors.",
+                                            "type": ["object", "null"],
+                                            "properties": {
+                                                "capacity": { "type": "integer" },
+                                                "occupancy": { "type": "integer" },
+                                                "waiting": { "type": "integer" },
+                                            },
+                                        },
+                                        "local_resources": {
+                                            "description": "Local resources of the command: permits of the host sharing broker (one per CPU), how many local actions hold, and the memory budget from `--local-resources`. Null until the command has set up its executors.",
+                                            "type": ["object", "null"],
+                                            "properties": {
+                                                "cpu_permits": { "type": "integer" },
+                                                "cpu_permits_in_use": { "type": "integer" },
+                                                "memory_bytes": { "type": ["integer", "null"] },
+                                            },
+                                        },
+                                    },
+                                },
+                            },
+                            "rss_bytes": { "type": ["integer", "null"] },
+                        },
+                        "required": ["running"],
+                    },
+                },
+            }),
+            JsonOutput::Targets => json!({
+                "description": "One object per target, with the requested attributes.",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "buck.type": { "type": "string" },
+                        "buck.package": { "type": "string" },
+                        "name": { "type": "string" },
+                    },
+                    "additionalProperties": true,
+                },
+            }),
+            JsonOutput::Lint => json!({
+                "description": "A single lint, printed one per line.",
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "line": { "type": ["integer", "null"], "minimum": 1 },
+                    "char": { "type": ["integer", "null"], "minimum": 1 },
+                    "code": { "type": "string" },
+                    "severity": { "enum": ["error", "warning", "advice", "disabled"] },
+                    "name": { "type": "string" },
+                    "description": { "type": ["string", "null"] },
+                    "original": { "type": "string" },
+                },
+                "required": ["path", "line", "char", "code", "severity", "name", "description"],
+            }),
+            JsonOutput::AuditActionEnv => json!({
+                "description": "Environment variables set for the action.",
+                "type": "object",
+                "additionalProperties": { "type": "string" },
+            }),
+        }
+    }
+
+    /// The schema, with its version in `$id`.
+    pub fn schema(self) -> serde_json::Value {
+        let mut schema = serde_json::Map::new();
+        schema.insert(
+            "$schema".to_owned(),
+            json!("http://json-schema.org/draft-07/schema#"),
+        );
+        schema.insert(
+            "$id".to_owned(),
+            json!(format!(
+                "buck2://json-schema/{}/v{}",
+                self.name(),
+                self.version()
+            )),
+        );
+        schema.insert("title".to_owned(), json!(self.name()));
+        if let serde_json::Value::Object(body) = self.body() {
+            schema.extend(body);
+        }
+        serde_json::Value::Object(schema)
+    }
+
+    /// Check that `value` is valid output according to the schema. Only the keywords the schemas
+    /// here use are supported, any other keyword in a schema is an error.
+    pub fn validate(self, value: &serde_json::Value) -> anyhow::Result<()> {
+        let schema = self.schema();
+        validate(&schema, &schema, value, "$")
+            .with_context(|| format!("Output doesn't match the `{}` schema", self.name()))
+    }
+}
+
+fn validate(
+    root: &serde_json::Value,
+    schema: &serde_json::Value,
+    value: &serde_json::Value,
+    path: &str,
+) -> anyhow::Result<()> {
+    let schema = schema
+        .as_object()
+        .with_context(|| format!("Schema for `{}` is not an object", path))?;
+    for (keyword, arg) in schema {
+        match keyword.as_str() {
+            // Annotations.
+            "$schema" | "$id" | "title" | "description" | "definitions" => {}
+            "$ref" => {
+                let name = arg
+                    .as_str()
+                    .and_then(|r| r.strip_prefix("#/definitions/"))
+                    .with_context(|| format!("Unsupported `$ref`: {}", arg))?;
+                let definition = root
+                    .get("definitions")
+                    .and_then(|d| d.get(name))
+                    .with_context(|| format!("Unknown definition `{}`", name))?;
+                validate(root, definition, value, path)?;
+            }
+            "type" => {
+                let types: Vec<&str> = match arg {
+                    serde_json::Value::String(t) => vec![t.as_str()],
+                    serde_json::Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+                    _ => return Err(anyhow::anyhow!("Invalid `type`: {}", arg)),
+                };
+                if !types.iter().any(|t| has_type(value, t)) {
+                    return Err(anyhow::anyhow!(
+                        "`{}` should be of type {}, got: {}",
+                        path,
+                        arg,
+                        value
+                    ));
+                }
+            }
+            "enum" => {
+                let values = arg.as_array().context("Invalid `enum`")?;
+                if !values.contains(value) {
+                    return Err(anyhow::anyhow!(
+                        "`{}` should be one of {}, got: {}",
+                        path,
+                        arg,
+                        value
+                    ));
+                }
+            }
+            "minimum" => {
+                let minimum = arg.as_f64().context("Invalid `minimum`")?;
+                if let Some(n) = value.as_f64() {
+                    if n < minimum {
+                        return Err(anyhow::anyhow!(
+                            "`{}` should be at least {}, got: {}",
+                            path,
+                            minimum,
+                            n
+                        ));
+                    }
+                }
+            }
+            "required" => {
+                if let Some(object) = value.as_object() {
+                    for key in arg.as_array().context("Invalid `required`")? {
+                        let key = key.as_str().context("Invalid `required`")?;
+                        if !object.contains_key(key) {
+                            return Err(anyhow::anyhow!("`{}` is missing `{}`", path, key));
+                        }
+                    }
+                }
+            }
+            "properties" => {
+                if let Some(object) = value.as_object() {
+                    for (key, property) in arg.as_object().context("Invalid `properties`")? {
+                        if let Some(v) = object.get(key) {
+                            validate(root, property, v, &format!("{}.{}", path, key))?;
+                        }
+                    }
+                }
+            }
+            "additionalProperties" => {
+                if let Some(object) = value.as_object() {
+                    let properties = schema.get("properties").and_then(|p| p.as_object());
+                    for (key, v) in object {
+                        if properties.map_or(false, |p| p.contains_key(key)) {
+                            continue;
+                        }
+                        match arg {
+                            serde_json::Value::Bool(true) => {}
+                            serde_json::Value::Bool(false) => {
+                                return Err(anyhow::anyhow!(
+                                    "`{}` has unexpected property `{}`",
+                                    path,
+                                    key
+                                ));
+                            }
+                            _ => validate(root, arg, v, &format!("{}.{}", path, key))?,
+                        }
+                    }
+                }
+            }
+            "items" => {
+                if let Some(array) = value.as_array() {
+                    for (i, v) in array.iter().enumerate() {
+                        validate(root, arg, v, &format!("{}[{}]", path, i))?;
+                    }
+                }
+            }
+            "oneOf" => {
+                let matching = arg
+                    .as_array()
+                    .context("Invalid `oneOf`")?
+                    .iter()
+                    .filter(|s| validate(root, s, value, path).is_ok())
+                    .count();
+                if matching != 1 {
+                    return Err(anyhow::anyhow!(
+                        "`{}` should match exactly one schema of `oneOf`, matches {}",
+                        path,
+                        matching
+                    ));
+                }
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported schema keyword `{}` at `{}`",
+                    keyword,
+                    path
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &serde_json::Value, t: &str) -> bool {
+    match t {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::ArgEnum;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_schemas_are_versioned() {
+        for output in JsonOutput::value_variants() {
+            let schema = output.schema();
+            let id = schema["$id"].as_str().unwrap();
+            assert!(id.ends_with(&format!("/v{}", output.version())), "{}", id);
+            assert!(schema.get("description").is_some(), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let audit = JsonOutput::AuditActionEnv;
+        assert!(audit.validate(&json!({ "PATH": "/bin" })).is_ok());
+        assert!(audit.validate(&json!({ "PATH": 1 })).is_err());
+        assert!(audit.validate(&json!(["PATH"])).is_err());
+
+        let status = JsonOutput::StatusJson;
+        assert!(status.validate(&json!({ "running": false })).is_ok());
+        assert!(status.validate(&json!([{ "running": false }])).is_ok());
+        // Missing a required property.
+        assert!(status.validate(&json!({ "pid": 1 })).is_err());
+
+        let lint = JsonOutput::Lint;
+        let message = json!({
+            "path": "BUCK",
+            "line": 1,
+            "char": 1,
+            "code": "STARLARK",
+            "severity": "warning",
+            "name": "unused-load",
+            "description": null,
+        });
+        assert!(lint.validate(&message).is_ok());
+        let mut bad = message.clone();
+        bad["severity"] = json!("fatal");
+        assert!(lint.validate(&bad).is_err());
+        bad = message;
+        bad["line"] = json!(0);
+        assert!(lint.validate(&bad).is_err());
+    }
+
+    #[test]
+    fn test_validate_unsupported_keyword() {
+        let schema = json!({ "type": "string", "pattern": "^a" });
+        assert!(validate(&schema, &schema, &json!("a"), "$").is_err());
+    }
+}
@@ -32,6 +32,7 @@ pub mod file_tailer;
 pub mod final_console;
 pub mod ide_support;
 pub mod immediate_config;
+pub mod json_schema;
 pub mod manifold;
 pub mod output_destination_arg;
 pub mod path_arg;
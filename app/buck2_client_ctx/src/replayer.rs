@@ -8,6 +8,7 @@
  */
 
 use std::pin::Pin;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use futures::stream::BoxStream;
@@ -89,8 +90,12 @@ impl Syncher {
     /// Returns an appropriate delay for this event.
     ///
     /// The first event will be sent immediately. Each subsequent event will be sent with a delay
-    /// based on its time since that first event.
+    /// based on its time since that first event, unless the speed is 0, in which case all events
+    /// are sent immediately.
     fn synch_playback_time(&mut self, event: &buck2_data::BuckEvent) -> anyhow::Result<Sleep> {
+        if self.speed == 0.0 {
+            return Ok(tokio::time::sleep(Duration::ZERO));
+        }
         let event_time = SystemTime::try_from(event.timestamp.as_ref().unwrap().clone())?;
         let (sync_start, log_start) = self.start.get_or_insert((Instant::now(), event_time));
         let log_offset_time = event_time.duration_since(*log_start)?;
@@ -7,6 +7,18 @@
  * of this source tree.
  */
 
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use buck2_common::daemon_dir::DaemonDir;
+use buck2_core::env_helper::EnvHelper;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileName;
+use serde::Deserialize;
+use serde::Serialize;
+
 use crate::daemon::client::connect::DaemonConstraintsRequest;
 use crate::daemon::client::BuckdClientConnector;
 
@@ -16,6 +28,36 @@ pub struct Restarter {
     pub reject_daemon: Option<String>,
     pub reject_materializer_state: Option<String>,
     pub enable_restarter: bool,
+    /// Whether the last observed command left the daemon in a state that needs a restart.
+    last_command_crashed: bool,
+    /// Whether a crash of the last observed command was recorded in the crash loop state, so that
+    /// it must be cleared if the restarted command succeeds.
+    recorded_crash: bool,
+    /// Set to the number of consecutive crashes once the same command has crashed too many times
+    /// in a row in this isolation dir. When set, we stop restarting.
+    crash_loop: Option<u32>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "This command crashed the Buck2 daemon {0} times in a row, not restarting it again. \
+    This is most likely a bug in Buck2, please report it."
+)]
+pub struct CrashLoopError(pub u32);
+
+/// Crashes of the same command older than this don't count towards a crash loop. Commands that
+/// don't crash don't touch the crash loop state, so this is what eventually forgets a crash when
+/// the next invocation of the command succeeded.
+const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Consecutive crashes of the same command, persisted in the daemon dir so that it is tracked
+/// across invocations (e.g. CI retries) and not just across restarts within one invocation.
+#[derive(Serialize, Deserialize)]
+struct CrashLoopState {
+    command: Vec<String>,
+    consecutive_crashes: u32,
+    /// Seconds since the epoch.
+    last_crash: u64,
 }
 
 impl Restarter {
@@ -24,15 +66,21 @@ impl Restarter {
             reject_daemon: None,
             reject_materializer_state: None,
             enable_restarter: false,
+            last_command_crashed: false,
+            recorded_crash: false,
+            crash_loop: None,
         }
     }
 
     /// Observe our BuckdClientConnector after execution to decide whether we should be
     /// restarting.
     pub fn observe(&mut self, client: &BuckdClientConnector) {
+        self.last_command_crashed = false;
+
         for obs in client.error_observers() {
             if obs.daemon_in_memory_state_is_corrupted() {
                 self.reject_daemon = Some(client.daemon_constraints().daemon_id.clone());
+                self.last_command_crashed = true;
             }
 
             if obs.daemon_materializer_state_is_corrupted() {
@@ -41,6 +89,7 @@ impl Restarter {
                     .extra
                     .as_ref()
                     .and_then(|e| e.materializer_state_identity.clone());
+                self.last_command_crashed = true;
             }
 
             if obs.restarter_is_enabled() {
@@ -49,8 +98,49 @@ impl Restarter {
         }
     }
 
+    /// Record the outcome of the last observed command in the isolation dir's crash loop state.
+    /// Returns an error if the same command has now crashed too many times in a row.
+    ///
+    /// This only does anything when a restart is being considered, i.e. the command crashed and
+    /// the restarter is enabled, or when the command was that restart. Other commands don't touch
+    /// the state.
+    ///
+    /// This is best-effort: failing to read or write the state never fails the command.
+    pub fn observe_crash_loop(
+        &mut self,
+        daemon_dir: &DaemonDir,
+        command: &[String],
+    ) -> Option<CrashLoopError> {
+        static CRASH_LOOP_THRESHOLD: EnvHelper<u32> = EnvHelper::new("BUCK2_CRASH_LOOP_THRESHOLD");
+
+        let crashed = self.enable_restarter && self.last_command_crashed;
+        if !crashed && !self.recorded_crash {
+            return None;
+        }
+        self.recorded_crash = crashed;
+
+        let threshold = CRASH_LOOP_THRESHOLD
+            .get_copied()
+            .ok()
+            .flatten()
+            .unwrap_or(3);
+
+        match update_crash_loop_state(daemon_dir, command, crashed, SystemTime::now()) {
+            Ok(crashes) if crashes >= threshold && threshold > 0 => {
+                self.crash_loop = Some(crashes);
+                Some(CrashLoopError(crashes))
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::debug!("Failed to update crash loop state: {:#}", e);
+                None
+            }
+        }
+    }
+
     pub fn should_restart(&self) -> bool {
         self.enable_restarter
+            && self.crash_loop.is_none()
             && (self.reject_daemon.is_some() || self.reject_materializer_state.is_some())
     }
 
@@ -59,3 +149,139 @@ impl Restarter {
         req.reject_materializer_state = self.reject_materializer_state.clone();
     }
 }
+
+fn crash_loop_state_path(daemon_dir: &DaemonDir) -> anyhow::Result<AbsNormPathBuf> {
+    Ok(daemon_dir.path.join(FileName::new("crash_loop.json")?))
+}
+
+/// Updates the persisted crash loop state and returns the number of consecutive crashes of
+/// `command` (zero if it did not crash).
+fn update_crash_loop_state(
+    daemon_dir: &DaemonDir,
+    command: &[String],
+    crashed: bool,
+    now: SystemTime,
+) -> anyhow::Result<u32> {
+    let path = crash_loop_state_path(daemon_dir)?;
+
+    if !crashed {
+        fs_util::remove_all(&path)?;
+        return Ok(0);
+    }
+
+    let previous = match fs_util::read_to_string_if_exists(&path)? {
+        Some(contents) => serde_json::from_str::<CrashLoopState>(&contents).ok(),
+        None => None,
+    };
+    let now = now.duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    let consecutive_crashes = match previous {
+        Some(previous)
+            if previous.command == command
+                && now.saturating_sub(previous.last_crash) < CRASH_LOOP_WINDOW.as_secs() =>
+        {
+            previous.consecutive_crashes + 1
+        }
+        _ => 1,
+    };
+
+    fs_util::create_dir_all(&daemon_dir.path)?;
+    fs_util::write(
+        &path,
+        serde_json::to_vec(&CrashLoopState {
+            command: command.to_vec(),
+            consecutive_crashes,
+            last_crash: now,
+        })?,
+    )
+    .context("Error writing crash loop state")?;
+
+    Ok(consecutive_crashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daemon_dir(tempdir: &tempfile::TempDir) -> DaemonDir {
+        DaemonDir {
+            path: AbsNormPathBuf::try_from(tempdir.path().join("buckd")).unwrap(),
+        }
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| (*a).to_owned()).collect()
+    }
+
+    #[test]
+    fn test_crash_loop_counts_consecutive_crashes_of_a_command() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let daemon_dir = daemon_dir(&tempdir);
+        let build = argv(&["buck2", "build", "//:foo"]);
+        let now = SystemTime::now();
+
+        assert_eq!(update_crash_loop_state(&daemon_dir, &build, true, now)?, 1);
+        assert_eq!(update_crash_loop_state(&daemon_dir, &build, true, now)?, 2);
+
+        // Another command crashing starts over.
+        let test = argv(&["buck2", "test", "//:foo"]);
+        assert_eq!(update_crash_loop_state(&daemon_dir, &test, true, now)?, 1);
+        assert_eq!(update_crash_loop_state(&daemon_dir, &build, true, now)?, 1);
+
+        // So does a success, or a crash long after the previous one.
+        assert_eq!(update_crash_loop_state(&daemon_dir, &build, false, now)?, 0);
+        assert!(!crash_loop_state_path(&daemon_dir)?.exists());
+        assert_eq!(update_crash_loop_state(&daemon_dir, &build, true, now)?, 1);
+        let later = now + CRASH_LOOP_WINDOW + Duration::from_secs(1);
+        assert_eq!(
+            update_crash_loop_state(&daemon_dir, &build, true, later)?,
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_observe_crash_loop_only_when_restarting() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let daemon_dir = daemon_dir(&tempdir);
+        let build = argv(&["buck2", "build", "//:foo"]);
+
+        // Commands which didn't crash, or crashed with the restarter disabled, don't touch the
+        // state and never fail.
+        let mut restarter = Restarter::new();
+        assert!(restarter.observe_crash_loop(&daemon_dir, &build).is_none());
+        restarter.last_command_crashed = true;
+        for _ in 0..5 {
+            assert!(restarter.observe_crash_loop(&daemon_dir, &build).is_none());
+        }
+        assert!(!daemon_dir.path.exists());
+
+        restarter.enable_restarter = true;
+        restarter.reject_daemon = Some("daemon".to_owned());
+        assert!(restarter.should_restart());
+        assert!(restarter.observe_crash_loop(&daemon_dir, &build).is_none());
+        assert!(restarter.observe_crash_loop(&daemon_dir, &build).is_none());
+        assert_eq!(
+            restarter
+                .observe_crash_loop(&daemon_dir, &build)
+                .map(|e| e.0),
+            Some(3)
+        );
+        assert!(restarter.crash_loop.is_some());
+        assert!(!restarter.should_restart());
+
+        // The restarted command succeeding clears the state.
+        let tempdir = tempfile::tempdir()?;
+        let daemon_dir = daemon_dir(&tempdir);
+        let mut restarter = Restarter::new();
+        restarter.enable_restarter = true;
+        restarter.last_command_crashed = true;
+        assert!(restarter.observe_crash_loop(&daemon_dir, &build).is_none());
+        assert!(crash_loop_state_path(&daemon_dir)?.exists());
+        restarter.last_command_crashed = false;
+        assert!(restarter.observe_crash_loop(&daemon_dir, &build).is_none());
+        assert!(!crash_loop_state_path(&daemon_dir)?.exists());
+
+        Ok(())
+    }
+}
@@ -7,21 +7,222 @@
  * of this source tree.
  */
 
+use dupe::Dupe;
 use futures::future;
 use futures::future::Either;
 use futures::Future;
 
+use crate::exit_result::ExitCode;
+
+/// A signal which asked the client to stop the command it is running.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum InterruptSignal {
+    /// SIGINT, i.e. ctrl+c.
+    Interrupt,
+    /// SIGTERM, which is what CI systems and process supervisors send.
+    Terminate,
+}
+
+impl InterruptSignal {
+    pub fn exit_code(self) -> ExitCode {
+        match self {
+            InterruptSignal::Interrupt => ExitCode::SignalInterrupt,
+            InterruptSignal::Terminate => ExitCode::SignalTerminate,
+        }
+    }
+}
+
 /// A simple SIGINT handler that lets `work` and ctrl+c future race. When ctrl+c
 /// is hit, it allows the `work` future and the other clean-up implementations
 /// such as AsyncCleanupContext to be dropped.
 pub async fn with_simple_sigint_handler<F: Future>(work: F) -> Option<F::Output> {
-    let exit = tokio::signal::ctrl_c();
+    race_signal(work, tokio::signal::ctrl_c()).await.ok()
+}
+
+/// Like `with_simple_sigint_handler`, but SIGTERM cancels `work` too, instead of killing the
+/// process on the spot. Dropping `work` drops the connection to the daemon, which cancels the
+/// command there: no new actions get scheduled, and those in flight finish or are cancelled. The
+/// client then still runs its AsyncCleanupContext before exiting, unless a second SIGTERM kills
+/// it first.
+pub async fn with_simple_signal_handler<F: Future>(work: F) -> Result<F::Output, InterruptSignal> {
+    race_signal(work, interrupt_signal()).await
+}
+
+fn interrupt_signal() -> impl Future<Output = InterruptSignal> {
+    // Install the SIGTERM handler right away rather than on first poll, so that a SIGTERM sent
+    // once the command has started is never missed.
+    let terminate = terminate_signal();
+    async move {
+        let interrupt = async {
+            let _ignored = tokio::signal::ctrl_c().await;
+            InterruptSignal::Interrupt
+        };
+        futures::pin_mut!(interrupt);
+        futures::pin_mut!(terminate);
+        future::select(interrupt, terminate).await.factor_first().0
+    }
+}
+
+#[cfg(unix)]
+fn terminate_signal() -> impl Future<Output = InterruptSignal> {
+    use tokio::signal::unix::signal;
+    use tokio::signal::unix::SignalKind;
+
+    let terminate = signal(SignalKind::terminate());
+    async move {
+        match terminate {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+                // Cleanup can still take a while (up to 30s for the AsyncCleanupContext), so
+                // a second SIGTERM kills the client on the spot, like it would without a handler.
+                restore_default_terminate();
+                InterruptSignal::Terminate
+            }
+            // Without a handler, SIGTERM keeps its default behaviour of killing the process.
+            Err(_) => future::pending().await,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn restore_default_terminate() {
+    use nix::sys::signal::signal;
+    use nix::sys::signal::SigHandler;
+    use nix::sys::signal::Signal;
+
+    // SAFETY: this only resets the disposition to the default one, no handler of ours runs.
+    if let Err(e) = unsafe { signal(Signal::SIGTERM, SigHandler::SigDfl) } {
+        tracing::warn!("Failed to restore the default SIGTERM action: {}", e);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate_signal() -> impl Future<Output = InterruptSignal> {
+    future::pending()
+}
 
+/// Run `work` until it completes or `signal` fires, whichever comes first. `work` is dropped in
+/// the latter case.
+async fn race_signal<F: Future, S: Future>(work: F, signal: S) -> Result<F::Output, S::Output> {
     futures::pin_mut!(work);
-    futures::pin_mut!(exit);
+    futures::pin_mut!(signal);
+
+    match future::select(work, signal).await {
+        Either::Left((res, _)) => Ok(res),
+        Either::Right((signal, _)) => Err(signal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use super::*;
+    use crate::cleanup_ctx::AsyncCleanupContextGuard;
+
+    /// Sets the flag when dropped, i.e. when the command is cancelled.
+    struct Cancelled(Arc<AtomicBool>);
+
+    impl Drop for Cancelled {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_terminate_cancels_command_and_runs_cleanup() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+
+        {
+            let async_cleanup = AsyncCleanupContextGuard::new(&runtime);
+            let cleanup_ctx = async_cleanup.ctx().dupe();
+            let guard = Cancelled(cancelled.dupe());
+            let cleaned_up = cleaned_up.dupe();
+            // A command which registers cleanup (like the event log upload does) and then waits
+            // on the daemon forever.
+            let command = async move {
+                let _guard = guard;
+                cleanup_ctx.register(
+                    "test cleanup",
+                    async move {
+                        cleaned_up.store(true, Ordering::SeqCst);
+                    }
+                    .boxed(),
+                );
+                future::pending::<()>().await
+            };
+
+            let signal = async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                InterruptSignal::Terminate
+            };
+            let res = runtime.block_on(race_signal(command, signal));
+
+            assert_eq!(res, Err(InterruptSignal::Terminate));
+            assert_eq!(res.unwrap_err().exit_code().exit_code(), 143);
+            // The command (and with it the connection to the daemon) was dropped, but cleanup
+            // only happens once the guard goes away.
+            assert!(cancelled.load(Ordering::SeqCst));
+            assert!(!cleaned_up.load(Ordering::SeqCst));
+        }
+
+        assert!(cleaned_up.load(Ordering::SeqCst));
+    }
+
+    /// Sends a real SIGTERM to the test process while a command is running.
+    #[cfg(unix)]
+    #[test]
+    fn test_sigterm_cancels_command_and_restores_default_action() {
+        use nix::sys::signal::kill;
+        use nix::sys::signal::Signal;
+        use nix::unistd::Pid;
+
+        fn sigterm_action() -> libc::sighandler_t {
+            let mut action = std::mem::MaybeUninit::<libc::sigaction>::zeroed();
+            // SAFETY: a null new action only reads the current one.
+            let ret =
+                unsafe { libc::sigaction(libc::SIGTERM, std::ptr::null(), action.as_mut_ptr()) };
+            assert_eq!(ret, 0);
+            // SAFETY: initialized by `sigaction` above.
+            unsafe { action.assume_init() }.sa_sigaction
+        }
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            // The daemon side of the command's stream: it sees the stream close once the client
+            // drops the command, which is what cancels the command in the daemon.
+            let (stream, mut daemon) = tokio::sync::mpsc::channel::<()>(1);
+            let daemon = tokio::spawn(async move { daemon.recv().await });
+
+            let command = async move {
+                let _stream = stream;
+                kill(Pid::this(), Signal::SIGTERM).unwrap();
+                future::pending::<()>().await
+            };
+            let res = with_simple_signal_handler(command).await;
+            assert_eq!(res, Err(InterruptSignal::Terminate));
+
+            let received = tokio::time::timeout(Duration::from_secs(10), daemon)
+                .await
+                .expect("daemon did not see the command go away")
+                .unwrap();
+            assert_eq!(received, None);
+        });
+
+        // A second SIGTERM would now kill the process.
+        assert_eq!(sigterm_action(), libc::SIG_DFL);
+    }
 
-    match future::select(work, exit).await {
-        Either::Left((res, _)) => Some(res),
-        Either::Right((_, _)) => None,
+    #[tokio::test]
+    async fn test_work_completes_before_signal() {
+        let res = race_signal(async { 1 }, future::pending::<InterruptSignal>()).await;
+        assert_eq!(res, Ok(1));
     }
 }
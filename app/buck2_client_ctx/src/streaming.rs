@@ -9,9 +9,11 @@
 
 use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use dupe::Dupe;
+use futures::Future;
 
 use crate::argv::Argv;
 use crate::argv::SanitizedArgv;
@@ -27,12 +29,14 @@ use crate::daemon::client::BuckdClientConnector;
 use crate::exit_result::ExitCode;
 use crate::exit_result::ExitResult;
 use crate::path_arg::PathArg;
-use crate::signal_handler::with_simple_sigint_handler;
+use crate::signal_handler::with_simple_signal_handler;
 use crate::subscribers::get::get_console_with_root;
 use crate::subscribers::get::try_get_build_graph_stats;
 use crate::subscribers::get::try_get_build_id_writer;
 use crate::subscribers::get::try_get_event_log_subscriber;
 use crate::subscribers::get::try_get_re_log_subscriber;
+use crate::subscribers::get::try_get_stderr_tail_subscriber;
+use crate::subscribers::get::try_get_verbosity_file_subscriber;
 use crate::subscribers::recorder::try_get_invocation_recorder;
 use crate::subscribers::subscriber::EventSubscriber;
 
@@ -58,6 +62,13 @@ fn default_subscribers<'a, T: StreamingCommand>(
         console_opts.superconsole_config(),
     )?);
 
+    if let Some(verbosity_file) = try_get_verbosity_file_subscriber(cmd, ctx)? {
+        subscribers.push(verbosity_file)
+    }
+    // After the console, so the tails are printed once the console is done.
+    if let Some(stderr_tail) = try_get_stderr_tail_subscriber(ctx) {
+        subscribers.push(stderr_tail)
+    }
     if let Some(event_log) = try_get_event_log_subscriber(cmd, ctx, log_size_counter_bytes.clone())?
     {
         subscribers.push(event_log)
@@ -84,6 +95,27 @@ fn default_subscribers<'a, T: StreamingCommand>(
     Ok(subscribers)
 }
 
+/// Run `work`, cancelling it if it has not finished after `timeout`. Like on Ctrl-C, cancelling
+/// drops the connection to the daemon, which then interrupts the command.
+async fn with_timeout(
+    timeout: Option<Duration>,
+    work: impl Future<Output = ExitResult>,
+) -> ExitResult {
+    let Some(timeout) = timeout else {
+        return work.await;
+    };
+    match tokio::time::timeout(timeout, work).await {
+        Ok(res) => res,
+        Err(_) => ExitResult::err_with_exit_code(
+            anyhow::anyhow!(
+                "Command timed out after {}",
+                humantime::format_duration(timeout)
+            ),
+            ExitCode::Timeout,
+        ),
+    }
+}
+
 /// Trait to generalize the behavior of executable buck2 commands that rely on a server.
 /// This trait is most helpful when the command wants a superconsole, to stream events, etc.
 /// However, this is the most robustly tested of our code paths, and there is little cost to defaulting to it.
@@ -154,6 +186,7 @@ impl<T: StreamingCommand> BuckSubcommand for T {
     /// Handles all of the business of setting up a runtime, server, and subscribers.
     fn exec(self, matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
         ctx.with_runtime(async move |mut ctx| {
+            let timeout = ctx.timeout;
             let work = async {
                 let constraints = if T::existing_only() {
                     BuckdConnectConstraints::ExistingOnly
@@ -194,12 +227,60 @@ impl<T: StreamingCommand> BuckSubcommand for T {
 
                 ctx.restarter.observe(&buckd);
 
+                if let Ok(paths) = ctx.paths()
+                    && let Ok(daemon_dir) = paths.daemon_dir()
+                    && let Some(e) = ctx
+                        .restarter
+                        .observe_crash_loop(&daemon_dir, &ctx.argv.argv)
+                {
+                    return ExitResult::err_with_exit_code(e.into(), ExitCode::CrashLoop);
+                }
+
                 command_result
             };
 
-            with_simple_sigint_handler(work)
+            with_simple_signal_handler(with_timeout(timeout, work))
                 .await
-                .unwrap_or_else(|| ExitResult::status(ExitCode::SignalInterrupt))
+                .unwrap_or_else(|signal| ExitResult::status(signal.exit_code()))
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    /// Sets the flag when dropped, i.e. when the command is cancelled.
+    struct Cancelled(Arc<AtomicBool>);
+
+    impl Drop for Cancelled {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_with_timeout() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let guard = Cancelled(cancelled.dupe());
+        let command = async move {
+            let _guard = guard;
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            ExitResult::success()
+        };
+
+        let res = with_timeout(Some(Duration::from_secs(60)), command).await;
+        assert_eq!(res.exit_code(), Some(5));
+        assert!(cancelled.load(Ordering::SeqCst));
+
+        let command = async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            ExitResult::success()
+        };
+        let res = with_timeout(Some(Duration::from_secs(60)), command).await;
+        assert_eq!(res.exit_code(), Some(0));
+    }
+}
@@ -225,6 +225,7 @@ impl EventLogPathBuf {
                 .transpose()
                 .context("Invalid TraceId")?
                 .unwrap_or_else(TraceId::null),
+            buck2_version: invocation.buck2_version,
         };
 
         let events = stream.and_then(|data| async move {
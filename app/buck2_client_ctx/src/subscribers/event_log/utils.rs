@@ -140,6 +140,9 @@ pub struct Invocation {
     pub working_dir: String,
     #[serde(default = "TraceId::null")]
     pub trace_id: TraceId,
+    /// The version of buck2 which wrote the log. Missing in logs written before it was recorded.
+    #[serde(default)]
+    pub buck2_version: Option<String>,
 }
 
 impl Invocation {
@@ -180,6 +183,7 @@ mod tests {
             working_dir: "/Users/nga/dir45".to_owned(),
             expanded_command_line_args: Vec::new(),
             trace_id: TraceId::from_str("281d1c16-8930-40cd-8fc1-7d71355c20f5").unwrap(),
+            buck2_version: None,
         };
         assert_eq!(expected, line);
     }
@@ -37,6 +37,7 @@ use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 
 use crate::cleanup_ctx::AsyncCleanupContext;
+use crate::daemon_constraints;
 use crate::subscribers::event_log::file_names::get_logfile_name;
 use crate::subscribers::event_log::file_names::remove_old_logs;
 use crate::subscribers::event_log::read::EventLogPathBuf;
@@ -184,6 +185,7 @@ impl<'a> WriteEventLog<'a> {
             expanded_command_line_args,
             working_dir: self.working_dir.to_string(),
             trace_id,
+            buck2_version: Some(daemon_constraints::version()),
         };
         self.write_ln(&[invocation]).await
     }
@@ -550,6 +552,7 @@ impl SerializeForLog for Invocation {
             expanded_command_line_args: self.expanded_command_line_args.clone(),
             working_dir: self.working_dir.clone(),
             trace_id: Some(self.trace_id.to_string()),
+            buck2_version: self.buck2_version.clone(),
         };
         invocation.encode_length_delimited(buf)?;
         Ok(())
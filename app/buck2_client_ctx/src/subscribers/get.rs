@@ -7,9 +7,11 @@
  * of this source tree.
  */
 
+use std::fs::File;
 use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 
+use anyhow::Context;
 use buck2_event_observer::event_observer::NoopEventObserverExtra;
 use buck2_event_observer::verbosity::Verbosity;
 use buck2_wrapper_common::invocation_id::TraceId;
@@ -25,6 +27,7 @@ use crate::subscribers::errorconsole::ErrorConsole;
 use crate::subscribers::event_log::subscriber::EventLog;
 use crate::subscribers::re_log::ReLog;
 use crate::subscribers::simpleconsole::SimpleConsole;
+use crate::subscribers::stderr_tail::StderrTail;
 use crate::subscribers::subscriber::EventSubscriber;
 use crate::subscribers::subscriber_unpack::UnpackingEventSubscriberAsEventSubscriber;
 use crate::subscribers::superconsole::StatefulSuperConsole;
@@ -88,6 +91,35 @@ pub fn get_console_with_root(
     }
 }
 
+/// If `--verbosity-file` is set, create a console writing to it at the highest verbosity.
+pub(crate) fn try_get_verbosity_file_subscriber<'a, T: StreamingCommand>(
+    cmd: &T,
+    ctx: &ClientCommandContext<'a>,
+) -> anyhow::Result<Option<Box<dyn EventSubscriber + 'a>>> {
+    let Some(path) = &ctx.verbosity_file else {
+        return Ok(None);
+    };
+    let path = path.resolve(&ctx.working_dir);
+    let file = File::create(&path)
+        .with_context(|| format!("Error creating verbosity file `{}`", path.display()))?;
+    Ok(Some(Box::new(UnpackingEventSubscriberAsEventSubscriber(
+        SimpleConsole::<NoopEventObserverExtra>::for_file(
+            ctx.trace_id.dupe(),
+            Verbosity::max(),
+            cmd.should_expect_spans(),
+            file,
+        ),
+    ))))
+}
+
+/// If `--stderr-tail` is set, print the end of the stderr of failed actions after the command.
+pub(crate) fn try_get_stderr_tail_subscriber<'a>(
+    ctx: &ClientCommandContext<'a>,
+) -> Option<Box<dyn EventSubscriber + 'a>> {
+    ctx.stderr_tail
+        .map(|lines| Box::new(StderrTail::new(lines)) as Box<dyn EventSubscriber>)
+}
+
 /// Given the command arguments, conditionally create an event log.
 pub(crate) fn try_get_event_log_subscriber<'a, T: StreamingCommand>(
     cmd: &T,
@@ -84,6 +84,7 @@ mod imp {
         min_build_count_since_rebase: u64,
         cache_upload_count: u64,
         cache_upload_attempt_count: u64,
+        cache_upload_skipped_size_count: u64,
         parsed_target_patterns: Option<buck2_data::ParsedTargetPatterns>,
         filesystem: String,
         watchman_version: Option<String>,
@@ -176,6 +177,7 @@ mod imp {
                 min_build_count_since_rebase: 0,
                 cache_upload_count: 0,
                 cache_upload_attempt_count: 0,
+                cache_upload_skipped_size_count: 0,
                 parsed_target_patterns: None,
                 filesystem,
                 watchman_version: None,
@@ -322,6 +324,7 @@ mod imp {
                 min_build_count_since_rebase: self.min_build_count_since_rebase,
                 cache_upload_count: self.cache_upload_count,
                 cache_upload_attempt_count: self.cache_upload_attempt_count,
+                cache_upload_skipped_size_count: self.cache_upload_skipped_size_count,
                 parsed_target_patterns: self.parsed_target_patterns.take(),
                 filesystem: std::mem::take(&mut self.filesystem),
                 watchman_version: self.watchman_version.take(),
@@ -551,7 +554,8 @@ mod imp {
                         self.run_local_count += 1;
                         self.local_actions_executed_via_worker += 1;
                     }
-                    LastCommandExecutionKind::Cached => {
+                    LastCommandExecutionKind::Cached
+                    | LastCommandExecutionKind::LocalActionCached => {
                         self.run_action_cache_count += 1;
                     }
                     LastCommandExecutionKind::RemoteDepFileCached => {
@@ -641,6 +645,9 @@ mod imp {
             if cache_upload.success {
                 self.cache_upload_count += 1;
             }
+            if cache_upload.output_size_limit.is_some() {
+                self.cache_upload_skipped_size_count += 1;
+            }
             self.cache_upload_attempt_count += 1;
             Ok(())
         }
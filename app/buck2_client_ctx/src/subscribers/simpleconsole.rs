@@ -10,7 +10,10 @@
 use std::borrow::Cow;
 use std::fmt::Display;
 use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write as _;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
@@ -63,24 +66,44 @@ fn with_timestamps(message: &str) -> String {
     s
 }
 
-// Echoes a message to stderr, along with a timestamp.
+// Echoes a message to the console output, along with a timestamp.
 macro_rules! echo {
-    () => {
+    ($out:expr) => {
         {
-            // patternlint-disable-next-line buck2-cli-simpleconsole-echo
-            crate::eprintln!("[{}]", now_display())
+            $out.writeln(&format!("[{}]", now_display()))
         }
     };
-    ($fmt:expr $(, $args:expr)*) => {
+    ($out:expr, $fmt:expr $(, $args:expr)*) => {
         {
             let message = format!($fmt $(, $args)*);
             let message = with_timestamps(&message);
-            // patternlint-disable-next-line buck2-cli-simpleconsole-echo
-            crate::eprintln!("{}", message)
+            $out.writeln(&message)
         }
     };
 }
 
+/// Where a [`SimpleConsole`] writes its output.
+pub(crate) enum SimpleConsoleOutput {
+    Stderr,
+    /// A file receiving a copy of the console output, see `--verbosity-file`.
+    File(Mutex<File>),
+}
+
+impl SimpleConsoleOutput {
+    fn writeln(&self, message: &str) -> anyhow::Result<()> {
+        match self {
+            SimpleConsoleOutput::Stderr => {
+                // patternlint-disable-next-line buck2-cli-simpleconsole-echo
+                crate::eprintln!("{}", message)
+            }
+            SimpleConsoleOutput::File(file) => {
+                let mut file = file.lock().unwrap();
+                writeln!(file, "{}", message).context("Error writing to verbosity file")
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone, Dupe, Debug, PartialEq)]
 enum TtyMode {
     Enabled,
@@ -90,6 +113,7 @@ enum TtyMode {
 /// Just repeats stdout and stderr to client process.
 pub(crate) struct SimpleConsole<E> {
     tty_mode: TtyMode,
+    output: SimpleConsoleOutput,
     verbosity: Verbosity,
     // Whether to show "Waiting for daemon..." when no root spans are received
     expect_spans: bool,
@@ -106,6 +130,7 @@ where
     pub(crate) fn with_tty(trace_id: TraceId, verbosity: Verbosity, expect_spans: bool) -> Self {
         SimpleConsole {
             tty_mode: TtyMode::Enabled,
+            output: SimpleConsoleOutput::Stderr,
             verbosity,
             expect_spans,
             observer: EventObserver::new(trace_id),
@@ -118,6 +143,7 @@ where
     pub(crate) fn without_tty(trace_id: TraceId, verbosity: Verbosity, expect_spans: bool) -> Self {
         SimpleConsole {
             tty_mode: TtyMode::Disabled,
+            output: SimpleConsoleOutput::Stderr,
             verbosity,
             expect_spans,
             observer: EventObserver::new(trace_id),
@@ -127,6 +153,20 @@ where
         }
     }
 
+    /// Create a SimpleConsole writing to a file rather than stderr. Output is not written to a
+    /// terminal, so it has no colors.
+    pub(crate) fn for_file(
+        trace_id: TraceId,
+        verbosity: Verbosity,
+        expect_spans: bool,
+        file: File,
+    ) -> Self {
+        SimpleConsole {
+            output: SimpleConsoleOutput::File(Mutex::new(file)),
+            ..Self::without_tty(trace_id, verbosity, expect_spans)
+        }
+    }
+
     /// Create a SimpleConsole that auto detects whether it has a TTY or not.
     pub(crate) fn autodetect(trace_id: TraceId, verbosity: Verbosity, expect_spans: bool) -> Self {
         match SuperConsole::compatible() {
@@ -157,7 +197,7 @@ where
             .re_state()
             .render_header(snapshots, DrawMode::Normal)
         {
-            echo!("{}", h)?;
+            echo!(self.output, "{}", h)?;
         }
 
         let last_snapshot_ts = snapshots.last.as_ref().map(|(ts, _)| *ts);
@@ -170,7 +210,7 @@ where
         };
 
         if is_snapshot_stale {
-            echo!("Resource usage: <snapshot is stale>")?;
+            echo!(self.output, "Resource usage: <snapshot is stale>")?;
         } else {
             let mut parts = Vec::with_capacity(2);
             if let Some((_, snapshot)) = &snapshots.last {
@@ -182,7 +222,7 @@ where
                 parts.push(format!("CPU: {}%", cpu));
             }
             if !parts.is_empty() {
-                echo!("Resource usage: {}", parts.join(" "))?;
+                echo!(self.output, "Resource usage: {}", parts.join(" "))?;
             }
 
             if let Some((_ts, snapshot)) = &snapshots.last {
@@ -191,9 +231,9 @@ where
                     parts.push(format!("{:?}: {}", key, value));
                 }
                 if !parts.is_empty() {
-                    echo!("IO: {}", parts.join(" "))?;
+                    echo!(self.output, "IO: {}", parts.join(" "))?;
                 } else {
-                    echo!("IO: none")?;
+                    echo!(self.output, "IO: none")?;
                 }
             }
 
@@ -207,11 +247,10 @@ where
         let display = display::display_action_error(error, TargetDisplayOptions::for_log())?;
         let message = display.simple_format_with_timestamps(with_timestamps);
         if self.tty_mode == TtyMode::Disabled {
-            // patternlint-disable-next-line buck2-cli-simpleconsole-echo
-            crate::eprintln!("{}", display::sanitize_output_colors(message.as_bytes()))?;
+            self.output
+                .writeln(&display::sanitize_output_colors(message.as_bytes()))?;
         } else {
-            // patternlint-disable-next-line buck2-cli-simpleconsole-echo
-            crate::eprintln!("{}", message)?;
+            self.output.writeln(&message)?;
         }
         self.notify_printed();
         Ok(())
@@ -224,6 +263,10 @@ where
     E: EventObserverExtra,
 {
     async fn handle_output(&mut self, raw_output: &[u8]) -> anyhow::Result<()> {
+        if let SimpleConsoleOutput::File(..) = self.output {
+            // This is the output of the command, not part of the log.
+            return Ok(());
+        }
         // We expect output that gets here to already have been buffered if possible (because it
         // primarily gets to us through a GRPC layer that already needs buffering), so we
         // unconditionally flush it.
@@ -234,7 +277,7 @@ where
     }
 
     async fn handle_stderr(&mut self, stderr: &str) -> anyhow::Result<()> {
-        echo!("{}", stderr)?;
+        echo!(self.output, "{}", stderr)?;
         self.notify_printed();
         Ok(())
     }
@@ -247,7 +290,7 @@ where
         if err.quiet {
             return Ok(());
         }
-        echo!("{}", err.payload)?;
+        echo!(self.output, "{}", err.payload)?;
         self.notify_printed();
         Ok(())
     }
@@ -259,7 +302,7 @@ where
     ) -> anyhow::Result<()> {
         if self.verbosity.print_status() {
             for x in display_file_watcher_end(file_watcher) {
-                echo!("{}", x)?;
+                echo!(self.output, "{}", x)?;
             }
             self.notify_printed();
         }
@@ -277,7 +320,7 @@ where
                 event.parent_id().into(),
                 event.data(),
                 self.observer().spans(),
-                &mut PrintDebugCommandToStderr,
+                &mut PrintDebugCommand(&self.output),
                 &WhatRanOptions::default(),
             )?;
         }
@@ -292,11 +335,12 @@ where
     ) -> anyhow::Result<()> {
         if cfg!(fbcode_build) {
             echo!(
+                self.output,
                 "Buck UI: https://www.internalfb.com/buck2/{}",
                 event.trace_id()?
             )?;
         } else {
-            echo!("Build ID: {}", event.trace_id()?)?;
+            echo!(self.output, "Build ID: {}", event.trace_id()?)?;
         }
         self.notify_printed();
         Ok(())
@@ -309,19 +353,28 @@ where
         let errors = std::mem::take(&mut self.action_errors);
 
         if !errors.is_empty() {
-            echo!()?;
-            echo!("BUILD ERRORS ({})", errors.len())?;
-            echo!("The following actions failed during the execution of this command:")?;
+            echo!(self.output)?;
+            echo!(self.output, "BUILD ERRORS ({})", errors.len())?;
+            echo!(
+                self.output,
+                "The following actions failed during the execution of this command:"
+            )?;
             for error in errors.iter() {
                 self.print_action_error(error)?;
             }
-            echo!()?;
+            echo!(self.output)?;
             self.notify_printed();
         }
 
-        crate::subscribers::errorconsole::ErrorConsole
-            .handle_command_result(result)
-            .await
+        match self.output {
+            SimpleConsoleOutput::Stderr => {
+                crate::subscribers::errorconsole::ErrorConsole
+                    .handle_command_result(result)
+                    .await
+            }
+            // The errors are already printed by the main console.
+            SimpleConsoleOutput::File(..) => Ok(()),
+        }
     }
 
     async fn handle_command_end(
@@ -333,8 +386,9 @@ where
 
         if self.verbosity.print_status() && self.observer().action_stats().log_stats() {
             let cache_hit_percentage = self.observer().action_stats().total_cache_hit_percentage();
-            echo!("Cache hits: {}%", cache_hit_percentage)?;
+            echo!(self.output, "Cache hits: {}%", cache_hit_percentage)?;
             echo!(
+                self.output,
                 "Commands: {} (cached: {}, remote: {}, local: {})",
                 self.observer()
                     .action_stats()
@@ -345,6 +399,7 @@ where
             )?;
             if self.observer().action_stats().fallback_actions > 0 {
                 echo!(
+                    self.output,
                     "Fallback: {}/{}",
                     self.observer().action_stats().fallback_actions,
                     self.observer().action_stats().total_executed_actions()
@@ -357,11 +412,11 @@ where
             .re_state()
             .render_header(snapshots, DrawMode::Final)
         {
-            echo!("{}", re)?;
+            echo!(self.output, "{}", re)?;
         }
 
         if let Some(test_session) = &self.observer().session_info().test_session {
-            echo!("Test session: {}", test_session.info)?;
+            echo!(self.output, "Test session: {}", test_session.info)?;
         }
 
         Ok(())
@@ -408,17 +463,24 @@ where
         if self.verbosity.print_all_actions() || stderr.is_some() {
             let complete = self.observer().spans().roots_completed();
             let incomplete = self.observer().spans().roots_ongoing();
-            echo!("{} / {}: {}", complete, complete + incomplete, action_id)?;
+            echo!(
+                self.output,
+                "{} / {}: {}",
+                complete,
+                complete + incomplete,
+                action_id
+            )?;
             if let Some(stderr) = stderr {
                 // TODO(nmj): Factor out behavior here so that handling ttymode isn't ad hoc.  i.e. write a method that formats text based on tty mode
                 match self.tty_mode {
                     TtyMode::Enabled => {
                         // Add the extra control character so that users' stderr messages can't
                         // mess up the terminal
-                        echo!("stderr:{}\x1b[0m", stderr)?;
+                        echo!(self.output, "stderr:{}\x1b[0m", stderr)?;
                     }
                     TtyMode::Disabled => {
                         echo!(
+                            self.output,
                             "stderr:\n{}",
                             display::sanitize_output_colors(stderr.as_bytes())
                         )?;
@@ -445,7 +507,7 @@ where
         if let Some(data) = &test_info.data {
             match data {
                 buck2_data::test_discovery::Data::Session(buck2_data::TestSessionInfo { info }) => {
-                    echo!("Test session: {}", info)?;
+                    echo!(self.output, "Test session: {}", info)?;
                     self.notify_printed();
                 }
                 buck2_data::test_discovery::Data::Tests(..) => {}
@@ -467,7 +529,7 @@ where
                 writeln!(buffer, "{}", line.to_unstyled())?;
             }
             //Printing the test output in multiple lines. It makes easier for the user to read.
-            echo!("{}", buffer)?;
+            echo!(self.output, "{}", buffer)?;
         }
 
         Ok(())
@@ -506,6 +568,7 @@ where
                         x => format!(", and {x} other actions"),
                     };
                     echo!(
+                        self.output,
                         "Waiting on {}{}{}",
                         display::display_event(
                             &sample_event.info().event,
@@ -520,6 +583,7 @@ where
                 None => {
                     if self.expect_spans {
                         echo!(
+                            self.output,
                             "Waiting on buck2 daemon {}...",
                             self.observer.session_info().trace_id
                         )?;
@@ -555,11 +619,12 @@ where
     }
 }
 
-struct PrintDebugCommandToStderr;
+struct PrintDebugCommand<'a>(&'a SimpleConsoleOutput);
 
-impl WhatRanOutputWriter for PrintDebugCommandToStderr {
+impl<'a> WhatRanOutputWriter for PrintDebugCommand<'a> {
     fn emit_command(&mut self, command: WhatRanOutputCommand<'_>) -> anyhow::Result<()> {
         echo!(
+            self.0,
             "{}",
             WhatRanCommandConsoleFormat {
                 reason: command.reason,
@@ -570,3 +635,35 @@ impl WhatRanOutputWriter for PrintDebugCommandToStderr {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_event_observer::event_observer::NoopEventObserverExtra;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_for_file() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("build.log");
+        let mut console = SimpleConsole::<NoopEventObserverExtra>::for_file(
+            TraceId::new(),
+            Verbosity::max(),
+            true,
+            File::create(&path)?,
+        );
+
+        console.handle_stderr("first\nsecond").await?;
+        // The command output goes to stdout, not to the file.
+        console.handle_output(b"command output").await?;
+
+        let contents = std::fs::read_to_string(&path)?;
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 2, "{contents}");
+        assert!(lines[0].starts_with('['));
+        assert!(lines[0].ends_with("] first"), "{contents}");
+        assert!(lines[1].ends_with("] second"), "{contents}");
+        assert!(!contents.contains("command output"));
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use buck2_data::buck_event;
+use buck2_data::instant_event;
+use buck2_event_observer::display;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_events::BuckEvent;
+
+use crate::subscribers::subscriber::EventSubscriber;
+
+/// Prints the last lines of stderr of every failed action once the command finishes, for
+/// `--stderr-tail`. This is independent of the verbosity of the console.
+pub(crate) struct StderrTail {
+    max_lines: usize,
+    failed: Vec<(String, String)>,
+}
+
+impl StderrTail {
+    pub(crate) fn new(max_lines: usize) -> Self {
+        Self {
+            max_lines,
+            failed: Vec::new(),
+        }
+    }
+
+    fn handle_action_error(&mut self, error: &buck2_data::ActionError) -> anyhow::Result<()> {
+        let action_id = display::display_action_identity(
+            error.key.as_ref(),
+            error.name.as_ref(),
+            TargetDisplayOptions::for_log(),
+        )?;
+        let stderr = error
+            .last_command
+            .as_ref()
+            .and_then(|c| c.details.as_ref())
+            .map(|d| display::sanitize_output_colors(d.stderr.as_bytes()))
+            .unwrap_or_default();
+        self.failed.push((action_id, stderr));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for StderrTail {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> anyhow::Result<()> {
+        for event in events {
+            if let buck_event::Data::Instant(instant) = event.data() {
+                if let Some(instant_event::Data::ActionError(error)) = &instant.data {
+                    self.handle_action_error(error)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_command_result(
+        &mut self,
+        _result: &buck2_cli_proto::CommandResult,
+    ) -> anyhow::Result<()> {
+        let failed = std::mem::take(&mut self.failed);
+        if failed.is_empty() {
+            return Ok(());
+        }
+        crate::eprintln!(
+            "Stderr of failed actions (last {} lines each):",
+            self.max_lines
+        )?;
+        for (action_id, stderr) in failed {
+            crate::eprintln!("Action failed: {}", action_id)?;
+            crate::eprintln!("{}", display::stderr_tail(&stderr, self.max_lines))?;
+        }
+        Ok(())
+    }
+}
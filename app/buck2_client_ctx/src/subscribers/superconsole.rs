@@ -801,6 +801,11 @@ fn lines_for_command_details(
                         .with(Color::DarkRed),
                     )]));
                 }
+                if let Some(inputs) = display::materialized_inputs_for_failed(remote_command) {
+                    lines.push(Line::from_iter([Span::new_styled_lossy(
+                        inputs.with(Color::DarkRed),
+                    )]));
+                }
             }
             Some(Command::OmittedLocalCommand(..)) | None => {
                 // Nothing to show in this case.
@@ -110,6 +110,20 @@ impl InvocationPaths {
             .join(ForwardRelativePath::unchecked_new("build_count"))
     }
 
+    /// Targets which built successfully in the last build, see `buck2 build --since-last-success`.
+    pub fn last_success_path(&self) -> AbsNormPathBuf {
+        self.buck_out_path()
+            .join(ForwardRelativePath::unchecked_new("last_success.json"))
+    }
+
+    /// Test targets which failed in the last test run, see `buck2 test --rerun-failed`.
+    pub fn last_test_failures_path(&self) -> AbsNormPathBuf {
+        self.buck_out_path()
+            .join(ForwardRelativePath::unchecked_new(
+                "last_test_failures.json",
+            ))
+    }
+
     pub fn dice_dump_dir(&self) -> AbsNormPathBuf {
         self.buck_out_path()
             .join(ForwardRelativePath::unchecked_new("dice_dump"))
@@ -93,6 +93,15 @@ impl ExecutionPlatform {
         }
     }
 
+    /// Whether this is the platform used when no execution platforms are configured, whose
+    /// executor config is the daemon's fallback config.
+    pub fn is_legacy(&self) -> bool {
+        matches!(
+            &*self.0,
+            ExecutionPlatformData::LegacyExecutionPlatform { .. }
+        )
+    }
+
     pub fn executor_config(&self) -> &Arc<CommandExecutorConfig> {
         match &*self.0 {
             ExecutionPlatformData::Platform {
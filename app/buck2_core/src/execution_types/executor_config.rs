@@ -61,6 +61,21 @@ impl Hash for RemoteExecutorUseCase {
 pub struct RemoteExecutorOptions {
     pub re_max_input_files_bytes: Option<u64>,
     pub re_max_queue_time_ms: Option<u64>,
+    /// With the `RemoteOnlyWithLocalFallback` execution strategy, the number of consecutive
+    /// remote execution errors after which commands may fall back to local execution.
+    pub local_fallback_after_failures: Option<u32>,
+    /// How to run commands that fall back to local execution with the
+    /// `RemoteOnlyWithLocalFallback` execution strategy. These are the local options of the
+    /// executor config, which remote-only executors otherwise don't use.
+    pub local_fallback_options: LocalExecutorOptions,
+    /// The number of times to attempt scheduling an action on RE when RE is unavailable.
+    /// Defaults to one, i.e. no retries.
+    pub re_max_attempts: Option<u32>,
+    /// The delay before the first retry, doubled for every subsequent one.
+    pub re_retry_base_backoff_ms: Option<u64>,
+    /// Whether to randomize retry delays, so that actions failing together don't all retry
+    /// at the same time.
+    pub re_retry_jitter: bool,
 }
 
 /// The actual executor portion of a RemoteEnabled executor. It's possible for a RemoteEnabled
@@ -192,6 +207,9 @@ pub enum CacheUploadBehavior {
     Enabled { max_bytes: Option<u64> },
     #[display(fmt = "disabled")]
     Disabled,
+    /// Report the uploads that would have happened with `Enabled`, without uploading anything.
+    #[display(fmt = "dry run")]
+    DryRun { max_bytes: Option<u64> },
 }
 
 impl Default for CacheUploadBehavior {
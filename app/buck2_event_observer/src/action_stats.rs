@@ -80,7 +80,7 @@ impl ActionStats {
             LastCommandExecutionKind::Local | LastCommandExecutionKind::LocalWorker => {
                 self.local_actions += 1;
             }
-            LastCommandExecutionKind::Cached => {
+            LastCommandExecutionKind::Cached | LastCommandExecutionKind::LocalActionCached => {
                 self.cached_actions += 1;
             }
             LastCommandExecutionKind::Remote => {
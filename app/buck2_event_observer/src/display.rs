@@ -30,6 +30,7 @@ use buck2_test_api::data::TestStatus;
 use buck2_util::commas::commas;
 use buck2_util::truncate::truncate;
 use dupe::Dupe;
+use itertools::Itertools;
 use starlark_map::ordered_set::OrderedSet;
 use superconsole::style::Stylize;
 use superconsole::Line;
@@ -628,6 +629,9 @@ impl<'a> ActionErrorDisplay<'a> {
                         },
                         remote_command.action_digest
                     );
+                    if let Some(inputs) = materialized_inputs_for_failed(remote_command) {
+                        append!("{}", inputs);
+                    }
                 }
                 Some(Command::OmittedLocalCommand(..)) | None => {
                     // Nothing to show in this case.
@@ -651,6 +655,45 @@ impl<'a> ActionErrorDisplay<'a> {
     }
 }
 
+/// Describe where `--materialize-failed-inputs` put the inputs of a failed remote action, so
+/// users know where to look. Returns `None` if nothing was materialized.
+pub fn materialized_inputs_for_failed(
+    remote_command: &buck2_data::RemoteCommand,
+) -> Option<String> {
+    const MAX_PATHS: usize = 10;
+
+    let paths = &remote_command.materialized_inputs_for_failed;
+    if paths.is_empty() {
+        return None;
+    }
+    let mut message = format!(
+        "Inputs materialized for debugging ({}): {}",
+        paths.len(),
+        paths.iter().take(MAX_PATHS).join(", ")
+    );
+    if paths.len() > MAX_PATHS {
+        write!(message, ", and {} more", paths.len() - MAX_PATHS).unwrap();
+    }
+    Some(message)
+}
+
+/// The last `max_lines` lines of a command's stderr, for `--stderr-tail`. The tail is taken on
+/// line boundaries, and says how many lines were left out. Empty stderr is reported as such.
+pub fn stderr_tail(stderr: &str, max_lines: usize) -> String {
+    let stderr = strip_trailing_newline(stderr);
+    if stderr.is_empty() {
+        return "(no stderr)".to_owned();
+    }
+    let lines: Vec<&str> = stderr.lines().collect();
+    let skip = lines.len().saturating_sub(max_lines);
+    let mut tail = String::new();
+    if skip > 0 {
+        writeln!(tail, "... ({} earlier lines omitted)", skip).unwrap();
+    }
+    tail.push_str(&lines[skip..].join("\n"));
+    tail
+}
+
 pub fn get_action_error_reason<'a>(error: &'a buck2_data::ActionError) -> anyhow::Result<String> {
     use buck2_data::action_error::Error;
 
@@ -817,6 +860,53 @@ mod tests {
         assert_eq!("Foo\tBar\nBaz\r\nQuz", sanitized);
     }
 
+    #[test]
+    fn test_stderr_tail() {
+        assert_eq!("(no stderr)", stderr_tail("", 5));
+        assert_eq!("(no stderr)", stderr_tail("\n", 5));
+        assert_eq!("a\nb", stderr_tail("a\nb\n", 5));
+        assert_eq!("a\nb", stderr_tail("a\nb", 2));
+        assert_eq!(
+            "... (2 earlier lines omitted)\nc\nd",
+            stderr_tail("a\nb\nc\nd\n", 2)
+        );
+        // A very long single line is kept whole, rather than truncated mid-line.
+        let long = "x".repeat(10000);
+        assert_eq!(
+            long,
+            stderr_tail(&format!("a\n{}", long), 1)
+                .lines()
+                .last()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_materialized_inputs_for_failed() {
+        let remote_command = |paths: Vec<String>| buck2_data::RemoteCommand {
+            materialized_inputs_for_failed: paths,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            None,
+            materialized_inputs_for_failed(&remote_command(vec![]))
+        );
+        assert_eq!(
+            Some("Inputs materialized for debugging (2): buck-out/a, buck-out/b".to_owned()),
+            materialized_inputs_for_failed(&remote_command(vec![
+                "buck-out/a".to_owned(),
+                "buck-out/b".to_owned()
+            ]))
+        );
+        let many = materialized_inputs_for_failed(&remote_command(
+            (0..12).map(|i| format!("in{}", i)).collect(),
+        ))
+        .unwrap();
+        assert!(many.starts_with("Inputs materialized for debugging (12): in0, in1,"));
+        assert!(many.ends_with("in9, and 2 more"));
+    }
+
     #[test]
     fn strips_trailing_newline_character() {
         let stream_contents = "test\n";
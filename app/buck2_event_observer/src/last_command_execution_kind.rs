@@ -9,6 +9,7 @@
 
 pub enum LastCommandExecutionKind {
     Local,
+    LocalActionCached,
     LocalWorker,
     Remote,
     Cached,
@@ -31,6 +32,13 @@ pub fn get_last_command_execution_kind(
     if let Some(command_kind) = last_command_kind {
         use buck2_data::command_execution_kind::Command;
         match command_kind.command.as_ref() {
+            Some(Command::LocalCommand(buck2_data::LocalCommand {
+                cache_hit: true, ..
+            }))
+            | Some(Command::OmittedLocalCommand(buck2_data::OmittedLocalCommand {
+                cache_hit: true,
+                ..
+            })) => LastCommandExecutionKind::LocalActionCached,
             Some(Command::LocalCommand(..)) | Some(Command::OmittedLocalCommand(..)) => {
                 LastCommandExecutionKind::Local
             }
@@ -15,7 +15,12 @@ use dupe::Dupe;
 enum VerbosityError {
     #[error("Can't have more than 1 level set at a time")]
     MoreThan1Level,
-    #[error("Item name not reconized: {0}")]
+    #[error(
+        "Verbosity not recognized: `{}`, expected a level (0-4, {}) or an item ({})",
+        .0,
+        VerbosityLevel::NAMES.join(", "),
+        VerbosityItem::NAMES.join(", ")
+    )]
     UnknownItem(String),
 }
 
@@ -101,6 +106,21 @@ impl VerbosityLevel {
         }
     }
 
+    /// The symbolic names accepted in place of numeric levels.
+    const NAMES: &'static [&'static str] = &["quiet", "normal", "verbose", "detailed", "trace"];
+
+    fn from_name(value: &str) -> Option<Self> {
+        let level = match value {
+            "quiet" => Self::Quiet,
+            "normal" => Self::Default,
+            "verbose" => Self::Verbose,
+            "detailed" => Self::AllCommands,
+            "trace" => Self::AllStderr,
+            _ => return None,
+        };
+        Some(level)
+    }
+
     fn from(value: i64) -> Self {
         match value {
             i if i <= 0 => Self::Quiet,
@@ -113,6 +133,16 @@ impl VerbosityLevel {
 }
 
 impl VerbosityItem {
+    const NAMES: &'static [&'static str] = &[
+        "stderr",
+        "full_failed_command",
+        "commands",
+        "actions",
+        "status",
+        "stats",
+        "success",
+    ];
+
     fn from(value: &str) -> anyhow::Result<Self> {
         let item = match value {
             "stderr" => Self::Stderr,
@@ -137,6 +167,8 @@ impl Verbosity {
         for &value in &split {
             if let Ok(value) = value.parse::<i64>() {
                 levels.push(VerbosityLevel::from(value));
+            } else if let Some(level) = VerbosityLevel::from_name(value) {
+                levels.push(level);
             } else {
                 items.insert(VerbosityItem::from(value)?);
             }
@@ -153,6 +185,11 @@ impl Verbosity {
         Ok(Self::from_items(items))
     }
 
+    /// The highest verbosity, printing everything.
+    pub fn max() -> Verbosity {
+        Self::from_items(VerbosityLevel::AllStderr.items())
+    }
+
     fn from_items(items: HashSet<VerbosityItem>) -> Self {
         let mut array = [None; VERBOSITY_ITEM_VARIANTS];
         let vec: Vec<_> = items.into_iter().map(Some).collect();
@@ -240,6 +277,18 @@ mod tests {
         assert!(verbosity.print_success_stderr());
     }
 
+    #[test]
+    fn test_max() {
+        let verbosity = Verbosity::max();
+        assert!(verbosity.print_status());
+        assert!(verbosity.print_success_message());
+        assert!(verbosity.print_failure_full_command());
+        assert!(verbosity.always_print_stats_in_status());
+        assert!(verbosity.print_all_actions());
+        assert!(verbosity.print_all_commands());
+        assert!(verbosity.print_success_stderr());
+    }
+
     #[test]
     fn test_verbose() {
         let verbosity = Verbosity::try_from_cli("2").unwrap();
@@ -264,6 +313,50 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_named_levels() {
+        for (name, number) in [
+            ("quiet", "0"),
+            ("normal", "1"),
+            ("verbose", "2"),
+            ("detailed", "3"),
+            ("trace", "4"),
+        ] {
+            let named = Verbosity::try_from_cli(name).unwrap();
+            let numeric = Verbosity::try_from_cli(number).unwrap();
+            assert_eq!(
+                named.items.iter().flatten().collect::<HashSet<_>>(),
+                numeric.items.iter().flatten().collect::<HashSet<_>>(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_named_level_with_items() {
+        let verbosity = Verbosity::try_from_cli("detailed,stderr").unwrap();
+        assert!(verbosity.print_all_commands());
+        assert!(verbosity.print_success_stderr());
+
+        let verbosity = Verbosity::try_from_cli("quiet,status").unwrap();
+        assert!(verbosity.print_status());
+        assert!(!verbosity.print_success_message());
+
+        assert!(Verbosity::try_from_cli("quiet,1").is_err());
+    }
+
+    #[test]
+    fn test_unknown_name_lists_valid_names() {
+        let err = Verbosity::try_from_cli("loud").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Verbosity not recognized: `loud`, expected a level \
+            (0-4, quiet, normal, verbose, detailed, trace) or an item \
+            (stderr, full_failed_command, commands, actions, status, stats, success)"
+        );
+    }
+
     #[test]
     fn test_default() {
         let verbosity = Verbosity::default();
@@ -11,6 +11,7 @@
 
 use std::sync::Arc;
 
+use buck2_core::category::Category;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use dice::DiceComputations;
@@ -36,10 +37,13 @@ pub trait SetCommandExecutor {
 }
 
 pub trait HasCommandExecutor {
+    /// `category` is the category of the action to execute, if any, which may override the
+    /// execution strategy.
     fn get_command_executor(
         &self,
         artifact_fs: &ArtifactFs,
         config: &CommandExecutorConfig,
+        category: Option<&Category>,
     ) -> anyhow::Result<CommandExecutorResponse>;
 }
 
@@ -57,13 +61,16 @@ impl HasCommandExecutor for DiceComputations {
         &self,
         artifact_fs: &ArtifactFs,
         config: &CommandExecutorConfig,
+        category: Option<&Category>,
     ) -> anyhow::Result<CommandExecutorResponse> {
         let holder = self
             .per_transaction_data()
             .data
             .get::<HasCommandExecutorHolder>()
             .expect("CommandExecutorDelegate should be set");
-        holder.delegate.get_command_executor(artifact_fs, config)
+        holder
+            .delegate
+            .get_command_executor(artifact_fs, config, category)
     }
 }
 
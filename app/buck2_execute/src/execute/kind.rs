@@ -31,6 +31,13 @@ pub enum CommandExecutionKind {
         command: Vec<String>,
         env: SortedVectorMap<String, String>,
     },
+    /// This action was served by the local action cache and not executed.
+    #[display(fmt = "local_action_cache")]
+    LocalActionCache {
+        digest: ActionDigest,
+        command: Vec<String>,
+        env: SortedVectorMap<String, String>,
+    },
     /// This action was executed via a remote executor.
     #[display(fmt = "remote")]
     Remote {
@@ -73,6 +80,7 @@ impl CommandExecutionKind {
     pub fn as_enum(&self) -> buck2_data::ActionExecutionKind {
         match self {
             Self::Local { .. } => buck2_data::ActionExecutionKind::Local,
+            Self::LocalActionCache { .. } => buck2_data::ActionExecutionKind::LocalActionCache,
             Self::LocalWorker { .. } | Self::LocalWorkerInit { .. } => {
                 buck2_data::ActionExecutionKind::LocalWorker
             }
@@ -90,25 +98,12 @@ impl CommandExecutionKind {
                 command,
                 env,
                 digest,
-            } => {
-                if omit_details {
-                    Command::OmittedLocalCommand(buck2_data::OmittedLocalCommand {
-                        action_digest: digest.to_string(),
-                    })
-                } else {
-                    Command::LocalCommand(buck2_data::LocalCommand {
-                        action_digest: digest.to_string(),
-                        argv: command.to_owned(),
-                        env: env
-                            .iter()
-                            .map(|(key, value)| buck2_data::EnvironmentEntry {
-                                key: key.clone(),
-                                value: value.clone(),
-                            })
-                            .collect(),
-                    })
-                }
-            }
+            } => local_command_to_proto(digest, command, env, false, omit_details),
+            Self::LocalActionCache {
+                command,
+                env,
+                digest,
+            } => local_command_to_proto(digest, command, env, true, omit_details),
             Self::Remote {
                 details,
                 queue_time,
@@ -187,6 +182,36 @@ impl CommandExecutionKind {
     }
 }
 
+fn local_command_to_proto(
+    digest: &ActionDigest,
+    command: &[String],
+    env: &SortedVectorMap<String, String>,
+    cache_hit: bool,
+    omit_details: bool,
+) -> buck2_data::command_execution_kind::Command {
+    use buck2_data::command_execution_kind::Command;
+
+    if omit_details {
+        Command::OmittedLocalCommand(buck2_data::OmittedLocalCommand {
+            action_digest: digest.to_string(),
+            cache_hit,
+        })
+    } else {
+        Command::LocalCommand(buck2_data::LocalCommand {
+            action_digest: digest.to_string(),
+            argv: command.to_owned(),
+            env: env
+                .iter()
+                .map(|(key, value)| buck2_data::EnvironmentEntry {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            cache_hit,
+        })
+    }
+}
+
 /// Structured data for a RE request.
 #[derive(Debug, Clone)]
 pub struct RemoteCommandExecutionDetails {
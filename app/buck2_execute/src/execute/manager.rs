@@ -43,6 +43,8 @@ pub struct CommandExecutionManager {
     pub events: EventDispatcher,
     pub liveliness_observer: Arc<dyn LivelinessObserver>,
     pub intend_to_fallback_on_failure: bool,
+    /// Whether the remote dep file cache was queried for this command, whatever the outcome.
+    pub remote_dep_file_cache_queried: bool,
 }
 
 impl CommandExecutionManager {
@@ -56,6 +58,7 @@ impl CommandExecutionManager {
             events,
             liveliness_observer,
             intend_to_fallback_on_failure: false,
+            remote_dep_file_cache_queried: false,
         }
     }
 
@@ -25,6 +25,7 @@ pub mod output;
 pub mod prepared;
 pub mod request;
 pub mod result;
+pub mod strategy;
 pub mod target;
 pub mod testing_dry_run;
 
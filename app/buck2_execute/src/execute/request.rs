@@ -11,6 +11,7 @@ use std::fmt::Display;
 use std::time::Duration;
 
 use allocative::Allocative;
+use buck2_cli_proto::common_build_options::ExecutionStrategy;
 use buck2_common::file_ops::FileMetadata;
 use buck2_common::file_ops::TrackedFileDigest;
 use buck2_common::local_resource_state::LocalResourceState;
@@ -193,6 +194,34 @@ impl ExecutorPreference {
             _ => false,
         }
     }
+
+    /// The preference of a hybrid executor for a build with this execution strategy. Commands
+    /// are then run with this and-ed with their own preference.
+    ///
+    /// In paranoid mode, both executors are always run, so preferences which are not
+    /// requirements are erased.
+    pub fn for_hybrid_executor(
+        strategy: ExecutionStrategy,
+        paranoid: bool,
+    ) -> anyhow::Result<Self> {
+        let preference = match strategy {
+            ExecutionStrategy::HybridPreferLocal => Self::LocalPreferred,
+            ExecutionStrategy::HybridPreferRemote => Self::RemotePreferred,
+            ExecutionStrategy::LocalOnly => Self::LocalRequired,
+            ExecutionStrategy::RemoteOnly | ExecutionStrategy::RemoteOnlyWithLocalFallback => {
+                Self::RemoteRequired
+            }
+            // The hybrid executor picks where each action runs from the size of its inputs.
+            ExecutionStrategy::LocalFirstThenRemote
+            | ExecutionStrategy::Default
+            | ExecutionStrategy::NoExecution => Self::Default,
+        };
+        if paranoid {
+            preference.and(Self::DefaultErasePreferences)
+        } else {
+            Ok(preference)
+        }
+    }
 }
 
 pub struct CommandExecutionPaths {
@@ -683,3 +712,58 @@ impl ResolvedCommandExecutionOutput {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn and(a: ExecutorPreference, b: ExecutorPreference) -> String {
+        match a.and(b) {
+            Ok(p) => p.to_string(),
+            Err(_) => "error".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_executor_preference_and() {
+        use ExecutorPreference::*;
+
+        // Requirements win over preferences, in either order.
+        assert_eq!(and(LocalPreferred, RemoteRequired), "RemoteRequired");
+        assert_eq!(and(RemoteRequired, LocalPreferred), "RemoteRequired");
+        assert_eq!(and(RemotePreferred, LocalRequired), "LocalRequired");
+        // Conflicting requirements are an error.
+        assert_eq!(and(LocalRequired, RemoteRequired), "error");
+        // Erasing preferences only erases preferences, not requirements.
+        assert_eq!(
+            and(LocalPreferred, DefaultErasePreferences),
+            "DefaultErasePreferences"
+        );
+        assert_eq!(and(DefaultErasePreferences, LocalRequired), "LocalRequired");
+        // Between two conflicting preferences, the first one wins.
+        assert_eq!(and(LocalPreferred, RemotePreferred), "LocalPreferred");
+        assert_eq!(and(RemotePreferred, LocalPreferred), "RemotePreferred");
+        assert_eq!(and(Default, RemotePreferred), "RemotePreferred");
+    }
+
+    #[test]
+    fn test_for_hybrid_executor() -> anyhow::Result<()> {
+        assert_eq!(
+            ExecutorPreference::for_hybrid_executor(ExecutionStrategy::HybridPreferLocal, false)?
+                .to_string(),
+            "LocalPreferred"
+        );
+        // This is why `--prefer-local` has no effect in paranoid mode.
+        assert_eq!(
+            ExecutorPreference::for_hybrid_executor(ExecutionStrategy::HybridPreferLocal, true)?
+                .to_string(),
+            "DefaultErasePreferences"
+        );
+        assert_eq!(
+            ExecutorPreference::for_hybrid_executor(ExecutionStrategy::LocalOnly, true)?
+                .to_string(),
+            "LocalRequired"
+        );
+        Ok(())
+    }
+}
@@ -372,6 +372,7 @@ mod tests {
                         value: "1".to_owned(),
                     }],
                     action_digest: format!("{}:{}", "0".repeat(64), "123"),
+                    cache_hit: false,
                 },
             )),
         };
@@ -476,6 +477,7 @@ mod tests {
             buck2_data::command_execution_kind::Command::OmittedLocalCommand(
                 buck2_data::OmittedLocalCommand {
                     action_digest: format!("{}:{}", "0".repeat(64), "123"),
+                    cache_hit: false,
                 },
             ),
         );
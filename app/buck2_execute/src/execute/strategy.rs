@@ -0,0 +1,245 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! How the execution strategy of a build selects the executor for an executor config. This is
+//! shared by the command executor factory and `buck2 audit execution-platforms`, so that the
+//! audit shows what a build would actually do.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use buck2_cli_proto::common_build_options::ExecutionStrategy;
+use buck2_core::execution_types::executor_config::Executor;
+use buck2_core::execution_types::executor_config::RemoteEnabledExecutor;
+use derive_more::Display;
+
+pub trait ExecutionStrategyExt {
+    fn ban_local(&self) -> bool;
+    fn ban_remote(&self) -> bool;
+    fn ban_hybrid(&self) -> bool;
+}
+
+impl ExecutionStrategyExt for ExecutionStrategy {
+    fn ban_local(&self) -> bool {
+        match self {
+            Self::RemoteOnly | Self::RemoteOnlyWithLocalFallback | Self::NoExecution => true,
+            _ => false,
+        }
+    }
+
+    fn ban_remote(&self) -> bool {
+        match self {
+            Self::LocalOnly | Self::NoExecution => true,
+            _ => false,
+        }
+    }
+
+    fn ban_hybrid(&self) -> bool {
+        match self {
+            Self::NoExecution => true,
+            _ => false,
+        }
+    }
+}
+
+/// Parses `build.execution_strategy_overrides`, a list of `category=strategy` pairs (e.g.
+/// `cxx_compile=remote_only`) which override the execution strategy for actions of a category.
+pub fn parse_execution_strategy_overrides(
+    entries: &[String],
+) -> anyhow::Result<HashMap<String, ExecutionStrategy>> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (category, strategy) = entry.split_once('=').with_context(|| {
+                format!(
+                    "Invalid execution strategy override `{}`, expected `category=strategy`",
+                    entry
+                )
+            })?;
+            let strategy = match strategy.trim() {
+                "default" => ExecutionStrategy::Default,
+                "local_only" => ExecutionStrategy::LocalOnly,
+                "remote_only" => ExecutionStrategy::RemoteOnly,
+                "remote_only_with_local_fallback" => ExecutionStrategy::RemoteOnlyWithLocalFallback,
+                "local_first_then_remote" => ExecutionStrategy::LocalFirstThenRemote,
+                "hybrid_prefer_local" => ExecutionStrategy::HybridPreferLocal,
+                "hybrid_prefer_remote" => ExecutionStrategy::HybridPreferRemote,
+                "no_execution" => ExecutionStrategy::NoExecution,
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "Invalid execution strategy `{}` for category `{}`",
+                        other,
+                        category.trim()
+                    ));
+                }
+            };
+            Ok((category.trim().to_owned(), strategy))
+        })
+        .collect()
+}
+
+/// The strategy for actions of `category`: its override if there is one, otherwise `strategy`.
+pub fn strategy_for_category(
+    strategy: ExecutionStrategy,
+    overrides: &HashMap<String, ExecutionStrategy>,
+    category: Option<&str>,
+) -> ExecutionStrategy {
+    category
+        .and_then(|c| overrides.get(c))
+        .copied()
+        .unwrap_or(strategy)
+}
+
+/// The executor that runs actions for an executor config under an execution strategy.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub enum SelectedExecutor {
+    #[display(fmt = "local")]
+    Local,
+    #[display(fmt = "remote")]
+    Remote,
+    /// A remote-only config, with a hybrid executor which only runs actions locally once RE
+    /// keeps failing.
+    #[display(fmt = "remote, with local fallback")]
+    RemoteWithLocalFallback,
+    #[display(fmt = "hybrid")]
+    Hybrid,
+}
+
+impl SelectedExecutor {
+    /// Select the executor, or `None` if the strategy bans the executor that is configured.
+    pub fn select(executor: &Executor, strategy: ExecutionStrategy) -> Option<SelectedExecutor> {
+        match executor {
+            Executor::Local(..)
+            | Executor::RemoteEnabled {
+                executor: RemoteEnabledExecutor::Local(..),
+                ..
+            } => (!strategy.ban_local()).then_some(SelectedExecutor::Local),
+            Executor::RemoteEnabled {
+                executor: RemoteEnabledExecutor::Remote(..),
+                ..
+            } => {
+                if strategy.ban_remote() {
+                    None
+                } else if strategy == ExecutionStrategy::RemoteOnlyWithLocalFallback {
+                    Some(SelectedExecutor::RemoteWithLocalFallback)
+                } else {
+                    Some(SelectedExecutor::Remote)
+                }
+            }
+            Executor::RemoteEnabled {
+                executor: RemoteEnabledExecutor::Hybrid { .. },
+                ..
+            } => (!strategy.ban_hybrid()).then_some(SelectedExecutor::Hybrid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use buck2_cli_proto::common_build_options::ExecutionStrategy;
+    use buck2_core::execution_types::executor_config::CacheUploadBehavior;
+    use buck2_core::execution_types::executor_config::Executor;
+    use buck2_core::execution_types::executor_config::HybridExecutionLevel;
+    use buck2_core::execution_types::executor_config::LocalExecutorOptions;
+    use buck2_core::execution_types::executor_config::RemoteEnabledExecutor;
+    use buck2_core::execution_types::executor_config::RemoteExecutorOptions;
+    use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
+    use starlark_map::sorted_map::SortedMap;
+
+    use super::*;
+
+    fn remote_enabled(executor: RemoteEnabledExecutor) -> Executor {
+        Executor::RemoteEnabled {
+            executor,
+            re_properties: SortedMap::new(),
+            re_use_case: RemoteExecutorUseCase::buck2_default(),
+            re_action_key: None,
+            cache_upload_behavior: CacheUploadBehavior::Disabled,
+            remote_cache_enabled: true,
+            remote_dep_file_cache_enabled: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_execution_strategy_overrides() -> anyhow::Result<()> {
+        let overrides = parse_execution_strategy_overrides(&[
+            "cxx_compile=remote_only".to_owned(),
+            " test = local_only".to_owned(),
+        ])?;
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides["cxx_compile"], ExecutionStrategy::RemoteOnly);
+        assert_eq!(overrides["test"], ExecutionStrategy::LocalOnly);
+
+        assert!(parse_execution_strategy_overrides(&["cxx_compile".to_owned()]).is_err());
+        assert!(parse_execution_strategy_overrides(&["cxx_compile=fast".to_owned()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_strategy_for_category() {
+        let overrides = HashMap::from([("cxx_compile".to_owned(), ExecutionStrategy::RemoteOnly)]);
+        assert_eq!(
+            strategy_for_category(ExecutionStrategy::Default, &overrides, Some("cxx_compile")),
+            ExecutionStrategy::RemoteOnly
+        );
+        assert_eq!(
+            strategy_for_category(ExecutionStrategy::Default, &overrides, Some("cxx_link")),
+            ExecutionStrategy::Default
+        );
+        assert_eq!(
+            strategy_for_category(ExecutionStrategy::LocalOnly, &overrides, None),
+            ExecutionStrategy::LocalOnly
+        );
+    }
+
+    #[test]
+    fn test_select_executor() {
+        let local = Executor::Local(LocalExecutorOptions::default());
+        let remote = remote_enabled(RemoteEnabledExecutor::Remote(
+            RemoteExecutorOptions::default(),
+        ));
+        let hybrid = remote_enabled(RemoteEnabledExecutor::Hybrid {
+            local: LocalExecutorOptions::default(),
+            remote: RemoteExecutorOptions::default(),
+            level: HybridExecutionLevel::Limited,
+        });
+
+        assert_eq!(
+            SelectedExecutor::select(&local, ExecutionStrategy::Default),
+            Some(SelectedExecutor::Local)
+        );
+        assert_eq!(
+            SelectedExecutor::select(&local, ExecutionStrategy::RemoteOnly),
+            None
+        );
+        assert_eq!(
+            SelectedExecutor::select(&remote, ExecutionStrategy::LocalOnly),
+            None
+        );
+        assert_eq!(
+            SelectedExecutor::select(&remote, ExecutionStrategy::Default),
+            Some(SelectedExecutor::Remote)
+        );
+        assert_eq!(
+            SelectedExecutor::select(&remote, ExecutionStrategy::RemoteOnlyWithLocalFallback),
+            Some(SelectedExecutor::RemoteWithLocalFallback)
+        );
+        // The hybrid executor enforces `--local-only` and `--remote-only` itself.
+        assert_eq!(
+            SelectedExecutor::select(&hybrid, ExecutionStrategy::LocalOnly),
+            Some(SelectedExecutor::Hybrid)
+        );
+        assert_eq!(
+            SelectedExecutor::select(&hybrid, ExecutionStrategy::NoExecution),
+            None
+        );
+    }
+}
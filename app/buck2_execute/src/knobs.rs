@@ -7,14 +7,36 @@
  * of this source tree.
  */
 
+use std::sync::Arc;
+
 use dupe::Dupe;
 
+/// Default for `build.local_retry_attempts`.
+pub const DEFAULT_LOCAL_RETRY_ATTEMPTS: u32 = 1;
+
 /// Command-level config that can tweak how the executors work.
-#[derive(Clone, Dupe, Default)]
+#[derive(Clone, Dupe)]
 pub struct ExecutorGlobalKnobs {
     pub enable_miniperf: bool,
 
     /// Whether to emit action keys to execution logs (thos are pretty verbose and omitted by
     /// default).
     pub log_action_keys: bool,
+
+    /// Exit codes of local commands which indicate a transient failure, so the command is run
+    /// again (up to `local_retry_attempts` times) rather than failing the action.
+    pub local_retry_exit_codes: Arc<[i32]>,
+
+    pub local_retry_attempts: u32,
+}
+
+impl Default for ExecutorGlobalKnobs {
+    fn default() -> Self {
+        Self {
+            enable_miniperf: false,
+            log_action_keys: false,
+            local_retry_exit_codes: Arc::new([]),
+            local_retry_attempts: DEFAULT_LOCAL_RETRY_ATTEMPTS,
+        }
+    }
 }
@@ -16,6 +16,7 @@ use buck2_action_metadata_proto::REMOTE_DEP_FILE_KEY;
 use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::execute::action_digest::ActionDigest;
 use buck2_execute::execute::action_digest::ActionDigestKind;
 use buck2_execute::execute::dep_file_digest::DepFileDigest;
@@ -66,6 +67,22 @@ impl CacheType {
     }
 }
 
+fn report_cache_outcome(
+    events: &EventDispatcher,
+    command: &PreparedCommand<'_, '_>,
+    cache_type: &CacheType,
+    digest: &ActionDigest,
+    hit: bool,
+) {
+    events.instant_event(buck2_data::ActionCacheOutcome {
+        key: Some(command.target.as_proto_action_key()),
+        name: Some(command.target.as_proto_action_name()),
+        action_digest: digest.to_string(),
+        cache_type: cache_type.to_proto().into(),
+        hit,
+    });
+}
+
 async fn query_action_cache_and_download_result(
     // Differentiate between regular action cache look up and remote dep file based look up
     cache_type: CacheType,
@@ -73,7 +90,7 @@ async fn query_action_cache_and_download_result(
     materializer: &Arc<dyn Materializer>,
     re_client: &ManagedRemoteExecutionClient,
     re_use_case: RemoteExecutorUseCase,
-    re_action_key: &Option<String>,
+    identity: &ReActionIdentity<'_>,
     paranoid: &Option<ParanoidDownloader>,
     action_digest: &ActionDigest,
     command: &PreparedCommand<'_, '_>,
@@ -128,7 +145,10 @@ async fn query_action_cache_and_download_result(
             return ControlFlow::Break(manager.error("remote_action_cache", e));
         }
         Ok(Some(response)) => response,
-        Ok(None) => return ControlFlow::Continue(manager),
+        Ok(None) => {
+            report_cache_outcome(&manager.events, command, &cache_type, &digest, false);
+            return ControlFlow::Continue(manager);
+        }
     };
 
     let action_exit_code = response.action_result.exit_code;
@@ -147,6 +167,7 @@ async fn query_action_cache_and_download_result(
 
                 if metadata.is_none() {
                     // No entry found
+                    report_cache_outcome(&manager.events, command, &cache_type, &digest, false);
                     return ControlFlow::Continue(manager);
                 }
                 let dep_file_entry = match RemoteDepFile::decode(metadata.unwrap().value.as_slice())
@@ -163,11 +184,9 @@ async fn query_action_cache_and_download_result(
             }
         };
 
-    let identity = ReActionIdentity::new(
-        command.target,
-        re_action_key.as_deref(),
-        command.request.paths(),
-    );
+    // Only report the hit once its outputs are materialized: until then, the action may still
+    // fail and fall back to running.
+    let events = manager.events.dupe();
 
     let res = download_action_results(
         request,
@@ -176,7 +195,7 @@ async fn query_action_cache_and_download_result(
         re_use_case,
         digest_config,
         manager,
-        &identity,
+        identity,
         buck2_data::CacheHit {
             action_digest: digest.to_string(),
             action_key: Some(identity.action_key.clone()),
@@ -199,6 +218,9 @@ async fn query_action_cache_and_download_result(
     .await;
 
     let DownloadResult::Result(mut res) = res;
+    if res.was_success() {
+        report_cache_outcome(&events, command, &cache_type, &digest, true);
+    }
     match &cache_type {
         CacheType::RemoteDepFileCache(key) => {
             tracing::trace!(
@@ -219,6 +241,20 @@ async fn query_action_cache_and_download_result(
     ControlFlow::Break(res)
 }
 
+impl ActionCacheChecker {
+    /// Identifies `command` in the requests sent to RE, including the `re_action_key`.
+    pub fn re_action_identity<'a>(
+        &self,
+        command: &PreparedCommand<'a, 'a>,
+    ) -> ReActionIdentity<'a> {
+        ReActionIdentity::new(
+            command.target,
+            self.re_action_key.as_deref(),
+            command.request.paths(),
+        )
+    }
+}
+
 #[async_trait]
 impl PreparedCommandOptionalExecutor for ActionCacheChecker {
     async fn maybe_execute(
@@ -234,7 +270,7 @@ impl PreparedCommandOptionalExecutor for ActionCacheChecker {
             &self.materializer,
             &self.re_client,
             self.re_use_case,
-            &self.re_action_key,
+            &self.re_action_identity(command),
             &self.paranoid,
             action_digest,
             command,
@@ -268,6 +304,20 @@ pub struct RemoteDepFileCacheChecker {
     pub paranoid: Option<ParanoidDownloader>,
 }
 
+impl RemoteDepFileCacheChecker {
+    /// Identifies `command` in the requests sent to RE, including the `re_action_key`.
+    pub fn re_action_identity<'a>(
+        &self,
+        command: &PreparedCommand<'a, 'a>,
+    ) -> ReActionIdentity<'a> {
+        ReActionIdentity::new(
+            command.target,
+            self.re_action_key.as_deref(),
+            command.request.paths(),
+        )
+    }
+}
+
 #[async_trait]
 impl PreparedCommandOptionalExecutor for RemoteDepFileCacheChecker {
     async fn maybe_execute(
@@ -283,6 +333,8 @@ impl PreparedCommandOptionalExecutor for RemoteDepFileCacheChecker {
             }
             Some(key) => key.dupe(),
         };
+        let mut manager = manager;
+        manager.remote_dep_file_cache_queried = true;
 
         let action_digest = &command.prepared_action.action_and_blobs.action;
 
@@ -292,7 +344,7 @@ impl PreparedCommandOptionalExecutor for RemoteDepFileCacheChecker {
             &self.materializer,
             &self.re_client,
             self.re_use_case,
-            &self.re_action_key,
+            &self.re_action_identity(command),
             &self.paranoid,
             action_digest,
             command,
@@ -264,17 +264,24 @@ impl CacheUploader {
                 }
                 .await;
 
-                let (success, error, re_error_code) = match &res {
+                let (success, error, re_error_code, output_size_limit) = match &res {
                     Ok(CacheUploadOutcome::Success) => {
                         tracing::info!("Cache upload for `{}` succeeded", digest_str);
-                        (CacheUploadSuccessful::Yes, String::new(), None)
+                        (CacheUploadSuccessful::Yes, String::new(), None, None)
                     }
                     Ok(CacheUploadOutcome::Rejected(reason)) => {
                         tracing::info!("Cache upload for `{}` rejected: {:#}", digest_str, reason);
+                        let output_size_limit = match reason {
+                            CacheUploadRejectionReason::OutputExceedsLimit { max_bytes } => {
+                                Some(*max_bytes)
+                            }
+                            CacheUploadRejectionReason::SymlinkOutput => None,
+                        };
                         (
                             CacheUploadSuccessful::No,
                             format!("Rejected: {}", reason),
                             None,
+                            output_size_limit,
                         )
                     }
                     Err(e) => (
@@ -282,6 +289,7 @@ impl CacheUploader {
                         format!("{:#}", e),
                         e.downcast_ref::<REClientError>()
                             .map(|e| e.code.to_string()),
+                        None,
                     ),
                 };
 
@@ -298,6 +306,7 @@ impl CacheUploader {
                         tree_digests: tree_digests.into_map(|d| d.to_string()),
                         output_bytes: Some(output_bytes),
                         reason: reason.into(),
+                        output_size_limit,
                     }),
                 )
             },
@@ -549,6 +558,91 @@ impl UploadCache for CacheUploader {
     }
 }
 
+/// A cache uploader for `CacheUploadBehavior::DryRun`, used to evaluate how much would be uploaded
+/// before enabling cache uploads. For every upload `CacheUploader` would attempt, it logs the
+/// action and the size of its outputs, and records a `CacheUpload` span with `dry_run` set, which
+/// `buck2 log summary --cache` adds up. Nothing is uploaded. Dep file uploads are not projected.
+pub struct DryRunCacheUploader {
+    max_bytes: Option<u64>,
+}
+
+impl DryRunCacheUploader {
+    pub fn new(max_bytes: Option<u64>) -> DryRunCacheUploader {
+        DryRunCacheUploader { max_bytes }
+    }
+
+    /// The end of the span for an upload of `output_bytes`, which `CacheUploader` would reject
+    /// if the outputs exceed `max_bytes`.
+    fn projected_upload_end(
+        &self,
+        target: &dyn CommandExecutionTarget,
+        action_digest: String,
+        output_bytes: u64,
+    ) -> buck2_data::CacheUploadEnd {
+        let output_size_limit = self.max_bytes.filter(|max_bytes| output_bytes > *max_bytes);
+        buck2_data::CacheUploadEnd {
+            key: Some(target.as_proto_action_key()),
+            name: Some(target.as_proto_action_name()),
+            action_digest,
+            success: false,
+            output_bytes: Some(output_bytes),
+            reason: buck2_data::CacheUploadReason::LocalExecution.into(),
+            output_size_limit,
+            dry_run: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl UploadCache for DryRunCacheUploader {
+    async fn upload(
+        &self,
+        info: &CacheUploadInfo<'_>,
+        res: &CommandExecutionResult,
+        _dep_file_entry: Option<DepFileEntry>,
+        action_digest_and_blobs: &ActionDigestAndBlobs,
+    ) -> anyhow::Result<CacheUploadResult> {
+        if res.was_locally_executed() {
+            let digest_str = action_digest_and_blobs.action.to_string();
+            let end = self.projected_upload_end(
+                info.target,
+                digest_str.clone(),
+                res.calc_output_size_bytes(),
+            );
+            match end.output_size_limit {
+                None => tracing::info!(
+                    "Cache upload dry run: would upload {} bytes for `{}` (`{}`)",
+                    end.output_bytes.unwrap_or_default(),
+                    info.target.re_action_key(),
+                    digest_str
+                ),
+                Some(max_bytes) => tracing::info!(
+                    "Cache upload dry run: would reject `{}` (`{}`), its outputs exceed {} bytes",
+                    info.target.re_action_key(),
+                    digest_str,
+                    max_bytes
+                ),
+            }
+            span_async(
+                buck2_data::CacheUploadStart {
+                    key: Some(info.target.as_proto_action_key()),
+                    name: Some(info.target.as_proto_action_name()),
+                    action_digest: digest_str,
+                    reason: buck2_data::CacheUploadReason::LocalExecution.into(),
+                },
+                async { ((), Box::new(end)) },
+            )
+            .await;
+        }
+
+        Ok(CacheUploadResult {
+            did_cache_upload: false,
+            did_dep_file_cache_upload: false,
+        })
+    }
+}
+
 fn systemtime_to_ttimestamp(time: SystemTime) -> anyhow::Result<TTimestamp> {
     let duration = time.duration_since(SystemTime::UNIX_EPOCH)?;
     Ok(TTimestamp {
@@ -558,3 +652,222 @@ fn systemtime_to_ttimestamp(time: SystemTime) -> anyhow::Result<TTimestamp> {
         ..Default::default()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_common::liveliness_observer::NoopLivelinessObserver;
+    use buck2_core::base_deferred_key::BaseDeferredKey;
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::name::CellName;
+    use buck2_core::cells::CellResolver;
+    use buck2_core::configuration::data::ConfigurationData;
+    use buck2_core::fs::buck_out_path::BuckOutPath;
+    use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+    use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+    use buck2_core::fs::project::ProjectRootTemp;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+    use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+    use buck2_events::create_source_sink_pair;
+    use buck2_events::dispatch::with_dispatcher_async;
+    use buck2_events::dispatch::EventDispatcher;
+    use buck2_execute::artifact_value::ArtifactValue;
+    use buck2_execute::execute::action_digest_and_blobs::ActionDigestAndBlobsBuilder;
+    use buck2_execute::execute::claim::MutexClaimManager;
+    use buck2_execute::execute::kind::CommandExecutionKind;
+    use buck2_execute::execute::manager::CommandExecutionManager;
+    use buck2_execute::execute::output::CommandStdStreams;
+    use buck2_execute::execute::request::CommandExecutionOutput;
+    use buck2_execute::execute::request::OutputType;
+    use buck2_execute::execute::result::CommandExecutionMetadata;
+    use buck2_execute::materialize::nodisk::NoDiskMaterializer;
+    use buck2_wrapper_common::invocation_id::TraceId;
+    use indexmap::indexmap;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Target;
+
+    impl CommandExecutionTarget for Target {
+        fn re_action_key(&self) -> String {
+            "root//foo:bar genrule".to_owned()
+        }
+
+        fn re_affinity_key(&self) -> String {
+            "root//foo:bar".to_owned()
+        }
+
+        fn as_proto_action_key(&self) -> buck2_data::ActionKey {
+            buck2_data::ActionKey::default()
+        }
+
+        fn as_proto_action_name(&self) -> buck2_data::ActionName {
+            buck2_data::ActionName {
+                category: "genrule".to_owned(),
+                identifier: "bar".to_owned(),
+            }
+        }
+    }
+
+    /// A successful local execution of an action with a single output of `output_bytes`.
+    async fn locally_executed(
+        output_bytes: usize,
+    ) -> (CommandExecutionResult, ActionDigestAndBlobs, DigestConfig) {
+        let digest_config = DigestConfig::testing_default();
+        let action_digest_and_blobs =
+            ActionDigestAndBlobsBuilder::new(digest_config).build(&RE::Action::default());
+        let output = CommandExecutionOutput::BuildArtifact {
+            path: BuckOutPath::new(
+                BaseDeferredKey::TargetLabel(ConfiguredTargetLabel::testing_parse(
+                    "root//foo:bar",
+                    ConfigurationData::testing_new(),
+                )),
+                ForwardRelativePathBuf::unchecked_new("out".to_owned()),
+            ),
+            output_type: OutputType::File,
+        };
+        let value = ArtifactValue::file(FileMetadata {
+            digest: TrackedFileDigest::from_content(
+                &vec![0; output_bytes],
+                digest_config.cas_digest_config(),
+            ),
+            is_executable: false,
+        });
+        let manager = CommandExecutionManager::new(
+            Box::new(MutexClaimManager::new()),
+            EventDispatcher::null(),
+            NoopLivelinessObserver::create(),
+        );
+        let res = manager.claim().await.success(
+            CommandExecutionKind::Local {
+                digest: action_digest_and_blobs.action.dupe(),
+                command: Vec::new(),
+                env: Default::default(),
+            },
+            indexmap! { output => value },
+            CommandStdStreams::Empty,
+            CommandExecutionMetadata {
+                wall_time: Duration::ZERO,
+                execution_time: Duration::ZERO,
+                start_time: SystemTime::now(),
+                execution_stats: None,
+                input_materialization_duration: Duration::ZERO,
+                hashing_duration: Duration::ZERO,
+            },
+        );
+        assert_eq!(res.calc_output_size_bytes(), output_bytes as u64);
+        (res, action_digest_and_blobs, digest_config)
+    }
+
+    /// Upload `res` with `uploader`, returning the result and the `CacheUploadEnd` events emitted.
+    async fn upload(
+        uploader: &dyn UploadCache,
+        res: &CommandExecutionResult,
+        action_digest_and_blobs: &ActionDigestAndBlobs,
+        digest_config: DigestConfig,
+    ) -> anyhow::Result<(CacheUploadResult, Vec<buck2_data::CacheUploadEnd>)> {
+        let (mut events, sink) = create_source_sink_pair();
+        let uploaded = with_dispatcher_async(
+            EventDispatcher::new(TraceId::new(), sink),
+            uploader.upload(
+                &CacheUploadInfo {
+                    target: &Target,
+                    digest_config,
+                },
+                res,
+                None,
+                action_digest_and_blobs,
+            ),
+        )
+        .await?;
+
+        let mut ends = Vec::new();
+        while let Some(event) = events.try_receive() {
+            if let Some(buck2_data::buck_event::Data::SpanEnd(end)) =
+                event.unpack_buck().map(|e| e.data())
+            {
+                if let Some(buck2_data::span_end_event::Data::CacheUpload(end)) = &end.data {
+                    ends.push(end.clone());
+                }
+            }
+        }
+        Ok((uploaded, ends))
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_projects_uploads() -> anyhow::Result<()> {
+        let uploader = DryRunCacheUploader::new(Some(100));
+
+        let (res, action_digest_and_blobs, digest_config) = locally_executed(60).await;
+        let (uploaded, ends) =
+            upload(&uploader, &res, &action_digest_and_blobs, digest_config).await?;
+        // Nothing was uploaded, but the bytes that would have been are recorded.
+        assert!(!uploaded.did_cache_upload);
+        assert!(!uploaded.did_dep_file_cache_upload);
+        assert_eq!(ends.len(), 1);
+        let end = &ends[0];
+        assert!(!end.success);
+        assert!(end.dry_run);
+        assert_eq!(end.output_bytes, Some(60));
+        assert_eq!(end.output_size_limit, None);
+        assert_eq!(
+            end.action_digest,
+            action_digest_and_blobs.action.to_string()
+        );
+        assert_eq!(end.name.as_ref().unwrap().category, "genrule");
+
+        // Too large for `CacheUploader`.
+        let (res, action_digest_and_blobs, digest_config) = locally_executed(200).await;
+        let (uploaded, ends) =
+            upload(&uploader, &res, &action_digest_and_blobs, digest_config).await?;
+        assert!(!uploaded.did_cache_upload);
+        assert_eq!(ends.len(), 1);
+        assert!(ends[0].dry_run);
+        assert_eq!(ends[0].output_bytes, Some(200));
+        assert_eq!(ends[0].output_size_limit, Some(100));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_outputs_over_the_limit_are_not_uploaded() -> anyhow::Result<()> {
+        let temp = ProjectRootTemp::new()?;
+        let artifact_fs = ArtifactFs::new(
+            CellResolver::testing_with_name_and_path(
+                CellName::testing_new("root"),
+                CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new("".into())),
+            ),
+            BuckOutPathResolver::new(ProjectRelativePathBuf::unchecked_new("buck-out/v2".into())),
+            temp.path().dupe(),
+        );
+        // Any use of this client fails, so an attempted upload would show up as an error.
+        let uploader = CacheUploader::new(
+            artifact_fs,
+            Arc::new(NoDiskMaterializer),
+            ManagedRemoteExecutionClient::testing_new_dummy(),
+            RemoteExecutorUseCase::buck2_default(),
+            RE::Platform::default(),
+            Some(100),
+        );
+
+        let (res, action_digest_and_blobs, digest_config) = locally_executed(200).await;
+        let (uploaded, ends) =
+            upload(&uploader, &res, &action_digest_and_blobs, digest_config).await?;
+        assert!(!uploaded.did_cache_upload);
+        assert!(!uploaded.did_dep_file_cache_upload);
+
+        assert_eq!(ends.len(), 1);
+        let end = &ends[0];
+        assert!(!end.success);
+        assert!(!end.dry_run);
+        assert_eq!(end.error, "Rejected: OutputExceedsLimit(100)");
+        assert_eq!(end.output_bytes, Some(200));
+        assert_eq!(end.output_size_limit, Some(100));
+        assert!(end.file_digests.is_empty());
+        assert!(end.tree_digests.is_empty());
+        Ok(())
+    }
+}
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use anyhow::Context;
@@ -38,6 +40,7 @@ use host_sharing::HostSharingRequirements;
 use more_futures::cancellation::CancellationContext;
 
 use crate::executors::local::LocalExecutor;
+use crate::executors::re::RE_RESOURCE_EXHAUSTED_STAGE;
 use crate::low_pass_filter::LowPassFilter;
 
 /// The [HybridExecutor] will accept requests and dispatch them to both a local and remote delegate
@@ -53,6 +56,75 @@ pub struct HybridExecutor<R> {
     pub executor_preference: ExecutorPreference,
     pub low_pass_filter: Arc<LowPassFilter>,
     pub re_max_input_files_bytes: u64,
+    /// When set, commands which require remote execution may still fall back to local execution,
+    /// but only once RE has errored repeatedly.
+    pub local_fallback: Option<LocalFallbackGraceWindow>,
+    /// When set, commands without a preference of their own run either locally or remotely
+    /// depending on the size of their inputs, instead of following `level`.
+    pub local_first: Option<LocalFirstThenRemote>,
+}
+
+/// Routes commands for the `LocalFirstThenRemote` execution strategy: those with small inputs run
+/// locally, where they're typically fastest when the cache is cold, and the others run remotely.
+/// This is decided before the command is scheduled, so the executors never race. Commands routed
+/// locally only run remotely if local execution errors.
+pub struct LocalFirstThenRemote {
+    max_local_input_files_bytes: u64,
+}
+
+impl LocalFirstThenRemote {
+    pub fn new(max_local_input_files_bytes: u64) -> Self {
+        Self {
+            max_local_input_files_bytes,
+        }
+    }
+
+    /// The preference for a command with this much input, which is only used for commands whose
+    /// own preference doesn't already require an executor.
+    fn route(&self, input_files_bytes: u64) -> ExecutorPreference {
+        if input_files_bytes <= self.max_local_input_files_bytes {
+            ExecutorPreference::LocalRequired
+        } else {
+            ExecutorPreference::RemoteRequired
+        }
+    }
+}
+
+/// Lets commands that require remote execution fall back to local execution once RE has failed
+/// `after_failures` commands in a row (e.g. during an RE outage). Only infra errors count as
+/// failures, and any command that RE runs to completion resets the count.
+pub struct LocalFallbackGraceWindow {
+    after_failures: u32,
+    /// Shared between all executors created for a command, since this tracks the health of RE
+    /// rather than of any particular action.
+    consecutive_failures: Arc<AtomicU32>,
+}
+
+impl LocalFallbackGraceWindow {
+    pub fn new(after_failures: u32, consecutive_failures: Arc<AtomicU32>) -> Self {
+        Self {
+            after_failures,
+            consecutive_failures,
+        }
+    }
+
+    /// Record the outcome of a remote execution, and return whether the command should now fall
+    /// back to local execution.
+    fn record(&self, status: &CommandExecutionStatus) -> bool {
+        match status {
+            CommandExecutionStatus::Error { .. } => {
+                let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                failures >= self.after_failures
+            }
+            CommandExecutionStatus::Cancelled => false,
+            CommandExecutionStatus::Success { .. }
+            | CommandExecutionStatus::Failure { .. }
+            | CommandExecutionStatus::TimedOut { .. } => {
+                self.consecutive_failures.store(0, Ordering::Relaxed);
+                false
+            }
+        }
+    }
 }
 
 impl<R> HybridExecutor<R>
@@ -123,6 +195,17 @@ where
             Err(e) => return manager.error("prepare_hybrid", e),
         };
 
+        let (executor_preference, routed_local_first) = match &self.local_first {
+            Some(local_first)
+                if !executor_preference.requires_local()
+                    && !executor_preference.requires_remote() =>
+            {
+                let routed = local_first.route(command.request.paths().input_files_bytes());
+                (routed, routed.requires_local())
+            }
+            _ => (executor_preference, false),
+        };
+
         // inspect that and construct our own result. Especially in the case of secondary fallback,
         // the current approach effectively loses the data from the primary result (for example, we
         // should have stage events the reflect the full duration not just the fallback part).
@@ -185,14 +268,45 @@ where
             fallback_on_failure,
         );
 
-        if executor_preference.requires_local()
-            || self.is_action_too_large_for_remote(command.request.paths())
-        {
+        if self.is_action_too_large_for_remote(command.request.paths()) {
             return local_result.await;
-        };
+        }
+
+        if executor_preference.requires_local() {
+            if !routed_local_first {
+                return local_result.await;
+            }
+            // Local-first actions only run locally because they are small, so they still run
+            // remotely if they could not run locally at all.
+            let res = local_result.await;
+            if matches!(res.report.status, CommandExecutionStatus::Error { .. }) {
+                return match fall_back(res, remote_result).await {
+                    Ok(res) => res,
+                    Err(e) => manager.error("hybrid", e),
+                };
+            }
+            return res;
+        }
 
         if executor_preference.requires_remote() {
-            return remote_result.await;
+            let local_fallback = match &self.local_fallback {
+                // Don't fall back for commands that themselves asked to only run remotely.
+                Some(local_fallback)
+                    if !command.request.executor_preference().requires_remote() =>
+                {
+                    local_fallback
+                }
+                _ => return remote_result.await,
+            };
+
+            let res = remote_result.await;
+            if local_fallback.record(&res.report.status) {
+                return match fall_back(res, local_result).await {
+                    Ok(res) => res,
+                    Err(e) => manager.error("hybrid", e),
+                };
+            }
+            return res;
         }
 
         let jobs = HybridExecutorJobs {
@@ -202,7 +316,16 @@ where
         };
 
         if is_limited {
-            return jobs.into_primary().await.0;
+            let (primary, secondary) = jobs.into_futures();
+            let (res, _) = primary.await;
+            // Limited hybrid doesn't fall back, except when RE has no capacity for the action.
+            if is_re_resource_exhausted(&res) {
+                return match fall_back(res, secondary.map(|(res, _)| res)).await {
+                    Ok(res) => res,
+                    Err(e) => manager.error("hybrid", e),
+                };
+            }
+            return res;
         }
 
         let weight = match command.request.host_sharing_requirements() {
@@ -335,6 +458,10 @@ where
         if executor_preference.requires_remote() {
             return false;
         }
+        // Local-first routes actions by the size of their inputs instead of following the level.
+        if self.local_first.is_some() {
+            return true;
+        }
         match self.level {
             HybridExecutionLevel::Limited => !executor_preference.prefers_remote(),
             HybridExecutionLevel::Fallback { .. } | HybridExecutionLevel::Full { .. } => true,
@@ -342,6 +469,30 @@ where
     }
 }
 
+/// Runs `fallback` in place of the `rejected` execution, which is recorded on the result. The
+/// claim that `rejected` may hold is released first, since the fallback can't complete without
+/// taking it.
+async fn fall_back(
+    mut rejected: CommandExecutionResult,
+    fallback: impl Future<Output = CommandExecutionResult>,
+) -> anyhow::Result<CommandExecutionResult> {
+    if let Some(claim) = rejected.report.claim.take() {
+        claim
+            .release()
+            .context("Local execution started executing without a Claim")?;
+    }
+    let mut res = fallback.await;
+    res.rejected_execution = Some(rejected.report);
+    Ok(res)
+}
+
+fn is_re_resource_exhausted(res: &CommandExecutionResult) -> bool {
+    matches!(
+        &res.report.status,
+        CommandExecutionStatus::Error { stage, .. } if *stage == RE_RESOURCE_EXHAUSTED_STAGE
+    )
+}
+
 struct ReClaimManager {
     inner: Option<ReClaimManagerInner>,
 }
@@ -519,3 +670,282 @@ where
 
 #[derive(PartialOrd, Ord, PartialEq, Eq)]
 struct JobPriority(u8);
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use buck2_common::liveliness_observer::NoopLivelinessObserver;
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::name::CellName;
+    use buck2_core::cells::CellResolver;
+    use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+    use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+    use buck2_core::fs::project::ProjectRootTemp;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+    use buck2_execute::digest_config::DigestConfig;
+    use buck2_execute::execute::action_digest_and_blobs::ActionDigestAndBlobsBuilder;
+    use buck2_execute::execute::blocking::testing::DummyBlockingExecutor;
+    use buck2_execute::execute::kind::CommandExecutionKind;
+    use buck2_execute::execute::prepared::PreparedAction;
+    use buck2_execute::execute::request::CommandExecutionRequest;
+    use buck2_execute::execute::target::CommandExecutionTarget;
+    use buck2_execute::knobs::ExecutorGlobalKnobs;
+    use buck2_execute::materialize::nodisk::NoDiskMaterializer;
+    use host_sharing::HostSharingBroker;
+    use host_sharing::HostSharingStrategy;
+    use indexmap::IndexSet;
+    use remote_execution as RE;
+
+    use super::*;
+
+    #[test]
+    fn test_local_fallback_grace_window() {
+        let window = LocalFallbackGraceWindow::new(2, Arc::new(AtomicU32::new(0)));
+        let error = || CommandExecutionStatus::Error {
+            stage: "remote_upload",
+            error: anyhow::anyhow!("RE is down"),
+        };
+        let timed_out = CommandExecutionStatus::TimedOut {
+            execution_kind: CommandExecutionKind::LocalWorkerInit {
+                command: Vec::new(),
+                env: Default::default(),
+            },
+            duration: Duration::from_secs(1),
+        };
+
+        assert!(!window.record(&error()));
+        // Cancellations don't tell us anything about RE.
+        assert!(!window.record(&CommandExecutionStatus::Cancelled));
+        assert!(window.record(&error()));
+        assert!(window.record(&error()));
+
+        // RE ran something, so it's back.
+        assert!(!window.record(&timed_out));
+        assert!(!window.record(&error()));
+        assert!(window.record(&error()));
+    }
+
+    #[test]
+    fn test_local_first_then_remote() {
+        let local_first = LocalFirstThenRemote::new(1024);
+        // A small action runs locally, a large one remotely.
+        assert_eq!(local_first.route(100).to_string(), "LocalRequired");
+        assert_eq!(local_first.route(1024).to_string(), "LocalRequired");
+        assert_eq!(local_first.route(1025).to_string(), "RemoteRequired");
+    }
+
+    #[derive(Debug)]
+    struct Target;
+
+    impl CommandExecutionTarget for Target {
+        fn re_action_key(&self) -> String {
+            "cell//pkg:foo genrule".to_owned()
+        }
+
+        fn re_affinity_key(&self) -> String {
+            "cell//pkg:foo".to_owned()
+        }
+
+        fn as_proto_action_key(&self) -> buck2_data::ActionKey {
+            buck2_data::ActionKey::default()
+        }
+
+        fn as_proto_action_name(&self) -> buck2_data::ActionName {
+            buck2_data::ActionName {
+                category: "genrule".to_owned(),
+                identifier: "foo".to_owned(),
+            }
+        }
+    }
+
+    /// Stands in for RE, counting the commands it is asked to run.
+    struct CountingRemote {
+        runs: AtomicU32,
+    }
+
+    #[async_trait]
+    impl PreparedCommandExecutor for CountingRemote {
+        async fn exec_cmd(
+            &self,
+            _command: &PreparedCommand<'_, '_>,
+            manager: CommandExecutionManager,
+            _cancellations: &CancellationContext,
+        ) -> CommandExecutionResult {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            manager
+                .claim()
+                .await
+                .error("remote", anyhow::anyhow!("ran remotely"))
+        }
+
+        fn is_local_execution_possible(&self, _executor_preference: ExecutorPreference) -> bool {
+            false
+        }
+    }
+
+    fn local_first_executor(
+        temp: &ProjectRootTemp,
+    ) -> (HybridExecutor<CountingRemote>, ArtifactFs) {
+        let project_fs = temp.path().dupe();
+        let artifact_fs = ArtifactFs::new(
+            CellResolver::testing_with_name_and_path(
+                CellName::testing_new("cell"),
+                CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new("cell_path".into())),
+            ),
+            BuckOutPathResolver::new(ProjectRelativePathBuf::unchecked_new("buck-out/v2".into())),
+            project_fs.dupe(),
+        );
+        let local = LocalExecutor::new(
+            artifact_fs.clone(),
+            Arc::new(NoDiskMaterializer),
+            Arc::new(DummyBlockingExecutor {
+                fs: project_fs.dupe(),
+            }),
+            Arc::new(HostSharingBroker::new(
+                HostSharingStrategy::SmallerTasksFirst,
+                1,
+            )),
+            project_fs.root().to_buf(),
+            None,
+            ExecutorGlobalKnobs::default(),
+            None,
+            None,
+        );
+        let executor = HybridExecutor {
+            local,
+            remote: CountingRemote {
+                runs: AtomicU32::new(0),
+            },
+            // Local-first routing takes over from the level, which would otherwise run everything
+            // remotely.
+            level: HybridExecutionLevel::Limited,
+            executor_preference: ExecutorPreference::RemotePreferred,
+            low_pass_filter: Arc::new(LowPassFilter::new(1)),
+            re_max_input_files_bytes: u64::MAX,
+            local_fallback: None,
+            local_first: Some(LocalFirstThenRemote::new(1024)),
+        };
+        (executor, artifact_fs)
+    }
+
+    async fn exec(
+        executor: &HybridExecutor<CountingRemote>,
+        artifact_fs: &ArtifactFs,
+        args: &[&str],
+    ) -> anyhow::Result<CommandExecutionResult> {
+        let digest_config = DigestConfig::testing_default();
+        let request = CommandExecutionRequest::new(
+            Vec::new(),
+            args.iter().map(|a| (*a).to_owned()).collect(),
+            CommandExecutionPaths::new(Vec::new(), IndexSet::new(), artifact_fs, digest_config)?,
+            Default::default(),
+        );
+        let prepared_action = PreparedAction {
+            action_and_blobs: ActionDigestAndBlobsBuilder::new(digest_config)
+                .build(&RE::Action::default()),
+            platform: RE::Platform::default(),
+        };
+        let command = PreparedCommand {
+            request: &request,
+            target: &Target,
+            prepared_action: &prepared_action,
+            digest_config,
+        };
+        let manager = CommandExecutionManager::new(
+            Box::new(MutexClaimManager::new()),
+            EventDispatcher::null(),
+            NoopLivelinessObserver::create(),
+        );
+        Ok(executor
+            .exec_cmd(&command, manager, CancellationContext::testing())
+            .await)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_local_first_runs_locally_then_falls_back_to_remote() -> anyhow::Result<()> {
+        let temp = ProjectRootTemp::new()?;
+        let (executor, artifact_fs) = local_first_executor(&temp);
+
+        // Despite the preference for remote execution, local-first makes it possible to run
+        // actions locally.
+        assert!(executor.is_local_execution_possible(ExecutorPreference::Default));
+        assert!(!executor.is_local_execution_possible(ExecutorPreference::RemoteRequired));
+
+        // A small action runs locally, and RE is not involved.
+        let res = exec(&executor, &artifact_fs, &["sh", "-c", "true"]).await?;
+        assert!(
+            matches!(
+                &res.report.status,
+                CommandExecutionStatus::Success {
+                    execution_kind: CommandExecutionKind::Local { .. }
+                }
+            ),
+            "status: {:?}",
+            res.report.status
+        );
+        assert_eq!(executor.remote.runs.load(Ordering::SeqCst), 0);
+
+        // An action that can't run locally at all (here, for lack of arguments) runs remotely.
+        let res = exec(&executor, &artifact_fs, &[]).await?;
+        assert!(matches!(
+            &res.report.status,
+            CommandExecutionStatus::Error {
+                stage: "remote",
+                ..
+            }
+        ));
+        assert!(matches!(
+            res.rejected_execution.map(|r| r.status),
+            Some(CommandExecutionStatus::Error {
+                stage: "no_args",
+                ..
+            })
+        ));
+        assert_eq!(executor.remote.runs.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fall_back_releases_the_rejected_claim() -> anyhow::Result<()> {
+        let claim_manager = MutexClaimManager::new();
+        let manager = || {
+            CommandExecutionManager::new(
+                Box::new(claim_manager.dupe()),
+                EventDispatcher::null(),
+                NoopLivelinessObserver::create(),
+            )
+        };
+
+        // RE took the claim before finding out it had no capacity for the action.
+        let rejected = manager().claim().await.error(
+            RE_RESOURCE_EXHAUSTED_STAGE,
+            anyhow::anyhow!("RESOURCE_EXHAUSTED"),
+        );
+        assert!(is_re_resource_exhausted(&rejected));
+
+        let fallback = async {
+            manager()
+                .claim()
+                .await
+                .error("local", anyhow::anyhow!("ran locally"))
+        };
+        let res = tokio::time::timeout(Duration::from_secs(10), fall_back(rejected, fallback))
+            .await
+            .context("Fallback is waiting for the rejected claim")??;
+
+        assert!(matches!(
+            &res.report.status,
+            CommandExecutionStatus::Error { stage: "local", .. }
+        ));
+        assert!(matches!(
+            res.rejected_execution.map(|r| r.status),
+            Some(CommandExecutionStatus::Error {
+                stage: RE_RESOURCE_EXHAUSTED_STAGE,
+                ..
+            })
+        ));
+        Ok(())
+    }
+}
@@ -10,6 +10,7 @@
 use std::borrow::Cow;
 use std::ffi::OsStr;
 use std::ffi::OsString;
+use std::future::Future;
 use std::ops::ControlFlow;
 use std::process::Command;
 use std::sync::Arc;
@@ -81,9 +82,11 @@ use indexmap::IndexMap;
 use more_futures::cancellable_future::CancellationObserver;
 use more_futures::cancellation::CancellationContext;
 use tracing::info;
+use tracing::warn;
 
 use crate::executors::worker::WorkerHandle;
 use crate::executors::worker::WorkerPool;
+use crate::re::prefetch::OutputPrefetcher;
 
 #[derive(Debug, buck2_error::Error)]
 enum LocalExecutionError {
@@ -107,6 +110,8 @@ pub struct LocalExecutor {
     knobs: ExecutorGlobalKnobs,
     #[allow(unused)]
     worker_pool: Option<Arc<WorkerPool>>,
+    /// Told about the inputs of every action, so it knows which remote outputs to prefetch.
+    prefetcher: Option<Arc<OutputPrefetcher>>,
 }
 
 impl LocalExecutor {
@@ -119,6 +124,7 @@ impl LocalExecutor {
         forkserver: Option<ForkserverClient>,
         knobs: ExecutorGlobalKnobs,
         worker_pool: Option<Arc<WorkerPool>>,
+        prefetcher: Option<Arc<OutputPrefetcher>>,
     ) -> Self {
         Self {
             artifact_fs,
@@ -129,6 +135,7 @@ impl LocalExecutor {
             forkserver,
             knobs,
             worker_pool,
+            prefetcher,
         }
     }
 
@@ -206,6 +213,62 @@ impl LocalExecutor {
         }
     }
 
+    /// Like `exec`, but runs the command again if it exits with one of the configured
+    /// `local_retry_exit_codes`, up to `local_retry_attempts` times. `prepare_retry` runs before
+    /// each retry, to clean up the outputs the failed attempt left behind.
+    async fn exec_with_retries<E, K, V, R>(
+        &self,
+        args: &[String],
+        env: impl Fn() -> E + Send + Sync,
+        working_directory: Option<&ProjectRelativePath>,
+        timeout: Option<Duration>,
+        env_inheritance: Option<&EnvironmentInheritance>,
+        liveliness_observer: Arc<dyn LivelinessObserver>,
+        disable_miniperf: bool,
+        prepare_retry: impl Fn() -> R + Send + Sync,
+    ) -> anyhow::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
+    where
+        E: IntoIterator<Item = (K, V)> + Send,
+        K: AsRef<OsStr> + Send,
+        V: AsRef<OsStr> + Send,
+        R: Future<Output = anyhow::Result<()>> + Send,
+    {
+        let mut attempt = 0;
+        loop {
+            let res = self
+                .exec(
+                    &args[0],
+                    &args[1..],
+                    env(),
+                    working_directory,
+                    timeout,
+                    env_inheritance,
+                    liveliness_observer.dupe(),
+                    disable_miniperf,
+                )
+                .await?;
+            match res.0 {
+                GatherOutputStatus::Finished { exit_code, .. }
+                    if attempt < self.knobs.local_retry_attempts
+                        && self.knobs.local_retry_exit_codes.contains(&exit_code) =>
+                {
+                    attempt += 1;
+                    warn!(
+                        "Local command exited with retryable exit code {}, retrying (attempt {} of {}): {}",
+                        exit_code,
+                        attempt,
+                        self.knobs.local_retry_attempts,
+                        args.join(" "),
+                    );
+                    prepare_retry()
+                        .await
+                        .context("Error preparing outputs to retry local command")?;
+                }
+                _ => return Ok(res),
+            }
+        }
+    }
+
     async fn exec_request(
         &self,
         action_digest: &ActionDigest,
@@ -251,10 +314,13 @@ impl LocalExecutor {
                 )
                 .await;
 
-                let scratch_path = r1?.scratch;
+                let inputs = r1?;
                 r2?;
+                if let Some(prefetcher) = &self.prefetcher {
+                    prefetcher.record_local_inputs(&inputs.paths);
+                }
 
-                anyhow::Ok((scratch_path, start.elapsed()))
+                anyhow::Ok((inputs.scratch, start.elapsed()))
             },
         )
         .await
@@ -367,7 +433,8 @@ impl LocalExecutor {
                     StrOrOsStr::from(build_id),
                 )))
         };
-        let liveliness_observer = manager.liveliness_observer.dupe().and(cancellation);
+        let liveliness_observer: Arc<dyn LivelinessObserver> =
+            Arc::new(manager.liveliness_observer.dupe().and(cancellation));
 
         let (worker, manager) = self.initialize_worker(request, manager, dispatcher).await?;
 
@@ -399,6 +466,7 @@ impl LocalExecutor {
                             action_digest: action_digest.to_string(),
                             argv: args.to_vec(),
                             env,
+                            cache_hit: false,
                         }),
                     }
                     .into(),
@@ -418,23 +486,30 @@ impl LocalExecutor {
                 let execution_start = Instant::now();
                 let start_time = SystemTime::now();
 
-                let env = iter_env().map(|(k, v)| (k, v.into_os_str()));
+                let env = || iter_env().map(|(k, v)| (k, v.into_os_str()));
                 let r = if let Some(worker) = worker {
-                    let env: Vec<(OsString, OsString)> = env
-                        .into_iter()
+                    let env: Vec<(OsString, OsString)> = env()
                         .map(|(k, v)| (OsString::from(k), v.to_owned()))
                         .collect();
                     Ok(worker.exec_cmd(request.args(), env).await)
                 } else {
-                    self.exec(
-                        &args[0],
-                        &args[1..],
+                    self.exec_with_retries(
+                        args,
                         env,
                         request.working_directory(),
                         request.timeout(),
                         request.local_environment_inheritance(),
                         liveliness_observer,
                         request.disable_miniperf(),
+                        || {
+                            create_output_dirs(
+                                &self.artifact_fs,
+                                request,
+                                self.materializer.dupe(),
+                                self.blocking_executor.dupe(),
+                                cancellations,
+                            )
+                        },
                     )
                     .await
                 };
@@ -467,9 +542,13 @@ impl LocalExecutor {
                 exit_code,
                 execution_stats,
             } => {
-                let (outputs, hashing_time) = match self
-                    .calculate_and_declare_output_values(request, digest_config)
-                    .await
+                let (outputs, hashing_time) = match calculate_and_declare_output_values(
+                    &self.artifact_fs,
+                    self.materializer.as_ref(),
+                    request,
+                    digest_config,
+                )
+                .await
                 {
                     Ok((output_values, hashing_time)) => (output_values, hashing_time),
                     Err(e) => return manager.error("calculate_output_values_failed", e),
@@ -529,58 +608,6 @@ impl LocalExecutor {
         }
     }
 
-    async fn calculate_and_declare_output_values(
-        &self,
-        request: &CommandExecutionRequest,
-        digest_config: DigestConfig,
-    ) -> anyhow::Result<(IndexMap<CommandExecutionOutput, ArtifactValue>, Duration)> {
-        let mut builder = inputs_directory(request.inputs(), &self.artifact_fs)?;
-
-        // Read outputs from disk and add them to the builder
-        let mut entries = Vec::new();
-        let mut total_hashing_time = Duration::ZERO;
-        for output in request.outputs() {
-            let path = output.resolve(&self.artifact_fs).into_path();
-            let abspath = self.root.join(&path);
-            let (entry, hashing_time) = build_entry_from_disk(
-                abspath,
-                FileDigestConfig::build(digest_config.cas_digest_config()),
-            )
-            .with_context(|| format!("collecting output {:?}", path))?;
-            total_hashing_time += hashing_time;
-            if let Some(entry) = entry {
-                insert_entry(&mut builder, &path, entry)?;
-                entries.push((output.cloned(), path));
-            }
-        }
-
-        let mut to_declare = vec![];
-        let mut mapped_outputs = IndexMap::with_capacity(entries.len());
-
-        for (output, path) in entries {
-            let value = extract_artifact_value(&builder, &path, digest_config)?;
-            if let Some(value) = value {
-                match output {
-                    CommandExecutionOutput::BuildArtifact { .. } => {
-                        to_declare.push((path, value.dupe()));
-                    }
-                    CommandExecutionOutput::TestPath { .. } => {
-                        // Don't declare those as we don't currently have any form of GC so this
-                        // would take up space for nothing, and most importantly, we will never
-                        // need them to be in materializer state for e.g. matching as nothing
-                        // should depend on them.
-                    }
-                }
-
-                mapped_outputs.insert(output, value);
-            }
-        }
-
-        self.materializer.declare_existing(to_declare).await?;
-
-        Ok((mapped_outputs, total_hashing_time))
-    }
-
     async fn acquire_worker_permit(
         &self,
         request: &CommandExecutionRequest,
@@ -761,6 +788,61 @@ impl PreparedCommandExecutor for LocalExecutor {
     }
 }
 
+/// Hash the outputs of a command which just ran locally, and declare them to the materializer as
+/// existing on disk.
+pub(crate) async fn calculate_and_declare_output_values(
+    artifact_fs: &ArtifactFs,
+    materializer: &dyn Materializer,
+    request: &CommandExecutionRequest,
+    digest_config: DigestConfig,
+) -> anyhow::Result<(IndexMap<CommandExecutionOutput, ArtifactValue>, Duration)> {
+    let mut builder = inputs_directory(request.inputs(), artifact_fs)?;
+
+    // Read outputs from disk and add them to the builder
+    let mut entries = Vec::new();
+    let mut total_hashing_time = Duration::ZERO;
+    for output in request.outputs() {
+        let path = output.resolve(artifact_fs).into_path();
+        let abspath = artifact_fs.fs().resolve(&path);
+        let (entry, hashing_time) = build_entry_from_disk(
+            abspath,
+            FileDigestConfig::build(digest_config.cas_digest_config()),
+        )
+        .with_context(|| format!("collecting output {:?}", path))?;
+        total_hashing_time += hashing_time;
+        if let Some(entry) = entry {
+            insert_entry(&mut builder, &path, entry)?;
+            entries.push((output.cloned(), path));
+        }
+    }
+
+    let mut to_declare = vec![];
+    let mut mapped_outputs = IndexMap::with_capacity(entries.len());
+
+    for (output, path) in entries {
+        let value = extract_artifact_value(&builder, &path, digest_config)?;
+        if let Some(value) = value {
+            match output {
+                CommandExecutionOutput::BuildArtifact { .. } => {
+                    to_declare.push((path, value.dupe()));
+                }
+                CommandExecutionOutput::TestPath { .. } => {
+                    // Don't declare those as we don't currently have any form of GC so this
+                    // would take up space for nothing, and most importantly, we will never
+                    // need them to be in materializer state for e.g. matching as nothing
+                    // should depend on them.
+                }
+            }
+
+            mapped_outputs.insert(output, value);
+        }
+    }
+
+    materializer.declare_existing(to_declare).await?;
+
+    Ok((mapped_outputs, total_hashing_time))
+}
+
 /// Either a str or a OsStr, so that we can turn it back into a String without having to check for
 /// valid utf-8, while using the same struct.
 #[derive(Copy, Clone, Dupe, From)]
@@ -1146,6 +1228,7 @@ mod tests {
     use buck2_core::cells::CellResolver;
     use buck2_core::fs::artifact_path_resolver::ArtifactFs;
     use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+    use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
     use buck2_core::fs::project::ProjectRoot;
     use buck2_core::fs::project::ProjectRootTemp;
     use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
@@ -1248,6 +1331,12 @@ mod tests {
     }
 
     fn test_executor() -> anyhow::Result<(LocalExecutor, AbsNormPathBuf, ProjectRootTemp)> {
+        test_executor_with_knobs(ExecutorGlobalKnobs::default())
+    }
+
+    fn test_executor_with_knobs(
+        knobs: ExecutorGlobalKnobs,
+    ) -> anyhow::Result<(LocalExecutor, AbsNormPathBuf, ProjectRootTemp)> {
         let temp = ProjectRootTemp::new().unwrap();
         let project_fs = temp.path();
         let artifact_fs = artifact_fs(project_fs.dupe());
@@ -1264,7 +1353,8 @@ mod tests {
             )),
             temp.path().root().to_buf(),
             None,
-            ExecutorGlobalKnobs::default(),
+            knobs,
+            None,
             None,
         );
 
@@ -1305,6 +1395,51 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_retries_on_exit_code() -> anyhow::Result<()> {
+        // Each attempt appends to `out`, like a partial output.
+        let args = [
+            "sh".to_owned(),
+            "-c".to_owned(),
+            "echo attempt >> out; if [ -e marker ]; then exit 0; fi; touch marker; exit 75"
+                .to_owned(),
+        ];
+        let args = &args;
+        let exec = |executor: LocalExecutor, root: AbsNormPathBuf| async move {
+            let out = root.join(ForwardRelativePath::new("out")?);
+            let (status, _, _) = executor
+                .exec_with_retries(
+                    &args,
+                    HashMap::<String, String>::default,
+                    None,
+                    None,
+                    None,
+                    NoopLivelinessObserver::create(),
+                    false,
+                    || async { fs_util::remove_all(&out) },
+                )
+                .await?;
+            // Outputs of a failed attempt are cleaned up before retrying.
+            assert_eq!(fs_util::read_to_string(&out)?, "attempt\n");
+            match status {
+                GatherOutputStatus::Finished { exit_code, .. } => anyhow::Ok(exit_code),
+                status => Err(anyhow::anyhow!("Unexpected status: {:?}", status)),
+            }
+        };
+
+        let (executor, root, _tmpdir) = test_executor()?;
+        assert_eq!(exec(executor, root).await?, 75);
+
+        let (executor, root, _tmpdir) = test_executor_with_knobs(ExecutorGlobalKnobs {
+            local_retry_exit_codes: Arc::new([75]),
+            ..Default::default()
+        })?;
+        assert_eq!(exec(executor, root).await?, 0);
+
+        Ok(())
+    }
+
     #[cfg(unix)] // TODO: something similar on Windows: T123279320
     #[tokio::test]
     async fn test_exec_cmd_environment_filtering() -> anyhow::Result<()> {
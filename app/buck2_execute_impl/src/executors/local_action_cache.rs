@@ -0,0 +1,988 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! An on-disk action cache for builds which only execute locally, enabled by
+//! `build.local_action_cache`. Successful local actions have their outputs stored in a CAS
+//! directory under `buck-out`, keyed by action digest, so that running the same action again
+//! (e.g. when switching back to a previous revision) restores the outputs instead of executing
+//! the command. The cache lives in `buck-out`, so `buck2 clean` deletes it.
+//!
+//! The cache is kept under `build.local_action_cache_max_bytes` by evicting the least recently
+//! used actions.
+//!
+//! The cache is only used for build actions: test runs always execute.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use buck2_common::file_ops::FileDigest;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_execute::digest_config::DigestConfig;
+use buck2_execute::execute::action_digest::ActionDigest;
+use buck2_execute::execute::blocking::BlockingExecutor;
+use buck2_execute::execute::kind::CommandExecutionKind;
+use buck2_execute::execute::manager::CommandExecutionManager;
+use buck2_execute::execute::manager::CommandExecutionManagerExt;
+use buck2_execute::execute::output::CommandStdStreams;
+use buck2_execute::execute::prepared::PreparedCommand;
+use buck2_execute::execute::prepared::PreparedCommandExecutor;
+use buck2_execute::execute::prepared::PreparedCommandOptionalExecutor;
+use buck2_execute::execute::request::CommandExecutionOutputRef;
+use buck2_execute::execute::request::CommandExecutionRequest;
+use buck2_execute::execute::request::ExecutorPreference;
+use buck2_execute::execute::result::CommandExecutionMetadata;
+use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::materialize::materializer::Materializer;
+use dupe::Dupe;
+use more_futures::cancellation::CancellationContext;
+
+use crate::executors::local::calculate_and_declare_output_values;
+use crate::executors::local::create_output_dirs;
+
+/// Bump whenever the on-disk format changes in an incompatible way.
+const LOCAL_ACTION_CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum CachedEntry {
+    File {
+        digest: String,
+        executable: bool,
+    },
+    Symlink {
+        target: String,
+    },
+    Dir {
+        entries: BTreeMap<String, CachedEntry>,
+    },
+}
+
+impl CachedEntry {
+    fn blobs<'a>(&'a self, blobs: &mut Vec<&'a str>) {
+        match self {
+            CachedEntry::File { digest, .. } => blobs.push(digest),
+            CachedEntry::Symlink { .. } => {}
+            CachedEntry::Dir { entries } => {
+                for entry in entries.values() {
+                    entry.blobs(blobs);
+                }
+            }
+        }
+    }
+}
+
+/// The result of a successful action, with file contents stored separately in the CAS.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct CachedAction {
+    version: u32,
+    /// Outputs which the action did not produce are absent.
+    outputs: BTreeMap<String, CachedEntry>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+/// Keeps the local action cache under a maximum size. One is shared by all the caches created
+/// for a command, so that the cache is trimmed on the first store of each command and then every
+/// time a tenth of the maximum size has been stored, rather than after every action.
+#[derive(Clone, Dupe)]
+pub struct LocalActionCacheLimit {
+    max_bytes: u64,
+    trimmed: Arc<AtomicBool>,
+    stored_since_trim: Arc<AtomicU64>,
+}
+
+impl LocalActionCacheLimit {
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            trimmed: Arc::new(AtomicBool::new(false)),
+            stored_since_trim: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Record that `bytes` were stored, and return whether the cache should be trimmed now.
+    fn should_trim_after_storing(&self, bytes: u64) -> bool {
+        let stored = self.stored_since_trim.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        if !self.trimmed.swap(true, Ordering::Relaxed) || stored >= self.max_bytes / 10 {
+            self.stored_since_trim.store(0, Ordering::Relaxed);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The storage of the local action cache: `ac/` has an entry for each cached action, and `cas/`
+/// has the contents of every file in these entries.
+#[derive(Clone, Dupe)]
+struct LocalActionCacheStore {
+    fs: ProjectRoot,
+    dir: Arc<ProjectRelativePathBuf>,
+}
+
+impl LocalActionCacheStore {
+    fn actions_dir(&self) -> AbsNormPathBuf {
+        self.fs
+            .resolve(&self.dir.join(ForwardRelativePath::unchecked_new("ac")))
+    }
+
+    fn blobs_dir(&self) -> AbsNormPathBuf {
+        self.fs
+            .resolve(&self.dir.join(ForwardRelativePath::unchecked_new("cas")))
+    }
+
+    fn action_path(&self, action_digest: &ActionDigest) -> AbsNormPathBuf {
+        self.actions_dir().join(ForwardRelativePath::unchecked_new(
+            &action_digest.raw_digest().to_string(),
+        ))
+    }
+
+    fn blob_path(&self, digest: &str) -> AbsNormPathBuf {
+        self.blobs_dir()
+            .join(ForwardRelativePath::unchecked_new(digest))
+    }
+
+    /// Write `contents` to `path` via a unique temporary file in the same directory, so that
+    /// concurrent readers never see a partial file, and concurrent writers don't clobber each
+    /// other's temporary file.
+    fn write_atomic(path: &AbsNormPathBuf, contents: &[u8]) -> anyhow::Result<()> {
+        let dir = path
+            .parent()
+            .with_context(|| format!("Path has no parent: `{}`", path))?;
+        fs_util::create_dir_all(dir)?;
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)
+            .with_context(|| format!("Error creating a temporary file in `{}`", dir))?;
+        tmp.write_all(contents)?;
+        tmp.persist(path)
+            .with_context(|| format!("Error writing `{}`", path))?;
+        Ok(())
+    }
+
+    /// Store the output at `path`, returning its entry and how many bytes were added to the CAS.
+    fn store_entry(
+        &self,
+        path: &AbsNormPathBuf,
+        digest_config: DigestConfig,
+        stored_bytes: &mut u64,
+    ) -> anyhow::Result<Option<CachedEntry>> {
+        let metadata = match fs_util::symlink_metadata_if_exists(path)? {
+            Some(metadata) => metadata,
+            None => return Ok(None),
+        };
+        let entry = if metadata.is_symlink() {
+            let target = fs_util::read_link(path)?;
+            CachedEntry::Symlink {
+                target: target
+                    .to_str()
+                    .with_context(|| format!("Symlink target is not UTF-8: `{}`", path))?
+                    .to_owned(),
+            }
+        } else if metadata.is_dir() {
+            let mut entries = BTreeMap::new();
+            for child in fs_util::read_dir(path)? {
+                let child = child?;
+                let name = child.file_name();
+                let name = name
+                    .to_str()
+                    .with_context(|| format!("File name is not UTF-8 in `{}`", path))?
+                    .to_owned();
+                if let Some(entry) = self.store_entry(
+                    &path.join(ForwardRelativePath::new(&name)?),
+                    digest_config,
+                    stored_bytes,
+                )? {
+                    entries.insert(name, entry);
+                }
+            }
+            CachedEntry::Dir { entries }
+        } else {
+            let contents = fs_util::read(path)?;
+            let digest = FileDigest::from_content(&contents, digest_config.cas_digest_config())
+                .raw_digest()
+                .to_string();
+            let blob = self.blob_path(&digest);
+            if !fs_util::try_exists(&blob)? {
+                Self::write_atomic(&blob, &contents)?;
+                *stored_bytes += contents.len() as u64;
+            }
+            CachedEntry::File {
+                digest,
+                executable: is_executable(&metadata),
+            }
+        };
+        Ok(Some(entry))
+    }
+
+    /// Record the outputs of a successful action, which must currently be on disk. Returns how
+    /// many bytes this added to the cache.
+    fn store(
+        &self,
+        action_digest: &ActionDigest,
+        outputs: &[ProjectRelativePathBuf],
+        stdout: &[u8],
+        stderr: &[u8],
+        digest_config: DigestConfig,
+    ) -> anyhow::Result<u64> {
+        let mut stored_bytes = 0;
+        let mut cached_outputs = BTreeMap::new();
+        for output in outputs {
+            if let Some(entry) =
+                self.store_entry(&self.fs.resolve(output), digest_config, &mut stored_bytes)?
+            {
+                cached_outputs.insert(output.to_string(), entry);
+            }
+        }
+        let action = CachedAction {
+            version: LOCAL_ACTION_CACHE_VERSION,
+            outputs: cached_outputs,
+            stdout: stdout.to_vec(),
+            stderr: stderr.to_vec(),
+        };
+        let action = serde_json::to_vec(&action)?;
+        Self::write_atomic(&self.action_path(action_digest), &action)?;
+        Ok(stored_bytes + action.len() as u64)
+    }
+
+    /// Evict the least recently used actions until the cache is no larger than `max_bytes`. The
+    /// files of an evicted action are deleted once no remaining action refers to them.
+    fn trim(&self, max_bytes: u64) -> anyhow::Result<()> {
+        struct Action {
+            path: AbsNormPathBuf,
+            size: u64,
+            last_used: SystemTime,
+            blobs: Vec<String>,
+        }
+
+        let mut total = 0;
+        let mut blobs = HashMap::new();
+        for blob in fs_util::read_dir_if_exists(self.blobs_dir())?
+            .into_iter()
+            .flatten()
+        {
+            let blob = blob?;
+            let size = blob.metadata()?.len();
+            total += size;
+            blobs.insert(blob.file_name().to_string_lossy().into_owned(), (size, 0));
+        }
+
+        let mut actions = Vec::new();
+        for action in fs_util::read_dir_if_exists(self.actions_dir())?
+            .into_iter()
+            .flatten()
+        {
+            let action = action?;
+            let metadata = action.metadata()?;
+            let path = action.path();
+            // An entry that can't be read is a miss, so it goes first.
+            let (last_used, blob_digests) = match fs_util::read(&path)
+                .ok()
+                .and_then(|data| serde_json::from_slice::<CachedAction>(&data).ok())
+            {
+                Some(cached) => {
+                    let mut blob_digests = Vec::new();
+                    for entry in cached.outputs.values() {
+                        entry.blobs(&mut blob_digests);
+                    }
+                    let blob_digests: Vec<String> =
+                        blob_digests.into_iter().map(str::to_owned).collect();
+                    (metadata.modified()?, blob_digests)
+                }
+                None => (SystemTime::UNIX_EPOCH, Vec::new()),
+            };
+            for digest in &blob_digests {
+                if let Some((_, refs)) = blobs.get_mut(digest) {
+                    *refs += 1;
+                }
+            }
+            total += metadata.len();
+            actions.push(Action {
+                path,
+                size: metadata.len(),
+                last_used,
+                blobs: blob_digests,
+            });
+        }
+
+        if total <= max_bytes {
+            return Ok(());
+        }
+
+        // Files that no action refers to are garbage, e.g. from an action that was evicted
+        // while being stored.
+        for (digest, (size, refs)) in &blobs {
+            if *refs == 0 {
+                fs_util::remove_file(self.blob_path(digest))?;
+                total -= size;
+            }
+        }
+
+        actions.sort_by_key(|action| action.last_used);
+        for action in actions {
+            if total <= max_bytes {
+                break;
+            }
+            fs_util::remove_file(&action.path)?;
+            total -= action.size;
+            for digest in &action.blobs {
+                if let Some((size, refs)) = blobs.get_mut(digest) {
+                    *refs -= 1;
+                    if *refs == 0 {
+                        fs_util::remove_file(self.blob_path(digest))?;
+                        total -= *size;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn has_blobs(&self, entry: &CachedEntry) -> anyhow::Result<bool> {
+        match entry {
+            CachedEntry::File { digest, .. } => fs_util::try_exists(self.blob_path(digest)),
+            CachedEntry::Symlink { .. } => Ok(true),
+            CachedEntry::Dir { entries } => {
+                for entry in entries.values() {
+                    if !self.has_blobs(entry)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// The cached result of an action, if there is one and all its files are still in the CAS.
+    /// An unreadable entry is a miss. A hit marks the action as recently used.
+    fn lookup(&self, action_digest: &ActionDigest) -> anyhow::Result<Option<CachedAction>> {
+        let path = self.action_path(action_digest);
+        let (action, data) = match fs_util::read_if_exists(&path)? {
+            Some(data) => match serde_json::from_slice::<CachedAction>(&data) {
+                Ok(action) if action.version == LOCAL_ACTION_CACHE_VERSION => (action, data),
+                _ => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+        for entry in action.outputs.values() {
+            if !self.has_blobs(entry)? {
+                return Ok(None);
+            }
+        }
+        // Rewriting the entry updates its modification time, which is what eviction goes by.
+        if let Err(e) = Self::write_atomic(&path, &data) {
+            tracing::debug!(
+                "Error marking `{}` as used in the local action cache: {:#}",
+                action_digest,
+                e
+            );
+        }
+        Ok(Some(action))
+    }
+
+    fn restore_entry(&self, path: &AbsNormPathBuf, entry: &CachedEntry) -> anyhow::Result<()> {
+        match entry {
+            CachedEntry::File { digest, executable } => {
+                fs_util::copy(self.blob_path(digest), path)?;
+                if *executable {
+                    fs_util::set_executable(path)?;
+                }
+            }
+            CachedEntry::Symlink { target } => fs_util::symlink(target, path)?,
+            CachedEntry::Dir { entries } => {
+                fs_util::create_dir_all(path)?;
+                for (name, entry) in entries {
+                    self.restore_entry(&path.join(ForwardRelativePath::new(name)?), entry)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the outputs of a cached action into place. The output paths must have been cleaned
+    /// up beforehand.
+    fn restore(&self, action: &CachedAction) -> anyhow::Result<()> {
+        for (output, entry) in &action.outputs {
+            let path = self.fs.resolve(ProjectRelativePath::new(output)?);
+            self.restore_entry(&path, entry)
+                .with_context(|| format!("Error restoring cached output `{}`", output))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Whether results of this request can be stored in and served from the local action cache.
+/// Actions which keep their outputs from a previous run (for incremental state) depend on more
+/// than their action digest, and test runs must actually run.
+fn is_cacheable(request: &CommandExecutionRequest) -> bool {
+    request.outputs_cleanup
+        && !request.executor_preference().requires_remote()
+        && request
+            .outputs()
+            .all(|output| matches!(output, CommandExecutionOutputRef::BuildArtifact { .. }))
+}
+
+/// A cache checker which serves actions from the local action cache. Put it in front of a
+/// `LocalActionCacheWriter` in a `StackedExecutor`, so that misses get executed and stored.
+#[derive(Clone)]
+pub struct LocalActionCache {
+    artifact_fs: ArtifactFs,
+    materializer: Arc<dyn Materializer>,
+    blocking_executor: Arc<dyn BlockingExecutor>,
+    store: LocalActionCacheStore,
+    limit: LocalActionCacheLimit,
+}
+
+impl LocalActionCache {
+    pub fn new(
+        artifact_fs: ArtifactFs,
+        materializer: Arc<dyn Materializer>,
+        blocking_executor: Arc<dyn BlockingExecutor>,
+        limit: LocalActionCacheLimit,
+    ) -> Self {
+        let store = LocalActionCacheStore {
+            fs: artifact_fs.fs().dupe(),
+            dir: Arc::new(
+                artifact_fs
+                    .buck_out_path_resolver()
+                    .root()
+                    .join(ForwardRelativePath::unchecked_new("cache/actions")),
+            ),
+        };
+        Self {
+            artifact_fs,
+            materializer,
+            blocking_executor,
+            store,
+            limit,
+        }
+    }
+
+    async fn restore(
+        &self,
+        command: &PreparedCommand<'_, '_>,
+        action: CachedAction,
+        manager: CommandExecutionManager,
+        cancellations: &CancellationContext<'_>,
+    ) -> CommandExecutionResult {
+        let request = command.request;
+        let action_digest = &command.prepared_action.action_and_blobs.action;
+        let start = Instant::now();
+        let start_time = SystemTime::now();
+
+        let manager = manager.claim().await;
+
+        // This invalidates the outputs in the materializer and deletes them, like local
+        // execution would.
+        if let Err(e) = create_output_dirs(
+            &self.artifact_fs,
+            request,
+            self.materializer.dupe(),
+            self.blocking_executor.dupe(),
+            cancellations,
+        )
+        .await
+        {
+            return manager.error("local_action_cache_prepare_output_dirs", e);
+        }
+
+        let store = self.store.dupe();
+        let action = match self
+            .blocking_executor
+            .execute_io_inline(move || {
+                store.restore(&action)?;
+                Ok(action)
+            })
+            .await
+        {
+            Ok(action) => action,
+            Err(e) => return manager.error("local_action_cache_restore", e),
+        };
+
+        let (outputs, hashing_duration) = match calculate_and_declare_output_values(
+            &self.artifact_fs,
+            self.materializer.as_ref(),
+            request,
+            command.digest_config,
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(e) => return manager.error("calculate_output_values_failed", e),
+        };
+
+        tracing::info!(
+            "Action result is in the local action cache, skipping execution of:\n```\n$ {}\n```\n for action `{}`",
+            request.all_args_str(),
+            action_digest,
+        );
+
+        manager.success(
+            CommandExecutionKind::LocalActionCache {
+                digest: action_digest.dupe(),
+                command: request.all_args_vec(),
+                env: request.env().clone(),
+            },
+            outputs,
+            CommandStdStreams::Local {
+                stdout: action.stdout,
+                stderr: action.stderr,
+            },
+            CommandExecutionMetadata {
+                wall_time: start.elapsed(),
+                execution_time: Duration::ZERO,
+                start_time,
+                execution_stats: None,
+                input_materialization_duration: Duration::ZERO,
+                hashing_duration,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl PreparedCommandOptionalExecutor for LocalActionCache {
+    async fn maybe_execute(
+        &self,
+        command: &PreparedCommand<'_, '_>,
+        manager: CommandExecutionManager,
+        cancellations: &CancellationContext,
+    ) -> ControlFlow<CommandExecutionResult, CommandExecutionManager> {
+        if !is_cacheable(command.request) {
+            return ControlFlow::Continue(manager);
+        }
+
+        let action_digest = &command.prepared_action.action_and_blobs.action;
+        let store = self.store.dupe();
+        let lookup = self
+            .blocking_executor
+            .execute_io_inline(|| store.lookup(action_digest))
+            .await;
+        let action = match lookup {
+            Ok(action) => action,
+            Err(e) => {
+                // The cache is only an optimisation, so just execute the action.
+                tracing::warn!(
+                    "Error reading local action cache entry for `{}`: {:#}",
+                    action_digest,
+                    e
+                );
+                None
+            }
+        };
+
+        manager
+            .events
+            .instant_event(buck2_data::ActionCacheOutcome {
+                key: Some(command.target.as_proto_action_key()),
+                name: Some(command.target.as_proto_action_name()),
+                action_digest: action_digest.to_string(),
+                cache_type: buck2_data::CacheType::ActionCache.into(),
+                hit: action.is_some(),
+            });
+
+        match action {
+            Some(action) => {
+                ControlFlow::Break(self.restore(command, action, manager, cancellations).await)
+            }
+            None => ControlFlow::Continue(manager),
+        }
+    }
+}
+
+/// Runs actions on the inner executor, and stores the results of those which succeeded locally
+/// in the local action cache.
+pub struct LocalActionCacheWriter<E> {
+    pub cache: LocalActionCache,
+    pub inner: E,
+}
+
+#[async_trait]
+impl<E> PreparedCommandExecutor for LocalActionCacheWriter<E>
+where
+    E: PreparedCommandExecutor,
+{
+    async fn exec_cmd(
+        &self,
+        command: &PreparedCommand<'_, '_>,
+        manager: CommandExecutionManager,
+        cancellations: &CancellationContext,
+    ) -> CommandExecutionResult {
+        let result = self.inner.exec_cmd(command, manager, cancellations).await;
+
+        // Results from workers aren't stored: workers keep state between actions.
+        if result.was_locally_executed() && is_cacheable(command.request) {
+            if let CommandStdStreams::Local { stdout, stderr } = &result.report.std_streams {
+                let action_digest = &command.prepared_action.action_and_blobs.action;
+                let outputs: Vec<_> = command
+                    .request
+                    .outputs()
+                    .map(|output| output.resolve(&self.cache.artifact_fs).into_path())
+                    .collect();
+                let digest_config = command.digest_config;
+                let store = self.cache.store.dupe();
+                let limit = self.cache.limit.dupe();
+                let res = self
+                    .cache
+                    .blocking_executor
+                    .execute_io_inline(|| {
+                        let stored_bytes =
+                            store.store(action_digest, &outputs, stdout, stderr, digest_config)?;
+                        if limit.should_trim_after_storing(stored_bytes) {
+                            store.trim(limit.max_bytes)?;
+                        }
+                        Ok(())
+                    })
+                    .await;
+                if let Err(e) = res {
+                    tracing::warn!(
+                        "Error storing `{}` in the local action cache: {:#}",
+                        action_digest,
+                        e
+                    );
+                }
+            }
+        }
+
+        result
+    }
+
+    fn is_local_execution_possible(&self, executor_preference: ExecutorPreference) -> bool {
+        self.inner.is_local_execution_possible(executor_preference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::cas_digest::CasDigestConfig;
+    use buck2_common::liveliness_observer::NoopLivelinessObserver;
+    use buck2_core::base_deferred_key::BaseDeferredKey;
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::name::CellName;
+    use buck2_core::cells::CellResolver;
+    use buck2_core::configuration::data::ConfigurationData;
+    use buck2_core::fs::buck_out_path::BuckOutPath;
+    use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+    use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+    use buck2_core::fs::project::ProjectRootTemp;
+    use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+    use buck2_events::dispatch::EventDispatcher;
+    use buck2_execute::execute::action_digest_and_blobs::ActionDigestAndBlobsBuilder;
+    use buck2_execute::execute::blocking::testing::DummyBlockingExecutor;
+    use buck2_execute::execute::claim::MutexClaimManager;
+    use buck2_execute::execute::clean_output_paths::CleanOutputPaths;
+    use buck2_execute::execute::prepared::PreparedAction;
+    use buck2_execute::execute::request::CommandExecutionOutput;
+    use buck2_execute::execute::request::CommandExecutionPaths;
+    use buck2_execute::execute::request::OutputType;
+    use buck2_execute::execute::result::CommandExecutionStatus;
+    use buck2_execute::execute::target::CommandExecutionTarget;
+    use buck2_execute::knobs::ExecutorGlobalKnobs;
+    use buck2_execute::materialize::nodisk::NoDiskMaterializer;
+    use host_sharing::HostSharingBroker;
+    use host_sharing::HostSharingStrategy;
+    use indexmap::indexset;
+    use remote_execution as RE;
+
+    use super::*;
+    use crate::executors::local::LocalExecutor;
+    use crate::executors::stacked::StackedExecutor;
+
+    fn digest(action: &str) -> ActionDigest {
+        ActionDigest::from_content(action.as_bytes(), CasDigestConfig::testing_default())
+    }
+
+    #[test]
+    fn test_local_action_cache_hit_across_builds() -> anyhow::Result<()> {
+        let temp = ProjectRootTemp::new()?;
+        let fs = temp.path().dupe();
+        let store = LocalActionCacheStore {
+            fs: fs.dupe(),
+            dir: Arc::new(ProjectRelativePathBuf::unchecked_new(
+                "buck-out/v2/cache/actions".to_owned(),
+            )),
+        };
+        let digest_config = DigestConfig::testing_default();
+        let file = ProjectRelativePathBuf::unchecked_new("buck-out/v2/gen/out.txt".to_owned());
+        let dir = ProjectRelativePathBuf::unchecked_new("buck-out/v2/gen/dir".to_owned());
+        let missing = ProjectRelativePathBuf::unchecked_new("buck-out/v2/gen/missing".to_owned());
+        let outputs = vec![file.clone(), dir.clone(), missing.clone()];
+
+        // The first build executes the action and stores its outputs.
+        fs.write_file(&file, "hello", false)?;
+        fs.write_file(
+            &dir.join(ForwardRelativePath::new("bin")?),
+            "#!/bin/sh",
+            true,
+        )?;
+        fs.write_file(
+            &dir.join(ForwardRelativePath::new("sub/data")?),
+            "hello",
+            false,
+        )?;
+        store.store(&digest("a"), &outputs, b"out", b"err", digest_config)?;
+
+        // The second build starts from clean outputs, and is served from the cache.
+        CleanOutputPaths::clean(outputs.iter().map(|p| p.as_ref()), &fs)?;
+        assert!(store.lookup(&digest("b"))?.is_none());
+        let action = store.lookup(&digest("a"))?.unwrap();
+        assert_eq!(action.stdout, b"out");
+        assert_eq!(action.stderr, b"err");
+        assert!(!action.outputs.contains_key(missing.as_str()));
+        store.restore(&action)?;
+
+        assert_eq!(fs_util::read_to_string(fs.resolve(&file))?, "hello");
+        let bin = fs.resolve(&dir.join(ForwardRelativePath::new("bin")?));
+        assert_eq!(fs_util::read_to_string(&bin)?, "#!/bin/sh");
+        assert_eq!(is_executable(&fs_util::metadata(&bin)?), cfg!(unix));
+        assert_eq!(
+            fs_util::read_to_string(fs.resolve(&dir.join(ForwardRelativePath::new("sub/data")?)))?,
+            "hello"
+        );
+        assert!(!fs_util::try_exists(fs.resolve(&missing))?);
+
+        // Files with the same contents share a blob, and losing it makes the entry a miss.
+        let blob = match &action.outputs[file.as_str()] {
+            CachedEntry::File { digest, .. } => digest,
+            entry => panic!("Expected a file, got {:?}", entry),
+        };
+        fs_util::remove_file(store.blob_path(blob))?;
+        assert!(store.lookup(&digest("a"))?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_action_cache_trim_evicts_least_recently_used() -> anyhow::Result<()> {
+        let temp = ProjectRootTemp::new()?;
+        let fs = temp.path().dupe();
+        let store = LocalActionCacheStore {
+            fs: fs.dupe(),
+            dir: Arc::new(ProjectRelativePathBuf::unchecked_new(
+                "buck-out/v2/cache/actions".to_owned(),
+            )),
+        };
+        let digest_config = DigestConfig::testing_default();
+        let output = ProjectRelativePathBuf::unchecked_new("buck-out/v2/gen/out.txt".to_owned());
+        let store_action = |action: &str, contents: &str| -> anyhow::Result<u64> {
+            fs.write_file(&output, contents, false)?;
+            let stored =
+                store.store(&digest(action), &[output.clone()], b"", b"", digest_config)?;
+            // Modification times are only so precise.
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(stored)
+        };
+
+        // `c` has the same output as `b`, so they share a blob.
+        let size = store_action("a", &"a".repeat(100))?
+            + store_action("b", &"b".repeat(100))?
+            + store_action("c", &"b".repeat(100))?;
+        // Reads the blob of an action without using it.
+        let blob = |action: &str| -> anyhow::Result<String> {
+            let cached: CachedAction =
+                serde_json::from_slice(&fs_util::read(store.action_path(&digest(action)))?)?;
+            match &cached.outputs[output.as_str()] {
+                CachedEntry::File { digest, .. } => Ok(digest.clone()),
+                entry => Err(anyhow::anyhow!("Expected a file, got {:?}", entry)),
+            }
+        };
+        let a_blob = blob("a")?;
+        let c_blob = blob("c")?;
+        // Using `a` makes `b` the least recently used, even though `a` was stored first.
+        assert!(store.lookup(&digest("a"))?.is_some());
+
+        // Under the limit, nothing is evicted.
+        store.trim(size)?;
+        assert!(fs_util::try_exists(store.action_path(&digest("b")))?);
+
+        store.trim(size - 1)?;
+        assert!(!fs_util::try_exists(store.action_path(&digest("b")))?);
+        assert!(fs_util::try_exists(store.action_path(&digest("a")))?);
+        // The blob that `c` still refers to is kept.
+        assert!(fs_util::try_exists(store.blob_path(&c_blob))?);
+        assert!(store.lookup(&digest("c"))?.is_some());
+
+        store.trim(0)?;
+        assert!(store.lookup(&digest("a"))?.is_none());
+        assert!(store.lookup(&digest("c"))?.is_none());
+        assert!(!fs_util::try_exists(store.blob_path(&a_blob))?);
+        assert!(!fs_util::try_exists(store.blob_path(&c_blob))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_action_cache_limit_trims_once_per_tenth() {
+        let limit = LocalActionCacheLimit::new(1000);
+        // The first store of a command trims what earlier commands left behind.
+        assert!(limit.should_trim_after_storing(1));
+        assert!(!limit.should_trim_after_storing(50));
+        assert!(limit.should_trim_after_storing(50));
+        assert!(!limit.should_trim_after_storing(99));
+    }
+
+    #[derive(Debug)]
+    struct Target;
+
+    impl CommandExecutionTarget for Target {
+        fn re_action_key(&self) -> String {
+            "cell//pkg:foo genrule".to_owned()
+        }
+
+        fn re_affinity_key(&self) -> String {
+            "cell//pkg:foo".to_owned()
+        }
+
+        fn as_proto_action_key(&self) -> buck2_data::ActionKey {
+            buck2_data::ActionKey::default()
+        }
+
+        fn as_proto_action_name(&self) -> buck2_data::ActionName {
+            buck2_data::ActionName {
+                category: "genrule".to_owned(),
+                identifier: "foo".to_owned(),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_local_action_cache_hit_across_local_builds() -> anyhow::Result<()> {
+        let temp = ProjectRootTemp::new()?;
+        let fs = temp.path().dupe();
+        let artifact_fs = ArtifactFs::new(
+            CellResolver::testing_with_name_and_path(
+                CellName::testing_new("cell"),
+                CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new("cell_path".into())),
+            ),
+            BuckOutPathResolver::new(ProjectRelativePathBuf::unchecked_new("buck-out/v2".into())),
+            fs.dupe(),
+        );
+        let blocking_executor: Arc<dyn BlockingExecutor> =
+            Arc::new(DummyBlockingExecutor { fs: fs.dupe() });
+        let cache = LocalActionCache::new(
+            artifact_fs.clone(),
+            Arc::new(NoDiskMaterializer),
+            blocking_executor.dupe(),
+            LocalActionCacheLimit::new(1024 * 1024),
+        );
+        let executor = StackedExecutor {
+            optional: cache.clone(),
+            fallback: LocalActionCacheWriter {
+                cache,
+                inner: LocalExecutor::new(
+                    artifact_fs.clone(),
+                    Arc::new(NoDiskMaterializer),
+                    blocking_executor,
+                    Arc::new(HostSharingBroker::new(
+                        HostSharingStrategy::SmallerTasksFirst,
+                        1,
+                    )),
+                    fs.root().to_buf(),
+                    None,
+                    ExecutorGlobalKnobs::default(),
+                    None,
+                    None,
+                ),
+            },
+        };
+
+        let output = BuckOutPath::new(
+            BaseDeferredKey::TargetLabel(ConfiguredTargetLabel::testing_parse(
+                "cell//pkg:foo",
+                ConfigurationData::testing_new(),
+            )),
+            ForwardRelativePathBuf::unchecked_new("out.txt".to_owned()),
+        );
+        let output_path = fs.resolve(&artifact_fs.resolve_build(&output));
+        let digest_config = DigestConfig::testing_default();
+        let request = CommandExecutionRequest::new(
+            Vec::new(),
+            vec![
+                "sh".to_owned(),
+                "-c".to_owned(),
+                format!("echo ran >> runs; printf hello > {}", output_path),
+            ],
+            CommandExecutionPaths::new(
+                Vec::new(),
+                indexset![CommandExecutionOutput::BuildArtifact {
+                    path: output,
+                    output_type: OutputType::File,
+                }],
+                &artifact_fs,
+                digest_config,
+            )?,
+            Default::default(),
+        );
+        let prepared_action = PreparedAction {
+            action_and_blobs: ActionDigestAndBlobsBuilder::new(digest_config)
+                .build(&RE::Action::default()),
+            platform: RE::Platform::default(),
+        };
+        let command = PreparedCommand {
+            request: &request,
+            target: &Target,
+            prepared_action: &prepared_action,
+            digest_config,
+        };
+        let build = || async {
+            let manager = CommandExecutionManager::new(
+                Box::new(MutexClaimManager::new()),
+                EventDispatcher::null(),
+                NoopLivelinessObserver::create(),
+            );
+            let res = executor
+                .exec_cmd(&command, manager, CancellationContext::testing())
+                .await;
+            match res.report.status {
+                CommandExecutionStatus::Success { execution_kind } => Ok(execution_kind),
+                status => Err(anyhow::anyhow!("Unexpected status: {:?}", status)),
+            }
+        };
+        let runs = || fs_util::read_to_string(fs.root().join(ForwardRelativePath::new("runs")?));
+
+        assert!(matches!(build().await?, CommandExecutionKind::Local { .. }));
+        assert_eq!(runs()?, "ran\n");
+
+        // The next build starts without the output, and is served from the cache rather than
+        // running the command again.
+        fs_util::remove_file(&output_path)?;
+        assert!(matches!(
+            build().await?,
+            CommandExecutionKind::LocalActionCache { .. }
+        ));
+        assert_eq!(runs()?, "ran\n");
+        assert_eq!(fs_util::read_to_string(&output_path)?, "hello");
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@ pub mod action_cache;
 pub mod caching;
 pub mod hybrid;
 pub mod local;
+pub mod local_action_cache;
 pub mod re;
 pub mod stacked;
 pub mod worker;
@@ -44,12 +44,15 @@ use indexmap::IndexMap;
 use more_futures::cancellation::CancellationContext;
 use remote_execution as RE;
 use remote_execution::ExecuteResponse;
+use remote_execution::REClientError;
 use remote_execution::TCode;
 use tracing::info;
 
 use crate::re::download::download_action_results;
 use crate::re::download::DownloadResult;
 use crate::re::paranoid_download::ParanoidDownloader;
+use crate::re::prefetch::OutputPrefetcher;
+use crate::re::retry::ReRetryPolicy;
 
 #[derive(Debug, buck2_error::Error)]
 pub enum RemoteExecutorError {
@@ -68,11 +71,27 @@ pub struct ReExecutor {
     pub skip_cache_read: bool,
     pub skip_cache_write: bool,
     pub re_max_queue_time_ms: Option<u64>,
+    /// Retries for transient failures to schedule actions on RE.
+    pub re_retry_policy: ReRetryPolicy,
     pub paranoid: Option<ParanoidDownloader>,
     pub materialize_failed_inputs: bool,
+    /// If set, outputs of successful actions are materialized in the background.
+    pub prefetcher: Option<Arc<OutputPrefetcher>>,
 }
 
 impl ReExecutor {
+    /// Identifies `command` in the requests sent to RE, including the `re_action_key`.
+    pub fn re_action_identity<'a>(
+        &self,
+        command: &PreparedCommand<'a, 'a>,
+    ) -> ReActionIdentity<'a> {
+        ReActionIdentity::new(
+            command.target,
+            self.re_action_key.as_deref(),
+            command.request.paths(),
+        )
+    }
+
     async fn upload(
         &self,
         manager: CommandExecutionManager,
@@ -119,7 +138,7 @@ impl ReExecutor {
 
     async fn re_execute(
         &self,
-        mut manager: CommandExecutionManager,
+        manager: CommandExecutionManager,
         identity: &ReActionIdentity<'_>,
         request: &CommandExecutionRequest,
         action_digest: &ActionDigest,
@@ -132,19 +151,25 @@ impl ReExecutor {
             action_digest,
         );
 
-        let execute_response = self
-            .re_client
-            .execute(
-                action_digest.dupe(),
-                platform,
-                self.re_use_case,
-                &identity,
-                &mut manager,
-                self.skip_cache_read,
-                self.skip_cache_write,
-                self.re_max_queue_time_ms.map(Duration::from_millis),
-                &self.knobs,
-            )
+        let (manager, execute_response) = self
+            .re_retry_policy
+            .retry(manager, move |mut manager| async move {
+                let res = self
+                    .re_client
+                    .execute(
+                        action_digest.dupe(),
+                        platform,
+                        self.re_use_case,
+                        identity,
+                        &mut manager,
+                        self.skip_cache_read,
+                        self.skip_cache_write,
+                        self.re_max_queue_time_ms.map(Duration::from_millis),
+                        &self.knobs,
+                    )
+                    .await;
+                (manager, res)
+            })
             .await;
 
         let response = match execute_response {
@@ -152,7 +177,13 @@ impl ReExecutor {
             Ok(ExecuteResponseOrCancelled::Cancelled) => {
                 return ControlFlow::Break(manager.cancel());
             }
-            Err(e) => return ControlFlow::Break(manager.error("remote_call_error", e)),
+            Err(e) => {
+                let stage = match e.downcast_ref::<REClientError>() {
+                    Some(e) if e.code == TCode::RESOURCE_EXHAUSTED => RE_RESOURCE_EXHAUSTED_STAGE,
+                    _ => "remote_call_error",
+                };
+                return ControlFlow::Break(manager.error(stage, e));
+            }
         };
 
         let remote_details = RemoteCommandExecutionDetails {
@@ -193,8 +224,13 @@ impl ReExecutor {
                     response.timing(),
                 )
             } else {
+                let stage = if response.error.code == TCode::RESOURCE_EXHAUSTED {
+                    RE_RESOURCE_EXHAUSTED_STAGE
+                } else {
+                    "remote_exec_error"
+                };
                 manager.error(
-                    "remote_exec_error",
+                    stage,
                     ReErrorWrapper {
                         action_digest: action_digest.dupe(),
                         inner: response.error,
@@ -239,7 +275,7 @@ impl PreparedCommandExecutor for ReExecutor {
     ) -> CommandExecutionResult {
         let PreparedCommand {
             request,
-            target,
+            target: _,
             prepared_action:
                 PreparedAction {
                     action_and_blobs,
@@ -254,8 +290,7 @@ impl PreparedCommandExecutor for ReExecutor {
             )?;
         }
 
-        let identity =
-            ReActionIdentity::new(*target, self.re_action_key.as_deref(), request.paths());
+        let identity = self.re_action_identity(command);
 
         // TODO(bobyf, torozco): remote execution probably needs to explicitly handle cancellations
         let manager = self
@@ -310,6 +345,16 @@ impl PreparedCommandExecutor for ReExecutor {
 
         let DownloadResult::Result(res) = res;
 
+        if let Some(prefetcher) = &self.prefetcher {
+            prefetcher.prefetch(
+                &res,
+                request
+                    .outputs()
+                    .map(|output| output.resolve(&self.artifact_fs).into_path())
+                    .collect(),
+            );
+        }
+
         res
     }
 
@@ -318,6 +363,11 @@ impl PreparedCommandExecutor for ReExecutor {
     }
 }
 
+/// The error stage used when RE rejects an action because it has no capacity for it, as opposed to
+/// the action failing. Hybrid execution falls back to local on this even when it otherwise would
+/// not fall back.
+pub const RE_RESOURCE_EXHAUSTED_STAGE: &str = "remote_resource_exhausted";
+
 #[derive(buck2_error::Error, Debug)]
 #[error(
     "action_digest={}, re_code={}, re_location={}, re_message={}",
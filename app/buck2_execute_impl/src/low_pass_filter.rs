@@ -7,6 +7,9 @@
  * of this source tree.
  */
 
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
 use async_condvar_fair::Condvar;
 use futures::future::Future;
 use futures::future::FutureExt;
@@ -27,6 +30,20 @@ pub struct LowPassFilter {
     /// were issued aren't relinquished, but as they are released, no replacement permits will be
     /// issued until the accessors count goes below the capacity.
     capacity: usize,
+
+    /// The total weight of the permits currently issued, for reporting.
+    occupancy: AtomicUsize,
+
+    /// The total weight of the requests currently waiting for a permit, for reporting.
+    waiting: AtomicUsize,
+}
+
+/// A snapshot of how much of a `LowPassFilter` is in use, with all values expressed as weights.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LowPassFilterStats {
+    pub capacity: usize,
+    pub occupancy: usize,
+    pub waiting: usize,
 }
 
 struct LowPassFilterState {
@@ -47,6 +64,8 @@ impl LowPassFilter {
             state: Mutex::new(LowPassFilterState { accessors: 0 }),
             cv: Condvar::new(),
             capacity,
+            occupancy: AtomicUsize::new(0),
+            waiting: AtomicUsize::new(0),
         }
     }
 
@@ -54,6 +73,14 @@ impl LowPassFilter {
         self.capacity
     }
 
+    pub fn stats(&self) -> LowPassFilterStats {
+        LowPassFilterStats {
+            capacity: self.capacity,
+            occupancy: self.occupancy.load(Ordering::Relaxed),
+            waiting: self.waiting.load(Ordering::Relaxed),
+        }
+    }
+
     /// Request a permit to enter the critical section.
     ///
     /// To make things predictable, we synchronously increment the accessors count. This ensures
@@ -71,19 +98,25 @@ impl LowPassFilter {
         // This needs to be created *here* and not at the return point so that if this future gets
         // dropped while we await (or possible en before we await), the guard will re-acquire the
         // mutex and update the accessors count.
-        let guard = LowPassFilterGuard {
+        let mut guard = LowPassFilterGuard {
             filter: self,
             weight,
+            issued: false,
         };
 
         if go {
+            guard.issue();
             return futures::future::ready(guard).left_future();
         }
 
+        self.waiting.fetch_add(weight, Ordering::Relaxed);
+
         async move {
             let mut state = self.state.lock();
             loop {
                 if state.can_dispatch_more(self.capacity) {
+                    self.waiting.fetch_sub(weight, Ordering::Relaxed);
+                    guard.issue();
                     return guard;
                 }
                 state = self.cv.wait(state).await;
@@ -96,6 +129,17 @@ impl LowPassFilter {
 pub struct LowPassFilterGuard<'a> {
     filter: &'a LowPassFilter,
     weight: usize,
+    /// Whether this guard holds a permit, as opposed to an outstanding request for one.
+    issued: bool,
+}
+
+impl LowPassFilterGuard<'_> {
+    fn issue(&mut self) {
+        self.issued = true;
+        self.filter
+            .occupancy
+            .fetch_add(self.weight, Ordering::Relaxed);
+    }
 }
 
 impl Drop for LowPassFilterGuard<'_> {
@@ -104,6 +148,15 @@ impl Drop for LowPassFilterGuard<'_> {
     /// LowPassFilterGuard had entered the critical section or not, since there is no difference
     /// between dropping a permit and dropping an outstading request for access.
     fn drop(&mut self) {
+        if self.issued {
+            self.filter
+                .occupancy
+                .fetch_sub(self.weight, Ordering::Relaxed);
+        } else {
+            self.filter
+                .waiting
+                .fetch_sub(self.weight, Ordering::Relaxed);
+        }
         let mut state = self.filter.state.lock();
         state.accessors -= self.weight;
         if state.can_dispatch_more(self.filter.capacity) {
@@ -193,4 +246,37 @@ mod tests {
         drop(t1);
         assert!(futures::poll!(t2.as_mut()).is_ready());
     }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let filter = LowPassFilter::new(2);
+        let stats = |occupancy, waiting| LowPassFilterStats {
+            capacity: 2,
+            occupancy,
+            waiting,
+        };
+        assert_eq!(filter.stats(), stats(0, 0));
+
+        let t0 = filter.access(2).await;
+        assert_eq!(filter.stats(), stats(2, 0));
+
+        let mut t1 = filter.access(1).boxed();
+        let mut t2 = filter.access(2).boxed();
+        assert!(futures::poll!(t1.as_mut()).is_pending());
+        assert_eq!(filter.stats(), stats(2, 3));
+
+        // Cancelling a request stops it waiting.
+        drop(t2);
+        assert_eq!(filter.stats(), stats(2, 1));
+
+        drop(t0);
+        let t1 = match futures::poll!(t1.as_mut()) {
+            std::task::Poll::Ready(t1) => t1,
+            std::task::Poll::Pending => panic!("Expected a permit"),
+        };
+        assert_eq!(filter.stats(), stats(1, 0));
+
+        drop(t1);
+        assert_eq!(filter.stats(), stats(0, 0));
+    }
 }
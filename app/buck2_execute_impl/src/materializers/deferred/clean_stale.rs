@@ -333,10 +333,18 @@ impl<'a> StaleFinder<'a> {
                     self.paths_to_remove.push(path);
                 }
                 ArtifactTree::Data(box ArtifactMaterializationData {
-                    stage: ArtifactMaterializationStage::Materialized { metadata, .. },
+                    stage:
+                        ArtifactMaterializationStage::Materialized {
+                            metadata,
+                            active,
+                            last_access_time,
+                        },
                     ..
                 }) => {
                     tracing::trace!(path = %path, file_type = ?file_type, "marking as retained");
+                    if *active && *last_access_time < self.keep_since_time {
+                        self.stats.active_stale_artifact_count += 1;
+                    }
                     self.stats.retained_artifact_count += 1;
                     self.stats.retained_bytes += metadata.size();
                 }
@@ -372,9 +380,73 @@ fn find_stale_tracked_only(
                 paths_to_invalidate.push(path);
             } else {
                 tracing::trace!(path = %path, "retaining artifact");
+                if *active && *last_access_time < keep_since_time {
+                    stats.active_stale_artifact_count += 1;
+                }
                 stats.retained_artifact_count += 1;
             }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_execute::artifact_value::ArtifactValue;
+    use buck2_execute::digest_config::DigestConfig;
+
+    use super::*;
+    use crate::materializers::deferred::ArtifactMetadata;
+    use crate::materializers::deferred::Processing;
+    use crate::materializers::deferred::Version;
+
+    fn insert(tree: &mut ArtifactTree, path: &str, last_access_time: DateTime<Utc>, active: bool) {
+        let value = ArtifactValue::file(FileMetadata::empty(
+            DigestConfig::testing_default().cas_digest_config(),
+        ));
+        tree.insert(
+            ProjectRelativePath::unchecked_new(path)
+                .iter()
+                .map(|f| f.to_owned()),
+            Box::new(ArtifactMaterializationData {
+                deps: None,
+                stage: ArtifactMaterializationStage::Materialized {
+                    metadata: ArtifactMetadata::new(value.entry()),
+                    last_access_time,
+                    active,
+                },
+                processing: Processing::Done(Version(0)),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_find_stale_tracked_only_keeps_active_artifacts() -> anyhow::Result<()> {
+        let keep_since_time = Utc::now();
+        let old = keep_since_time - chrono::Duration::weeks(2);
+        let recent = keep_since_time + chrono::Duration::hours(1);
+
+        let mut tree = ArtifactTree::new();
+        insert(&mut tree, "buck-out/gen/stale", old, false);
+        insert(&mut tree, "buck-out/gen/stale_active", old, true);
+        insert(&mut tree, "buck-out/gen/recent", recent, false);
+        insert(&mut tree, "buck-out/gen/recent_active", recent, true);
+
+        let mut stats = buck2_data::CleanStaleStats::default();
+        let mut paths_to_invalidate = Vec::new();
+        find_stale_tracked_only(&tree, keep_since_time, &mut stats, &mut paths_to_invalidate)?;
+
+        assert_eq!(
+            paths_to_invalidate,
+            vec![ProjectRelativePathBuf::unchecked_new(
+                "buck-out/gen/stale".to_owned()
+            )]
+        );
+        assert_eq!(stats.stale_artifact_count, 1);
+        assert_eq!(stats.retained_artifact_count, 3);
+        // Only the artifact which is both stale and in use is reported.
+        assert_eq!(stats.active_stale_artifact_count, 1);
+        Ok(())
+    }
+}
@@ -9,3 +9,5 @@
 
 pub mod download;
 pub mod paranoid_download;
+pub mod prefetch;
+pub mod retry;
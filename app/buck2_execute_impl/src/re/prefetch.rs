@@ -0,0 +1,171 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_events::dispatch::get_dispatcher_opt;
+use buck2_events::dispatch::with_dispatcher_async;
+use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::execute::result::CommandExecutionStatus;
+use buck2_execute::materialize::materializer::Materializer;
+use dupe::Dupe;
+use parking_lot::Mutex;
+use tokio::sync::Semaphore;
+
+/// The outputs which local actions have used as inputs, across all the builds of a daemon. An
+/// output which was needed locally before is likely to be needed by its dependents again, so
+/// these are the outputs worth prefetching.
+#[derive(Default)]
+pub struct LocallyConsumedOutputs {
+    paths: Mutex<HashSet<ProjectRelativePathBuf>>,
+}
+
+impl LocallyConsumedOutputs {
+    /// Past this many paths, new ones are not recorded, so that the memory used stays bounded.
+    const MAX_PATHS: usize = 1_000_000;
+
+    pub fn record(&self, paths: &[ProjectRelativePathBuf]) {
+        let mut recorded = self.paths.lock();
+        for path in paths {
+            if recorded.len() >= Self::MAX_PATHS {
+                return;
+            }
+            if !recorded.contains(path) {
+                recorded.insert(path.clone());
+            }
+        }
+    }
+
+    fn contains(&self, path: &ProjectRelativePathBuf) -> bool {
+        self.paths.lock().contains(path)
+    }
+}
+
+/// Materializes the outputs of remote actions in the background once they complete, so that they
+/// are already on disk when a local action or the user needs them instead of being downloaded on
+/// first use.
+///
+/// Only outputs which a dependent action has consumed locally in an earlier build are
+/// prefetched, since outputs only consumed by other remote actions never need to be on disk.
+///
+/// To bound the bandwidth used, only actions whose outputs are at most `max_bytes` in total are
+/// prefetched, and prefetches are skipped rather than queued while `max_concurrent` are in flight.
+pub struct OutputPrefetcher {
+    materializer: Arc<dyn Materializer>,
+    consumed: Arc<LocallyConsumedOutputs>,
+    max_bytes: u64,
+    in_flight: Arc<Semaphore>,
+}
+
+impl OutputPrefetcher {
+    pub fn new(
+        materializer: Arc<dyn Materializer>,
+        consumed: Arc<LocallyConsumedOutputs>,
+        max_bytes: u64,
+        max_concurrent: usize,
+    ) -> Self {
+        Self {
+            materializer,
+            consumed,
+            max_bytes,
+            in_flight: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Remember the inputs of a local action, so that the actions producing them prefetch their
+    /// outputs in later builds.
+    pub fn record_local_inputs(&self, paths: &[ProjectRelativePathBuf]) {
+        self.consumed.record(paths);
+    }
+
+    /// The outputs of a remote action that a dependent consumed locally before.
+    fn outputs_to_prefetch(
+        &self,
+        paths: Vec<ProjectRelativePathBuf>,
+    ) -> Vec<ProjectRelativePathBuf> {
+        paths
+            .into_iter()
+            .filter(|path| self.consumed.contains(path))
+            .collect()
+    }
+
+    pub fn prefetch(&self, result: &CommandExecutionResult, paths: Vec<ProjectRelativePathBuf>) {
+        if !matches!(result.report.status, CommandExecutionStatus::Success { .. })
+            || result.calc_output_size_bytes() > self.max_bytes
+        {
+            return;
+        }
+
+        let paths = self.outputs_to_prefetch(paths);
+        if paths.is_empty() {
+            return;
+        }
+
+        let Ok(permit) = self.in_flight.dupe().try_acquire_owned() else {
+            return;
+        };
+
+        let materializer = self.materializer.dupe();
+        let prefetch = async move {
+            let _permit = permit;
+            if let Err(e) = materializer.ensure_materialized(paths).await {
+                tracing::debug!("Failed to prefetch remote action outputs: {:#}", e);
+            }
+        };
+        match get_dispatcher_opt() {
+            Some(dispatcher) => tokio::spawn(with_dispatcher_async(dispatcher, prefetch)),
+            None => tokio::spawn(prefetch),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+    use buck2_execute::materialize::nodisk::NoDiskMaterializer;
+    use dupe::Dupe;
+
+    use super::LocallyConsumedOutputs;
+    use super::OutputPrefetcher;
+
+    fn path(p: &str) -> ProjectRelativePathBuf {
+        ProjectRelativePathBuf::unchecked_new(p.to_owned())
+    }
+
+    #[test]
+    fn test_only_prefetches_outputs_consumed_locally() {
+        let consumed = Arc::new(LocallyConsumedOutputs::default());
+        let prefetcher =
+            OutputPrefetcher::new(Arc::new(NoDiskMaterializer), consumed.dupe(), 1024, 1);
+        let outputs = vec![path("buck-out/v2/gen/lib.a"), path("buck-out/v2/gen/lib.h")];
+
+        // Nothing downstream has needed these outputs locally yet.
+        assert!(prefetcher.outputs_to_prefetch(outputs.clone()).is_empty());
+
+        prefetcher.record_local_inputs(&[
+            path("buck-out/v2/gen/lib.h"),
+            path("buck-out/v2/gen/other.o"),
+        ]);
+        assert_eq!(
+            prefetcher.outputs_to_prefetch(outputs.clone()),
+            vec![path("buck-out/v2/gen/lib.h")]
+        );
+
+        // What was recorded is shared with the prefetchers of later builds.
+        let later = OutputPrefetcher::new(Arc::new(NoDiskMaterializer), consumed, 1024, 1);
+        assert_eq!(
+            later.outputs_to_prefetch(outputs),
+            vec![path("buck-out/v2/gen/lib.h")]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::future::Future;
+use std::time::Duration;
+
+use buck2_core::execution_types::executor_config::RemoteExecutorOptions;
+use rand::Rng;
+use remote_execution::REClientError;
+use remote_execution::TCode;
+
+/// How to retry scheduling an action on RE when RE is unavailable.
+///
+/// Only errors talking to RE are retried. An action which ran and failed is a successful
+/// response from RE, so it is never retried here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReRetryPolicy {
+    pub max_attempts: u32,
+    pub base_backoff: Duration,
+    pub jitter: bool,
+}
+
+impl Default for ReRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            base_backoff: Duration::from_millis(500),
+            jitter: false,
+        }
+    }
+}
+
+impl ReRetryPolicy {
+    pub fn from_options(options: &RemoteExecutorOptions) -> Self {
+        let default = Self::default();
+        Self {
+            max_attempts: options
+                .re_max_attempts
+                .unwrap_or(default.max_attempts)
+                .max(1),
+            base_backoff: options
+                .re_retry_base_backoff_ms
+                .map_or(default.base_backoff, Duration::from_millis),
+            jitter: options.re_retry_jitter,
+        }
+    }
+
+    /// Whether an error is a transient failure to reach RE. Scheduling an action is keyed by
+    /// its digest, so doing it again is safe.
+    fn is_retryable(error: &anyhow::Error) -> bool {
+        if let Some(e) = error.downcast_ref::<REClientError>() {
+            return e.code == TCode::UNAVAILABLE;
+        }
+        if let Some(status) = error.downcast_ref::<tonic::Status>() {
+            return status.code() == tonic::Code::Unavailable;
+        }
+        false
+    }
+
+    /// The delay before retrying after attempt number `attempt` (starting from 1) failed with
+    /// `error`, or `None` if it should not be retried.
+    pub fn retry_after(&self, attempt: u32, error: &anyhow::Error) -> Option<Duration> {
+        if attempt >= self.max_attempts || !Self::is_retryable(error) {
+            return None;
+        }
+        let backoff = self
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1));
+        if self.jitter {
+            Some(backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0)))
+        } else {
+            Some(backoff)
+        }
+    }
+
+    /// Run `f` until it succeeds, fails with an error which is not retryable, or runs out of
+    /// attempts. `state` is threaded through the attempts, so that they can borrow it mutably.
+    pub async fn retry<S, T, F, Fut>(&self, mut state: S, mut f: F) -> (S, anyhow::Result<T>)
+    where
+        F: FnMut(S) -> Fut,
+        Fut: Future<Output = (S, anyhow::Result<T>)>,
+    {
+        let mut attempt = 1;
+        loop {
+            let (s, res) = f(state).await;
+            state = s;
+            if let Err(e) = &res {
+                if let Some(delay) = self.retry_after(attempt, e) {
+                    tracing::warn!(
+                        "RE call failed (attempt {} of {}), retrying in {:?}: {:#}",
+                        attempt,
+                        self.max_attempts,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+            }
+            return (state, res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails with `code` for the first `failures` calls.
+    struct MockReClient {
+        failures: u32,
+        code: TCode,
+        calls: u32,
+    }
+
+    impl MockReClient {
+        async fn execute(&mut self) -> anyhow::Result<&'static str> {
+            self.calls += 1;
+            if self.calls <= self.failures {
+                return Err(REClientError {
+                    message: "failed".to_owned(),
+                    code: self.code.clone(),
+                }
+                .into());
+            }
+            Ok("response")
+        }
+    }
+
+    async fn execute_with(policy: ReRetryPolicy, client: MockReClient) -> (u32, bool) {
+        let (client, res) = policy
+            .retry(client, |mut client| async move {
+                let res = client.execute().await;
+                (client, res)
+            })
+            .await;
+        (client.calls, res.is_ok())
+    }
+
+    fn policy(max_attempts: u32) -> ReRetryPolicy {
+        ReRetryPolicy {
+            max_attempts,
+            base_backoff: Duration::from_millis(100),
+            jitter: false,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_succeeds_after_transient_failures() {
+        let client = MockReClient {
+            failures: 2,
+            code: TCode::UNAVAILABLE,
+            calls: 0,
+        };
+        assert_eq!(execute_with(policy(3), client).await, (3, true));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_gives_up() {
+        let client = MockReClient {
+            failures: 2,
+            code: TCode::UNAVAILABLE,
+            calls: 0,
+        };
+        assert_eq!(execute_with(policy(2), client).await, (2, false));
+
+        // By default, nothing is retried.
+        let client = MockReClient {
+            failures: 2,
+            code: TCode::UNAVAILABLE,
+            calls: 0,
+        };
+        assert_eq!(
+            execute_with(ReRetryPolicy::default(), client).await,
+            (1, false)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_only_unavailable() {
+        let client = MockReClient {
+            failures: 1,
+            code: TCode::INVALID_ARGUMENT,
+            calls: 0,
+        };
+        assert_eq!(execute_with(policy(3), client).await, (1, false));
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let error = anyhow::Error::from(REClientError {
+            message: "failed".to_owned(),
+            code: TCode::UNAVAILABLE,
+        });
+        let p = policy(4);
+        assert_eq!(p.retry_after(1, &error), Some(Duration::from_millis(100)));
+        assert_eq!(p.retry_after(2, &error), Some(Duration::from_millis(200)));
+        assert_eq!(p.retry_after(3, &error), Some(Duration::from_millis(400)));
+        assert_eq!(p.retry_after(4, &error), None);
+
+        let p = ReRetryPolicy {
+            jitter: true,
+            ..policy(4)
+        };
+        let delay = p.retry_after(2, &error).unwrap();
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+    }
+}
@@ -18,8 +18,10 @@ use buck2_common::legacy_configs::LegacyBuckConfig;
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::is_open_source;
 use dice::DiceTransactionUpdater;
+use tokio::sync::broadcast;
 
 use crate::mergebase::Mergebase;
 use crate::notify::NotifyFileWatcher;
@@ -31,6 +33,12 @@ pub trait FileWatcher: Allocative + Send + Sync + 'static {
         &self,
         dice: DiceTransactionUpdater,
     ) -> anyhow::Result<(DiceTransactionUpdater, Mergebase)>;
+
+    /// Receive the paths of changed files as the watcher sees them, rather than at the next
+    /// `sync`. Ignored paths are not reported. Returns `None` if this watcher can't do that.
+    fn subscribe(&self) -> Option<broadcast::Receiver<ProjectRelativePathBuf>> {
+        None
+    }
 }
 
 impl dyn FileWatcher {
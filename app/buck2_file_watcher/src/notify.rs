@@ -22,6 +22,7 @@ use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_events::dispatch::span_async;
 use dice::DiceTransactionUpdater;
 use dupe::Dupe;
@@ -33,6 +34,7 @@ use notify::EventKind;
 use notify::RecommendedWatcher;
 use notify::Watcher;
 use starlark_map::ordered_set::OrderedSet;
+use tokio::sync::broadcast;
 use tracing::info;
 
 use crate::file_watcher::FileWatcher;
@@ -99,6 +101,7 @@ impl NotifyFileData {
         root: &ProjectRoot,
         cells: &CellResolver,
         ignore_specs: &HashMap<CellName, IgnoreSet>,
+        changes: &broadcast::Sender<ProjectRelativePathBuf>,
     ) -> anyhow::Result<()> {
         let event = event?;
         let change_type = ChangeType::new(event.kind);
@@ -131,6 +134,8 @@ impl NotifyFileData {
             if ignore || change_type == ChangeType::None {
                 self.ignored += 1;
             } else {
+                // Nobody subscribing is not an error.
+                let _ignored = changes.send(path.clone().into_owned());
                 self.events.insert((cell_path, change_type));
             }
         }
@@ -181,6 +186,8 @@ pub struct NotifyFileWatcher {
     #[allocative(skip)]
     watcher: RecommendedWatcher,
     data: Arc<Mutex<anyhow::Result<NotifyFileData>>>,
+    #[allocative(skip)]
+    changes: broadcast::Sender<ProjectRelativePathBuf>,
 }
 
 impl NotifyFileWatcher {
@@ -192,16 +199,23 @@ impl NotifyFileWatcher {
         let data = Arc::new(Mutex::new(Ok(NotifyFileData::new())));
         let data2 = data.dupe();
         let root2 = root.dupe();
+        // Subscribers which fall behind miss changes rather than holding up the watcher.
+        let changes = broadcast::channel(1000).0;
+        let changes2 = changes.clone();
         let mut watcher = notify::recommended_watcher(move |event| {
             let mut guard = data2.lock().unwrap();
             if let Ok(state) = &mut *guard {
-                if let Err(e) = state.process(event, &root2, &cells, &ignore_specs) {
+                if let Err(e) = state.process(event, &root2, &cells, &ignore_specs, &changes2) {
                     *guard = Err(e);
                 }
             }
         })?;
         watcher.watch(root.root().as_path(), notify::RecursiveMode::Recursive)?;
-        Ok(Self { watcher, data })
+        Ok(Self {
+            watcher,
+            data,
+            changes,
+        })
     }
 
     fn sync2(
@@ -239,4 +253,8 @@ impl FileWatcher for NotifyFileWatcher {
         )
         .await
     }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<ProjectRelativePathBuf>> {
+        Some(self.changes.subscribe())
+    }
 }
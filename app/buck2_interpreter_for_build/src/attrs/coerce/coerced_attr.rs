@@ -342,6 +342,50 @@ mod tests {
                 .unwrap_err()
                 .to_string()
         );
+
+        // The keys of the branches taken, including in the taken branch and in concat.
+        let nested = CoercedAttr::Selector(Box::new(
+            CoercedSelector::new(
+                ArcSlice::new([(linux_arm64.dupe(), literal_true())]),
+                Some(literal_str()),
+            )
+            .unwrap(),
+        ));
+        let select = CoercedAttr::Selector(Box::new(
+            CoercedSelector::new(
+                ArcSlice::new([(linux.dupe(), nested), (linux_x86_64.dupe(), literal_str())]),
+                None,
+            )
+            .unwrap(),
+        ));
+        assert_eq!(
+            vec!["config//:linux-x86_64"],
+            select.selected_keys(&ctx).unwrap()
+        );
+        let select = CoercedAttr::Selector(Box::new(
+            CoercedSelector::new(
+                ArcSlice::new([(linux.dupe(), select)]),
+                Some(literal_true()),
+            )
+            .unwrap(),
+        ));
+        assert_eq!(
+            vec!["config//:linux", "config//:linux-x86_64"],
+            select.selected_keys(&ctx).unwrap()
+        );
+        let simple = CoercedAttr::Selector(Box::new(
+            CoercedSelector::new(ArcSlice::new([(linux.dupe(), literal_str())]), None).unwrap(),
+        ));
+        assert_eq!(
+            vec!["config//:linux", "config//:linux-x86_64", "config//:linux"],
+            CoercedAttr::Concat(Box::new([select, literal_str(), simple]))
+                .selected_keys(&ctx)
+                .unwrap()
+        );
+        assert_eq!(
+            Vec::<String>::new(),
+            literal_str().selected_keys(&ctx).unwrap()
+        );
     }
 
     #[test]
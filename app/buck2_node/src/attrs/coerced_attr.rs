@@ -481,16 +481,25 @@ impl CoercedAttr {
         ctx: &dyn AttrConfigurationContext,
         select_entries: &'a [(TargetLabel, CoercedAttr)],
     ) -> anyhow::Result<Option<&'a CoercedAttr>> {
-        let mut matching: Option<(&TargetLabel, &ConfigSettingData, &CoercedAttr)> = None;
-        for (k, v) in select_entries {
+        Ok(Self::select_the_most_specific_entry(ctx, select_entries)?.map(|(_k, v)| v))
+    }
+
+    fn select_the_most_specific_entry<'a>(
+        ctx: &dyn AttrConfigurationContext,
+        select_entries: &'a [(TargetLabel, CoercedAttr)],
+    ) -> anyhow::Result<Option<&'a (TargetLabel, CoercedAttr)>> {
+        let mut matching: Option<(&(TargetLabel, CoercedAttr), &ConfigSettingData)> = None;
+        for entry in select_entries {
+            let (k, _v) = entry;
             matching = match (ctx.matches(k), matching) {
                 (None, matching) => matching,
-                (Some(conf), None) => Some((k, conf, v)),
-                (Some(conf), Some((prev_k, prev_conf, prev_v))) => {
+                (Some(conf), None) => Some((entry, conf)),
+                (Some(conf), Some((prev_entry, prev_conf))) => {
+                    let (prev_k, _prev_v) = prev_entry;
                     if conf.refines(prev_conf) {
-                        Some((k, conf, v))
+                        Some((entry, conf))
                     } else if prev_conf.refines(conf) {
-                        Some((prev_k, prev_conf, prev_v))
+                        Some((prev_entry, prev_conf))
                     } else {
                         return Err(SelectError::TwoKeysDoNotRefineEachOther(
                             prev_k.to_string(),
@@ -501,24 +510,53 @@ impl CoercedAttr {
                 }
             }
         }
-        Ok(matching.map(|(_k, _conf, v)| v))
+        Ok(matching.map(|(entry, _conf)| entry))
     }
 
+    /// Resolve a `select()`, returning the key of the branch taken (`None` for the default)
+    /// and its value.
     fn select<'a>(
         ctx: &dyn AttrConfigurationContext,
         select: &'a CoercedSelector,
-    ) -> anyhow::Result<&'a CoercedAttr> {
+    ) -> anyhow::Result<(Option<&'a TargetLabel>, &'a CoercedAttr)> {
         let CoercedSelector { entries, default } = select;
-        if let Some(v) = Self::select_the_most_specific(ctx, entries)? {
-            Ok(v)
+        if let Some((k, v)) = Self::select_the_most_specific_entry(ctx, entries)? {
+            Ok((Some(k), v))
         } else {
-            default.as_ref().ok_or_else(|| {
+            let default = default.as_ref().ok_or_else(|| {
                 SelectError::MissingDefault(
                     ctx.cfg().cfg().dupe(),
                     entries.iter().map(|(k, _)| k).duped().collect(),
                 )
-                .into()
-            })
+            })?;
+            Ok((None, default))
+        }
+    }
+
+    /// The keys of the `select()` branches taken in the provided context, in the order the
+    /// selects appear in the attribute, with `DEFAULT` for a default branch. Empty if the
+    /// attribute has no selects.
+    pub fn selected_keys(&self, ctx: &dyn AttrConfigurationContext) -> anyhow::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        self.collect_selected_keys(ctx, &mut keys)?;
+        Ok(keys)
+    }
+
+    fn collect_selected_keys(
+        &self,
+        ctx: &dyn AttrConfigurationContext,
+        keys: &mut Vec<String>,
+    ) -> anyhow::Result<()> {
+        match self {
+            CoercedAttr::Selector(select) => {
+                let (k, v) = Self::select(ctx, select)?;
+                keys.push(k.map_or_else(|| "DEFAULT".to_owned(), |k| k.to_string()));
+                v.collect_selected_keys(ctx, keys)
+            }
+            CoercedAttr::Concat(items) => items
+                .iter()
+                .try_for_each(|item| item.collect_selected_keys(ctx, keys)),
+            _ => Ok(()),
         }
     }
 
@@ -533,7 +571,7 @@ impl CoercedAttr {
     ) -> anyhow::Result<ConfiguredAttr> {
         Ok(match CoercedAttrWithType::pack(self, ty)? {
             CoercedAttrWithType::Selector(select, t) => {
-                Self::select(ctx, select)?.configure(t, ctx)?
+                Self::select(ctx, select)?.1.configure(t, ctx)?
             }
             CoercedAttrWithType::Concat(items, t) => {
                 let singleton = items.len() == 1;
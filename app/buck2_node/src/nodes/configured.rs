@@ -539,6 +539,25 @@ impl ConfiguredTargetNode {
         })
     }
 
+    /// The `select()` branches taken for each attribute which has any selects, as returned by
+    /// [`CoercedAttr::selected_keys`](crate::attrs::coerced_attr::CoercedAttr::selected_keys).
+    pub fn selected_keys<'a>(
+        &'a self,
+        opts: AttrInspectOptions,
+    ) -> impl Iterator<Item = (&'a str, Vec<String>)> + 'a {
+        self.0.target_node.attrs(opts).filter_map(move |a| {
+            let keys = a
+                .value
+                .selected_keys(&self.attr_configuration_context())
+                .expect("checked attr configuration in constructor");
+            if keys.is_empty() {
+                None
+            } else {
+                Some((a.name, keys))
+            }
+        })
+    }
+
     pub fn call_stack(&self) -> Option<String> {
         match &self.0.target_node {
             TargetNodeOrForward::TargetNode(n) => n.call_stack(),
@@ -366,3 +366,35 @@ async fn test_paths_with_cycles_present() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_iter_with_duplicates() -> anyhow::Result<()> {
+    let mut env = TestEnvBuilder::default();
+    // A diamond: 4 is reached through both 2 and 3.
+    env.edge(1, 2);
+    env.edge(1, 3);
+    env.edge(2, 4);
+    env.edge(3, 4);
+    env.edge(4, 5);
+    // 6 is not in the set, so this edge is not followed.
+    env.edge(5, 6);
+    let env = env.build();
+
+    let set = env.set("5,4,3,2,1")?;
+    let ids = |targets: Vec<&TestTarget>| targets.into_iter().map(|t| t.id.0).collect::<Vec<_>>();
+    // Independent of the order of the set.
+    assert_eq!(ids(set.iter_with_duplicates()), vec![1, 2, 3, 4, 4, 5]);
+    assert_eq!(
+        ids(env.set("1,2,3,4,5")?.iter_with_duplicates()),
+        vec![1, 2, 3, 4, 4, 5]
+    );
+
+    // Every node in a cycle has a dep edge leading to it, so there is no root.
+    let mut env = TestEnvBuilder::default();
+    env.edge(1, 2);
+    env.edge(2, 1);
+    let env = env.build();
+    assert_eq!(ids(env.set("2,1")?.iter_with_duplicates()), vec![2, 1, 2]);
+
+    Ok(())
+}
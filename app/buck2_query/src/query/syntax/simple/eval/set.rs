@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::collections::VecDeque;
 use std::fmt;
 use std::fmt::Display;
 
@@ -136,6 +137,53 @@ impl<T: QueryTarget> TargetSet<T> {
     pub fn last(&self) -> Option<&T> {
         self.targets.last()
     }
+
+    /// The targets in breadth-first order of a traversal of the deps between them, with each
+    /// target appearing once for every dep edge leading to it from within the set (and roots,
+    /// which have no such edge, once). The traversal starts from the roots in set order, and
+    /// follows deps in the order they are declared, so the result is deterministic.
+    pub fn iter_with_duplicates(&self) -> Vec<&T> {
+        let mut has_rdeps = vec![false; self.len()];
+        for target in self.targets.iter() {
+            for dep in target.deps() {
+                if let Some(index) = self.get_index_of(dep) {
+                    has_rdeps[index] = true;
+                }
+            }
+        }
+
+        let mut visited = vec![false; self.len()];
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+        // Roots first, then whatever is only reachable from a cycle which was not reached yet.
+        let starts = (0..self.len())
+            .filter(|i| !has_rdeps[*i])
+            .chain(0..self.len())
+            .collect::<Vec<_>>();
+        for start in starts {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let target = self.targets.get_index(start).unwrap();
+            result.push(target);
+            queue.push_back(target);
+            while let Some(target) = queue.pop_front() {
+                for dep in target.deps() {
+                    let Some(index) = self.get_index_of(dep) else {
+                        continue;
+                    };
+                    let dep = self.targets.get_index(index).unwrap();
+                    result.push(dep);
+                    if !visited[index] {
+                        visited[index] = true;
+                        queue.push_back(dep);
+                    }
+                }
+            }
+        }
+        result
+    }
 }
 
 pub type Iter<'a, T> = label_indexed::Iter<'a, T>;
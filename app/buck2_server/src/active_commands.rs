@@ -10,9 +10,13 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use buck2_cli_proto::ClientContext;
 use buck2_event_observer::dice_state::DiceState;
+use buck2_event_observer::display::display_action_identity;
+use buck2_event_observer::display::TargetDisplayOptions;
 use buck2_event_observer::pending_estimate::pending_estimate;
 use buck2_event_observer::span_tracker;
 use buck2_event_observer::span_tracker::RootData;
@@ -20,11 +24,15 @@ use buck2_event_observer::span_tracker::Roots;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_events::span::SpanId;
 use buck2_events::BuckEvent;
+use buck2_execute_impl::low_pass_filter::LowPassFilter;
+use buck2_execute_impl::low_pass_filter::LowPassFilterStats;
 use buck2_wrapper_common::invocation_id::TraceId;
 use dupe::Dupe;
+use host_sharing::HostSharingBroker;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use parking_lot::MutexGuard;
+use tokio::sync::broadcast;
 use tokio::sync::oneshot;
 
 static ACTIVE_COMMANDS: Lazy<Mutex<HashMap<TraceId, ActiveCommandHandle>>> =
@@ -40,6 +48,73 @@ pub fn active_commands() -> MutexGuard<'static, HashMap<TraceId, ActiveCommandHa
     ACTIVE_COMMANDS.lock()
 }
 
+/// Cache outcomes of actions executed by any command, for subscriptions. Subscribers which
+/// fall behind miss outcomes rather than holding up commands.
+static CACHE_OUTCOMES: Lazy<broadcast::Sender<buck2_subscription_proto::CacheOutcome>> =
+    Lazy::new(|| broadcast::channel(1000).0);
+
+pub fn subscribe_to_cache_outcomes() -> broadcast::Receiver<buck2_subscription_proto::CacheOutcome>
+{
+    CACHE_OUTCOMES.subscribe()
+}
+
+/// The cache outcome described by an event, if any.
+fn cache_outcome(buck_event: &BuckEvent) -> Option<buck2_subscription_proto::CacheOutcome> {
+    use buck2_data::buck_event::Data::*;
+    use buck2_data::instant_event::Data as InstantData;
+    use buck2_data::span_end_event::Data as EndData;
+    use buck2_subscription_proto::cache_outcome::Kind;
+
+    let (key, name, action_digest, kind, success) = match buck_event.data() {
+        Instant(instant) => match instant.data.as_ref()? {
+            InstantData::ActionCacheOutcome(outcome) => {
+                let kind = match (outcome.cache_type(), outcome.hit) {
+                    (buck2_data::CacheType::ActionCache, true) => Kind::ActionCacheHit,
+                    (buck2_data::CacheType::ActionCache, false) => Kind::ActionCacheMiss,
+                    (buck2_data::CacheType::RemoteDepFileCache, true) => {
+                        Kind::RemoteDepFileCacheHit
+                    }
+                    (buck2_data::CacheType::RemoteDepFileCache, false) => {
+                        Kind::RemoteDepFileCacheMiss
+                    }
+                };
+                (
+                    outcome.key.as_ref(),
+                    outcome.name.as_ref(),
+                    &outcome.action_digest,
+                    kind,
+                    true,
+                )
+            }
+            _ => return None,
+        },
+        SpanEnd(end) => match end.data.as_ref()? {
+            EndData::CacheUpload(upload) => (
+                upload.key.as_ref(),
+                upload.name.as_ref(),
+                &upload.action_digest,
+                Kind::Upload,
+                upload.success,
+            ),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let action = display_action_identity(key, name, TargetDisplayOptions::for_log())
+        .unwrap_or_else(|_| "<unknown action>".to_owned());
+
+    Some(buck2_subscription_proto::CacheOutcome {
+        trace_id: buck_event
+            .trace_id()
+            .map_or_else(|_| String::new(), |t| t.to_string()),
+        action,
+        action_digest: action_digest.clone(),
+        kind: kind.into(),
+        success,
+    })
+}
+
 /// Broadcasts an instant event, returns whether any subscribers were connected.
 pub fn broadcast_instant_event<E: Into<buck2_data::instant_event::Data> + Clone>(
     event: &E,
@@ -104,6 +179,21 @@ pub struct ActiveCommandState {
     pub argv: Vec<String>,
 
     spans: Mutex<SpansSnapshot>,
+
+    actions: Mutex<ActionTracker>,
+
+    low_pass_filter: Mutex<Option<Arc<LowPassFilter>>>,
+
+    /// The host sharing broker of the local executor, and the memory budget of local actions.
+    local_resources: Mutex<Option<(Arc<HostSharingBroker>, Option<u64>)>>,
+}
+
+/// How much of the resources available to local actions are in use.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LocalResourcesUsage {
+    pub cpu_permits: usize,
+    pub cpu_permits_in_use: usize,
+    pub memory_bytes: Option<u64>,
 }
 
 impl ActiveCommandState {
@@ -111,12 +201,61 @@ impl ActiveCommandState {
         *self.spans.lock()
     }
 
+    pub fn actions(&self) -> ActionsSnapshot {
+        self.actions.lock().snapshot()
+    }
+
+    /// The actions which have been executing for the longest, longest first.
+    pub fn longest_running_actions(&self, limit: usize) -> Vec<RunningAction> {
+        let now = SystemTime::now();
+        let actions = self.actions.lock();
+        let mut open: Vec<_> = actions.actions.values().collect();
+        open.sort_by_key(|action| action.start);
+        open.into_iter()
+            .take(limit)
+            .map(|action| action.running(now))
+            .collect()
+    }
+
     fn new(argv: Vec<String>) -> Self {
         Self {
             argv,
             spans: Mutex::new(SpansSnapshot::default()),
+            actions: Mutex::new(ActionTracker::default()),
+            low_pass_filter: Mutex::new(None),
+            local_resources: Mutex::new(None),
         }
     }
+
+    /// Record the low pass filter of the executors used by this command, so that its usage can
+    /// be reported while the command runs.
+    pub fn set_low_pass_filter(&self, low_pass_filter: Arc<LowPassFilter>) {
+        *self.low_pass_filter.lock() = Some(low_pass_filter);
+    }
+
+    pub fn low_pass_filter(&self) -> Option<LowPassFilterStats> {
+        self.low_pass_filter.lock().as_ref().map(|f| f.stats())
+    }
+
+    /// Record the host sharing broker which limits the local actions of this command, so that
+    /// its utilization can be reported while the command runs.
+    pub fn set_local_resources(&self, broker: Arc<HostSharingBroker>, memory_bytes: Option<u64>) {
+        *self.local_resources.lock() = Some((broker, memory_bytes));
+    }
+
+    pub fn local_resources(&self) -> Option<LocalResourcesUsage> {
+        self.local_resources
+            .lock()
+            .as_ref()
+            .map(|(broker, memory_bytes)| {
+                let cpu_permits = broker.num_machine_permits();
+                LocalResourcesUsage {
+                    cpu_permits,
+                    cpu_permits_in_use: cpu_permits.saturating_sub(broker.available_permits()),
+                    memory_bytes: *memory_bytes,
+                }
+            })
+    }
 }
 
 #[derive(PartialEq, Debug, Default, Copy, Clone, Dupe)]
@@ -126,6 +265,244 @@ pub struct SpansSnapshot {
     pub pending: u64,
 }
 
+/// The number of actions in each state, for actions executed by this command.
+#[derive(PartialEq, Debug, Default, Copy, Clone, Dupe)]
+pub struct ActionsSnapshot {
+    pub queued: u64,
+    pub running_local: u64,
+    pub running_remote: u64,
+    /// Actions served from the cache, whether or not they have finished.
+    pub cached: u64,
+    /// Finished actions which were not cache hits.
+    pub done: u64,
+}
+
+/// An action which is currently executing.
+#[derive(Debug)]
+pub struct RunningAction {
+    /// The owner of the action, and its category and identifier.
+    pub identity: String,
+    pub state: &'static str,
+    /// The command the action is running, if it got that far.
+    pub command: Option<String>,
+    pub elapsed: Duration,
+}
+
+/// Merge the longest running actions of several commands, keeping the `limit` longest running
+/// ones overall, longest first.
+pub fn merge_longest_running_actions<K>(
+    commands: impl IntoIterator<Item = (K, Vec<RunningAction>)>,
+    limit: usize,
+) -> Vec<(K, RunningAction)>
+where
+    K: Clone,
+{
+    let mut actions: Vec<(K, RunningAction)> = commands
+        .into_iter()
+        .flat_map(|(command, actions)| {
+            actions
+                .into_iter()
+                .map(move |action| (command.clone(), action))
+        })
+        .collect();
+    actions.sort_by_key(|(_, action)| std::cmp::Reverse(action.elapsed));
+    actions.truncate(limit);
+    actions
+}
+
+#[derive(PartialEq, Debug, Copy, Clone, Dupe)]
+enum ActionState {
+    Queued,
+    RunningLocal,
+    RunningRemote,
+    Cached,
+}
+
+impl ActionState {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::RunningLocal => "running-local",
+            Self::RunningRemote => "running-remote",
+            Self::Cached => "cached",
+        }
+    }
+
+    /// The state an action is in once it enters an executor stage, if that stage tells us.
+    fn from_stage(stage: &buck2_data::executor_stage_start::Stage) -> Option<Self> {
+        use buck2_data::executor_stage_start::Stage;
+        use buck2_data::local_stage::Stage as LocalStage;
+        use buck2_data::re_stage::Stage as ReStage;
+
+        match stage {
+            Stage::CacheHit(..) => Some(Self::Cached),
+            Stage::Re(re) => match re.stage.as_ref()? {
+                ReStage::Queue(..) => Some(Self::Queued),
+                ReStage::Execute(..)
+                | ReStage::Download(..)
+                | ReStage::WorkerDownload(..)
+                | ReStage::WorkerUpload(..)
+                | ReStage::Unknown(..) => Some(Self::RunningRemote),
+            },
+            Stage::Local(local) => match local.stage.as_ref()? {
+                LocalStage::Queued(..)
+                | LocalStage::WorkerQueued(..)
+                | LocalStage::AcquireLocalResource(..)
+                | LocalStage::WorkerWait(..) => Some(Self::Queued),
+                LocalStage::Execute(..)
+                | LocalStage::MaterializeInputs(..)
+                | LocalStage::PrepareOutputs(..)
+                | LocalStage::WorkerInit(..)
+                | LocalStage::WorkerExecute(..) => Some(Self::RunningLocal),
+            },
+            Stage::CacheQuery(..) | Stage::Prepare(..) => None,
+        }
+    }
+}
+
+/// The command run by an executor stage, if any.
+fn stage_command(stage: &buck2_data::executor_stage_start::Stage) -> Option<String> {
+    use buck2_data::executor_stage_start::Stage;
+    use buck2_data::local_stage::Stage as LocalStage;
+    use buck2_data::re_stage::Stage as ReStage;
+
+    match stage {
+        Stage::Local(local) => match local.stage.as_ref()? {
+            LocalStage::Execute(execute) => Some(execute.command.as_ref()?.argv.join(" ")),
+            LocalStage::WorkerExecute(execute) => Some(execute.command.as_ref()?.argv.join(" ")),
+            _ => None,
+        },
+        Stage::Re(re) => match re.stage.as_ref()? {
+            ReStage::Execute(execute) => Some(format!("remote action {}", execute.action_digest)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+struct OpenAction {
+    state: ActionState,
+    start: SystemTime,
+    action: buck2_data::ActionExecutionStart,
+    command: Option<String>,
+}
+
+impl OpenAction {
+    fn running(&self, now: SystemTime) -> RunningAction {
+        let identity = display_action_identity(
+            self.action.key.as_ref(),
+            self.action.name.as_ref(),
+            TargetDisplayOptions::for_log(),
+        )
+        .unwrap_or_else(|_| "<unknown action>".to_owned());
+
+        RunningAction {
+            identity,
+            state: self.state.as_str(),
+            command: self.command.clone(),
+            elapsed: now.duration_since(self.start).unwrap_or_default(),
+        }
+    }
+}
+
+/// Tracks the state of actions as they execute.
+#[derive(Default)]
+struct ActionTracker {
+    /// Open action executions.
+    actions: HashMap<SpanId, OpenAction>,
+    /// Maps spans nested within an action execution to that action.
+    nested: HashMap<SpanId, SpanId>,
+    cached: u64,
+    done: u64,
+}
+
+impl ActionTracker {
+    fn peek_event(&mut self, buck_event: &BuckEvent) {
+        use buck2_data::buck_event::Data::*;
+        use buck2_data::span_start_event::Data as StartData;
+
+        let span_id = match buck_event.span_id() {
+            Some(id) => id,
+            None => return,
+        };
+
+        match buck_event.data() {
+            SpanStart(start) => {
+                if let Some(StartData::ActionExecution(action)) = &start.data {
+                    self.actions.insert(
+                        span_id,
+                        OpenAction {
+                            state: ActionState::Queued,
+                            start: buck_event.timestamp(),
+                            action: action.clone(),
+                            command: None,
+                        },
+                    );
+                    return;
+                }
+
+                let action = match buck_event.parent_id() {
+                    Some(parent) if self.actions.contains_key(&parent) => parent,
+                    Some(parent) => match self.nested.get(&parent) {
+                        Some(action) => *action,
+                        None => return,
+                    },
+                    None => return,
+                };
+                self.nested.insert(span_id, action);
+
+                let (Some(StartData::ExecutorStage(stage)), Some(open)) =
+                    (&start.data, self.actions.get_mut(&action))
+                else {
+                    return;
+                };
+                let Some(stage) = stage.stage.as_ref() else {
+                    return;
+                };
+                match ActionState::from_stage(stage) {
+                    // Once we've had a cache hit, that's what the action is.
+                    Some(state) if open.state != ActionState::Cached => open.state = state,
+                    _ => {}
+                }
+                if let Some(command) = stage_command(stage) {
+                    open.command = Some(command);
+                }
+            }
+            SpanEnd(..) => {
+                if self.nested.remove(&span_id).is_some() {
+                    return;
+                }
+                match self.actions.remove(&span_id) {
+                    Some(OpenAction {
+                        state: ActionState::Cached,
+                        ..
+                    }) => self.cached += 1,
+                    Some(_) => self.done += 1,
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn snapshot(&self) -> ActionsSnapshot {
+        let mut snapshot = ActionsSnapshot {
+            cached: self.cached,
+            done: self.done,
+            ..Default::default()
+        };
+        for action in self.actions.values() {
+            match action.state {
+                ActionState::Queued => snapshot.queued += 1,
+                ActionState::RunningLocal => snapshot.running_local += 1,
+                ActionState::RunningRemote => snapshot.running_remote += 1,
+                ActionState::Cached => snapshot.cached += 1,
+            }
+        }
+        snapshot
+    }
+}
+
 /// A wrapper around ActiveCommandState that allows 1 client to write to it.
 pub struct ActiveCommandStateWriter {
     /// Maps a SpanId to whether it is a root (i.e. no parent)
@@ -150,6 +527,17 @@ impl ActiveCommandStateWriter {
     pub fn peek_event(&mut self, buck_event: &BuckEvent) {
         use buck2_data::buck_event::Data::*;
 
+        if matches!(buck_event.data(), SpanStart(..) | SpanEnd(..)) {
+            self.shared.actions.lock().peek_event(buck_event);
+        }
+
+        if CACHE_OUTCOMES.receiver_count() > 0 {
+            if let Some(outcome) = cache_outcome(buck_event) {
+                // Nothing to do if all subscribers hung up in the meantime.
+                let _ignored = CACHE_OUTCOMES.send(outcome);
+            }
+        }
+
         let mut changed = false;
 
         match buck_event.data() {
@@ -407,4 +795,385 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_active_command_actions() {
+        let mut writer =
+            ActiveCommandStateWriter::new(Arc::new(ActiveCommandState::new(Vec::new())));
+        let trace = TraceId::new();
+
+        let start = |writer: &mut ActiveCommandStateWriter,
+                     span: SpanId,
+                     parent: Option<SpanId>,
+                     data: buck2_data::span_start_event::Data| {
+            writer.peek_event(&BuckEvent::new(
+                SystemTime::now(),
+                trace.clone(),
+                Some(span),
+                parent,
+                buck2_data::SpanStartEvent { data: Some(data) }.into(),
+            ));
+        };
+        let end = |writer: &mut ActiveCommandStateWriter,
+                   span: SpanId,
+                   parent: Option<SpanId>,
+                   data: buck2_data::span_end_event::Data| {
+            writer.peek_event(&BuckEvent::new(
+                SystemTime::now(),
+                trace.clone(),
+                Some(span),
+                parent,
+                buck2_data::SpanEndEvent {
+                    data: Some(data),
+                    ..Default::default()
+                }
+                .into(),
+            ));
+        };
+        let stage =
+            |stage: buck2_data::executor_stage_start::Stage| -> buck2_data::span_start_event::Data {
+                buck2_data::ExecutorStageStart { stage: Some(stage) }.into()
+            };
+        let local_execute = stage(
+            buck2_data::LocalStage {
+                stage: Some(buck2_data::LocalExecute::default().into()),
+            }
+            .into(),
+        );
+        let cache_hit = stage(buck2_data::CacheHit::default().into());
+
+        let local = SpanId::next();
+        let local_stage = SpanId::next();
+        let cached = SpanId::next();
+        let cached_stage = SpanId::next();
+        let queued = SpanId::next();
+
+        start(
+            &mut writer,
+            local,
+            None,
+            buck2_data::ActionExecutionStart::default().into(),
+        );
+        start(
+            &mut writer,
+            cached,
+            None,
+            buck2_data::ActionExecutionStart::default().into(),
+        );
+        start(
+            &mut writer,
+            queued,
+            None,
+            buck2_data::ActionExecutionStart::default().into(),
+        );
+        start(&mut writer, local_stage, Some(local), local_execute);
+        start(&mut writer, cached_stage, Some(cached), cache_hit);
+
+        assert_eq!(
+            writer.shared.actions(),
+            ActionsSnapshot {
+                queued: 1,
+                running_local: 1,
+                running_remote: 0,
+                cached: 1,
+                done: 0,
+            }
+        );
+
+        end(
+            &mut writer,
+            local_stage,
+            Some(local),
+            buck2_data::ExecutorStageEnd::default().into(),
+        );
+        end(
+            &mut writer,
+            local,
+            None,
+            buck2_data::ActionExecutionEnd::default().into(),
+        );
+        end(
+            &mut writer,
+            cached_stage,
+            Some(cached),
+            buck2_data::ExecutorStageEnd::default().into(),
+        );
+        end(
+            &mut writer,
+            cached,
+            None,
+            buck2_data::ActionExecutionEnd::default().into(),
+        );
+
+        assert_eq!(
+            writer.shared.actions(),
+            ActionsSnapshot {
+                queued: 1,
+                running_local: 0,
+                running_remote: 0,
+                cached: 1,
+                done: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_longest_running_actions() {
+        let mut writer =
+            ActiveCommandStateWriter::new(Arc::new(ActiveCommandState::new(Vec::new())));
+        let trace = TraceId::new();
+        let now = SystemTime::now();
+
+        let older = SpanId::next();
+        let newer = SpanId::next();
+        let stage = SpanId::next();
+
+        for (span, started) in [(newer, 10), (older, 20)] {
+            writer.peek_event(&BuckEvent::new(
+                now - Duration::from_secs(started),
+                trace.clone(),
+                Some(span),
+                None,
+                buck2_data::SpanStartEvent {
+                    data: Some(buck2_data::ActionExecutionStart::default().into()),
+                }
+                .into(),
+            ));
+        }
+        writer.peek_event(&BuckEvent::new(
+            now,
+            trace,
+            Some(stage),
+            Some(older),
+            buck2_data::SpanStartEvent {
+                data: Some(
+                    buck2_data::ExecutorStageStart {
+                        stage: Some(
+                            buck2_data::LocalStage {
+                                stage: Some(
+                                    buck2_data::LocalExecute {
+                                        command: Some(buck2_data::LocalCommand {
+                                            argv: vec!["cc".to_owned(), "foo.c".to_owned()],
+                                            ..Default::default()
+                                        }),
+                                    }
+                                    .into(),
+                                ),
+                            }
+                            .into(),
+                        ),
+                    }
+                    .into(),
+                ),
+            }
+            .into(),
+        ));
+
+        let running = writer.shared.longest_running_actions(1);
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].state, "running-local");
+        assert_eq!(running[0].command.as_deref(), Some("cc foo.c"));
+        assert!(running[0].elapsed >= Duration::from_secs(20));
+
+        assert_eq!(writer.shared.longest_running_actions(5).len(), 2);
+    }
+
+    #[test]
+    fn test_longest_running_actions_ended() {
+        let mut writer =
+            ActiveCommandStateWriter::new(Arc::new(ActiveCommandState::new(Vec::new())));
+        let trace = TraceId::new();
+        let action = SpanId::next();
+
+        writer.peek_event(&BuckEvent::new(
+            SystemTime::now(),
+            trace.clone(),
+            Some(action),
+            None,
+            buck2_data::SpanStartEvent {
+                data: Some(buck2_data::ActionExecutionStart::default().into()),
+            }
+            .into(),
+        ));
+        let running = writer.shared.longest_running_actions(5);
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].state, "queued");
+        assert_eq!(running[0].command, None);
+
+        writer.peek_event(&BuckEvent::new(
+            SystemTime::now(),
+            trace,
+            Some(action),
+            None,
+            buck2_data::SpanEndEvent {
+                data: Some(buck2_data::ActionExecutionEnd::default().into()),
+                ..Default::default()
+            }
+            .into(),
+        ));
+        assert!(writer.shared.longest_running_actions(5).is_empty());
+    }
+
+    #[test]
+    fn test_stage_command() {
+        use buck2_data::executor_stage_start::Stage;
+
+        let local = Stage::Local(buck2_data::LocalStage {
+            stage: Some(
+                buck2_data::LocalExecute {
+                    command: Some(buck2_data::LocalCommand {
+                        argv: vec!["cc".to_owned(), "foo.c".to_owned()],
+                        ..Default::default()
+                    }),
+                }
+                .into(),
+            ),
+        });
+        assert_eq!(stage_command(&local).as_deref(), Some("cc foo.c"));
+
+        let worker = Stage::Local(buck2_data::LocalStage {
+            stage: Some(
+                buck2_data::WorkerExecute {
+                    command: Some(buck2_data::WorkerCommand {
+                        argv: vec!["javac".to_owned(), "Foo.java".to_owned()],
+                        ..Default::default()
+                    }),
+                }
+                .into(),
+            ),
+        });
+        assert_eq!(stage_command(&worker).as_deref(), Some("javac Foo.java"));
+
+        let remote = Stage::Re(buck2_data::ReStage {
+            stage: Some(
+                buck2_data::ReExecute {
+                    action_digest: "abc:12".to_owned(),
+                    ..Default::default()
+                }
+                .into(),
+            ),
+        });
+        assert_eq!(
+            stage_command(&remote).as_deref(),
+            Some("remote action abc:12")
+        );
+
+        // Stages that don't run anything.
+        let queued = Stage::Re(buck2_data::ReStage {
+            stage: Some(buck2_data::ReQueue::default().into()),
+        });
+        assert_eq!(stage_command(&queued), None);
+        assert_eq!(
+            stage_command(&Stage::CacheHit(buck2_data::CacheHit::default())),
+            None
+        );
+        // A local execution that didn't report its command.
+        let no_command = Stage::Local(buck2_data::LocalStage {
+            stage: Some(buck2_data::LocalExecute::default().into()),
+        });
+        assert_eq!(stage_command(&no_command), None);
+    }
+
+    #[test]
+    fn test_merge_longest_running_actions() {
+        let action = |name: &str, secs: u64| RunningAction {
+            identity: name.to_owned(),
+            state: "running-local",
+            command: None,
+            elapsed: Duration::from_secs(secs),
+        };
+        let merged = merge_longest_running_actions(
+            [
+                ("a", vec![action("a1", 30), action("a2", 5)]),
+                ("b", vec![action("b1", 40), action("b2", 10)]),
+                ("c", Vec::new()),
+            ],
+            3,
+        );
+        assert_eq!(
+            merged
+                .iter()
+                .map(|(command, action)| (*command, action.identity.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("b", "b1"), ("a", "a1"), ("b", "b2")]
+        );
+        assert!(merge_longest_running_actions([("a", vec![action("a1", 1)])], 0).is_empty());
+    }
+
+    #[test]
+    fn test_cache_outcome() {
+        let trace = TraceId::new();
+        let name = Some(buck2_data::ActionName {
+            category: "cxx_compile".to_owned(),
+            identifier: "foo.cpp".to_owned(),
+        });
+
+        let outcome = |cache_type: buck2_data::CacheType, hit| {
+            cache_outcome(&BuckEvent::new(
+                SystemTime::now(),
+                trace.clone(),
+                None,
+                None,
+                buck2_data::InstantEvent {
+                    data: Some(
+                        buck2_data::ActionCacheOutcome {
+                            key: None,
+                            name: name.clone(),
+                            action_digest: "abc:1".to_owned(),
+                            cache_type: cache_type.into(),
+                            hit,
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+            ))
+            .unwrap()
+        };
+
+        use buck2_subscription_proto::cache_outcome::Kind;
+
+        // An unset kind is not mistaken for any real outcome.
+        assert_eq!(
+            buck2_subscription_proto::CacheOutcome::default().kind(),
+            Kind::Unknown
+        );
+
+        let hit = outcome(buck2_data::CacheType::ActionCache, true);
+        assert_eq!(hit.kind(), Kind::ActionCacheHit);
+        assert_eq!(hit.trace_id, trace.to_string());
+        assert_eq!(hit.action_digest, "abc:1");
+        assert!(hit.success);
+        // Dep file cache hits are distinguishable from action cache hits.
+        assert_eq!(
+            outcome(buck2_data::CacheType::RemoteDepFileCache, true).kind(),
+            Kind::RemoteDepFileCacheHit
+        );
+        assert_eq!(
+            outcome(buck2_data::CacheType::ActionCache, false).kind(),
+            Kind::ActionCacheMiss
+        );
+
+        let upload = cache_outcome(&BuckEvent::new(
+            SystemTime::now(),
+            trace.clone(),
+            Some(SpanId::next()),
+            None,
+            buck2_data::SpanEndEvent {
+                data: Some(buck2_data::span_end_event::Data::CacheUpload(Box::new(
+                    buck2_data::CacheUploadEnd {
+                        name,
+                        action_digest: "abc:1".to_owned(),
+                        success: false,
+                        ..Default::default()
+                    },
+                ))),
+                ..Default::default()
+            }
+            .into(),
+        ))
+        .unwrap();
+        assert_eq!(upload.kind(), Kind::Upload);
+        assert!(!upload.success);
+    }
 }
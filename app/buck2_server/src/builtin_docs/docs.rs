@@ -39,8 +39,10 @@ use buck2_server_ctx::template::ServerCommandTemplate;
 use dice::DiceComputations;
 use dice::DiceTransaction;
 use dupe::Dupe;
+use itertools::Itertools;
 use starlark::collections::SmallMap;
 use starlark::docs::get_registered_starlark_docs;
+use starlark::docs::markdown::render_doc_item;
 use starlark::docs::Doc;
 use starlark::docs::DocItem;
 use starlark::docs::DocMember;
@@ -58,6 +60,64 @@ use crate::builtin_docs::markdown::generate_markdown_files;
 enum DocsError {
     #[error("Unknown format requested (internal error)")]
     UnknownFormat,
+    #[error("No documentation found for symbol `{0}`{}", suggest_symbols(.1))]
+    UnknownSymbol(String, Vec<String>),
+}
+
+fn suggest_symbols(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\nDid you mean one of:\n{}",
+            suggestions.iter().map(|s| format!("  {}", s)).join("\n")
+        )
+    }
+}
+
+/// All the documented items by name, including the members of modules (which is how builtins
+/// such as `glob` are documented).
+fn named_doc_items(docs: Vec<Doc>) -> Vec<(String, DocItem)> {
+    let mut items = Vec::new();
+    for doc in docs {
+        if let DocItem::Module(module) = &doc.item {
+            items.extend(
+                module
+                    .members
+                    .iter()
+                    .map(|(name, member)| (name.clone(), member.clone().to_doc_item())),
+            );
+        }
+        items.push((doc.id.name, doc.item));
+    }
+    items
+}
+
+/// Render the documentation of every item named `symbol`, or fail with the closest names.
+fn render_symbol_docs(docs: Vec<Doc>, symbol: &str) -> anyhow::Result<String> {
+    const MAX_SUGGESTIONS: usize = 10;
+    const MAX_LEVENSHTEIN_DISTANCE: usize = 3;
+
+    let items = named_doc_items(docs);
+    let rendered: Vec<String> = items
+        .iter()
+        .filter(|(name, _)| name == symbol)
+        .map(|(name, item)| render_doc_item(name, item))
+        .collect();
+    if !rendered.is_empty() {
+        return Ok(rendered.join("\n\n"));
+    }
+
+    let suggestions = items
+        .iter()
+        .map(|(name, _)| (name, strsim::levenshtein(symbol, name)))
+        .filter(|(_, lev)| *lev <= MAX_LEVENSHTEIN_DISTANCE)
+        .sorted_by_key(|&(name, lev)| (lev, name.as_str()))
+        .map(|(name, _)| name.clone())
+        .dedup()
+        .take(MAX_SUGGESTIONS)
+        .collect();
+    Err(DocsError::UnknownSymbol(symbol.to_owned(), suggestions).into())
 }
 
 fn parse_import_paths(
@@ -311,6 +371,13 @@ async fn docs(
     let modules_docs = futures::future::try_join_all(module_calcs).await?;
     docs.extend(modules_docs.into_iter().flatten());
 
+    if let Some(symbol) = &request.symbol {
+        return Ok(UnstableDocsResponse {
+            json_output: None,
+            symbol_output: Some(render_symbol_docs(docs, symbol)?),
+        });
+    }
+
     let json_output = match format {
         Format::Json => Some(serde_json::to_string(&docs)?),
         Format::Markdown => {
@@ -324,5 +391,54 @@ async fn docs(
         }
     };
 
-    Ok(UnstableDocsResponse { json_output })
+    Ok(UnstableDocsResponse {
+        json_output,
+        symbol_output: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark::docs::DocFunction;
+
+    use super::*;
+
+    fn docs() -> Vec<Doc> {
+        let mut members = SmallMap::new();
+        members.insert(
+            "glob".to_owned(),
+            DocMember::Function(DocFunction::default()),
+        );
+        members.insert(
+            "genrule".to_owned(),
+            DocMember::Function(DocFunction::default()),
+        );
+        vec![
+            builtin_doc(
+                "globals",
+                "",
+                DocItem::Module(DocModule {
+                    docs: None,
+                    members,
+                }),
+            ),
+            builtin_doc("read_config", "", DocItem::Function(DocFunction::default())),
+        ]
+    }
+
+    #[test]
+    fn test_render_symbol_docs() {
+        assert!(render_symbol_docs(docs(), "glob").unwrap().contains("glob"));
+        assert!(render_symbol_docs(docs(), "read_config").is_ok());
+
+        let err = render_symbol_docs(docs(), "globb").unwrap_err();
+        assert_eq!(
+            "No documentation found for symbol `globb`\nDid you mean one of:\n  glob\n  globals",
+            format!("{:#}", err)
+        );
+        assert_eq!(
+            "No documentation found for symbol `zzz`",
+            format!("{:#}", render_symbol_docs(docs(), "zzz").unwrap_err())
+        );
+    }
 }
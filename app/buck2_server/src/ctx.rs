@@ -12,6 +12,8 @@ use std::collections::HashSet;
 use std::io::BufWriter;
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use allocative::Allocative;
@@ -23,6 +25,7 @@ use buck2_build_api::build::HasCreateUnhashedSymlinkLock;
 use buck2_build_api::build_signals::create_build_signals;
 use buck2_build_api::build_signals::BuildSignalsInstaller;
 use buck2_build_api::build_signals::SetBuildSignals;
+use buck2_build_api::bxl::calculation::SetBxlCacheGeneration;
 use buck2_build_api::context::SetBuildContextData;
 use buck2_build_api::keep_going::HasKeepGoing;
 use buck2_build_api::spawner::BuckSpawner;
@@ -47,6 +50,7 @@ use buck2_configured::calculation::ConfiguredGraphCycleDescriptor;
 use buck2_core::async_once_cell::AsyncOnceCell;
 use buck2_core::cells::CellResolver;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
+use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
 use buck2_core::facebook_only;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
@@ -67,15 +71,19 @@ use buck2_execute::execute::blocking::SetBlockingExecutor;
 use buck2_execute::execute::dice_data::set_fallback_executor_config;
 use buck2_execute::execute::dice_data::SetCommandExecutor;
 use buck2_execute::execute::dice_data::SetReClient;
+use buck2_execute::execute::strategy::parse_execution_strategy_overrides;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
+use buck2_execute::knobs::DEFAULT_LOCAL_RETRY_ATTEMPTS;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::materialize::materializer::SetMaterializer;
 use buck2_execute::re::client::RemoteExecutionClient;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute::re::manager::ReConnectionObserver;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCacheLimit;
 use buck2_execute_impl::executors::worker::WorkerPool;
 use buck2_execute_impl::low_pass_filter::LowPassFilter;
 use buck2_execute_impl::re::paranoid_download::ParanoidDownloader;
+use buck2_execute_impl::re::prefetch::LocallyConsumedOutputs;
 use buck2_file_watcher::file_watcher::FileWatcher;
 use buck2_file_watcher::mergebase::SetMergebase;
 use buck2_forkserver::client::ForkserverClient;
@@ -114,12 +122,14 @@ use more_futures::cancellation::ExplicitCancellationContext;
 use tokio::sync::Mutex;
 use tracing::warn;
 
+use crate::active_commands::active_commands;
 use crate::active_commands::ActiveCommandDropGuard;
 use crate::configs::get_legacy_config_args;
 use crate::configs::parse_legacy_cells;
 use crate::daemon::common::get_default_executor_config;
 use crate::daemon::common::parse_concurrency;
 use crate::daemon::common::CommandExecutorFactory;
+use crate::daemon::common::ResolvedLocalResources;
 use crate::daemon::state::DaemonStateData;
 use crate::dice_tracker::BuckDiceTracker;
 use crate::heartbeat_guard::HeartbeatGuard;
@@ -149,6 +159,10 @@ pub struct BaseServerCommandContext {
     pub spawner: Arc<BuckSpawner>,
 }
 
+/// Bumped by every command that passes `--no-bxl-cache`, so that bxl functions are evaluated again
+/// in that command and the fresh results are what later commands reuse.
+static BXL_CACHE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 /// ServerCommandContext provides access to the global daemon state and information about the calling client for
 /// the implementation of DaemonApi endpoints (ex. targets, query, build).
 pub struct ServerCommandContext<'a> {
@@ -182,6 +196,9 @@ pub struct ServerCommandContext<'a> {
 
     debugger_handle: Option<BuckStarlarkDebuggerHandle>,
 
+    /// Generation of cached bxl results to use for this command, bumped by `--no-bxl-cache`.
+    bxl_cache_generation: u64,
+
     record_target_call_stacks: bool,
     skip_targets_with_duplicate_names: bool,
     disable_starlark_types: bool,
@@ -220,6 +237,7 @@ impl<'a> ServerCommandContext<'a> {
         base_context: BaseServerCommandContext,
         client_context: &ClientContext,
         starlark_profiler_instrumentation_override: StarlarkProfilerConfiguration,
+        no_bxl_cache: bool,
         build_options: Option<&CommonBuildOptions>,
         paths: &InvocationPaths,
         snapshot_collector: SnapshotCollector,
@@ -308,6 +326,12 @@ impl<'a> ServerCommandContext<'a> {
 
         let debugger_handle = create_debugger_handle(base_context.events.dupe());
 
+        let bxl_cache_generation = if no_bxl_cache {
+            BXL_CACHE_GENERATION.fetch_add(1, Ordering::Relaxed) + 1
+        } else {
+            BXL_CACHE_GENERATION.load(Ordering::Relaxed)
+        };
+
         Ok(ServerCommandContext {
             base_context,
             working_dir: working_dir_project_relative.to_buf().into(),
@@ -332,6 +356,7 @@ impl<'a> ServerCommandContext<'a> {
             command_name: client_context.command_name.clone(),
             sanitized_argv: client_context.sanitized_argv.clone(),
             debugger_handle,
+            bxl_cache_generation,
             cancellations,
             exit_when_different_state: client_context.exit_when_different_state,
         })
@@ -381,7 +406,9 @@ impl<'a> ServerCommandContext<'a> {
             .map(|obj| parse_concurrency(obj.concurrency))
             .map(|v| v.map_err(buck2_error::Error::from));
 
-        let executor_config = get_default_executor_config(self.host_platform_override);
+        let executor_config = get_default_executor_config(self.host_platform_override)
+            .map(Arc::new)
+            .map_err(buck2_error::Error::from);
         let blocking_executor: Arc<_> = self.base_context.daemon.blocking_executor.dupe();
         let materializer = self.base_context.daemon.materializer.dupe();
         let re_connection = Arc::new(self.get_re_connection());
@@ -402,7 +429,7 @@ impl<'a> ServerCommandContext<'a> {
             execution_strategy,
             run_action_knobs,
             concurrency,
-            executor_config: Arc::new(executor_config),
+            executor_config,
             blocking_executor,
             materializer,
             re_connection,
@@ -412,6 +439,7 @@ impl<'a> ServerCommandContext<'a> {
             skip_cache_read,
             skip_cache_write,
             create_unhashed_symlink_lock,
+            locally_consumed_outputs: self.base_context.daemon.locally_consumed_outputs.dupe(),
             starlark_debugger: self.debugger_handle.dupe(),
             keep_going: self
                 .build_options
@@ -424,6 +452,26 @@ impl<'a> ServerCommandContext<'a> {
                 .build_options
                 .as_ref()
                 .map_or(false, |opts| opts.materialize_failed_inputs),
+            re_max_queue_time_ms_override: self
+                .build_options
+                .as_ref()
+                .map(|opts| opts.max_re_queue_time_ms)
+                .filter(|ms| *ms != 0),
+            re_use_case_override: self
+                .build_options
+                .as_ref()
+                .map(|opts| opts.re_use_case_override.as_str())
+                .filter(|use_case| !use_case.is_empty())
+                .map(|use_case| RemoteExecutorUseCase::new(use_case.to_owned())),
+            re_action_key_override: self
+                .build_options
+                .as_ref()
+                .map(|opts| opts.re_action_key_override.clone())
+                .filter(|key| !key.is_empty()),
+            local_resources: self
+                .build_options
+                .as_ref()
+                .and_then(|opts| opts.local_resources.clone()),
         }
     }
 
@@ -445,6 +493,7 @@ impl<'a> ServerCommandContext<'a> {
             starlark_profiler_instrumentation_override: self
                 .starlark_profiler_instrumentation_override
                 .dupe(),
+            bxl_cache_generation: self.bxl_cache_generation,
             disable_starlark_types: self.disable_starlark_types,
             unstable_typecheck: self.unstable_typecheck,
             skip_targets_with_duplicate_names: self.skip_targets_with_duplicate_names,
@@ -514,7 +563,7 @@ struct DiceCommandDataProvider {
     execution_strategy: ExecutionStrategy,
     events: EventDispatcher,
     concurrency: Option<Result<usize, buck2_error::Error>>,
-    executor_config: Arc<CommandExecutorConfig>,
+    executor_config: Result<Arc<CommandExecutorConfig>, buck2_error::Error>,
     blocking_executor: Arc<dyn BlockingExecutor>,
     materializer: Arc<dyn Materializer>,
     re_connection: Arc<ReConnectionHandle>,
@@ -525,12 +574,17 @@ struct DiceCommandDataProvider {
     skip_cache_read: bool,
     skip_cache_write: bool,
     create_unhashed_symlink_lock: Arc<Mutex<()>>,
+    locally_consumed_outputs: Arc<LocallyConsumedOutputs>,
     starlark_debugger: Option<BuckStarlarkDebuggerHandle>,
     keep_going: bool,
     http_client: HttpClient,
     paranoid: Option<ParanoidDownloader>,
     spawner: Arc<BuckSpawner>,
     materialize_failed_inputs: bool,
+    re_max_queue_time_ms_override: Option<u64>,
+    re_use_case_override: Option<RemoteExecutorUseCase>,
+    re_action_key_override: Option<String>,
+    local_resources: Option<buck2_cli_proto::LocalResources>,
 }
 
 #[async_trait]
@@ -576,13 +630,62 @@ impl DiceDataProvider for DiceCommandDataProvider {
             .parse::<u32>("build", "persistent_worker_shutdown_timeout_s")?
             .or(Some(10));
 
+        let local_retry_exit_codes = root_config
+            .parse_list::<i32>("build", "local_retry_exit_codes")?
+            .unwrap_or_default();
+
+        let local_retry_attempts = root_config
+            .parse::<u32>("build", "local_retry_attempts")?
+            .unwrap_or(DEFAULT_LOCAL_RETRY_ATTEMPTS);
+
+        // Outputs of remote actions up to this size are materialized in the background, if a local
+        // action consumed them in an earlier build.
+        let prefetch_remote_outputs_max_bytes =
+            root_config.parse::<u64>("build", "prefetch_remote_outputs_max_bytes")?;
+
+        // Results of local actions are stored in an on-disk cache in buck-out, and reused by
+        // later builds.
+        const DEFAULT_LOCAL_ACTION_CACHE_MAX_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+        let local_action_cache = if root_config
+            .parse::<bool>("build", "local_action_cache")?
+            .unwrap_or(false)
+        {
+            Some(LocalActionCacheLimit::new(
+                root_config
+                    .parse::<u64>("build", "local_action_cache_max_bytes")?
+                    .unwrap_or(DEFAULT_LOCAL_ACTION_CACHE_MAX_BYTES),
+            ))
+        } else {
+            None
+        };
+
+        // With `--local-first-then-remote`, actions with more input than this run remotely.
+        const DEFAULT_LOCAL_FIRST_MAX_INPUT_BYTES: u64 = 64 * 1024 * 1024;
+        let local_first_max_input_bytes = root_config
+            .parse::<u64>("build", "local_first_max_input_bytes")?
+            .unwrap_or(DEFAULT_LOCAL_FIRST_MAX_INPUT_BYTES);
+
+        let execution_strategy_overrides = parse_execution_strategy_overrides(
+            &root_config
+                .parse_list::<String>("build", "execution_strategy_overrides")?
+                .unwrap_or_default(),
+        )?;
+
         let executor_global_knobs = ExecutorGlobalKnobs {
             enable_miniperf,
             log_action_keys,
+            local_retry_exit_codes: local_retry_exit_codes.into(),
+            local_retry_attempts,
         };
 
+        let (local_resources, warnings) =
+            ResolvedLocalResources::resolve(self.local_resources.as_ref(), concurrency);
+        for warning in warnings {
+            self.events.console_message(warning);
+        }
+
         let host_sharing_broker =
-            HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, concurrency);
+            HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, local_resources.cpu);
 
         // We use the job count for the low pass filter too. The low pass filter prevents sending
         // RE-eligile tasks to local if their concurrency is higher than our threshold. While it
@@ -623,15 +726,16 @@ impl DiceDataProvider for DiceCommandDataProvider {
             .parse("buck2", "critical_path_backend2")?
             .unwrap_or(CriticalPathBackendName::Default);
 
-        set_fallback_executor_config(&mut data.data, self.executor_config.dupe());
+        set_fallback_executor_config(&mut data.data, self.executor_config.dupe()?);
         data.set_re_client(self.re_connection.get_client());
-        data.set_command_executor(Box::new(CommandExecutorFactory::new(
+        let command_executor_factory = CommandExecutorFactory::new(
             self.re_connection.dupe(),
             host_sharing_broker,
             low_pass_filter,
             self.materializer.dupe(),
             self.blocking_executor.dupe(),
             self.execution_strategy,
+            execution_strategy_overrides,
             executor_global_knobs,
             self.upload_all_actions,
             self.forkserver.dupe(),
@@ -644,7 +748,25 @@ impl DiceDataProvider for DiceCommandDataProvider {
             worker_pool,
             self.paranoid.dupe(),
             self.materialize_failed_inputs,
-        )));
+            prefetch_remote_outputs_max_bytes,
+            self.locally_consumed_outputs.dupe(),
+            local_action_cache,
+            local_first_max_input_bytes,
+            self.re_max_queue_time_ms_override,
+            self.re_use_case_override,
+            self.re_action_key_override.clone(),
+        );
+        // Make the usage of the low pass filter and of local resources visible to `buck2 status`
+        // while this command runs.
+        if let Some(command) = active_commands().get(self.events.trace_id()) {
+            let state = command.state();
+            state.set_low_pass_filter(command_executor_factory.low_pass_filter.dupe());
+            state.set_local_resources(
+                command_executor_factory.host_sharing_broker.dupe(),
+                local_resources.memory_bytes,
+            );
+        }
+        data.set_command_executor(Box::new(command_executor_factory));
         data.set_blocking_executor(self.blocking_executor.dupe());
         data.set_http_client(self.http_client.dupe());
         data.set_materializer(self.materializer.dupe());
@@ -686,6 +808,7 @@ struct DiceCommandUpdater {
     interpreter_architecture: InterpreterHostArchitecture,
     interpreter_xcode_version: Option<XcodeVersionInfo>,
     starlark_profiler_instrumentation_override: StarlarkProfilerConfiguration,
+    bxl_cache_generation: u64,
     disable_starlark_types: bool,
     unstable_typecheck: bool,
     record_target_call_stacks: bool,
@@ -735,6 +858,8 @@ impl DiceUpdater for DiceCommandUpdater {
             self.unstable_typecheck,
         )?;
 
+        ctx.set_bxl_cache_generation(self.bxl_cache_generation)?;
+
         Ok(ctx)
     }
 }
@@ -7,12 +7,17 @@
  * of this source tree.
  */
 
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::OnceLock;
 
 use anyhow::Context as _;
 use buck2_cli_proto::client_context::HostPlatformOverride;
 use buck2_cli_proto::common_build_options::ExecutionStrategy;
+use buck2_core::category::Category;
 use buck2_core::env_helper::EnvHelper;
 use buck2_core::execution_types::executor_config::CacheUploadBehavior;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
@@ -34,19 +39,31 @@ use buck2_execute::execute::prepared::NoOpCommandOptionalExecutor;
 use buck2_execute::execute::prepared::PreparedCommandExecutor;
 use buck2_execute::execute::prepared::PreparedCommandOptionalExecutor;
 use buck2_execute::execute::request::ExecutorPreference;
+use buck2_execute::execute::strategy::strategy_for_category;
+use buck2_execute::execute::strategy::ExecutionStrategyExt;
+use buck2_execute::execute::strategy::SelectedExecutor;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute_impl::executors::action_cache::ActionCacheChecker;
 use buck2_execute_impl::executors::action_cache::RemoteDepFileCacheChecker;
 use buck2_execute_impl::executors::caching::CacheUploader;
+use buck2_execute_impl::executors::caching::DryRunCacheUploader;
 use buck2_execute_impl::executors::hybrid::HybridExecutor;
+use buck2_execute_impl::executors::hybrid::LocalFallbackGraceWindow;
+use buck2_execute_impl::executors::hybrid::LocalFirstThenRemote;
 use buck2_execute_impl::executors::local::LocalExecutor;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCache;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCacheLimit;
+use buck2_execute_impl::executors::local_action_cache::LocalActionCacheWriter;
 use buck2_execute_impl::executors::re::ReExecutor;
 use buck2_execute_impl::executors::stacked::StackedExecutor;
 use buck2_execute_impl::executors::worker::WorkerPool;
 use buck2_execute_impl::low_pass_filter::LowPassFilter;
 use buck2_execute_impl::re::paranoid_download::ParanoidDownloader;
+use buck2_execute_impl::re::prefetch::LocallyConsumedOutputs;
+use buck2_execute_impl::re::prefetch::OutputPrefetcher;
+use buck2_execute_impl::re::retry::ReRetryPolicy;
 use buck2_forkserver::client::ForkserverClient;
 use dupe::Dupe;
 use host_sharing::HostSharingBroker;
@@ -57,12 +74,144 @@ pub fn parse_concurrency(requested: u32) -> anyhow::Result<usize> {
     let mut ret = requested.try_into().context("Invalid concurrency")?;
 
     if ret == 0 {
-        ret = num_cpus::get();
+        ret = default_concurrency();
     }
 
     Ok(ret)
 }
 
+/// The number of CPUs, clamped to the CPU quota of our cgroup if there is one, so that we don't
+/// oversubscribe in containers with CPU limits.
+fn default_concurrency() -> usize {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+    let quota = if cfg!(target_os = "linux") {
+        cgroup_cpu_quota(
+            read("/sys/fs/cgroup/cpu.max").as_deref(),
+            read("/sys/fs/cgroup/cpu/cpu.cfs_quota_us").as_deref(),
+            read("/sys/fs/cgroup/cpu/cpu.cfs_period_us").as_deref(),
+        )
+    } else {
+        None
+    };
+    clamp_to_cpu_quota(num_cpus::get(), quota)
+}
+
+fn clamp_to_cpu_quota(cpus: usize, quota: Option<usize>) -> usize {
+    match quota {
+        Some(quota) => cpus.min(quota).max(1),
+        None => cpus,
+    }
+}
+
+/// The CPU quota, rounded up to a whole number of CPUs, from the contents of cgroup v2
+/// `cpu.max` (`$MAX $PERIOD`, where `$MAX` is `max` when unlimited), or failing that cgroup v1
+/// `cpu.cfs_quota_us` (`-1` when unlimited) and `cpu.cfs_period_us`.
+fn cgroup_cpu_quota(
+    v2_cpu_max: Option<&str>,
+    v1_cfs_quota_us: Option<&str>,
+    v1_cfs_period_us: Option<&str>,
+) -> Option<usize> {
+    fn quota(quota: &str, period: &str) -> Option<usize> {
+        let quota: u64 = quota.trim().parse().ok()?;
+        let period: u64 = period.trim().parse().ok()?;
+        if quota == 0 || period == 0 {
+            return None;
+        }
+        usize::try_from((quota + period - 1) / period).ok()
+    }
+
+    if let Some(cpu_max) = v2_cpu_max {
+        let mut parts = cpu_max.split_whitespace();
+        return match (parts.next(), parts.next()) {
+            (Some(max), Some(period)) => quota(max, period),
+            _ => None,
+        };
+    }
+
+    quota(v1_cfs_quota_us?, v1_cfs_period_us?)
+}
+
+/// The resources available to the local actions of a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedLocalResources {
+    /// The number of permits of the host sharing broker.
+    pub cpu: usize,
+    /// Only reported by `buck2 status`, local actions are not limited by it.
+    pub memory_bytes: Option<u64>,
+}
+
+impl ResolvedLocalResources {
+    /// `--local-resources` can only narrow `concurrency` (from `-j` or `build.threads`, see
+    /// `parse_concurrency`), and asking for more than the machine has is clamped to what it has,
+    /// with a warning for each clamped resource.
+    pub fn resolve(
+        requested: Option<&buck2_cli_proto::LocalResources>,
+        concurrency: usize,
+    ) -> (ResolvedLocalResources, Vec<String>) {
+        Self::resolve_for_machine(requested, concurrency, default_concurrency(), system_memory)
+    }
+
+    fn resolve_for_machine(
+        requested: Option<&buck2_cli_proto::LocalResources>,
+        concurrency: usize,
+        machine_cpus: usize,
+        machine_memory_bytes: impl FnOnce() -> u64,
+    ) -> (ResolvedLocalResources, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut resources = ResolvedLocalResources {
+            cpu: concurrency,
+            memory_bytes: None,
+        };
+        let requested = match requested {
+            Some(requested) => requested,
+            None => return (resources, warnings),
+        };
+
+        // 0 means all of them, like for `parse_concurrency`.
+        if requested.cpu != 0 {
+            let mut cpu = requested.cpu as usize;
+            if cpu > machine_cpus {
+                warnings.push(format!(
+                    "Requested {} CPUs for local actions, but only {} are available, using {}",
+                    cpu, machine_cpus, machine_cpus
+                ));
+                cpu = machine_cpus;
+            }
+            resources.cpu = resources.cpu.min(cpu);
+        }
+
+        if requested.memory_bytes != 0 {
+            let machine_memory_bytes = machine_memory_bytes();
+            let mut memory_bytes = requested.memory_bytes;
+            if memory_bytes > machine_memory_bytes {
+                warnings.push(format!(
+                    "Requested {} of memory for local actions, but only {} is available, using {}",
+                    gib(memory_bytes),
+                    gib(machine_memory_bytes),
+                    gib(machine_memory_bytes)
+                ));
+                memory_bytes = machine_memory_bytes;
+            }
+            resources.memory_bytes = Some(memory_bytes);
+        }
+
+        (resources, warnings)
+    }
+}
+
+fn gib(bytes: u64) -> String {
+    format!("{:.1} GiB", bytes as f64 / (1u64 << 30) as f64)
+}
+
+fn system_memory() -> u64 {
+    use sysinfo::RefreshKind;
+    use sysinfo::System;
+    use sysinfo::SystemExt;
+
+    let system = System::new_with_specifics(RefreshKind::new().with_memory());
+    system.total_memory()
+}
+
 /// For each buck invocations, we'll have a single CommandExecutorFactory. This contains shared
 /// state used by all command executor strategies.
 pub struct CommandExecutorFactory {
@@ -76,6 +225,8 @@ pub struct CommandExecutorFactory {
     pub materializer: Arc<dyn Materializer>,
     pub blocking_executor: Arc<dyn BlockingExecutor>,
     pub strategy: ExecutionStrategy,
+    /// Strategies which override `strategy` for actions of a given category.
+    pub category_strategies: HashMap<String, ExecutionStrategy>,
     pub executor_global_knobs: ExecutorGlobalKnobs,
     pub upload_all_actions: bool,
     pub forkserver: Option<ForkserverClient>,
@@ -85,6 +236,21 @@ pub struct CommandExecutorFactory {
     worker_pool: Arc<WorkerPool>,
     paranoid: Option<ParanoidDownloader>,
     materialize_failed_inputs: bool,
+    /// Whether we told the user that `materialize_failed_inputs` does nothing for local actions.
+    warned_materialize_failed_inputs_local: AtomicBool,
+    output_prefetcher: Option<Arc<OutputPrefetcher>>,
+    /// Whether local execution is fronted by the on-disk local action cache, and its size limit.
+    local_action_cache: Option<LocalActionCacheLimit>,
+    /// With the `LocalFirstThenRemote` strategy, actions with more input than this run remotely.
+    local_first_max_input_bytes: u64,
+    /// Takes precedence over the `re_max_queue_time_ms` of the executor config when set.
+    re_max_queue_time_ms_override: Option<u64>,
+    /// Takes precedence over the `re_use_case` of the executor config when set.
+    re_use_case_override: Option<RemoteExecutorUseCase>,
+    /// Takes precedence over the `re_action_key` of the executor config when set.
+    re_action_key_override: Option<String>,
+    /// Consecutive remote execution errors, for `RemoteOnlyWithLocalFallback`.
+    consecutive_remote_failures: Arc<AtomicU32>,
 }
 
 impl CommandExecutorFactory {
@@ -95,6 +261,7 @@ impl CommandExecutorFactory {
         materializer: Arc<dyn Materializer>,
         blocking_executor: Arc<dyn BlockingExecutor>,
         strategy: ExecutionStrategy,
+        category_strategies: HashMap<String, ExecutionStrategy>,
         executor_global_knobs: ExecutorGlobalKnobs,
         upload_all_actions: bool,
         forkserver: Option<ForkserverClient>,
@@ -104,7 +271,27 @@ impl CommandExecutorFactory {
         worker_pool: Arc<WorkerPool>,
         paranoid: Option<ParanoidDownloader>,
         materialize_failed_inputs: bool,
+        prefetch_remote_outputs_max_bytes: Option<u64>,
+        locally_consumed_outputs: Arc<LocallyConsumedOutputs>,
+        local_action_cache: Option<LocalActionCacheLimit>,
+        local_first_max_input_bytes: u64,
+        re_max_queue_time_ms_override: Option<u64>,
+        re_use_case_override: Option<RemoteExecutorUseCase>,
+        re_action_key_override: Option<String>,
     ) -> Self {
+        // Concurrent prefetches beyond this are skipped, so that prefetching can't saturate the
+        // network at the expense of downloads that are actually blocking the build.
+        const MAX_CONCURRENT_PREFETCHES: usize = 8;
+
+        let output_prefetcher = prefetch_remote_outputs_max_bytes.map(|max_bytes| {
+            Arc::new(OutputPrefetcher::new(
+                materializer.dupe(),
+                locally_consumed_outputs,
+                max_bytes,
+                MAX_CONCURRENT_PREFETCHES,
+            ))
+        });
+
         Self {
             re_connection,
             host_sharing_broker: Arc::new(host_sharing_broker),
@@ -112,6 +299,7 @@ impl CommandExecutorFactory {
             materializer,
             blocking_executor,
             strategy,
+            category_strategies,
             executor_global_knobs,
             upload_all_actions,
             forkserver,
@@ -121,6 +309,14 @@ impl CommandExecutorFactory {
             worker_pool,
             paranoid,
             materialize_failed_inputs,
+            warned_materialize_failed_inputs_local: AtomicBool::new(false),
+            output_prefetcher,
+            local_action_cache,
+            local_first_max_input_bytes,
+            re_max_queue_time_ms_override,
+            re_use_case_override,
+            re_action_key_override,
+            consecutive_remote_failures: Arc::new(AtomicU32::new(0)),
         }
     }
 }
@@ -130,10 +326,31 @@ impl HasCommandExecutor for CommandExecutorFactory {
         &self,
         artifact_fs: &ArtifactFs,
         executor_config: &CommandExecutorConfig,
+        category: Option<&Category>,
     ) -> anyhow::Result<CommandExecutorResponse> {
+        let strategy = strategy_for_category(
+            self.strategy,
+            &self.category_strategies,
+            category.map(|c| c.as_str()),
+        );
+        let selected = SelectedExecutor::select(&executor_config.executor, strategy);
+
+        if self.materialize_failed_inputs
+            && runs_only_locally(selected, strategy)
+            && !self
+                .warned_materialize_failed_inputs_local
+                .swap(true, Ordering::Relaxed)
+        {
+            tracing::warn!(
+                "`--materialize-failed-inputs` has no effect on actions that run locally, their inputs are already on disk"
+            );
+        }
+
         // 30GB is the max RE can currently support.
         const DEFAULT_RE_MAX_INPUT_FILE_BYTES: u64 = 30 * 1024 * 1024 * 1024;
 
+        const DEFAULT_LOCAL_FALLBACK_AFTER_FAILURES: u32 = 3;
+
         let local_executor_new = |options: &LocalExecutorOptions| {
             let worker_pool = if options.use_persistent_workers {
                 Some(self.worker_pool.dupe())
@@ -149,24 +366,58 @@ impl HasCommandExecutor for CommandExecutorFactory {
                 self.forkserver.dupe(),
                 self.executor_global_knobs.dupe(),
                 worker_pool,
+                self.output_prefetcher.dupe(),
             )
         };
 
+        // Local executors for `Executor::Local`, which get the local action cache in front of
+        // them when it is enabled.
+        let local_only_executor_new =
+            |options: &LocalExecutorOptions| -> Arc<dyn PreparedCommandExecutor> {
+                let local = local_executor_new(options);
+                let limit = match &self.local_action_cache {
+                    Some(limit) => limit.dupe(),
+                    None => return Arc::new(local),
+                };
+                let cache = LocalActionCache::new(
+                    artifact_fs.clone(),
+                    self.materializer.dupe(),
+                    self.blocking_executor.dupe(),
+                    limit,
+                );
+                Arc::new(StackedExecutor {
+                    optional: cache.clone(),
+                    fallback: LocalActionCacheWriter {
+                        cache,
+                        inner: local,
+                    },
+                })
+            };
+
         if !buck2_core::is_open_source() && !cfg!(fbcode_build) {
+            // Builds that never expect remote execution can set this to only log at debug level.
+            static SUPPRESS_WARNING: EnvHelper<bool> =
+                EnvHelper::new("BUCK2_SUPPRESS_CARGO_RE_WARNING");
+            let suppress_warning = SUPPRESS_WARNING.get_copied()?.unwrap_or(false);
+
             static WARN: OnceLock<()> = OnceLock::new();
             WARN.get_or_init(|| {
-                tracing::warn!("Cargo build detected: disabling remote execution and caching!")
+                log_cargo_build_detected(
+                    suppress_warning,
+                    |msg| tracing::warn!("{}", msg),
+                    |msg| tracing::debug!("{}", msg),
+                )
             });
 
-            if self.strategy.ban_local() {
+            if strategy.ban_local() {
                 return Err(anyhow::anyhow!(
                     "The desired execution strategy (`{:?}`) is incompatible with the local executor",
-                    self.strategy,
+                    strategy,
                 ));
             }
 
             return Ok(CommandExecutorResponse {
-                executor: Arc::new(local_executor_new(&LocalExecutorOptions::default())),
+                executor: local_only_executor_new(&LocalExecutorOptions::default()),
                 platform: Default::default(),
                 cache_checker: Arc::new(NoOpCommandOptionalExecutor {}),
                 cache_uploader: Arc::new(NoOpCacheUploader {}),
@@ -183,22 +434,38 @@ impl HasCommandExecutor for CommandExecutorFactory {
                 re_client: self.re_connection.get_client(),
                 re_use_case: *re_use_case,
                 re_action_key: re_action_key.clone(),
-                re_max_queue_time_ms: options.re_max_queue_time_ms,
+                re_max_queue_time_ms: self
+                    .re_max_queue_time_ms_override
+                    .or(options.re_max_queue_time_ms),
+                re_retry_policy: ReRetryPolicy::from_options(options),
                 knobs: self.executor_global_knobs.dupe(),
                 skip_cache_read: self.skip_cache_read || !remote_cache_enabled,
                 skip_cache_write: self.skip_cache_write || !remote_cache_enabled,
                 paranoid: self.paranoid.dupe(),
                 materialize_failed_inputs: self.materialize_failed_inputs,
+                prefetcher: self.output_prefetcher.dupe(),
             }
         };
 
+        let local_fallback_new = |options: &RemoteExecutorOptions| {
+            if strategy != ExecutionStrategy::RemoteOnlyWithLocalFallback {
+                return None;
+            }
+            Some(LocalFallbackGraceWindow::new(
+                options
+                    .local_fallback_after_failures
+                    .unwrap_or(DEFAULT_LOCAL_FALLBACK_AFTER_FAILURES),
+                self.consecutive_remote_failures.dupe(),
+            ))
+        };
+
         let response = match &executor_config.executor {
             Executor::Local(local) => {
-                if self.strategy.ban_local() {
+                if selected.is_none() {
                     None
                 } else {
                     Some(CommandExecutorResponse {
-                        executor: Arc::new(local_executor_new(local)),
+                        executor: local_only_executor_new(local),
                         platform: Default::default(),
                         cache_checker: Arc::new(NoOpCommandOptionalExecutor {}),
                         cache_uploader: Arc::new(NoOpCacheUploader {}),
@@ -214,6 +481,10 @@ impl HasCommandExecutor for CommandExecutorFactory {
                 remote_cache_enabled,
                 remote_dep_file_cache_enabled,
             } => {
+                let re_use_case = self.re_use_case_override.as_ref().unwrap_or(re_use_case);
+                let re_action_key =
+                    &resolve_re_action_key(self.re_action_key_override.as_deref(), re_action_key);
+
                 // NOTE: While we now have a legit flag for this, we keep the env var. This has been used
                 // in remediating prod incidents in the past, and this is the kind of thing that can easily
                 // become tribal knowledge. Keeping this does not hurt us.
@@ -273,25 +544,51 @@ impl HasCommandExecutor for CommandExecutorFactory {
                 };
 
                 let executor: Option<Arc<dyn PreparedCommandExecutor>> = match &executor {
-                    RemoteEnabledExecutor::Local(local) if !self.strategy.ban_local() => {
+                    RemoteEnabledExecutor::Local(local)
+                        if selected == Some(SelectedExecutor::Local) =>
+                    {
                         Some(Arc::new(local_executor_new(local)))
                     }
-                    RemoteEnabledExecutor::Remote(remote) if !self.strategy.ban_remote() => {
-                        Some(Arc::new(remote_executor_new(
+                    RemoteEnabledExecutor::Remote(remote) if selected.is_some() => {
+                        let re_executor = remote_executor_new(
                             remote,
                             re_use_case,
                             re_action_key,
                             *remote_cache_enabled,
-                        )))
+                        );
+                        match (selected, local_fallback_new(remote)) {
+                            // Local execution isn't configured for this platform, but the
+                            // strategy asks to fall back to it anyway, so use a hybrid executor
+                            // that only runs locally once RE is failing.
+                            (
+                                Some(SelectedExecutor::RemoteWithLocalFallback),
+                                Some(local_fallback),
+                            ) => Some(Arc::new(HybridExecutor {
+                                local: local_executor_new(&remote.local_fallback_options),
+                                remote: re_executor,
+                                level: HybridExecutionLevel::Limited,
+                                executor_preference: ExecutorPreference::for_hybrid_executor(
+                                    strategy, false,
+                                )?,
+                                re_max_input_files_bytes: remote
+                                    .re_max_input_files_bytes
+                                    .unwrap_or(DEFAULT_RE_MAX_INPUT_FILE_BYTES),
+                                low_pass_filter: self.low_pass_filter.dupe(),
+                                local_fallback: Some(local_fallback),
+                                local_first: None,
+                            })),
+                            _ => Some(Arc::new(re_executor)),
+                        }
                     }
                     RemoteEnabledExecutor::Hybrid {
                         local,
                         remote,
                         level,
-                    } if !self.strategy.ban_hybrid() => {
+                    } if selected == Some(SelectedExecutor::Hybrid) => {
                         let re_max_input_files_bytes = remote
                             .re_max_input_files_bytes
                             .unwrap_or(DEFAULT_RE_MAX_INPUT_FILE_BYTES);
+                        let local_fallback = local_fallback_new(remote);
                         let local = local_executor_new(local);
                         let remote = remote_executor_new(
                             remote,
@@ -299,13 +596,20 @@ impl HasCommandExecutor for CommandExecutorFactory {
                             re_action_key,
                             *remote_cache_enabled,
                         );
-                        let executor_preference = self.strategy.hybrid_preference();
+                        let executor_preference = ExecutorPreference::for_hybrid_executor(
+                            strategy,
+                            self.paranoid.is_some(),
+                        )?;
                         let low_pass_filter = self.low_pass_filter.dupe();
+                        // Actions with more input than `re_max_input_files_bytes` still run
+                        // locally, since RE would reject them.
+                        let local_first = if strategy == ExecutionStrategy::LocalFirstThenRemote {
+                            Some(LocalFirstThenRemote::new(self.local_first_max_input_bytes))
+                        } else {
+                            None
+                        };
 
                         if self.paranoid.is_some() {
-                            let executor_preference = executor_preference
-                                .and(ExecutorPreference::DefaultErasePreferences)?;
-
                             Some(Arc::new(HybridExecutor {
                                 local,
                                 remote: StackedExecutor {
@@ -319,6 +623,9 @@ impl HasCommandExecutor for CommandExecutorFactory {
                                 executor_preference,
                                 re_max_input_files_bytes,
                                 low_pass_filter,
+                                local_fallback,
+                                // Paranoid mode runs actions on both executors.
+                                local_first: None,
                             }))
                         } else {
                             Some(Arc::new(HybridExecutor {
@@ -328,6 +635,8 @@ impl HasCommandExecutor for CommandExecutorFactory {
                                 executor_preference,
                                 re_max_input_files_bytes,
                                 low_pass_filter,
+                                local_fallback,
+                                local_first,
                             }))
                         }
                     }
@@ -350,19 +659,23 @@ impl HasCommandExecutor for CommandExecutorFactory {
                         .collect(),
                 };
 
-                let cache_uploader = if disable_caching {
-                    Arc::new(NoOpCacheUploader {}) as _
-                } else if let CacheUploadBehavior::Enabled { max_bytes } = cache_upload_behavior {
-                    Arc::new(CacheUploader::new(
+                let cache_uploader = match effective_cache_upload_behavior(
+                    cache_upload_behavior,
+                    disable_caching,
+                    self.skip_cache_write,
+                ) {
+                    CacheUploadBehavior::Enabled { max_bytes } => Arc::new(CacheUploader::new(
                         artifact_fs.clone(),
                         self.materializer.dupe(),
                         self.re_connection.get_client(),
                         *re_use_case,
                         platform.clone(),
-                        *max_bytes,
-                    )) as _
-                } else {
-                    Arc::new(NoOpCacheUploader {}) as _
+                        max_bytes,
+                    )) as _,
+                    CacheUploadBehavior::DryRun { max_bytes } => {
+                        Arc::new(DryRunCacheUploader::new(max_bytes)) as _
+                    }
+                    CacheUploadBehavior::Disabled => Arc::new(NoOpCacheUploader {}) as _,
                 };
 
                 executor.map(|executor| CommandExecutorResponse {
@@ -377,54 +690,54 @@ impl HasCommandExecutor for CommandExecutorFactory {
         let response = response
             .with_context(|| format!(
 "The desired execution strategy (`{:?}`) is incompatible with the executor config that was selected: {:?}",
-self.strategy, executor_config))?;
+strategy, executor_config))?;
 
         Ok(response)
     }
 }
 
-trait ExecutionStrategyExt {
-    fn ban_local(&self) -> bool;
-    fn ban_remote(&self) -> bool;
-    fn ban_hybrid(&self) -> bool;
-    fn hybrid_preference(&self) -> ExecutorPreference;
-}
-
-impl ExecutionStrategyExt for ExecutionStrategy {
-    fn ban_local(&self) -> bool {
-        match self {
-            Self::RemoteOnly | Self::NoExecution => true,
-            _ => false,
-        }
-    }
-
-    fn ban_remote(&self) -> bool {
-        match self {
-            Self::LocalOnly | Self::NoExecution => true,
-            _ => false,
-        }
+/// Whether to upload the results of local actions to the cache (or only pretend to, for a dry
+/// run) and, if so, the maximum size of outputs to upload. Uploads are disabled along with
+/// caching, but also on their own when cache writes are skipped (e.g. `--no-remote-cache-upload`),
+/// in which case the cache is still read.
+fn effective_cache_upload_behavior(
+    behavior: &CacheUploadBehavior,
+    disable_caching: bool,
+    skip_cache_write: bool,
+) -> CacheUploadBehavior {
+    if disable_caching || skip_cache_write {
+        CacheUploadBehavior::Disabled
+    } else {
+        *behavior
     }
+}
 
-    fn ban_hybrid(&self) -> bool {
-        match self {
-            Self::NoExecution => true,
-            _ => false,
-        }
+/// Whether every action run by the selected executor runs locally: with a local executor, whether
+/// or not the config enables remote execution, or with a hybrid executor restricted to local
+/// execution.
+fn runs_only_locally(selected: Option<SelectedExecutor>, strategy: ExecutionStrategy) -> bool {
+    match selected {
+        Some(SelectedExecutor::Local) => true,
+        Some(SelectedExecutor::Hybrid) => strategy == ExecutionStrategy::LocalOnly,
+        Some(SelectedExecutor::Remote | SelectedExecutor::RemoteWithLocalFallback) | None => false,
     }
+}
 
-    fn hybrid_preference(&self) -> ExecutorPreference {
-        match self {
-            Self::HybridPreferLocal => ExecutorPreference::LocalPreferred,
-            Self::HybridPreferRemote => ExecutorPreference::RemotePreferred,
-            Self::LocalOnly => ExecutorPreference::LocalRequired,
-            Self::RemoteOnly => ExecutorPreference::RemoteRequired,
-            _ => ExecutorPreference::Default,
-        }
-    }
+/// The action key to attach to remote actions and cache lookups: the one passed on the command
+/// line if any, otherwise the one from the executor configuration.
+fn resolve_re_action_key(
+    override_key: Option<&str>,
+    configured_key: &Option<String>,
+) -> Option<String> {
+    override_key
+        .map(ToOwned::to_owned)
+        .or_else(|| configured_key.clone())
 }
 
 /// This is used when execution platforms are not configured.
-pub fn get_default_executor_config(host_platform: HostPlatformOverride) -> CommandExecutorConfig {
+pub fn get_default_executor_config(
+    host_platform: HostPlatformOverride,
+) -> anyhow::Result<CommandExecutorConfig> {
     let executor = if buck2_core::is_open_source() {
         Executor::Local(LocalExecutorOptions::default())
     } else {
@@ -434,7 +747,7 @@ pub fn get_default_executor_config(host_platform: HostPlatformOverride) -> Comma
                 remote: RemoteExecutorOptions::default(),
                 level: HybridExecutionLevel::Limited,
             },
-            re_properties: get_default_re_properties(host_platform),
+            re_properties: get_default_re_properties(host_platform)?,
             re_use_case: RemoteExecutorUseCase::buck2_default(),
             re_action_key: None,
             cache_upload_behavior: CacheUploadBehavior::Disabled,
@@ -443,30 +756,82 @@ pub fn get_default_executor_config(host_platform: HostPlatformOverride) -> Comma
         }
     };
 
-    CommandExecutorConfig {
+    Ok(CommandExecutorConfig {
         executor,
         options: CommandGenerationOptions {
             path_separator: get_default_path_separator(host_platform),
             output_paths_behavior: Default::default(),
         },
+    })
+}
+
+fn log_cargo_build_detected(
+    suppress_warning: bool,
+    warn: impl FnOnce(&str),
+    debug: impl FnOnce(&str),
+) {
+    let msg = "Cargo build detected: disabling remote execution and caching!";
+    if suppress_warning {
+        debug(msg)
+    } else {
+        warn(msg)
     }
 }
 
-fn get_default_re_properties(host_platform: HostPlatformOverride) -> SortedMap<String, String> {
+/// Remote execution properties to use instead of the defaults for the host, as comma-separated
+/// `name=value` pairs, e.g. `platform=freebsd,pool=large`.
+static DEFAULT_RE_PROPERTIES: EnvHelper<SortedMap<String, String>> =
+    EnvHelper::with_converter("BUCK2_DEFAULT_RE_PROPERTIES", parse_re_properties);
+
+fn parse_re_properties(s: &str) -> anyhow::Result<SortedMap<String, String>> {
+    s.split(',')
+        .map(|property| match property.split_once('=') {
+            Some((name, value)) if !name.is_empty() => Ok((name.to_owned(), value.to_owned())),
+            _ => Err(anyhow::anyhow!(
+                "Invalid remote execution property `{}`, expected `name=value`",
+                property
+            )),
+        })
+        .collect()
+}
+
+fn get_default_re_properties(
+    host_platform: HostPlatformOverride,
+) -> anyhow::Result<SortedMap<String, String>> {
+    if let Some(properties) = DEFAULT_RE_PROPERTIES.get()? {
+        return Ok(properties.clone());
+    }
+    let os = match host_platform {
+        HostPlatformOverride::Linux => "linux",
+        HostPlatformOverride::MacOs => "macos",
+        HostPlatformOverride::Windows => "windows",
+        HostPlatformOverride::DefaultPlatform => std::env::consts::OS,
+    };
+    Ok(get_default_re_properties_for_os(os))
+}
+
+fn get_default_re_properties_for_os(os: &str) -> SortedMap<String, String> {
     let linux = &[("platform", "linux-remote-execution")];
     let macos = &[("platform", "mac"), ("subplatform", "any")];
     let windows = &[("platform", "windows")];
 
-    let props = match host_platform {
-        HostPlatformOverride::Linux => linux.as_slice(),
-        HostPlatformOverride::MacOs => macos.as_slice(),
-        HostPlatformOverride::Windows => windows.as_slice(),
-        HostPlatformOverride::DefaultPlatform => match std::env::consts::OS {
-            "linux" => linux.as_slice(),
-            "macos" => macos.as_slice(),
-            "windows" => windows.as_slice(),
-            v => unimplemented!("no support yet for operating system `{}`", v),
-        },
+    let props = match os {
+        "linux" => linux.as_slice(),
+        "macos" => macos.as_slice(),
+        "windows" => windows.as_slice(),
+        v => {
+            // E.g. FreeBSD. Executing remotely on Linux is the most likely to work, and
+            // execution platforms or `BUCK2_DEFAULT_RE_PROPERTIES` can set different properties.
+            static WARN: OnceLock<()> = OnceLock::new();
+            WARN.get_or_init(|| {
+                tracing::warn!(
+                    "No default remote execution platform for operating system `{}`, using Linux \
+                    (set BUCK2_DEFAULT_RE_PROPERTIES to override)",
+                    v
+                )
+            });
+            linux.as_slice()
+        }
     };
 
     props
@@ -483,3 +848,351 @@ fn get_default_path_separator(host_platform: HostPlatformOverride) -> PathSepara
         HostPlatformOverride::DefaultPlatform => PathSeparatorKind::system_default(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::name::CellName;
+    use buck2_core::cells::CellResolver;
+    use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+    use buck2_core::fs::project::ProjectRootTemp;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+    use buck2_execute::digest_config::DigestConfig;
+    use buck2_execute::execute::action_digest_and_blobs::ActionDigestAndBlobsBuilder;
+    use buck2_execute::execute::prepared::PreparedAction;
+    use buck2_execute::execute::prepared::PreparedCommand;
+    use buck2_execute::execute::request::CommandExecutionPaths;
+    use buck2_execute::execute::request::CommandExecutionRequest;
+    use buck2_execute::execute::target::CommandExecutionTarget;
+    use buck2_execute::materialize::nodisk::NoDiskMaterializer;
+    use buck2_execute::re::manager::ManagedRemoteExecutionClient;
+
+    use super::*;
+
+    #[test]
+    fn test_effective_cache_upload_behavior() {
+        let enabled = CacheUploadBehavior::Enabled {
+            max_bytes: Some(100),
+        };
+        assert_eq!(
+            effective_cache_upload_behavior(&enabled, false, false),
+            enabled
+        );
+        // `--no-remote-cache-upload`: only uploads are disabled, `disable_caching` (which
+        // disables the cache checker) is not set.
+        assert_eq!(
+            effective_cache_upload_behavior(&enabled, false, true),
+            CacheUploadBehavior::Disabled
+        );
+        assert_eq!(
+            effective_cache_upload_behavior(&enabled, true, false),
+            CacheUploadBehavior::Disabled
+        );
+        assert_eq!(
+            effective_cache_upload_behavior(&CacheUploadBehavior::Disabled, false, false),
+            CacheUploadBehavior::Disabled
+        );
+
+        // A dry run happens wherever real uploads would.
+        let dry_run = CacheUploadBehavior::DryRun {
+            max_bytes: Some(100),
+        };
+        assert_eq!(
+            effective_cache_upload_behavior(&dry_run, false, false),
+            dry_run
+        );
+        assert_eq!(
+            effective_cache_upload_behavior(&dry_run, false, true),
+            CacheUploadBehavior::Disabled
+        );
+    }
+
+    #[test]
+    fn test_runs_only_locally() {
+        let remote_enabled = |executor| Executor::RemoteEnabled {
+            executor,
+            re_properties: Default::default(),
+            re_use_case: RemoteExecutorUseCase::buck2_default(),
+            re_action_key: None,
+            cache_upload_behavior: CacheUploadBehavior::Disabled,
+            remote_cache_enabled: true,
+            remote_dep_file_cache_enabled: false,
+        };
+        let local = Executor::Local(LocalExecutorOptions::default());
+        let remote_enabled_local =
+            remote_enabled(RemoteEnabledExecutor::Local(LocalExecutorOptions::default()));
+        let remote = remote_enabled(RemoteEnabledExecutor::Remote(
+            RemoteExecutorOptions::default(),
+        ));
+        let hybrid = remote_enabled(RemoteEnabledExecutor::Hybrid {
+            local: LocalExecutorOptions::default(),
+            remote: RemoteExecutorOptions::default(),
+            level: HybridExecutionLevel::Limited,
+        });
+        let runs_only_locally = |executor: &Executor, strategy| {
+            runs_only_locally(SelectedExecutor::select(executor, strategy), strategy)
+        };
+
+        assert!(runs_only_locally(&local, ExecutionStrategy::Default));
+        assert!(runs_only_locally(
+            &remote_enabled_local,
+            ExecutionStrategy::Default
+        ));
+        assert!(runs_only_locally(&hybrid, ExecutionStrategy::LocalOnly));
+
+        assert!(!runs_only_locally(&hybrid, ExecutionStrategy::Default));
+        assert!(!runs_only_locally(
+            &hybrid,
+            ExecutionStrategy::HybridPreferLocal
+        ));
+        assert!(!runs_only_locally(&remote, ExecutionStrategy::Default));
+        assert!(!runs_only_locally(
+            &remote,
+            ExecutionStrategy::RemoteOnlyWithLocalFallback
+        ));
+        // Nothing runs at all.
+        assert!(!runs_only_locally(&local, ExecutionStrategy::RemoteOnly));
+    }
+
+    #[test]
+    fn test_resolve_re_action_key() {
+        assert_eq!(None, resolve_re_action_key(None, &None));
+        assert_eq!(
+            Some("configured".to_owned()),
+            resolve_re_action_key(None, &Some("configured".to_owned()))
+        );
+        assert_eq!(
+            Some("ci-nightly".to_owned()),
+            resolve_re_action_key(Some("ci-nightly"), &Some("configured".to_owned()))
+        );
+        assert_eq!(
+            Some("ci-nightly".to_owned()),
+            resolve_re_action_key(Some("ci-nightly"), &None)
+        );
+    }
+
+    #[derive(Debug)]
+    struct Target;
+
+    impl CommandExecutionTarget for Target {
+        fn re_action_key(&self) -> String {
+            "cell//pkg:foo genrule".to_owned()
+        }
+
+        fn re_affinity_key(&self) -> String {
+            "cell//pkg:foo".to_owned()
+        }
+
+        fn as_proto_action_key(&self) -> buck2_data::ActionKey {
+            buck2_data::ActionKey::default()
+        }
+
+        fn as_proto_action_name(&self) -> buck2_data::ActionName {
+            buck2_data::ActionName {
+                category: "genrule".to_owned(),
+                identifier: "foo".to_owned(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_re_action_key_override_is_sent_by_executor_and_cache_checker() -> anyhow::Result<()> {
+        let temp = ProjectRootTemp::new()?;
+        let artifact_fs = ArtifactFs::new(
+            CellResolver::testing_with_name_and_path(
+                CellName::testing_new("cell"),
+                CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new("cell_path".into())),
+            ),
+            BuckOutPathResolver::new(ProjectRelativePathBuf::unchecked_new("buck-out/v2".into())),
+            temp.path().dupe(),
+        );
+        let digest_config = DigestConfig::testing_default();
+        let request = CommandExecutionRequest::new(
+            Vec::new(),
+            vec!["true".to_owned()],
+            CommandExecutionPaths::new(
+                Vec::new(),
+                Default::default(),
+                &artifact_fs,
+                digest_config,
+            )?,
+            Default::default(),
+        );
+        let prepared_action = PreparedAction {
+            action_and_blobs: ActionDigestAndBlobsBuilder::new(digest_config)
+                .build(&RE::Action::default()),
+            platform: RE::Platform::default(),
+        };
+        let command = PreparedCommand {
+            request: &request,
+            target: &Target,
+            prepared_action: &prepared_action,
+            digest_config,
+        };
+
+        let re_action_key =
+            resolve_re_action_key(Some("ci-nightly"), &Some("configured".to_owned()));
+        let re_executor = ReExecutor {
+            artifact_fs: artifact_fs.clone(),
+            project_fs: temp.path().dupe(),
+            materializer: Arc::new(NoDiskMaterializer),
+            re_client: ManagedRemoteExecutionClient::testing_new_dummy(),
+            re_use_case: RemoteExecutorUseCase::buck2_default(),
+            re_action_key: re_action_key.clone(),
+            knobs: ExecutorGlobalKnobs::default(),
+            skip_cache_read: false,
+            skip_cache_write: false,
+            re_max_queue_time_ms: None,
+            re_retry_policy: ReRetryPolicy::default(),
+            paranoid: None,
+            materialize_failed_inputs: false,
+            prefetcher: None,
+        };
+        let cache_checker = ActionCacheChecker {
+            artifact_fs,
+            materializer: Arc::new(NoDiskMaterializer),
+            re_client: ManagedRemoteExecutionClient::testing_new_dummy(),
+            re_use_case: RemoteExecutorUseCase::buck2_default(),
+            re_action_key,
+            upload_all_actions: false,
+            knobs: ExecutorGlobalKnobs::default(),
+            paranoid: None,
+            remote_dep_file_checker: Arc::new(NoOpCommandOptionalExecutor {}),
+        };
+
+        // The override replaces the configured key, and is prepended to the action's own key.
+        assert_eq!(
+            "ci-nightly cell//pkg:foo genrule",
+            re_executor.re_action_identity(&command).action_key
+        );
+        assert_eq!(
+            "ci-nightly cell//pkg:foo genrule",
+            cache_checker.re_action_identity(&command).action_key
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cgroup_cpu_quota() {
+        // cgroup v2.
+        assert_eq!(
+            cgroup_cpu_quota(Some("200000 100000\n"), None, None),
+            Some(2)
+        );
+        assert_eq!(
+            cgroup_cpu_quota(Some("150000 100000\n"), None, None),
+            Some(2)
+        );
+        assert_eq!(
+            cgroup_cpu_quota(Some("50000 100000\n"), None, None),
+            Some(1)
+        );
+        assert_eq!(cgroup_cpu_quota(Some("max 100000\n"), None, None), None);
+        // cgroup v1.
+        assert_eq!(
+            cgroup_cpu_quota(None, Some("400000\n"), Some("100000\n")),
+            Some(4)
+        );
+        assert_eq!(cgroup_cpu_quota(None, Some("-1\n"), Some("100000\n")), None);
+        // No cgroup.
+        assert_eq!(cgroup_cpu_quota(None, None, None), None);
+    }
+
+    #[test]
+    fn test_clamp_to_cpu_quota() {
+        assert_eq!(clamp_to_cpu_quota(64, Some(4)), 4);
+        assert_eq!(clamp_to_cpu_quota(2, Some(4)), 2);
+        assert_eq!(clamp_to_cpu_quota(64, None), 64);
+    }
+
+    #[test]
+    fn test_resolve_local_resources() {
+        let requested = |cpu, memory_bytes| buck2_cli_proto::LocalResources { cpu, memory_bytes };
+        let resolve = |requested: Option<&buck2_cli_proto::LocalResources>, concurrency| {
+            ResolvedLocalResources::resolve_for_machine(requested, concurrency, 16, || 32 << 30)
+        };
+
+        assert_eq!(
+            resolve(None, 12),
+            (
+                ResolvedLocalResources {
+                    cpu: 12,
+                    memory_bytes: None
+                },
+                Vec::new()
+            )
+        );
+        assert_eq!(
+            resolve(Some(&requested(8, 16 << 30)), 16),
+            (
+                ResolvedLocalResources {
+                    cpu: 8,
+                    memory_bytes: Some(16 << 30)
+                },
+                Vec::new()
+            )
+        );
+        // `-j` still applies when it is lower, and 0 means no limit.
+        assert_eq!(resolve(Some(&requested(8, 0)), 4).0.cpu, 4);
+        assert_eq!(resolve(Some(&requested(0, 0)), 16).0.cpu, 16);
+
+        let (resources, warnings) = resolve(Some(&requested(64, 64 << 30)), 32);
+        assert_eq!(
+            resources,
+            ResolvedLocalResources {
+                cpu: 16,
+                memory_bytes: Some(32 << 30)
+            }
+        );
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            warnings[0],
+            "Requested 64 CPUs for local actions, but only 16 are available, using 16"
+        );
+        assert_eq!(
+            warnings[1],
+            "Requested 64.0 GiB of memory for local actions, but only 32.0 GiB is available, \
+            using 32.0 GiB"
+        );
+    }
+
+    #[test]
+    fn test_log_cargo_build_detected() {
+        let mut warned = false;
+        let mut logged = false;
+        log_cargo_build_detected(true, |_| warned = true, |_| logged = true);
+        assert!(!warned);
+        assert!(logged);
+
+        let mut warned = false;
+        log_cargo_build_detected(false, |_| warned = true, |_| panic!());
+        assert!(warned);
+    }
+
+    #[test]
+    fn test_default_re_properties_unknown_os() {
+        assert_eq!(
+            get_default_re_properties_for_os("freebsd"),
+            get_default_re_properties_for_os("linux")
+        );
+    }
+
+    #[test]
+    fn test_parse_re_properties() -> anyhow::Result<()> {
+        let properties = parse_re_properties("platform=freebsd,pool=large,empty=")?;
+        assert_eq!(
+            properties
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("empty", ""), ("platform", "freebsd"), ("pool", "large")]
+        );
+        assert_eq!(
+            parse_re_properties("platform").unwrap_err().to_string(),
+            "Invalid remote execution property `platform`, expected `name=value`"
+        );
+        assert!(parse_re_properties("=freebsd").is_err());
+        assert!(parse_re_properties("").is_err());
+        Ok(())
+    }
+}
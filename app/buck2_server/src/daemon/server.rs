@@ -445,6 +445,7 @@ impl BuckdServer {
                             base_context,
                             req.client_context()?,
                             opts.starlark_profiler_instrumentation_override(&req)?,
+                            opts.no_bxl_cache(&req),
                             req.build_options(),
                             &daemon_state.paths,
                             snapshot_collector,
@@ -833,7 +834,28 @@ impl DaemonApi for BuckdServer {
                     .as_ref()
                     .ok()
                     .map(|state| state.http_client.supports_vpnless()),
-                ..Default::default()
+                active_commands: crate::active_commands::active_commands()
+                    .iter()
+                    .map(|(trace_id, handle)| status_response::ActiveCommand {
+                        trace_id: trace_id.to_string(),
+                        argv: handle.state().argv.clone(),
+                        low_pass_filter: handle.state().low_pass_filter().map(|stats| {
+                            status_response::LowPassFilter {
+                                capacity: stats.capacity as u64,
+                                occupancy: stats.occupancy as u64,
+                                waiting: stats.waiting as u64,
+                            }
+                        }),
+                        local_resources: handle.state().local_resources().map(|usage| {
+                            status_response::LocalResources {
+                                cpu_permits: usage.cpu_permits as u64,
+                                cpu_permits_in_use: usage.cpu_permits_in_use as u64,
+                                memory_bytes: usage.memory_bytes,
+                            }
+                        }),
+                    })
+                    .collect(),
+                rss_bytes: buck2_util::process_stats::process_stats().rss_bytes,
             };
             Ok(base)
         })
@@ -886,9 +908,19 @@ impl DaemonApi for BuckdServer {
 
     type BxlStream = ResponseStream;
     async fn bxl(&self, req: Request<BxlRequest>) -> Result<Response<ResponseStream>, Status> {
+        struct BxlCommandOptions;
+
+        impl OneshotCommandOptions for BxlCommandOptions {}
+
+        impl StreamingCommandOptions<BxlRequest> for BxlCommandOptions {
+            fn no_bxl_cache(&self, req: &BxlRequest) -> bool {
+                req.no_bxl_cache
+            }
+        }
+
         self.run_streaming(
             req,
-            DefaultCommandOptions,
+            BxlCommandOptions,
             |ctx, partial_result_dispatcher, req| {
                 Box::pin(async {
                     BXL_SERVER_COMMANDS
@@ -1134,6 +1166,71 @@ impl DaemonApi for BuckdServer {
         }
     }
 
+    async fn unstable_action_histogram(
+        &self,
+        _req: Request<UnstableActionHistogramRequest>,
+    ) -> Result<Response<UnstableActionHistogramResponse>, Status> {
+        self.check_if_accepting_requests()?;
+
+        let commands = crate::active_commands::active_commands()
+            .iter()
+            .map(|(trace_id, handle)| {
+                let state = handle.state();
+                let actions = state.actions();
+
+                unstable_action_histogram_response::Command {
+                    trace_id: trace_id.to_string(),
+                    argv: state.argv.clone(),
+                    queued: actions.queued,
+                    running_local: actions.running_local,
+                    running_remote: actions.running_remote,
+                    cached: actions.cached,
+                    done: actions.done,
+                }
+            })
+            .collect();
+
+        Ok(Response::new(UnstableActionHistogramResponse { commands }))
+    }
+
+    async fn unstable_longest_running_actions(
+        &self,
+        req: Request<UnstableLongestRunningActionsRequest>,
+    ) -> Result<Response<UnstableLongestRunningActionsResponse>, Status> {
+        self.check_if_accepting_requests()?;
+
+        let limit = req.into_inner().limit as usize;
+        let active_commands = crate::active_commands::active_commands();
+
+        let actions = crate::active_commands::merge_longest_running_actions(
+            active_commands.iter().map(|(trace_id, handle)| {
+                (
+                    trace_id.dupe(),
+                    handle.state().longest_running_actions(limit),
+                )
+            }),
+            limit,
+        );
+
+        let actions = actions
+            .into_iter()
+            .map(
+                |(trace_id, action)| unstable_longest_running_actions_response::Action {
+                    trace_id: trace_id.to_string(),
+                    identity: action.identity,
+                    state: action.state.to_owned(),
+                    command: action.command,
+                    elapsed: action.elapsed.try_into().ok(),
+                },
+            )
+            .collect();
+
+        Ok(Response::new(UnstableLongestRunningActionsResponse {
+            actions,
+            idle: active_commands.is_empty(),
+        }))
+    }
+
     async fn unstable_dice_dump(
         &self,
         req: Request<UnstableDiceDumpRequest>,
@@ -1321,7 +1418,9 @@ impl DaemonApi for BuckdServer {
              partial_result_dispatcher,
              _client_ctx,
              req: StreamingRequestHandler<SubscriptionRequestWrapper>| {
-                run_subscription_server_command(ctx, partial_result_dispatcher, req).boxed()
+                let file_watcher = ctx.base_context.daemon.file_watcher.dupe();
+                run_subscription_server_command(ctx, partial_result_dispatcher, req, file_watcher)
+                    .boxed()
             },
         )
         .await
@@ -1405,6 +1504,11 @@ trait StreamingCommandOptions<Req>: OneshotCommandOptions {
     ) -> anyhow::Result<StarlarkProfilerConfiguration> {
         Ok(StarlarkProfilerConfiguration::None)
     }
+
+    /// Whether cached bxl results should be discarded at the start of this command.
+    fn no_bxl_cache(&self, _req: &Req) -> bool {
+        false
+    }
 }
 
 fn server_shutdown_signal(
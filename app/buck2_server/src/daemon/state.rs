@@ -55,6 +55,7 @@ use buck2_execute_impl::materializers::sqlite::MaterializerState;
 use buck2_execute_impl::materializers::sqlite::MaterializerStateIdentity;
 use buck2_execute_impl::materializers::sqlite::MaterializerStateSqliteDb;
 use buck2_execute_impl::re::paranoid_download::ParanoidDownloader;
+use buck2_execute_impl::re::prefetch::LocallyConsumedOutputs;
 use buck2_file_watcher::file_watcher::FileWatcher;
 use buck2_forkserver::client::ForkserverClient;
 use buck2_http::HttpClient;
@@ -156,6 +157,11 @@ pub struct DaemonStateData {
     #[allocative(skip)]
     pub create_unhashed_outputs_lock: Arc<Mutex<()>>,
 
+    /// Outputs which local actions consumed, kept across commands so that remote outputs can be
+    /// prefetched when something downstream of them needs them locally.
+    #[allocative(skip)]
+    pub(crate) locally_consumed_outputs: Arc<LocallyConsumedOutputs>,
+
     /// A unique identifier for the materializer state.
     pub materializer_state_identity: Option<MaterializerStateIdentity>,
 
@@ -499,6 +505,7 @@ impl DaemonState {
                 disk_state_options,
                 start_time: std::time::Instant::now(),
                 create_unhashed_outputs_lock,
+                locally_consumed_outputs: Arc::new(LocallyConsumedOutputs::default()),
                 materializer_state_identity,
                 enable_restarter,
                 http_client,
@@ -7,16 +7,19 @@
  * of this source tree.
  */
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context as _;
 use buck2_events::dispatch::span_async;
+use buck2_file_watcher::file_watcher::FileWatcher;
 use buck2_server_ctx::command_end::command_end;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use buck2_server_ctx::streaming_request_handler::StreamingRequestHandler;
 use futures::future::FutureExt;
 use gazebo::prelude::*;
+use tokio::sync::broadcast;
 use tokio::time::MissedTickBehavior;
 
 use crate::active_commands;
@@ -27,6 +30,7 @@ pub(crate) async fn run_subscription_server_command(
         buck2_cli_proto::SubscriptionResponseWrapper,
     >,
     mut req: StreamingRequestHandler<buck2_cli_proto::SubscriptionRequestWrapper>,
+    file_watcher: Arc<dyn FileWatcher>,
 ) -> anyhow::Result<buck2_cli_proto::SubscriptionCommandResponse> {
     let start_event = buck2_data::CommandStart {
         metadata: ctx.request_metadata().await?,
@@ -49,6 +53,8 @@ pub(crate) async fn run_subscription_server_command(
                 .context("Error creating a materializer subscription")?;
 
             let mut wants_active_commands = false;
+            let mut cache_outcomes = None;
+            let mut file_changes = None;
 
             let mut ticker = tokio::time::interval(Duration::from_millis(100));
             ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
@@ -73,8 +79,38 @@ pub(crate) async fn run_subscription_server_command(
                             Request::SubscribeToActiveCommands(buck2_subscription_proto::SubscribeToActiveCommands {}) => {
                                 wants_active_commands = true;
                             }
+                            Request::SubscribeToCacheOutcomes(buck2_subscription_proto::SubscribeToCacheOutcomes {}) => {
+                                if cache_outcomes.is_none() {
+                                    cache_outcomes = Some(active_commands::subscribe_to_cache_outcomes());
+                                }
+                            }
+                            Request::SubscribeToFileChanges(buck2_subscription_proto::SubscribeToFileChanges {}) => {
+                                if file_changes.is_none() {
+                                    match file_watcher.subscribe() {
+                                        Some(receiver) => file_changes = Some(receiver),
+                                        None => break buck2_subscription_proto::Disconnect {
+                                            reason: "The file watcher in use does not report file changes, use `buck2.file_watcher = notify`".to_owned(),
+                                            ok: false,
+                                        },
+                                    }
+                                }
+                            }
                         }
                     }
+                    path = next_broadcast(&mut file_changes, "file changes").fuse() => {
+                        partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
+                            response: Some(buck2_subscription_proto::SubscriptionResponse {
+                                response: Some(buck2_subscription_proto::FileChanged { path: path.to_string() }.into())
+                            })
+                        });
+                    }
+                    outcome = next_broadcast(&mut cache_outcomes, "cache outcomes").fuse() => {
+                        partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
+                            response: Some(buck2_subscription_proto::SubscriptionResponse {
+                                response: Some(outcome.into())
+                            })
+                        });
+                    }
                     path = materializer_subscription.next_materialization().fuse() => {
                         let path = path.context("Materializer hung up")?;
                         partial_result_dispatcher.emit(buck2_cli_proto::SubscriptionResponseWrapper {
@@ -115,6 +151,22 @@ pub(crate) async fn run_subscription_server_command(
     .await
 }
 
+/// Wait for the next broadcast message, if subscribed to them. `what` describes the messages.
+async fn next_broadcast<T: Clone>(receiver: &mut Option<broadcast::Receiver<T>>, what: &str) -> T {
+    if let Some(receiver) = receiver {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => return message,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("Subscriber lagged, dropped {} {}", n, what);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+    futures::future::pending().await
+}
+
 fn active_commands_snapshot() -> buck2_subscription_proto::ActiveCommandsSnapshot {
     let active_commands = active_commands::active_commands()
         .iter()
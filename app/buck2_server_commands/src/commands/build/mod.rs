@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io::BufWriter;
 use std::io::Write;
 use std::sync::Arc;
@@ -20,6 +22,7 @@ use buck2_artifact::artifact::artifact_dump::ExternalSymlinkInfo;
 use buck2_artifact::artifact::artifact_dump::FileInfo;
 use buck2_artifact::artifact::artifact_dump::SymlinkInfo;
 use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
 use buck2_build_api::build;
 use buck2_build_api::build::BuildEvent;
 use buck2_build_api::build::BuildTargetResult;
@@ -40,6 +43,7 @@ use buck2_common::dice::file_ops::HasFileOps;
 use buck2_common::legacy_configs::dice::HasLegacyConfigs;
 use buck2_common::pattern::resolve::resolve_target_patterns;
 use buck2_common::pattern::resolve::ResolvedPattern;
+use buck2_core::configuration::compatibility::MaybeCompatible;
 use buck2_core::directory::Directory;
 use buck2_core::directory::DirectoryEntry;
 use buck2_core::directory::DirectoryIterator;
@@ -257,12 +261,15 @@ async fn build(
         .await?
         .unwrap_or_default();
 
+    let exclude_targets: HashSet<String> = request.exclude_targets.iter().cloned().collect();
+
     let build_result = build_targets(
         &ctx,
         resolved_pattern,
         target_resolution_config,
         build_providers,
         &materialization_context,
+        &exclude_targets,
         build_opts.fail_fast,
         MissingTargetBehavior::from_skip(build_opts.skip_missing_targets),
         build_opts.skip_incompatible_targets,
@@ -288,12 +295,32 @@ async fn process_build_result(
     let cell_resolver = ctx.get_cell_resolver().await?;
     let artifact_fs = ctx.get_artifact_fs().await?;
 
+    let mut provider_summaries = HashMap::new();
+    if response_options.return_providers {
+        for (label, result) in &build_result.configured {
+            if result.is_none() {
+                continue;
+            }
+            // The target was analysed to build it, so this doesn't compute anything new.
+            match ctx.get_providers(label).await {
+                Ok(MaybeCompatible::Compatible(providers)) => {
+                    provider_summaries
+                        .insert(label.dupe(), providers.provider_collection().summaries());
+                }
+                Ok(MaybeCompatible::Incompatible(..)) => {}
+                // Already reported as a build error for this target.
+                Err(e) => tracing::debug!("Failed to get providers of {}: {:#}", label, e),
+            }
+        }
+    }
+
     let result_reports = ResultReporter::convert(
         &artifact_fs,
         ResultReporterOptions {
             return_outputs: response_options.return_outputs,
             return_default_other_outputs: response_options.return_default_other_outputs,
         },
+        &provider_summaries,
         &build_result,
     );
 
@@ -407,6 +434,7 @@ async fn build_targets(
     target_resolution_config: TargetResolutionConfig,
     build_providers: Arc<BuildProviders>,
     materialization_context: &MaterializationContext,
+    exclude_targets: &HashSet<String>,
     fail_fast: bool,
     missing_target_behavior: MissingTargetBehavior,
     skip_incompatible_targets: bool,
@@ -423,6 +451,7 @@ async fn build_targets(
                 global_target_platform,
                 build_providers,
                 materialization_context,
+                exclude_targets,
                 missing_target_behavior,
                 skip_incompatible_targets,
                 want_configured_graph_size,
@@ -435,6 +464,7 @@ async fn build_targets(
             universe,
             build_providers,
             materialization_context,
+            exclude_targets,
             want_configured_graph_size,
         )
         .map(BuildEvent::Configured)
@@ -450,12 +480,14 @@ fn build_targets_in_universe<'a>(
     universe: CqueryUniverse,
     build_providers: Arc<BuildProviders>,
     materialization_context: &'a MaterializationContext,
+    exclude_targets: &HashSet<String>,
     want_configured_graph_size: bool,
 ) -> impl Stream<Item = ConfiguredBuildEvent> + Unpin + 'a {
     let providers_to_build = build_providers_to_providers_to_build(&build_providers);
     let provider_labels = universe.get_provider_labels(&spec);
     provider_labels
         .into_iter()
+        .filter(|p| !exclude_targets.contains(&p.unconfigured().to_string()))
         .map(|p| {
             let providers_to_build = providers_to_build.clone();
             async move {
@@ -482,6 +514,7 @@ fn build_targets_with_global_target_platform<'a>(
     global_target_platform: Option<TargetLabel>,
     build_providers: Arc<BuildProviders>,
     materialization_context: &'a MaterializationContext,
+    exclude_targets: &'a HashSet<String>,
     missing_target_behavior: MissingTargetBehavior,
     skip_incompatible_targets: bool,
     want_configured_graph_size: bool,
@@ -494,6 +527,7 @@ fn build_targets_with_global_target_platform<'a>(
             global_target_platform.dupe(),
             build_providers.dupe(),
             materialization_context,
+            exclude_targets,
             missing_target_behavior,
             skip_incompatible_targets,
             want_configured_graph_size,
@@ -541,6 +575,7 @@ async fn build_targets_for_spec<'a>(
     global_target_platform: Option<TargetLabel>,
     build_providers: Arc<BuildProviders>,
     materialization_context: &'a MaterializationContext,
+    exclude_targets: &HashSet<String>,
     missing_target_behavior: MissingTargetBehavior,
     skip_incompatible_targets: bool,
     want_configured_graph_size: bool,
@@ -603,6 +638,13 @@ async fn build_targets_for_spec<'a>(
 
     let todo_targets: Vec<TargetBuildSpec> = targets
         .into_iter()
+        .filter(|((_target_name, extra), target)| {
+            exclude_targets.is_empty()
+                || !exclude_targets.contains(
+                    &ProvidersLabel::new(target.label().dupe(), extra.providers.clone())
+                        .to_string(),
+                )
+        })
         .map(|((_target_name, extra), target)| TargetBuildSpec {
             target,
             providers: extra.providers,
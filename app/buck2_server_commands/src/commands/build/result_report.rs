@@ -9,10 +9,13 @@
 
 //! Processing and reporting the the results of the build
 
+use std::collections::HashMap;
+
 use buck2_build_api::build::BuildProviderType;
 use buck2_build_api::build::BuildTargetResult;
 use buck2_build_api::build::ConfiguredBuildTargetResult;
 use buck2_build_api::build::ProviderArtifacts;
+use buck2_build_api::interpreter::rule_defs::provider::collection::ProviderSummary;
 use buck2_core::configuration::compatibility::MaybeCompatible;
 use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
@@ -22,7 +25,9 @@ use starlark_map::small_map::SmallMap;
 
 mod proto {
     pub use buck2_cli_proto::build_target::build_output::BuildOutputProviders;
+    pub use buck2_cli_proto::build_target::provider_summary::Field as ProviderSummaryField;
     pub use buck2_cli_proto::build_target::BuildOutput;
+    pub use buck2_cli_proto::build_target::ProviderSummary;
     pub use buck2_cli_proto::BuildTarget;
 }
 
@@ -42,6 +47,7 @@ pub(crate) struct ResultReporterOptions {
 pub(crate) struct ResultReporter<'a> {
     artifact_fs: &'a ArtifactFs,
     options: ResultReporterOptions,
+    provider_summaries: &'a HashMap<ConfiguredProvidersLabel, Vec<ProviderSummary>>,
     results: Vec<proto::BuildTarget>,
 }
 
@@ -54,11 +60,13 @@ impl<'a> ResultReporter<'a> {
     pub(crate) fn convert(
         artifact_fs: &'a ArtifactFs,
         options: ResultReporterOptions,
+        provider_summaries: &'a HashMap<ConfiguredProvidersLabel, Vec<ProviderSummary>>,
         build_result: &BuildTargetResult,
     ) -> BuildTargetsAndErrors {
         let mut out = Self {
             artifact_fs,
             options,
+            provider_summaries,
             results: Vec::new(),
         };
 
@@ -177,13 +185,38 @@ impl<'a> ResultReporter<'a> {
             None => None,
         };
 
+        let success = result.errors.is_empty() && result.outputs.iter().all(|x| x.is_ok());
+
+        let providers = self
+            .provider_summaries
+            .get(label)
+            .map(|summaries| {
+                summaries
+                    .iter()
+                    .map(|summary| proto::ProviderSummary {
+                        name: summary.name.clone(),
+                        fields: summary
+                            .fields
+                            .iter()
+                            .map(|(name, summary)| proto::ProviderSummaryField {
+                                name: name.clone(),
+                                summary: summary.clone(),
+                            })
+                            .collect(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         self.results.push(proto::BuildTarget {
             target,
+            success,
             configuration,
             run_args: result.run_args.clone().unwrap_or_default(),
             target_rule_type_name: result.target_rule_type_name.clone(),
             outputs: artifacts,
             configured_graph_size,
+            providers,
         })
     }
 }
@@ -60,7 +60,7 @@ impl ServerCommandTemplate for CqueryServerCommand {
         buck2_data::CQueryCommandStart {
             query: truncate(&self.req.query, 50000),
             query_args: truncate(&self.req.query_args.join(","), 1000),
-            target_universe: truncate(&self.req.target_universe.join(","), 1000),
+            target_universe: truncate(&target_universe_description(&self.req), 1000),
         }
     }
 
@@ -95,12 +95,14 @@ async fn cquery(
         &cell_resolver,
         &request.output_attributes,
         request.unstable_output_format,
-    )?;
+    )?
+    .with_keep_duplicates(request.keep_duplicates)?;
 
     let CqueryRequest {
         query,
         query_args,
         target_universe,
+        target_universes,
         context,
         show_providers,
         correct_owner,
@@ -126,6 +128,46 @@ async fn cquery(
         false => CqueryOwnerBehavior::Deprecated,
     };
 
+    let should_print_providers = if *show_providers {
+        ShouldPrintProviders::Yes(&*ctx as &dyn ProviderLookUp<ConfiguredTargetNode>)
+    } else {
+        ShouldPrintProviders::No
+    };
+
+    if !target_universes.is_empty() {
+        // Each universe gets its own configured graph, so the same target may appear under
+        // several universes, possibly in different configurations.
+        let mut groups = Vec::with_capacity(target_universes.len());
+        for universe in target_universes {
+            let query_result = QUERY_FRONTEND
+                .get()?
+                .eval_cquery(
+                    &ctx,
+                    server_ctx.working_dir(),
+                    owner_behavior,
+                    query,
+                    query_args,
+                    global_target_platform.dupe(),
+                    Some(universe.targets.as_slice()),
+                )
+                .await?;
+            let value = match query_result {
+                QueryEvaluationResult::Single(value) => value,
+                QueryEvaluationResult::Multiple(results) => results.merged()?,
+            };
+            groups.push((universe.targets.join(","), value));
+        }
+        output_configuration
+            .print_grouped_output(
+                &mut stdout,
+                groups,
+                target_call_stacks,
+                should_print_providers,
+            )
+            .await?;
+        return Ok(CqueryResponse {});
+    }
+
     let query_result = QUERY_FRONTEND
         .get()?
         .eval_cquery(
@@ -139,12 +181,6 @@ async fn cquery(
         )
         .await?;
 
-    let should_print_providers = if *show_providers {
-        ShouldPrintProviders::Yes(&*ctx as &dyn ProviderLookUp<ConfiguredTargetNode>)
-    } else {
-        ShouldPrintProviders::No
-    };
-
     match query_result {
         QueryEvaluationResult::Single(targets) => {
             output_configuration
@@ -171,6 +207,19 @@ async fn cquery(
     Ok(CqueryResponse {})
 }
 
+fn target_universe_description(request: &CqueryRequest) -> String {
+    if request.target_universes.is_empty() {
+        request.target_universe.join(",")
+    } else {
+        request
+            .target_universes
+            .iter()
+            .map(|universe| universe.targets.join(","))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 #[async_trait]
 impl ProviderLookUp<ConfiguredTargetNode> for DiceComputations {
     async fn lookup(
@@ -18,4 +18,8 @@ enum QueryCommandError {
         "query result was a set of files and one or more --output-attribute was requested, but files have not attributes"
     )]
     FileSetHasNoAttributes,
+    #[error(
+        "--keep-duplicates is only supported with the default list output, other output formats (including the JSON output used for --output-attribute) need a set of targets"
+    )]
+    KeepDuplicatesRequiresListOutput,
 }
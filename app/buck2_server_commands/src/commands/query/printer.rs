@@ -41,6 +41,7 @@ use serde::Serializer;
 use crate::commands::query::QueryCommandError;
 use crate::dot::targets::DotTargetGraph;
 use crate::dot::Dot;
+use crate::dot::DotCollapsed;
 use crate::dot::DotCompact;
 
 #[derive(Copy_, Dupe_, Clone_, UnpackVariants)]
@@ -60,6 +61,7 @@ pub struct QueryResultPrinter<'a> {
     resolver: &'a CellResolver,
     attributes: Option<RegexSet>,
     output_format: QueryOutputFormat,
+    keep_duplicates: bool,
 }
 
 struct TargetSetJsonPrinter<'a, T: QueryTarget> {
@@ -75,8 +77,13 @@ impl<'a, T: QueryTarget> TargetSetJsonPrinter<'a, T> {
         targets: &'a TargetSet<T>,
     ) -> anyhow::Result<TargetSetJsonPrinter<'a, T>> {
         Ok(TargetSetJsonPrinter {
-            value: printable_targets(targets, print_providers, attributes, target_call_stacks)
-                .await?,
+            value: printable_targets(
+                targets.iter().collect(),
+                print_providers,
+                attributes,
+                target_call_stacks,
+            )
+            .await?,
             is_complex: attributes.is_some()
                 || target_call_stacks
                 || print_providers.unpack_yes().is_some(),
@@ -250,9 +257,21 @@ impl<'a> QueryResultPrinter<'a> {
             resolver,
             attributes,
             output_format,
+            keep_duplicates: false,
         })
     }
 
+    /// Print target sets in dep traversal order, with each target repeated once for every dep
+    /// edge leading to it (see `TargetSet::iter_with_duplicates`). Only the list output can
+    /// represent that.
+    pub fn with_keep_duplicates(mut self, keep_duplicates: bool) -> anyhow::Result<Self> {
+        if keep_duplicates && self.output_format != QueryOutputFormat::Default {
+            return Err(QueryCommandError::KeepDuplicatesRequiresListOutput.into());
+        }
+        self.keep_duplicates = keep_duplicates;
+        Ok(self)
+    }
+
     pub async fn print_multi_output<'b, T: QueryTarget, W: std::io::Write>(
         &self,
         mut output: W,
@@ -319,6 +338,64 @@ impl<'a> QueryResultPrinter<'a> {
         }
     }
 
+    /// Print the results of evaluating the same query in several groups (e.g. one per target
+    /// universe). Unlike a multi-query, groups are never merged: JSON output is a map from group
+    /// to its result, and every other format prints each group after a `#` comment naming it.
+    pub async fn print_grouped_output<'b, T: QueryTarget, W: std::io::Write>(
+        &self,
+        mut output: W,
+        groups: Vec<(String, QueryEvaluationValue<T>)>,
+        target_call_stacks: bool,
+        print_providers: ShouldPrintProviders<'b, T>,
+    ) -> anyhow::Result<()> {
+        match self.output_format {
+            QueryOutputFormat::Json => {
+                let mut ser = serde_json::Serializer::pretty(&mut output);
+                let mut map = ser.serialize_map(Some(groups.len()))?;
+                for (group, value) in &groups {
+                    match value {
+                        QueryEvaluationValue::TargetSet(targets) => map.serialize_entry(
+                            group,
+                            &TargetSetJsonPrinter::new(
+                                target_call_stacks,
+                                print_providers,
+                                &self.attributes,
+                                targets,
+                            )
+                            .await?,
+                        )?,
+                        QueryEvaluationValue::FileSet(files) => map.serialize_entry(
+                            group,
+                            &FileSetJsonPrinter {
+                                resolver: self.resolver,
+                                value: files,
+                            },
+                        )?,
+                    }
+                }
+                SerializeMap::end(map)?;
+                std::mem::drop(ser);
+                // need to add a newline to flush the output.
+                writeln!(&mut output)?;
+                Ok(())
+            }
+            _ => {
+                for (group, value) in groups {
+                    // `#` lines are also ignored by dot, so this keeps dot output valid.
+                    writeln!(&mut output, "# {}", group)?;
+                    self.print_single_output(
+                        &mut output,
+                        value,
+                        target_call_stacks,
+                        print_providers,
+                    )
+                    .await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
     pub async fn print_single_output<'b, T: QueryTarget, W: std::io::Write>(
         &self,
         mut output: W,
@@ -329,8 +406,13 @@ impl<'a> QueryResultPrinter<'a> {
         match result {
             QueryEvaluationValue::TargetSet(targets) => match self.output_format {
                 QueryOutputFormat::Default => {
+                    let targets = if self.keep_duplicates {
+                        targets.iter_with_duplicates()
+                    } else {
+                        targets.iter().collect()
+                    };
                     for target in
-                        printable_targets(&targets, print_providers, &self.attributes, call_stack)
+                        printable_targets(targets, print_providers, &self.attributes, call_stack)
                             .await?
                     {
                         writeln!(&mut output, "{}", target)?;
@@ -368,6 +450,15 @@ impl<'a> QueryResultPrinter<'a> {
                         &mut output,
                     )?;
                 }
+                QueryOutputFormat::DotCollapsed => {
+                    DotCollapsed::render(
+                        &DotTargetGraph {
+                            targets,
+                            attributes: self.attributes.clone(),
+                        },
+                        &mut output,
+                    )?;
+                }
             },
             QueryEvaluationValue::FileSet(files) => {
                 if self.attributes.is_some() {
@@ -400,6 +491,9 @@ impl<'a> QueryResultPrinter<'a> {
                     QueryOutputFormat::DotCompact => {
                         unimplemented!("dot_compact output for files not implemented yet")
                     }
+                    QueryOutputFormat::DotCollapsed => {
+                        unimplemented!("dot_collapsed output for files not implemented yet")
+                    }
                 }
             }
         }
@@ -409,12 +503,12 @@ impl<'a> QueryResultPrinter<'a> {
 }
 
 async fn printable_targets<'a, T: QueryTarget>(
-    targets: &'a TargetSet<T>,
+    targets: Vec<&'a T>,
     print_providers: ShouldPrintProviders<'a, T>,
     attributes: &'a Option<RegexSet>,
     target_call_stacks: bool,
 ) -> anyhow::Result<Vec<PrintableQueryTarget<'a, T>>> {
-    futures::future::join_all(targets.iter().map(|t| {
+    futures::future::join_all(targets.into_iter().map(|t| {
         let print_providers = &print_providers;
         async move {
             Ok(PrintableQueryTarget {
@@ -479,3 +573,49 @@ pub(crate) fn init_print_action_node() {
         ))
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::name::CellName;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_keep_duplicates_requires_list_output() -> anyhow::Result<()> {
+        let resolver = CellResolver::testing_with_name_and_path(
+            CellName::testing_new("root"),
+            CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new(String::new())),
+        );
+        let keep_duplicates = |attributes: &[String], output_format| {
+            QueryResultPrinter::from_options(&resolver, attributes, output_format)?
+                .with_keep_duplicates(true)
+        };
+        let expected = QueryCommandError::KeepDuplicatesRequiresListOutput.to_string();
+
+        assert!(keep_duplicates(&[], QueryOutputFormat::Default)?.keep_duplicates);
+        for output_format in [
+            QueryOutputFormat::Json,
+            QueryOutputFormat::Dot,
+            QueryOutputFormat::DotCompact,
+            QueryOutputFormat::DotCollapsed,
+        ] {
+            let error = keep_duplicates(&[], output_format).err().unwrap();
+            assert_eq!(error.to_string(), expected, "{:?}", output_format);
+        }
+        // `--output-attribute` switches the default output to JSON.
+        let error = keep_duplicates(&["name".to_owned()], QueryOutputFormat::Default)
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), expected);
+
+        // Without `--keep-duplicates`, any output format is fine.
+        assert!(
+            !QueryResultPrinter::from_options(&resolver, &[], QueryOutputFormat::Json)?
+                .with_keep_duplicates(false)?
+                .keep_duplicates
+        );
+        Ok(())
+    }
+}
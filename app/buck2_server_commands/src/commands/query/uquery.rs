@@ -76,7 +76,8 @@ async fn uquery(
         &cell_resolver,
         &request.output_attributes,
         request.unstable_output_format,
-    )?;
+    )?
+    .with_keep_duplicates(request.keep_duplicates)?;
 
     let UqueryRequest {
         query,
@@ -371,6 +371,21 @@ pub(crate) fn print_target_call_stack_after_target(out: &mut String, call_stack:
     }
 }
 
+/// Attributes are selected if they contain a match of one of `search`, or entirely match one
+/// of `anchored`.
+fn attributes_regex_set(
+    search: &[String],
+    anchored: &[String],
+) -> anyhow::Result<Option<RegexSet>> {
+    if search.is_empty() && anchored.is_empty() {
+        return Ok(None);
+    }
+    // A group, so that alternations like `a|b` are anchored as a whole. Anchors already in the
+    // regex are harmless.
+    let anchored = anchored.iter().map(|r| format!("^(?:{})$", r));
+    Ok(Some(RegexSet::new(search.iter().cloned().chain(anchored))?))
+}
+
 pub(crate) fn create_formatter(
     request: &TargetsRequest,
     other: &targets_request::Other,
@@ -384,7 +399,7 @@ pub(crate) fn create_formatter(
         OutputFormat::Json | OutputFormat::JsonLines => {}
         _ => {
             // Self-check.
-            if !other.output_attributes.is_empty() {
+            if !other.output_attributes.is_empty() || !other.output_attributes_anchored.is_empty() {
                 return Err(FormatterError::AttrsOnlyWithJson.into());
             }
         }
@@ -399,11 +414,10 @@ pub(crate) fn create_formatter(
                 .expect("buck cli should send valid target hash graph type"),
         })),
         OutputFormat::Json | OutputFormat::JsonLines => Ok(Arc::new(JsonFormat {
-            attributes: if other.output_attributes.is_empty() {
-                None
-            } else {
-                Some(RegexSet::new(&other.output_attributes)?)
-            },
+            attributes: attributes_regex_set(
+                &other.output_attributes,
+                &other.output_attributes_anchored,
+            )?,
             attr_inspect_opts: if other.include_default_attributes {
                 AttrInspectOptions::All
             } else {
@@ -421,3 +435,143 @@ pub(crate) fn create_formatter(
         })),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use allocative::Allocative;
+    use buck2_client_ctx::json_schema::JsonOutput;
+    use buck2_core::target::label::TargetLabel;
+    use buck2_node::attrs::attr::Attribute;
+    use buck2_node::attrs::attr_type::string::StringLiteral;
+    use buck2_node::attrs::attr_type::AttrType;
+    use buck2_node::attrs::coerced_attr::CoercedAttr;
+    use buck2_node::metadata::key::MetadataKey;
+    use buck2_node::metadata::key::MetadataKeyRef;
+    use buck2_node::metadata::super_package_values::SuperPackageValues;
+    use buck2_node::nodes::unconfigured::testing::TargetNodeExt;
+    use buck2_node::rule_type::RuleType;
+    use buck2_node::rule_type::StarlarkRuleType;
+    use dupe::Dupe;
+    use starlark_map::small_map::SmallMap;
+
+    use super::*;
+
+    #[derive(Debug, Default, Allocative)]
+    struct NoPackageValues;
+
+    impl SuperPackageValues for NoPackageValues {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn is_empty(&self) -> bool {
+            true
+        }
+
+        fn package_values_json(&self) -> anyhow::Result<SmallMap<MetadataKey, serde_json::Value>> {
+            Ok(SmallMap::new())
+        }
+
+        fn contains_key(&self, _key: &MetadataKeyRef) -> bool {
+            false
+        }
+
+        fn get_package_value_json(
+            &self,
+            _key: &MetadataKeyRef,
+        ) -> anyhow::Result<Option<serde_json::Value>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_json_matches_schema() -> anyhow::Result<()> {
+        let rule_type = RuleType::Starlark(Arc::new(StarlarkRuleType {
+            import_path: ImportPath::testing_new("root//foo:defs.bzl"),
+            name: "my_rule".to_owned(),
+        }));
+        let nodes = [
+            TargetNode::testing_new(
+                TargetLabel::testing_parse("root//foo:bar"),
+                rule_type.dupe(),
+                vec![(
+                    "greeting",
+                    Attribute::new(None, "", AttrType::string()),
+                    CoercedAttr::String(StringLiteral("hello".into())),
+                )],
+            ),
+            TargetNode::testing_new(
+                TargetLabel::testing_parse("root//foo:baz"),
+                rule_type,
+                Vec::new(),
+            ),
+        ];
+        let super_package = SuperPackage::empty::<NoPackageValues>();
+
+        for json_lines in [false, true] {
+            let format = JsonFormat {
+                attributes: None,
+                attr_inspect_opts: AttrInspectOptions::DefinedOnly,
+                target_call_stacks: false,
+                package_values: None,
+                writer: JsonWriter { json_lines },
+            };
+            let mut buffer = String::new();
+            format.begin(&mut buffer);
+            for (i, node) in nodes.iter().enumerate() {
+                if i != 0 {
+                    format.separator(&mut buffer);
+                }
+                format.target(
+                    TargetInfo {
+                        node,
+                        target_hash: None,
+                        super_package: &super_package,
+                    },
+                    &mut buffer,
+                );
+            }
+            format.end(&Stats::default(), &mut buffer);
+
+            let json: serde_json::Value = if json_lines {
+                // Each line is an item of the array `--json` prints.
+                serde_json::Value::Array(
+                    buffer
+                        .lines()
+                        .map(serde_json::from_str)
+                        .collect::<Result<_, _>>()?,
+                )
+            } else {
+                serde_json::from_str(&buffer)?
+            };
+            JsonOutput::Targets.validate(&json)?;
+            assert_eq!(
+                json[0]["buck.type"], "root//foo:defs.bzl:my_rule",
+                "{}",
+                json
+            );
+            assert_eq!(json[0]["greeting"], "hello", "{}", json);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_attributes_regex_set_anchored() -> anyhow::Result<()> {
+        let set = attributes_regex_set(&[], &["^deps$".to_owned()])?.unwrap();
+        assert!(set.is_match("deps"));
+        assert!(!set.is_match("dep"));
+        assert!(!set.is_match("exported_deps"));
+
+        let set = attributes_regex_set(&[], &["deps|srcs".to_owned()])?.unwrap();
+        assert!(set.is_match("srcs"));
+        assert!(!set.is_match("exported_deps"));
+
+        let set = attributes_regex_set(&["dep".to_owned()], &[])?.unwrap();
+        assert!(set.is_match("exported_deps"));
+
+        assert!(attributes_regex_set(&[], &[])?.is_none());
+        Ok(())
+    }
+}
@@ -175,3 +175,283 @@ impl DotCompact {
         Ok(())
     }
 }
+
+/// Like [`DotCompact`], but with chains of nodes that have exactly one incoming and one outgoing
+/// edge collapsed into a single edge labeled with the number of nodes it replaces.
+pub struct DotCollapsed {}
+
+impl DotCollapsed {
+    pub fn render<'a, T: DotDigraph<'a>, W: Write>(graph: &'a T, mut w: W) -> anyhow::Result<()> {
+        let mut ids: Vec<String> = Vec::new();
+        let mut attrs: Vec<Option<DotNodeAttrs>> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut edges: Vec<(usize, String)> = Vec::new();
+
+        graph.for_each_node(|node| {
+            let id = node.id();
+            let i = match index.entry(id.clone()) {
+                Vacant(entry) => {
+                    ids.push(id);
+                    attrs.push(None);
+                    *entry.insert(ids.len() - 1)
+                }
+                Occupied(entry) => *entry.get(),
+            };
+            attrs[i] = Some(node.attrs()?);
+            graph.for_each_edge(node, |edge| {
+                edges.push((i, edge.to.to_owned()));
+                Ok(())
+            })?;
+            Ok(())
+        })?;
+
+        // Edges may point at nodes the graph didn't list; give them an index without attrs.
+        let mut succs: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+        for (from, to) in edges {
+            let to = match index.entry(to) {
+                Vacant(entry) => {
+                    ids.push(entry.key().clone());
+                    attrs.push(None);
+                    succs.push(Vec::new());
+                    *entry.insert(ids.len() - 1)
+                }
+                Occupied(entry) => *entry.get(),
+            };
+            succs[from].push(to);
+        }
+
+        let collapsed = Self::collapsed_nodes(&attrs, &succs);
+        let node_count = attrs.iter().filter(|a| a.is_some()).count();
+        let collapsed_count = collapsed.iter().filter(|c| **c).count();
+
+        writeln!(w, "digraph {} {{", graph.name())?;
+        writeln!(
+            w,
+            "  // dot_collapsed: collapsed {} of {} nodes into edges, {} nodes remain",
+            collapsed_count,
+            node_count,
+            node_count - collapsed_count
+        )?;
+
+        let mut next_id: u32 = 0;
+        let mut numeric_ids: Vec<Option<u32>> = vec![None; ids.len()];
+        let mut number = |i: usize| -> u32 {
+            *numeric_ids[i].get_or_insert_with(|| {
+                next_id += 1;
+                next_id
+            })
+        };
+
+        for (i, node_attrs) in attrs.iter().enumerate() {
+            let node_attrs = match node_attrs {
+                Some(node_attrs) if !collapsed[i] => node_attrs,
+                _ => continue,
+            };
+            writeln!(
+                w,
+                "  {} [{},label={}];",
+                number(i),
+                node_attrs,
+                escape_id(&ids[i])
+            )?;
+            for &succ in &succs[i] {
+                // Follow the chain of collapsed nodes to the next node that is kept.
+                let mut to = succ;
+                let mut skipped = 0;
+                while collapsed[to] {
+                    skipped += 1;
+                    to = succs[to][0];
+                }
+                if skipped == 0 {
+                    writeln!(w, "  {} -> {};", number(i), number(to))?;
+                } else {
+                    writeln!(
+                        w,
+                        "  {} -> {} [style=dashed,label={}];",
+                        number(i),
+                        number(to),
+                        skipped
+                    )?;
+                }
+            }
+        }
+        writeln!(w, "}}")?;
+        Ok(())
+    }
+
+    /// Which nodes are hidden inside a collapsed edge. A node is collapsed if it has exactly one
+    /// incoming and one outgoing edge, and the chain it is part of starts at a node we keep.
+    /// Cycles made up entirely of such nodes have no node to start from, so are kept as they are.
+    fn collapsed_nodes(attrs: &[Option<DotNodeAttrs>], succs: &[Vec<usize>]) -> Vec<bool> {
+        let mut in_degree = vec![0; succs.len()];
+        for to in succs.iter().flatten() {
+            in_degree[*to] += 1;
+        }
+        let collapsible: Vec<bool> = (0..succs.len())
+            .map(|i| {
+                attrs[i].is_some() && in_degree[i] == 1 && succs[i].len() == 1 && succs[i][0] != i
+            })
+            .collect();
+
+        let mut collapsed = vec![false; succs.len()];
+        for i in 0..succs.len() {
+            if collapsible[i] {
+                continue;
+            }
+            for &succ in &succs[i] {
+                let mut to = succ;
+                // Each collapsible node has a single predecessor, so can only be reached once.
+                while collapsible[to] && !collapsed[to] {
+                    collapsed[to] = true;
+                    to = succs[to][0];
+                }
+            }
+        }
+        collapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestNode(&'static str);
+
+    impl DotNode for TestNode {
+        fn attrs(&self) -> anyhow::Result<DotNodeAttrs> {
+            Ok(DotNodeAttrs {
+                style: Some("filled".to_owned()),
+                ..DotNodeAttrs::default()
+            })
+        }
+
+        fn id(&self) -> String {
+            self.0.to_owned()
+        }
+    }
+
+    struct TestGraph(Vec<(TestNode, Vec<&'static str>)>);
+
+    impl<'a> DotDigraph<'a> for TestGraph {
+        type Node = TestNode;
+
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn for_each_node<F: FnMut(&Self::Node) -> anyhow::Result<()>>(
+            &'a self,
+            mut f: F,
+        ) -> anyhow::Result<()> {
+            for (node, _) in &self.0 {
+                f(node)?;
+            }
+            Ok(())
+        }
+
+        fn for_each_edge<F: FnMut(&DotEdge) -> anyhow::Result<()>>(
+            &'a self,
+            node: &Self::Node,
+            mut f: F,
+        ) -> anyhow::Result<()> {
+            let (_, deps) = self.0.iter().find(|(n, _)| n.0 == node.0).unwrap();
+            for dep in deps {
+                f(&DotEdge {
+                    from: node.0,
+                    to: dep,
+                })?;
+            }
+            Ok(())
+        }
+    }
+
+    fn test_graph(nodes: Vec<(&'static str, Vec<&'static str>)>) -> TestGraph {
+        TestGraph(
+            nodes
+                .into_iter()
+                .map(|(n, deps)| (TestNode(n), deps))
+                .collect(),
+        )
+    }
+
+    fn render_collapsed(nodes: Vec<(&'static str, Vec<&'static str>)>) -> String {
+        let mut out = Vec::new();
+        DotCollapsed::render(&test_graph(nodes), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_dot_compact_keeps_chains() {
+        let mut out = Vec::new();
+        DotCompact::render(
+            &test_graph(vec![("a", vec!["b"]), ("b", vec!["c"]), ("c", vec![])]),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "digraph test {\n  \
+            1 [style=filled,label=a];\n  \
+            1 -> 2;\n  \
+            2 [style=filled,label=b];\n  \
+            2 -> 3;\n  \
+            3 [style=filled,label=c];\n\
+            }\n"
+        );
+    }
+
+    #[test]
+    fn test_dot_collapsed_collapses_chains() {
+        let out = render_collapsed(vec![
+            ("a", vec!["b", "e"]),
+            ("b", vec!["c"]),
+            ("c", vec!["d"]),
+            ("d", vec![]),
+            ("e", vec!["d"]),
+        ]);
+        assert_eq!(
+            out,
+            "digraph test {\n  \
+            // dot_collapsed: collapsed 3 of 5 nodes into edges, 2 nodes remain\n  \
+            1 [style=filled,label=a];\n  \
+            1 -> 2 [style=dashed,label=2];\n  \
+            1 -> 2 [style=dashed,label=1];\n  \
+            2 [style=filled,label=d];\n\
+            }\n"
+        );
+    }
+
+    #[test]
+    fn test_dot_collapsed_keeps_cycles() {
+        // A cycle made up only of single-in/single-out nodes is kept as is.
+        let out = render_collapsed(vec![("a", vec!["b"]), ("b", vec!["a"])]);
+        assert_eq!(
+            out,
+            "digraph test {\n  \
+            // dot_collapsed: collapsed 0 of 2 nodes into edges, 2 nodes remain\n  \
+            1 [style=filled,label=a];\n  \
+            1 -> 2;\n  \
+            2 [style=filled,label=b];\n  \
+            2 -> 1;\n\
+            }\n"
+        );
+
+        // A cycle through a node with other edges collapses into a self-edge on that node.
+        let out = render_collapsed(vec![
+            ("a", vec!["b", "d"]),
+            ("b", vec!["c"]),
+            ("c", vec!["a"]),
+            ("d", vec![]),
+        ]);
+        assert_eq!(
+            out,
+            "digraph test {\n  \
+            // dot_collapsed: collapsed 2 of 4 nodes into edges, 2 nodes remain\n  \
+            1 [style=filled,label=a];\n  \
+            1 -> 1 [style=dashed,label=2];\n  \
+            1 -> 2;\n  \
+            2 [style=filled,label=d];\n\
+            }\n"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io::Write;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use buck2_cli_proto::ClientContext;
+use buck2_client_ctx::path_arg::PathArg;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::dice::data::HasIoProvider;
+use buck2_common::dice::file_ops::HasFileOps;
+use buck2_core::fs::fs_util;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use starlark::analysis::fix_imports;
+
+use crate::util::paths::starlark_files;
+use crate::StarlarkCommandCommonOptions;
+use crate::StarlarkOpaqueSubcommand;
+
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(
+    name = "starlark-fix-imports",
+    about = "Sort, deduplicate and merge the `load` statements of Starlark files, in place."
+)]
+pub struct StarlarkFixImportsCommand {
+    #[clap(flatten)]
+    common_opts: StarlarkCommandCommonOptions,
+
+    #[clap(value_name = "PATH", required = true)]
+    paths: Vec<PathArg>,
+}
+
+#[async_trait]
+impl StarlarkOpaqueSubcommand for StarlarkFixImportsCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        _stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_ctx: ClientContext,
+    ) -> anyhow::Result<()> {
+        server_ctx
+            .with_dice_ctx(async move |server_ctx, ctx| {
+                let cell_resolver = ctx.get_cell_resolver().await?;
+                let fs = ctx.file_ops();
+                let io = ctx.global_data().get_io_provider();
+
+                let mut stderr = server_ctx.stderr()?;
+                let mut fixed_count = 0;
+                let files =
+                    starlark_files(&self.paths, server_ctx, &cell_resolver, &fs, &*io).await?;
+                for file in &files {
+                    let file = file.borrow();
+                    let proj_path = cell_resolver.resolve_path(file.path().as_ref().as_ref())?;
+                    let path_str = proj_path.to_string();
+                    let content = io
+                        .read_file_if_exists(proj_path.clone())
+                        .await?
+                        .with_context(|| format!("File not found: `{}`", path_str))?;
+                    let fixed = fix_imports(&path_str, &content)
+                        .with_context(|| format!("Error fixing imports in `{}`", path_str))?;
+                    if let Some(fixed) = fixed {
+                        fs_util::write(server_ctx.project_root().resolve(&proj_path), fixed)?;
+                        writeln!(stderr, "Fixed imports in `{}`", path_str)?;
+                        fixed_count += 1;
+                    }
+                }
+                writeln!(
+                    stderr,
+                    "Fixed imports in {} of {} files",
+                    fixed_count,
+                    files.len()
+                )?;
+                Ok(())
+            })
+            .await
+    }
+
+    fn common_opts(&self) -> &StarlarkCommandCommonOptions {
+        &self.common_opts
+    }
+}
@@ -29,10 +29,13 @@ use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 
 use crate::debug::StarlarkDebugAttachCommand;
+use crate::fix_imports::StarlarkFixImportsCommand;
+pub use crate::lint::LintCommand;
 use crate::lint::StarlarkLintCommand;
 use crate::typecheck::StarlarkTypecheckCommand;
 
 mod debug;
+mod fix_imports;
 mod lint;
 pub mod server;
 mod typecheck;
@@ -53,6 +56,7 @@ pub enum StarlarkCommand {
 pub enum StarlarkOpaqueCommand {
     Lint(StarlarkLintCommand),
     Typecheck(StarlarkTypecheckCommand),
+    FixImports(StarlarkFixImportsCommand),
 }
 
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize, Default)]
@@ -94,6 +98,7 @@ impl StarlarkOpaqueCommand {
         match self {
             Self::Lint(cmd) => cmd,
             Self::Typecheck(cmd) => cmd,
+            Self::FixImports(cmd) => cmd,
         }
     }
 }
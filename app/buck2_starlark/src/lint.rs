@@ -10,18 +10,29 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::Context;
 use async_trait::async_trait;
 use buck2_cli_proto::ClientContext;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::path_arg::PathArg;
+use buck2_client_ctx::streaming::BuckSubcommand;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::data::HasIoProvider;
 use buck2_common::dice::file_ops::HasFileOps;
+use buck2_common::invocation_paths::InvocationPaths;
 use buck2_common::io::IoProvider;
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::fs::working_dir::WorkingDir;
+use buck2_events::daemon_id::DAEMON_UUID;
 use buck2_interpreter::file_type::StarlarkFileType;
 use buck2_interpreter::paths::path::StarlarkPath;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
@@ -30,15 +41,23 @@ use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use dice::DiceTransaction;
 use dupe::Dupe;
 use starlark::analysis::AstModuleLint;
+use starlark::analysis::LintMessage;
 use starlark::codemap::FileSpan;
 use starlark::errors::Diagnostic;
+use starlark::errors::EvalMessage;
 use starlark::errors::EvalSeverity;
 use starlark::errors::Lint;
 use starlark::syntax::AstModule;
+use starlark::syntax::Dialect;
 
 use crate::util::environment::Environment;
+use crate::util::fail_on::FailOn;
+use crate::util::lint_cache::globals_hash;
+use crate::util::lint_cache::LintCache;
 use crate::util::paths::starlark_files;
+use crate::util::paths::starlark_files_for_patterns;
 use crate::StarlarkCommandCommonOptions;
+use crate::StarlarkOpaqueCommand;
 use crate::StarlarkOpaqueSubcommand;
 
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
@@ -47,14 +66,80 @@ pub struct StarlarkLintCommand {
     #[clap(flatten)]
     common_opts: StarlarkCommandCommonOptions,
 
-    #[clap(value_name = "PATH", required = true)]
+    /// Print each lint as a JSON object on its own line.
+    #[clap(long)]
+    json: bool,
+
+    /// The least severe kind of lint which causes the command to fail.
+    #[clap(long, arg_enum, default_value = "advice")]
+    fail_on: FailOn,
+
+    /// Also lint the build files of the packages matched by these target patterns.
+    #[clap(long = "target", value_name = "TARGET_PATTERN")]
+    targets: Vec<String>,
+
+    #[clap(value_name = "PATH", required_unless_present = "targets")]
     paths: Vec<PathArg>,
 }
 
+/// `buck2 lint`, which accepts both paths and target patterns, and only fails on errors.
+#[derive(Debug, clap::Parser)]
+#[clap(
+    name = "lint",
+    about = "Run the Starlark linter over build files and `.bzl` files.",
+    long_about = "Run the Starlark linter over build files and `.bzl` files.\n\n\
+    Arguments which exist on disk are linted as files or directories, \
+    anything else is treated as a target pattern whose build files are linted. \
+    The command only fails if a lint with error severity is found."
+)]
+pub struct LintCommand {
+    #[clap(flatten)]
+    common_opts: StarlarkCommandCommonOptions,
+
+    /// Print each lint as a JSON object on its own line.
+    #[clap(long)]
+    json: bool,
+
+    #[clap(value_name = "PATH_OR_TARGET_PATTERN", required = true)]
+    args: Vec<String>,
+}
+
+impl LintCommand {
+    pub fn exec(self, matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let lint = self.into_starlark_lint(&ctx.working_dir)?;
+        StarlarkOpaqueCommand::Lint(lint).exec(matches, ctx)
+    }
+
+    fn into_starlark_lint(self, working_dir: &WorkingDir) -> anyhow::Result<StarlarkLintCommand> {
+        let mut paths = Vec::new();
+        let mut targets = Vec::new();
+        for arg in self.args {
+            if working_dir.resolve(Path::new(&arg)).exists() {
+                paths.push(PathArg::from_str(&arg)?);
+            } else {
+                targets.push(arg);
+            }
+        }
+        Ok(StarlarkLintCommand {
+            common_opts: self.common_opts,
+            json: self.json,
+            fail_on: FailOn::Error,
+            targets,
+            paths,
+        })
+    }
+}
+
+/// The global names visible to a file, along with their hash for the lint cache.
+struct Names {
+    names: HashSet<String>,
+    hash: String,
+}
+
 /// The cache of names for a path, keyed by its CellName and its path type.
 struct Cache<'a> {
     dice: &'a DiceTransaction,
-    cached: HashMap<(CellName, StarlarkFileType), Arc<HashSet<String>>>,
+    cached: HashMap<(CellName, StarlarkFileType), Arc<Names>>,
 }
 
 impl<'a> Cache<'a> {
@@ -68,14 +153,16 @@ impl<'a> Cache<'a> {
     pub(crate) async fn get_names(
         &mut self,
         path: &StarlarkPath<'_>,
-    ) -> anyhow::Result<Arc<HashSet<String>>> {
+    ) -> anyhow::Result<Arc<Names>> {
         let path_type = path.file_type();
         let cell = path.cell();
         if let Some(res) = self.cached.get(&(cell, path_type)) {
             return Ok(res.dupe());
         }
         let env: Environment = Environment::new(cell, path_type, self.dice).await?;
-        let res = Arc::new(env.get_names(path_type, self.dice).await?);
+        let names = env.get_names(path_type, self.dice).await?;
+        let hash = globals_hash(&names);
+        let res = Arc::new(Names { names, hash });
         self.cached.insert((cell, path_type), res.dupe());
         Ok(res)
     }
@@ -86,6 +173,7 @@ async fn lint_file(
     cell_resolver: &CellResolver,
     io: &dyn IoProvider,
     cache: &mut Cache<'_>,
+    lint_cache: &mut LintCache,
 ) -> anyhow::Result<Vec<Lint>> {
     let dialect = path.file_type().dialect(false);
     let proj_path = cell_resolver.resolve_path(path.path().as_ref().as_ref())?;
@@ -94,8 +182,24 @@ async fn lint_file(
         .read_file_if_exists(proj_path)
         .await?
         .with_context(|| format!("File not found: `{}`", path_str))?;
-    match AstModule::parse(&path_str, content.clone(), &dialect) {
-        Ok(ast) => Ok(ast.lint(Some(&*cache.get_names(path).await?))),
+    let names = cache.get_names(path).await?;
+    if let Some(lints) = lint_cache.get(&path_str, &content, &names.hash) {
+        return Ok(lints);
+    }
+    let lints = lint_content(&path_str, &content, &dialect, &names.names);
+    lint_cache.insert(&path_str, &content, &names.hash, &lints);
+    Ok(lints)
+}
+
+/// A file which fails to parse gives a single `parse_error` lint, rather than failing the command.
+fn lint_content(
+    path_str: &str,
+    content: &str,
+    dialect: &Dialect,
+    names: &HashSet<String>,
+) -> Vec<Lint> {
+    match AstModule::parse(path_str, content.to_owned(), dialect) {
+        Ok(ast) => ast.lint(Some(names)),
         Err(err) => {
             let err: buck2_error::Error = err.into();
             // There was a parse error, so we don't want to fail, we want to give a nice error message
@@ -104,17 +208,36 @@ async fn lint_file(
                 None => (None, &err as &dyn std::fmt::Display),
                 Some(diag) => (diag.span.dupe(), &diag.message as &dyn std::fmt::Display),
             };
-            Ok(vec![Lint {
-                location: span.unwrap_or_else(|| FileSpan::new(path_str, content)),
+            vec![Lint {
+                location: span
+                    .unwrap_or_else(|| FileSpan::new(path_str.to_owned(), content.to_owned())),
                 short_name: "parse_error".to_owned(),
                 severity: EvalSeverity::Error,
                 problem: format!("{:#}", message),
                 original: "".to_owned(),
-            }])
+            }]
         }
     }
 }
 
+/// Lints are only reused by the same buck2 binary. Release builds are identified by their
+/// revision; other builds by the daemon, since rebuilding buck2 restarts it.
+fn linter_id() -> String {
+    match buck2_build_info::revision() {
+        Some(revision) => revision.to_owned(),
+        None => DAEMON_UUID.to_string(),
+    }
+}
+
+/// Lint results are cached under the isolation dir, next to the other daemon caches.
+fn lint_cache_path(server_ctx: &dyn ServerCommandContextTrait) -> AbsNormPathBuf {
+    server_ctx.project_root().resolve(
+        &InvocationPaths::buck_out_dir_prefix()
+            .join(server_ctx.isolation_prefix())
+            .join(ForwardRelativePath::unchecked_new("cache/lint/lints.json")),
+    )
+}
+
 #[async_trait]
 impl StarlarkOpaqueSubcommand for StarlarkLintCommand {
     async fn server_execute(
@@ -124,25 +247,76 @@ impl StarlarkOpaqueSubcommand for StarlarkLintCommand {
         _client_ctx: ClientContext,
     ) -> anyhow::Result<()> {
         server_ctx
-            .with_dice_ctx(async move |server_ctx, ctx| {
+            .with_dice_ctx(async move |server_ctx, mut ctx| {
                 let cell_resolver = ctx.get_cell_resolver().await?;
+                let pattern_files = starlark_files_for_patterns(
+                    &self.targets,
+                    server_ctx,
+                    &mut ctx,
+                    &cell_resolver,
+                )
+                .await?;
                 let fs = ctx.file_ops();
                 let io = ctx.global_data().get_io_provider();
-                let mut cache = Cache::new(&ctx);
 
+                let mut files =
+                    starlark_files(&self.paths, server_ctx, &cell_resolver, &fs, &*io).await?;
+                for file in pattern_files {
+                    if !files.contains(&file) {
+                        files.push(file);
+                    }
+                }
+
+                let mut cache = Cache::new(&ctx);
+                let mut lint_cache = LintCache::load(lint_cache_path(server_ctx), &linter_id());
                 let mut stdout = stdout.as_writer();
                 let mut lint_count = 0;
-                let files =
-                    starlark_files(&self.paths, server_ctx, &cell_resolver, &fs, &*io).await?;
+                let mut failures = 0;
                 for file in &files {
-                    let lints = lint_file(&file.borrow(), &cell_resolver, &*io, &mut cache).await?;
+                    let lints = lint_file(
+                        &file.borrow(),
+                        &cell_resolver,
+                        &*io,
+                        &mut cache,
+                        &mut lint_cache,
+                    )
+                    .await?;
                     lint_count += lints.len();
                     for lint in lints {
-                        writeln!(stdout, "{}", lint)?;
+                        if self.fail_on.fails(lint.severity) {
+                            failures += 1;
+                        }
+                        if self.json {
+                            let message = LintMessage::new(EvalMessage::from(lint));
+                            writeln!(stdout, "{}", serde_json::to_string(&message)?)?;
+                        } else {
+                            writeln!(stdout, "{}", lint)?;
+                        }
                     }
                 }
-                if lint_count > 0 {
-                    Err(anyhow::anyhow!("Found {} lints", lint_count))
+                let project_root = server_ctx.project_root();
+                lint_cache.prune(|path| {
+                    ProjectRelativePath::new(path)
+                        .map_or(false, |path| project_root.resolve(path).exists())
+                });
+                // The cache is only an optimisation, so failing to write it shouldn't fail the lint.
+                if let Err(e) = lint_cache.save() {
+                    writeln!(server_ctx.stderr()?, "Failed to save lint cache: {:#}", e)?;
+                }
+                if failures > 0 {
+                    Err(anyhow::anyhow!(
+                        "Found {} lints ({} failing)",
+                        lint_count,
+                        failures
+                    ))
+                } else if lint_count > 0 {
+                    writeln!(
+                        server_ctx.stderr()?,
+                        "Found {} lints in {} files, none failing",
+                        lint_count,
+                        files.len()
+                    )?;
+                    Ok(())
                 } else {
                     writeln!(
                         server_ctx.stderr()?,
@@ -159,3 +333,76 @@ impl StarlarkOpaqueSubcommand for StarlarkLintCommand {
         &self.common_opts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_client_ctx::json_schema::JsonOutput;
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_lint_json_matches_schema() -> anyhow::Result<()> {
+        let path = "foo/BUCK";
+        let ast = AstModule::parse(path, "x = 1\nx = 2\n".to_owned(), &Dialect::Extended)?;
+        let mut lints = ast.lint(None);
+        assert!(!lints.is_empty());
+        // What a parse error is reported as.
+        lints.push(Lint {
+            location: FileSpan::new(path.to_owned(), "x = (".to_owned()),
+            short_name: "parse_error".to_owned(),
+            severity: EvalSeverity::Error,
+            problem: "Parse error".to_owned(),
+            original: "".to_owned(),
+        });
+
+        for lint in lints {
+            let message = LintMessage::new(EvalMessage::from(lint));
+            JsonOutput::Lint.validate(&serde_json::to_value(&message)?)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_splits_paths_from_target_patterns() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        std::fs::create_dir(tempdir.path().join("foo"))?;
+        std::fs::write(tempdir.path().join("foo/BUCK"), "")?;
+        std::fs::write(tempdir.path().join("defs.bzl"), "")?;
+        let working_dir =
+            WorkingDir::unchecked_new(AbsNormPathBuf::new(tempdir.path().to_owned())?);
+
+        let lint =
+            LintCommand::try_parse_from(["lint", "foo/BUCK", "//bar:", "defs.bzl", "--json"])?
+                .into_starlark_lint(&working_dir)?;
+
+        assert_eq!(
+            vec![
+                PathArg::from_str("foo/BUCK")?,
+                PathArg::from_str("defs.bzl")?
+            ],
+            lint.paths
+        );
+        assert_eq!(vec!["//bar:".to_owned()], lint.targets);
+        assert!(lint.json);
+        // `buck2 lint` only fails on errors.
+        assert_eq!(FailOn::Error, lint.fail_on);
+        assert!(!lint.fail_on.fails(EvalSeverity::Warning));
+        assert!(lint.fail_on.fails(EvalSeverity::Error));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_content() {
+        let names = HashSet::from(["glob".to_owned()]);
+
+        let lints = lint_content("foo/BUCK", "x = 1\nx = 2\n", &Dialect::Extended, &names);
+        assert!(!lints.is_empty());
+        assert!(lints.iter().all(|l| l.short_name != "parse_error"));
+
+        let lints = lint_content("foo/BUCK", "x = (\ny = [\n", &Dialect::Extended, &names);
+        assert_eq!(1, lints.len());
+        assert_eq!("parse_error", lints[0].short_name);
+        assert!(matches!(lints[0].severity, EvalSeverity::Error));
+    }
+}
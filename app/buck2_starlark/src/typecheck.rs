@@ -9,6 +9,7 @@
 
 use std::collections::HashMap;
 use std::io::Write;
+use std::path::Path;
 
 use anyhow::Context;
 use async_recursion::async_recursion;
@@ -30,11 +31,15 @@ use buck2_server_ctx::ctx::ServerCommandDiceContext;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use dice::DiceTransaction;
 use dupe::Dupe;
+use starlark::analysis::EvalSeverity;
 use starlark::environment::Globals;
+use starlark::errors::EvalMessage;
+use starlark::typing::Approximation;
 use starlark::typing::AstModuleTypecheck;
 use starlark::typing::Interface;
 
 use crate::util::environment::Environment;
+use crate::util::fail_on::FailOn;
 use crate::util::paths::starlark_files;
 use crate::StarlarkCommandCommonOptions;
 use crate::StarlarkOpaqueSubcommand;
@@ -45,10 +50,63 @@ pub struct StarlarkTypecheckCommand {
     #[clap(flatten)]
     common_opts: StarlarkCommandCommonOptions,
 
+    /// The least severe kind of message which causes the command to fail.
+    /// Approximations, where the typechecker gave up on part of a file, are reported as advice.
+    #[clap(long, arg_enum, default_value = "error")]
+    fail_on: FailOn,
+
     #[clap(value_name = "PATH", required = true)]
     paths: Vec<PathArg>,
 }
 
+fn approximation_message(path: &str, approximation: Approximation) -> EvalMessage {
+    EvalMessage {
+        path: path.to_owned(),
+        span: None,
+        severity: EvalSeverity::Advice,
+        name: "approximation".to_owned(),
+        description: approximation.to_string(),
+        full_error_with_span: None,
+        original: None,
+    }
+}
+
+/// What the typechecker reported.
+#[derive(Debug, Default)]
+struct Counts {
+    files: usize,
+    /// Type errors, whatever `--fail-on` is.
+    errors: usize,
+    /// Messages at least as severe as `--fail-on`, which may include warnings and advice.
+    failures: usize,
+}
+
+impl Counts {
+    fn record(&mut self, severity: EvalSeverity, fail_on: FailOn) {
+        if matches!(severity, EvalSeverity::Error) {
+            self.errors += 1;
+        }
+        if fail_on.fails(severity) {
+            self.failures += 1;
+        }
+    }
+
+    /// The summary to print, or the error to fail the command with.
+    fn outcome(&self) -> anyhow::Result<String> {
+        let summary = format!("{} type errors in {} files", self.errors, self.files);
+        let others = self.failures.saturating_sub(self.errors);
+        if self.failures == 0 {
+            Ok(format!("Found {summary}"))
+        } else if others == 0 {
+            Err(anyhow::anyhow!("Detected {summary}"))
+        } else {
+            Err(anyhow::anyhow!(
+                "Detected {summary}, and {others} other messages at or above the `--fail-on` severity"
+            ))
+        }
+    }
+}
+
 struct Cache<'a> {
     // Things we have access to get information
     dice: &'a DiceTransaction,
@@ -57,7 +115,9 @@ struct Cache<'a> {
     // Things we have access to write information
     stdout: &'a mut (dyn Write + Send + Sync),
     stderr: &'a mut (dyn Write + Send + Sync),
+    fail_on: FailOn,
     // Our accumulated state
+    counts: Counts,
     oracle: HashMap<(CellName, StarlarkFileType), Globals>,
     cache: HashMap<OwnedStarlarkModulePath, Interface>,
 }
@@ -98,6 +158,7 @@ impl<'a> Cache<'a> {
     async fn run(&mut self, path: OwnedStarlarkPath) -> anyhow::Result<Interface> {
         let path_ref = path.borrow();
         writeln!(self.stderr, "Type checking: {path_ref}")?;
+        self.counts.files += 1;
         let proj_path = self
             .cell_resolver
             .resolve_path(path_ref.path().as_ref().as_ref())?;
@@ -123,27 +184,24 @@ impl<'a> Cache<'a> {
         let globals = self
             .get_oracle(path_ref.cell(), path_ref.file_type())
             .await?;
-        let (errors, bindings, interface, approxiomations) = ast.typecheck(&globals, &loads);
-
-        if !approxiomations.is_empty() {
-            writeln!(self.stderr, "\n\nAPPROXIMATIONS:")?;
-            for x in approxiomations {
-                writeln!(self.stderr, "{x}")?;
-            }
-        }
+        let (errors, bindings, interface, approximations) = ast.typecheck(&globals, &loads);
 
         writeln!(self.stderr, "\n\nBINDINGS:\n{bindings}")?;
 
-        let errors_count = errors.len();
-        if errors_count == 0 {
-            Ok(interface)
-        } else {
-            writeln!(self.stdout, "\n\nERRORS:")?;
-            for x in errors {
-                writeln!(self.stdout, "{x}")?;
-            }
-            Err(anyhow::anyhow!("Detected {errors_count} errors"))
+        // Report problems rather than failing, so that files loading this one are still checked.
+        let messages = errors
+            .iter()
+            .map(|e| EvalMessage::from_anyhow(Path::new(&path_str), e))
+            .chain(
+                approximations
+                    .into_iter()
+                    .map(|x| approximation_message(&path_str, x)),
+            );
+        for x in messages {
+            self.counts.record(x.severity, self.fail_on);
+            writeln!(self.stdout, "{x}")?;
         }
+        Ok(interface)
     }
 }
 
@@ -171,14 +229,16 @@ impl StarlarkOpaqueSubcommand for StarlarkTypecheckCommand {
                     cell_resolver: &cell_resolver,
                     stdout: &mut stdout,
                     stderr: &mut stderr,
+                    fail_on: self.fail_on,
+                    counts: Counts::default(),
                     oracle: HashMap::new(),
                     cache: HashMap::new(),
                 };
                 for file in files {
                     cache.typecheck(file).await?;
                 }
-                let file_count = cache.cache.len();
-                writeln!(stderr, "Found no type errors in {file_count} files")?;
+                let summary = cache.counts.outcome()?;
+                writeln!(stderr, "{summary}")?;
                 Ok(())
             })
             .await
@@ -188,3 +248,53 @@ impl StarlarkOpaqueSubcommand for StarlarkTypecheckCommand {
         &self.common_opts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(severities: &[EvalSeverity], fail_on: FailOn) -> Counts {
+        let mut counts = Counts {
+            files: 2,
+            ..Default::default()
+        };
+        for severity in severities {
+            counts.record(*severity, fail_on);
+        }
+        counts
+    }
+
+    #[test]
+    fn test_counts_only_errors_as_type_errors() {
+        let severities = [
+            EvalSeverity::Error,
+            EvalSeverity::Advice,
+            EvalSeverity::Advice,
+        ];
+
+        assert_eq!(
+            counts(&severities, FailOn::Error)
+                .outcome()
+                .unwrap_err()
+                .to_string(),
+            "Detected 1 type errors in 2 files"
+        );
+        assert_eq!(
+            counts(&severities, FailOn::Advice)
+                .outcome()
+                .unwrap_err()
+                .to_string(),
+            "Detected 1 type errors in 2 files, and 2 other messages at or above the `--fail-on` severity"
+        );
+        assert_eq!(
+            counts(&severities, FailOn::Never).outcome().unwrap(),
+            "Found 1 type errors in 2 files"
+        );
+        assert_eq!(
+            counts(&[EvalSeverity::Advice], FailOn::Error)
+                .outcome()
+                .unwrap(),
+            "Found 0 type errors in 2 files"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use dupe::Dupe;
+use starlark::analysis::EvalSeverity;
+
+/// The least severe kind of message which causes a command to fail.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Dupe,
+    PartialEq,
+    Eq,
+    clap::ArgEnum,
+    serde::Serialize,
+    serde::Deserialize
+)]
+#[clap(rename_all = "snake_case")]
+pub(crate) enum FailOn {
+    Error,
+    Warning,
+    Advice,
+    Never,
+}
+
+impl FailOn {
+    pub(crate) fn fails(self, severity: EvalSeverity) -> bool {
+        match (self, severity) {
+            (FailOn::Never, _) | (_, EvalSeverity::Disabled) => false,
+            (FailOn::Advice, _) => true,
+            (FailOn::Warning, severity) => {
+                matches!(severity, EvalSeverity::Error | EvalSeverity::Warning)
+            }
+            (FailOn::Error, severity) => matches!(severity, EvalSeverity::Error),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! An on-disk cache of lint results, so that linting a large tree only re-lints the files
+//! which changed since the last run.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+
+use anyhow::Context;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use itertools::Itertools;
+use starlark::codemap::CodeMap;
+use starlark::codemap::Pos;
+use starlark::codemap::Span;
+use starlark::errors::EvalSeverity;
+use starlark::errors::Lint;
+
+/// Bump whenever the on-disk format changes in an incompatible way.
+const LINT_CACHE_VERSION: u32 = 2;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CachedLint {
+    begin: u32,
+    end: u32,
+    short_name: String,
+    severity: EvalSeverity,
+    problem: String,
+    original: String,
+}
+
+/// The lints of a single file, valid for as long as the file content and the globals match.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CachedFile {
+    content_hash: String,
+    globals_hash: String,
+    lints: Vec<CachedLint>,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct LintCacheData {
+    version: u32,
+    /// Identifies the linter which produced the lints, so that a different buck2 binary,
+    /// which may have different lints, never reuses them.
+    linter: String,
+    files: HashMap<String, CachedFile>,
+}
+
+/// Lint results keyed by file path, each entry recording the content and globals hashes
+/// it was computed with. An entry is only reused if both hashes match, and the whole cache
+/// is discarded if it was written by a different linter.
+pub(crate) struct LintCache {
+    path: AbsNormPathBuf,
+    data: LintCacheData,
+    dirty: bool,
+}
+
+fn content_hash(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
+
+/// The hash of the set of global names, since the `names` lint depends on it.
+pub(crate) fn globals_hash(globals: &HashSet<String>) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for name in globals.iter().sorted() {
+        hasher.update(name.as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+impl LintCache {
+    /// Load the cache from `path`. A missing or unreadable cache, or one written by a linter
+    /// other than `linter`, is treated as empty.
+    pub(crate) fn load(path: AbsNormPathBuf, linter: &str) -> LintCache {
+        let data = fs_util::read_to_string_if_exists(&path)
+            .ok()
+            .flatten()
+            .and_then(|s| serde_json::from_str::<LintCacheData>(&s).ok())
+            .filter(|data| data.version == LINT_CACHE_VERSION && data.linter == linter)
+            .unwrap_or_else(|| LintCacheData {
+                version: LINT_CACHE_VERSION,
+                linter: linter.to_owned(),
+                files: HashMap::new(),
+            });
+        LintCache {
+            path,
+            data,
+            dirty: false,
+        }
+    }
+
+    /// The cached lints for `path`, if it was last linted with the same content and globals.
+    pub(crate) fn get(&self, path: &str, content: &str, globals_hash: &str) -> Option<Vec<Lint>> {
+        let entry = self.data.files.get(path)?;
+        if entry.globals_hash != globals_hash || entry.content_hash != content_hash(content) {
+            return None;
+        }
+        let codemap = CodeMap::new(path.to_owned(), content.to_owned());
+        let len = content.len() as u32;
+        entry
+            .lints
+            .iter()
+            .map(|lint| {
+                if lint.begin > lint.end || lint.end > len {
+                    return None;
+                }
+                Some(Lint {
+                    location: codemap
+                        .file_span(Span::new(Pos::new(lint.begin), Pos::new(lint.end))),
+                    short_name: lint.short_name.clone(),
+                    severity: lint.severity,
+                    problem: lint.problem.clone(),
+                    original: lint.original.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Record the lints for `path`, replacing any previous entry for it.
+    pub(crate) fn insert(&mut self, path: &str, content: &str, globals_hash: &str, lints: &[Lint]) {
+        let lints = lints
+            .iter()
+            .map(|lint| CachedLint {
+                begin: lint.location.span.begin().get(),
+                end: lint.location.span.end().get(),
+                short_name: lint.short_name.clone(),
+                severity: lint.severity,
+                problem: lint.problem.clone(),
+                original: lint.original.clone(),
+            })
+            .collect();
+        self.data.files.insert(
+            path.to_owned(),
+            CachedFile {
+                content_hash: content_hash(content),
+                globals_hash: globals_hash.to_owned(),
+                lints,
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// Drop the entries for files which no longer exist, so the cache doesn't grow forever.
+    pub(crate) fn prune(&mut self, exists: impl Fn(&str) -> bool) {
+        let before = self.data.files.len();
+        self.data.files.retain(|path, _| exists(path));
+        if self.data.files.len() != before {
+            self.dirty = true;
+        }
+    }
+
+    /// Write the cache back to disk if anything changed.
+    pub(crate) fn save(&mut self) -> anyhow::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let dir = self
+            .path
+            .parent()
+            .context("Lint cache path has no parent")?;
+        fs_util::create_dir_all(dir)?;
+        // Write to a uniquely named temporary file and rename, so a concurrent lint neither reads
+        // a partial cache nor writes to the same temporary file.
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        tmp.write_all(serde_json::to_string(&self.data)?.as_bytes())?;
+        tmp.persist(&self.path)
+            .with_context(|| format!("Writing lint cache to `{}`", self.path))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(codemap: &CodeMap, begin: u32, end: u32, problem: &str) -> Lint {
+        Lint {
+            location: codemap.file_span(Span::new(Pos::new(begin), Pos::new(end))),
+            short_name: "test".to_owned(),
+            severity: EvalSeverity::Warning,
+            problem: problem.to_owned(),
+            original: "".to_owned(),
+        }
+    }
+
+    fn render(lints: &[Lint]) -> Vec<String> {
+        lints.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_lint_cache_hit_and_invalidation() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let cache_path = AbsNormPathBuf::new(tempdir.path().join("cache").join("lint.json"))?;
+        let globals = globals_hash(&HashSet::from(["glob".to_owned()]));
+
+        let a = "x = 1\ny = 2\n";
+        let b = "z = 3\n";
+        let a_lints = vec![lint(
+            &CodeMap::new("a.bzl".to_owned(), a.to_owned()),
+            6,
+            11,
+            "a",
+        )];
+        let b_lints = vec![lint(
+            &CodeMap::new("b.bzl".to_owned(), b.to_owned()),
+            0,
+            1,
+            "b",
+        )];
+
+        let mut cache = LintCache::load(cache_path.clone(), "linter");
+        assert!(cache.get("a.bzl", a, &globals).is_none());
+        cache.insert("a.bzl", a, &globals, &a_lints);
+        cache.insert("b.bzl", b, &globals, &b_lints);
+        cache.save()?;
+
+        // A second run with no edits hits the cache and renders identically.
+        let mut cache = LintCache::load(cache_path.clone(), "linter");
+        assert_eq!(
+            render(&cache.get("a.bzl", a, &globals).unwrap()),
+            render(&a_lints)
+        );
+        assert_eq!(
+            render(&cache.get("b.bzl", b, &globals).unwrap()),
+            render(&b_lints)
+        );
+
+        // Editing a file only invalidates that file's entry.
+        let a_edited = "x = 1\ny = 3\n";
+        assert!(cache.get("a.bzl", a_edited, &globals).is_none());
+        assert!(cache.get("b.bzl", b, &globals).is_some());
+        cache.insert("a.bzl", a_edited, &globals, &[]);
+        cache.save()?;
+
+        let cache = LintCache::load(cache_path, "linter");
+        assert!(cache.get("a.bzl", a, &globals).is_none());
+        assert_eq!(cache.get("a.bzl", a_edited, &globals).unwrap().len(), 0);
+        assert!(cache.get("b.bzl", b, &globals).is_some());
+
+        // Changing the globals invalidates everything.
+        let other_globals = globals_hash(&HashSet::from(["glob".to_owned(), "other".to_owned()]));
+        assert!(cache.get("b.bzl", b, &other_globals).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_cache_ignores_corrupt_file() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let cache_path = AbsNormPathBuf::new(tempdir.path().join("lint.json"))?;
+        fs_util::write(&cache_path, "not json")?;
+        let cache = LintCache::load(cache_path, "linter");
+        assert!(
+            cache
+                .get("a.bzl", "", &globals_hash(&HashSet::new()))
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_cache_discarded_for_other_linter() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let cache_path = AbsNormPathBuf::new(tempdir.path().join("lint.json"))?;
+        let globals = globals_hash(&HashSet::new());
+
+        let mut cache = LintCache::load(cache_path.clone(), "old");
+        cache.insert("a.bzl", "x = 1\n", &globals, &[]);
+        cache.save()?;
+
+        assert!(
+            LintCache::load(cache_path.clone(), "old")
+                .get("a.bzl", "x = 1\n", &globals)
+                .is_some()
+        );
+        assert!(
+            LintCache::load(cache_path, "new")
+                .get("a.bzl", "x = 1\n", &globals)
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_cache_prune() -> anyhow::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let cache_path = AbsNormPathBuf::new(tempdir.path().join("lint.json"))?;
+        let globals = globals_hash(&HashSet::new());
+
+        let mut cache = LintCache::load(cache_path.clone(), "linter");
+        cache.insert("kept.bzl", "", &globals, &[]);
+        cache.insert("deleted.bzl", "", &globals, &[]);
+        cache.save()?;
+
+        let mut cache = LintCache::load(cache_path.clone(), "linter");
+        cache.prune(|path| path == "kept.bzl");
+        cache.save()?;
+
+        let cache = LintCache::load(cache_path, "linter");
+        assert!(cache.get("kept.bzl", "", &globals).is_some());
+        assert!(cache.get("deleted.bzl", "", &globals).is_none());
+        // Only the cache itself is left in the directory, with no temporary files.
+        assert_eq!(std::fs::read_dir(tempdir.path())?.count(), 1);
+        Ok(())
+    }
+}
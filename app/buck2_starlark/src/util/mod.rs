@@ -8,4 +8,6 @@
  */
 
 pub(crate) mod environment;
+pub(crate) mod fail_on;
+pub(crate) mod lint_cache;
 pub(crate) mod paths;
@@ -11,20 +11,26 @@ use std::ops::Deref;
 
 use async_recursion::async_recursion;
 use buck2_client_ctx::path_arg::PathArg;
+use buck2_common::dice::file_ops::HasFileOps;
 use buck2_common::file_ops::FileOps;
 use buck2_common::file_ops::FileType;
 use buck2_common::file_ops::RawPathMetadata;
 use buck2_common::io::IoProvider;
+use buck2_common::package_listing::dice::HasPackageListingResolver;
+use buck2_common::pattern::resolve::resolve_target_patterns;
 use buck2_core::build_file_path::BuildFilePath;
 use buck2_core::bzl::ImportPath;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::paths::file_name::FileName;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_core::package::PackageLabel;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
 use buck2_interpreter::paths::bxl::BxlFilePath;
 use buck2_interpreter::paths::package::PackageFilePath;
 use buck2_interpreter::paths::path::OwnedStarlarkPath;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::pattern::PatternParser;
+use dice::DiceComputations;
 use dupe::Dupe;
 
 #[derive(Debug, buck2_error::Error)]
@@ -139,3 +145,32 @@ pub(crate) async fn starlark_files(
     }
     Ok(files)
 }
+
+/// Find the build files of the packages matched by target patterns.
+pub(crate) async fn starlark_files_for_patterns(
+    patterns: &[String],
+    context: &dyn ServerCommandContextTrait,
+    dice: &mut DiceComputations,
+    cell_resolver: &CellResolver,
+) -> anyhow::Result<Vec<OwnedStarlarkPath>> {
+    if patterns.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let parser = PatternParser::new(dice, context.working_dir()).await?;
+    let parsed = patterns
+        .iter()
+        .map(|x| parser.parse_pattern::<TargetPatternExtra>(x))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let resolved = resolve_target_patterns(cell_resolver, &parsed, &dice.file_ops()).await?;
+
+    let mut files = Vec::new();
+    for package in resolved.specs.into_keys() {
+        let listing = dice.resolve_package_listing(package.dupe()).await?;
+        files.push(OwnedStarlarkPath::BuildFile(BuildFilePath::new(
+            package,
+            listing.buildfile().to_owned(),
+        )));
+    }
+    Ok(files)
+}
@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::PathBuf;
@@ -102,6 +104,9 @@ pub(crate) struct TestReport {
 
 struct TestOutcome {
     errors: Vec<buck2_data::ErrorReport>,
+    build_failed_targets: Vec<String>,
+    /// The unconfigured labels of `build_failed_targets`, for `--rerun-failed`.
+    build_failed_unconfigured_targets: BTreeSet<String>,
     executor_report: ExecutorReport,
     executor_stdout: String,
     executor_stderr: String,
@@ -126,13 +131,34 @@ struct ExecutorReport {
     exit_code: Option<i32>,
     statuses: TestStatuses,
     info_messages: Vec<String>,
+    /// Test targets with at least one test which did not succeed, for `--rerun-failed`, with the
+    /// names of those tests. Targets whose listing failed have no names.
+    failed_tests: BTreeMap<String, BTreeSet<String>>,
 }
 
 impl ExecutorReport {
-    fn ingest(&mut self, status: &ExecutorMessage) {
+    fn ingest(&mut self, status: &ExecutorMessage, session: &TestSession) {
         match status {
             ExecutorMessage::TestResult(res) => {
                 self.statuses.ingest(res);
+                if matches!(
+                    res.status,
+                    TestStatus::FAIL
+                        | TestStatus::FATAL
+                        | TestStatus::TIMEOUT
+                        | TestStatus::LISTING_FAILED
+                ) {
+                    // The handle came from this session, so this is not expected to fail.
+                    if let Ok(label) = session.get(res.target) {
+                        let names = self
+                            .failed_tests
+                            .entry(label.target().unconfigured().to_string())
+                            .or_default();
+                        if res.status != TestStatus::LISTING_FAILED {
+                            names.insert(res.name.clone());
+                        }
+                    }
+                }
             }
             ExecutorMessage::ExitCode(exit_code) => {
                 self.exit_code = Some(*exit_code);
@@ -260,8 +286,6 @@ async fn test(
     mut ctx: DiceTransaction,
     request: &TestRequest,
 ) -> anyhow::Result<TestResponse> {
-    // TODO (torozco): Should the --fail-fast flag work here?
-
     let cwd = server_ctx.working_dir();
     let cell_resolver = ctx.get_cell_resolver().await?;
     let working_dir_cell = cell_resolver.find(cwd)?;
@@ -337,6 +361,7 @@ async fn test(
         working_dir_cell,
         build_opts.skip_incompatible_targets,
         MissingTargetBehavior::from_skip(build_opts.skip_missing_targets),
+        build_opts.fail_fast,
     )
     .await?;
 
@@ -391,10 +416,29 @@ async fn test(
     Ok(TestResponse {
         exit_code,
         errors: test_outcome.errors,
+        build_failed_targets: test_outcome.build_failed_targets,
         test_statuses: Some(test_statuses),
         executor_stdout: test_outcome.executor_stdout,
         executor_stderr: test_outcome.executor_stderr,
         executor_info_messages: test_outcome.executor_report.info_messages,
+        failed_targets: test_outcome
+            .executor_report
+            .failed_tests
+            .keys()
+            .chain(&test_outcome.build_failed_unconfigured_targets)
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+        failed_tests: test_outcome
+            .executor_report
+            .failed_tests
+            .into_iter()
+            .map(|(target, names)| buck2_cli_proto::FailedTests {
+                target,
+                names: names.into_iter().collect(),
+            })
+            .collect(),
     })
 }
 
@@ -410,6 +454,7 @@ async fn test_targets(
     working_dir_cell: CellName,
     skip_incompatible_targets: bool,
     missing_target_behavior: MissingTargetBehavior,
+    fail_fast: bool,
 ) -> anyhow::Result<TestOutcome> {
     let session = Arc::new(session);
     let (liveliness_observer, _guard) = LivelinessGuard::create();
@@ -480,6 +525,7 @@ async fn test_targets(
                     cell_resolver: &cell_resolver,
                     working_dir_cell,
                     missing_target_behavior,
+                    fail_fast,
                 });
 
                 driver.push_pattern(
@@ -511,7 +557,7 @@ async fn test_targets(
 
                 let test_statuses = test_status_receiver
                     .try_fold(ExecutorReport::default(), |mut acc, result| {
-                        acc.ingest(&result);
+                        acc.ingest(&result, &session);
                         future::ready(Ok(acc))
                     })
                     .await
@@ -532,7 +578,11 @@ async fn test_targets(
 
                 // And finally return our results;
 
-                anyhow::Ok((driver.build_errors, test_statuses))
+                anyhow::Ok((
+                    driver.build_errors,
+                    driver.build_failed_targets,
+                    test_statuses,
+                ))
             },
         )
     });
@@ -556,7 +606,7 @@ async fn test_targets(
     )));
 
     // TODO(bobyf, torozco) we can use cancellation handle here instead of liveliness observer
-    let (build_errors, executor_report) = test_server
+    let (build_errors, build_failed_targets, executor_report) = test_server
         .await
         .context("Failed to collect executor report")??;
 
@@ -566,8 +616,20 @@ async fn test_targets(
         .unique_by(|e| e.message.clone())
         .collect();
 
+    let build_failed_unconfigured_targets = build_failed_targets
+        .iter()
+        .map(|label| label.target().unconfigured().to_string())
+        .collect();
+    let build_failed_targets = build_failed_targets
+        .iter()
+        .map(|label| label.to_string())
+        .sorted()
+        .collect();
+
     Ok(TestOutcome {
         errors,
+        build_failed_targets,
+        build_failed_unconfigured_targets,
         executor_stdout: executor_output.stdout,
         executor_stderr: executor_output.stderr,
         executor_report,
@@ -599,15 +661,27 @@ pub(crate) struct TestDriverState<'a, 'e> {
     cell_resolver: &'a CellResolver,
     working_dir_cell: CellName,
     missing_target_behavior: MissingTargetBehavior,
+    /// Stop scheduling new work after the first error, rather than testing everything that
+    /// can still be built.
+    fail_fast: bool,
 }
 
+/// Result of a unit of work in the test driver. Errors from building or analysing a
+/// target are attributed to it, so that it can be reported as not tested.
+type TestDriverResult = (
+    Option<ConfiguredProvidersLabel>,
+    anyhow::Result<Vec<TestDriverTask>>,
+);
+
 /// Maintains the state of an ongoing test execution.
 struct TestDriver<'a, 'e> {
     state: TestDriverState<'a, 'e>,
-    work: FuturesUnordered<BoxFuture<'a, anyhow::Result<Vec<TestDriverTask>>>>,
+    work: FuturesUnordered<BoxFuture<'a, TestDriverResult>>,
     labels_configured: HashSet<(ProvidersLabel, bool)>,
     labels_tested: HashSet<ConfiguredProvidersLabel>,
     build_errors: Vec<buck2_error::Error>,
+    /// Test targets which failed to build, and so were not run.
+    build_failed_targets: Vec<ConfiguredProvidersLabel>,
 }
 
 impl<'a, 'e> TestDriver<'a, 'e> {
@@ -618,6 +692,7 @@ impl<'a, 'e> TestDriver<'a, 'e> {
             labels_configured: HashSet::new(),
             labels_tested: HashSet::new(),
             build_errors: Vec::new(),
+            build_failed_targets: Vec::new(),
         }
     }
 
@@ -628,11 +703,14 @@ impl<'a, 'e> TestDriver<'a, 'e> {
         skip_incompatible_targets: bool,
     ) {
         for (package, spec) in pattern.specs.into_iter() {
-            let fut = future::ready(anyhow::Ok(vec![TestDriverTask::InterpretTarget {
-                package,
-                spec,
-                skip_incompatible_targets,
-            }]))
+            let fut = future::ready((
+                None,
+                anyhow::Ok(vec![TestDriverTask::InterpretTarget {
+                    package,
+                    spec,
+                    skip_incompatible_targets,
+                }]),
+            ))
             .boxed();
 
             self.work.push(fut);
@@ -641,7 +719,7 @@ impl<'a, 'e> TestDriver<'a, 'e> {
 
     /// Drive the test loop until all work is complete.
     async fn drive_to_completion(&mut self) {
-        while let Some(tasks) = self.work.next().await {
+        while let Some((label, tasks)) = self.work.next().await {
             match tasks {
                 Ok(tasks) => {
                     for task in tasks {
@@ -664,6 +742,12 @@ impl<'a, 'e> TestDriver<'a, 'e> {
                 }
                 Err(e) => {
                     self.build_errors.push(e.into());
+                    self.build_failed_targets.extend(label);
+                    if self.state.fail_fast {
+                        // Dropping the outstanding work cancels it.
+                        self.work.clear();
+                        break;
+                    }
                 }
             }
         }
@@ -698,6 +782,7 @@ impl<'a, 'e> TestDriver<'a, 'e> {
 
                 anyhow::Ok(work)
             }
+            .map(|res| (None, res))
             .boxed(),
         );
     }
@@ -750,6 +835,7 @@ impl<'a, 'e> TestDriver<'a, 'e> {
 
             anyhow::Ok(work)
         }
+        .map(|res| (None, res))
         .boxed();
 
         self.work.push(fut);
@@ -762,17 +848,17 @@ impl<'a, 'e> TestDriver<'a, 'e> {
 
         let state = self.state;
         let fut = async move {
-            test_target(
+            let res = test_target(
                 state.ctx,
-                label,
+                label.clone(),
                 state.test_executor.dupe(),
                 state.session,
                 state.label_filtering.dupe(),
                 state.cell_resolver,
                 state.working_dir_cell,
             )
-            .await?;
-            anyhow::Ok(vec![])
+            .await;
+            attribute_test_target_error(label, res.map(|_| vec![]))
         }
         .boxed();
 
@@ -780,6 +866,26 @@ impl<'a, 'e> TestDriver<'a, 'e> {
     }
 }
 
+/// Error from testing a single target, split by the stage that failed.
+enum TestTargetError {
+    /// The target, or one of its dependencies, failed to analyse or build.
+    Build(anyhow::Error),
+    /// The target was built, but its tests could not be run.
+    Test(anyhow::Error),
+}
+
+/// Only build and analysis failures mark the target as not tested due to a build failure.
+fn attribute_test_target_error<L, T>(
+    label: L,
+    res: Result<T, TestTargetError>,
+) -> (Option<L>, anyhow::Result<T>) {
+    match res {
+        Ok(v) => (None, Ok(v)),
+        Err(TestTargetError::Build(e)) => (Some(label), Err(e)),
+        Err(TestTargetError::Test(e)) => (None, Err(e)),
+    }
+}
+
 struct SpecTargets {
     labels: Vec<(TargetName, ProvidersPatternExtra)>,
     /// Indicates whether this should be skipped if incompatible.
@@ -824,13 +930,19 @@ async fn test_target(
     label_filtering: Arc<TestLabelFiltering>,
     cell_resolver: &CellResolver,
     working_dir_cell: CellName,
-) -> anyhow::Result<Option<ConfiguredProvidersLabel>> {
+) -> Result<Option<ConfiguredProvidersLabel>, TestTargetError> {
     // NOTE: We fail if we hit an incompatible target here. This can happen if we reach an
     // incompatible target via `tests = [...]`. This should perhaps change, but that's how it works
     // in v1: https://fb.workplace.com/groups/buckeng/posts/8520953297953210
-    let frozen_providers = ctx.get_providers(&target).await?.require_compatible()?;
+    let frozen_providers = ctx
+        .get_providers(&target)
+        .await
+        .and_then(|providers| providers.require_compatible())
+        .map_err(TestTargetError::Build)?;
     let providers = frozen_providers.provider_collection();
-    build_artifacts(ctx, providers, &label_filtering).await?;
+    build_artifacts(ctx, providers, &label_filtering)
+        .await
+        .map_err(TestTargetError::Build)?;
 
     let fut = match <dyn TestProvider>::from_collection(providers) {
         Some(test_info) => {
@@ -857,7 +969,7 @@ async fn test_target(
     // NOTE: We produce the future above, then await it here because Module & TestInfo aren't Sync.
     // That's OK, because we don't need them across an await point, but we do need to structure the
     // code this way for the compiler to see it.
-    fut.await
+    fut.await.map_err(TestTargetError::Test)
 }
 
 fn skip_run_based_on_labels(
@@ -1029,7 +1141,41 @@ fn post_process_test_executor(s: &str) -> anyhow::Result<PathBuf> {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use buck2_core::cells::cell_root_path::CellRootPathBuf;
+    use buck2_core::cells::name::CellName;
+    use buck2_core::cells::CellResolver;
+    use buck2_core::configuration::data::ConfigurationData;
+    use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+    use buck2_core::provider::label::ConfiguredProvidersLabel;
+    use buck2_core::provider::label::ProvidersName;
+    use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+    use buck2_node::load_patterns::MissingTargetBehavior;
+    use buck2_test_api::data::ExternalRunnerSpec;
+    use buck2_test_api::data::TestResult;
+    use buck2_test_api::data::TestStatus;
+    use buck2_test_api::protocol::TestExecutor;
+    use dice::testing::DiceBuilder;
+    use dice::UserComputationData;
+    use dupe::Dupe;
+    use futures::future;
+    use futures::future::BoxFuture;
+    use futures::future::FutureExt;
+
+    use crate::command::attribute_test_target_error;
+    use crate::command::ExecutorReport;
+    use crate::command::TestDriver;
+    use crate::command::TestDriverResult;
+    use crate::command::TestDriverState;
+    use crate::command::TestDriverTask;
     use crate::command::TestLabelFiltering;
+    use crate::command::TestTargetError;
+    use crate::orchestrator::ExecutorMessage;
+    use crate::session::TestSession;
+    use crate::session::TestSessionOptions;
 
     #[test]
     fn only_include_labels_in_includes() {
@@ -1097,4 +1243,191 @@ mod tests {
 
         assert!(conflicting_filter.is_excluded(vec!["include_me"]));
     }
+
+    #[test]
+    fn only_build_failures_are_attributed_to_the_target() {
+        let (label, res) = attribute_test_target_error::<_, ()>(
+            "root//:build_fails",
+            Err(TestTargetError::Build(anyhow::anyhow!("action failed"))),
+        );
+        assert_eq!(Some("root//:build_fails"), label);
+        assert_eq!("action failed", res.unwrap_err().to_string());
+
+        let (label, res) = attribute_test_target_error::<_, ()>(
+            "root//:dispatch_fails",
+            Err(TestTargetError::Test(anyhow::anyhow!("executor went away"))),
+        );
+        assert_eq!(None, label);
+        assert_eq!("executor went away", res.unwrap_err().to_string());
+
+        let (label, res) = attribute_test_target_error("root//:passes", Ok(()));
+        assert_eq!(None, label);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn executor_report_records_failed_tests() {
+        let session = TestSession::new(TestSessionOptions::default());
+        let target = |label: &str| {
+            session.register(ConfiguredProvidersLabel::new(
+                ConfiguredTargetLabel::testing_parse(label, ConfigurationData::testing_new()),
+                ProvidersName::Default,
+            ))
+        };
+        let (a, b, c) = (target("root//:a"), target("root//:b"), target("root//:c"));
+
+        let mut report = ExecutorReport::default();
+        for (target, name, status) in [
+            (a, "a_passes", TestStatus::PASS),
+            (a, "a_fails", TestStatus::FAIL),
+            (a, "a_times_out", TestStatus::TIMEOUT),
+            (b, "b_passes", TestStatus::PASS),
+            (c, "listing", TestStatus::LISTING_FAILED),
+        ] {
+            report.ingest(
+                &ExecutorMessage::TestResult(TestResult {
+                    target,
+                    name: name.to_owned(),
+                    status,
+                    msg: None,
+                    duration: None,
+                    details: String::new(),
+                }),
+                &session,
+            );
+        }
+
+        assert_eq!(
+            report
+                .failed_tests
+                .iter()
+                .map(|(target, names)| (
+                    target.as_str(),
+                    names.iter().map(String::as_str).collect()
+                ))
+                .collect::<Vec<(_, Vec<_>)>>(),
+            vec![
+                ("root//:a", vec!["a_fails", "a_times_out"]),
+                ("root//:c", vec![]),
+            ]
+        );
+    }
+
+    struct NoopTestExecutor;
+
+    #[async_trait::async_trait]
+    impl TestExecutor for NoopTestExecutor {
+        async fn external_runner_spec(&self, _s: ExternalRunnerSpec) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn end_of_test_requests(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn label(label: &str) -> ConfiguredProvidersLabel {
+        ConfiguredProvidersLabel::new(
+            ConfiguredTargetLabel::testing_parse(label, ConfigurationData::testing_new()),
+            ProvidersName::Default,
+        )
+    }
+
+    /// Runs the driver on a target that fails to build, followed by `later`, which is only
+    /// ready once the failure has been processed. Returns the targets reported as failing to
+    /// build and the targets the driver started testing.
+    async fn drive_after_failure(
+        fail_fast: bool,
+        later: BoxFuture<'static, TestDriverResult>,
+    ) -> anyhow::Result<(Vec<ConfiguredProvidersLabel>, Vec<ConfiguredProvidersLabel>)> {
+        let ctx = DiceBuilder::new()
+            .build(UserComputationData::new())?
+            .commit()
+            .await;
+        let label_filtering = Arc::new(TestLabelFiltering::new(
+            Vec::new(),
+            Vec::new(),
+            false,
+            false,
+        ));
+        let session = TestSession::new(TestSessionOptions::default());
+        let test_executor = Arc::new(NoopTestExecutor) as Arc<dyn TestExecutor>;
+        let cell_resolver = CellResolver::testing_with_name_and_path(
+            CellName::testing_new("root"),
+            CellRootPathBuf::new(ProjectRelativePathBuf::unchecked_new("root".to_owned())),
+        );
+
+        let mut driver = TestDriver::new(TestDriverState {
+            ctx: &ctx,
+            label_filtering: &label_filtering,
+            global_target_platform: &None,
+            session: &session,
+            test_executor: &test_executor,
+            cell_resolver: &cell_resolver,
+            working_dir_cell: CellName::testing_new("root"),
+            missing_target_behavior: MissingTargetBehavior::Fail,
+            fail_fast,
+        });
+
+        driver.work.push(
+            future::ready((
+                Some(label("root//:build_fails")),
+                Err(anyhow::anyhow!("action failed")),
+            ))
+            .boxed(),
+        );
+        driver.work.push(
+            async move {
+                tokio::task::yield_now().await;
+                later.await
+            }
+            .boxed(),
+        );
+        driver.drive_to_completion().await;
+
+        assert_eq!(1, driver.build_errors.len());
+        Ok((
+            driver.build_failed_targets,
+            driver.labels_tested.into_iter().collect(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn fail_fast_starts_no_tests_after_the_first_failure() -> anyhow::Result<()> {
+        let (build_failed, tested) = drive_after_failure(
+            true,
+            future::ready((
+                None,
+                Ok(vec![TestDriverTask::TestTarget {
+                    label: label("root//:would_be_tested"),
+                }]),
+            ))
+            .boxed(),
+        )
+        .await?;
+
+        assert_eq!(vec![label("root//:build_fails")], build_failed);
+        assert_eq!(Vec::<ConfiguredProvidersLabel>::new(), tested);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn outstanding_work_runs_without_fail_fast() -> anyhow::Result<()> {
+        for fail_fast in [true, false] {
+            let ran = Arc::new(AtomicBool::new(false));
+            let (build_failed, _) = drive_after_failure(fail_fast, {
+                let ran = ran.dupe();
+                async move {
+                    ran.store(true, Ordering::SeqCst);
+                    (None, Ok(Vec::new()))
+                }
+                .boxed()
+            })
+            .await?;
+
+            assert_eq!(vec![label("root//:build_fails")], build_failed);
+            assert_eq!(!fail_fast, ran.load(Ordering::SeqCst));
+        }
+        Ok(())
+    }
 }
@@ -33,6 +33,7 @@ use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::events::HasEvents;
 use buck2_common::liveliness_observer::LivelinessObserver;
 use buck2_common::local_resource_state::LocalResourceState;
+use buck2_core::category::Category;
 use buck2_core::cells::cell_root_path::CellRootPathBuf;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::execution_types::executor_config::CommandGenerationOptions;
@@ -719,7 +720,9 @@ impl<'b> BuckTestOrchestrator<'b> {
             platform,
             cache_checker: _,
             cache_uploader: _,
-        } = self.dice.get_command_executor(fs, executor_config)?;
+        } = self
+            .dice
+            .get_command_executor(fs, executor_config, Some(&test_category()?))?;
         let executor = CommandExecutor::new(
             executor,
             // Caching is not enabled for tests yet. Use the NoOp
@@ -745,7 +748,9 @@ impl<'b> BuckTestOrchestrator<'b> {
             platform,
             cache_checker: _,
             cache_uploader: _,
-        } = self.dice.get_command_executor(fs, &executor_config)?;
+        } = self
+            .dice
+            .get_command_executor(fs, &executor_config, Some(&test_category()?))?;
         let executor = CommandExecutor::new(
             executor,
             Arc::new(NoOpCommandOptionalExecutor {}),
@@ -1403,9 +1408,19 @@ impl CommandExecutionTarget for LocalResourceTarget<'_> {
     }
 }
 
+/// The category tests are executed as, so that `build.execution_strategy_overrides` such as
+/// `test=local_only` apply to them.
+fn test_category() -> anyhow::Result<Category> {
+    Ok(Category::try_from("test")?)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
     use buck2_build_api::context::SetBuildContextData;
+    use buck2_cli_proto::common_build_options::ExecutionStrategy;
     use buck2_common::dice::cells::SetCellResolver;
     use buck2_common::dice::data::testing::SetTestingIoProvider;
     use buck2_common::liveliness_observer::NoopLivelinessObserver;
@@ -1415,6 +1430,10 @@ mod tests {
     use buck2_core::fs::project::ProjectRootTemp;
     use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
     use buck2_events::dispatch::EventDispatcher;
+    use buck2_execute::execute::dice_data::SetCommandExecutor;
+    use buck2_execute::execute::strategy::strategy_for_category;
+    use buck2_execute::execute::strategy::SelectedExecutor;
+    use buck2_execute::execute::testing_dry_run::DryRunExecutor;
     use buck2_test_api::data::TestStatus;
     use dice::testing::DiceBuilder;
     use dice::UserComputationData;
@@ -1428,6 +1447,15 @@ mod tests {
     async fn make() -> anyhow::Result<(
         BuckTestOrchestrator<'static>,
         UnboundedReceiver<anyhow::Result<ExecutorMessage>>,
+    )> {
+        make_with_data(UserComputationData::new()).await
+    }
+
+    async fn make_with_data(
+        data: UserComputationData,
+    ) -> anyhow::Result<(
+        BuckTestOrchestrator<'static>,
+        UnboundedReceiver<anyhow::Result<ExecutorMessage>>,
     )> {
         let fs = ProjectRootTemp::new().unwrap();
 
@@ -1438,7 +1466,7 @@ mod tests {
         let buckout_path = ProjectRelativePathBuf::unchecked_new("buck_out/v2".into());
         let mut dice = DiceBuilder::new()
             .set_data(|d| d.set_testing_io_provider(&fs))
-            .build(UserComputationData::new())?;
+            .build(data)?;
         dice.set_buck_out_path(Some(buckout_path))?;
         dice.set_cell_resolver(cell_resolver)?;
 
@@ -1554,6 +1582,69 @@ mod tests {
         Ok(())
     }
 
+    /// Selects executors the way the daemon does, recording what it selected.
+    struct CategoryStrategies {
+        strategy: ExecutionStrategy,
+        overrides: HashMap<String, ExecutionStrategy>,
+        selected: Arc<Mutex<Vec<Option<SelectedExecutor>>>>,
+    }
+
+    impl HasCommandExecutor for CategoryStrategies {
+        fn get_command_executor(
+            &self,
+            artifact_fs: &ArtifactFs,
+            config: &CommandExecutorConfig,
+            category: Option<&Category>,
+        ) -> anyhow::Result<CommandExecutorResponse> {
+            let strategy =
+                strategy_for_category(self.strategy, &self.overrides, category.map(|c| c.as_str()));
+            self.selected
+                .lock()
+                .unwrap()
+                .push(SelectedExecutor::select(&config.executor, strategy));
+            Ok(CommandExecutorResponse {
+                executor: Arc::new(DryRunExecutor::new(Default::default(), artifact_fs.clone())),
+                platform: Default::default(),
+                cache_checker: Arc::new(NoOpCommandOptionalExecutor {}),
+                cache_uploader: Arc::new(NoOpCacheUploader {}),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_executor_uses_test_category_override() -> anyhow::Result<()> {
+        let config = CommandExecutorConfig::testing_local();
+        let node = ConfiguredTargetNode::testing_new(
+            ConfiguredTargetLabel::testing_parse("cell//pkg:foo", ConfigurationData::testing_new()),
+            "foo_test",
+        );
+
+        for (overrides, expected) in [
+            // Local execution is banned for everything...
+            (HashMap::new(), None),
+            // ...except tests.
+            (
+                HashMap::from([("test".to_owned(), ExecutionStrategy::LocalOnly)]),
+                Some(SelectedExecutor::Local),
+            ),
+        ] {
+            let selected = Arc::new(Mutex::new(Vec::new()));
+            let mut data = UserComputationData::new();
+            data.set_command_executor(Box::new(CategoryStrategies {
+                strategy: ExecutionStrategy::RemoteOnly,
+                overrides,
+                selected: selected.dupe(),
+            }));
+            let (orchestrator, _channel) = make_with_data(data).await?;
+            let fs = orchestrator.dice.get_artifact_fs().await?;
+
+            orchestrator.get_command_executor(&fs, &node, Some(&*config))?;
+            orchestrator.get_local_executor(&fs)?;
+            assert_eq!(*selected.lock().unwrap(), vec![expected, expected]);
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_orchestrator_channel_drop() -> anyhow::Result<()> {
         let (orchestrator, channel) = make().await?;
@@ -79,7 +79,17 @@ fn find_buck2_processes(who_is_asking: WhoIsAsking) -> Vec<ProcessInfo> {
 /// Kills all running Buck2 processes, except this process's hierarchy. Returns whether it
 /// succeeded without errors.
 pub fn killall(who_is_asking: WhoIsAsking, write: impl Fn(String)) -> bool {
-    let buck2_processes = find_buck2_processes(who_is_asking);
+    killall_matching(who_is_asking, |_| true, write)
+}
+
+/// Like [`killall`], but only kills the processes whose command line satisfies `filter`.
+pub fn killall_matching(
+    who_is_asking: WhoIsAsking,
+    filter: impl Fn(&[String]) -> bool,
+    write: impl Fn(String),
+) -> bool {
+    let mut buck2_processes = find_buck2_processes(who_is_asking);
+    buck2_processes.retain(|process| filter(&process.cmd));
 
     if buck2_processes.is_empty() {
         write("No buck2 processes found".to_owned());
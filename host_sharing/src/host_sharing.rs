@@ -162,6 +162,11 @@ impl HostSharingBroker {
         self.num_machine_permits
     }
 
+    /// The number of permits not currently held by any action, for reporting utilization.
+    pub fn available_permits(&self) -> usize {
+        self.permits.permits()
+    }
+
     pub async fn acquire(
         &self,
         host_sharing_requirements: &HostSharingRequirements,
@@ -43,6 +43,8 @@ impl TCode {
     pub const OK: Self = TCode(0i32);
     pub const INVALID_ARGUMENT: Self = TCode(3i32);
     pub const NOT_FOUND: Self = TCode(5i32);
+    pub const RESOURCE_EXHAUSTED: Self = TCode(8i32);
+    pub const UNAVAILABLE: Self = TCode(14i32);
 }
 
 impl Display for TCode {
@@ -51,6 +53,10 @@ impl Display for TCode {
             write!(f, "OK")
         } else if self == &TCode::INVALID_ARGUMENT {
             write!(f, "INVALID_ARGUMENT")
+        } else if self == &TCode::RESOURCE_EXHAUSTED {
+            write!(f, "RESOURCE_EXHAUSTED")
+        } else if self == &TCode::UNAVAILABLE {
+            write!(f, "UNAVAILABLE")
         } else {
             write!(f, "UNKNOWN")
         }
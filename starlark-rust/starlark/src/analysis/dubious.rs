@@ -16,11 +16,17 @@
  */
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 
+use starlark_syntax::syntax::ast::AssignP;
 use starlark_syntax::syntax::ast::AstExpr;
 use starlark_syntax::syntax::ast::AstLiteral;
 use starlark_syntax::syntax::ast::AstStmt;
+use starlark_syntax::syntax::ast::BinOp;
+use starlark_syntax::syntax::ast::Clause;
 use starlark_syntax::syntax::ast::Expr;
+use starlark_syntax::syntax::ast::ForP;
+use starlark_syntax::syntax::ast::LambdaP;
 use starlark_syntax::syntax::ast::Stmt;
 use starlark_syntax::syntax::module::AstModuleFields;
 use thiserror::Error;
@@ -41,6 +47,12 @@ pub(crate) enum Dubious {
     DuplicateKey(String, FileSpan),
     #[error("Variable `{0}` will either do nothing or fail if uninitialised")]
     IdentifierAsStatement(String),
+    #[error(
+        "Comparison `{0}` is always `{1}`, did you mean to compare against a different value?"
+    )]
+    SelfComparison(String, bool),
+    #[error("Comparing function `{0}` with `{1}`, did you mean to call it, `{0}()`?")]
+    FunctionComparison(String, String),
 }
 
 impl LintWarning for Dubious {
@@ -52,6 +64,8 @@ impl LintWarning for Dubious {
         match self {
             Dubious::DuplicateKey(..) => "duplicate-key",
             Dubious::IdentifierAsStatement(..) => "ident-as-statement",
+            Dubious::SelfComparison(..) => "self-comparison",
+            Dubious::FunctionComparison(..) => "function-comparison",
         }
     }
 }
@@ -136,10 +150,141 @@ fn identifier_as_statement(module: &AstModule, res: &mut Vec<LintT<Dubious>>) {
     stmt(module.statement(), module.codemap(), res)
 }
 
+// Using `==` or `!=` where the result is known statically is almost always a mistake,
+// e.g. `x == x` when `x == y` was intended, or `f == "a"` when `f() == "a"` was intended.
+// We only flag the cases where we are sure: the same variable (or attribute of one) on
+// both sides, or a function defined at the top level of this module which is never rebound.
+fn suspicious_equality(module: &AstModule, res: &mut Vec<LintT<Dubious>>) {
+    // Expressions which evaluate to the same value both times they are evaluated.
+    fn is_stable(x: &AstExpr) -> bool {
+        match &**x {
+            Expr::Identifier(..) => true,
+            Expr::Dot(x, _) => is_stable(x),
+            _ => false,
+        }
+    }
+
+    // All the names bound anywhere in the module, other than by top-level `def`.
+    fn stmt_bindings<'a>(x: &'a AstStmt, top: bool, res: &mut HashSet<&'a str>) {
+        match &**x {
+            Stmt::Def(def) => {
+                if !top {
+                    res.insert(def.name.ident.as_str());
+                }
+                for p in &def.params {
+                    if let Some(p) = p.ident() {
+                        res.insert(p.ident.as_str());
+                    }
+                }
+            }
+            Stmt::Assign(AssignP { lhs: x, .. })
+            | Stmt::AssignModify(x, _, _)
+            | Stmt::For(ForP { var: x, .. }) => x.visit_lvalue(|x| {
+                res.insert(x.ident.as_str());
+            }),
+            Stmt::Load(load) => {
+                for x in &load.args {
+                    res.insert(x.local.ident.as_str());
+                }
+            }
+            _ => {}
+        }
+        let top = top && matches!(&**x, Stmt::Statements(..));
+        x.visit_stmt(|x| stmt_bindings(x, top, res));
+    }
+
+    fn expr_bindings<'a>(x: &'a AstExpr, res: &mut HashSet<&'a str>) {
+        match &**x {
+            Expr::Lambda(LambdaP { params, .. }) => {
+                for p in params {
+                    if let Some(p) = p.ident() {
+                        res.insert(p.ident.as_str());
+                    }
+                }
+            }
+            Expr::ListComprehension(_, for_, clauses)
+            | Expr::DictComprehension(_, for_, clauses) => {
+                for_.var.visit_lvalue(|x| {
+                    res.insert(x.ident.as_str());
+                });
+                for clause in clauses {
+                    if let Clause::For(for_) = clause {
+                        for_.var.visit_lvalue(|x| {
+                            res.insert(x.ident.as_str());
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        x.visit_expr(|x| expr_bindings(x, res));
+    }
+
+    fn expr(
+        x: &AstExpr,
+        functions: &HashSet<&str>,
+        codemap: &CodeMap,
+        res: &mut Vec<LintT<Dubious>>,
+    ) {
+        if let Expr::Op(lhs, op @ (BinOp::Equal | BinOp::NotEqual), rhs) = &**x {
+            if is_stable(lhs) && lhs.node.to_string() == rhs.node.to_string() {
+                res.push(LintT::new(
+                    codemap,
+                    x.span,
+                    Dubious::SelfComparison(
+                        format!("{}{}{}", lhs.node, op, rhs.node),
+                        *op == BinOp::Equal,
+                    ),
+                ));
+            } else {
+                let is_function = |x: &AstExpr| match &**x {
+                    Expr::Identifier(x) => functions.contains(x.node.ident.as_str()),
+                    _ => false,
+                };
+                let function = if is_function(lhs) {
+                    Some((lhs, rhs))
+                } else if is_function(rhs) {
+                    Some((rhs, lhs))
+                } else {
+                    None
+                };
+                if let Some((function, other)) = function {
+                    res.push(LintT::new(
+                        codemap,
+                        x.span,
+                        Dubious::FunctionComparison(function.to_string(), other.to_string()),
+                    ));
+                }
+            }
+        }
+        x.visit_expr(|x| expr(x, functions, codemap, res));
+    }
+
+    let mut rebound = HashSet::new();
+    stmt_bindings(module.statement(), true, &mut rebound);
+    module
+        .statement()
+        .visit_expr(|x| expr_bindings(x, &mut rebound));
+
+    let mut functions = HashSet::new();
+    module.statement().visit_stmt(|x| {
+        if let Stmt::Def(def) = &**x {
+            if !rebound.contains(def.name.ident.as_str()) {
+                functions.insert(def.name.ident.as_str());
+            }
+        }
+    });
+
+    module
+        .statement()
+        .visit_expr(|x| expr(x, &functions, module.codemap(), res))
+}
+
 pub(crate) fn lint(module: &AstModule) -> Vec<LintT<Dubious>> {
     let mut res = Vec::new();
     duplicate_dictionary_key(module, &mut res);
     identifier_as_statement(module, &mut res);
+    suspicious_equality(module, &mut res);
     res
 }
 
@@ -159,6 +304,8 @@ mod tests {
             match self {
                 Dubious::DuplicateKey(x, _) => x,
                 Dubious::IdentifierAsStatement(x) => x,
+                Dubious::SelfComparison(x, _) => x,
+                Dubious::FunctionComparison(x, _) => x,
             }
         }
     }
@@ -204,4 +351,44 @@ def foo():
         identifier_as_statement(&m, &mut res);
         assert_eq!(res.map(|x| x.problem.about()), &["no1", "no2"]);
     }
+
+    #[test]
+    fn test_lint_suspicious_equality() {
+        let m = module(
+            r#"
+def no2():
+    pass
+
+def redefined():
+    pass
+
+redefined = 1
+
+def foo(x, y, f):
+    if x == x:
+        pass
+    if x.a != x.a:
+        pass
+    if x == y or x.a == x.b or f == y:
+        pass
+    if no2 == "a":
+        pass
+    if redefined == 1:
+        pass
+
+    # Calls may return different values each time
+    if f() == f():
+        pass
+    if no2() == 1:
+        pass
+    return [g == 1 for g in y]
+"#,
+        );
+        let mut res = Vec::new();
+        suspicious_equality(&m, &mut res);
+        assert_eq!(
+            res.map(|x| x.problem.about()),
+            &["x == x", "x.a != x.a", "no2"]
+        );
+    }
 }
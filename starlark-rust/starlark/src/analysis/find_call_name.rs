@@ -36,6 +36,13 @@ pub trait AstModuleFindCallName {
     /// NOTE: If the AST is exposed in the future, this function may be removed and implemented
     ///       by specific programs instead.
     fn find_function_call_with_name(&self, name: &str) -> Option<Span>;
+
+    /// Find the locations of all calls (at any depth) whose callee is the identifier `function`.
+    ///
+    /// The returned spans cover the callee identifier, not the whole call. No attempt is made
+    /// to account for shadowing, so a local variable named `function` will also match.
+    /// Spans are returned in source order.
+    fn find_function_calls(&self, function: &str) -> Vec<Span>;
 }
 
 impl AstModuleFindCallName for AstModule {
@@ -76,6 +83,27 @@ impl AstModuleFindCallName for AstModule {
             .visit_expr(|x| visit_expr(&mut ret, name, x));
         ret
     }
+
+    fn find_function_calls(&self, function: &str) -> Vec<Span> {
+        let mut ret = Vec::new();
+
+        fn visit_expr(ret: &mut Vec<Span>, function: &str, node: &AstExpr) {
+            if let Expr::Call(callee, _) = &node.node {
+                if let Expr::Identifier(identifier) = &callee.node {
+                    if identifier.node.ident == function {
+                        ret.push(callee.span);
+                    }
+                }
+            }
+            node.visit_expr(|x| visit_expr(ret, function, x));
+        }
+
+        self.statement()
+            .visit_expr(|x| visit_expr(&mut ret, function, x));
+        // Comprehensions visit their clauses before the body, so restore source order.
+        ret.sort_by_key(|span| span.begin());
+        ret
+    }
 }
 
 #[cfg(test)]
@@ -113,4 +141,35 @@ def x(name = "foo_name"):
         assert_eq!(None, module.find_function_call_with_name("bar_name"));
         Ok(())
     }
+
+    #[test]
+    fn finds_all_calls_of_function() -> anyhow::Result<()> {
+        let contents = r#"
+foo(name = "a")
+bar(foo)
+
+def x():
+    return [foo(y) for y in foo()]
+"#;
+
+        let module = AstModule::parse("foo.star", contents.to_owned(), &Dialect::Extended).unwrap();
+
+        let resolved = |line, begin, end| ResolvedSpan {
+            begin: ResolvedPos {
+                line,
+                column: begin,
+            },
+            end: ResolvedPos { line, column: end },
+        };
+        assert_eq!(
+            vec![resolved(1, 0, 3), resolved(5, 12, 15), resolved(5, 28, 31)],
+            module
+                .find_function_calls("foo")
+                .into_iter()
+                .map(|span| module.codemap().resolve_span(span))
+                .collect::<Vec<_>>()
+        );
+        assert!(module.find_function_calls("y").is_empty());
+        Ok(())
+    }
 }
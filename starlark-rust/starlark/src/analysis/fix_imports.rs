@@ -0,0 +1,346 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Normalize `load` statements: sort them by module, sort and deduplicate the symbols
+//! within each, and merge loads of the same module.
+
+use starlark_syntax::codemap::CodeMap;
+use starlark_syntax::codemap::Pos;
+use starlark_syntax::codemap::Span;
+use starlark_syntax::syntax::ast::Load;
+use starlark_syntax::syntax::ast::Stmt;
+use starlark_syntax::syntax::module::AstModuleFields;
+use starlark_syntax::syntax::top_level_stmts::top_level_stmts;
+
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+
+/// A `load` statement, together with the comments attached to it.
+struct Chunk<'a> {
+    /// The span of the `load` and its comments in the original source.
+    begin: Pos,
+    end: Pos,
+    /// The module being loaded, used for sorting.
+    module: &'a str,
+    /// The module being loaded, as written in the source.
+    module_src: &'a str,
+    /// Comment lines directly above the `load`, including the final newline.
+    leading: &'a str,
+    /// The original `load` statement.
+    load: &'a str,
+    /// Anything following the `load` on its last line, i.e. a comment.
+    trailing: &'a str,
+    /// The loaded symbols as `(their, local, source)`.
+    args: Vec<(&'a str, &'a str, &'a str)>,
+    /// Whether there are comments inside the `load` statement, in which case
+    /// we can't rewrite it without losing them.
+    inner_comments: bool,
+    /// Whether other loads have been merged into this one.
+    merged: bool,
+}
+
+impl<'a> Chunk<'a> {
+    fn new(codemap: &'a CodeMap, span: Span, load: &'a Load, block_begin: Pos) -> Option<Self> {
+        let source = codemap.source();
+        let first_line = codemap.find_line(span.begin());
+        let last_line = codemap.find_line(span.end());
+        let line_begin = codemap.line_span(first_line).begin();
+        let line_end =
+            codemap.line_span(last_line).begin() + codemap.source_line(last_line).len() as u32;
+
+        let before = codemap.source_span(Span::new(line_begin, span.begin()));
+        let trailing = codemap.source_span(Span::new(span.end(), line_end));
+        let trailing_trimmed = trailing.trim_start();
+        if !before.trim().is_empty()
+            || !(trailing_trimmed.is_empty() || trailing_trimmed.starts_with('#'))
+        {
+            // Something else shares a line with this `load`, e.g. `x = 1; load(...)`.
+            return None;
+        }
+
+        // Attach comment lines directly above the `load`.
+        let mut begin = line_begin;
+        let mut line = first_line;
+        while line > 0 {
+            let prev = codemap.line_span(line - 1);
+            if prev.begin() < block_begin
+                || !codemap.source_line(line - 1).trim_start().starts_with('#')
+            {
+                break;
+            }
+            begin = prev.begin();
+            line -= 1;
+        }
+
+        let mut strings = vec![load.module.span];
+        strings.extend(load.args.iter().map(|arg| arg.their.span));
+        strings.sort_by_key(|x| x.begin());
+        let mut pos = span.begin();
+        let mut inner_comments = false;
+        for s in strings {
+            inner_comments |= source[pos.get() as usize..s.begin().get() as usize].contains('#');
+            pos = s.end();
+        }
+        inner_comments |= source[pos.get() as usize..span.end().get() as usize].contains('#');
+
+        Some(Chunk {
+            begin,
+            end: line_end,
+            module: &load.module.node,
+            module_src: codemap.source_span(load.module.span),
+            leading: codemap.source_span(Span::new(begin, line_begin)),
+            load: codemap.source_span(span),
+            trailing,
+            args: load
+                .args
+                .iter()
+                .map(|arg| {
+                    (
+                        arg.their.node.as_str(),
+                        arg.local.ident.as_str(),
+                        codemap.source_span(arg.span()),
+                    )
+                })
+                .collect(),
+            inner_comments,
+            merged: false,
+        })
+    }
+
+    /// Whether another load of the same module can be merged into this one without
+    /// losing comments.
+    fn can_merge(&self, other: &Chunk<'a>) -> bool {
+        !self.inner_comments
+            && !other.inner_comments
+            && (self.trailing.trim().is_empty() || other.trailing.trim().is_empty())
+    }
+
+    fn merge(&mut self, other: Chunk<'a>) {
+        self.args.extend(other.args);
+        // Keep the multi-line layout if either load used it.
+        if other.load.contains('\n') {
+            self.load = other.load;
+        }
+        if self.trailing.trim().is_empty() {
+            self.trailing = other.trailing;
+        }
+        self.merged = true;
+    }
+
+    fn render(mut self, leading: &str, out: &mut String) {
+        let original: Vec<_> = self.args.iter().map(|x| x.2).collect();
+        if !self.inner_comments {
+            self.args.sort_by_key(|x| (x.0, x.1));
+            self.args.dedup_by_key(|x| (x.0, x.1));
+        }
+        out.push_str(leading);
+        if !self.merged && self.args.iter().map(|x| x.2).eq(original) {
+            out.push_str(self.load);
+        } else if self.load.contains('\n') {
+            out.push_str("load(\n    ");
+            out.push_str(self.module_src);
+            out.push_str(",\n");
+            for (_, _, arg) in &self.args {
+                out.push_str("    ");
+                out.push_str(arg);
+                out.push_str(",\n");
+            }
+            out.push(')');
+        } else {
+            out.push_str("load(");
+            out.push_str(self.module_src);
+            for (_, _, arg) in &self.args {
+                out.push_str(", ");
+                out.push_str(arg);
+            }
+            out.push(')');
+        }
+        out.push_str(self.trailing);
+    }
+}
+
+/// Normalize the `load` statements in a module: sort loads by module path, sort and
+/// deduplicate the symbols within a load, and merge loads of the same module.
+///
+/// Only consecutive loads are reordered, so blank lines separate groups of loads which are
+/// kept apart. Comment lines directly above a load, and a comment at the end of its line,
+/// move with the load. Loads containing comments are never rewritten, only reordered.
+///
+/// Return `None` if the loads are already normalized.
+pub fn fix_imports(name: &str, program: &str) -> anyhow::Result<Option<String>> {
+    let module = AstModule::parse(name, program.to_owned(), &Dialect::Extended)?;
+    let codemap = module.codemap();
+
+    // Group loads which are only separated by comments and single newlines.
+    let mut blocks: Vec<Vec<Chunk>> = Vec::new();
+    let mut block_begin = Pos::new(0);
+    let mut prev_end: Option<Pos> = None;
+    for stmt in top_level_stmts(module.statement()) {
+        let Stmt::Load(load) = &**stmt else {
+            prev_end = None;
+            block_begin = stmt.span.end();
+            continue;
+        };
+        let adjacent = prev_end.is_some_and(|end| {
+            let gap = codemap.source_span(Span::new(end, stmt.span.begin()));
+            !gap.contains("\n\n") && !gap.contains("\n\r\n")
+        });
+        if !adjacent {
+            block_begin = prev_end.unwrap_or(block_begin);
+            blocks.push(Vec::new());
+        }
+        match Chunk::new(codemap, stmt.span, load, block_begin) {
+            Some(chunk) => {
+                prev_end = Some(chunk.end);
+                blocks.last_mut().unwrap().push(chunk);
+            }
+            None => {
+                prev_end = None;
+                block_begin = stmt.span.end();
+            }
+        }
+    }
+
+    let source = codemap.source();
+    let mut out = String::new();
+    let mut pos = 0;
+    for block in blocks {
+        let (Some(first), Some(last)) = (block.first(), block.last()) else {
+            continue;
+        };
+        let begin = first.begin.get() as usize;
+        let end = last.end.get() as usize;
+
+        let mut merged: Vec<(String, Chunk)> = Vec::new();
+        for chunk in block {
+            match merged.iter_mut().find(|(_, x)| x.module == chunk.module) {
+                Some((leading, x)) if x.can_merge(&chunk) => {
+                    leading.push_str(chunk.leading);
+                    x.merge(chunk);
+                }
+                _ => merged.push((chunk.leading.to_owned(), chunk)),
+            }
+        }
+        merged.sort_by(|a, b| a.1.module.cmp(b.1.module));
+
+        let mut rendered = String::new();
+        for (i, (leading, chunk)) in merged.into_iter().enumerate() {
+            if i != 0 {
+                rendered.push('\n');
+            }
+            chunk.render(&leading, &mut rendered);
+        }
+
+        out.push_str(&source[pos..begin]);
+        out.push_str(&rendered);
+        pos = end;
+    }
+    out.push_str(&source[pos..]);
+
+    if out == source {
+        Ok(None)
+    } else {
+        Ok(Some(out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(program: &str) -> Option<String> {
+        fix_imports("X", program).unwrap()
+    }
+
+    #[test]
+    fn test_fix_imports_sorted() {
+        assert_eq!(
+            fix("load(\"a\", \"x\", \"y\")\nload(\"b\", \"z\")\nx(y, z)\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fix_imports_sort_and_merge() {
+        let program = r#"
+# Header.
+
+# About c.
+load("c", "x") # trailing
+load("a", "z", "y", "y")
+load("c", w = "v")
+load("b", "q")
+
+load("e", "e")
+load("d", "d")
+print(1)
+"#;
+        let expected = r#"
+# Header.
+
+load("a", "y", "z")
+load("b", "q")
+# About c.
+load("c", w = "v", "x") # trailing
+
+load("d", "d")
+load("e", "e")
+print(1)
+"#;
+        assert_eq!(fix(program).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_fix_imports_inner_comments() {
+        let program = r#"
+load("b", "y")
+load(
+    "a",
+    "y", # why
+    "x",
+)
+"#;
+        let expected = r#"
+load(
+    "a",
+    "y", # why
+    "x",
+)
+load("b", "y")
+"#;
+        assert_eq!(fix(program).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_fix_imports_multiline() {
+        let program = r#"
+load(
+    "a",
+    "y",
+    "x",
+)
+"#;
+        let expected = r#"
+load(
+    "a",
+    "x",
+    "y",
+)
+"#;
+        assert_eq!(fix(program).as_deref(), Some(expected));
+    }
+}
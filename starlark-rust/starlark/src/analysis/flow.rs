@@ -436,6 +436,42 @@ def f():
         );
     }
 
+    #[test]
+    fn test_lint_unreachable_conditional() {
+        let m = module(
+            r#"
+def test1(x):
+    for y in x:
+        break
+        no1
+    reachable
+def test2(x):
+    if x == 1:
+        return 1
+    elif x == 2:
+        fail("two")
+    else:
+        return 3
+    no2
+def test3(x):
+    if x == 1:
+        return 1
+    elif x == 2:
+        pass
+    else:
+        return 3
+    reachable
+def test4(x):
+    if x:
+        fail("x")
+    reachable
+"#,
+        );
+        let mut res = Vec::new();
+        reachable(m.codemap(), m.statement(), &mut res);
+        assert_eq!(res.map(|x| x.problem.about()), &["no1", "no2"]);
+    }
+
     #[test]
     fn test_lint_redundant() {
         let m = module(
@@ -18,44 +18,189 @@
 //! Linter.
 
 use std::collections::HashSet;
+use std::panic;
+use std::path::Path;
+use std::thread;
 
+pub use fix_imports::fix_imports;
 pub use lint_message::LintMessage;
+use starlark_syntax::syntax::module::AstModuleFields;
 pub use types::EvalMessage;
 pub use types::EvalSeverity;
 pub use types::Lint;
 pub use unused_loads::remove::remove_unused_loads;
+pub use unused_loads::remove::remove_unused_loads_with_options;
+pub use unused_loads::remove::RemoveUnusedLoadsOptions;
 
 use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
 use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::wasm::is_wasm;
 
 mod dubious;
 pub mod find_call_name;
+mod fix_imports;
 mod flow;
 mod incompatible;
 mod lint_message;
 mod names;
 mod performance;
+mod style;
+mod suppressions;
 mod types;
 mod underscore;
 mod unused_loads;
 
+/// Configuration for [`AstModuleLint::lint_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Names of function arguments which are never reported as unused,
+    /// e.g. because the function is called with them by convention.
+    pub allowed_unused_arguments: HashSet<String>,
+    /// Names which are defined by a prelude rather than by the globals. Like `globals`,
+    /// these are never reported as undefined, but they are kept separate so that
+    /// callers don't have to merge the two sets.
+    pub prelude: HashSet<String>,
+    /// Report lines longer than this many characters. Off by default.
+    pub max_line_length: Option<usize>,
+    /// Report lines with trailing whitespace. Off by default.
+    pub trailing_whitespace: bool,
+    /// Report `# starlark-lint-disable` comments which don't suppress any lint.
+    /// Off by default.
+    pub report_unused_suppressions: bool,
+    /// Advise on functions with more branches than this (`if`/`elif`, `for`, conditional
+    /// expressions, `and`/`or`, and comprehension clauses). Off by default.
+    pub max_function_branches: Option<usize>,
+    /// Advise on comprehensions nested more deeply than this. Off by default.
+    pub max_comprehension_depth: Option<usize>,
+}
+
 /// Run the linter.
 pub trait AstModuleLint {
     /// Run a static linter over the module. If the complete set of global variables are known
     /// they can be passed as the `globals` argument, resulting in name-resolution lint errors.
     /// The precise checks run by the linter are not considered stable between versions.
-    fn lint(&self, globals: Option<&HashSet<String>>) -> Vec<Lint>;
+    ///
+    /// A lint can be suppressed with a `# starlark-lint-disable <short-name>` comment,
+    /// either at the end of the line it is reported on or on the line before.
+    fn lint(&self, globals: Option<&HashSet<String>>) -> Vec<Lint> {
+        self.lint_with_options(globals, &LintOptions::default())
+    }
+
+    /// Like [`lint`](AstModuleLint::lint), but with additional configuration.
+    fn lint_with_options(
+        &self,
+        globals: Option<&HashSet<String>>,
+        options: &LintOptions,
+    ) -> Vec<Lint>;
 }
 
 impl AstModuleLint for AstModule {
-    fn lint(&self, globals: Option<&HashSet<String>>) -> Vec<Lint> {
-        let mut res = Vec::new();
-        res.extend(flow::lint(self).into_iter().map(LintT::erase));
-        res.extend(incompatible::lint(self).into_iter().map(LintT::erase));
-        res.extend(dubious::lint(self).into_iter().map(LintT::erase));
-        res.extend(names::lint(self, globals).into_iter().map(LintT::erase));
-        res.extend(underscore::lint(self).into_iter().map(LintT::erase));
-        res.extend(performance::lint(self).into_iter().map(LintT::erase));
-        res
+    fn lint_with_options(
+        &self,
+        globals: Option<&HashSet<String>>,
+        options: &LintOptions,
+    ) -> Vec<Lint> {
+        let parallel = !is_wasm() && self.codemap().source().len() > PARALLEL_LINT_THRESHOLD;
+        let lints = lint_passes(self, globals, options, parallel);
+        suppressions::apply(self, lints, options.report_unused_suppressions)
+    }
+}
+
+/// Parse the file at `path` with [`Dialect::Extended`] and [lint](AstModuleLint::lint) it,
+/// returning the lints as [`LintMessage`]s, with `path` as their path.
+pub fn lint_file(
+    path: &Path,
+    globals: Option<&HashSet<String>>,
+) -> anyhow::Result<Vec<LintMessage>> {
+    let module = AstModule::parse_file(path, &Dialect::Extended)?;
+    Ok(module
+        .lint(globals)
+        .into_iter()
+        .map(|lint| LintMessage::new(EvalMessage::from(lint)))
+        .collect())
+}
+
+/// Modules with more source than this run each lint pass on its own thread.
+/// For smaller modules, spawning the threads costs more than it saves.
+const PARALLEL_LINT_THRESHOLD: usize = 256 * 1024;
+
+fn lint_passes(
+    module: &AstModule,
+    globals: Option<&HashSet<String>>,
+    options: &LintOptions,
+    parallel: bool,
+) -> Vec<Lint> {
+    fn erase<T: LintWarning>(xs: Vec<LintT<T>>) -> Vec<Lint> {
+        xs.into_iter().map(LintT::erase).collect()
+    }
+
+    let passes: [&(dyn Fn() -> Vec<Lint> + Sync); 8] = [
+        &|| erase(flow::lint(module)),
+        &|| erase(incompatible::lint(module)),
+        &|| erase(dubious::lint(module)),
+        &|| erase(names::lint(module, globals, options)),
+        &|| erase(underscore::lint(module)),
+        &|| erase(performance::lint(module)),
+        &|| {
+            erase(performance::lint_complexity(
+                module,
+                options.max_function_branches,
+                options.max_comprehension_depth,
+            ))
+        },
+        &|| erase(style::lint(module, options)),
+    ];
+
+    if parallel {
+        thread::scope(|s| {
+            let handles = passes.map(|pass| s.spawn(pass));
+            // Join in the order the passes were spawned, so the results are in the
+            // same order as when running sequentially.
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        })
+    } else {
+        passes.iter().flat_map(|pass| pass()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_parallel_is_deterministic() {
+        let source = "def f(x):\n    return y == y\n    pass\n".repeat(100);
+        let module = AstModule::parse("X", source, &Dialect::Extended).unwrap();
+        let globals = HashSet::new();
+        let lint = |parallel| {
+            lint_passes(&module, Some(&globals), &LintOptions::default(), parallel)
+                .into_iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        };
+        let sequential = lint(false);
+        assert!(!sequential.is_empty());
+        assert_eq!(sequential, lint(true));
+    }
+
+    #[test]
+    fn test_lint_file() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testcases/lint/unused_load.star"
+        ));
+        let messages = lint_file(path, None).unwrap();
+        let messages = serde_json::to_value(messages).unwrap();
+        let messages = messages.as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["path"], path.to_string_lossy().as_ref());
+        assert_eq!(messages[0]["name"], "unused-load");
+        assert_eq!(messages[0]["line"], 1);
+        assert_eq!(messages[0]["char"], 26);
     }
 }
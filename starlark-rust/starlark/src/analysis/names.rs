@@ -35,6 +35,7 @@ use starlark_syntax::syntax::ast::AstAssignIdent;
 use starlark_syntax::syntax::ast::AstAssignTarget;
 use starlark_syntax::syntax::ast::AstExpr;
 use starlark_syntax::syntax::ast::AstIdent;
+use starlark_syntax::syntax::ast::AstParameter;
 use starlark_syntax::syntax::ast::AstStmt;
 use starlark_syntax::syntax::ast::AstTypeExpr;
 use starlark_syntax::syntax::ast::Clause;
@@ -42,6 +43,7 @@ use starlark_syntax::syntax::ast::Expr;
 use starlark_syntax::syntax::ast::ForClause;
 use starlark_syntax::syntax::ast::ForP;
 use starlark_syntax::syntax::ast::LoadArgP;
+use starlark_syntax::syntax::ast::Parameter;
 use starlark_syntax::syntax::ast::Stmt;
 use starlark_syntax::syntax::module::AstModuleFields;
 use thiserror::Error;
@@ -49,6 +51,7 @@ use thiserror::Error;
 use crate::analysis::types::LintT;
 use crate::analysis::types::LintWarning;
 use crate::analysis::EvalSeverity;
+use crate::analysis::LintOptions;
 use crate::codemap::CodeMap;
 use crate::codemap::Span;
 use crate::codemap::Spanned;
@@ -60,7 +63,11 @@ pub(crate) enum NameWarning {
     UnusedLoad(String),
     #[error("Unused assignment of `{0}`")]
     UnusedAssign(String),
-    #[error("Unused argument `{0}`")]
+    #[error(
+        "Unused assignment of local variable `{0}`, prefix it with `_` if it is intentionally unused"
+    )]
+    UnusedLocalAssign(String),
+    #[error("Unused argument `{0}`, prefix it with `_` if it is intentionally unused")]
     UnusedArgument(String),
     #[error("Use of unassigned variable `{0}`")]
     UsingUnassigned(String),
@@ -73,7 +80,10 @@ pub(crate) enum NameWarning {
 impl LintWarning for NameWarning {
     fn severity(&self) -> EvalSeverity {
         match self {
-            Self::UsingUnassigned(..) | Self::UsingMaybeUndefined(..) => EvalSeverity::Warning,
+            Self::UsingUnassigned(..)
+            | Self::UsingMaybeUndefined(..)
+            | Self::UnusedArgument(..)
+            | Self::UnusedLocalAssign(..) => EvalSeverity::Warning,
             _ => EvalSeverity::Disabled,
         }
     }
@@ -82,6 +92,7 @@ impl LintWarning for NameWarning {
         match self {
             Self::UnusedLoad(..) => "unused-load",
             Self::UnusedAssign(..) => "unused-assign",
+            Self::UnusedLocalAssign(..) => "unused-assignment",
             Self::UnusedArgument(..) => "unused-argument",
             Self::UsingUnassigned(..) => "using-unassigned",
             Self::UsingUndefined(..) => "using-undefined",
@@ -94,6 +105,8 @@ impl LintWarning for NameWarning {
 enum Kind {
     Load,
     Argument,
+    /// `*args` or `**kwargs`, which are never reported as unused.
+    StarArgument,
     Assign,
 }
 
@@ -101,7 +114,7 @@ impl Kind {
     fn unused(self, name: String) -> NameWarning {
         match self {
             Kind::Load => NameWarning::UnusedLoad(name),
-            Kind::Argument => NameWarning::UnusedArgument(name),
+            Kind::Argument | Kind::StarArgument => NameWarning::UnusedArgument(name),
             Kind::Assign => NameWarning::UnusedAssign(name),
         }
     }
@@ -175,6 +188,9 @@ struct ScopeState<'a> {
     last_set: HashMap<&'a str, (Assigned, HashSet<Span>)>,
     /// Whether I can be reached
     abort: Option<Abort>,
+    /// Whether this is the scope of a `def` body. Assignments in it are local variables,
+    /// which can't be used from elsewhere, so their unused assignments are more likely mistakes.
+    def: bool,
 }
 
 /// The state we use when scanning the variables
@@ -183,6 +199,10 @@ struct State<'a> {
     /// Those that are set in the global scope.
     /// If None then assume anything might be set at the global scope.
     globals: Option<&'a HashSet<String>>,
+    /// Those that are provided by the prelude, in addition to the globals.
+    prelude: &'a HashSet<String>,
+    /// Arguments which are never reported as unused.
+    allowed_unused_arguments: &'a HashSet<String>,
     /// These are the various scopes - one for the module, one for each def.
     scopes: Vec<ScopeState<'a>>,
     /// The current list of warnings.
@@ -241,10 +261,17 @@ impl<'a> State<'a> {
                         .last_set
                         .get(ident.node)
                         .map_or(false, |x| x.1.contains(&ident.span));
-                let ignored = !top && underscore;
+                let ignored = (!top && underscore)
+                    || kind == Kind::StarArgument
+                    || (kind == Kind::Argument
+                        && self.allowed_unused_arguments.contains(ident.node));
 
                 if !exported && !ignored {
-                    self.add_warning(ident, |s| kind.unused(s));
+                    if scope.def && kind == Kind::Assign {
+                        self.add_warning(ident, NameWarning::UnusedLocalAssign);
+                    } else {
+                        self.add_warning(ident, |s| kind.unused(s));
+                    }
                 }
             }
         }
@@ -255,7 +282,7 @@ impl<'a> State<'a> {
             None => {
                 if let Some(globals) = self.globals {
                     for x in unbound_undefined {
-                        if !globals.contains(x.node) {
+                        if !globals.contains(x.node) && !self.prelude.contains(x.node) {
                             self.add_warning(x, NameWarning::UsingUndefined)
                         }
                     }
@@ -387,6 +414,21 @@ impl<'a> State<'a> {
 
     // Traverse the syntax tree
 
+    fn params(&mut self, params: &'a [AstParameter]) {
+        for p in params {
+            match &p.node {
+                Parameter::Args(pname, _) | Parameter::KwArgs(pname, _) => {
+                    self.set_ident(pname, Kind::StarArgument)
+                }
+                _ => {
+                    if let Some(pname) = p.node.ident() {
+                        self.set_ident(pname, Kind::Argument);
+                    }
+                }
+            }
+        }
+    }
+
     fn assign(&mut self, assign: &'a AstAssignTarget) {
         assign.visit_expr(|x| self.expr(x));
         assign.visit_lvalue(|x| self.set_ident(x, Kind::Assign))
@@ -427,11 +469,7 @@ impl<'a> State<'a> {
                     p.node.visit_expr(|e| self.expr(e));
                 }
                 self.enter_scope();
-                for p in &x.params {
-                    if let Some(pname) = p.node.ident() {
-                        self.set_ident(pname, Kind::Argument);
-                    }
-                }
+                self.params(&x.params);
                 self.expr(&x.body);
                 self.exit_scope();
             }
@@ -512,11 +550,8 @@ impl<'a> State<'a> {
                 self.typ_opt(x.return_type.as_deref());
                 self.set_ident(&x.name, Kind::Assign);
                 self.enter_scope();
-                for p in &x.params {
-                    if let Some(pname) = p.node.ident() {
-                        self.set_ident(pname, Kind::Argument);
-                    }
-                }
+                self.scopes.last_mut().unwrap().def = true;
+                self.params(&x.params);
                 self.stmt(&x.body);
                 self.exit_scope();
             }
@@ -545,10 +580,13 @@ impl<'a> State<'a> {
 pub(crate) fn lint(
     module: &AstModule,
     globals: Option<&HashSet<String>>,
+    options: &LintOptions,
 ) -> Vec<LintT<NameWarning>> {
     let mut state = State {
         codemap: module.codemap(),
         globals,
+        prelude: &options.prelude,
+        allowed_unused_arguments: &options.allowed_unused_arguments,
         scopes: Vec::new(),
         warnings: Vec::new(),
         warned: HashSet::new(),
@@ -570,6 +608,7 @@ mod tests {
             match self {
                 NameWarning::UnusedLoad(x) => x,
                 NameWarning::UnusedAssign(x) => x,
+                NameWarning::UnusedLocalAssign(x) => x,
                 NameWarning::UnusedArgument(x) => x,
                 NameWarning::UsingUnassigned(x) => x,
                 NameWarning::UsingUndefined(x) => x,
@@ -624,7 +663,7 @@ def foo():
     return array
 "#,
         );
-        let res = lint(&m, None);
+        let res = lint(&m, None, &LintOptions::default());
         let mut res = res.map(|x| x.problem.about());
         res.sort();
         assert_eq!(res, &["_no2", "_no4", "_no6", "no1", "no3", "no5"]);
@@ -663,12 +702,46 @@ e = 10
 capture_e()
 "#,
         );
-        let res = lint(&m, None);
+        let res = lint(&m, None, &LintOptions::default());
         let mut res = res.map(|x| x.problem.about());
         res.sort();
         assert_eq!(res, &["_no4", "no1", "no2", "no2", "no3"]);
     }
 
+    #[test]
+    fn test_lint_unused_local_assign() {
+        let m = module(
+            r#"
+unused_global = 1
+def foo(xs):
+    no1 = 1
+    no2 = 1
+    no2 = 2
+    augmented = 1
+    augmented += 1
+    for no3 in xs:
+        pass
+    _ignored = 1
+    return no2 + augmented + len([1 for unused_comprehension in xs])
+"#,
+        );
+        let res = lint(&m, None, &LintOptions::default());
+        let mut res = res
+            .iter()
+            .map(|x| (x.problem.about().as_str(), x.problem.short_name()))
+            .collect::<Vec<_>>();
+        res.sort();
+        assert_eq!(
+            res,
+            &[
+                ("no1", "unused-assignment"),
+                ("no2", "unused-assignment"),
+                ("no3", "unused-assignment"),
+                ("unused_comprehension", "unused-assign"),
+            ]
+        );
+    }
+
     #[test]
     fn test_lint_unassigned() {
         let m = module(
@@ -687,7 +760,7 @@ def uses_h():
 _h = []
 "#,
         );
-        let res = lint(&m, None);
+        let res = lint(&m, None, &LintOptions::default());
         let mut res = res.map(|x| x.problem.about());
         res.sort();
         assert_eq!(res, &["no1", "no2", "no3"]);
@@ -708,12 +781,29 @@ def bar(ctx):
     ctx[0].default_outputs
 "#,
         );
-        let res = lint(&m, Some(&globals));
+        let res = lint(&m, Some(&globals), &LintOptions::default());
         let mut res = res.map(|x| x.problem.about());
         res.sort();
         assert_eq!(res, &["no1", "no2"])
     }
 
+    #[test]
+    fn test_lint_undefined_prelude() {
+        let globals = HashSet::from(["True".to_owned()]);
+        let options = LintOptions {
+            prelude: HashSet::from(["from_prelude".to_owned()]),
+            ..LintOptions::default()
+        };
+
+        let m = module(
+            r#"
+a = True + from_prelude + no1
+"#,
+        );
+        let res = lint(&m, Some(&globals), &options);
+        assert_eq!(res.map(|x| x.problem.about()), &["no1"])
+    }
+
     #[test]
     fn test_early_fail() {
         let m = module(
@@ -736,7 +826,7 @@ def bar(xs):
         print(ok2)
 "#,
         );
-        let res = lint(&m, None);
+        let res = lint(&m, None, &LintOptions::default());
         let res = res.map(|x| x.problem.about());
         assert_eq!(res, Vec::<&String>::new())
     }
@@ -755,7 +845,7 @@ def foo(xs):
     return reached
 "#,
         );
-        let res = lint(&m, None);
+        let res = lint(&m, None, &LintOptions::default());
         let res = res.map(|x| x.problem.about());
         assert_eq!(res, Vec::<&String>::new())
     }
@@ -779,7 +869,7 @@ def foo(b, xs):
     return yes
 "#,
         );
-        let res = lint(&m, None);
+        let res = lint(&m, None, &LintOptions::default());
         let res = res.map(|x| x.problem.about());
         assert_eq!(res, &["no1", "no2"])
     }
@@ -796,7 +886,7 @@ def foo():
     lam()
 "#,
         );
-        let res = lint(&m, None);
+        let res = lint(&m, None, &LintOptions::default());
         assert_eq!(res.len(), 0);
     }
 
@@ -810,7 +900,27 @@ def _bar():
     pass
 "#,
         );
-        let res = lint(&m, Some(&HashSet::new()));
+        let res = lint(&m, Some(&HashSet::new()), &LintOptions::default());
         assert_eq!(res.len(), 0);
     }
+
+    #[test]
+    fn test_lint_unused_argument() {
+        let m = module(
+            r#"
+def f(no1, _ignored, ctx, *args, **kwargs):
+    pass
+def g(x, no2 = 1):
+    return lambda y, no3: x + y
+"#,
+        );
+        let options = LintOptions {
+            allowed_unused_arguments: ["ctx".to_owned()].into_iter().collect(),
+            ..LintOptions::default()
+        };
+        let res = lint(&m, None, &options);
+        let mut res = res.map(|x| x.problem.about());
+        res.sort();
+        assert_eq!(res, &["no1", "no2", "no3"]);
+    }
 }
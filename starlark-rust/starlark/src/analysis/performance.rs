@@ -17,8 +17,12 @@
 
 use starlark_syntax::syntax::ast::Argument;
 use starlark_syntax::syntax::ast::AstExpr;
+use starlark_syntax::syntax::ast::AstStmt;
+use starlark_syntax::syntax::ast::BinOp;
 use starlark_syntax::syntax::ast::Expr;
+use starlark_syntax::syntax::ast::Stmt;
 use starlark_syntax::syntax::module::AstModuleFields;
+use starlark_syntax::syntax::uniplate::Visit;
 use thiserror::Error;
 
 use crate::analysis::types::LintT;
@@ -39,11 +43,24 @@ pub(crate) enum Performance {
 
     #[error("`{0}` allocates a new {1} for the results. Prefer using a for-loop.")]
     InefficientBoolCheck(String, String),
+
+    #[error("`{0}` has {1} branches, more than the limit of {2}. Consider splitting it up.")]
+    TooManyBranches(String, usize, usize),
+
+    #[error(
+        "Comprehensions are nested {0} deep, more than the limit of {1}. Consider using a for-loop."
+    )]
+    DeeplyNestedComprehension(usize, usize),
 }
 
 impl LintWarning for Performance {
     fn severity(&self) -> EvalSeverity {
-        EvalSeverity::Warning
+        match self {
+            Performance::TooManyBranches(..) | Performance::DeeplyNestedComprehension(..) => {
+                EvalSeverity::Advice
+            }
+            _ => EvalSeverity::Warning,
+        }
     }
 
     fn short_name(&self) -> &'static str {
@@ -51,6 +68,8 @@ impl LintWarning for Performance {
             Performance::DictWithoutStarStar(..) => "dict-without-star-star",
             Performance::EagerAndInefficientBoolCheck(..) => "eager-and-inefficient-bool-check",
             Performance::InefficientBoolCheck(..) => "inefficient-bool-check",
+            Performance::TooManyBranches(..) => "too-many-branches",
+            Performance::DeeplyNestedComprehension(..) => "deeply-nested-comprehension",
         }
     }
 }
@@ -128,6 +147,118 @@ pub(crate) fn lint(module: &AstModule) -> Vec<LintT<Performance>> {
     res
 }
 
+/// The number of branches in an expression: conditional expressions, `and`/`or`, and each
+/// `for` and `if` clause of a comprehension.
+fn count_branches_expr(x: &AstExpr) -> usize {
+    let mut n = match &**x {
+        Expr::If(..) | Expr::Op(_, BinOp::And | BinOp::Or, _) => 1,
+        Expr::ListComprehension(_, _, clauses) | Expr::DictComprehension(_, _, clauses) => {
+            1 + clauses.len()
+        }
+        _ => 0,
+    };
+    x.visit_expr(|x| n += count_branches_expr(x));
+    n
+}
+
+/// The number of branches in a statement: `if`/`elif`, `for`, and those in expressions.
+/// Nested function definitions are not counted, they are checked on their own.
+fn count_branches_stmt(x: &AstStmt) -> usize {
+    let mut n = match &**x {
+        Stmt::If(..) | Stmt::IfElse(..) | Stmt::For(..) => 1,
+        _ => 0,
+    };
+    x.visit_children(|x| match x {
+        Visit::Stmt(x) => {
+            if !matches!(&**x, Stmt::Def(..)) {
+                n += count_branches_stmt(x)
+            }
+        }
+        Visit::Expr(x) => n += count_branches_expr(x),
+    });
+    n
+}
+
+fn check_too_many_branches(
+    module: &AstModule,
+    max_branches: usize,
+    res: &mut Vec<LintT<Performance>>,
+) {
+    fn check(
+        codemap: &CodeMap,
+        x: &AstStmt,
+        max_branches: usize,
+        res: &mut Vec<LintT<Performance>>,
+    ) {
+        if let Stmt::Def(def) = &**x {
+            let branches = count_branches_stmt(&def.body);
+            if branches > max_branches {
+                res.push(LintT::new(
+                    codemap,
+                    def.name.span,
+                    Performance::TooManyBranches(def.name.ident.clone(), branches, max_branches),
+                ));
+            }
+        }
+        x.visit_stmt(|x| check(codemap, x, max_branches, res));
+    }
+    check(module.codemap(), module.statement(), max_branches, res);
+}
+
+fn comprehension_depth(x: &AstExpr) -> usize {
+    let mut depth = 0;
+    x.visit_expr(|x| depth = depth.max(comprehension_depth(x)));
+    match &**x {
+        Expr::ListComprehension(..) | Expr::DictComprehension(..) => depth + 1,
+        _ => depth,
+    }
+}
+
+fn check_nested_comprehension(
+    module: &AstModule,
+    max_depth: usize,
+    res: &mut Vec<LintT<Performance>>,
+) {
+    fn check(codemap: &CodeMap, x: &AstExpr, max_depth: usize, res: &mut Vec<LintT<Performance>>) {
+        let depth = comprehension_depth(x);
+        if depth > max_depth
+            && matches!(
+                &**x,
+                Expr::ListComprehension(..) | Expr::DictComprehension(..)
+            )
+        {
+            // Only report the outermost comprehension.
+            res.push(LintT::new(
+                codemap,
+                x.span,
+                Performance::DeeplyNestedComprehension(depth, max_depth),
+            ));
+        } else if depth > max_depth {
+            x.visit_expr(|x| check(codemap, x, max_depth, res));
+        }
+    }
+    module
+        .statement()
+        .visit_expr(|x| check(module.codemap(), x, max_depth, res));
+}
+
+/// Complexity checks, which are off unless a limit is given: functions with more than
+/// `max_branches` branches, and comprehensions nested more than `max_comprehension_depth` deep.
+pub(crate) fn lint_complexity(
+    module: &AstModule,
+    max_branches: Option<usize>,
+    max_comprehension_depth: Option<usize>,
+) -> Vec<LintT<Performance>> {
+    let mut res = Vec::new();
+    if let Some(max_branches) = max_branches {
+        check_too_many_branches(module, max_branches, &mut res);
+    }
+    if let Some(max_depth) = max_comprehension_depth {
+        check_nested_comprehension(module, max_depth, &mut res);
+    }
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use starlark_syntax::slice_vec_ext::SliceExt;
@@ -188,4 +319,71 @@ def foo(items):
             ]
         );
     }
+
+    #[test]
+    fn test_lint_too_many_branches() {
+        let m = module(
+            r#"
+def under(x, ys):
+    if x:
+        pass
+    elif x > 1:
+        pass
+    for y in ys:
+        pass
+    return x or [y for y in ys if y]
+
+def over(x, ys):
+    if x:
+        pass
+    elif x > 1:
+        pass
+    for y in ys:
+        pass
+    return x or [y for y in ys if y if y > 1]
+
+def outer(x):
+    def inner(x):
+        return 1 if x else 2 if x > 1 else 3 if x > 2 else 4
+    return inner
+"#,
+        );
+        // `under` has 6 branches, `over` has 7, `inner` has 3, and `outer` does not count
+        // the branches of `inner`.
+        let res = lint_complexity(&m, Some(6), None);
+        assert_eq!(
+            res.map(|x| x.to_string()),
+            &[
+                "bad.bzl:11:5-9: `over` has 7 branches, more than the limit of 6. Consider splitting it up."
+            ]
+        );
+        assert!(matches!(res[0].problem.severity(), EvalSeverity::Advice));
+
+        let res = lint_complexity(&m, Some(2), None);
+        assert_eq!(
+            res.map(|x| x.problem.to_string().split('`').nth(1).unwrap().to_owned()),
+            &["under", "over", "inner"]
+        );
+        assert!(lint_complexity(&m, None, None).is_empty());
+    }
+
+    #[test]
+    fn test_lint_deeply_nested_comprehension() {
+        let m = module(
+            r#"
+a = [[x for x in y] for y in z]
+b = [[[x for x in y] for y in z] for z in w]
+c = [x for x in [y for y in [z for z in w]]]
+"#,
+        );
+        let res = lint_complexity(&m, None, Some(2));
+        assert_eq!(
+            res.map(|x| x.to_string()),
+            &[
+                "bad.bzl:3:5-45: Comprehensions are nested 3 deep, more than the limit of 2. Consider using a for-loop.",
+                "bad.bzl:4:5-45: Comprehensions are nested 3 deep, more than the limit of 2. Consider using a for-loop.",
+            ]
+        );
+        assert!(matches!(res[0].problem.severity(), EvalSeverity::Advice));
+    }
 }
@@ -0,0 +1,121 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use starlark_syntax::syntax::module::AstModuleFields;
+use thiserror::Error;
+
+use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
+use crate::analysis::EvalSeverity;
+use crate::analysis::LintOptions;
+use crate::codemap::Span;
+use crate::syntax::AstModule;
+
+#[derive(Error, Debug)]
+pub(crate) enum StyleWarning {
+    #[error("Line is {0} characters long, which exceeds the maximum of {1}")]
+    LineTooLong(usize, usize),
+    #[error("Trailing whitespace")]
+    TrailingWhitespace,
+}
+
+impl LintWarning for StyleWarning {
+    fn severity(&self) -> EvalSeverity {
+        EvalSeverity::Warning
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            StyleWarning::LineTooLong(..) => "line-too-long",
+            StyleWarning::TrailingWhitespace => "trailing-whitespace",
+        }
+    }
+}
+
+// These checks work on the source text rather than the AST, so also apply to the
+// contents of multi-line strings. There is no need to check for tabs, since the
+// parser rejects them outside of string literals.
+pub(crate) fn lint(module: &AstModule, options: &LintOptions) -> Vec<LintT<StyleWarning>> {
+    let mut res = Vec::new();
+    if options.max_line_length.is_none() && !options.trailing_whitespace {
+        return res;
+    }
+
+    let codemap = module.codemap();
+    let mut line = 0;
+    while let Some(span) = codemap.line_span_opt(line) {
+        let text = codemap.source_line(line);
+        line += 1;
+        let begin = span.begin();
+
+        if let Some(max) = options.max_line_length {
+            let len = text.chars().count();
+            if len > max {
+                res.push(LintT::new(
+                    codemap,
+                    Span::new(begin, begin + text.len() as u32),
+                    StyleWarning::LineTooLong(len, max),
+                ));
+            }
+        }
+
+        if options.trailing_whitespace {
+            let trimmed = text.trim_end_matches([' ', '\t']);
+            if trimmed.len() != text.len() {
+                res.push(LintT::new(
+                    codemap,
+                    Span::new(begin + trimmed.len() as u32, begin + text.len() as u32),
+                    StyleWarning::TrailingWhitespace,
+                ));
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark_syntax::slice_vec_ext::SliceExt;
+
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn module(x: &str) -> AstModule {
+        AstModule::parse("X", x.to_owned(), &Dialect::Extended).unwrap()
+    }
+
+    #[test]
+    fn test_lint_style() {
+        let m = module("def f():\n    x = 'long line'  \n    return x\n\nyyyyyyyy = 1\n");
+        let res = lint(&m, &LintOptions::default());
+        assert!(res.is_empty());
+
+        let options = LintOptions {
+            max_line_length: Some(14),
+            trailing_whitespace: true,
+            ..LintOptions::default()
+        };
+        let res = lint(&m, &options);
+        assert_eq!(
+            res.map(|x| (x.problem.short_name(), x.original.as_str())),
+            &[
+                ("line-too-long", "    x = 'long line'  "),
+                ("trailing-whitespace", "  "),
+            ]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Suppressing lints with `# starlark-lint-disable <name>` comments.
+//!
+//! A directive on a line of its own applies to the following line, one after
+//! code applies to the line it is on. Several lint names can be given, separated
+//! by commas or whitespace.
+
+use dupe::Dupe;
+use starlark_syntax::lexer::Lexer;
+use starlark_syntax::lexer::Token;
+use starlark_syntax::syntax::module::AstModuleFields;
+use thiserror::Error;
+
+use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
+use crate::analysis::EvalSeverity;
+use crate::analysis::Lint;
+use crate::codemap::Pos;
+use crate::codemap::Span;
+use crate::syntax::AstModule;
+
+const DIRECTIVE: &str = "starlark-lint-disable";
+
+#[derive(Error, Debug)]
+pub(crate) enum SuppressionWarning {
+    #[error("Suppression of `{0}` does not suppress any lint")]
+    UnusedSuppression(String),
+}
+
+impl LintWarning for SuppressionWarning {
+    fn severity(&self) -> EvalSeverity {
+        EvalSeverity::Advice
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            SuppressionWarning::UnusedSuppression(..) => "unused-suppression",
+        }
+    }
+}
+
+/// A single lint name from a `starlark-lint-disable` directive.
+struct Suppression {
+    /// The line (0-indexed) whose lints are suppressed.
+    line: usize,
+    /// The directive comment, to report it if unused.
+    span: Span,
+    name: String,
+    used: bool,
+}
+
+/// The lint names in a comment, if it is a suppression directive.
+fn parse_directive(comment: &str) -> Option<impl Iterator<Item = &str>> {
+    let rest = comment.trim_start().strip_prefix(DIRECTIVE)?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(
+        rest.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|x| !x.is_empty()),
+    )
+}
+
+fn find_suppressions(module: &AstModule) -> Vec<Suppression> {
+    let codemap = module.codemap();
+    let mut res = Vec::new();
+    for lexeme in Lexer::new(codemap.source(), module.dialect(), codemap.dupe()) {
+        // The module parsed, so this is unreachable, but there is nothing useful to do anyway.
+        let Ok((begin, token, end)) = lexeme else {
+            break;
+        };
+        let Token::Comment(comment) = token else {
+            continue;
+        };
+        let Some(names) = parse_directive(&comment) else {
+            continue;
+        };
+        let span = Span::new(Pos::new(begin as u32), Pos::new(end as u32));
+        let line = codemap.find_line(span.begin());
+        let own_line = codemap
+            .source_span(Span::new(codemap.line_span(line).begin(), span.begin()))
+            .trim()
+            .is_empty();
+        let line = if own_line { line + 1 } else { line };
+        res.extend(names.map(|name| Suppression {
+            line,
+            span,
+            name: name.to_owned(),
+            used: false,
+        }));
+    }
+    res
+}
+
+/// Remove the lints suppressed by directives in the module, and if `report_unused` is set,
+/// add a lint for each directive which didn't suppress anything.
+pub(crate) fn apply(module: &AstModule, lints: Vec<Lint>, report_unused: bool) -> Vec<Lint> {
+    let mut suppressions = find_suppressions(module);
+    if suppressions.is_empty() {
+        return lints;
+    }
+
+    let mut res: Vec<Lint> = lints
+        .into_iter()
+        .filter(|lint| {
+            let span = lint.location.resolve_span();
+            let mut suppressed = false;
+            for s in &mut suppressions {
+                if s.name == lint.short_name && (span.begin.line..=span.end.line).contains(&s.line)
+                {
+                    s.used = true;
+                    suppressed = true;
+                }
+            }
+            !suppressed
+        })
+        .collect();
+
+    if report_unused {
+        let codemap = module.codemap();
+        res.extend(suppressions.into_iter().filter(|s| !s.used).map(|s| {
+            LintT::new(
+                codemap,
+                s.span,
+                SuppressionWarning::UnusedSuppression(s.name),
+            )
+            .erase()
+        }));
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use starlark_syntax::slice_vec_ext::SliceExt;
+
+    use super::*;
+    use crate::analysis::AstModuleLint;
+    use crate::analysis::LintOptions;
+    use crate::syntax::Dialect;
+
+    fn lint(program: &str, report_unused_suppressions: bool) -> Vec<(String, String)> {
+        let module = AstModule::parse("X", program.to_owned(), &Dialect::Extended).unwrap();
+        let options = LintOptions {
+            report_unused_suppressions,
+            ..LintOptions::default()
+        };
+        module
+            .lint_with_options(Some(&HashSet::new()), &options)
+            .map(|x| (x.short_name.clone(), x.original.clone()))
+    }
+
+    #[test]
+    fn test_suppress_lints() {
+        let program = r#"
+# starlark-lint-disable unused-load
+load("a", "x")
+load("b", "y")
+load("c", "z") # starlark-lint-disable unused-load, no-effect
+
+def f():
+    return 1
+    return 2
+"#;
+        assert_eq!(
+            lint(program, false),
+            &[
+                ("unreachable".to_owned(), "return 2".to_owned()),
+                ("unused-load".to_owned(), "\"y\"".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suppress_other_lint() {
+        // The suppression names a different lint, so the load is still reported.
+        let program = "# starlark-lint-disable unreachable\nload(\"a\", \"x\")\n";
+        assert_eq!(
+            lint(program, false),
+            &[("unused-load".to_owned(), "\"x\"".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_unused_suppressions() {
+        let program = r#"
+load("c", "z") # starlark-lint-disable unused-load, no-effect
+# starlark-lint-disabled unused-load
+"#;
+        assert_eq!(
+            lint(program, true),
+            &[(
+                "unused-suppression".to_owned(),
+                "# starlark-lint-disable unused-load, no-effect".to_owned()
+            )]
+        );
+        assert_eq!(lint(program, false), &[]);
+    }
+}
@@ -20,6 +20,7 @@ use std::fmt::Display;
 use std::path::Path;
 
 use dupe::Dupe;
+use serde::Deserialize;
 use serde::Serialize;
 use starlark_syntax::diagnostic::Diagnostic;
 
@@ -92,7 +93,7 @@ impl<T: LintWarning> LintT<T> {
 }
 
 /// A standardised set of severities.
-#[derive(Debug, Serialize, Dupe, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Dupe, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum EvalSeverity {
     /// An error while the program was being parsed.
@@ -26,10 +26,12 @@ use starlark_syntax::slice_vec_ext::SliceExt;
 use starlark_syntax::syntax::ast::LoadArgP;
 use starlark_syntax::syntax::ast::LoadP;
 use starlark_syntax::syntax::ast::StmtP;
+use starlark_syntax::syntax::ast::Visibility;
 use starlark_syntax::syntax::module::AstModuleFields;
 use starlark_syntax::syntax::top_level_stmts::top_level_stmts;
 
 use crate::environment::names::MutableNames;
+use crate::environment::Module;
 use crate::eval::compiler::scope::payload::CstPayload;
 use crate::eval::compiler::scope::scope_resolver_globals::ScopeResolverGlobals;
 use crate::eval::compiler::scope::BindingId;
@@ -69,9 +71,13 @@ fn has_unused_marker_in_range(span: FileSpanRef) -> bool {
 }
 
 /// Parse the module and find unused loads.
+///
+/// If `keep_reexports` is set, symbols which other modules may load from this one
+/// (i.e. public names, since `load` reexports them) are not considered unused.
 pub(crate) fn find_unused_loads(
     name: &str,
     program: &str,
+    keep_reexports: bool,
 ) -> anyhow::Result<(CodeMap, Vec<UnusedLoad>)> {
     let module = AstModule::parse(name, program.to_owned(), &Dialect::Extended)?;
     let names = MutableNames::new();
@@ -158,6 +164,10 @@ pub(crate) fn find_unused_loads(
             .filter_map(|arg| {
                 if arg.used {
                     None
+                } else if keep_reexports
+                    && Module::default_visibility(&arg.arg.local.ident) == Visibility::Public
+                {
+                    None
                 } else if has_unused_marker_in_range(FileSpanRef {
                     file: &codemap,
                     span: arg.arg.span_with_trailing_comma(),
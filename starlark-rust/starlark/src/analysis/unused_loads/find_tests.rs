@@ -31,7 +31,7 @@ fn test_unused_loads(name: &str, program: &str) {
     writeln!(out, "{}", program).unwrap();
     writeln!(out).unwrap();
 
-    let (codemap, unused_loads) = find_unused_loads(name, program).unwrap();
+    let (codemap, unused_loads) = find_unused_loads(name, program, false).unwrap();
     if unused_loads.is_empty() {
         writeln!(out, "No unused loads").unwrap();
     } else {
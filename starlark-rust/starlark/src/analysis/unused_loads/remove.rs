@@ -47,11 +47,74 @@ impl<'a> Out<'a> {
         self.append_to(span.begin());
         self.skip_to(span.end());
     }
+
+    /// Remove a whole statement, along with its lines if nothing else is on them.
+    fn skip_stmt(&mut self, span: Span) {
+        self.skip_span(whole_lines(self.codemap, span).unwrap_or(span));
+    }
+
+    /// Remove a symbol from a `load`, keeping the layout of the remaining symbols.
+    fn skip_load_arg(&mut self, span: Span, has_comma: bool) {
+        if let Some(lines) = whole_lines(self.codemap, span) {
+            // One symbol per line: drop the line, indentation and trailing comma stay as they are.
+            self.skip_span(lines);
+        } else if has_comma {
+            // Drop the symbol, its comma, and the space separating it from the next one.
+            let rest = &self.codemap.source()[span.end().get() as usize..];
+            let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+            self.skip_span(Span::new(span.begin(), span.end() + spaces as u32));
+        } else {
+            // The last symbol without a trailing comma: drop the comma before it instead.
+            self.append_to(span.begin());
+            if let Some(trimmed) = self.out.trim_end().strip_suffix(',') {
+                self.out.truncate(trimmed.len());
+            }
+            self.skip_to(span.end());
+        }
+    }
 }
 
+/// If `span` is the only thing on its lines, apart from whitespace and a trailing comment,
+/// the span of those lines including the final line terminator.
+fn whole_lines(codemap: &CodeMap, span: Span) -> Option<Span> {
+    let first = codemap.line_span(codemap.find_line(span.begin()));
+    let last = codemap.line_span(codemap.find_line(span.end()));
+    let before = codemap.source_span(Span::new(first.begin(), span.begin()));
+    let after = codemap
+        .source_span(Span::new(span.end(), last.end()))
+        .trim();
+    if before.trim().is_empty() && (after.is_empty() || after.starts_with('#')) {
+        Some(Span::new(first.begin(), last.end()))
+    } else {
+        None
+    }
+}
+
+/// Options for [`remove_unused_loads_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct RemoveUnusedLoadsOptions {
+    /// Don't remove loads of public symbols (those not starting with `_`), since `load`
+    /// reexports them and other modules may be loading them from this one.
+    pub keep_reexports: bool,
+}
+
+/// Remove unused symbols from `load` statements, and statements which only load unused symbols.
+///
+/// The layout of the remaining symbols is preserved: in a `load` with one symbol per line,
+/// the lines of unused symbols are deleted and everything else is kept byte for byte.
+///
 /// Return `None` if there is no unused loads.
 pub fn remove_unused_loads(name: &str, program: &str) -> anyhow::Result<Option<String>> {
-    let (codemap, unused_loads) = find_unused_loads(name, program)?;
+    remove_unused_loads_with_options(name, program, &RemoveUnusedLoadsOptions::default())
+}
+
+/// Like [`remove_unused_loads`], but with additional configuration.
+pub fn remove_unused_loads_with_options(
+    name: &str,
+    program: &str,
+    options: &RemoveUnusedLoadsOptions,
+) -> anyhow::Result<Option<String>> {
+    let (codemap, unused_loads) = find_unused_loads(name, program, options.keep_reexports)?;
     if unused_loads.is_empty() {
         return Ok(None);
     }
@@ -64,10 +127,10 @@ pub fn remove_unused_loads(name: &str, program: &str) -> anyhow::Result<Option<S
 
     for load in unused_loads {
         if load.all_unused() {
-            out.skip_span(load.load.span);
+            out.skip_stmt(load.load.span);
         } else {
             for arg in load.unused_args {
-                out.skip_span(arg.span_with_trailing_comma());
+                out.skip_load_arg(arg.span_with_trailing_comma(), arg.comma.is_some());
             }
         }
     }
@@ -21,9 +21,14 @@ use std::fmt::Write;
 
 use starlark_syntax::golden_test_template::golden_test_template;
 
-use crate::analysis::unused_loads::remove::remove_unused_loads;
+use crate::analysis::unused_loads::remove::remove_unused_loads_with_options;
+use crate::analysis::unused_loads::remove::RemoveUnusedLoadsOptions;
 
 fn test_remove(name: &str, program: &str) {
+    test_remove_with_options(name, program, &RemoveUnusedLoadsOptions::default())
+}
+
+fn test_remove_with_options(name: &str, program: &str, options: &RemoveUnusedLoadsOptions) {
     let program = program.trim();
 
     let mut out = String::new();
@@ -32,7 +37,7 @@ fn test_remove(name: &str, program: &str) {
     writeln!(out, "{}", program).unwrap();
     writeln!(out).unwrap();
 
-    let removed = remove_unused_loads(name, program).unwrap();
+    let removed = remove_unused_loads_with_options(name, program, options).unwrap();
     match removed {
         None => writeln!(out, "No unused loads").unwrap(),
         Some(removed) => {
@@ -79,3 +84,49 @@ print("test")
 "#,
     );
 }
+
+#[test]
+fn test_remove_first_of_three() {
+    test_remove(
+        "remove_first_of_three",
+        r#"
+load("foo", "x", "y", "z")
+print(y, z)
+"#,
+    );
+}
+
+#[test]
+fn test_remove_multiline_preserves_layout() {
+    test_remove(
+        "remove_multiline_preserves_layout",
+        r#"
+load(
+    "foo",
+    "a",
+    "b",  # used by c
+    "c",
+    "d",
+)
+load(
+    "bar",
+    "e",
+)
+print(b, d)
+"#,
+    );
+}
+
+#[test]
+fn test_keep_reexports() {
+    test_remove_with_options(
+        "keep_reexports",
+        r#"
+load("foo", "x", "_y", "z")
+print(z)
+"#,
+        &RemoveUnusedLoadsOptions {
+            keep_reexports: true,
+        },
+    );
+}
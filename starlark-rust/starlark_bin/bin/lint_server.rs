@@ -0,0 +1,210 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A line-based JSON protocol for running the linter, for editors which don't speak LSP.
+//! Each line on stdin is a [`LintRequest`], answered by a single line on stdout
+//! containing a [`LintResponse`].
+
+use std::collections::HashSet;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+use starlark::analysis::AstModuleLint;
+use starlark::analysis::LintMessage;
+use starlark::errors::EvalMessage;
+use starlark::syntax::AstModule;
+
+use crate::eval::dialect;
+
+#[derive(Debug, Deserialize)]
+struct LintRequest {
+    /// Echoed back in the response, so clients can match responses to requests.
+    #[serde(default)]
+    id: serde_json::Value,
+    /// The file name to report lints against.
+    #[serde(default = "default_path")]
+    path: String,
+    /// The Starlark source to lint.
+    source: String,
+    /// The complete set of global names, if known, enabling undefined-name lints.
+    #[serde(default)]
+    globals: Option<HashSet<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct LintResponse {
+    id: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lints: Option<Vec<LintMessage>>,
+    /// Set if the request couldn't be understood.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn default_path() -> String {
+    "<stdin>".to_owned()
+}
+
+fn lint(request: LintRequest) -> Vec<LintMessage> {
+    let messages = match AstModule::parse(&request.path, request.source, &dialect()) {
+        Ok(ast) => ast
+            .lint(request.globals.as_ref())
+            .into_iter()
+            .map(EvalMessage::from)
+            .collect(),
+        Err(e) => vec![EvalMessage::from_anyhow(Path::new(&request.path), &e)],
+    };
+    messages.into_iter().map(LintMessage::new).collect()
+}
+
+fn respond(line: &str) -> LintResponse {
+    match serde_json::from_str::<LintRequest>(line) {
+        Ok(mut request) => LintResponse {
+            id: request.id.take(),
+            lints: Some(lint(request)),
+            error: None,
+        },
+        Err(e) => LintResponse {
+            id: serde_json::Value::Null,
+            lints: None,
+            error: Some(format!("Invalid lint request: {}", e)),
+        },
+    }
+}
+
+pub(crate) fn server() -> anyhow::Result<()> {
+    serve(io::stdin().lock(), io::stdout().lock())
+}
+
+fn serve(input: impl BufRead, mut output: impl Write) -> anyhow::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        serde_json::to_writer(&mut output, &respond(&line))?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn round_trip(requests: &[serde_json::Value]) -> Vec<serde_json::Value> {
+        let mut input = String::new();
+        for request in requests {
+            input.push_str(&request.to_string());
+            // Blank lines between requests are skipped.
+            input.push_str("\n\n");
+        }
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn lint_names(response: &serde_json::Value) -> Vec<&str> {
+        response["lints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|lint| lint["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let responses = round_trip(&[
+            json!({ "id": 1, "path": "foo.bzl", "source": "x = 1\nx = 2\n" }),
+            json!({ "id": "clean", "source": "x = 1\n" }),
+            json!({ "id": 3, "source": "def f(:\n" }),
+        ]);
+        assert_eq!(responses.len(), 3);
+
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[0].get("error").is_none());
+        let duplicate = responses[0]["lints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|lint| lint["name"] == "duplicate-top-level-assign")
+            .unwrap();
+        assert_eq!(duplicate["path"], "foo.bzl");
+        assert_eq!(duplicate["line"], 2);
+
+        assert_eq!(responses[1]["id"], "clean");
+        assert!(
+            !lint_names(&responses[1]).contains(&"duplicate-top-level-assign"),
+            "{}",
+            responses[1]
+        );
+
+        // Parse errors are reported as lints, against the default path.
+        assert_eq!(responses[2]["id"], 3);
+        assert_eq!(responses[2]["lints"].as_array().unwrap().len(), 1);
+        assert_eq!(responses[2]["lints"][0]["path"], "<stdin>");
+        assert_eq!(responses[2]["lints"][0]["severity"], "error");
+    }
+
+    #[test]
+    fn test_globals() {
+        let source = "def f():\n    return g()\n";
+        let responses = round_trip(&[
+            json!({ "source": source }),
+            json!({ "source": source, "globals": ["g"] }),
+            json!({ "source": source, "globals": [] }),
+        ]);
+        // Without globals, names can't be checked.
+        assert!(!lint_names(&responses[0]).contains(&"using-undefined"));
+        assert!(!lint_names(&responses[1]).contains(&"using-undefined"));
+        assert!(lint_names(&responses[2]).contains(&"using-undefined"));
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let mut output = Vec::new();
+        serve("not json\n{\"id\": 1}\n".as_bytes(), &mut output).unwrap();
+        let responses: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 2);
+        for response in responses {
+            assert_eq!(response["id"], serde_json::Value::Null);
+            assert!(response.get("lints").is_none());
+            assert!(
+                response["error"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("Invalid lint request:")
+            );
+        }
+    }
+}
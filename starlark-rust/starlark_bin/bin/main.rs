@@ -49,6 +49,7 @@ use crate::eval::ContextMode;
 mod bazel;
 mod dap;
 mod eval;
+mod lint_server;
 
 #[derive(Debug, Parser)]
 #[command(name = "starlark", about = "Evaluate Starlark code")]
@@ -84,6 +85,24 @@ struct Args {
     )]
     dap: bool,
 
+    #[arg(
+        long = "lint-server",
+        help = "Start a server which reads lint requests as JSON lines on stdin.",
+        // Conflicts with all options.
+        conflicts_with_all = &[
+            "lsp",
+            "dap",
+            "check",
+            "json",
+            "docs",
+            "extension",
+            "prelude",
+            "evaluate",
+            "files",
+        ],
+    )]
+    lint_server: bool,
+
     #[arg(
         long = "check",
         help = "Run checks and lints.",
@@ -257,6 +276,8 @@ fn main() -> anyhow::Result<()> {
     let args: Args = Args::parse_from(args);
     if args.dap {
         dap::server();
+    } else if args.lint_server {
+        lint_server::server()?;
     } else {
         let is_interactive = args.evaluate.is_empty() && args.files.is_empty();
 
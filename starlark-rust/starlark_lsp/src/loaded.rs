@@ -26,6 +26,9 @@ use starlark_syntax::syntax::top_level_stmts::top_level_stmts;
 pub struct LoadedSymbol<'a> {
     /// The name of the symbol.
     pub name: &'a str,
+    /// The name the symbol is bound to in the loading module. Differs from
+    /// `name` when loaded with an alias, e.g. `load("foo", local = "name")`.
+    pub local: &'a str,
     /// The file it's loaded from. Note that this is an unresolved path, so it
     /// might be a relative load.
     pub loaded_from: &'a str,
@@ -48,6 +51,7 @@ impl AstModuleLoadedSymbols for AstModule {
             .flat_map(|l| {
                 l.args.iter().map(|symbol| LoadedSymbol {
                     name: &symbol.their,
+                    local: &symbol.local.ident,
                     loaded_from: &l.module,
                 })
             })
@@ -79,5 +83,6 @@ load("foo", "bar")
             res.map(|symbol| format!("{}:{}", symbol.loaded_from, symbol.name)),
             &["test:a", "test:c", "foo:bar"]
         );
+        assert_eq!(res.map(|symbol| symbol.local), &["a", "b", "bar"]);
     }
 }
@@ -45,6 +45,7 @@ use lsp_types::notification::PublishDiagnostics;
 use lsp_types::request::Completion;
 use lsp_types::request::GotoDefinition;
 use lsp_types::request::HoverRequest;
+use lsp_types::request::References;
 use lsp_types::CompletionItem;
 use lsp_types::CompletionItemKind;
 use lsp_types::CompletionOptions;
@@ -64,6 +65,7 @@ use lsp_types::HoverParams;
 use lsp_types::HoverProviderCapability;
 use lsp_types::InitializeParams;
 use lsp_types::LanguageString;
+use lsp_types::Location;
 use lsp_types::LocationLink;
 use lsp_types::LogMessageParams;
 use lsp_types::MarkedString;
@@ -74,6 +76,7 @@ use lsp_types::OneOf;
 use lsp_types::Position;
 use lsp_types::PublishDiagnosticsParams;
 use lsp_types::Range;
+use lsp_types::ReferenceParams;
 use lsp_types::ServerCapabilities;
 use lsp_types::TextDocumentSyncCapability;
 use lsp_types::TextDocumentSyncKind;
@@ -86,6 +89,7 @@ use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
+use starlark::analysis::find_call_name::AstModuleFindCallName;
 use starlark::codemap::ResolvedSpan;
 use starlark::codemap::Span;
 use starlark::docs::markdown::render_doc_item;
@@ -428,6 +432,7 @@ impl<T: LspContext> Backend<T> {
                 ..Default::default()
             }),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
+            references_provider: Some(OneOf::Left(true)),
             ..ServerCapabilities::default()
         }
     }
@@ -526,6 +531,19 @@ impl<T: LspContext> Backend<T> {
         self.send_response(new_response(id, self.hover_info(params, initialize_params)));
     }
 
+    /// Find the call sites of the function at the current cursor in all open files.
+    fn references(
+        &self,
+        id: RequestId,
+        params: ReferenceParams,
+        initialize_params: &InitializeParams,
+    ) {
+        self.send_response(new_response(
+            id,
+            self.find_references(params, initialize_params),
+        ));
+    }
+
     /// Get the file contents of a starlark: URI.
     fn get_starlark_file_contents(&self, id: RequestId, params: StarlarkFileContentsParams) {
         let response: anyhow::Result<_> = match params.uri {
@@ -740,6 +758,103 @@ impl<T: LspContext> Backend<T> {
         Ok(GotoDefinitionResponse::Link(response))
     }
 
+    /// Find the calls of the top level symbol under the cursor.
+    ///
+    /// The symbol is first resolved to the file that defines it, so a cursor on a loaded
+    /// (and possibly aliased) name finds the same references as a cursor on the definition.
+    /// Every open file is then searched for calls of the name the symbol is bound to there.
+    ///
+    /// NOTE: Only files with a valid parse are searched, and shadowing by local variables
+    /// is not taken into account.
+    fn find_references(
+        &self,
+        params: ReferenceParams,
+        initialize_params: &InitializeParams,
+    ) -> anyhow::Result<Vec<Location>> {
+        let uri = params.text_document_position.text_document.uri.try_into()?;
+        let line = params.text_document_position.position.line;
+        let character = params.text_document_position.position.character;
+        let workspace_root =
+            Self::get_workspace_root(initialize_params.workspace_folders.as_ref(), &uri);
+
+        let Some(ast) = self.get_ast(&uri) else {
+            return Ok(Vec::new());
+        };
+        let (definition_uri, name) = match ast.find_definition_at_location(line, character) {
+            Definition::Identifier(IdentifierDefinition::Location {
+                destination, name, ..
+            }) if ast.find_exported_symbol_span(&name) == Some(destination) => (uri, name),
+            Definition::Identifier(IdentifierDefinition::LoadedLocation { path, name, .. }) => (
+                self.resolve_load_path(&path, &uri, workspace_root.as_deref())?,
+                name,
+            ),
+            // The cursor may be on the name in the definition itself, which isn't a use.
+            _ => {
+                let position = ResolvedPos {
+                    line: line as usize,
+                    column: character as usize,
+                };
+                match ast
+                    .get_exported_symbols()
+                    .into_iter()
+                    .find(|symbol| symbol.span.resolve_span().contains(position))
+                {
+                    Some(symbol) => (uri, symbol.name),
+                    None => return Ok(Vec::new()),
+                }
+            }
+        };
+
+        let mut locations = Vec::new();
+        if params.context.include_declaration {
+            if let Some(span) = self
+                .get_ast_or_load_from_disk(&definition_uri)?
+                .and_then(|ast| ast.find_exported_symbol_span(&name))
+            {
+                locations.push(Location::new((&definition_uri).try_into()?, span.into()));
+            }
+        }
+
+        let open_files: Vec<_> = {
+            let last_valid_parse = self.last_valid_parse.read().unwrap();
+            last_valid_parse
+                .iter()
+                .map(|(uri, module)| (uri.clone(), module.dupe()))
+                .collect()
+        };
+        for (file_uri, module) in open_files {
+            let workspace_root =
+                Self::get_workspace_root(initialize_params.workspace_folders.as_ref(), &file_uri);
+            let mut local_names = Vec::new();
+            if file_uri == definition_uri {
+                local_names.push(name.as_str());
+            }
+            for loaded in module.get_loaded_symbols() {
+                if loaded.name == name
+                    && self
+                        .resolve_load_path(loaded.loaded_from, &file_uri, workspace_root.as_deref())
+                        .is_ok_and(|loaded_uri| loaded_uri == definition_uri)
+                {
+                    local_names.push(loaded.local);
+                }
+            }
+            if local_names.is_empty() {
+                continue;
+            }
+            let url: Url = (&file_uri).try_into()?;
+            for local_name in local_names {
+                for span in module.ast.find_function_calls(local_name) {
+                    let range = module.ast.codemap().resolve_span(span).into();
+                    locations.push(Location::new(url.clone(), range));
+                }
+            }
+        }
+
+        locations
+            .sort_by(|a, b| (a.uri.as_str(), a.range.start).cmp(&(b.uri.as_str(), b.range.start)));
+        Ok(locations)
+    }
+
     fn completion_options(
         &self,
         params: CompletionParams,
@@ -1246,6 +1361,8 @@ impl<T: LspContext> Backend<T> {
                         self.completion(req.id, params, &initialize_params);
                     } else if let Some(params) = as_request::<HoverRequest>(&req) {
                         self.hover(req.id, params, &initialize_params);
+                    } else if let Some(params) = as_request::<References>(&req) {
+                        self.references(req.id, params, &initialize_params);
                     } else if self.connection.handle_shutdown(&req)? {
                         return Ok(());
                     }
@@ -1395,11 +1512,15 @@ mod test {
     use lsp_server::Request;
     use lsp_server::RequestId;
     use lsp_types::request::GotoDefinition;
+    use lsp_types::request::References;
     use lsp_types::GotoDefinitionParams;
     use lsp_types::GotoDefinitionResponse;
+    use lsp_types::Location;
     use lsp_types::LocationLink;
     use lsp_types::Position;
     use lsp_types::Range;
+    use lsp_types::ReferenceContext;
+    use lsp_types::ReferenceParams;
     use lsp_types::TextDocumentIdentifier;
     use lsp_types::TextDocumentPositionParams;
     use lsp_types::Url;
@@ -1431,6 +1552,26 @@ mod test {
         })
     }
 
+    fn references_request(
+        server: &mut TestServer,
+        uri: Url,
+        line: u32,
+        character: u32,
+        include_declaration: bool,
+    ) -> Request {
+        server.new_request::<References>(ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position { line, character },
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration,
+            },
+        })
+    }
+
     fn goto_definition_response_location(
         server: &mut TestServer,
         request_id: RequestId,
@@ -1631,6 +1772,71 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn finds_references_through_load_aliases() -> anyhow::Result<()> {
+        if is_wasm() {
+            return Ok(());
+        }
+
+        let foo_uri = temp_file_uri("foo.star");
+        let bar_uri = temp_file_uri("bar.star");
+
+        let foo_contents = dedent(
+            r#"
+            load("{load}", qux = "baz")
+            <qux_call>q<qux_click>u</qux_click>x</qux_call>()
+            x = [<qux_call2>qux</qux_call2>(y) for y in z]
+            "#,
+        )
+        .replace("{load}", bar_uri.path())
+        .trim()
+        .to_owned();
+        let bar_contents =
+            "def <baz>b<baz_click>a</baz_click>z</baz>():\n    pass\n<baz_call>baz</baz_call>()";
+        let foo = FixtureWithRanges::from_fixture(foo_uri.path(), &foo_contents)?;
+        let bar = FixtureWithRanges::from_fixture(bar_uri.path(), bar_contents)?;
+
+        let calls = vec![
+            Location::new(bar_uri.clone(), bar.resolved_span("baz_call").into()),
+            Location::new(foo_uri.clone(), foo.resolved_span("qux_call").into()),
+            Location::new(foo_uri.clone(), foo.resolved_span("qux_call2").into()),
+        ];
+
+        let mut server = TestServer::new()?;
+        server.open_file(foo_uri.clone(), foo.program())?;
+        server.open_file(bar_uri.clone(), bar.program())?;
+
+        let request = references_request(
+            &mut server,
+            foo_uri,
+            foo.begin_line("qux_click"),
+            foo.begin_column("qux_click"),
+            true,
+        );
+        let request_id = server.send_request(request)?;
+        let locations = server.get_response::<Vec<Location>>(request_id)?;
+
+        let mut expected = vec![Location::new(
+            bar_uri.clone(),
+            bar.resolved_span("baz").into(),
+        )];
+        expected.extend(calls.iter().cloned());
+        assert_eq!(expected, locations);
+
+        let request = references_request(
+            &mut server,
+            bar_uri,
+            bar.begin_line("baz_click"),
+            bar.begin_column("baz_click"),
+            false,
+        );
+        let request_id = server.send_request(request)?;
+        let locations = server.get_response::<Vec<Location>>(request_id)?;
+
+        assert_eq!(calls, locations);
+        Ok(())
+    }
+
     #[test]
     fn jumps_to_definition_from_closed_loaded_file() -> anyhow::Result<()> {
         if is_wasm() {